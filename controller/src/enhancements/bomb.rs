@@ -95,6 +95,12 @@ impl Enhancement for BombInfoIndicator {
 
                     ui.set_cursor_pos([offset_x, offset_y]);
                     ui.unicode_text_colored_with_shadow(unicode_text, color, &defuse_text);
+                } else if bomb_state.likely_faking {
+                    ui.set_cursor_pos([offset_x, offset_y]);
+                    ui.text_with_shadow(&format!(
+                        "Not defusing (faked {} times)",
+                        bomb_state.defuse_attempts_this_plant
+                    ));
                 } else {
                     ui.set_cursor_pos([offset_x, offset_y]);
                     ui.text_with_shadow("Not defusing");
@@ -0,0 +1,506 @@
+use std::{
+    fs::File,
+    io::{
+        Read,
+        Seek,
+        SeekFrom,
+        Write,
+    },
+    path::{
+        Path,
+        PathBuf,
+    },
+    time::{
+        SystemTime,
+        UNIX_EPOCH,
+    },
+};
+
+use anyhow::Context;
+use cs2::{
+    MatchEvent,
+    StateCurrentMap,
+    StateGameRules,
+    StateMatchEvents,
+};
+use cs2_schema_generated::cs2::client::C_CSGameRules;
+use overlay::UnicodeTextRenderer;
+use serde::Serialize;
+
+use super::Enhancement;
+use crate::settings::AppSettings;
+
+/// Version of the match log line schema.
+/// Must be incremented on incompatible changes.
+pub const MATCH_LOG_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+enum MatchLogEvent<'a> {
+    #[serde(rename_all = "camelCase")]
+    RoundStart {
+        round_number: i32,
+        score_terrorists: Option<i32>,
+        score_counter_terrorists: Option<i32>,
+    },
+    #[serde(rename_all = "camelCase")]
+    RoundEnd {
+        round_number: i32,
+        score_terrorists: Option<i32>,
+        score_counter_terrorists: Option<i32>,
+    },
+    #[serde(rename_all = "camelCase")]
+    HalfTimeStarted { round_number: i32 },
+    #[serde(rename_all = "camelCase")]
+    OvertimeStarted { round_number: i32 },
+    #[serde(rename_all = "camelCase")]
+    LastRoundOfHalf { round_number: i32 },
+    #[serde(rename_all = "camelCase")]
+    MatchPoint { round_number: i32, team: u8 },
+    #[serde(rename_all = "camelCase")]
+    BombPlanted {
+        bomb_site: u8,
+        time_detonation: f32,
+        planted_at_round_remaining: Option<f32>,
+        planted_at_server_time: Option<f32>,
+        planter_name: Option<&'a str>,
+    },
+    #[serde(rename_all = "camelCase")]
+    BombDefused {
+        bomb_site: u8,
+        time_detonation: Option<f32>,
+        defuser_name: Option<&'a str>,
+    },
+    #[serde(rename_all = "camelCase")]
+    BombDetonated { bomb_site: u8 },
+    #[serde(rename_all = "camelCase")]
+    Kill {
+        victim_name: &'a str,
+        attacker_name: Option<&'a str>,
+        headshot: bool,
+    },
+}
+
+impl<'a> From<&'a MatchEvent> for MatchLogEvent<'a> {
+    fn from(value: &'a MatchEvent) -> Self {
+        match value {
+            MatchEvent::RoundStart {
+                round_number,
+                score_terrorists,
+                score_counter_terrorists,
+            } => Self::RoundStart {
+                round_number: *round_number,
+                score_terrorists: *score_terrorists,
+                score_counter_terrorists: *score_counter_terrorists,
+            },
+            MatchEvent::RoundEnd {
+                round_number,
+                score_terrorists,
+                score_counter_terrorists,
+            } => Self::RoundEnd {
+                round_number: *round_number,
+                score_terrorists: *score_terrorists,
+                score_counter_terrorists: *score_counter_terrorists,
+            },
+            MatchEvent::HalfTimeStarted { round_number } => Self::HalfTimeStarted {
+                round_number: *round_number,
+            },
+            MatchEvent::OvertimeStarted { round_number } => Self::OvertimeStarted {
+                round_number: *round_number,
+            },
+            MatchEvent::LastRoundOfHalf { round_number } => Self::LastRoundOfHalf {
+                round_number: *round_number,
+            },
+            MatchEvent::MatchPoint { round_number, team } => Self::MatchPoint {
+                round_number: *round_number,
+                team: *team,
+            },
+            MatchEvent::BombPlanted {
+                bomb_site,
+                time_detonation,
+                planted_at_round_remaining,
+                planted_at_server_time,
+                planter_name,
+            } => Self::BombPlanted {
+                bomb_site: *bomb_site,
+                time_detonation: *time_detonation,
+                planted_at_round_remaining: *planted_at_round_remaining,
+                planted_at_server_time: *planted_at_server_time,
+                planter_name: planter_name.as_deref(),
+            },
+            MatchEvent::BombDefused {
+                bomb_site,
+                time_detonation,
+                defuser_name,
+            } => Self::BombDefused {
+                bomb_site: *bomb_site,
+                time_detonation: *time_detonation,
+                defuser_name: defuser_name.as_deref(),
+            },
+            MatchEvent::BombDetonated { bomb_site } => Self::BombDetonated {
+                bomb_site: *bomb_site,
+            },
+            MatchEvent::Kill {
+                victim_name,
+                attacker_name,
+                headshot,
+            } => Self::Kill {
+                victim_name,
+                attacker_name: attacker_name.as_deref(),
+                headshot: *headshot,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MatchLogLine<'a> {
+    version: u32,
+
+    /// Unix timestamp in milliseconds
+    timestamp: u64,
+
+    map: Option<&'a str>,
+
+    #[serde(flatten)]
+    event: MatchLogEvent<'a>,
+}
+
+/// Open a match log file for appending.
+/// An incomplete trailing line (e.g. the process has been killed while writing) will be truncated.
+fn open_match_log(path: &Path) -> anyhow::Result<File> {
+    let mut file = File::options()
+        .create(true)
+        .read(true)
+        .write(true)
+        .open(path)?;
+
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)?;
+
+    let valid_length = content
+        .iter()
+        .rposition(|value| *value == b'\n')
+        .map(|index| index + 1)
+        .unwrap_or(0);
+
+    if valid_length != content.len() {
+        log::warn!(
+            "Truncating incomplete trailing line of match log {}",
+            path.to_string_lossy()
+        );
+        file.set_len(valid_length as u64)?;
+    }
+
+    file.seek(SeekFrom::Start(valid_length as u64))?;
+    Ok(file)
+}
+
+fn get_match_log_directory() -> anyhow::Result<PathBuf> {
+    let exe_file = std::env::current_exe().context("missing current exe path")?;
+    let base_dir = exe_file.parent().context("could not get exe directory")?;
+
+    Ok(base_dir.join("match_logs"))
+}
+
+/// Identifies the match a log file belongs to
+#[derive(Debug, Clone, PartialEq)]
+struct MatchLogKey {
+    /// Unix timestamp in seconds when the match log has been created.
+    /// The server time is not unique across servers (e.g. every local server starts at zero)
+    /// and a later session must not append to the log of a previous match.
+    session_start: u64,
+
+    map: Option<String>,
+
+    /// Server time when the match has been started
+    match_start_time: Option<i64>,
+}
+
+impl MatchLogKey {
+    fn file_name(&self) -> String {
+        let map = self
+            .map
+            .as_deref()
+            .unwrap_or("unknown")
+            .replace(|c: char| !c.is_ascii_alphanumeric() && c != '_', "_");
+
+        match self.match_start_time {
+            Some(start_time) => format!("{}_{}_{}.jsonl", map, self.session_start, start_time),
+            None => format!("{}_{}.jsonl", map, self.session_start),
+        }
+    }
+}
+
+/// Appends all detected match events to a JSONL file.
+/// One file will be created for each session, map and match.
+pub struct MatchLog {
+    /// Directory of the log files or None for the default directory next to the executable
+    directory: Option<PathBuf>,
+    session_start: u64,
+
+    current: Option<(MatchLogKey, File)>,
+}
+
+impl MatchLog {
+    pub fn new() -> Self {
+        let session_start = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        Self {
+            directory: None,
+            session_start,
+            current: None,
+        }
+    }
+
+    fn log_file(&mut self, key: MatchLogKey) -> anyhow::Result<&mut File> {
+        if !matches!(&self.current, Some((current_key, _)) if *current_key == key) {
+            let directory = match &self.directory {
+                Some(directory) => directory.clone(),
+                None => get_match_log_directory()?,
+            };
+            std::fs::create_dir_all(&directory).with_context(|| {
+                format!(
+                    "failed to create match log directory {}",
+                    directory.to_string_lossy()
+                )
+            })?;
+
+            let path = directory.join(key.file_name());
+            let file = open_match_log(&path)
+                .with_context(|| format!("failed to open match log {}", path.to_string_lossy()))?;
+
+            log::debug!("Writing match log to {}", path.to_string_lossy());
+            self.current = Some((key, file));
+        }
+
+        Ok(&mut self.current.as_mut().unwrap().1)
+    }
+
+    fn write_events(
+        &mut self,
+        key: MatchLogKey,
+        timestamp: u64,
+        events: &[MatchEvent],
+    ) -> anyhow::Result<()> {
+        let file = self.log_file(key.clone())?;
+        for event in events.iter() {
+            let mut line = serde_json::to_vec(&MatchLogLine {
+                version: MATCH_LOG_VERSION,
+                timestamp,
+                map: key.map.as_deref(),
+                event: event.into(),
+            })?;
+            line.push(b'\n');
+
+            /* write every line at once so a partial write only affects the trailing line */
+            if let Err(err) = file.write_all(&line).and_then(|_| file.flush()) {
+                /* reopen (and repair) the log file with the next event */
+                self.current = None;
+                return Err(err.into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Enhancement for MatchLog {
+    fn update(&mut self, ctx: &crate::UpdateContext) -> anyhow::Result<()> {
+        let settings = ctx.states.resolve::<AppSettings>(())?;
+        if !settings.match_log {
+            self.current = None;
+            return Ok(());
+        }
+
+        let events = ctx.states.resolve::<StateMatchEvents>(())?;
+        if events.events.is_empty() {
+            return Ok(());
+        }
+
+        let current_map = ctx.states.resolve::<StateCurrentMap>(())?;
+        let game_rules = ctx.states.resolve::<StateGameRules>(())?;
+        let key = MatchLogKey {
+            session_start: self.session_start,
+            map: current_map.current_map.clone(),
+            match_start_time: match &game_rules.rules {
+                Some(rules) => Some(rules.m_fMatchStartTime()? as i64),
+                None => None,
+            },
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+
+        self.write_events(key, timestamp, &events.events)
+    }
+
+    fn render(
+        &self,
+        _states: &utils_state::StateRegistry,
+        _ui: &imgui::Ui,
+        _unicode_text: &UnicodeTextRenderer,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        fs,
+        io::Write,
+        path::{
+            Path,
+            PathBuf,
+        },
+        time::{
+            SystemTime,
+            UNIX_EPOCH,
+        },
+    };
+
+    use cs2::MatchEvent;
+
+    use super::{
+        open_match_log,
+        MatchLog,
+        MatchLogKey,
+    };
+
+    fn temp_directory(name: &str) -> PathBuf {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let directory = std::env::temp_dir().join(format!("match-log-{}-{}", name, nonce));
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn key(session_start: u64, map: &str, match_start_time: i64) -> MatchLogKey {
+        MatchLogKey {
+            session_start,
+            map: Some(map.to_string()),
+            match_start_time: Some(match_start_time),
+        }
+    }
+
+    fn round_start(round_number: i32) -> MatchEvent {
+        MatchEvent::RoundStart {
+            round_number,
+            score_terrorists: Some(0),
+            score_counter_terrorists: None,
+        }
+    }
+
+    /// Round numbers of all lines of a match log
+    fn logged_rounds(directory: &Path, key: &MatchLogKey) -> Vec<i64> {
+        fs::read_to_string(directory.join(key.file_name()))
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let line = serde_json::from_str::<serde_json::Value>(line).unwrap();
+                assert_eq!(line["event"], "round-start");
+                assert_eq!(line["map"], key.map.as_deref().unwrap());
+                line["roundNumber"].as_i64().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn file_name() {
+        assert_eq!(
+            key(1700000000, "workshop/123/de_dust2", 42).file_name(),
+            "workshop_123_de_dust2_1700000000_42.jsonl"
+        );
+
+        let key = MatchLogKey {
+            session_start: 1700000000,
+            map: None,
+            match_start_time: None,
+        };
+        assert_eq!(key.file_name(), "unknown_1700000000.jsonl");
+    }
+
+    #[test]
+    fn truncate_incomplete_line() {
+        let directory = temp_directory("truncate");
+
+        let path = directory.join("incomplete.jsonl");
+        fs::write(&path, "{\"line\":1}\n{\"line\":2}\n{\"li").unwrap();
+        let mut file = open_match_log(&path).unwrap();
+        file.write_all(b"{\"line\":3}\n").unwrap();
+        drop(file);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\"line\":1}\n{\"line\":2}\n{\"line\":3}\n"
+        );
+
+        /* the only line is incomplete */
+        let path = directory.join("partial.jsonl");
+        fs::write(&path, "{\"li").unwrap();
+        drop(open_match_log(&path).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+
+        /* complete logs are untouched */
+        let path = directory.join("complete.jsonl");
+        fs::write(&path, "{\"line\":1}\n").unwrap();
+        drop(open_match_log(&path).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"line\":1}\n");
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn rotation() {
+        let directory = temp_directory("rotation");
+        let mut log = MatchLog {
+            directory: Some(directory.clone()),
+            session_start: 1000,
+            current: None,
+        };
+
+        let first_match = key(1000, "de_mirage", 0);
+        let second_match = key(1000, "de_mirage", 1800);
+        let other_map = key(1000, "de_nuke", 1800);
+        log.write_events(first_match.clone(), 1, &[round_start(1), round_start(2)])
+            .unwrap();
+        log.write_events(second_match.clone(), 2, &[round_start(1)])
+            .unwrap();
+        log.write_events(other_map.clone(), 3, &[round_start(1)])
+            .unwrap();
+
+        /* returning to a match appends to its log */
+        log.write_events(first_match.clone(), 4, &[round_start(3)])
+            .unwrap();
+        drop(log);
+
+        assert_eq!(logged_rounds(&directory, &first_match), [1, 2, 3]);
+        assert_eq!(logged_rounds(&directory, &second_match), [1]);
+        assert_eq!(logged_rounds(&directory, &other_map), [1]);
+
+        /* the same match start time on the same map within a later session */
+        let mut log = MatchLog {
+            directory: Some(directory.clone()),
+            session_start: 2000,
+            current: None,
+        };
+        let next_session = key(2000, "de_mirage", 0);
+        log.write_events(next_session.clone(), 5, &[round_start(1)])
+            .unwrap();
+        drop(log);
+
+        assert_eq!(logged_rounds(&directory, &next_session), [1]);
+        assert_eq!(logged_rounds(&directory, &first_match), [1, 2, 3]);
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 4);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
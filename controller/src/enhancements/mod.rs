@@ -45,6 +45,9 @@ pub use aim::*;
 mod grenade_helper;
 pub use grenade_helper::*;
 
+mod match_log;
+pub use match_log::*;
+
 pub mod sniper_crosshair;
 
 use utils_state::StateRegistry;
@@ -4,6 +4,7 @@ use cs2::{
     CS2Model,
     ClassNameCache,
     LocalCameraControllerTarget,
+    PawnIndex,
     PlayerPawnState,
     StateCS2Memory,
     StateEntityList,
@@ -131,7 +132,7 @@ impl Enhancement for PlayerESP {
         };
 
         for entity_identity in entities.entities() {
-            if entity_identity.handle::<()>()?.get_entity_index() == view_target_entity_id {
+            if PawnIndex::from_handle(&entity_identity.handle::<()>()?) == view_target_entity_id {
                 continue;
             }
 
@@ -151,14 +152,27 @@ impl Enhancement for PlayerESP {
                 continue;
             }
 
-            let pawn_info = ctx
+            let pawn_info = match ctx
                 .states
-                .resolve::<StatePawnInfo>(entity_identity.handle()?)?;
+                .resolve::<StatePawnInfo>(entity_identity.handle()?)
+            {
+                Ok(pawn_info) => pawn_info,
+                Err(error) => {
+                    /* e.g. the position of the pawn is not known */
+                    log::trace!("Skipping player pawn: {:#}", error);
+                    continue;
+                }
+            };
 
             if pawn_info.player_health <= 0 || pawn_info.player_name.is_none() {
                 continue;
             }
 
+            if !pawn_info.position_confidence.is_available() {
+                /* do not draw players at a made up position */
+                continue;
+            }
+
             let pawn_model = ctx
                 .states
                 .resolve::<StatePawnModelInfo>(entity_identity.handle()?)?;
@@ -464,7 +478,7 @@ impl Enhancement for PlayerESP {
                     }
                 }
 
-                if esp_settings.info_weapon {
+                if esp_settings.info_weapon && pawn_info.weapon_confidence.is_available() {
                     let text = pawn_info.weapon.display_name();
                     player_info.add_line(
                         esp_settings
@@ -474,7 +488,10 @@ impl Enhancement for PlayerESP {
                     );
                 }
 
-                if esp_settings.info_ammo && pawn_info.weapon_current_ammo != -1 {
+                if esp_settings.info_ammo
+                    && pawn_info.weapon_confidence.is_available()
+                    && pawn_info.weapon_current_ammo != -1
+                {
                     let text = format!(
                         "{}/{}",
                         pawn_info.weapon_current_ammo, pawn_info.weapon_reserve_ammo
@@ -529,7 +546,10 @@ impl Enhancement for PlayerESP {
                 }
 
                 let mut player_flags = Vec::new();
-                if esp_settings.info_flag_kit && pawn_info.player_has_defuser {
+                if esp_settings.info_flag_kit
+                    && pawn_info.economy_confidence.is_available()
+                    && pawn_info.player_has_defuser
+                {
                     player_flags.push("Kit");
                 }
 
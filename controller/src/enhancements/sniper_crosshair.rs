@@ -3,6 +3,7 @@ use cs2::{
     CEntityIdentityEx,
     ClassNameCache,
     LocalCameraControllerTarget,
+    PawnIndex,
     StateCS2Memory,
     StateEntityList,
     WeaponId,
@@ -39,7 +40,7 @@ impl SniperCrosshair {
         entities: &StateEntityList,
         memory: &StateCS2Memory,
         class_name_cache: &ClassNameCache,
-        target_entity_id: u32,
+        target_entity_id: PawnIndex,
     ) -> anyhow::Result<Option<u16>> {
         let entity_identity = entities
             .identity_from_index(target_entity_id)
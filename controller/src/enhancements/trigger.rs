@@ -75,7 +75,7 @@ impl TriggerBot {
         if settings.trigger_bot_team_check {
             let crosshair_entity = entities
                 .entity_from_handle(&EntityHandle::<dyn C_CSPlayerPawn>::from_index(
-                    target.entity_id,
+                    target.entity_id.value(),
                 ))
                 .context("missing crosshair player pawn")?
                 .value_reference(memory.view_arc())
@@ -24,12 +24,21 @@ use std::{
 use anyhow::Context;
 use clap::Parser;
 use cs2::{
+    spawn_warmup,
     CS2Handle,
     ConVars,
+    FrameReadCache,
     InterfaceError,
+    PrefetchMemoryView,
+    PrefetchStatistics,
+    ReadCacheStatistics,
     StateBuildInfo,
     StateCS2Handle,
     StateCS2Memory,
+    StateFramePrefetch,
+    StateFrameReadCache,
+    StateOffsetValidation,
+    StatePrefetchView,
 };
 use enhancements::{
     Enhancement,
@@ -60,7 +69,10 @@ use settings::{
 };
 use tokio::runtime;
 use utils::show_critical_error;
-use utils_state::StateRegistry;
+use utils_state::{
+    StateRegistry,
+    StateWatchdog,
+};
 use view::ViewController;
 use windows::Win32::UI::Shell::IsUserAnAdmin;
 
@@ -69,6 +81,7 @@ use crate::{
         sniper_crosshair::SniperCrosshair,
         AntiAimPunsh,
         BombInfoIndicator,
+        MatchLog,
         PlayerESP,
         SpectatorsListIndicator,
         TriggerBot,
@@ -153,6 +166,8 @@ pub struct Application {
 
     pub frame_read_calls: usize,
     pub last_total_read_calls: usize,
+    pub frame_prefetch: PrefetchStatistics,
+    pub frame_read_cache: ReadCacheStatistics,
 
     pub settings_visible: bool,
     pub settings_key_warning_visible: RefCell<bool>,
@@ -248,7 +263,16 @@ impl Application {
             *self.settings_key_warning_visible.borrow_mut() = true;
         }
 
+        #[cfg(feature = "tracing")]
+        let _frame_span = tracing::trace_span!("frame").entered();
+
         self.app_state.invalidate_states();
+        self.frame_prefetch = self
+            .app_state
+            .resolve::<StateFramePrefetch>(())
+            .map(|prefetch| prefetch.statistics)
+            .unwrap_or_default();
+        let _ = self.app_state.resolve::<StateOffsetValidation>(());
         if let Ok(mut view_controller) = self.app_state.resolve_mut::<ViewController>(()) {
             view_controller.update_screen_bounds(mint::Vector2::from_slice(&ui.io().display_size));
         }
@@ -265,6 +289,10 @@ impl Application {
             enhancement.update(&update_context)?;
         }
 
+        if let Some(read_cache) = self.app_state.get::<StateFrameReadCache>(()) {
+            self.frame_read_cache = read_cache.statistics();
+        }
+
         let read_calls = self.cs2.ke_interface.total_read_calls();
         self.frame_read_calls = read_calls - self.last_total_read_calls;
         self.last_total_read_calls = read_calls;
@@ -367,7 +395,13 @@ impl Application {
                 ui.text_with_shadow(&text)
             }
             {
-                let text = format!("{} Reads", self.frame_read_calls);
+                let text = format!(
+                    "{} Reads ({} ranges, {} KiB prefetched, {:.0}% cached)",
+                    self.frame_read_calls,
+                    self.frame_prefetch.plan_ranges,
+                    self.frame_prefetch.bytes_read / 1024,
+                    self.frame_read_cache.hit_rate() * 100.0
+                );
                 ui.set_cursor_pos([
                     ui.window_size()[0] - ui.calc_text_size(&text)[0] - 10.0,
                     38.0,
@@ -490,8 +524,34 @@ fn real_main(args: &AppArgs) -> anyhow::Result<()> {
     cs2.add_metrics_record(obfstr!("controller-status"), "initializing");
 
     let mut app_state = StateRegistry::new(1024 * 8);
+    let _watchdog = StateWatchdog::spawn(app_state.heartbeat(), Duration::from_secs(5), {
+        let cs2 = cs2.clone();
+        move |report| {
+            let active_states = report
+                .active_states
+                .iter()
+                .map(|state| format!("{} ({:?})", state.name, state.operation))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+
+            log::warn!(
+                "Frame {} did not complete within {:#?}. Active states: {}",
+                report.frame,
+                report.stalled_for,
+                active_states
+            );
+            cs2.add_metrics_record("controller-frame-stalled", &active_states);
+        }
+    })?;
     app_state.set(StateCS2Handle::new(cs2.clone()), ())?;
-    app_state.set(StateCS2Memory::new(cs2.create_memory_view()), ())?;
+    let prefetch_view = Arc::new(PrefetchMemoryView::new(cs2.create_memory_view()));
+    let read_cache = Arc::new(FrameReadCache::new(
+        prefetch_view.clone(),
+        app_state.heartbeat(),
+    ));
+    app_state.set(StateCS2Memory::from_view(read_cache.clone()), ())?;
+    app_state.set(StatePrefetchView::new(prefetch_view), ())?;
+    app_state.set(StateFrameReadCache::new(read_cache), ())?;
     app_state.set(settings, ())?;
 
     {
@@ -543,6 +603,15 @@ fn real_main(args: &AppArgs) -> anyhow::Result<()> {
         .context("cvar ensitivity")?
         .context("missing cvar sensitivity")?;
 
+    /* build the persistent states while the overlay is initializing */
+    let warmup = spawn_warmup(app_state, |stage| {
+        log::debug!(
+            "State warmup: {:?} ({:.0}%)",
+            stage,
+            stage.progress() * 100.0
+        )
+    })?;
+
     log::debug!("Initialize overlay");
     let app_fonts: AppFonts = Default::default();
     let overlay_options = OverlayOptions {
@@ -592,6 +661,16 @@ fn real_main(args: &AppArgs) -> anyhow::Result<()> {
         value => value?,
     };
 
+    let (app_state, warmup_errors) = warmup.join()?;
+    for error in warmup_errors {
+        log::warn!(
+            "Failed to warmup {} ({:?}): {:#}",
+            error.state,
+            error.stage,
+            error.error
+        );
+    }
+
     {
         let settings = app_state.resolve::<AppSettings>(())?;
         if let Some(imgui_settings) = &settings.imgui {
@@ -613,10 +692,13 @@ fn real_main(args: &AppArgs) -> anyhow::Result<()> {
             Rc::new(RefCell::new(TriggerBot::new())),
             Rc::new(RefCell::new(GrenadeHelper::new())),
             Rc::new(RefCell::new(SniperCrosshair::new())),
+            Rc::new(RefCell::new(MatchLog::new())),
         ],
 
         last_total_read_calls: 0,
         frame_read_calls: 0,
+        frame_prefetch: Default::default(),
+        frame_read_cache: Default::default(),
 
         settings_visible: false,
         settings_key_warning_visible: RefCell::new(false),
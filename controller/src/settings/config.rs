@@ -283,6 +283,10 @@ pub struct AppSettings {
     #[serde(default = "bool_true")]
     pub sniper_crosshair: bool,
 
+    /// Write all match events into a JSONL file
+    #[serde(default = "bool_false")]
+    pub match_log: bool,
+
     #[serde(flatten, with = "serde_prefix_grenade_helper")]
     pub grenade_helper: GrenadeSettings,
 
@@ -1,14 +1,21 @@
 use anyhow::Context;
 use cs2::{
+    damage::{
+        shots_to_kill,
+        ShotsToKill,
+    },
     CEntityIdentityEx,
     ClassNameCache,
+    EntityIndex,
     StateCS2Memory,
     StateEntityList,
     StateLocalPlayerController,
+    StatePawnInfo,
 };
 use cs2_schema_cutl::EntityHandle;
 use cs2_schema_generated::cs2::client::{
     CEntityInstance,
+    C_CSPlayerPawn,
     C_CSPlayerPawnBase,
 };
 use utils_state::{
@@ -19,7 +26,7 @@ use utils_state::{
 
 #[derive(Debug)]
 pub struct CrosshairTarget {
-    pub entity_id: u32,
+    pub entity_id: EntityIndex,
     pub entity_type: Option<String>,
 }
 
@@ -52,7 +59,7 @@ impl State for StateLocalCrosshair {
         let new_target = self
             .current_target
             .as_ref()
-            .map(|target| target.entity_id != crosshair_entity_handle.get_entity_index())
+            .map(|target| target.entity_id != EntityIndex::from_handle(&crosshair_entity_handle))
             .unwrap_or(true);
 
         if new_target {
@@ -60,14 +67,14 @@ impl State for StateLocalCrosshair {
             let class_name_cache = states.resolve::<ClassNameCache>(())?;
 
             let crosshair_entity_identnity = entities
-                .identity_from_index(crosshair_entity_handle.get_entity_index())
+                .identity_from_index(EntityIndex::from_handle(&crosshair_entity_handle))
                 .context("missing crosshair entity")?;
 
             let target_type =
                 class_name_cache.lookup(&crosshair_entity_identnity.entity_class_info()?)?;
 
             self.current_target = Some(CrosshairTarget {
-                entity_id: crosshair_entity_handle.get_entity_index(),
+                entity_id: EntityIndex::from_handle(&crosshair_entity_handle),
                 entity_type: target_type.cloned(),
             });
         }
@@ -111,3 +118,76 @@ impl StateLocalCrosshair {
         }
     }
 }
+
+/// Bullets required to kill the current crosshair target with the active weapon
+pub struct StateCrosshairShotsToKill {
+    pub target_entity_id: Option<EntityIndex>,
+    pub shots_to_kill: Option<ShotsToKill>,
+}
+
+impl State for StateCrosshairShotsToKill {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let crosshair = states.resolve::<StateLocalCrosshair>(())?;
+        let Some(target) = crosshair.current_target() else {
+            return Ok(Self {
+                target_entity_id: None,
+                shots_to_kill: None,
+            });
+        };
+
+        if !target
+            .entity_type
+            .as_ref()
+            .map(|t| t == "C_CSPlayerPawn")
+            .unwrap_or(false)
+        {
+            return Ok(Self {
+                target_entity_id: Some(target.entity_id),
+                shots_to_kill: None,
+            });
+        }
+
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let local_player_controller = states.resolve::<StateLocalPlayerController>(())?;
+        let local_player_controller = local_player_controller
+            .instance
+            .value_reference(memory.view_arc())
+            .context("missing local player controller")?;
+
+        let local_pawn =
+            states.resolve::<StatePawnInfo>(local_player_controller.m_hPlayerPawn()?)?;
+        let target_pawn = states.resolve::<StatePawnInfo>(
+            EntityHandle::<dyn C_CSPlayerPawn>::from_index(target.entity_id.value()),
+        )?;
+
+        if !local_pawn.position_confidence.is_available()
+            || !local_pawn.weapon_confidence.is_available()
+            || !target_pawn.position_confidence.is_available()
+            || !target_pawn.economy_confidence.is_available()
+        {
+            /* the estimate would be based on default values */
+            return Ok(Self {
+                target_entity_id: Some(target.entity_id),
+                shots_to_kill: None,
+            });
+        }
+
+        let distance = (target_pawn.position - local_pawn.position).norm();
+        Ok(Self {
+            target_entity_id: Some(target.entity_id),
+            shots_to_kill: shots_to_kill(
+                local_pawn.weapon,
+                distance,
+                target_pawn.player_health,
+                target_pawn.player_armor,
+                target_pawn.player_has_helmet,
+            ),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
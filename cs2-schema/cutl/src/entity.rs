@@ -7,8 +7,22 @@ use std::{
     marker::PhantomData,
 };
 
+/// Schema class of a generated class type (e.g. `dyn C_CSPlayerPawn`).
+/// Implemented for every class emitted by the schema definition.
+pub trait SchemaClass {
+    /// Schema scope name (e.g. `client.dll`)
+    const SCHEMA_SCOPE: &'static str;
+
+    /// Schema class name (e.g. `C_CSPlayerPawn`)
+    const CLASS_NAME: &'static str;
+}
+
 /// CS2 32 bit entity handle packed with
 /// the entity index and serial number.
+///
+/// The handle type `T` documents the class the handle is expected to point to.
+/// It is not validated when reading the handle. Use the typed resolution helpers
+/// (requires `T: SchemaClass`) to check the class of the entity at runtime.
 #[repr(C)]
 #[derive(Default)]
 pub struct EntityHandle<T: ?Sized> {
@@ -31,6 +45,12 @@ impl<T: ?Sized> EntityHandle<T> {
         }
     }
 
+    /// Reinterpret the handle as a handle to another class (e.g. a more specific class).
+    /// The class will only be checked when resolving the handle.
+    pub fn cast<U: ?Sized>(&self) -> EntityHandle<U> {
+        EntityHandle::from_index(self.value)
+    }
+
     pub fn get_entity_index(&self) -> u32 {
         self.value & 0x7FFF
     }
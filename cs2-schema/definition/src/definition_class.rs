@@ -58,6 +58,11 @@ impl ClassDefinition {
         output.pop_ident();
         output.emit_line(&format!("}}"))?;
 
+        output.emit_line(&format!(
+            "impl cs2_schema_cutl::SchemaClass for dyn {class_name} {{ const SCHEMA_SCOPE: &'static str = {:?}; const CLASS_NAME: &'static str = {:?}; }}",
+            mod_name, self.class_name
+        ))?;
+
         for class in inheritance.get_inherited_classes(&ClassReference {
             class_name: self.class_name.clone(),
             module_name: mod_name_from_schema_name(&mod_name).to_string(),
@@ -0,0 +1,45 @@
+use crate::{
+    EmitOutput,
+    SchemaScope,
+};
+
+/// Emit the class sizes and field offsets of all scopes as a constant table
+/// (`SCHEMA_CLASS_LAYOUTS`) of `crate::SchemaClassLayout`.
+/// The table contains all fields, including the fields without a mapped type.
+pub fn emit_class_layouts(
+    output: &mut dyn EmitOutput,
+    scopes: &[SchemaScope],
+) -> anyhow::Result<()> {
+    output.emit_line("pub const SCHEMA_CLASS_LAYOUTS: &[crate::SchemaClassLayout] = &[")?;
+    output.push_ident();
+
+    for scope in scopes.iter() {
+        for class in scope.classes.iter() {
+            output.emit_line("crate::SchemaClassLayout {")?;
+            output.push_ident();
+
+            output.emit_line(&format!("module: {:?},", scope.schema_name))?;
+            output.emit_line(&format!("class_name: {:?},", class.class_name))?;
+            output.emit_line(&format!("class_size: 0x{:X},", class.class_size))?;
+            match &class.inherits {
+                Some(inherits) => output.emit_line(&format!("inherits: Some({:?}),", inherits))?,
+                None => output.emit_line("inherits: None,")?,
+            }
+
+            output.emit_line("fields: &[")?;
+            output.push_ident();
+            for field in class.offsets.iter() {
+                output.emit_line(&format!("({:?}, 0x{:X}),", field.field_name, field.offset))?;
+            }
+            output.pop_ident();
+            output.emit_line("],")?;
+
+            output.pop_ident();
+            output.emit_line("},")?;
+        }
+    }
+
+    output.pop_ident();
+    output.emit_line("];")?;
+    Ok(())
+}
@@ -17,6 +17,9 @@ pub use definition_class::*;
 mod inheritance;
 pub use inheritance::*;
 
+mod layout;
+pub use layout::*;
+
 mod writer;
 use serde::{
     Deserialize,
@@ -47,6 +50,12 @@ pub fn emit_to_dir(target: impl AsRef<Path>, scopes: &[SchemaScope]) -> anyhow::
         scope.emit_rust_definition(&mut writer, &inheritance)?;
     }
 
+    /* class layouts for comparing the offsets against a runtime dump */
+    {
+        let mut writer = FileEmitter::new(target.join("layouts.rs"))?;
+        emit_class_layouts(&mut writer, scopes)?;
+    }
+
     /* create the mod.rs */
     {
         let mut writer = FileEmitter::new(target.join("lib.rs"))?;
@@ -54,6 +63,7 @@ pub fn emit_to_dir(target: impl AsRef<Path>, scopes: &[SchemaScope]) -> anyhow::
             let name = mod_name_from_schema_name(&scope.schema_name);
             writer.emit_line(&format!("pub mod {};", name))?;
         }
+        writer.emit_line("pub mod layouts;")?;
     }
 
     Ok(())
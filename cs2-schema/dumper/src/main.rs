@@ -77,7 +77,7 @@ fn main() -> anyhow::Result<()> {
 
     let mut state = StateRegistry::new(64);
     state.set(StateCS2Handle::new(cs2.clone()), ())?;
-    state.set(StateCS2Memory::new(cs2.create_memory_view()), ())?;
+    state.set(StateCS2Memory::from_view(cs2.create_memory_view()), ())?;
 
     let mut schema = DumpedSchema::default();
     schema.scopes = cs2::dump_schema(
@@ -6,6 +6,22 @@ pub(crate) use cs2_schema_provider::runtime_offset;
 // FIXME: Correct type here. Is it a 3xf32, 4xf32 or 3xu8 or 4xu8
 pub type Color = u8;
 
+/// Class size and field offsets of a class within the schema dump this crate has been generated from.
+/// All classes are listed in `cs2::layouts::SCHEMA_CLASS_LAYOUTS`.
+#[derive(Debug, Clone, Copy)]
+pub struct SchemaClassLayout {
+    /// Schema scope name (e.g. `client.dll`)
+    pub module: &'static str,
+    pub class_name: &'static str,
+    pub class_size: u64,
+
+    /// Inherited class as module path (e.g. `client::C_BaseEntity`)
+    pub inherits: Option<&'static str>,
+
+    /// Name and offset of all fields including the fields which are not accessible
+    pub fields: &'static [(&'static str, u64)],
+}
+
 pub mod cs2 {
     #![allow(
         dead_code,
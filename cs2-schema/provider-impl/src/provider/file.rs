@@ -8,6 +8,7 @@ use std::{
 use anyhow::Context;
 use cs2::{
     CS2Offset,
+    StateOffsetValidation,
     StatePredefinedOffset,
 };
 use cs2_schema_definition::{
@@ -83,5 +84,9 @@ pub fn setup_schema_from_file(states: &mut StateRegistry, file: &Path) -> anyhow
         }
     }
 
+    states.set(
+        StateOffsetValidation::new(Some(schema.cs2_revision.clone())),
+        (),
+    )?;
     Ok(())
 }
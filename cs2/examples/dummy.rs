@@ -26,7 +26,7 @@ fn main() -> anyhow::Result<()> {
 
     let mut state = StateRegistry::new(0xFF);
     let _ = state.set(StateCS2Handle::new(handle.clone()), ());
-    let _ = state.set(StateCS2Memory::new(handle.create_memory_view()), ());
+    let _ = state.set(StateCS2Memory::from_view(handle.create_memory_view()), ());
     state.invalidate_states();
 
     let memory = state.resolve::<StateCS2Memory>(())?;
@@ -40,8 +40,9 @@ fn main() -> anyhow::Result<()> {
     {
         let player_controllers = state.resolve::<StatePlayerControllers>(())?;
         log::info!("Player controllers: {}", player_controllers.instances.len());
-        for controller in player_controllers.instances.iter() {
-            let controller = controller
+        for entry in player_controllers.instances.iter() {
+            let controller = entry
+                .instance
                 .value_reference(memory.view_arc())
                 .context("player controller nullptr")?;
 
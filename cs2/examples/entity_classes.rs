@@ -0,0 +1,62 @@
+use std::{
+    thread,
+    time::Duration,
+};
+
+use cs2::{
+    CS2Handle,
+    StateCS2Handle,
+    StateCS2Memory,
+    StateEntityClassRegistry,
+};
+use utils_state::StateRegistry;
+
+/// Lists all entity classes observed within the entity list.
+/// Useful to check if a (custom) entity is visible to us at all.
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+
+    let observe_frames = std::env::args()
+        .nth(1)
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(100);
+
+    let handle = CS2Handle::create(false)?;
+
+    let mut state = StateRegistry::new(0xFF);
+    state.set(StateCS2Handle::new(handle.clone()), ())?;
+    state.set(StateCS2Memory::from_view(handle.create_memory_view()), ())?;
+
+    for _ in 0..observe_frames {
+        state.invalidate_states();
+        if let Err(error) = state.resolve::<StateEntityClassRegistry>(()) {
+            log::warn!("Failed to update class registry: {:#}", error);
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let registry = state.resolve::<StateEntityClassRegistry>(())?;
+    log::info!(
+        "Observed {} entity classes within {} frames ({} unresolved entities)",
+        registry.records().len(),
+        observe_frames,
+        registry.unresolved_count
+    );
+    if registry.overflowed {
+        log::warn!("Class registry capacity exceeded. Some classes are missing.");
+    }
+
+    for record in registry.records() {
+        log::info!(
+            " - {:<48} current: {:>4}, max: {:>4}, first seen: {}, last seen: {}",
+            record.class_name,
+            record.current_count,
+            record.max_count,
+            record.first_seen_frame,
+            record.last_seen_frame
+        );
+    }
+
+    Ok(())
+}
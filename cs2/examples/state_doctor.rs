@@ -0,0 +1,153 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{
+        Path,
+        PathBuf,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::Context;
+use cs2::{
+    diagnostics::{
+        self,
+        BundleOptions,
+        StateFrameRecorder,
+    },
+    dump_schema,
+    CS2Handle,
+    StateCS2Handle,
+    StateCS2Memory,
+    StateEntityClassRegistry,
+    StateOffsetValidation,
+};
+use cs2_schema_definition::DumpedSchema;
+use utils_state::StateRegistry;
+
+/// Compare the offsets of the generated schema against a schema dump (see cs2-schema-dumper)
+/// or the schema of the running game if no dump has been given.
+fn schema_diff(schema_file: Option<&Path>) -> anyhow::Result<()> {
+    let scopes = match schema_file {
+        Some(path) => {
+            let file = File::open(path).context("open schema file")?;
+            let schema = serde_json::from_reader::<_, DumpedSchema>(BufReader::new(file))
+                .context("parse schema file")?;
+
+            log::info!(
+                "Comparing against schema of CS2 revision {}",
+                schema.cs2_revision
+            );
+            schema.scopes
+        }
+        None => {
+            let handle = CS2Handle::create(false)?;
+
+            let mut state = StateRegistry::new(0xFF);
+            state.set(StateCS2Handle::new(handle.clone()), ())?;
+            state.set(StateCS2Memory::from_view(handle.create_memory_view()), ())?;
+            dump_schema(&state, Some(&["client.dll", "!GlobalTypes"]))?
+        }
+    };
+
+    let diff = diagnostics::diff_schema(&scopes);
+    print!("{}", diff);
+
+    if diff.broken_fields().next().is_some() {
+        log::warn!(
+            "{} of {} classes changed, {} fields moved or are missing",
+            diff.changed_classes().count(),
+            diff.classes.len(),
+            diff.broken_fields().count()
+        );
+    } else {
+        log::info!("All used fields are unchanged");
+    }
+    Ok(())
+}
+
+/// Checks the state of the game and reports all issues found.
+///
+/// Usage: `state_doctor [frames] [--bundle [directory]] [--keep-names]`
+/// - `--bundle` captures a bug report bundle (see [diagnostics::capture_bundle])
+/// - `--keep-names` does not redact player names within the bundle
+///
+/// Usage: `state_doctor schema-diff [schema file]`
+/// - lists the fields which moved since the schema has been generated (see [diagnostics::diff_schema])
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.first().map(String::as_str) == Some("schema-diff") {
+        return schema_diff(args.get(1).map(Path::new));
+    }
+
+    let observe_frames = args
+        .first()
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(100);
+    let bundle_directory = args.iter().position(|arg| arg == "--bundle").map(|index| {
+        args.get(index + 1)
+            .filter(|value| !value.starts_with("--"))
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("."))
+    });
+    let redact_player_names = !args.iter().any(|arg| arg == "--keep-names");
+
+    let handle = CS2Handle::create(false)?;
+
+    let mut state = StateRegistry::new(0xFF);
+    state.set(StateCS2Handle::new(handle.clone()), ())?;
+    state.set(StateCS2Memory::from_view(handle.create_memory_view()), ())?;
+
+    for _ in 0..observe_frames {
+        state.invalidate_states();
+        let _ = state.resolve::<StateOffsetValidation>(());
+
+        if let Err(error) = state.resolve::<StateEntityClassRegistry>(()) {
+            log::warn!("Failed to update class registry: {:#}", error);
+        }
+
+        if let Err(error) = state.resolve::<StateFrameRecorder>(()) {
+            log::warn!("Failed to record frame: {:#}", error);
+        }
+
+        #[cfg(feature = "bomb-transition-log")]
+        if let Err(error) = state.resolve::<diagnostics::StateBombTransitionLog>(()) {
+            log::warn!("Failed to record bomb transitions: {:#}", error);
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    match state.degraded_reason() {
+        Some(reason) => log::warn!("State registry is degraded: {}", reason),
+        None => log::info!("Offsets are valid"),
+    }
+
+    let statistics = state.statistics();
+    log::info!(
+        "Observed {} frames ({} state types, {} panics)",
+        observe_frames,
+        statistics.states.len(),
+        statistics.panics
+    );
+    if let Some(panic) = &statistics.last_panic {
+        log::warn!("Last panic: {}", panic);
+    }
+
+    if let Some(output_directory) = bundle_directory {
+        let path = diagnostics::capture_bundle(
+            &state,
+            &BundleOptions {
+                output_directory,
+                redact_player_names,
+                ..Default::default()
+            },
+        )?;
+        log::info!("Attach {} to your bug report", path.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,266 @@
+//! View angles and conversions between the engine convention and other common conventions.
+
+use std::f32::consts::FRAC_PI_2;
+
+use nalgebra::Vector3;
+
+use crate::normalize_view_angles;
+
+/// View angles as reported by the engine (`QAngle`) in degrees.
+///
+/// The engine uses a right-handed coordinate system with Z pointing up:
+/// - the pitch is zero at the horizon and positive when looking down
+/// - the yaw is zero along the positive X axis and rotates counter-clockwise (seen from above)
+///
+/// The raw engine values are kept. Use the accessors and conversions instead of
+/// interpreting the values manually.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ViewAngles {
+    pitch: f32,
+    yaw: f32,
+}
+
+/// Spherical coordinates (in radians) of a view direction in the engine coordinate system
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SphericalAngles {
+    /// Angle between the positive Z axis and the view direction [0, PI]
+    pub polar: f32,
+
+    /// Angle counter-clockwise from the positive X axis [-PI, PI)
+    pub azimuth: f32,
+}
+
+impl ViewAngles {
+    pub fn from_engine(pitch: f32, yaw: f32) -> Self {
+        Self { pitch, yaw }
+    }
+
+    /// Pitch clamped into [-89, 89] and the yaw wrapped into [-180, 180) (see [normalize_view_angles])
+    pub fn normalized(self) -> Self {
+        let [pitch, yaw] = normalize_view_angles(self.pitch, self.yaw);
+        Self { pitch, yaw }
+    }
+
+    /// Pitch in degrees, positive when looking down (engine convention)
+    pub fn pitch_down_positive(&self) -> f32 {
+        self.pitch
+    }
+
+    /// Pitch in degrees, positive when looking up
+    pub fn pitch_up_positive(&self) -> f32 {
+        -self.pitch
+    }
+
+    /// Yaw in degrees [-180, 180), counter-clockwise from the positive X axis (engine convention)
+    pub fn yaw_ccw_from_x(&self) -> f32 {
+        (self.yaw + 180.0).rem_euclid(360.0) - 180.0
+    }
+
+    /// Engine `QAngle` (pitch, yaw, roll) without any roll
+    pub fn to_qangle(&self) -> [f32; 3] {
+        [self.pitch, self.yaw, 0.0]
+    }
+
+    /// Unit vector of the view direction in engine coordinates
+    pub fn forward(&self) -> Vector3<f32> {
+        let (pitch_sin, pitch_cos) = self.pitch.to_radians().sin_cos();
+        let (yaw_sin, yaw_cos) = self.yaw.to_radians().sin_cos();
+        Vector3::new(pitch_cos * yaw_cos, pitch_cos * yaw_sin, -pitch_sin)
+    }
+
+    /// View angles looking along the direction (engine coordinates).
+    /// The yaw is zero when looking straight up or down.
+    pub fn from_forward(direction: &Vector3<f32>) -> Self {
+        let horizontal = (direction.x * direction.x + direction.y * direction.y).sqrt();
+        Self {
+            pitch: (-direction.z).atan2(horizontal).to_degrees(),
+            yaw: direction.y.atan2(direction.x).to_degrees(),
+        }
+    }
+
+    pub fn to_spherical(&self) -> SphericalAngles {
+        SphericalAngles {
+            polar: FRAC_PI_2 + self.pitch.to_radians(),
+            azimuth: self.yaw_ccw_from_x().to_radians(),
+        }
+    }
+
+    pub fn from_spherical(angles: &SphericalAngles) -> Self {
+        Self {
+            pitch: (angles.polar - FRAC_PI_2).to_degrees(),
+            yaw: angles.azimuth.to_degrees(),
+        }
+    }
+
+    /// Euler angles (x, y, z) in degrees [0, 360) for a left-handed coordinate system
+    /// with Y pointing up and Z pointing forward (e.g. Unity).
+    /// Positions have to be converted using [engine_to_left_handed_position].
+    pub fn to_left_handed_euler(&self) -> [f32; 3] {
+        [
+            self.pitch.rem_euclid(360.0),
+            (-self.yaw).rem_euclid(360.0),
+            0.0,
+        ]
+    }
+
+    pub fn from_left_handed_euler(euler: [f32; 3]) -> Self {
+        Self {
+            pitch: (euler[0] + 180.0).rem_euclid(360.0) - 180.0,
+            yaw: -euler[1],
+        }
+    }
+}
+
+/// Convert an engine position into a left-handed coordinate system
+/// with Y pointing up, Z pointing forward and X pointing right (e.g. Unity)
+pub fn engine_to_left_handed_position(position: &Vector3<f32>) -> Vector3<f32> {
+    Vector3::new(-position.y, position.z, position.x)
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32::consts::{
+        FRAC_PI_2,
+        PI,
+    };
+
+    use nalgebra::Vector3;
+
+    use super::{
+        engine_to_left_handed_position,
+        SphericalAngles,
+        ViewAngles,
+    };
+
+    const EPSILON: f32 = 1e-4;
+
+    fn assert_vector(actual: Vector3<f32>, expected: Vector3<f32>) {
+        assert!(
+            (actual - expected).norm() < EPSILON,
+            "{:?} (expected {:?})",
+            actual,
+            expected
+        );
+    }
+
+    fn assert_angle(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < EPSILON,
+            "{} (expected {})",
+            actual,
+            expected
+        );
+    }
+
+    /// (pitch, yaw, forward in engine coordinates)
+    const DIRECTIONS: &[(f32, f32, [f32; 3])] = &[
+        (0.0, 0.0, [1.0, 0.0, 0.0]),
+        (0.0, 90.0, [0.0, 1.0, 0.0]),
+        (0.0, 180.0, [-1.0, 0.0, 0.0]),
+        (0.0, -90.0, [0.0, -1.0, 0.0]),
+        (0.0, 270.0, [0.0, -1.0, 0.0]),
+        (0.0, -180.0, [-1.0, 0.0, 0.0]),
+        (90.0, 0.0, [0.0, 0.0, -1.0]),
+        (-90.0, 0.0, [0.0, 0.0, 1.0]),
+        (90.0, 135.0, [0.0, 0.0, -1.0]),
+        (-90.0, -45.0, [0.0, 0.0, 1.0]),
+    ];
+
+    #[test]
+    fn engine_convention() {
+        for (pitch, yaw, forward) in DIRECTIONS.iter().copied() {
+            let angles = ViewAngles::from_engine(pitch, yaw);
+            assert_vector(angles.forward(), Vector3::from(forward));
+            assert_eq!(angles.to_qangle(), [pitch, yaw, 0.0]);
+            assert_angle(angles.pitch_down_positive(), pitch);
+            assert_angle(angles.pitch_up_positive(), -pitch);
+
+            let yaw_ccw = angles.yaw_ccw_from_x();
+            assert!((-180.0..180.0).contains(&yaw_ccw));
+            assert_angle((yaw_ccw - yaw).rem_euclid(360.0), 0.0);
+
+            let restored = ViewAngles::from_forward(&angles.forward());
+            assert_angle(restored.pitch_down_positive(), pitch);
+            if pitch.abs() < 90.0 {
+                assert_angle(restored.yaw_ccw_from_x(), yaw_ccw);
+            }
+        }
+
+        /* looking up and to the left */
+        let angles = ViewAngles::from_engine(-45.0, 90.0);
+        assert_vector(
+            angles.forward(),
+            Vector3::new(0.0, 1.0, 1.0) / 2.0f32.sqrt(),
+        );
+        assert!(angles.forward().z > 0.0);
+    }
+
+    #[test]
+    fn normalization() {
+        let angles = ViewAngles::from_engine(120.0, 540.0).normalized();
+        assert_eq!(angles.pitch_down_positive(), 89.0);
+        assert_eq!(angles.yaw_ccw_from_x(), -180.0);
+        assert_eq!(angles.to_qangle(), [89.0, -180.0, 0.0]);
+    }
+
+    #[test]
+    fn spherical() {
+        let cases = [
+            (0.0, 0.0, FRAC_PI_2, 0.0),
+            (0.0, 90.0, FRAC_PI_2, FRAC_PI_2),
+            (0.0, -90.0, FRAC_PI_2, -FRAC_PI_2),
+            (0.0, 180.0, FRAC_PI_2, -PI),
+            (90.0, 0.0, PI, 0.0),
+            (-90.0, 0.0, 0.0, 0.0),
+        ];
+
+        for (pitch, yaw, polar, azimuth) in cases {
+            let angles = ViewAngles::from_engine(pitch, yaw);
+            let spherical = angles.to_spherical();
+            assert_angle(spherical.polar, polar);
+            assert_angle(spherical.azimuth, azimuth);
+
+            /* the math convention of the same direction */
+            let (polar_sin, polar_cos) = spherical.polar.sin_cos();
+            let (azimuth_sin, azimuth_cos) = spherical.azimuth.sin_cos();
+            assert_vector(
+                Vector3::new(polar_sin * azimuth_cos, polar_sin * azimuth_sin, polar_cos),
+                angles.forward(),
+            );
+
+            let restored = ViewAngles::from_spherical(&SphericalAngles { polar, azimuth });
+            assert_vector(restored.forward(), angles.forward());
+        }
+    }
+
+    #[test]
+    fn left_handed() {
+        for (pitch, yaw, forward) in DIRECTIONS.iter().copied() {
+            let angles = ViewAngles::from_engine(pitch, yaw);
+            let euler = angles.to_left_handed_euler();
+            assert!(euler.iter().all(|value| (0.0..360.0).contains(value)));
+
+            /* forward vector of the euler angles within the left-handed system */
+            let (x_sin, x_cos) = euler[0].to_radians().sin_cos();
+            let (y_sin, y_cos) = euler[1].to_radians().sin_cos();
+            assert_vector(
+                Vector3::new(y_sin * x_cos, -x_sin, y_cos * x_cos),
+                engine_to_left_handed_position(&Vector3::from(forward)),
+            );
+
+            let restored = ViewAngles::from_left_handed_euler(euler);
+            assert_vector(restored.forward(), angles.forward());
+            assert_angle(restored.pitch_down_positive(), pitch);
+        }
+
+        /* turning right is a positive yaw */
+        let euler = ViewAngles::from_engine(0.0, -90.0).to_left_handed_euler();
+        assert_angle(euler[1], 90.0);
+        assert_vector(
+            engine_to_left_handed_position(&Vector3::new(0.0, -1.0, 0.0)),
+            Vector3::new(1.0, 0.0, 0.0),
+        );
+    }
+}
@@ -1,4 +1,14 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{
+        BTreeMap,
+        HashMap,
+    },
+    hash::{
+        DefaultHasher,
+        Hash,
+        Hasher,
+    },
+};
 
 use anyhow::{
     anyhow,
@@ -8,8 +18,10 @@ use cs2_schema_cutl::{
     CStringUtil,
     PtrCStr,
 };
+use cs2_schema_generated::cs2::client::CEntityIdentity;
 use raw_struct::{
     builtins::Ptr64,
+    Copy,
     FromMemoryView,
 };
 use utils_state::{
@@ -22,12 +34,17 @@ use crate::{
     CEntityIdentityEx,
     CS2Handle,
     StateCS2Handle,
+    StateCS2Memory,
     StateEntityList,
+    StateGlobals,
 };
 
 pub struct ClassNameCache {
     lookup: BTreeMap<u64, String>,
     reverse_lookup: BTreeMap<String, u64>,
+
+    /// Memory epoch the cached class addresses belong to
+    memory_epoch: u64,
 }
 
 impl State for ClassNameCache {
@@ -37,6 +54,7 @@ impl State for ClassNameCache {
         Ok(Self {
             lookup: Default::default(),
             reverse_lookup: Default::default(),
+            memory_epoch: 0,
         })
     }
 
@@ -45,6 +63,8 @@ impl State for ClassNameCache {
     }
 
     fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        self.check_memory_epoch(states.resolve::<StateCS2Memory>(())?.epoch());
+
         let cs2 = states.resolve::<StateCS2Handle>(())?;
         let entities = states.resolve::<StateEntityList>(())?;
         for identity in entities.entities() {
@@ -63,6 +83,20 @@ impl State for ClassNameCache {
 }
 
 impl ClassNameCache {
+    /// Drop all class names if the memory view has been attached to another process since the last check.
+    /// Returns true if the class names have been dropped.
+    fn check_memory_epoch(&mut self, memory_epoch: u64) -> bool {
+        if self.memory_epoch == memory_epoch {
+            return false;
+        }
+
+        /* the process changed, all class addresses are invalid */
+        self.lookup.clear();
+        self.reverse_lookup.clear();
+        self.memory_epoch = memory_epoch;
+        true
+    }
+
     fn register_class_info(
         &mut self,
         cs2: &CS2Handle,
@@ -84,11 +118,16 @@ impl ClassNameCache {
         .read_string(&*memory)?
         .context("failed to read class name")?;
 
-        self.lookup.insert(address, class_name.clone());
-        self.reverse_lookup.insert(class_name, address);
+        self.insert(address, class_name);
         Ok(())
     }
 
+    /// Register the class name of a class info address (e.g. for memory fixtures)
+    pub fn insert(&mut self, class_info_address: u64, class_name: String) {
+        self.lookup.insert(class_info_address, class_name.clone());
+        self.reverse_lookup.insert(class_name, class_info_address);
+    }
+
     pub fn lookup(&self, class_info: &Ptr64<()>) -> anyhow::Result<Option<&String>> {
         let address = class_info.address;
         Ok(self.lookup.get(&address))
@@ -98,3 +137,292 @@ impl ClassNameCache {
         self.reverse_lookup.get(name).cloned()
     }
 }
+
+/// Max amount of distinct classes tracked by the [StateEntityClassRegistry]
+const ENTITY_CLASS_REGISTRY_CAPACITY: usize = 4096;
+
+#[derive(Debug, Clone)]
+pub struct EntityClassRecord {
+    pub class_name: String,
+
+    /// Amount of entities of this class within the last observed frame
+    pub current_count: usize,
+
+    /// Max amount of entities of this class observed within a single frame
+    pub max_count: usize,
+
+    /// Frame (`globals.frame_count_1`) this class has been seen the first time
+    pub first_seen_frame: u32,
+
+    /// Frame (`globals.frame_count_1`) this class has been seen the last time
+    pub last_seen_frame: u32,
+}
+
+/// Registry of all entity classes observed within the entity list during this session.
+/// Use [StateEntityClassRegistry::reset] to clear all records.
+pub struct StateEntityClassRegistry {
+    records: HashMap<u64, EntityClassRecord>,
+
+    /// Entities which class name could not be resolved within the last observed frame
+    pub unresolved_count: usize,
+
+    /// Classes have been dropped as the registry capacity has been exceeded
+    pub overflowed: bool,
+}
+
+impl State for StateEntityClassRegistry {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            records: Default::default(),
+            unresolved_count: 0,
+            overflowed: false,
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        let entities = states.resolve::<StateEntityList>(())?;
+        let class_name_cache = states.resolve::<ClassNameCache>(())?;
+        let frame = states
+            .resolve::<StateGlobals>(())
+            .ok()
+            .and_then(|globals| globals.frame_count_1().ok())
+            .unwrap_or_default();
+
+        let mut frame_counts = HashMap::<u64, (&String, usize)>::new();
+        self.unresolved_count = 0;
+        for identity in entities.entities() {
+            let Some(class_name) = class_name_cache.lookup(&identity.entity_class_info()?)? else {
+                self.unresolved_count += 1;
+                continue;
+            };
+
+            let mut hasher = DefaultHasher::new();
+            class_name.hash(&mut hasher);
+            frame_counts
+                .entry(hasher.finish())
+                .or_insert((class_name, 0))
+                .1 += 1;
+        }
+
+        for record in self.records.values_mut() {
+            record.current_count = 0;
+        }
+
+        for (class_hash, (class_name, count)) in frame_counts {
+            if !self.records.contains_key(&class_hash)
+                && self.records.len() >= ENTITY_CLASS_REGISTRY_CAPACITY
+            {
+                self.overflowed = true;
+                continue;
+            }
+
+            let record = self
+                .records
+                .entry(class_hash)
+                .or_insert_with(|| EntityClassRecord {
+                    class_name: class_name.clone(),
+                    current_count: 0,
+                    max_count: 0,
+                    first_seen_frame: frame,
+                    last_seen_frame: frame,
+                });
+
+            record.current_count = count;
+            record.max_count = record.max_count.max(count);
+            record.last_seen_frame = frame;
+        }
+
+        Ok(())
+    }
+}
+
+impl StateEntityClassRegistry {
+    /// All observed classes sorted by their class name
+    pub fn records(&self) -> Vec<&EntityClassRecord> {
+        let mut records = self.records.values().collect::<Vec<_>>();
+        records.sort_by(|a, b| a.class_name.cmp(&b.class_name));
+        records
+    }
+
+    pub fn lookup(&self, class_name: &str) -> Option<&EntityClassRecord> {
+        let mut hasher = DefaultHasher::new();
+        class_name.hash(&mut hasher);
+        self.records.get(&hasher.finish())
+    }
+
+    pub fn reset(&mut self) {
+        self.records.clear();
+        self.unresolved_count = 0;
+        self.overflowed = false;
+    }
+}
+
+/// Entities of the current frame grouped by their class name.
+///
+/// The index is built once per frame, hence states looking for entities of a specific class
+/// do not have to scan and compare the class of every entity themselves.
+/// Entities which class could not be resolved are skipped (see [StateEntityClassIndex::unresolved_count]).
+pub struct StateEntityClassIndex {
+    /// Positions within [StateEntityList::entities] by the class name
+    by_class: HashMap<String, Vec<usize>>,
+
+    /// Entities which class name could not be resolved within this frame
+    pub unresolved_count: usize,
+}
+
+impl State for StateEntityClassIndex {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let entities = states.resolve::<StateEntityList>(())?;
+        let class_name_cache = states.resolve::<ClassNameCache>(())?;
+
+        Ok(Self::build(entities.entities().iter(), |identity| {
+            Ok(class_name_cache
+                .lookup(&identity.entity_class_info()?)?
+                .map(String::as_str))
+        }))
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+impl StateEntityClassIndex {
+    fn build<'a, E>(
+        entities: impl Iterator<Item = E>,
+        class_name: impl Fn(&E) -> anyhow::Result<Option<&'a str>>,
+    ) -> Self {
+        let mut result = Self {
+            by_class: HashMap::with_capacity(64),
+            unresolved_count: 0,
+        };
+
+        for (position, entity) in entities.enumerate() {
+            let class_name = match class_name(&entity) {
+                Ok(Some(class_name)) => class_name,
+                Ok(None) | Err(_) => {
+                    result.unresolved_count += 1;
+                    continue;
+                }
+            };
+
+            match result.by_class.get_mut(class_name) {
+                Some(positions) => positions.push(position),
+                None => {
+                    result
+                        .by_class
+                        .insert(class_name.to_string(), vec![position]);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Positions within [StateEntityList::entities] of all entities of the class
+    pub fn positions(&self, class_name: &str) -> &[usize] {
+        self.by_class
+            .get(class_name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// All known classes with the positions of their entities
+    pub fn classes(&self) -> impl Iterator<Item = (&str, &[usize])> {
+        self.by_class
+            .iter()
+            .map(|(class_name, positions)| (class_name.as_str(), positions.as_slice()))
+    }
+
+    /// All entities of the class.
+    /// The entity list must be the entity list of the current frame.
+    pub fn entities_of_class<'a>(
+        &'a self,
+        entities: &'a StateEntityList,
+        class_name: &str,
+    ) -> impl Iterator<Item = &'a Copy<dyn CEntityIdentity>> + 'a {
+        self.positions(class_name)
+            .iter()
+            .filter_map(|position| entities.entities().get(*position))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        cell::Cell,
+        sync::Arc,
+    };
+
+    use utils_state::State;
+
+    use super::{
+        ClassNameCache,
+        StateEntityClassIndex,
+    };
+    use crate::{
+        test_fixture::EntityFixture,
+        StateCS2Memory,
+    };
+
+    #[test]
+    fn memory_epoch() {
+        let states = EntityFixture::default().into_states();
+        let mut cache = ClassNameCache::create(&states, ()).unwrap();
+        cache.insert(0x7800_0000, "C_CSPlayerPawn".to_string());
+
+        let memory = states.resolve::<StateCS2Memory>(()).unwrap();
+        assert!(!cache.check_memory_epoch(memory.epoch()));
+        assert_eq!(cache.reverse_lookup("C_CSPlayerPawn"), Some(0x7800_0000));
+
+        /* re-attached to another process after the cache has been created */
+        memory.swap_view(Arc::new(EntityFixture::default().memory));
+        assert!(cache.check_memory_epoch(memory.epoch()));
+        assert_eq!(cache.reverse_lookup("C_CSPlayerPawn"), None);
+
+        /* the new class names remain until the next swap */
+        cache.insert(0x7800_0010, "C_CSPlayerPawn".to_string());
+        assert!(!cache.check_memory_epoch(memory.epoch()));
+        assert_eq!(cache.reverse_lookup("C_CSPlayerPawn"), Some(0x7800_0010));
+    }
+
+    #[test]
+    fn class_index() {
+        let entities = [
+            Some("C_CSPlayerPawn"),
+            Some("CCSPlayerController"),
+            None,
+            Some("C_CSPlayerPawn"),
+            Some("C_PlantedC4"),
+            Some("<torn read>"),
+            Some("C_CSPlayerPawn"),
+        ];
+
+        let lookups = Cell::new(0);
+        let index = StateEntityClassIndex::build(entities.iter(), |entity| {
+            lookups.set(lookups.get() + 1);
+            match entity {
+                Some("<torn read>") => anyhow::bail!("invalid class info"),
+                entity => Ok(**entity),
+            }
+        });
+
+        /* every state uses the same index, the entities have only been looked up once */
+        assert_eq!(index.positions("C_CSPlayerPawn"), &[0, 3, 6]);
+        assert_eq!(index.positions("C_PlantedC4"), &[4]);
+        assert_eq!(index.positions("CCSPlayerController"), &[1]);
+        assert!(index.positions("C_C4").is_empty());
+        assert_eq!(lookups.get(), entities.len());
+
+        /* failing entities do not affect the other entities */
+        assert_eq!(index.unresolved_count, 2);
+    }
+}
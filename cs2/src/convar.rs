@@ -29,6 +29,12 @@ impl ConVars {
     }
 
     pub fn find_cvar(&self, name: &str) -> anyhow::Result<Option<Reference<dyn ConVar>>> {
+        Ok(self.find_cvars(&[name])?.pop().flatten())
+    }
+
+    /// Find multiple cvars at once while only iterating the cvar list once.
+    /// The result has the same order as the requested names.
+    pub fn find_cvars(&self, names: &[&str]) -> anyhow::Result<Vec<Option<Reference<dyn ConVar>>>> {
         let memory_view_arc = self.ccvars.reference_memory();
         let memory_view = memory_view_arc.deref();
 
@@ -39,7 +45,13 @@ impl ConVars {
             .entries()?
             .elements_copy(memory_view, 0..entry_count)?;
 
+        let mut result = names.iter().map(|_| None).collect::<Vec<_>>();
+        let mut remaining = names.len();
         for entry in entries {
+            if remaining == 0 {
+                break;
+            }
+
             let Some(con_var) = entry.value()?.value_reference(memory_view_arc.clone()) else {
                 continue;
             };
@@ -48,13 +60,16 @@ impl ConVars {
                 continue;
             };
 
-            if con_var_name != name {
+            let Some(index) = names.iter().position(|name| *name == con_var_name) else {
                 continue;
-            }
+            };
 
-            return Ok(Some(con_var));
+            if result[index].is_none() {
+                result[index] = Some(con_var);
+                remaining -= 1;
+            }
         }
 
-        Ok(None)
+        Ok(result)
     }
 }
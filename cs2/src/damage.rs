@@ -0,0 +1,435 @@
+use crate::{
+    WeaponDamageProfile,
+    WeaponId,
+};
+
+/// Damage multiplier for head shots
+pub const HEADSHOT_MULTIPLIER: f32 = 4.0;
+
+/// Distance (in units) for which the weapon range modifier is applied once
+const RANGE_MODIFIER_DISTANCE: f32 = 500.0;
+
+/// Fraction of the absorbed damage which is dealt to the armor
+const ARMOR_BONUS: f32 = 0.5;
+
+/// Upper limit of shots simulated until we give up
+const SHOTS_TO_KILL_LIMIT: u32 = 64;
+
+/// Hit group of a player hitbox
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HitGroup {
+    Generic,
+    Head,
+    Chest,
+    Stomach,
+    LeftArm,
+    RightArm,
+    LeftLeg,
+    RightLeg,
+    Neck,
+    Gear,
+}
+
+impl HitGroup {
+    /// Resolve the hit group by the engines hit group index
+    pub fn from_id(id: i32) -> Option<Self> {
+        Some(match id {
+            0 => Self::Generic,
+            1 => Self::Head,
+            2 => Self::Chest,
+            3 => Self::Stomach,
+            4 => Self::LeftArm,
+            5 => Self::RightArm,
+            6 => Self::LeftLeg,
+            7 => Self::RightLeg,
+            8 => Self::Neck,
+            10 => Self::Gear,
+            _ => return None,
+        })
+    }
+
+    pub fn damage_multiplier(&self) -> f32 {
+        match self {
+            Self::Head => HEADSHOT_MULTIPLIER,
+            Self::Stomach => 1.25,
+            Self::LeftLeg | Self::RightLeg => 0.75,
+            _ => 1.0,
+        }
+    }
+
+    /// Check if damage to this hit group will be reduced by the players armor
+    pub fn is_armored(&self, has_helmet: bool) -> bool {
+        match self {
+            Self::Head => has_helmet,
+            Self::LeftLeg | Self::RightLeg => false,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DamageResult {
+    /// Damage dealt to the players health
+    pub hp_damage: i32,
+
+    /// Damage dealt to the players armor
+    pub armor_damage: i32,
+}
+
+fn compute_with_profile(
+    profile: &WeaponDamageProfile,
+    distance: f32,
+    hitgroup: HitGroup,
+    armor: i32,
+    has_helmet: bool,
+) -> DamageResult {
+    let damage = profile.damage
+        * profile
+            .range_modifier
+            .powf(distance.max(0.0) / RANGE_MODIFIER_DISTANCE)
+        * hitgroup.damage_multiplier();
+
+    if armor <= 0 || !hitgroup.is_armored(has_helmet) {
+        return DamageResult {
+            hp_damage: damage as i32,
+            armor_damage: 0,
+        };
+    }
+
+    apply_armor(damage, profile.armor_ratio, armor)
+}
+
+/// Split the damage between health and armor
+fn apply_armor(damage: f32, armor_ratio: f32, armor: i32) -> DamageResult {
+    let mut hp_damage = damage * armor_ratio * 0.5;
+    let mut armor_damage = (damage - hp_damage) * ARMOR_BONUS;
+    if armor_damage > armor as f32 {
+        armor_damage = armor as f32;
+        hp_damage = damage - armor_damage / ARMOR_BONUS;
+    }
+
+    DamageResult {
+        hp_damage: hp_damage as i32,
+        armor_damage: armor_damage as i32,
+    }
+}
+
+/// Default damage of a HE grenade at the center of the explosion
+pub const HE_GRENADE_DAMAGE: f32 = 99.0;
+
+/// Default radius of a HE grenade explosion
+pub const HE_GRENADE_RADIUS: f32 = 350.0;
+
+const HE_GRENADE_ARMOR_RATIO: f32 = 1.0;
+
+/// Calculate the damage of an explosion with the given center damage and radius.
+/// The damage falls off with a gaussian curve and is zero outside of the radius.
+/// Occlusion by the world geometry is not taken into account.
+pub fn explosion_damage(damage: f32, radius: f32, distance: f32, armor: i32) -> DamageResult {
+    if radius <= 0.0 || distance >= radius {
+        return DamageResult::default();
+    }
+
+    let sigma = radius / 3.0;
+    let damage = damage * (-(distance * distance) / (2.0 * sigma * sigma)).exp();
+    if armor <= 0 {
+        return DamageResult {
+            hp_damage: damage as i32,
+            armor_damage: 0,
+        };
+    }
+
+    apply_armor(damage, HE_GRENADE_ARMOR_RATIO, armor)
+}
+
+/// Default damage of the C4 explosion at the bomb position (`bombradius` of the maps `info_map_parameters`)
+pub const C4_DAMAGE: f32 = 500.0;
+
+/// Radius of the C4 explosion relative to its damage
+pub const C4_RADIUS_MULTIPLIER: f32 = 3.5;
+
+/// Calculate the damage of a C4 explosion with the given center damage.
+/// The radius of the explosion is derived from the damage (see [C4_RADIUS_MULTIPLIER]).
+pub fn c4_explosion_damage(damage: f32, distance: f32, armor: i32) -> DamageResult {
+    explosion_damage(damage, damage * C4_RADIUS_MULTIPLIER, distance, armor)
+}
+
+/// Calculate the damage of a single bullet hitting a player.
+/// Weapons which do not fire bullets (e.g. knifes & grenades) deal no damage.
+pub fn compute(
+    weapon: WeaponId,
+    distance: f32,
+    hitgroup: HitGroup,
+    armor: i32,
+    has_helmet: bool,
+) -> DamageResult {
+    match weapon.damage_profile() {
+        Some(profile) => compute_with_profile(&profile, distance, hitgroup, armor, has_helmet),
+        None => DamageResult::default(),
+    }
+}
+
+fn simulate_shots_to_kill(
+    profile: &WeaponDamageProfile,
+    distance: f32,
+    hitgroup: HitGroup,
+    mut health: i32,
+    mut armor: i32,
+    has_helmet: bool,
+) -> Option<u32> {
+    for shot in 1..=SHOTS_TO_KILL_LIMIT {
+        let damage = compute_with_profile(profile, distance, hitgroup, armor, has_helmet);
+
+        health -= damage.hp_damage;
+        armor = (armor - damage.armor_damage).max(0);
+        if health <= 0 {
+            return Some(shot);
+        }
+    }
+
+    None
+}
+
+/// Bullets required to kill a target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShotsToKill {
+    /// Bullets required when hitting the chest
+    pub body: Option<u32>,
+
+    /// Bullets required when hitting the head
+    pub head: Option<u32>,
+}
+
+/// Estimate the amount of bullets required to kill a target at the given distance.
+/// Returns None if the weapon does not fire bullets.
+pub fn shots_to_kill(
+    weapon: WeaponId,
+    distance: f32,
+    health: i32,
+    armor: i32,
+    has_helmet: bool,
+) -> Option<ShotsToKill> {
+    let profile = weapon.damage_profile()?;
+    Some(ShotsToKill {
+        body: simulate_shots_to_kill(
+            &profile,
+            distance,
+            HitGroup::Chest,
+            health,
+            armor,
+            has_helmet,
+        ),
+        head: simulate_shots_to_kill(
+            &profile,
+            distance,
+            HitGroup::Head,
+            health,
+            armor,
+            has_helmet,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        c4_explosion_damage,
+        compute,
+        explosion_damage,
+        shots_to_kill,
+        DamageResult,
+        HitGroup,
+        C4_DAMAGE,
+        HE_GRENADE_DAMAGE,
+        HE_GRENADE_RADIUS,
+    };
+    use crate::WeaponId;
+
+    #[test]
+    fn damage_vectors() {
+        let cases = [
+            /* (weapon, distance, hit group, armor, helmet, hp damage, armor damage) */
+            (WeaponId::Ak47, 0.0, HitGroup::Head, 0, false, 144, 0),
+            (WeaponId::Ak47, 0.0, HitGroup::Head, 100, true, 111, 16),
+            (WeaponId::Ak47, 0.0, HitGroup::Head, 100, false, 144, 0),
+            (WeaponId::Ak47, 0.0, HitGroup::Chest, 100, true, 27, 4),
+            (WeaponId::Ak47, 0.0, HitGroup::Chest, 0, false, 36, 0),
+            (WeaponId::Ak47, 0.0, HitGroup::Stomach, 0, false, 45, 0),
+            (WeaponId::Ak47, 0.0, HitGroup::Stomach, 100, true, 34, 5),
+            (WeaponId::Ak47, 0.0, HitGroup::LeftLeg, 100, true, 27, 0),
+            (WeaponId::Ak47, 1000.0, HitGroup::Head, 0, false, 138, 0),
+            (WeaponId::Ak47, 2000.0, HitGroup::Head, 100, true, 102, 14),
+            (WeaponId::M4A4, 0.0, HitGroup::Head, 100, true, 92, 19),
+            (WeaponId::M4A4, 0.0, HitGroup::Chest, 100, true, 23, 4),
+            (WeaponId::M4A4, 1500.0, HitGroup::Chest, 0, false, 30, 0),
+            (
+                WeaponId::M4A1Silencer,
+                0.0,
+                HitGroup::Head,
+                100,
+                true,
+                106,
+                22,
+            ),
+            (
+                WeaponId::M4A1Silencer,
+                2000.0,
+                HitGroup::Head,
+                100,
+                true,
+                83,
+                17,
+            ),
+            (WeaponId::AWP, 0.0, HitGroup::Chest, 100, true, 112, 1),
+            (WeaponId::AWP, 0.0, HitGroup::RightLeg, 100, true, 86, 0),
+            (WeaponId::AWP, 0.0, HitGroup::Stomach, 100, true, 140, 1),
+            (WeaponId::AWP, 3000.0, HitGroup::Chest, 100, true, 105, 1),
+            (WeaponId::Deagle, 0.0, HitGroup::Head, 100, true, 197, 7),
+            (WeaponId::Deagle, 1000.0, HitGroup::Head, 100, true, 142, 5),
+            (WeaponId::Deagle, 0.0, HitGroup::Chest, 100, true, 49, 1),
+            (WeaponId::Glock, 0.0, HitGroup::Chest, 100, true, 14, 7),
+            (WeaponId::Glock, 0.0, HitGroup::Head, 100, true, 56, 31),
+            (WeaponId::Glock, 0.0, HitGroup::Head, 0, false, 120, 0),
+            (WeaponId::USPS, 0.0, HitGroup::Head, 100, true, 70, 34),
+            (WeaponId::USPS, 500.0, HitGroup::Head, 100, true, 64, 31),
+            (WeaponId::Ssg08, 0.0, HitGroup::Chest, 100, true, 74, 6),
+            (WeaponId::Ssg08, 0.0, HitGroup::Head, 100, true, 299, 26),
+            (WeaponId::Mac10, 0.0, HitGroup::Chest, 100, true, 16, 6),
+            (WeaponId::Nova, 0.0, HitGroup::Chest, 100, true, 13, 6),
+            (WeaponId::Negev, 0.0, HitGroup::LeftArm, 100, true, 24, 5),
+            /* armor depleted by the hit */
+            (WeaponId::Ak47, 0.0, HitGroup::Chest, 5, true, 27, 4),
+            (WeaponId::Ak47, 0.0, HitGroup::Chest, 3, true, 30, 3),
+            (WeaponId::Deagle, 0.0, HitGroup::Head, 2, true, 208, 2),
+        ];
+
+        for (weapon, distance, hitgroup, armor, helmet, hp_damage, armor_damage) in cases {
+            assert_eq!(
+                compute(weapon, distance, hitgroup, armor, helmet),
+                DamageResult {
+                    hp_damage,
+                    armor_damage
+                },
+                "{:?} at {} hitting {:?} (armor: {}, helmet: {})",
+                weapon,
+                distance,
+                hitgroup,
+                armor,
+                helmet
+            );
+        }
+
+        assert_eq!(
+            compute(WeaponId::Knife, 0.0, HitGroup::Head, 0, false),
+            DamageResult::default()
+        );
+    }
+
+    #[test]
+    fn explosion() {
+        let cases = [
+            /* (distance, armor, hp damage, armor damage) */
+            (0.0, 0, 99, 0),
+            (0.0, 100, 49, 24),
+            (100.0, 0, 68, 0),
+            (100.0, 100, 34, 17),
+            (200.0, 0, 22, 0),
+            (349.0, 0, 1, 0),
+            (350.0, 0, 0, 0),
+            (1000.0, 100, 0, 0),
+        ];
+
+        for (distance, armor, hp_damage, armor_damage) in cases {
+            assert_eq!(
+                explosion_damage(HE_GRENADE_DAMAGE, HE_GRENADE_RADIUS, distance, armor),
+                DamageResult {
+                    hp_damage,
+                    armor_damage
+                },
+                "{} (armor: {})",
+                distance,
+                armor
+            );
+        }
+    }
+
+    #[test]
+    fn c4_explosion() {
+        let cases = [
+            /* (distance, armor, hp damage, armor damage) */
+            (0.0, 0, 500, 0),
+            (0.0, 100, 300, 100),
+            (500.0, 0, 346, 0),
+            (500.0, 100, 173, 86),
+            (700.0, 100, 121, 60),
+            (1000.0, 0, 115, 0),
+            (1000.0, 100, 57, 28),
+            (1500.0, 0, 18, 0),
+            (1749.0, 0, 5, 0),
+            (1750.0, 0, 0, 0),
+            (3000.0, 100, 0, 0),
+        ];
+
+        for (distance, armor, hp_damage, armor_damage) in cases {
+            assert_eq!(
+                c4_explosion_damage(C4_DAMAGE, distance, armor),
+                DamageResult {
+                    hp_damage,
+                    armor_damage
+                },
+                "{} (armor: {})",
+                distance,
+                armor
+            );
+        }
+
+        /* the radius scales with the damage configured by the map */
+        assert_eq!(c4_explosion_damage(250.0, 874.0, 0).hp_damage, 2);
+        assert_eq!(
+            c4_explosion_damage(250.0, 875.0, 0),
+            DamageResult::default()
+        );
+    }
+
+    #[test]
+    fn one_taps() {
+        for distance in [0.0, 500.0, 1000.0, 1500.0, 2000.0, 2500.0] {
+            let ak = shots_to_kill(WeaponId::Ak47, distance, 100, 100, true).unwrap();
+            assert_eq!(ak.head, Some(1), "AK-47 at {}", distance);
+
+            let m4 = shots_to_kill(WeaponId::M4A4, distance, 100, 100, true).unwrap();
+            assert_eq!(m4.head, Some(2), "M4A4 at {}", distance);
+        }
+    }
+
+    #[test]
+    fn known_values() {
+        let cases = [
+            /* (weapon, distance, health, armor, helmet, body, head) */
+            (WeaponId::Ak47, 0.0, 100, 100, true, 4, 1),
+            (WeaponId::Ak47, 0.0, 100, 0, false, 3, 1),
+            (WeaponId::M4A4, 0.0, 100, 0, false, 4, 1),
+            (WeaponId::M4A1Silencer, 0.0, 100, 100, true, 4, 1),
+            (WeaponId::AWP, 0.0, 100, 100, true, 1, 1),
+            (WeaponId::Ssg08, 0.0, 100, 100, true, 2, 1),
+            (WeaponId::Deagle, 0.0, 100, 100, true, 3, 1),
+            (WeaponId::Glock, 0.0, 100, 100, true, 8, 2),
+            (WeaponId::Glock, 0.0, 100, 0, false, 4, 1),
+            (WeaponId::USPS, 0.0, 100, 100, false, 6, 1),
+            (WeaponId::Ak47, 0.0, 20, 100, true, 1, 1),
+        ];
+
+        for (weapon, distance, health, armor, helmet, body, head) in cases {
+            let result = shots_to_kill(weapon, distance, health, armor, helmet).unwrap();
+            assert_eq!(
+                (result.body, result.head),
+                (Some(body), Some(head)),
+                "{:?} at {}",
+                weapon,
+                distance
+            );
+        }
+
+        assert!(shots_to_kill(WeaponId::Knife, 0.0, 100, 100, true).is_none());
+    }
+}
@@ -0,0 +1,838 @@
+#[cfg(feature = "serialize")]
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+use nalgebra::Vector3;
+
+use crate::{
+    predict_grenade_position,
+    BombDefuser,
+    BombState,
+    ControllerIndex,
+    DataQuality,
+    EntityIndex,
+    FieldConfidence,
+    GameSnapshot,
+    GrenadeKind,
+    PawnIndex,
+    PlantedC4Entry,
+    PlantedC4RawFields,
+    PlantedC4State,
+    SnapshotDetail,
+    SnapshotGrenade,
+    SnapshotPlayer,
+    SnapshotPlayerDetails,
+    ViewAngles,
+    WeaponId,
+    GRENADE_GRAVITY,
+    SMOKE_EFFECT_DURATION,
+};
+
+/// Simulated time (in seconds) advanced for every generated snapshot
+pub const DEMO_TICK_INTERVAL: f32 = 0.05;
+
+/// Map of the generated snapshots
+pub const DEMO_MAP: &str = "de_dust2";
+
+/// Entity index of the planted C4
+pub const DEMO_PLANTED_C4_ENTITY_ID: u32 = 401;
+
+/// Entity index of the first grenade thrown within a round
+pub const DEMO_GRENADE_ENTITY_ID: u32 = 300;
+
+const TEAM_ID_TERRORIST: u8 = 2;
+const TEAM_ID_COUNTER_TERRORIST: u8 = 3;
+
+const ROUND_FREEZE_TIME: f32 = 5.0;
+const ROUND_RESTART_DELAY: f32 = 5.0;
+const ROUND_MAX_TIME: f32 = 115.0;
+
+const PLAYER_SPEED: f32 = 220.0;
+const PLAYER_EYE_HEIGHT: f32 = 64.0;
+const BOMB_PLANT_DURATION: f32 = 3.2;
+const BOMB_TIMER: f32 = 40.0;
+const BOMB_DEFUSE_DURATION_KIT: f32 = 5.0;
+
+/// Time (in seconds) between throwing a grenade and its detonation
+const GRENADE_FLIGHT_TIME: f32 = 1.5;
+
+/// Players within this distance of a detonating flashbang get blinded
+const FLASHBANG_RADIUS: f32 = 800.0;
+
+/// Time (in seconds) a player right next to a detonating flashbang stays blinded
+const FLASHBANG_MAX_DURATION: f32 = 4.0;
+
+const SPAWN_TERRORIST: [f32; 3] = [-600.0, -800.0, 180.0];
+const SPAWN_COUNTER_TERRORIST: [f32; 3] = [300.0, 2300.0, -120.0];
+const BOMB_SITES: [[f32; 3]; 2] = [[1150.0, 2500.0, 96.0], [-1550.0, 2550.0, 2.0]];
+
+/// Waypoints from the T spawn towards each bomb site
+const ROUTES_TERRORIST: [&[[f32; 3]]; 2] = [
+    &[
+        [500.0, -300.0, 0.0],
+        [1300.0, 1000.0, 0.0],
+        [1150.0, 2500.0, 96.0],
+    ],
+    &[
+        [-1000.0, 600.0, 0.0],
+        [-1900.0, 1400.0, 0.0],
+        [-1550.0, 2550.0, 2.0],
+    ],
+];
+
+/// Holding positions of the CTs
+const POSITIONS_COUNTER_TERRORIST: [[f32; 3]; 5] = [
+    [1000.0, 2700.0, 96.0],
+    [1400.0, 2300.0, 96.0],
+    [-300.0, 1700.0, 0.0],
+    [-1400.0, 2700.0, 2.0],
+    [-1700.0, 2300.0, 2.0],
+];
+
+/// Small deterministic pseudo random number generator (SplitMix64)
+#[derive(Debug, Clone)]
+pub struct DemoRandom {
+    state: u64,
+}
+
+impl DemoRandom {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D049BB133111EB);
+        value ^ (value >> 31)
+    }
+
+    /// Random value within [0; 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
+
+fn jitter(random: &mut DemoRandom, position: [f32; 3], amount: f32) -> [f32; 3] {
+    [
+        position[0] + random.range(-amount, amount),
+        position[1] + random.range(-amount, amount),
+        position[2],
+    ]
+}
+
+#[derive(Debug, Clone)]
+struct DemoPlayer {
+    controller_entity_id: u32,
+    pawn_entity_id: u32,
+    team_id: u8,
+    name: String,
+    has_defuser: bool,
+
+    /// Path the player walks along after the freeze time
+    path: Vec<[f32; 3]>,
+
+    /// Round time at which the player dies
+    time_death: Option<f32>,
+}
+
+impl DemoPlayer {
+    /// Position and view rotation (yaw in degrees) after walking `distance` along the path
+    fn position(&self, distance: f32) -> ([f32; 3], f32) {
+        let mut remaining = distance.max(0.0);
+        for segment in self.path.windows(2) {
+            let (from, to) = (segment[0], segment[1]);
+            let delta = [to[0] - from[0], to[1] - from[1], to[2] - from[2]];
+            let length = (delta[0] * delta[0] + delta[1] * delta[1]).sqrt();
+            let rotation = delta[1].atan2(delta[0]).to_degrees();
+            if remaining <= length && length > 0.0 {
+                let factor = remaining / length;
+                return (
+                    [
+                        from[0] + delta[0] * factor,
+                        from[1] + delta[1] * factor,
+                        from[2] + delta[2] * factor,
+                    ],
+                    rotation,
+                );
+            }
+
+            remaining -= length;
+        }
+
+        let last = self.path.last().copied().unwrap_or_default();
+        (last, 0.0)
+    }
+}
+
+/// Grenade thrown within a round
+#[derive(Debug, Clone)]
+struct DemoGrenade {
+    entity_id: u32,
+    kind: GrenadeKind,
+
+    /// Player index of the thrower
+    thrower: usize,
+    time_throw: f32,
+    target: [f32; 3],
+}
+
+impl DemoGrenade {
+    fn time_detonation(&self) -> f32 {
+        self.time_throw + GRENADE_FLIGHT_TIME
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DemoRoundOutcome {
+    Defused,
+    Detonated,
+}
+
+/// Scripted round which will be replayed with different seeds
+#[derive(Debug, Clone)]
+struct DemoRound {
+    players: Vec<DemoPlayer>,
+    grenades: Vec<DemoGrenade>,
+
+    bomb_site: u8,
+    bomb_carrier: usize,
+
+    /// Round time at which the bomb has been planted
+    time_planted: f32,
+
+    /// Player index of the defuser and the round time the defuse starts
+    defuse: Option<(usize, f32)>,
+
+    outcome: DemoRoundOutcome,
+}
+
+impl DemoRound {
+    fn generate(random: &mut DemoRandom, round_number: i32) -> Self {
+        let bomb_site = (random.next_u64() % 2) as u8;
+        let bomb_carrier = (random.next_u64() % 5) as usize;
+
+        let mut players = Vec::with_capacity(10);
+        for index in 0..10u32 {
+            let team_id = if index < 5 {
+                TEAM_ID_TERRORIST
+            } else {
+                TEAM_ID_COUNTER_TERRORIST
+            };
+
+            let mut path = Vec::with_capacity(4);
+            if team_id == TEAM_ID_TERRORIST {
+                path.push(jitter(random, SPAWN_TERRORIST, 64.0));
+                for waypoint in ROUTES_TERRORIST[bomb_site as usize].iter() {
+                    path.push(jitter(random, *waypoint, 64.0));
+                }
+
+                if index as usize == bomb_carrier {
+                    /* the bomb carrier walks straight to the bomb site */
+                    *path.last_mut().unwrap() = BOMB_SITES[bomb_site as usize];
+                }
+            } else {
+                path.push(jitter(random, SPAWN_COUNTER_TERRORIST, 64.0));
+                path.push(jitter(
+                    random,
+                    POSITIONS_COUNTER_TERRORIST[(index - 5) as usize],
+                    64.0,
+                ));
+            }
+
+            players.push(DemoPlayer {
+                controller_entity_id: 1 + index,
+                pawn_entity_id: 100 + index * 4,
+                team_id,
+                name: format!(
+                    "{} {}",
+                    if team_id == TEAM_ID_TERRORIST {
+                        "Terrorist"
+                    } else {
+                        "Counter-Terrorist"
+                    },
+                    index % 5 + 1
+                ),
+                has_defuser: team_id == TEAM_ID_COUNTER_TERRORIST && random.next_f32() < 0.6,
+                path,
+                time_death: None,
+            });
+        }
+
+        let time_planted = ROUND_FREEZE_TIME
+            + Self::path_length(&players[bomb_carrier].path) / PLAYER_SPEED
+            + BOMB_PLANT_DURATION;
+
+        /* a few players die before the bomb explodes, but never the bomb carrier before the plant */
+        for index in 0..10 {
+            if random.next_f32() >= 0.35 {
+                continue;
+            }
+
+            let earliest = if index == bomb_carrier {
+                time_planted + 1.0
+            } else {
+                ROUND_FREEZE_TIME + 5.0
+            };
+            players[index].time_death = Some(random.range(earliest, time_planted + BOMB_TIMER));
+        }
+
+        let outcome = if round_number % 3 == 0 || random.next_f32() < 0.3 {
+            DemoRoundOutcome::Detonated
+        } else {
+            DemoRoundOutcome::Defused
+        };
+
+        let defuse = match outcome {
+            DemoRoundOutcome::Defused => {
+                /* the defuser must survive the defuse */
+                let defuser = (5..10)
+                    .find(|index| players[*index].time_death.is_none())
+                    .unwrap_or(5);
+                players[defuser].time_death = None;
+                players[defuser].has_defuser = true;
+
+                Some((defuser, time_planted + random.range(5.0, 20.0)))
+            }
+            DemoRoundOutcome::Detonated => None,
+        };
+
+        /* the Ts take the site with a smoke and a flash, the CTs retake it with an HE and an incendiary */
+        let site = BOMB_SITES[bomb_site as usize];
+        let grenade_plan = [
+            (
+                GrenadeKind::Smoke,
+                random.next_u64() % 5,
+                time_planted - random.range(8.0, 12.0),
+            ),
+            (
+                GrenadeKind::Flashbang,
+                random.next_u64() % 5,
+                time_planted - random.range(4.0, 7.0),
+            ),
+            (
+                GrenadeKind::HighExplosive,
+                5 + random.next_u64() % 5,
+                time_planted + random.range(2.0, 8.0),
+            ),
+            (
+                GrenadeKind::Incendiary,
+                5 + random.next_u64() % 5,
+                time_planted + random.range(5.0, 12.0),
+            ),
+        ];
+
+        let mut grenades = Vec::with_capacity(grenade_plan.len());
+        for (kind, thrower, time_throw) in grenade_plan {
+            grenades.push(DemoGrenade {
+                entity_id: DEMO_GRENADE_ENTITY_ID + grenades.len() as u32,
+                kind,
+                thrower: thrower as usize,
+                time_throw: time_throw.max(ROUND_FREEZE_TIME),
+                target: jitter(random, site, 250.0),
+            });
+        }
+
+        Self {
+            players,
+            grenades,
+            bomb_site,
+            bomb_carrier,
+            time_planted,
+            defuse,
+            outcome,
+        }
+    }
+
+    fn path_length(path: &[[f32; 3]]) -> f32 {
+        path.windows(2)
+            .map(|segment| {
+                let dx = segment[1][0] - segment[0][0];
+                let dy = segment[1][1] - segment[0][1];
+                (dx * dx + dy * dy).sqrt()
+            })
+            .sum()
+    }
+
+    /// Round time at which the round ends (bomb defused or detonated)
+    fn time_end(&self) -> f32 {
+        match (self.outcome, self.defuse) {
+            (DemoRoundOutcome::Defused, Some((_, time_defuse))) => {
+                time_defuse + BOMB_DEFUSE_DURATION_KIT
+            }
+            _ => (self.time_planted + BOMB_TIMER).min(ROUND_MAX_TIME),
+        }
+    }
+
+    fn is_alive(&self, player: &DemoPlayer, time: f32) -> bool {
+        player
+            .time_death
+            .map(|time_death| time < time_death)
+            .unwrap_or(true)
+    }
+
+    /// Position and view rotation of the player at the round time.
+    /// Dead players remain where they died.
+    fn player_position(&self, index: usize, time: f32) -> ([f32; 3], f32) {
+        let player = &self.players[index];
+        if let Some((defuser, time_defuse)) = self.defuse {
+            if defuser == index && time >= time_defuse - 2.0 {
+                /* walk to the bomb */
+                return (BOMB_SITES[self.bomb_site as usize], 0.0);
+            }
+        }
+
+        let time = player
+            .time_death
+            .map(|time_death| time.min(time_death))
+            .unwrap_or(time);
+        player.position((time - ROUND_FREEZE_TIME).max(0.0) * PLAYER_SPEED)
+    }
+
+    /// Position and velocity of the grenade while it is flying
+    fn grenade_trajectory(&self, grenade: &DemoGrenade, time: f32) -> (Vector3<f32>, Vector3<f32>) {
+        let (origin, _) = self.player_position(grenade.thrower, grenade.time_throw);
+        let origin = Vector3::new(origin[0], origin[1], origin[2] + PLAYER_EYE_HEIGHT);
+        let target = Vector3::from(grenade.target);
+
+        /* launch velocity of the arc hitting the target after the flight time */
+        let mut velocity = (target - origin) / GRENADE_FLIGHT_TIME;
+        velocity.z += 0.5 * GRENADE_GRAVITY * GRENADE_FLIGHT_TIME;
+
+        let elapsed = (time - grenade.time_throw).clamp(0.0, GRENADE_FLIGHT_TIME);
+        let position = predict_grenade_position(origin, velocity, elapsed, target.z);
+        velocity.z -= GRENADE_GRAVITY * elapsed;
+        (position, velocity)
+    }
+
+    /// Grenades which exist at the round time.
+    /// Grenades of players who died before throwing them are never thrown.
+    fn active_grenades(&self, time: f32) -> impl Iterator<Item = &DemoGrenade> + '_ {
+        self.grenades.iter().filter(move |grenade| {
+            if time < grenade.time_throw
+                || !self.is_alive(&self.players[grenade.thrower], grenade.time_throw)
+            {
+                return false;
+            }
+
+            let duration = match grenade.kind {
+                GrenadeKind::Smoke => GRENADE_FLIGHT_TIME + SMOKE_EFFECT_DURATION,
+                _ => GRENADE_FLIGHT_TIME,
+            };
+            time < grenade.time_throw + duration
+        })
+    }
+
+    /// Remaining time (in seconds) the player is blinded by the flashbangs of the opposing team
+    fn flash_remaining(&self, index: usize, time: f32) -> f32 {
+        let player = &self.players[index];
+        self.grenades
+            .iter()
+            .filter(|grenade| grenade.kind == GrenadeKind::Flashbang)
+            .filter(|grenade| {
+                let thrower = &self.players[grenade.thrower];
+                thrower.team_id != player.team_id && self.is_alive(thrower, grenade.time_throw)
+            })
+            .filter_map(|grenade| {
+                let time_detonation = grenade.time_detonation();
+                if time < time_detonation || !self.is_alive(player, time_detonation) {
+                    return None;
+                }
+
+                let (position, _) = self.player_position(index, time_detonation);
+                let dx = position[0] - grenade.target[0];
+                let dy = position[1] - grenade.target[1];
+                let distance = (dx * dx + dy * dy).sqrt();
+                if distance >= FLASHBANG_RADIUS {
+                    return None;
+                }
+
+                let duration = FLASHBANG_MAX_DURATION * (1.0 - distance / FLASHBANG_RADIUS);
+                Some((time_detonation + duration - time).max(0.0))
+            })
+            .fold(0.0, f32::max)
+    }
+}
+
+/// Simulates a deterministic match without CS2 running: 10 players walking along scripted paths,
+/// a bomb plant followed by a defuse or detonation, grenades and round restarts.
+/// The same seed will always produce the same sequence of snapshots.
+pub struct DemoMatch {
+    random: DemoRandom,
+    round: DemoRound,
+
+    /// Round number starting with 1
+    round_number: i32,
+    round_time: f32,
+    server_time: f32,
+
+    score_terrorists: i32,
+    score_counter_terrorists: i32,
+}
+
+impl DemoMatch {
+    pub fn new(seed: u64) -> Self {
+        let mut random = DemoRandom::new(seed);
+        let round = DemoRound::generate(&mut random, 1);
+        Self {
+            random,
+            round,
+
+            round_number: 1,
+            round_time: 0.0,
+            server_time: 0.0,
+
+            score_terrorists: 0,
+            score_counter_terrorists: 0,
+        }
+    }
+
+    /// Advance the simulation by [DEMO_TICK_INTERVAL]
+    pub fn advance(&mut self) {
+        self.server_time += DEMO_TICK_INTERVAL;
+        self.round_time += DEMO_TICK_INTERVAL;
+        if self.round_time < self.round.time_end() + ROUND_RESTART_DELAY {
+            return;
+        }
+
+        match self.round.outcome {
+            DemoRoundOutcome::Defused => self.score_counter_terrorists += 1,
+            DemoRoundOutcome::Detonated => self.score_terrorists += 1,
+        }
+
+        self.round_number += 1;
+        self.round_time = 0.0;
+        self.round = DemoRound::generate(&mut self.random, self.round_number);
+    }
+
+    pub fn server_time(&self) -> f32 {
+        self.server_time
+    }
+
+    pub fn round_number(&self) -> i32 {
+        self.round_number
+    }
+
+    pub fn score_terrorists(&self) -> i32 {
+        self.score_terrorists
+    }
+
+    pub fn score_counter_terrorists(&self) -> i32 {
+        self.score_counter_terrorists
+    }
+
+    /// Controller of the spectating local player (the first terrorist)
+    pub fn local_controller(&self) -> ControllerIndex {
+        ControllerIndex(self.round.players[0].controller_entity_id)
+    }
+
+    fn player_index(&self, pawn: PawnIndex) -> Option<usize> {
+        self.round
+            .players
+            .iter()
+            .position(|player| player.pawn_entity_id == pawn.0)
+    }
+
+    /// The player has a defuse kit
+    pub fn has_defuser(&self, pawn: PawnIndex) -> bool {
+        self.player_index(pawn)
+            .map(|index| self.round.players[index].has_defuser)
+            .unwrap_or(false)
+    }
+
+    /// Remaining time (in seconds) the player is blinded by a flashbang
+    pub fn flash_remaining(&self, pawn: PawnIndex) -> f32 {
+        self.player_index(pawn)
+            .map(|index| self.round.flash_remaining(index, self.round_time))
+            .unwrap_or(0.0)
+    }
+
+    fn planted_c4(&self) -> Option<PlantedC4Entry> {
+        let round = &self.round;
+        let time = self.round_time;
+        if time < round.time_planted {
+            return None;
+        }
+
+        let round_start = self.server_time - time;
+        let plant_time = round_start + round.time_planted;
+        let time_blow = plant_time + BOMB_TIMER;
+        let defuse = round
+            .defuse
+            .filter(|(_, time_defuse)| time >= *time_defuse)
+            .map(|(defuser, time_defuse)| (defuser, time_defuse + BOMB_DEFUSE_DURATION_KIT));
+
+        let state = if time >= round.time_end() {
+            match round.outcome {
+                DemoRoundOutcome::Defused => PlantedC4State::Defused,
+                DemoRoundOutcome::Detonated => PlantedC4State::Detonated,
+            }
+        } else {
+            PlantedC4State::Active {
+                time_detonation: round.time_planted + BOMB_TIMER - time,
+            }
+        };
+
+        let defuser = match (state, defuse) {
+            (PlantedC4State::Active { time_detonation }, Some((defuser, time_defused))) => {
+                let player = &round.players[defuser];
+                let time_remaining = time_defused - time;
+                Some(BombDefuser {
+                    pawn_entity_id: PawnIndex(player.pawn_entity_id),
+                    time_remaining,
+                    defuse_duration_total: BOMB_DEFUSE_DURATION_KIT,
+                    has_kit: true,
+                    can_defuse_in_time: time_remaining <= time_detonation,
+                    player_name: player.name.clone(),
+                    health: 100,
+                    armor: 100,
+                    is_last_alive_ct: round
+                        .players
+                        .iter()
+                        .filter(|player| {
+                            player.team_id == TEAM_ID_COUNTER_TERRORIST
+                                && round.is_alive(player, time)
+                        })
+                        .count()
+                        <= 1,
+                    confidence: FieldConfidence::Fresh,
+                })
+            }
+            _ => None,
+        };
+
+        Some(PlantedC4Entry {
+            entity_index: EntityIndex(DEMO_PLANTED_C4_ENTITY_ID),
+            bomb_site: round.bomb_site,
+            state,
+            position: Vector3::from(BOMB_SITES[round.bomb_site as usize]),
+            defuser,
+            time_blow,
+            plant_time,
+            pre_planted: false,
+            detonation_deadline: None,
+            raw_fields: PlantedC4RawFields {
+                activated: matches!(state, PlantedC4State::Active { .. }),
+                time_blow,
+                being_defused: matches!(state, PlantedC4State::Active { .. }) && defuse.is_some(),
+                defused: matches!(state, PlantedC4State::Defused),
+                defuse_countdown: defuse
+                    .map(|(_, time_defused)| round_start + time_defused)
+                    .unwrap_or_default(),
+            },
+        })
+    }
+
+    /// Snapshot of the current simulation state
+    pub fn snapshot(&self) -> GameSnapshot {
+        let round = &self.round;
+        let time = self.round_time;
+
+        let players = round
+            .players
+            .iter()
+            .enumerate()
+            .map(|(index, player)| {
+                let alive = round.is_alive(player, time);
+                let (position, rotation) = round.player_position(index, time);
+                SnapshotPlayer {
+                    pawn_entity_id: PawnIndex(player.pawn_entity_id),
+                    controller_entity_id: Some(ControllerIndex(player.controller_entity_id)),
+                    team_id: player.team_id,
+
+                    alive,
+                    position: Vector3::from(position),
+
+                    details: Some(SnapshotPlayerDetails {
+                        player_name: Some(player.name.clone()),
+                        player_health: match player.time_death {
+                            Some(_) if !alive => 0,
+                            /* take some damage before dying */
+                            Some(time_death) if time_death - time < 3.0 => 27,
+                            _ => 100,
+                        },
+                        weapon: if player.team_id == TEAM_ID_TERRORIST {
+                            WeaponId::Ak47
+                        } else {
+                            WeaponId::M4A4
+                        },
+                        view_angles: ViewAngles::from_engine(0.0, rotation),
+                    }),
+                }
+            })
+            .collect();
+
+        let planted_c4 = self.planted_c4();
+        let bomb = match &planted_c4 {
+            Some(planted_c4) => BombState::Planted {
+                bomb_site: planted_c4.bomb_site,
+                position: planted_c4.position,
+            },
+            None => {
+                let carrier = &round.players[round.bomb_carrier];
+                BombState::Carried {
+                    carrier_entity_id: PawnIndex(carrier.pawn_entity_id),
+                    carrier_name: Some(carrier.name.clone()),
+                    carrier_team_id: carrier.team_id,
+                    position: Vector3::from(round.player_position(round.bomb_carrier, time).0),
+                }
+            }
+        };
+
+        let grenades = round
+            .active_grenades(time)
+            .map(|grenade| {
+                let owner_team_id = Some(round.players[grenade.thrower].team_id);
+                if time < grenade.time_detonation() {
+                    let (position, velocity) = round.grenade_trajectory(grenade, time);
+                    SnapshotGrenade {
+                        entity_id: EntityIndex(grenade.entity_id),
+                        kind: grenade.kind,
+                        position,
+                        velocity,
+                        owner_team_id,
+                        effect_time_remaining: None,
+                    }
+                } else {
+                    /* deployed smoke */
+                    SnapshotGrenade {
+                        entity_id: EntityIndex(grenade.entity_id),
+                        kind: grenade.kind,
+                        position: Vector3::from(grenade.target),
+                        velocity: Vector3::zeros(),
+                        owner_team_id,
+                        effect_time_remaining: Some(
+                            grenade.time_detonation() + SMOKE_EFFECT_DURATION - time,
+                        ),
+                    }
+                }
+            })
+            .collect();
+
+        GameSnapshot {
+            server_time: self.server_time,
+            captured_at: SystemTime::now(),
+            map: Some(DEMO_MAP.to_string()),
+
+            players,
+            bomb,
+            planted_c4,
+            grenades,
+
+            world_data_quality: DataQuality::Good,
+
+            detail: SnapshotDetail::Full,
+
+            #[cfg(feature = "serialize")]
+            custom: BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        DemoMatch,
+        DEMO_TICK_INTERVAL,
+    };
+    use crate::{
+        BombState,
+        GrenadeKind,
+        PlantedC4State,
+    };
+
+    #[test]
+    fn deterministic() {
+        let mut match_a = DemoMatch::new(1337);
+        let mut match_b = DemoMatch::new(1337);
+        for _ in 0..2000 {
+            match_a.advance();
+            match_b.advance();
+
+            let (snapshot_a, snapshot_b) = (match_a.snapshot(), match_b.snapshot());
+            assert_eq!(snapshot_a.server_time, snapshot_b.server_time);
+            assert_eq!(snapshot_a.players, snapshot_b.players);
+            assert_eq!(snapshot_a.grenades, snapshot_b.grenades);
+            assert_eq!(
+                format!("{:?}", snapshot_a.planted_c4),
+                format!("{:?}", snapshot_b.planted_c4)
+            );
+        }
+    }
+
+    #[test]
+    fn valid_snapshots() {
+        for seed in 0..8 {
+            let mut demo = DemoMatch::new(seed);
+            let mut plants = 0;
+            let mut was_planted = false;
+            let mut smokes = 0;
+            let mut flashed = false;
+
+            /* simulate 10 minutes */
+            for _ in 0..(600.0 / DEMO_TICK_INTERVAL) as usize {
+                demo.advance();
+
+                let snapshot = demo.snapshot();
+                if let Err(err) = snapshot.validate() {
+                    panic!("seed {} round {}: {}", seed, demo.round_number(), err);
+                }
+
+                let planted = matches!(snapshot.bomb, BombState::Planted { .. });
+                if planted && !was_planted {
+                    plants += 1;
+                }
+                was_planted = planted;
+
+                smokes += snapshot
+                    .grenades
+                    .iter()
+                    .filter(|grenade| {
+                        grenade.kind == GrenadeKind::Smoke
+                            && grenade.effect_time_remaining.is_some()
+                    })
+                    .count();
+                flashed |= snapshot
+                    .players
+                    .iter()
+                    .any(|player| demo.flash_remaining(player.pawn_entity_id) > 0.0);
+
+                if let Some(planted_c4) = &snapshot.planted_c4 {
+                    if planted_c4.defuser.is_some() {
+                        assert!(matches!(planted_c4.state, PlantedC4State::Active { .. }));
+                    }
+                }
+            }
+
+            assert!(demo.round_number() > 3, "seed {}", seed);
+            assert!(plants >= demo.round_number() - 1, "seed {}", seed);
+            assert_eq!(
+                demo.score_terrorists() + demo.score_counter_terrorists(),
+                demo.round_number() - 1
+            );
+            assert!(smokes > 0, "seed {}", seed);
+            assert!(flashed, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn grenade_arc() {
+        let mut demo = DemoMatch::new(7);
+        let mut launched = false;
+        for _ in 0..(120.0 / DEMO_TICK_INTERVAL) as usize {
+            demo.advance();
+            for grenade in demo.snapshot().grenades {
+                if grenade.effect_time_remaining.is_none() && grenade.velocity.z > 0.0 {
+                    launched = true;
+                }
+            }
+        }
+
+        assert!(launched);
+    }
+}
@@ -0,0 +1,489 @@
+//! Custom states derived by the consumer from the public state outputs.
+//!
+//! A [DerivedState] can only access the states marked as [PublicState] and the outputs of other derived states.
+//! It has no access to the process memory and therefore can not break the core states.
+//! Derived states will be evaluated after their dependencies and failing derived states are reported
+//! within [StateDerivedOutputs::errors] without affecting the other derived states.
+use std::{
+    any::Any,
+    cell::Ref,
+    collections::{
+        BTreeMap,
+        HashSet,
+    },
+    panic::{
+        self,
+        AssertUnwindSafe,
+    },
+    sync::{
+        Arc,
+        Mutex,
+        PoisonError,
+    },
+};
+
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    BombState,
+    MatchContext,
+    PlantedC4,
+    PlantedC4List,
+    StateCurrentMap,
+    StateGrenadeProjectiles,
+    StateMatchEvents,
+    StatePawnInfo,
+    StatePeekingPlayers,
+    StatePlayerList,
+    StateRound,
+    StateRoundHistory,
+    StateRoundInfo,
+};
+
+/// States which may be accessed by a [DerivedState].
+/// States with access to the process memory must not implement this trait.
+pub trait PublicState: State {}
+
+impl PublicState for StatePlayerList {}
+impl PublicState for StatePawnInfo {}
+impl PublicState for PlantedC4 {}
+impl PublicState for PlantedC4List {}
+impl PublicState for BombState {}
+impl PublicState for MatchContext {}
+impl PublicState for StateCurrentMap {}
+impl PublicState for StateRoundInfo {}
+impl PublicState for StateRound {}
+impl PublicState for StateMatchEvents {}
+impl PublicState for StateRoundHistory {}
+impl PublicState for StatePeekingPlayers {}
+impl PublicState for StateGrenadeProjectiles {}
+
+/// Inputs available to a [DerivedState]
+pub struct DerivedInputs<'a> {
+    states: &'a StateRegistry,
+    outputs: &'a BTreeMap<String, serde_json::Value>,
+}
+
+impl<'a> DerivedInputs<'a> {
+    pub fn resolve<T: PublicState>(&self, params: T::Parameter) -> anyhow::Result<Ref<'a, T>> {
+        self.states.resolve::<T>(params)
+    }
+
+    /// Output of a derived state listed within [DerivedState::dependencies]
+    pub fn output(&self, name: &str) -> Option<&'a serde_json::Value> {
+        self.outputs.get(name)
+    }
+}
+
+/// A custom analysis over the public state outputs which will be evaluated every frame
+pub trait DerivedState: Send + 'static {
+    /// Unique name of the derived state.
+    /// The output will be contained under this name in [StateDerivedOutputs::custom].
+    fn name(&self) -> &str;
+
+    /// Names of the derived states whose outputs are required
+    fn dependencies(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn derive(&mut self, inputs: &DerivedInputs) -> anyhow::Result<serde_json::Value>;
+}
+
+/// All registered derived states.
+///
+/// This state is shared (cloning yields the same registrations) so the registrations
+/// survive a [CS2Session](crate::CS2Session) being detached.
+#[derive(Clone, Default)]
+pub struct DerivedStates {
+    derived: Arc<Mutex<Vec<Box<dyn DerivedState>>>>,
+}
+
+impl State for DerivedStates {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self::default())
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+impl DerivedStates {
+    pub fn register(&self, derived: impl DerivedState) -> anyhow::Result<()> {
+        let mut registered = self.derived.lock().unwrap_or_else(PoisonError::into_inner);
+        if registered
+            .iter()
+            .any(|current| current.name() == derived.name())
+        {
+            anyhow::bail!("derived state {} already registered", derived.name());
+        }
+
+        registered.push(Box::new(derived));
+        Ok(())
+    }
+
+    /// Returns false if there is no derived state with that name
+    pub fn unregister(&self, name: &str) -> bool {
+        let mut registered = self.derived.lock().unwrap_or_else(PoisonError::into_inner);
+        let count = registered.len();
+        registered.retain(|derived| derived.name() != name);
+        registered.len() != count
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.derived
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|derived| derived.name().to_string())
+            .collect()
+    }
+
+    fn evaluate(&self, states: &StateRegistry) -> StateDerivedOutputs {
+        let mut registered = self.derived.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut outputs = StateDerivedOutputs::default();
+
+        let dependencies = registered
+            .iter()
+            .map(|derived| derived.dependencies())
+            .collect::<Vec<_>>();
+
+        /* evaluate in dependency order, a derived state becomes ready once all dependencies have been evaluated */
+        let mut evaluated = HashSet::new();
+        loop {
+            let ready = (0..registered.len()).find(|index| {
+                !evaluated.contains(index)
+                    && dependencies[*index].iter().all(|dependency| {
+                        outputs.custom.contains_key(dependency)
+                            || outputs.errors.contains_key(dependency)
+                    })
+            });
+            let Some(index) = ready else {
+                break;
+            };
+            evaluated.insert(index);
+
+            let derived = &mut registered[index];
+            let name = derived.name().to_string();
+            if let Some(dependency) = dependencies[index]
+                .iter()
+                .find(|dependency| outputs.errors.contains_key(*dependency))
+            {
+                outputs
+                    .errors
+                    .insert(name, format!("dependency {} failed", dependency));
+                continue;
+            }
+
+            let inputs = DerivedInputs {
+                states,
+                outputs: &outputs.custom,
+            };
+            let result = panic::catch_unwind(AssertUnwindSafe(|| derived.derive(&inputs)));
+            match result {
+                Ok(Ok(value)) => {
+                    outputs.custom.insert(name, value);
+                }
+                Ok(Err(error)) => {
+                    outputs.errors.insert(name, format!("{:#}", error));
+                }
+                Err(payload) => {
+                    outputs
+                        .errors
+                        .insert(name, format!("panicked: {}", panic_message(&*payload)));
+                }
+            }
+        }
+
+        let names = registered
+            .iter()
+            .map(|derived| derived.name())
+            .collect::<HashSet<_>>();
+        for (index, derived) in registered.iter().enumerate() {
+            if evaluated.contains(&index) {
+                continue;
+            }
+
+            let error = match dependencies[index]
+                .iter()
+                .find(|dependency| !names.contains(dependency.as_str()))
+            {
+                Some(dependency) => format!("unknown dependency {}", dependency),
+                None => "circular dependency".to_string(),
+            };
+            outputs.errors.insert(derived.name().to_string(), error);
+        }
+
+        outputs
+    }
+}
+
+/// Outputs of all registered derived states (see [DerivedStates::register])
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateDerivedOutputs {
+    /// Output by the name of the derived state
+    pub custom: BTreeMap<String, serde_json::Value>,
+
+    /// Error by the name of the derived state
+    pub errors: BTreeMap<String, String>,
+}
+
+impl StateDerivedOutputs {
+    /// Serialize the outputs as `{ "custom": { <name>: <output> } }`
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({ "custom": self.custom })
+    }
+}
+
+impl State for StateDerivedOutputs {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let derived = states.resolve::<DerivedStates>(())?.clone();
+        Ok(derived.evaluate(states))
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use utils_state::StateRegistry;
+
+    use super::{
+        DerivedInputs,
+        DerivedState,
+        DerivedStates,
+        StateDerivedOutputs,
+    };
+    use crate::{
+        PawnIndex,
+        PlayerInterest,
+        PlayerListEntry,
+        StatePlayerList,
+        TEAM_ID_COUNTER_TERRORIST,
+        TEAM_ID_TERRORIST,
+    };
+
+    /// Average distance between all alive players of a team
+    struct AverageTeamDistance;
+    impl DerivedState for AverageTeamDistance {
+        fn name(&self) -> &str {
+            "average_team_distance"
+        }
+
+        fn derive(&mut self, inputs: &DerivedInputs) -> anyhow::Result<serde_json::Value> {
+            let player_list = inputs.resolve::<StatePlayerList>(PlayerInterest::All)?;
+
+            let mut result = BTreeMap::new();
+            for (team_name, team_id) in [
+                ("terrorists", TEAM_ID_TERRORIST),
+                ("counter_terrorists", TEAM_ID_COUNTER_TERRORIST),
+            ] {
+                let positions = player_list
+                    .players
+                    .iter()
+                    .filter(|entry| entry.alive && entry.team_id == team_id)
+                    .map(|entry| entry.position)
+                    .collect::<Vec<_>>();
+
+                let mut distances = Vec::new();
+                for (index, position) in positions.iter().enumerate() {
+                    for other in positions.iter().skip(index + 1) {
+                        distances.push((position - other).norm());
+                    }
+                }
+
+                let average = if distances.is_empty() {
+                    None
+                } else {
+                    Some(distances.iter().sum::<f32>() / distances.len() as f32)
+                };
+                result.insert(team_name, average);
+            }
+
+            Ok(serde_json::to_value(result)?)
+        }
+    }
+
+    /// Difference between the team spreads (depends on [AverageTeamDistance])
+    struct SpreadDifference;
+    impl DerivedState for SpreadDifference {
+        fn name(&self) -> &str {
+            "spread_difference"
+        }
+
+        fn dependencies(&self) -> Vec<String> {
+            vec!["average_team_distance".to_string()]
+        }
+
+        fn derive(&mut self, inputs: &DerivedInputs) -> anyhow::Result<serde_json::Value> {
+            let distances = inputs
+                .output("average_team_distance")
+                .ok_or_else(|| anyhow::anyhow!("missing average team distance"))?;
+
+            let team = |name: &str| distances[name].as_f64().unwrap_or(0.0);
+            Ok(serde_json::json!(
+                team("terrorists") - team("counter_terrorists")
+            ))
+        }
+    }
+
+    struct Failing(&'static str, Vec<String>);
+    impl DerivedState for Failing {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn dependencies(&self) -> Vec<String> {
+            self.1.clone()
+        }
+
+        fn derive(&mut self, _inputs: &DerivedInputs) -> anyhow::Result<serde_json::Value> {
+            anyhow::bail!("analysis failed")
+        }
+    }
+
+    struct Panicking;
+    impl DerivedState for Panicking {
+        fn name(&self) -> &str {
+            "panicking"
+        }
+
+        fn derive(&mut self, _inputs: &DerivedInputs) -> anyhow::Result<serde_json::Value> {
+            panic!("bug in the derived state")
+        }
+    }
+
+    fn player(pawn: u32, team_id: u8, alive: bool, x: f32) -> PlayerListEntry {
+        PlayerListEntry {
+            pawn_entity_id: PawnIndex(pawn),
+            controller_entity_id: None,
+            team_id,
+            alive,
+            position: nalgebra::Vector3::new(x, 0.0, 0.0),
+            details: None,
+            details_confidence: None,
+        }
+    }
+
+    fn states() -> StateRegistry {
+        let mut states = StateRegistry::new(0x20);
+        states
+            .set(
+                StatePlayerList::from_entries(vec![
+                    player(1, TEAM_ID_TERRORIST, true, 0.0),
+                    player(2, TEAM_ID_TERRORIST, true, 300.0),
+                    player(3, TEAM_ID_TERRORIST, false, 5000.0),
+                    player(4, TEAM_ID_COUNTER_TERRORIST, true, 100.0),
+                    player(5, TEAM_ID_COUNTER_TERRORIST, true, 200.0),
+                    player(6, TEAM_ID_COUNTER_TERRORIST, true, 400.0),
+                ]),
+                PlayerInterest::All,
+            )
+            .unwrap();
+        states
+    }
+
+    #[test]
+    fn dependency_order() {
+        let states = states();
+        let derived = states.resolve::<DerivedStates>(()).unwrap().clone();
+
+        /* registered before its dependency */
+        derived.register(SpreadDifference).unwrap();
+        derived.register(AverageTeamDistance).unwrap();
+        assert!(derived.register(AverageTeamDistance).is_err());
+
+        let outputs = states.resolve::<StateDerivedOutputs>(()).unwrap();
+        assert!(outputs.errors.is_empty(), "{:?}", outputs.errors);
+        assert_eq!(
+            outputs.to_json(),
+            serde_json::json!({
+                "custom": {
+                    "average_team_distance": {
+                        "terrorists": 300.0,
+                        "counter_terrorists": 200.0,
+                    },
+                    "spread_difference": 100.0,
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn error_isolation() {
+        let states = states();
+        let derived = states.resolve::<DerivedStates>(()).unwrap().clone();
+        derived.register(Panicking).unwrap();
+        derived.register(Failing("failing", vec![])).unwrap();
+        derived
+            .register(Failing("dependent", vec!["failing".to_string()]))
+            .unwrap();
+        derived
+            .register(Failing("unknown", vec!["missing".to_string()]))
+            .unwrap();
+        derived
+            .register(Failing("cycle_a", vec!["cycle_b".to_string()]))
+            .unwrap();
+        derived
+            .register(Failing("cycle_b", vec!["cycle_a".to_string()]))
+            .unwrap();
+        derived.register(AverageTeamDistance).unwrap();
+
+        let outputs = states.resolve::<StateDerivedOutputs>(()).unwrap();
+        assert_eq!(
+            outputs.custom.keys().collect::<Vec<_>>(),
+            vec!["average_team_distance"]
+        );
+        assert_eq!(
+            outputs
+                .errors
+                .iter()
+                .map(|(name, error)| (name.as_str(), error.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("cycle_a", "circular dependency"),
+                ("cycle_b", "circular dependency"),
+                ("dependent", "dependency failing failed"),
+                ("failing", "analysis failed"),
+                ("panicking", "panicked: bug in the derived state"),
+                ("unknown", "unknown dependency missing"),
+            ]
+        );
+
+        /* the core states remain available */
+        assert_eq!(
+            states
+                .resolve::<StatePlayerList>(PlayerInterest::All)
+                .unwrap()
+                .players
+                .len(),
+            6
+        );
+
+        assert!(derived.unregister("panicking"));
+        assert!(!derived.unregister("panicking"));
+        assert_eq!(derived.names().len(), 6);
+    }
+}
@@ -0,0 +1,166 @@
+use std::io::{
+    self,
+    Write,
+};
+
+/// CRC-32 (IEEE) as used by the zip format
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFFFFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+struct ArchiveEntry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Minimal zip writer storing all files without compression.
+/// The bundles only contain a few small text files, hence compression is not worth a dependency.
+pub struct ZipWriter<W: Write> {
+    output: W,
+    offset: u32,
+    entries: Vec<ArchiveEntry>,
+}
+
+impl<W: Write> ZipWriter<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            output,
+            offset: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    fn write_bytes(&mut self, data: &[u8]) -> io::Result<()> {
+        self.output.write_all(data)?;
+        self.offset = u32::try_from(self.offset as usize + data.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "archive exceeds 4 GiB"))?;
+        Ok(())
+    }
+
+    pub fn add_file(&mut self, name: &str, data: &[u8]) -> io::Result<()> {
+        let size = u32::try_from(data.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "file exceeds 4 GiB"))?;
+        let entry = ArchiveEntry {
+            name: name.to_string(),
+            crc: crc32(data),
+            size,
+            offset: self.offset,
+        };
+
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend_from_slice(&0x04034B50u32.to_le_bytes());
+        header.extend_from_slice(&20u16.to_le_bytes()); /* version needed */
+        header.extend_from_slice(&0x0800u16.to_le_bytes()); /* flags: utf-8 names */
+        header.extend_from_slice(&0u16.to_le_bytes()); /* method: stored */
+        header.extend_from_slice(&0u32.to_le_bytes()); /* modification time & date */
+        header.extend_from_slice(&entry.crc.to_le_bytes());
+        header.extend_from_slice(&entry.size.to_le_bytes());
+        header.extend_from_slice(&entry.size.to_le_bytes());
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); /* extra field length */
+        header.extend_from_slice(name.as_bytes());
+
+        self.write_bytes(&header)?;
+        self.write_bytes(data)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Write the central directory and return the underlying output
+    pub fn finish(mut self) -> io::Result<W> {
+        let directory_offset = self.offset;
+        let entries = std::mem::take(&mut self.entries);
+        for entry in entries.iter() {
+            let mut header = Vec::with_capacity(46 + entry.name.len());
+            header.extend_from_slice(&0x02014B50u32.to_le_bytes());
+            header.extend_from_slice(&20u16.to_le_bytes()); /* version made by */
+            header.extend_from_slice(&20u16.to_le_bytes()); /* version needed */
+            header.extend_from_slice(&0x0800u16.to_le_bytes());
+            header.extend_from_slice(&0u16.to_le_bytes());
+            header.extend_from_slice(&0u32.to_le_bytes());
+            header.extend_from_slice(&entry.crc.to_le_bytes());
+            header.extend_from_slice(&entry.size.to_le_bytes());
+            header.extend_from_slice(&entry.size.to_le_bytes());
+            header.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            header.extend_from_slice(&[0u8; 12]); /* extra, comment, disk, attributes */
+            header.extend_from_slice(&entry.offset.to_le_bytes());
+            header.extend_from_slice(entry.name.as_bytes());
+            self.write_bytes(&header)?;
+        }
+
+        let directory_size = self.offset - directory_offset;
+        let mut footer = Vec::with_capacity(22);
+        footer.extend_from_slice(&0x06054B50u32.to_le_bytes());
+        footer.extend_from_slice(&[0u8; 4]); /* disk numbers */
+        footer.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        footer.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        footer.extend_from_slice(&directory_size.to_le_bytes());
+        footer.extend_from_slice(&directory_offset.to_le_bytes());
+        footer.extend_from_slice(&0u16.to_le_bytes()); /* comment length */
+        self.write_bytes(&footer)?;
+
+        Ok(self.output)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        crc32,
+        ZipWriter,
+    };
+
+    fn u32_at(data: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn checksum() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+    }
+
+    #[test]
+    fn archive_layout() {
+        let mut writer = ZipWriter::new(Vec::new());
+        writer.add_file("build_info.txt", b"revision: 1").unwrap();
+        writer.add_file("frames.txt", b"").unwrap();
+        let archive = writer.finish().unwrap();
+
+        assert_eq!(u32_at(&archive, 0), 0x04034B50);
+        assert_eq!(&archive[30..44], b"build_info.txt");
+        assert_eq!(&archive[44..55], b"revision: 1");
+
+        let footer = archive.len() - 22;
+        assert_eq!(u32_at(&archive, footer), 0x06054B50);
+        assert_eq!(
+            u16::from_le_bytes([archive[footer + 10], archive[footer + 11]]),
+            2
+        );
+
+        let directory_offset = u32_at(&archive, footer + 16) as usize;
+        assert_eq!(u32_at(&archive, directory_offset), 0x02014B50);
+        assert_eq!(
+            u32_at(&archive, directory_offset + 16),
+            crc32(b"revision: 1")
+        );
+        assert_eq!(
+            directory_offset + u32_at(&archive, footer + 12) as usize,
+            footer
+        );
+    }
+}
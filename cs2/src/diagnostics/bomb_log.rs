@@ -0,0 +1,254 @@
+use std::collections::VecDeque;
+
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    PlantedC4,
+    PlantedC4RawFields,
+    PlantedC4State,
+    StateGlobals,
+};
+
+/// Max amount of transitions kept by the [StateBombTransitionLog]
+pub const BOMB_TRANSITION_LOG_CAPACITY: usize = 64;
+
+/// Phase of the planted C4 (see [PlantedC4State]).
+/// In contrast to the [PlantedC4State] an active bomb is split into being defused or not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BombPhase {
+    NotPlanted,
+    Active,
+    Defusing,
+    Defused,
+    Detonated,
+}
+
+impl BombPhase {
+    /// Returns None if the bomb state is unavailable
+    pub fn from_planted_c4(planted_c4: &PlantedC4) -> Option<Self> {
+        if planted_c4.unavailable {
+            return None;
+        }
+
+        Some(match planted_c4.state {
+            PlantedC4State::NotPlanted => Self::NotPlanted,
+            PlantedC4State::Active { .. } if planted_c4.defuser.is_some() => Self::Defusing,
+            PlantedC4State::Active { .. } => Self::Active,
+            PlantedC4State::Defused => Self::Defused,
+            PlantedC4State::Detonated => Self::Detonated,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BombTransition {
+    /// Frame of the state registry (see [utils_state::StateHeartbeat::frame])
+    pub frame: u64,
+    pub server_time: Option<f32>,
+
+    pub from: BombPhase,
+    pub to: BombPhase,
+
+    /// Values of the C4 entity in the frame of the transition
+    pub fields: Option<PlantedC4RawFields>,
+}
+
+/// Ring buffer of bomb phase transitions with a bounded capacity
+#[derive(Debug, Clone)]
+pub struct BombTransitionLog {
+    capacity: usize,
+    phase: BombPhase,
+    transitions: VecDeque<BombTransition>,
+}
+
+impl BombTransitionLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            phase: BombPhase::NotPlanted,
+            transitions: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Current phase of the bomb
+    pub fn phase(&self) -> BombPhase {
+        self.phase
+    }
+
+    /// Observe the phase of a frame.
+    /// Returns the recorded transition if the phase changed.
+    pub fn push(
+        &mut self,
+        frame: u64,
+        server_time: Option<f32>,
+        phase: BombPhase,
+        fields: Option<PlantedC4RawFields>,
+    ) -> Option<&BombTransition> {
+        if phase == self.phase {
+            return None;
+        }
+
+        let transition = BombTransition {
+            frame,
+            server_time,
+            from: self.phase,
+            to: phase,
+            fields,
+        };
+        self.phase = phase;
+
+        if self.capacity == 0 {
+            return None;
+        }
+
+        while self.transitions.len() >= self.capacity {
+            self.transitions.pop_front();
+        }
+        self.transitions.push_back(transition);
+        self.transitions.back()
+    }
+
+    /// All recorded transitions, oldest first
+    pub fn transitions(&self) -> impl Iterator<Item = &BombTransition> {
+        self.transitions.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transitions.is_empty()
+    }
+}
+
+/// Records the transitions of the [PlantedC4] within the last [BOMB_TRANSITION_LOG_CAPACITY] phase changes.
+/// Transitions will only be recorded while this state is being resolved every frame.
+pub struct StateBombTransitionLog {
+    pub log: BombTransitionLog,
+}
+
+impl State for StateBombTransitionLog {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            log: BombTransitionLog::new(BOMB_TRANSITION_LOG_CAPACITY),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        let planted_c4 = states.resolve::<PlantedC4>(())?;
+        let Some(phase) = BombPhase::from_planted_c4(&planted_c4) else {
+            return Ok(());
+        };
+
+        let server_time = states
+            .resolve::<StateGlobals>(())
+            .ok()
+            .and_then(|globals| globals.server_time().ok());
+
+        if let Some(transition) = self.log.push(
+            states.heartbeat().frame(),
+            server_time,
+            phase,
+            planted_c4.raw_fields,
+        ) {
+            log::debug!("Bomb transition: {:?}", transition);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        BombPhase,
+        BombTransitionLog,
+    };
+    use crate::PlantedC4RawFields;
+
+    fn fields(being_defused: bool, defused: bool) -> Option<PlantedC4RawFields> {
+        Some(PlantedC4RawFields {
+            activated: true,
+            time_blow: 140.0,
+            being_defused,
+            defused,
+            defuse_countdown: if being_defused { 105.0 } else { 0.0 },
+        })
+    }
+
+    /// Scripted round: plant, a defuse attempt flickering for a single frame, a second attempt and a successful defuse
+    #[test]
+    fn scripted_defuse() {
+        let frames = [
+            (BombPhase::NotPlanted, None),
+            (BombPhase::NotPlanted, None),
+            (BombPhase::Active, fields(false, false)),
+            (BombPhase::Active, fields(false, false)),
+            (BombPhase::Defusing, fields(true, false)),
+            (BombPhase::Active, fields(false, false)),
+            (BombPhase::Defusing, fields(true, false)),
+            (BombPhase::Defusing, fields(true, false)),
+            (BombPhase::Defused, fields(false, true)),
+            (BombPhase::Defused, fields(false, true)),
+            (BombPhase::NotPlanted, None),
+        ];
+
+        let mut log = BombTransitionLog::new(16);
+        for (frame, (phase, fields)) in frames.into_iter().enumerate() {
+            log.push(frame as u64, Some(100.0 + frame as f32), phase, fields);
+        }
+
+        assert_eq!(
+            log.transitions()
+                .map(|transition| (transition.frame, transition.from, transition.to))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, BombPhase::NotPlanted, BombPhase::Active),
+                (4, BombPhase::Active, BombPhase::Defusing),
+                (5, BombPhase::Defusing, BombPhase::Active),
+                (6, BombPhase::Active, BombPhase::Defusing),
+                (8, BombPhase::Defusing, BombPhase::Defused),
+                (10, BombPhase::Defused, BombPhase::NotPlanted),
+            ]
+        );
+
+        let defused = log.transitions().nth(4).unwrap();
+        assert_eq!(defused.server_time, Some(108.0));
+        assert_eq!(defused.fields, fields(false, true));
+        assert_eq!(log.phase(), BombPhase::NotPlanted);
+    }
+
+    #[test]
+    fn bounded() {
+        let mut log = BombTransitionLog::new(4);
+        for frame in 0..20 {
+            let phase = if frame % 2 == 0 {
+                BombPhase::Active
+            } else {
+                BombPhase::NotPlanted
+            };
+            assert!(log.push(frame, None, phase, None).is_some());
+        }
+
+        assert_eq!(log.len(), 4);
+        assert_eq!(
+            log.transitions().next().map(|transition| transition.frame),
+            Some(16)
+        );
+
+        let mut disabled = BombTransitionLog::new(0);
+        assert!(disabled.push(0, None, BombPhase::Active, None).is_none());
+        assert!(disabled.is_empty());
+        assert_eq!(disabled.phase(), BombPhase::Active);
+    }
+}
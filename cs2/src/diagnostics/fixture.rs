@@ -0,0 +1,446 @@
+use std::{
+    collections::{
+        BTreeMap,
+        HashMap,
+    },
+    error::Error,
+    sync::{
+        Arc,
+        Mutex,
+        PoisonError,
+    },
+};
+
+use raw_struct::MemoryView;
+
+use super::NameRedactor;
+
+/// Process memory reads recorded while resolving the states (e.g. for bug report fixtures).
+/// The fixture itself is a memory view and can be used to replay the recorded states offline.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryFixture {
+    /// Recorded reads by their address
+    reads: BTreeMap<u64, Vec<u8>>,
+}
+
+/// Amount of values replaced by [MemoryFixture::redact]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FixtureRedaction {
+    pub names: usize,
+    pub steam_ids: usize,
+}
+
+/// Characters of pseudonyms which neither fit as `Player N`, `PN` nor as plain number
+const PSEUDONYM_LETTERS: &[u8; 52] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Pseudonym (see [NameRedactor::redact]) with exactly `length` bytes so the redacted string has the same length as the original.
+///
+/// The pseudonym is used as it is if it fits and padded with nul bytes, hence the replayed name equals the
+/// pseudonym used within the snapshots. Otherwise it is shortened to `P3`, `3` or finally to the player
+/// number encoded with letters only. Each form is distinguishable from the others, hence pseudonyms of different
+/// players never collide as long as the player number can be encoded within `length` letters
+/// (52 players for single character names).
+fn fit_pseudonym(pseudonym: &str, length: usize) -> Vec<u8> {
+    let number = pseudonym.trim_start_matches(|c: char| !c.is_ascii_digit());
+
+    let mut value = if pseudonym.len() <= length {
+        pseudonym.as_bytes().to_vec()
+    } else if number.len() < length {
+        format!("P{}", number).into_bytes()
+    } else if number.len() == length {
+        number.as_bytes().to_vec()
+    } else {
+        let mut number = number.parse::<usize>().unwrap_or_default();
+        let mut value = vec![0u8; length];
+        for letter in value.iter_mut().rev() {
+            *letter = PSEUDONYM_LETTERS[number % PSEUDONYM_LETTERS.len()];
+            number /= PSEUDONYM_LETTERS.len();
+        }
+        value
+    };
+
+    value.resize(length, 0);
+    value
+}
+
+/// Replace all nul terminated strings which equal one of the `names` with their replacement (of the same length).
+/// Strings start at the beginning of the read or after a nul byte, hence names contained within other names
+/// (e.g. `Bob` within `JimBob`) will not be replaced. The name buffer of prefetched player controllers is
+/// preceded by the zero high byte of `m_iConnected`.
+/// Returns the amount of replacements.
+fn replace_terminated(memory: &mut [u8], names: &HashMap<&[u8], Vec<u8>>) -> usize {
+    let mut count = 0;
+    let mut start = 0;
+    while let Some(length) = memory[start..].iter().position(|value| *value == 0) {
+        let end = start + length;
+        if let Some(replacement) = names.get(&memory[start..end]) {
+            debug_assert_eq!(replacement.len(), length);
+            memory[start..end].copy_from_slice(replacement);
+            count += 1;
+        }
+
+        start = end + 1;
+    }
+
+    count
+}
+
+/// Replace all occurrences of `pattern` with `replacement` (of the same length).
+/// Returns the amount of replacements.
+fn replace_all(memory: &mut [u8], pattern: &[u8], replacement: &[u8]) -> usize {
+    debug_assert_eq!(pattern.len(), replacement.len());
+
+    let mut count = 0;
+    let mut offset = 0;
+    while offset + pattern.len() <= memory.len() {
+        let end = offset + pattern.len();
+        if &memory[offset..end] == pattern {
+            memory[offset..end].copy_from_slice(replacement);
+            count += 1;
+            offset = end;
+        } else {
+            offset += 1;
+        }
+    }
+
+    count
+}
+
+impl MemoryFixture {
+    /// Record a read. Repeated reads of the same address keep the longest read.
+    pub fn record(&mut self, address: u64, memory: &[u8]) {
+        let entry = self.reads.entry(address).or_default();
+        if entry.len() <= memory.len() {
+            *entry = memory.to_vec();
+        }
+    }
+
+    /// Recorded reads ordered by their address
+    pub fn reads(&self) -> impl Iterator<Item = (u64, &[u8])> {
+        self.reads
+            .iter()
+            .map(|(address, memory)| (*address, memory.as_slice()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.reads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reads.is_empty()
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.reads.values().map(Vec::len).sum()
+    }
+
+    fn read(&self, address: u64, buffer: &mut [u8]) -> bool {
+        for (start, memory) in self.reads.range(..=address).rev() {
+            let begin = (address - start) as usize;
+            if let Some(source) = memory.get(begin..begin + buffer.len()) {
+                buffer.copy_from_slice(source);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Rewrite the name buffers and steam ids of the players within all recorded reads.
+    ///
+    /// Names are replaced by the pseudonyms of the redactor (see [NameRedactor::redact]) fitted to
+    /// the length of the original name (see [fit_pseudonym]) and steam ids by their redacted steam id
+    /// (see [NameRedactor::redact_steam_id]). Only whole names will be replaced.
+    /// Pseudonyms are assigned in the order of `player_names`.
+    /// The reads keep their addresses and lengths and the names stay nul terminated,
+    /// hence the fixture replays the same code paths as the original fixture.
+    pub fn redact(
+        &mut self,
+        redactor: &mut NameRedactor,
+        player_names: &[&str],
+        steam_ids: &[u64],
+    ) -> FixtureRedaction {
+        let mut names = HashMap::with_capacity(player_names.len());
+        for name in player_names.iter().filter(|name| !name.is_empty()) {
+            if !names.contains_key(name.as_bytes()) {
+                let pseudonym = fit_pseudonym(&redactor.redact(name), name.len());
+                names.insert(name.as_bytes(), pseudonym);
+            }
+        }
+
+        let steam_ids = steam_ids
+            .iter()
+            .filter(|steam_id| **steam_id != 0)
+            .map(|steam_id| {
+                (
+                    steam_id.to_le_bytes(),
+                    redactor.redact_steam_id(*steam_id).to_le_bytes(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut result = FixtureRedaction::default();
+        for memory in self.reads.values_mut() {
+            result.names += replace_terminated(memory, &names);
+
+            for (steam_id, redacted) in steam_ids.iter() {
+                result.steam_ids += replace_all(memory, steam_id, redacted);
+            }
+        }
+
+        result
+    }
+}
+
+impl MemoryView for MemoryFixture {
+    fn read_memory(
+        &self,
+        offset: u64,
+        buffer: &mut [u8],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.read(offset, buffer) {
+            Ok(())
+        } else {
+            Err(format!(
+                "read of {} bytes at {:X} has not been recorded",
+                buffer.len(),
+                offset
+            )
+            .into())
+        }
+    }
+}
+
+/// Memory view recording all successful reads of the underlying view into a [MemoryFixture]
+pub struct RecordingMemoryView {
+    inner: Arc<dyn MemoryView + Send + Sync>,
+    fixture: Mutex<MemoryFixture>,
+}
+
+impl RecordingMemoryView {
+    pub fn new(inner: Arc<dyn MemoryView + Send + Sync>) -> Self {
+        Self {
+            inner,
+            fixture: Default::default(),
+        }
+    }
+
+    /// Take all reads recorded so far
+    pub fn take_fixture(&self) -> MemoryFixture {
+        std::mem::take(&mut *self.fixture.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl MemoryView for RecordingMemoryView {
+    fn read_memory(
+        &self,
+        offset: u64,
+        buffer: &mut [u8],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.inner.read_memory(offset, buffer)?;
+        self.fixture
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .record(offset, buffer);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::HashSet,
+        sync::Arc,
+    };
+
+    use raw_struct::MemoryView;
+
+    use super::{
+        fit_pseudonym,
+        FixtureRedaction,
+        MemoryFixture,
+        RecordingMemoryView,
+    };
+    use crate::{
+        diagnostics::NameRedactor,
+        test_fixture::{
+            match_fixture,
+            match_steam_id,
+            setup_dump_schema,
+            EntityFixture,
+            MATCH_PLAYER_NAMES,
+        },
+        PlayerInterest,
+        PrefetchMemoryView,
+        StateFramePrefetch,
+        StatePlayerList,
+        StatePrefetchView,
+    };
+
+    #[test]
+    fn pseudonym_length() {
+        assert_eq!(fit_pseudonym("Player 1", 8), b"Player 1");
+        assert_eq!(fit_pseudonym("Player 1", 10), b"Player 1\0\0");
+        assert_eq!(fit_pseudonym("Player 12", 4), b"P12\0");
+        assert_eq!(fit_pseudonym("Player 12", 2), b"12");
+        assert_eq!(fit_pseudonym("Player 12", 1), b"M");
+
+        /* the replayed names of different players never collide */
+        for length in 1..=12 {
+            let pseudonyms = (1..=52)
+                .map(|player| {
+                    let mut pseudonym = fit_pseudonym(&format!("Player {}", player), length);
+                    assert_eq!(pseudonym.len(), length);
+                    pseudonym.retain(|value| *value != 0);
+                    pseudonym
+                })
+                .collect::<HashSet<_>>();
+            assert_eq!(pseudonyms.len(), 52, "{}", length);
+        }
+    }
+
+    #[test]
+    fn redact_contained_names() {
+        let mut fixture = MemoryFixture::default();
+        fixture.record(0x1000, b"Bob\0JimBob\0xBob\0Bob");
+        fixture.record(0x2000, b"\0Bob\0bob\0");
+
+        let mut redactor = NameRedactor::default();
+        assert_eq!(
+            fixture.redact(&mut redactor, &["JimBob", "Bob", "JimBob", ""], &[0]),
+            FixtureRedaction {
+                names: 3,
+                steam_ids: 0
+            }
+        );
+
+        /* only whole nul terminated names are replaced */
+        assert_eq!(
+            fixture.reads().collect::<Vec<_>>(),
+            vec![
+                (0x1000, &b"P2\0\0P1\0\0\0\0\0xBob\0Bob"[..]),
+                (0x2000, &b"\0P2\0\0bob\0"[..]),
+            ]
+        );
+
+        /* reads which have not been recorded fail */
+        let mut buffer = [0u8; 4];
+        assert!(fixture.read_memory(0x3000, &mut buffer).is_err());
+    }
+
+    const PLAYERS: usize = 4;
+
+    /// Player names of the player list ordered by the pawn entity id
+    fn resolve_player_names(
+        fixture: &EntityFixture,
+        memory: Arc<dyn MemoryView + Send + Sync>,
+        prefetch: bool,
+    ) -> Vec<Option<String>> {
+        let states = if prefetch {
+            let view = Arc::new(PrefetchMemoryView::new(memory));
+            let mut states = fixture.clone().states(view.clone());
+            states.set(StatePrefetchView::new(view), ()).unwrap();
+            states.resolve::<StateFramePrefetch>(()).unwrap();
+            states
+        } else {
+            fixture.clone().states(memory)
+        };
+
+        let player_list = states
+            .resolve::<StatePlayerList>(PlayerInterest::All)
+            .unwrap();
+        let mut players = player_list
+            .players
+            .iter()
+            .map(|entry| {
+                (
+                    entry.pawn_entity_id,
+                    entry
+                        .details
+                        .as_ref()
+                        .and_then(|details| details.player_name.clone()),
+                )
+            })
+            .collect::<Vec<_>>();
+        players.sort_by_key(|(pawn, _)| *pawn);
+        players.into_iter().map(|(_, name)| name).collect()
+    }
+
+    /// Record the player list of a match, redact the recording and replay the player list from it
+    #[test]
+    fn replay_redacted_player_list() {
+        setup_dump_schema();
+
+        let fixture = match_fixture(PLAYERS);
+        let player_names = &MATCH_PLAYER_NAMES[..PLAYERS];
+        let steam_ids = (0..PLAYERS).map(match_steam_id).collect::<Vec<_>>();
+
+        /* a prefetched frame reads the whole player controllers including their steam ids */
+        for prefetch in [false, true] {
+            let recorder = Arc::new(RecordingMemoryView::new(Arc::new(fixture.memory.clone())));
+            assert_eq!(
+                resolve_player_names(&fixture, recorder.clone(), prefetch),
+                player_names
+                    .iter()
+                    .map(|name| Some(name.to_string()))
+                    .collect::<Vec<_>>()
+            );
+            let recording = recorder.take_fixture();
+
+            let mut redacted = recording.clone();
+            let mut redactor = NameRedactor::default();
+            assert_eq!(
+                redacted.redact(&mut redactor, player_names, &steam_ids),
+                FixtureRedaction {
+                    names: PLAYERS,
+                    steam_ids: if prefetch { PLAYERS } else { 0 },
+                }
+            );
+
+            /* the same reads are recorded without any of the real names or steam ids */
+            assert_eq!(
+                redacted
+                    .reads()
+                    .map(|(address, memory)| (address, memory.len()))
+                    .collect::<Vec<_>>(),
+                recording
+                    .reads()
+                    .map(|(address, memory)| (address, memory.len()))
+                    .collect::<Vec<_>>()
+            );
+            for (address, memory) in redacted.reads() {
+                for name in player_names {
+                    assert!(
+                        !memory
+                            .windows(name.len())
+                            .any(|window| window == name.as_bytes()),
+                        "{} at {:X}",
+                        name,
+                        address
+                    );
+                }
+                for steam_id in steam_ids.iter() {
+                    assert!(!memory
+                        .windows(8)
+                        .any(|window| window == steam_id.to_le_bytes()));
+                }
+            }
+
+            /* names which fit keep the pseudonym of the snapshots */
+            let replayed = resolve_player_names(&fixture, Arc::new(redacted.clone()), prefetch);
+            assert_eq!(
+                replayed,
+                ["Player 1", "Player 2", "P3", "P4"]
+                    .map(|name| Some(name.to_string()))
+                    .to_vec()
+            );
+            assert_eq!(replayed[0], Some(redactor.redact(player_names[0])));
+
+            /* pseudonyms are stable across multiple fixtures of the same recording */
+            let mut second = recording.clone();
+            let reversed_names = player_names.iter().rev().copied().collect::<Vec<_>>();
+            second.redact(&mut redactor, &reversed_names, &steam_ids);
+            assert_eq!(second, redacted);
+        }
+    }
+}
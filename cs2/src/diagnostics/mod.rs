@@ -0,0 +1,295 @@
+//! Diagnostics for bug reports
+use std::{
+    fmt::Write,
+    fs,
+    path::PathBuf,
+    time::{
+        SystemTime,
+        UNIX_EPOCH,
+    },
+};
+
+use anyhow::Context;
+use utils_state::StateRegistry;
+
+use crate::{
+    CS2Offset,
+    StateBuildInfo,
+    StateEntityClassRegistry,
+    StateOffsetValidation,
+    StatePredefinedOffset,
+    StateResolvedOffset,
+};
+
+mod archive;
+pub use archive::*;
+
+#[cfg(feature = "bomb-transition-log")]
+mod bomb_log;
+#[cfg(feature = "bomb-transition-log")]
+pub use bomb_log::*;
+
+mod fixture;
+pub use fixture::*;
+
+mod recorder;
+pub use recorder::*;
+
+mod redact;
+pub use redact::*;
+
+mod schema_diff;
+pub use schema_diff::*;
+
+pub struct BundleOptions {
+    /// Directory where the bundle will be created
+    pub output_directory: PathBuf,
+
+    /// Replace all player names by pseudonyms and remove steam ids
+    pub redact_player_names: bool,
+
+    /// Amount of recorded frames to include (at most [FRAME_RECORDER_CAPACITY])
+    pub frames: usize,
+
+    /// Config of the application.
+    /// Credentials will be removed before it's included.
+    pub config: Option<serde_json::Value>,
+}
+
+impl Default for BundleOptions {
+    fn default() -> Self {
+        Self {
+            output_directory: PathBuf::from("."),
+            redact_player_names: true,
+            frames: FRAME_RECORDER_CAPACITY,
+            config: None,
+        }
+    }
+}
+
+fn offset_report(states: &StateRegistry) -> String {
+    let mut report = String::new();
+    match states.degraded_reason() {
+        Some(reason) => writeln!(report, "degraded: {}", reason),
+        None => writeln!(report, "degraded: no"),
+    }
+    .unwrap();
+
+    if let Some(validation) = states.get::<StateOffsetValidation>(()) {
+        writeln!(
+            report,
+            "expected revision: {}",
+            validation.expected_revision.as_deref().unwrap_or("any")
+        )
+        .unwrap();
+    }
+
+    /* only available if the schema has already been compared (e.g. by a failed validation) */
+    if let Some(schema_diff) = states.get::<StateSchemaDiff>(()) {
+        match &schema_diff.report {
+            Ok(diff) => writeln!(
+                report,
+                "schema: {} changed classes, {} broken fields\n{}",
+                diff.changed_classes().count(),
+                diff.broken_fields().count(),
+                diff
+            ),
+            Err(error) => writeln!(report, "schema: {}", error),
+        }
+        .unwrap();
+    }
+
+    for offset in CS2Offset::available_offsets() {
+        let (module, _) = offset.signature();
+        let source = if states.get::<StatePredefinedOffset>(*offset).is_some() {
+            "predefined"
+        } else {
+            "signature"
+        };
+
+        match states.resolve::<StateResolvedOffset>(*offset) {
+            Ok(resolved) => writeln!(
+                report,
+                "{:<32} {:?}+{:X} ({})",
+                offset.cache_name(),
+                module,
+                resolved.offset,
+                source
+            ),
+            Err(error) => writeln!(
+                report,
+                "{:<32} error ({}): {:#}",
+                offset.cache_name(),
+                source,
+                error
+            ),
+        }
+        .unwrap();
+    }
+
+    report
+}
+
+fn entity_class_report(states: &StateRegistry) -> anyhow::Result<String> {
+    let registry = states.resolve::<StateEntityClassRegistry>(())?;
+
+    let mut report = String::new();
+    writeln!(
+        report,
+        "{} entity classes, {} unresolved entities, overflowed: {}",
+        registry.records().len(),
+        registry.unresolved_count,
+        registry.overflowed
+    )?;
+    for record in registry.records() {
+        writeln!(
+            report,
+            "{:<48} current: {:>4}, max: {:>4}, first seen: {}, last seen: {}",
+            record.class_name,
+            record.current_count,
+            record.max_count,
+            record.first_seen_frame,
+            record.last_seen_frame
+        )?;
+    }
+
+    Ok(report)
+}
+
+fn statistics_report(states: &StateRegistry) -> String {
+    let statistics = states.statistics();
+    let (creations, updates) =
+        statistics
+            .states
+            .values()
+            .fold((0, 0), |(creations, updates), counters| {
+                (creations + counters.creations, updates + counters.updates)
+            });
+
+    let mut report = String::new();
+    writeln!(report, "state types: {}", statistics.states.len()).unwrap();
+    writeln!(report, "creations: {}", creations).unwrap();
+    writeln!(report, "updates: {}", updates).unwrap();
+    writeln!(report, "panics: {}", statistics.panics).unwrap();
+    if let Some(panic) = &statistics.last_panic {
+        writeln!(report, "last panic: {}", panic).unwrap();
+    }
+
+    report
+}
+
+/// Redacts the frames if requested.
+/// Must be called before the config is redacted as the redactor only knows the names of the frames.
+fn frames_report(
+    states: &StateRegistry,
+    options: &BundleOptions,
+    redactor: &mut NameRedactor,
+) -> anyhow::Result<String> {
+    let recorder = states.resolve::<StateFrameRecorder>(())?;
+
+    let mut report = String::new();
+    for frame in recorder
+        .recording
+        .last(options.frames.min(FRAME_RECORDER_CAPACITY))
+    {
+        let mut frame = frame.clone();
+        if options.redact_player_names {
+            redactor.redact_frame(&mut frame);
+        }
+
+        writeln!(report, "{:#?}", frame)?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(feature = "bomb-transition-log")]
+fn bomb_transition_report(states: &StateRegistry) -> anyhow::Result<String> {
+    let transitions = states.resolve::<StateBombTransitionLog>(())?;
+
+    let mut report = String::new();
+    for transition in transitions.log.transitions() {
+        writeln!(
+            report,
+            "frame {:>8} (server time {:>10}): {:?} -> {:?}, {:?}",
+            transition.frame,
+            transition
+                .server_time
+                .map(|time| format!("{:.3}", time))
+                .unwrap_or_else(|| "unknown".to_string()),
+            transition.from,
+            transition.to,
+            transition.fields
+        )?;
+    }
+
+    Ok(report)
+}
+
+/// Capture a bug report bundle (zip archive) containing the offset validation, entity classes,
+/// registry statistics, the last recorded frames (see [StateFrameRecorder]), the bomb transitions (if enabled),
+/// the build info and the sanitized config.
+/// Sections which could not be captured contain the error instead.
+///
+/// Returns the path of the created bundle.
+pub fn capture_bundle(states: &StateRegistry, options: &BundleOptions) -> anyhow::Result<PathBuf> {
+    let or_error = |result: anyhow::Result<String>| {
+        result.unwrap_or_else(|error| format!("error: {:#}\n", error))
+    };
+
+    let mut redactor = NameRedactor::default();
+    let frames = or_error(frames_report(states, options, &mut redactor));
+
+    let build_info = or_error(
+        states
+            .resolve::<StateBuildInfo>(())
+            .map(|info| format!("{:#?}\n", &*info)),
+    );
+
+    #[cfg(feature = "bomb-transition-log")]
+    let bomb_transitions = or_error(bomb_transition_report(states));
+    #[cfg(not(feature = "bomb-transition-log"))]
+    let bomb_transitions = "disabled (requires the bomb-transition-log feature)\n".to_string();
+
+    let config = match &options.config {
+        Some(config) => {
+            let mut config = config.clone();
+            sanitize_config(&mut config);
+            if options.redact_player_names {
+                redactor.redact_value(&mut config);
+            }
+
+            serde_json::to_string_pretty(&config)?
+        }
+        None => "null".to_string(),
+    };
+
+    let files = [
+        ("offsets.txt", offset_report(states)),
+        ("entity_classes.txt", or_error(entity_class_report(states))),
+        ("statistics.txt", statistics_report(states)),
+        ("frames.txt", frames),
+        ("bomb_transitions.txt", bomb_transitions),
+        ("build_info.txt", build_info),
+        ("config.json", config),
+    ];
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = options
+        .output_directory
+        .join(format!("bug-report-{}.zip", timestamp));
+
+    let mut archive = ZipWriter::new(Vec::new());
+    for (name, content) in files.iter() {
+        archive.add_file(name, content.as_bytes())?;
+    }
+
+    fs::write(&path, archive.finish()?)
+        .with_context(|| format!("write bundle to {}", path.display()))?;
+
+    log::info!("Bug report bundle captured at {}", path.display());
+    Ok(path)
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::VecDeque,
+    time::SystemTime,
+};
+
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    MatchEvent,
+    MatchSnapshot,
+    StateMatchEvents,
+};
+
+/// Max amount of frames kept by the [StateFrameRecorder]
+pub const FRAME_RECORDER_CAPACITY: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedFrame {
+    /// Frame number since the recorder has been created
+    pub frame: u64,
+    pub timestamp: SystemTime,
+
+    pub snapshot: MatchSnapshot,
+    pub events: Vec<MatchEvent>,
+}
+
+/// Ring buffer of recorded frames with a bounded capacity
+#[derive(Debug, Clone)]
+pub struct FrameRecording {
+    capacity: usize,
+    frames: VecDeque<RecordedFrame>,
+}
+
+impl FrameRecording {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            frames: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, frame: RecordedFrame) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        while self.frames.len() >= self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    /// The most recent `count` frames, oldest first
+    pub fn last(&self, count: usize) -> impl Iterator<Item = &RecordedFrame> {
+        self.frames
+            .iter()
+            .skip(self.frames.len().saturating_sub(count))
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+/// Records the [MatchSnapshot] of the last [FRAME_RECORDER_CAPACITY] frames.
+/// Frames will only be recorded while this state is being resolved every frame.
+pub struct StateFrameRecorder {
+    frame: u64,
+    pub recording: FrameRecording,
+}
+
+impl State for StateFrameRecorder {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            frame: 0,
+            recording: FrameRecording::new(FRAME_RECORDER_CAPACITY),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        self.frame += 1;
+
+        let match_events = states.resolve::<StateMatchEvents>(())?;
+        let Some(snapshot) = match_events.snapshot() else {
+            return Ok(());
+        };
+
+        self.recording.push(RecordedFrame {
+            frame: self.frame,
+            timestamp: SystemTime::now(),
+
+            snapshot: snapshot.clone(),
+            events: match_events.events.clone(),
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use super::{
+        FrameRecording,
+        RecordedFrame,
+    };
+    use crate::{
+        BombSnapshot,
+        MatchSnapshot,
+    };
+
+    fn frame(frame: u64) -> RecordedFrame {
+        RecordedFrame {
+            frame,
+            timestamp: SystemTime::UNIX_EPOCH,
+            snapshot: MatchSnapshot {
+                rounds_played: Some(3),
+                freeze_period: Some(false),
+                score_terrorists: Some(2),
+                score_counter_terrorists: Some(1),
+
+                bomb: BombSnapshot::NotPlanted,
+                bomb_carrier_name: None,
+                bomb_defuser_name: None,
+
+                players: Vec::new(),
+            },
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn bounded() {
+        let mut recording = FrameRecording::new(10);
+        for index in 0..25 {
+            recording.push(frame(index));
+        }
+
+        assert_eq!(recording.len(), 10);
+        assert_eq!(
+            recording
+                .last(3)
+                .map(|frame| frame.frame)
+                .collect::<Vec<_>>(),
+            vec![22, 23, 24]
+        );
+        assert_eq!(
+            recording.last(100).next().map(|frame| frame.frame),
+            Some(15)
+        );
+
+        let mut disabled = FrameRecording::new(0);
+        disabled.push(frame(0));
+        assert!(disabled.is_empty());
+    }
+}
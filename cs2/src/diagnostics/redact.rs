@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use super::RecordedFrame;
+use crate::MatchEvent;
+
+/// Config keys containing any of these will be removed from the bundle
+const CONFIG_SENSITIVE_KEYS: &[&str] = &["token", "password", "secret", "session", "auth"];
+
+/// Steam id of the first individual account.
+/// Redacted steam ids will be counted up from here.
+const REDACTED_STEAM_ID_BASE: u64 = 76561197960265728;
+
+/// Replaces player names by stable pseudonyms (`Player 1`, `Player 2`, ...)
+/// and steam ids by stable fake steam ids in the order they have been encountered.
+#[derive(Debug, Default)]
+pub struct NameRedactor {
+    pseudonyms: HashMap<String, String>,
+    steam_ids: HashMap<u64, u64>,
+}
+
+impl NameRedactor {
+    pub fn redact(&mut self, name: &str) -> String {
+        let next_index = self.pseudonyms.len() + 1;
+        self.pseudonyms
+            .entry(name.to_string())
+            .or_insert_with(|| format!("Player {}", next_index))
+            .clone()
+    }
+
+    /// Bots (steam id zero) will be kept as they are
+    pub fn redact_steam_id(&mut self, steam_id: u64) -> u64 {
+        if steam_id == 0 {
+            return 0;
+        }
+
+        let next_index = self.steam_ids.len() as u64 + 1;
+        *self
+            .steam_ids
+            .entry(steam_id)
+            .or_insert(REDACTED_STEAM_ID_BASE + next_index)
+    }
+
+    pub(crate) fn redact_option(&mut self, name: &mut Option<String>) {
+        if let Some(name) = name {
+            *name = self.redact(name);
+        }
+    }
+
+    fn redact_event(&mut self, event: &mut MatchEvent) {
+        match event {
+            MatchEvent::BombPlanted { planter_name, .. } => self.redact_option(planter_name),
+            MatchEvent::BombDefused { defuser_name, .. } => self.redact_option(defuser_name),
+            MatchEvent::Kill {
+                victim_name,
+                attacker_name,
+                ..
+            } => {
+                *victim_name = self.redact(victim_name);
+                self.redact_option(attacker_name);
+            }
+            _ => {}
+        }
+    }
+
+    /// Redact all player names and steam ids of the frame
+    pub fn redact_frame(&mut self, frame: &mut RecordedFrame) {
+        let snapshot = &mut frame.snapshot;
+        for (_, player) in snapshot.players.iter_mut() {
+            player.player_name = self.redact(&player.player_name);
+            player.steam_id = 0;
+        }
+
+        self.redact_option(&mut snapshot.bomb_carrier_name);
+        self.redact_option(&mut snapshot.bomb_defuser_name);
+
+        for event in frame.events.iter_mut() {
+            self.redact_event(event);
+        }
+    }
+
+    /// Replace all strings which are known player names
+    pub fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => {
+                if let Some(pseudonym) = self.pseudonyms.get(text) {
+                    *text = pseudonym.clone();
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(|value| self.redact_value(value)),
+            Value::Object(values) => values
+                .values_mut()
+                .for_each(|value| self.redact_value(value)),
+            _ => {}
+        }
+    }
+}
+
+/// Remove all credentials from the config
+pub fn sanitize_config(config: &mut Value) {
+    match config {
+        Value::Array(values) => values.iter_mut().for_each(sanitize_config),
+        Value::Object(values) => {
+            values.retain(|key, _| {
+                let key = key.to_lowercase();
+                !CONFIG_SENSITIVE_KEYS
+                    .iter()
+                    .any(|sensitive| key.contains(sensitive))
+            });
+            values.values_mut().for_each(sanitize_config);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{
+        sanitize_config,
+        NameRedactor,
+    };
+
+    #[test]
+    fn pseudonyms() {
+        let mut redactor = NameRedactor::default();
+        assert_eq!(redactor.redact("s1mple"), "Player 1");
+        assert_eq!(redactor.redact("device"), "Player 2");
+        assert_eq!(redactor.redact("s1mple"), "Player 1");
+
+        let mut value = json!({ "esp": { "highlight": ["device", "unknown"] } });
+        redactor.redact_value(&mut value);
+        assert_eq!(
+            value,
+            json!({ "esp": { "highlight": ["Player 2", "unknown"] } })
+        );
+    }
+
+    #[test]
+    fn steam_ids() {
+        let mut redactor = NameRedactor::default();
+        let first = redactor.redact_steam_id(76561198000000001);
+        let second = redactor.redact_steam_id(76561198000000002);
+        assert_ne!(first, second);
+        assert_ne!(first, 76561198000000001);
+        assert_eq!(redactor.redact_steam_id(76561198000000001), first);
+
+        /* bots stay bots */
+        assert_eq!(redactor.redact_steam_id(0), 0);
+    }
+
+    #[test]
+    fn config() {
+        let mut config = json!({
+            "web_radar_url": "wss://radar.example",
+            "Radar_Session_Token": "abc",
+            "esp": { "enabled": true, "api_key_password": "x" },
+        });
+        sanitize_config(&mut config);
+        assert_eq!(
+            config,
+            json!({
+                "web_radar_url": "wss://radar.example",
+                "esp": { "enabled": true },
+            })
+        );
+    }
+}
@@ -0,0 +1,577 @@
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    fmt::{
+        self,
+        Write,
+    },
+};
+
+use cs2_schema_definition::{
+    mod_name_from_schema_name,
+    SchemaScope,
+};
+use cs2_schema_generated::{
+    cs2::layouts::SCHEMA_CLASS_LAYOUTS,
+    SchemaClassLayout,
+};
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::dump_schema;
+
+/// `client.dll` classes accessed by this crate.
+/// Their inherited classes will be compared as well.
+pub const SCHEMA_CLASSES_USED: &[&str] = &[
+    "CBasePlayerController",
+    "CCSPlayerController",
+    "CCSPlayerController_ActionTrackingServices",
+    "CCSPlayerController_InGameMoneyServices",
+    "CCSPlayer_ItemServices",
+    "CCSPlayer_WeaponServices",
+    "CEntityIdentity",
+    "CEntityInstance",
+    "CGameSceneNode",
+    "CModelState",
+    "CPlayer_WeaponServices",
+    "CSkeletonInstance",
+    "C_BaseCSGrenadeProjectile",
+    "C_BaseEntity",
+    "C_BaseGrenade",
+    "C_BasePlayerPawn",
+    "C_BasePlayerWeapon",
+    "C_C4",
+    "C_CSGameRules",
+    "C_CSGameRulesProxy",
+    "C_CSObserverPawn",
+    "C_CSPlayerPawn",
+    "C_CSPlayerPawnBase",
+    "C_CSWeaponBase",
+    "C_EconEntity",
+    "C_GameRules",
+    "C_HEGrenadeProjectile",
+    "C_MolotovProjectile",
+    "C_PlantedC4",
+    "C_SmokeGrenadeProjectile",
+    "C_Team",
+];
+
+/// Change of a field offset between the generated schema and the runtime schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldChange {
+    Unchanged {
+        offset: u64,
+    },
+    Moved {
+        old: u64,
+        new: u64,
+    },
+
+    /// The field does not exist within the runtime schema
+    Missing {
+        old: u64,
+    },
+
+    /// The field only exists within the runtime schema
+    New {
+        offset: u64,
+    },
+}
+
+impl FieldChange {
+    /// Reading the field using the generated offset will fail or return garbage
+    pub fn is_broken(&self) -> bool {
+        matches!(self, Self::Moved { .. } | Self::Missing { .. })
+    }
+
+    /// Offset within the runtime schema or the generated schema if the field is missing
+    fn sort_offset(&self) -> u64 {
+        match *self {
+            Self::Unchanged { offset } | Self::New { offset } => offset,
+            Self::Moved { new, .. } => new,
+            Self::Missing { old } => old,
+        }
+    }
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unchanged { offset } => write!(f, "0x{:X}", offset),
+            Self::Moved { old, new } => write!(f, "0x{:X} -> 0x{:X}", old, new),
+            Self::Missing { old } => write!(f, "missing (was 0x{:X})", old),
+            Self::New { offset } => write!(f, "new at 0x{:X}", offset),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    pub field_name: String,
+    pub change: FieldChange,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassDiff {
+    /// Schema scope name (e.g. `client.dll`)
+    pub module: String,
+    pub class_name: String,
+
+    /// Class size within the generated schema.
+    /// None if the class only exists within the runtime schema.
+    pub generated_size: Option<u64>,
+
+    /// Class size within the runtime schema.
+    /// None if the class does not exist within the runtime schema.
+    pub runtime_size: Option<u64>,
+
+    /// All fields ordered by their (runtime) offset
+    pub fields: Vec<FieldDiff>,
+}
+
+impl ClassDiff {
+    pub fn is_unchanged(&self) -> bool {
+        self.generated_size == self.runtime_size
+            && self
+                .fields
+                .iter()
+                .all(|field| matches!(field.change, FieldChange::Unchanged { .. }))
+    }
+}
+
+/// Differences of the class layouts between the generated schema and the runtime schema
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDiffReport {
+    /// All compared classes ordered by their module and name
+    pub classes: Vec<ClassDiff>,
+}
+
+impl SchemaDiffReport {
+    pub fn changed_classes(&self) -> impl Iterator<Item = &ClassDiff> {
+        self.classes.iter().filter(|class| !class.is_unchanged())
+    }
+
+    /// Fields which moved or do not exist any more
+    pub fn broken_fields(&self) -> impl Iterator<Item = (&ClassDiff, &FieldDiff)> {
+        self.classes.iter().flat_map(|class| {
+            class
+                .fields
+                .iter()
+                .filter(|field| field.change.is_broken())
+                .map(move |field| (class, field))
+        })
+    }
+
+    /// Single line listing the first `limit` broken fields (e.g. `C_BaseEntity.m_iHealth 0x344 -> 0x34C`).
+    /// None if no field is broken.
+    pub fn summary(&self, limit: usize) -> Option<String> {
+        let broken = self.broken_fields().collect::<Vec<_>>();
+        if broken.is_empty() {
+            return None;
+        }
+
+        let mut summary = broken
+            .iter()
+            .take(limit)
+            .map(|(class, field)| {
+                format!("{}.{} {}", class.class_name, field.field_name, field.change)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        if broken.len() > limit {
+            write!(summary, " and {} more", broken.len() - limit).unwrap();
+        }
+
+        Some(summary)
+    }
+}
+
+impl fmt::Display for SchemaDiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format_size = |size: Option<u64>| match size {
+            Some(size) => format!("0x{:X}", size),
+            None => "none".to_string(),
+        };
+
+        for class in self.classes.iter() {
+            if class.is_unchanged() {
+                writeln!(
+                    f,
+                    "{} ({}): unchanged ({} fields)",
+                    class.class_name,
+                    class.module,
+                    class.fields.len()
+                )?;
+                continue;
+            }
+
+            if class.generated_size == class.runtime_size {
+                writeln!(f, "{} ({}):", class.class_name, class.module)?;
+            } else {
+                writeln!(
+                    f,
+                    "{} ({}): size {} -> {}",
+                    class.class_name,
+                    class.module,
+                    format_size(class.generated_size),
+                    format_size(class.runtime_size)
+                )?;
+            }
+
+            for field in class.fields.iter() {
+                if matches!(field.change, FieldChange::Unchanged { .. }) {
+                    continue;
+                }
+
+                writeln!(f, "  {:<48} {}", field.field_name, field.change)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Class layout keyed by the schema scope and class name
+struct LayoutEntry<'a> {
+    size: u64,
+    inherits: Option<&'a str>,
+    fields: BTreeMap<&'a str, u64>,
+}
+
+type LayoutMap<'a> = BTreeMap<(&'a str, &'a str), LayoutEntry<'a>>;
+
+/// Resolve an inherited class path (e.g. `globals::CPlayerPawnComponent`) to its scope and class name
+fn resolve_inherited<'a>(layouts: &[&LayoutMap<'a>], inherits: &str) -> Option<(&'a str, &'a str)> {
+    let (module_name, class_name) = inherits.split_once("::")?;
+    layouts
+        .iter()
+        .flat_map(|layout| layout.keys())
+        .find(|(module, class)| {
+            mod_name_from_schema_name(module) == module_name && *class == class_name
+        })
+        .copied()
+}
+
+/// Compare the class layouts of `classes` within `module` and all their inherited classes
+pub fn diff_class_layouts(
+    generated: &[SchemaClassLayout],
+    runtime: &[SchemaScope],
+    module: &str,
+    classes: &[&str],
+) -> SchemaDiffReport {
+    let generated = generated
+        .iter()
+        .map(|class| {
+            (
+                (class.module, class.class_name),
+                LayoutEntry {
+                    size: class.class_size,
+                    inherits: class.inherits,
+                    fields: class.fields.iter().copied().collect(),
+                },
+            )
+        })
+        .collect::<LayoutMap>();
+
+    let runtime = runtime
+        .iter()
+        .flat_map(|scope| {
+            scope.classes.iter().map(move |class| {
+                (
+                    (scope.schema_name.as_str(), class.class_name.as_str()),
+                    LayoutEntry {
+                        size: class.class_size,
+                        inherits: class.inherits.as_deref(),
+                        fields: class
+                            .offsets
+                            .iter()
+                            .map(|field| (field.field_name.as_str(), field.offset))
+                            .collect(),
+                    },
+                )
+            })
+        })
+        .collect::<LayoutMap>();
+
+    /* the requested classes including their inherited classes of both schemas */
+    let mut compared = BTreeSet::new();
+    let mut open_list = classes
+        .iter()
+        .map(|class| (module, *class))
+        .collect::<Vec<_>>();
+    while let Some(key) = open_list.pop() {
+        if !compared.insert(key) {
+            continue;
+        }
+
+        for layout in [&generated, &runtime] {
+            if let Some(inherited) = layout
+                .get(&key)
+                .and_then(|entry| entry.inherits)
+                .and_then(|inherits| resolve_inherited(&[&generated, &runtime], inherits))
+            {
+                open_list.push(inherited);
+            }
+        }
+    }
+
+    let classes = compared
+        .into_iter()
+        .filter_map(|key @ (module, class_name)| {
+            let generated = generated.get(&key);
+            let runtime = runtime.get(&key);
+            if generated.is_none() && runtime.is_none() {
+                /* unknown class */
+                return None;
+            }
+
+            let mut fields = Vec::new();
+            if let Some(generated) = generated {
+                for (field_name, old) in generated.fields.iter() {
+                    let change = match runtime.and_then(|runtime| runtime.fields.get(field_name)) {
+                        Some(new) if new == old => FieldChange::Unchanged { offset: *old },
+                        Some(new) => FieldChange::Moved {
+                            old: *old,
+                            new: *new,
+                        },
+                        None => FieldChange::Missing { old: *old },
+                    };
+
+                    fields.push(FieldDiff {
+                        field_name: field_name.to_string(),
+                        change,
+                    });
+                }
+            }
+
+            if let Some(runtime) = runtime {
+                for (field_name, offset) in runtime.fields.iter() {
+                    if generated.is_some_and(|generated| generated.fields.contains_key(field_name))
+                    {
+                        continue;
+                    }
+
+                    fields.push(FieldDiff {
+                        field_name: field_name.to_string(),
+                        change: FieldChange::New { offset: *offset },
+                    });
+                }
+            }
+
+            fields.sort_by_key(|field| field.change.sort_offset());
+            Some(ClassDiff {
+                module: module.to_string(),
+                class_name: class_name.to_string(),
+                generated_size: generated.map(|entry| entry.size),
+                runtime_size: runtime.map(|entry| entry.size),
+                fields,
+            })
+        })
+        .collect();
+
+    SchemaDiffReport { classes }
+}
+
+/// Compare the offsets of the generated schema against the runtime schema (e.g. dumped by [dump_schema])
+/// for all classes used by this crate (see [SCHEMA_CLASSES_USED])
+pub fn diff_schema(runtime: &[SchemaScope]) -> SchemaDiffReport {
+    diff_class_layouts(
+        SCHEMA_CLASS_LAYOUTS,
+        runtime,
+        "client.dll",
+        SCHEMA_CLASSES_USED,
+    )
+}
+
+/// Comparison of the generated schema against the schema of the running game (see [diff_schema]).
+/// Dumping the schema is expensive, hence the comparison will only be created once.
+pub struct StateSchemaDiff {
+    /// The comparison or the reason why the runtime schema could not be dumped
+    pub report: Result<SchemaDiffReport, String>,
+}
+
+impl State for StateSchemaDiff {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let report = dump_schema(states, Some(&["client.dll", "!GlobalTypes"]))
+            .map(|scopes| diff_schema(&scopes))
+            .map_err(|err| format!("failed to dump the runtime schema: {:#}", err));
+
+        Ok(Self { report })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cs2_schema_definition::{
+        ClassDefinition,
+        ClassField,
+        SchemaScope,
+    };
+    use cs2_schema_generated::SchemaClassLayout;
+
+    use super::{
+        diff_class_layouts,
+        FieldChange,
+        FieldDiff,
+    };
+
+    const GENERATED: &[SchemaClassLayout] = &[
+        SchemaClassLayout {
+            module: "client.dll",
+            class_name: "C_BaseEntity",
+            class_size: 0x500,
+            inherits: Some("client::CEntityInstance"),
+            fields: &[
+                ("m_iHealth", 0x344),
+                ("m_iTeamNum", 0x3E3),
+                ("m_flOld", 0x400),
+            ],
+        },
+        SchemaClassLayout {
+            module: "client.dll",
+            class_name: "CEntityInstance",
+            class_size: 0x38,
+            inherits: None,
+            fields: &[("m_pEntity", 0x10)],
+        },
+        SchemaClassLayout {
+            module: "client.dll",
+            class_name: "C_Unused",
+            class_size: 0x10,
+            inherits: None,
+            fields: &[("m_nValue", 0x08)],
+        },
+    ];
+
+    fn class(
+        name: &str,
+        size: u64,
+        inherits: Option<&str>,
+        fields: &[(&str, u64)],
+    ) -> ClassDefinition {
+        ClassDefinition {
+            schema_scope_name: "client.dll".to_string(),
+            class_name: name.to_string(),
+            class_size: size,
+            inherits: inherits.map(str::to_string),
+            metadata: Vec::new(),
+            offsets: fields
+                .iter()
+                .map(|(field_name, offset)| ClassField {
+                    field_name: field_name.to_string(),
+                    field_type: None,
+                    field_ctype: "int32".to_string(),
+                    offset: *offset,
+                    metadata: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+
+    fn runtime() -> Vec<SchemaScope> {
+        vec![SchemaScope {
+            schema_name: "client.dll".to_string(),
+            classes: vec![
+                class(
+                    "C_BaseEntity",
+                    0x508,
+                    Some("client::CEntityInstance"),
+                    &[
+                        ("m_iHealth", 0x34C),
+                        ("m_iTeamNum", 0x3E3),
+                        ("m_flNew", 0x500),
+                    ],
+                ),
+                class("CEntityInstance", 0x38, None, &[("m_pEntity", 0x10)]),
+                class("C_Unused", 0x20, None, &[]),
+            ],
+            enums: Vec::new(),
+        }]
+    }
+
+    #[test]
+    fn moved_missing_new() {
+        let report = diff_class_layouts(GENERATED, &runtime(), "client.dll", &["C_BaseEntity"]);
+
+        /* the inherited class has been compared, the unused class not */
+        assert_eq!(
+            report
+                .classes
+                .iter()
+                .map(|class| class.class_name.as_str())
+                .collect::<Vec<_>>(),
+            ["CEntityInstance", "C_BaseEntity"]
+        );
+        assert!(report.classes[0].is_unchanged());
+
+        let entity = &report.classes[1];
+        assert_eq!(
+            (entity.generated_size, entity.runtime_size),
+            (Some(0x500), Some(0x508))
+        );
+        assert_eq!(
+            entity.fields,
+            [
+                FieldDiff {
+                    field_name: "m_iHealth".to_string(),
+                    change: FieldChange::Moved {
+                        old: 0x344,
+                        new: 0x34C
+                    },
+                },
+                FieldDiff {
+                    field_name: "m_iTeamNum".to_string(),
+                    change: FieldChange::Unchanged { offset: 0x3E3 },
+                },
+                FieldDiff {
+                    field_name: "m_flOld".to_string(),
+                    change: FieldChange::Missing { old: 0x400 },
+                },
+                FieldDiff {
+                    field_name: "m_flNew".to_string(),
+                    change: FieldChange::New { offset: 0x500 },
+                },
+            ]
+        );
+
+        assert_eq!(report.changed_classes().count(), 1);
+        assert_eq!(report.broken_fields().count(), 2);
+        assert_eq!(
+            report.summary(1).as_deref(),
+            Some("C_BaseEntity.m_iHealth 0x344 -> 0x34C and 1 more")
+        );
+    }
+
+    #[test]
+    fn missing_class() {
+        let report = diff_class_layouts(GENERATED, &[], "client.dll", &["C_Unused", "C_Unknown"]);
+        assert_eq!(report.classes.len(), 1);
+
+        let unused = &report.classes[0];
+        assert_eq!(unused.class_name, "C_Unused");
+        assert_eq!(unused.runtime_size, None);
+        assert_eq!(unused.fields[0].change, FieldChange::Missing { old: 0x08 });
+        assert_eq!(
+            report.summary(8).as_deref(),
+            Some("C_Unused.m_nValue missing (was 0x8)")
+        );
+    }
+
+    #[test]
+    fn unchanged() {
+        let report = diff_class_layouts(GENERATED, &runtime(), "client.dll", &["CEntityInstance"]);
+        assert_eq!(report.changed_classes().count(), 0);
+        assert_eq!(report.summary(8), None);
+    }
+}
@@ -0,0 +1,362 @@
+use std::time::{
+    Duration,
+    Instant,
+};
+
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::ConVars;
+
+/// Convars which are used to determine the [EconomyRules]
+pub const ECONOMY_CONVARS: &[&str] = &[
+    "mp_startmoney",
+    "mp_maxmoney",
+    "mp_maxrounds",
+    "mp_overtime_enable",
+    "mp_overtime_maxrounds",
+    "mp_overtime_startmoney",
+    "cash_team_loser_bonus",
+    "cash_team_loser_bonus_consecutive_rounds",
+];
+
+/// Interval in which the economy convars will be read again
+const ECONOMY_RULES_UPDATE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Economy configuration of the current server.
+/// All values default to the competitive matchmaking configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EconomyRules {
+    pub start_money: i32,
+    pub max_money: i32,
+
+    /// Max rounds of the regulation time (both halves)
+    pub max_rounds: i32,
+
+    pub overtime_enabled: bool,
+
+    /// Max rounds of each overtime (both halves)
+    pub overtime_max_rounds: i32,
+    pub overtime_start_money: i32,
+
+    /// Loss bonus for the first lost round
+    pub loss_bonus: i32,
+
+    /// Additional loss bonus for each consecutive lost round
+    pub loss_bonus_consecutive: i32,
+
+    /// Max amount of consecutive loss bonus increments
+    pub loss_bonus_max_increments: u32,
+}
+
+impl Default for EconomyRules {
+    fn default() -> Self {
+        Self {
+            start_money: 800,
+            max_money: 16000,
+
+            max_rounds: 24,
+
+            overtime_enabled: true,
+            overtime_max_rounds: 6,
+            overtime_start_money: 12500,
+
+            loss_bonus: 1400,
+            loss_bonus_consecutive: 500,
+            loss_bonus_max_increments: 4,
+        }
+    }
+}
+
+/// Predicted economy of a team for the next round
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EconomyPrediction {
+    /// Rules used for the prediction
+    pub rules: EconomyRules,
+
+    /// Money each player receives if the current round is lost
+    pub loss_bonus: i32,
+
+    /// The next round starts a new half (or overtime half) and the money will be reset
+    pub money_reset: bool,
+
+    /// Min money each player will have at the start of the next round
+    pub next_round_min_money: i32,
+}
+
+impl EconomyRules {
+    /// Read the economy rules from the game convars.
+    /// Missing convars will retain their default value.
+    pub fn from_convars(cvars: &ConVars) -> anyhow::Result<Self> {
+        let values = cvars
+            .find_cvars(ECONOMY_CONVARS)?
+            .into_iter()
+            .map(|cvar| match cvar {
+                Some(cvar) => Ok(Some(cvar.n_value()? as i32)),
+                None => Ok(None),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut result = Self::default();
+        for (name, value) in ECONOMY_CONVARS.iter().zip(values) {
+            let Some(value) = value else {
+                log::debug!("Missing economy cvar {}", name);
+                continue;
+            };
+
+            result.apply_convar(name, value);
+        }
+
+        Ok(result)
+    }
+
+    /// Apply the value of an economy convar.
+    /// Returns false if the convar does not affect the economy.
+    pub fn apply_convar(&mut self, name: &str, value: i32) -> bool {
+        match name {
+            "mp_startmoney" => self.start_money = value,
+            "mp_maxmoney" => self.max_money = value,
+            "mp_maxrounds" => self.max_rounds = value,
+            "mp_overtime_enable" => self.overtime_enabled = value != 0,
+            "mp_overtime_maxrounds" => self.overtime_max_rounds = value,
+            "mp_overtime_startmoney" => self.overtime_start_money = value,
+            "cash_team_loser_bonus" => self.loss_bonus = value,
+            "cash_team_loser_bonus_consecutive_rounds" => self.loss_bonus_consecutive = value,
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Check if the round (zero based) is played in overtime
+    pub fn is_overtime(&self, round_index: i32) -> bool {
+        self.overtime_enabled && round_index >= self.max_rounds
+    }
+
+    /// Check if the round (zero based) is the first round of a half.
+    /// This includes the halves of each overtime.
+    pub fn is_half_start(&self, round_index: i32) -> bool {
+        if round_index < self.max_rounds {
+            let half_length = (self.max_rounds / 2).max(1);
+            return round_index % half_length == 0;
+        }
+
+        if !self.overtime_enabled {
+            return false;
+        }
+
+        let half_length = (self.overtime_max_rounds / 2).max(1);
+        (round_index - self.max_rounds) % half_length == 0
+    }
+
+    /// Check if the round (zero based) is the first round of an overtime
+    pub fn is_overtime_start(&self, round_index: i32) -> bool {
+        self.is_overtime(round_index)
+            && (round_index - self.max_rounds) % self.overtime_max_rounds.max(1) == 0
+    }
+
+    /// Rounds a team must have won to win the match while the round (zero based) is being played.
+    /// Includes the rounds of all overtimes up to the current one.
+    pub fn rounds_to_win(&self, round_index: i32) -> i32 {
+        let regulation = self.max_rounds / 2 + 1;
+        if !self.is_overtime(round_index) {
+            return regulation;
+        }
+
+        let overtimes = (round_index - self.max_rounds) / self.overtime_max_rounds.max(1) + 1;
+        regulation + overtimes * (self.overtime_max_rounds / 2)
+    }
+
+    /// Money each player receives at the start of a half
+    pub fn half_start_money(&self, round_index: i32) -> i32 {
+        if self.is_overtime(round_index) {
+            self.overtime_start_money
+        } else {
+            self.start_money
+        }
+    }
+
+    /// Loss bonus for losing `consecutive_losses` rounds in a row (including the current round)
+    pub fn loss_bonus(&self, consecutive_losses: u32) -> i32 {
+        let increments = consecutive_losses
+            .saturating_sub(1)
+            .min(self.loss_bonus_max_increments);
+
+        self.loss_bonus + self.loss_bonus_consecutive * increments as i32
+    }
+
+    /// Predict the money of a player for the next round, assuming the team loses
+    /// the current round (zero based) with `consecutive_losses` rounds lost in a row.
+    pub fn predict(
+        &self,
+        round_index: i32,
+        consecutive_losses: u32,
+        current_money: i32,
+    ) -> EconomyPrediction {
+        let loss_bonus = self.loss_bonus(consecutive_losses);
+        let money_reset = self.is_half_start(round_index + 1);
+        let next_round_min_money = if money_reset {
+            self.half_start_money(round_index + 1)
+        } else {
+            (current_money + loss_bonus).min(self.max_money)
+        };
+
+        EconomyPrediction {
+            rules: *self,
+            loss_bonus,
+            money_reset,
+            next_round_min_money,
+        }
+    }
+}
+
+/// Economy rules of the current server
+pub struct StateEconomyRules {
+    pub rules: EconomyRules,
+    last_update: Instant,
+}
+
+impl State for StateEconomyRules {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let cvars = ConVars::new(states)?;
+        Ok(Self {
+            rules: EconomyRules::from_convars(&cvars)?,
+            last_update: Instant::now(),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        if self.last_update.elapsed() < ECONOMY_RULES_UPDATE_INTERVAL {
+            return Ok(());
+        }
+
+        let cvars = ConVars::new(states)?;
+        self.rules = EconomyRules::from_convars(&cvars)?;
+        self.last_update = Instant::now();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::EconomyRules;
+
+    fn community_rules() -> EconomyRules {
+        let mut rules = EconomyRules::default();
+        for (name, value) in [
+            ("mp_startmoney", 16000),
+            ("mp_maxrounds", 30),
+            ("mp_overtime_enable", 0),
+        ] {
+            assert!(rules.apply_convar(name, value));
+        }
+        rules
+    }
+
+    fn esl_rules() -> EconomyRules {
+        let mut rules = EconomyRules::default();
+        for (name, value) in [
+            ("mp_overtime_maxrounds", 6),
+            ("mp_overtime_startmoney", 10000),
+            ("cash_team_loser_bonus", 1900),
+            ("cash_team_loser_bonus_consecutive_rounds", 0),
+        ] {
+            assert!(rules.apply_convar(name, value));
+        }
+        rules
+    }
+
+    #[test]
+    fn unknown_convar() {
+        let mut rules = EconomyRules::default();
+        assert!(!rules.apply_convar("sv_cheats", 1));
+        assert_eq!(rules, EconomyRules::default());
+    }
+
+    #[test]
+    fn prediction() {
+        let cases = [
+            /* (rules, round index, consecutive losses, current money, loss bonus, money reset, next round money) */
+            (EconomyRules::default(), 0, 1, 0, 1400, false, 1400),
+            (EconomyRules::default(), 1, 2, 0, 1900, false, 1900),
+            (EconomyRules::default(), 5, 5, 0, 3400, false, 3400),
+            (EconomyRules::default(), 6, 9, 0, 3400, false, 3400),
+            (EconomyRules::default(), 8, 1, 15000, 1400, false, 16000),
+            (EconomyRules::default(), 11, 1, 5000, 1400, true, 800),
+            (EconomyRules::default(), 23, 1, 5000, 1400, true, 12500),
+            (EconomyRules::default(), 25, 1, 5000, 1400, false, 6400),
+            (EconomyRules::default(), 26, 1, 5000, 1400, true, 12500),
+            (EconomyRules::default(), 29, 1, 5000, 1400, true, 12500),
+            (esl_rules(), 12, 3, 1000, 1900, false, 2900),
+            (esl_rules(), 23, 1, 1000, 1900, true, 10000),
+            (esl_rules(), 24, 2, 1000, 1900, false, 2900),
+            (esl_rules(), 26, 2, 1000, 1900, true, 10000),
+            (community_rules(), 0, 1, 16000, 1400, false, 16000),
+            (community_rules(), 11, 1, 0, 1400, false, 1400),
+            (community_rules(), 14, 1, 0, 1400, true, 16000),
+            (community_rules(), 29, 1, 0, 1400, false, 1400),
+        ];
+
+        for (
+            rules,
+            round_index,
+            consecutive_losses,
+            current_money,
+            loss_bonus,
+            money_reset,
+            next_round_min_money,
+        ) in cases
+        {
+            let prediction = rules.predict(round_index, consecutive_losses, current_money);
+            assert_eq!(prediction.rules, rules);
+            assert_eq!(
+                (
+                    prediction.loss_bonus,
+                    prediction.money_reset,
+                    prediction.next_round_min_money
+                ),
+                (loss_bonus, money_reset, next_round_min_money),
+                "round {} ({:?})",
+                round_index,
+                rules
+            );
+        }
+    }
+
+    #[test]
+    fn overtime() {
+        let rules = EconomyRules::default();
+        assert!(!rules.is_overtime(23));
+        assert!(rules.is_overtime(24));
+
+        let rules = community_rules();
+        assert!(!rules.is_overtime(30));
+        assert!(!rules.is_half_start(30));
+    }
+
+    #[test]
+    fn rounds_to_win() {
+        let rules = EconomyRules::default();
+        assert_eq!(rules.rounds_to_win(0), 13);
+        assert_eq!(rules.rounds_to_win(23), 13);
+        assert!(rules.is_overtime_start(24));
+        assert_eq!(rules.rounds_to_win(24), 16);
+        assert_eq!(rules.rounds_to_win(29), 16);
+        assert!(!rules.is_overtime_start(27));
+        assert!(rules.is_overtime_start(30));
+        assert_eq!(rules.rounds_to_win(30), 19);
+
+        let rules = community_rules();
+        assert_eq!(rules.rounds_to_win(29), 16);
+        assert!(!rules.is_overtime_start(30));
+    }
+}
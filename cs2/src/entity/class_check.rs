@@ -0,0 +1,203 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    sync::OnceLock,
+};
+
+use cs2_schema_cutl::{
+    EntityHandle,
+    SchemaClass,
+};
+use cs2_schema_generated::{
+    cs2::layouts::SCHEMA_CLASS_LAYOUTS,
+    SchemaClassLayout,
+};
+use raw_struct::builtins::Ptr64;
+
+use super::{
+    CEntityIdentityEx,
+    EntityIndex,
+    StateEntityList,
+};
+use crate::ClassNameCache;
+
+/// The entity a handle points to is not of the class the handle expects.
+/// This happens when the entity slot has been reused by another entity
+/// or the handle field has been read at a wrong offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityClassMismatch {
+    pub entity_index: EntityIndex,
+
+    /// Schema class name of the handle type (e.g. `C_CSPlayerPawn`)
+    pub expected: &'static str,
+
+    /// Class name of the entity or None if the class is unknown to the [ClassNameCache]
+    pub actual: Option<String>,
+}
+
+impl fmt::Display for EntityClassMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected entity {} to be a {} but it is a {}",
+            self.entity_index,
+            self.expected,
+            self.actual.as_deref().unwrap_or("<unknown class>")
+        )
+    }
+}
+
+impl Error for EntityClassMismatch {}
+
+/// Inherited class name of all classes within a schema scope
+type ClassHierarchy = HashMap<&'static str, Option<&'static str>>;
+
+fn build_class_hierarchy(layouts: &'static [SchemaClassLayout], scope: &str) -> ClassHierarchy {
+    layouts
+        .iter()
+        .filter(|layout| layout.module == scope)
+        .map(|layout| {
+            (
+                layout.class_name,
+                layout.inherits.map(|inherits| {
+                    inherits
+                        .rsplit_once("::")
+                        .map_or(inherits, |(_, name)| name)
+                }),
+            )
+        })
+        .collect()
+}
+
+/// Check if `class_name` is `base_class` or inherits from it.
+/// Classes unknown to the hierarchy (e.g. added by a game update) only match themselves.
+fn class_inherits_from(hierarchy: &ClassHierarchy, class_name: &str, base_class: &str) -> bool {
+    let mut current = Some(class_name);
+
+    /* bounded in case the schema contains an inheritance cycle */
+    for _ in 0..=hierarchy.len() {
+        let Some(class_name) = current else {
+            return false;
+        };
+        if class_name == base_class {
+            return true;
+        }
+
+        current = hierarchy.get(class_name).copied().flatten();
+    }
+
+    false
+}
+
+/// Check if the client class `class_name` is `base_class` or inherits from it
+/// according to the schema dump.
+pub fn client_class_inherits_from(class_name: &str, base_class: &str) -> bool {
+    static CLIENT_HIERARCHY: OnceLock<ClassHierarchy> = OnceLock::new();
+
+    let hierarchy =
+        CLIENT_HIERARCHY.get_or_init(|| build_class_hierarchy(SCHEMA_CLASS_LAYOUTS, "client.dll"));
+    class_inherits_from(hierarchy, class_name, base_class)
+}
+
+/// Check the class name of an entity against the class of the handle type `T`.
+/// Entities of derived classes are accepted (e.g. a `C_CSPlayerPawn` for a `C_BaseEntity`).
+pub fn check_entity_class<T: ?Sized + SchemaClass>(
+    entity_index: EntityIndex,
+    class_name: Option<&str>,
+) -> Result<(), EntityClassMismatch> {
+    let matches = class_name.is_some_and(|class_name| {
+        if T::SCHEMA_SCOPE == "client.dll" {
+            client_class_inherits_from(class_name, T::CLASS_NAME)
+        } else {
+            /* entities are client classes, other scopes can only be matched exactly */
+            class_name == T::CLASS_NAME
+        }
+    });
+
+    if matches {
+        Ok(())
+    } else {
+        Err(EntityClassMismatch {
+            entity_index,
+            expected: T::CLASS_NAME,
+            actual: class_name.map(str::to_string),
+        })
+    }
+}
+
+impl StateEntityList {
+    /// Resolve the entity of a handle and check its class against the handle type `T`
+    /// (e.g. `resolve_typed::<dyn C_CSPlayerPawn>`).
+    ///
+    /// Returns None if the entity does not exist and an [EntityClassMismatch]
+    /// if the entity is of another class instead of handing out a pointer to unrelated fields.
+    pub fn resolve_typed<T: ?Sized + SchemaClass + 'static>(
+        &self,
+        class_name_cache: &ClassNameCache,
+        handle: &EntityHandle<T>,
+    ) -> anyhow::Result<Option<Ptr64<T>>> {
+        let entity_index = EntityIndex::from_handle(handle);
+        let Some(identity) = self.identity_from_index(entity_index) else {
+            return Ok(None);
+        };
+
+        let class_name = class_name_cache.lookup(&identity.entity_class_info()?)?;
+        check_entity_class::<T>(entity_index, class_name.map(String::as_str))?;
+
+        Ok(Some(identity.entity_ptr()?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cs2_schema_generated::cs2::client::{
+        CCSPlayerController,
+        C_BaseEntity,
+        C_CSPlayerPawn,
+        C_C4,
+    };
+
+    use super::{
+        check_entity_class,
+        EntityClassMismatch,
+    };
+    use crate::EntityIndex;
+
+    #[test]
+    fn derived_classes() {
+        let entity_index = EntityIndex(0x40);
+        assert!(
+            check_entity_class::<dyn C_CSPlayerPawn>(entity_index, Some("C_CSPlayerPawn")).is_ok()
+        );
+        assert!(
+            check_entity_class::<dyn C_BaseEntity>(entity_index, Some("C_CSPlayerPawn")).is_ok()
+        );
+        assert!(check_entity_class::<dyn C_BaseEntity>(entity_index, Some("C_C4")).is_ok());
+
+        /* a base class is not a derived class */
+        assert!(
+            check_entity_class::<dyn C_CSPlayerPawn>(entity_index, Some("C_BaseEntity")).is_err()
+        );
+    }
+
+    #[test]
+    fn mismatch() {
+        let entity_index = EntityIndex(0x40);
+        assert_eq!(
+            check_entity_class::<dyn C_CSPlayerPawn>(entity_index, Some("C_C4")),
+            Err(EntityClassMismatch {
+                entity_index,
+                expected: "C_CSPlayerPawn",
+                actual: Some("C_C4".to_string()),
+            })
+        );
+        assert!(check_entity_class::<dyn CCSPlayerController>(
+            entity_index,
+            Some("C_CSPlayerPawn")
+        )
+        .is_err());
+        assert!(check_entity_class::<dyn C_C4>(entity_index, Some("C_Unknown")).is_err());
+        assert!(check_entity_class::<dyn C_C4>(entity_index, None).is_err());
+    }
+}
@@ -3,8 +3,10 @@ use anyhow::{
     Context,
 };
 use cs2_schema_generated::cs2::client::{
+    CBasePlayerController,
     CCSPlayerController,
     CEntityInstance,
+    C_BaseEntity,
 };
 use raw_struct::{
     builtins::Ptr64,
@@ -18,10 +20,12 @@ use utils_state::{
 
 use super::{
     CEntityIdentityEx,
+    ControllerIndex,
     StateEntityList,
 };
 use crate::{
     CS2Offset,
+    PlayerOrderKey,
     StateCS2Memory,
     StateResolvedOffset,
 };
@@ -78,8 +82,37 @@ impl State for StatePlayerControllerClass {
     }
 }
 
+pub struct PlayerControllerEntry {
+    pub entity_index: ControllerIndex,
+    pub order_key: PlayerOrderKey,
+    pub instance: Ptr64<dyn CCSPlayerController>,
+}
+
+impl PlayerControllerEntry {
+    fn read_order_key(
+        states: &StateRegistry,
+        instance: &Ptr64<dyn CCSPlayerController>,
+        entity_index: ControllerIndex,
+    ) -> anyhow::Result<PlayerOrderKey> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let controller = instance
+            .value_reference(memory.view_arc())
+            .context("player controller nullptr")?;
+
+        Ok(PlayerOrderKey::new(
+            controller.m_iTeamNum()?,
+            controller.m_steamID()?,
+            entity_index,
+        ))
+    }
+}
+
+/// All player controllers ordered by their [PlayerOrderKey]
 pub struct StatePlayerControllers {
-    pub instances: Vec<Ptr64<dyn CCSPlayerController>>,
+    pub instances: Vec<PlayerControllerEntry>,
+
+    /// Reason why the controllers are not available (degraded mode)
+    pub unavailable_reason: Option<String>,
 }
 
 impl State for StatePlayerControllers {
@@ -89,19 +122,45 @@ impl State for StatePlayerControllers {
         let controller_class_address = states.resolve::<StatePlayerControllerClass>(())?;
         let entities = states.resolve::<StateEntityList>(())?;
 
+        let mut instances = Vec::new();
+        for entity in entities.entities().iter() {
+            if !entity
+                .entity_class_info()
+                .map(|ptr| ptr.address == controller_class_address.address)
+                .unwrap_or(false)
+            {
+                continue;
+            }
+
+            let entity_index = ControllerIndex::from_handle(&entity.handle::<()>()?);
+            let instance = entity.entity_ptr()?;
+            let order_key = PlayerControllerEntry::read_order_key(states, &instance, entity_index)
+                .unwrap_or_else(|_| PlayerOrderKey::new(0, 0, entity_index));
+
+            instances.push(PlayerControllerEntry {
+                entity_index,
+                order_key,
+                instance,
+            });
+        }
+        instances.sort_by_key(|entry| entry.order_key);
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            controllers = instances.len(),
+            "player controllers collected"
+        );
+
         Ok(Self {
-            instances: entities
-                .entities()
-                .iter()
-                .filter(|entity| {
-                    if let Ok(ptr) = entity.entity_class_info() {
-                        ptr.address == controller_class_address.address
-                    } else {
-                        false
-                    }
-                })
-                .map(|entity| entity.entity_ptr())
-                .collect::<anyhow::Result<Vec<_>>>()?,
+            instances,
+            unavailable_reason: None,
+        })
+    }
+
+    fn unavailable(reason: &str) -> Option<Self> {
+        Some(Self {
+            instances: Vec::new(),
+            unavailable_reason: Some(reason.to_string()),
         })
     }
 }
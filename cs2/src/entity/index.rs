@@ -0,0 +1,154 @@
+use std::fmt;
+
+use anyhow::Context;
+use cs2_schema_cutl::EntityHandle;
+use cs2_schema_generated::cs2::client::{
+    CCSPlayerController,
+    C_BasePlayerPawn,
+};
+use utils_state::StateRegistry;
+
+use super::{
+    CEntityIdentityEx,
+    StateEntityList,
+};
+use crate::StateCS2Memory;
+
+macro_rules! entity_index_type {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+        #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serialize", serde(transparent))]
+        pub struct $name(pub u32);
+
+        impl $name {
+            /// Entity index of the handle regardless of the handle being valid
+            pub fn from_handle<T: ?Sized>(handle: &EntityHandle<T>) -> Self {
+                Self(handle.get_entity_index())
+            }
+
+            /// Entity index of the handle or None if the handle is invalid
+            pub fn from_valid_handle<T: ?Sized>(handle: &EntityHandle<T>) -> Option<Self> {
+                if handle.is_valid() {
+                    Some(Self::from_handle(handle))
+                } else {
+                    None
+                }
+            }
+
+            pub fn value(self) -> u32 {
+                self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+    };
+}
+
+entity_index_type!(
+    /// Index of an entity within the entity list.
+    /// Prefer [PawnIndex] or [ControllerIndex] if the kind of the entity is known.
+    EntityIndex
+);
+
+entity_index_type!(
+    /// Entity index of a player pawn (`C_CSPlayerPawn`)
+    PawnIndex
+);
+
+entity_index_type!(
+    /// Entity index of a player controller (`CCSPlayerController`)
+    ControllerIndex
+);
+
+impl From<u32> for EntityIndex {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<PawnIndex> for EntityIndex {
+    fn from(value: PawnIndex) -> Self {
+        Self(value.0)
+    }
+}
+
+impl From<ControllerIndex> for EntityIndex {
+    fn from(value: ControllerIndex) -> Self {
+        Self(value.0)
+    }
+}
+
+impl PawnIndex {
+    /// Resolve the controller currently controlling this pawn.
+    /// Returns None if the pawn does not exist or has no controller.
+    pub fn resolve_controller(
+        self,
+        states: &StateRegistry,
+    ) -> anyhow::Result<Option<ControllerIndex>> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let entities = states.resolve::<StateEntityList>(())?;
+        let Some(identity) = entities.identity_from_index(self) else {
+            return Ok(None);
+        };
+
+        let pawn = identity
+            .entity_ptr::<dyn C_BasePlayerPawn>()?
+            .value_reference(memory.view_arc())
+            .context("player pawn nullptr")?;
+
+        Ok(ControllerIndex::from_valid_handle(&pawn.m_hController()?))
+    }
+}
+
+impl ControllerIndex {
+    /// Resolve the player pawn of this controller.
+    /// Returns None if the controller does not exist or has no pawn.
+    pub fn resolve_pawn(self, states: &StateRegistry) -> anyhow::Result<Option<PawnIndex>> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let entities = states.resolve::<StateEntityList>(())?;
+        let Some(identity) = entities.identity_from_index(self) else {
+            return Ok(None);
+        };
+
+        let controller = identity
+            .entity_ptr::<dyn CCSPlayerController>()?
+            .value_reference(memory.view_arc())
+            .context("player controller nullptr")?;
+
+        Ok(PawnIndex::from_valid_handle(&controller.m_hPlayerPawn()?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cs2_schema_cutl::EntityHandle;
+
+    use super::{
+        ControllerIndex,
+        EntityIndex,
+        PawnIndex,
+    };
+
+    #[test]
+    fn handle_conversion() {
+        /* serial number 0x12, entity index 0x42 */
+        let handle = EntityHandle::<()>::from_index((0x12 << 15) | 0x42);
+        assert_eq!(PawnIndex::from_handle(&handle), PawnIndex(0x42));
+        assert_eq!(
+            ControllerIndex::from_valid_handle(&handle),
+            Some(ControllerIndex(0x42))
+        );
+
+        let invalid = EntityHandle::<()>::from_index(0xFFFFFFFF);
+        assert_eq!(PawnIndex::from_valid_handle(&invalid), None);
+
+        assert_eq!(EntityIndex::from(PawnIndex(7)), EntityIndex(7));
+        assert_eq!(EntityIndex::from(ControllerIndex(7)), EntityIndex(7));
+    }
+}
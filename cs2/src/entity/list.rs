@@ -7,6 +7,7 @@ use raw_struct::{
     builtins::Ptr64,
     Copy,
     FromMemoryView,
+    MemoryView,
 };
 use utils_state::{
     State,
@@ -15,7 +16,10 @@ use utils_state::{
 };
 
 use crate::{
-    entity::identity::CEntityIdentityEx,
+    entity::{
+        identity::CEntityIdentityEx,
+        EntityIndex,
+    },
     CS2Offset,
     StateCS2Memory,
     StateResolvedOffset,
@@ -90,22 +94,86 @@ impl State for StateEntityList {
             }
         }
 
+        #[cfg(feature = "tracing")]
+        tracing::trace!(entities = self.entities.len(), "entity list updated");
+
         Ok(())
     }
 }
 
+/// Read the identity of a single entity without walking the whole entity list.
+/// Returns `None` if the entity does not exist.
+pub fn read_entity_identity(
+    memory: &dyn MemoryView,
+    entity_list_address: u64,
+    entity_index: u32,
+) -> anyhow::Result<Option<Copy<dyn CEntityIdentity>>> {
+    let bulk_index = (entity_index >> 9) as usize;
+    let entry_index = (entity_index & 0x1FF) as usize;
+
+    let outer_list = Ptr64::<OuterEntityList>::read_object(memory, entity_list_address)
+        .map_err(|e| anyhow!("outer entity list: {}", e))?;
+    if bulk_index >= outer_list.len().unwrap() {
+        return Ok(None);
+    }
+
+    let Some(bulk) = outer_list
+        .elements(memory, bulk_index..bulk_index + 1)?
+        .into_iter()
+        .next()
+    else {
+        return Ok(None);
+    };
+    if bulk.is_null() {
+        return Ok(None);
+    }
+
+    let Some(identity) = bulk
+        .elements(memory, entry_index..entry_index + 1)?
+        .into_iter()
+        .next()
+    else {
+        return Ok(None);
+    };
+    if identity.handle::<()>()?.get_entity_index() != entity_index {
+        /* entity is invalid */
+        return Ok(None);
+    }
+
+    Ok(Some(identity))
+}
+
 impl StateEntityList {
+    /// Entity list of the given identities (e.g. for memory fixtures).
+    /// Identities are keyed by the entity index of their handle.
+    pub fn from_identities(entities: Vec<Copy<dyn CEntityIdentity>>) -> anyhow::Result<Self> {
+        let mut handle_lookup = BTreeMap::new();
+        for (index, identity) in entities.iter().enumerate() {
+            handle_lookup.insert(identity.handle::<()>()?.get_entity_index(), index);
+        }
+
+        Ok(Self {
+            entities,
+            handle_lookup,
+        })
+    }
+
     pub fn entities(&self) -> &[Copy<dyn CEntityIdentity>] {
         &self.entities
     }
 
-    pub fn identity_from_index(&self, entity_index: u32) -> Option<&Copy<dyn CEntityIdentity>> {
+    pub fn identity_from_index(
+        &self,
+        entity_index: impl Into<EntityIndex>,
+    ) -> Option<&Copy<dyn CEntityIdentity>> {
         self.handle_lookup
-            .get(&entity_index)
+            .get(&entity_index.into().value())
             .map(|index| self.entities.get(*index))
             .flatten()
     }
 
+    /// Entity pointer of the handle without checking the class of the entity
+    /// (see [StateEntityList::resolve_typed]).
     pub fn entity_from_handle<T: ?Sized + 'static>(
         &self,
         handle: &EntityHandle<T>,
@@ -0,0 +1,267 @@
+use std::marker::PhantomData;
+
+use cs2_schema_cutl::EntityHandle;
+use cs2_schema_generated::cs2::client::CEntityIdentity;
+use raw_struct::Copy;
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use super::{
+    CEntityIdentityEx,
+    EntityIndex,
+    StateEntityList,
+};
+use crate::{
+    ClassNameCache,
+    StateEntityClassIndex,
+};
+
+/// Class of the entities located by a [CachedEntityLocator]
+pub trait EntityClassFilter: 'static {
+    /// Schema class name of the entity (e.g. `C_PlantedC4`)
+    const CLASS_NAME: &'static str;
+}
+
+/// Index and serial number of an entity.
+/// The serial number changes when the entity slot gets reused by another entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EntityKey {
+    pub entity_index: EntityIndex,
+    pub serial_number: u32,
+}
+
+impl EntityKey {
+    pub fn from_handle<T: ?Sized>(handle: &EntityHandle<T>) -> Self {
+        Self {
+            entity_index: EntityIndex::from_handle(handle),
+            serial_number: handle.get_serial_number(),
+        }
+    }
+}
+
+/// Locates all entities of a class across multiple frames.
+///
+/// The keys of the entities found within the [StateEntityClassIndex] will be cached.
+/// In subsequent frames the cached entities will be resolved directly by their index and only be
+/// trusted if the serial number and the class still match. Otherwise the class index will be consulted again.
+pub struct CachedEntityLocator<F> {
+    cached: Option<Vec<EntityKey>>,
+    full_scans: u64,
+    _filter: PhantomData<F>,
+}
+
+impl<F: EntityClassFilter> State for CachedEntityLocator<F> {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self::new())
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+}
+
+impl<F: EntityClassFilter> Default for CachedEntityLocator<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: EntityClassFilter> CachedEntityLocator<F> {
+    pub fn new() -> Self {
+        Self {
+            cached: None,
+            full_scans: 0,
+            _filter: Default::default(),
+        }
+    }
+
+    /// Keys of the entities found by the last class index lookup
+    pub fn cached(&self) -> Option<&[EntityKey]> {
+        self.cached.as_deref()
+    }
+
+    /// Consult the class index again within the next lookup (e.g. when the entity reached its terminal state)
+    pub fn invalidate(&mut self) {
+        self.cached = None;
+    }
+
+    /// Locate all entities of [EntityClassFilter::CLASS_NAME]
+    pub fn locate<'a>(
+        &mut self,
+        entities: &'a StateEntityList,
+        class_name_cache: &ClassNameCache,
+        class_index: &'a StateEntityClassIndex,
+    ) -> anyhow::Result<Vec<&'a Copy<dyn CEntityIdentity>>> {
+        self.locate_with(
+            |entity_index| entities.identity_from_index(entity_index),
+            class_index.entities_of_class(entities, F::CLASS_NAME),
+            |identity| Ok(EntityKey::from_handle(&identity.handle::<()>()?)),
+            |identity| {
+                Ok(class_name_cache
+                    .lookup(&identity.entity_class_info()?)?
+                    .map(|name| name == F::CLASS_NAME)
+                    .unwrap_or(false))
+            },
+        )
+    }
+
+    fn locate_with<E>(
+        &mut self,
+        lookup: impl Fn(EntityIndex) -> Option<E>,
+        entities: impl Iterator<Item = E>,
+        key: impl Fn(&E) -> anyhow::Result<EntityKey>,
+        is_class: impl Fn(&E) -> anyhow::Result<bool>,
+    ) -> anyhow::Result<Vec<E>> {
+        if let Some(cached) = &self.cached {
+            let mut verified = Vec::with_capacity(cached.len());
+            for cached_key in cached.iter() {
+                let Some(entity) = lookup(cached_key.entity_index) else {
+                    break;
+                };
+
+                if key(&entity)? != *cached_key || !is_class(&entity)? {
+                    /* slot has been reused by another entity */
+                    break;
+                }
+
+                verified.push(entity);
+            }
+
+            if verified.len() == cached.len() {
+                return Ok(verified);
+            }
+        }
+
+        let mut located = Vec::new();
+        let mut keys = Vec::new();
+        for entity in entities {
+            if !is_class(&entity)? {
+                continue;
+            }
+
+            keys.push(key(&entity)?);
+            located.push(entity);
+        }
+
+        self.full_scans += 1;
+        self.cached = if keys.is_empty() { None } else { Some(keys) };
+        Ok(located)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        CachedEntityLocator,
+        EntityClassFilter,
+        EntityKey,
+    };
+    use crate::EntityIndex;
+
+    struct PlantedC4Class;
+    impl EntityClassFilter for PlantedC4Class {
+        const CLASS_NAME: &'static str = "C_PlantedC4";
+    }
+
+    type Slot = (EntityKey, &'static str);
+
+    fn slot(entity_index: u32, serial_number: u32, class_name: &'static str) -> Slot {
+        (
+            EntityKey {
+                entity_index: EntityIndex(entity_index),
+                serial_number,
+            },
+            class_name,
+        )
+    }
+
+    fn locate(locator: &mut CachedEntityLocator<PlantedC4Class>, slots: &[Slot]) -> Vec<Slot> {
+        locator
+            .locate_with(
+                |entity_index| {
+                    slots
+                        .iter()
+                        .find(|(key, _)| key.entity_index == entity_index)
+                        .copied()
+                },
+                slots.iter().copied(),
+                |(key, _)| Ok(*key),
+                |(_, class_name)| Ok(*class_name == PlantedC4Class::CLASS_NAME),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn cached() {
+        let mut locator = CachedEntityLocator::<PlantedC4Class>::new();
+        let mut slots = vec![slot(1, 1, "CCSPlayerController"), slot(2, 1, "C_CSTeam")];
+
+        /* nothing to cache, scan every frame */
+        assert!(locate(&mut locator, &slots).is_empty());
+        assert!(locate(&mut locator, &slots).is_empty());
+        assert_eq!(locator.full_scans, 2);
+
+        slots.push(slot(200, 7, "C_PlantedC4"));
+        assert_eq!(
+            locate(&mut locator, &slots),
+            vec![slot(200, 7, "C_PlantedC4")]
+        );
+        assert_eq!(locator.full_scans, 3);
+
+        for _ in 0..10 {
+            assert_eq!(
+                locate(&mut locator, &slots),
+                vec![slot(200, 7, "C_PlantedC4")]
+            );
+        }
+        assert_eq!(locator.full_scans, 3);
+
+        locator.invalidate();
+        assert_eq!(
+            locate(&mut locator, &slots),
+            vec![slot(200, 7, "C_PlantedC4")]
+        );
+        assert_eq!(locator.full_scans, 4);
+    }
+
+    #[test]
+    fn slot_reuse() {
+        let mut locator = CachedEntityLocator::<PlantedC4Class>::new();
+        let slots = vec![slot(100, 3, "C_PlantedC4")];
+        assert_eq!(locate(&mut locator, &slots).len(), 1);
+
+        /* the bomb got removed on round restart and the slot is used by a grenade with a new serial */
+        let slots = vec![
+            slot(100, 4, "C_HEGrenadeProjectile"),
+            slot(180, 1, "C_PlantedC4"),
+        ];
+        assert_eq!(
+            locate(&mut locator, &slots),
+            vec![slot(180, 1, "C_PlantedC4")]
+        );
+        assert_eq!(
+            locator.cached().unwrap(),
+            &[EntityKey {
+                entity_index: EntityIndex(180),
+                serial_number: 1
+            }]
+        );
+
+        /* same serial but another class (e.g. torn read of the identity) */
+        let slots = vec![slot(180, 1, "C_C4")];
+        assert!(locate(&mut locator, &slots).is_empty());
+        assert!(locator.cached().is_none());
+
+        /* entity removed from the entity list */
+        let slots = vec![slot(120, 2, "C_PlantedC4")];
+        assert_eq!(locate(&mut locator, &slots).len(), 1);
+        assert!(locate(&mut locator, &[]).is_empty());
+        assert!(locator.cached().is_none());
+        assert_eq!(locator.full_scans, 5);
+    }
+}
@@ -1,8 +1,17 @@
 mod identity;
 pub use identity::*;
 
+mod index;
+pub use index::*;
+
 mod list;
 pub use list::*;
 
+mod class_check;
+pub use class_check::*;
+
+mod locator;
+pub use locator::*;
+
 mod controller;
 pub use controller::*;
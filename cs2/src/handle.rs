@@ -47,6 +47,7 @@ use crate::{
     SearchPattern,
     Signature,
     SignatureType,
+    SwappableMemoryView,
 };
 
 struct CS2MemoryView {
@@ -213,6 +214,13 @@ impl CS2Handle {
     }
 
     pub fn read_slice<T: Copy>(&self, address: u64, buffer: &mut [T]) -> anyhow::Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            address,
+            bytes = std::mem::size_of_val(buffer),
+            "memory read"
+        );
+
         Ok(self.ke_interface.read_slice(
             self.process_id,
             DirectoryTableType::Default,
@@ -367,9 +375,13 @@ impl<T: 'static + Send + Sync> DerefMut for StateVariable<T> {
 }
 
 pub type StateCS2Handle = StateVariable<Arc<CS2Handle>>;
-pub type StateCS2Memory = StateVariable<Arc<dyn MemoryView + Send + Sync>>;
+pub type StateCS2Memory = StateVariable<Arc<SwappableMemoryView>>;
 
 impl StateCS2Memory {
+    pub fn from_view(view: Arc<dyn MemoryView + Send + Sync>) -> Self {
+        Self::new(Arc::new(SwappableMemoryView::new(view)))
+    }
+
     pub fn view_arc(&self) -> Arc<dyn MemoryView> {
         self.value().clone()
     }
@@ -377,4 +389,14 @@ impl StateCS2Memory {
     pub fn view(&self) -> &dyn MemoryView {
         &**self.value()
     }
+
+    /// Redirect all views (including already handed out clones) to a new process
+    pub fn swap_view(&self, view: Arc<dyn MemoryView + Send + Sync>) -> u64 {
+        self.value().swap_backend(view)
+    }
+
+    /// See [SwappableMemoryView::epoch]
+    pub fn epoch(&self) -> u64 {
+        self.value().epoch()
+    }
 }
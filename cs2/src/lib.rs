@@ -1,41 +1,95 @@
-mod handle;
-pub use handle::*;
-
-mod signature;
-pub use signature::*;
-
-pub mod schema;
-
-mod offsets;
-pub use offsets::*;
-
-pub mod state;
-pub use state::*;
-
-mod entity;
-pub use entity::*;
-
-mod schema_gen;
-pub use schema_gen::*;
-
-mod model;
-pub use model::*;
-
-mod convar;
-pub use convar::*;
-
-mod weapon;
-pub use weapon::*;
-
-mod class_name_cache;
-pub use class_name_cache::*;
-
-mod pattern;
-pub use pattern::*;
-pub use vtd_libum::{
-    protocol::command::{
-        KeyboardState,
-        MouseState,
-    },
-    InterfaceError,
-};
+mod handle;
+pub use handle::*;
+
+mod memory_view;
+pub use memory_view::*;
+
+mod signature;
+pub use signature::*;
+
+pub mod schema;
+
+mod offsets;
+pub use offsets::*;
+
+pub mod state;
+pub use state::*;
+
+mod entity;
+pub use entity::*;
+
+mod schema_gen;
+pub use schema_gen::*;
+
+mod model;
+pub use model::*;
+
+mod convar;
+pub use convar::*;
+
+mod economy;
+pub use economy::*;
+
+mod weapon;
+pub use weapon::*;
+
+mod angles;
+pub use angles::*;
+
+pub mod damage;
+
+pub mod units;
+
+pub mod presence;
+
+pub mod diagnostics;
+
+pub mod map_data;
+
+pub mod derived;
+
+mod class_name_cache;
+pub use class_name_cache::*;
+
+mod pattern;
+pub use pattern::*;
+
+mod sampler;
+pub use sampler::*;
+
+mod prefetch;
+pub use prefetch::*;
+
+mod read_cache;
+pub use read_cache::*;
+
+mod warmup;
+pub use warmup::*;
+
+mod session;
+pub use session::*;
+
+mod snapshot;
+pub use snapshot::*;
+
+mod rewind;
+pub use rewind::*;
+
+mod demo;
+pub use demo::*;
+
+mod source;
+pub use source::*;
+
+#[cfg(feature = "serialize")]
+mod serialize;
+
+#[cfg(test)]
+mod test_fixture;
+pub use vtd_libum::{
+    protocol::command::{
+        KeyboardState,
+        MouseState,
+    },
+    InterfaceError,
+};
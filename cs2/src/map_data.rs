@@ -0,0 +1,470 @@
+//! Per map data authored by the user.
+//!
+//! The data of a map will be loaded from `<root>/<map name>/` (see [MapDataDirectory]):
+//! - `bomb_sites.json` bomb site bounds (same format as a map within `resources/bomb_site_bounds.json`)
+//! - `site_execute.json` utility requirements (same format as a map within `resources/site_execute_requirements.json`)
+//! - any other `*.json` document (e.g. calibration or callouts)
+//! - any `*.bin` file (e.g. visibility geometry)
+//!
+//! Files which fail to validate will be reported and the built-in defaults will be used instead.
+//! The directory will be polled for changes, therefore files can be edited while the tool is running.
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt,
+    fs,
+    io,
+    path::{
+        Path,
+        PathBuf,
+    },
+    sync::Arc,
+    time::{
+        Duration,
+        Instant,
+        SystemTime,
+    },
+};
+
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    parse_bomb_site_bounds,
+    parse_utility_counts,
+    BombSiteBounds,
+    StateCurrentMap,
+    UtilityCounts,
+};
+
+/// Interval in which the map data directory will be checked for changes
+pub const MAP_DATA_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A map data file which could not be loaded
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapDataError {
+    pub path: PathBuf,
+
+    /// Line (starting with 1) of the error if known
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for MapDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+impl Error for MapDataError {}
+
+/// Line of the first occurrence of a JSON key
+fn key_line(data: &str, key: &str) -> Option<usize> {
+    let key = format!("\"{}\"", key);
+    data.lines()
+        .position(|line| line.contains(&key))
+        .map(|index| index + 1)
+}
+
+fn parse_document(path: &Path, data: &str) -> Result<serde_json::Value, MapDataError> {
+    serde_json::from_str(data).map_err(|error| {
+        /* the position will be reported separately */
+        let message = error.to_string();
+        let message = message
+            .rsplit_once(" at line ")
+            .map(|(message, _)| message)
+            .unwrap_or(&message);
+
+        MapDataError {
+            path: path.to_path_buf(),
+            line: Some(error.line()),
+            message: message.to_string(),
+        }
+    })
+}
+
+/// Parse a document containing one entry per bomb site
+fn parse_sites<T>(
+    path: &Path,
+    data: &str,
+    parse: impl Fn(&str, &serde_json::Value) -> anyhow::Result<T>,
+) -> Result<BTreeMap<String, T>, MapDataError> {
+    let document = parse_document(path, data)?;
+    let Some(sites) = document.as_object() else {
+        return Err(MapDataError {
+            path: path.to_path_buf(),
+            line: Some(1),
+            message: "expected an object with one entry per bomb site".to_string(),
+        });
+    };
+
+    let mut result = BTreeMap::new();
+    for (site_name, value) in sites {
+        let entry = parse(&format!("site {}", site_name), value).map_err(|error| MapDataError {
+            path: path.to_path_buf(),
+            line: key_line(data, site_name),
+            message: format!("{:#}", error),
+        })?;
+        result.insert(site_name.clone(), entry);
+    }
+
+    Ok(result)
+}
+
+/// Authored data of a single map
+#[derive(Debug, Clone, Default)]
+pub struct MapData {
+    /// Overrides the built-in bomb site bounds (see [crate::StateBombSiteGeometry])
+    pub bomb_sites: Option<BTreeMap<String, BombSiteBounds>>,
+
+    /// Overrides the built-in utility requirements (see [crate::StateSiteExecuteRequirements])
+    pub site_execute_requirements: Option<BTreeMap<String, UtilityCounts>>,
+
+    /// All other JSON documents by their file stem
+    pub documents: BTreeMap<String, serde_json::Value>,
+
+    /// Binary files by their file stem
+    pub blobs: BTreeMap<String, Vec<u8>>,
+
+    /// Files which could not be loaded
+    pub errors: Vec<MapDataError>,
+}
+
+impl MapData {
+    /// Load all files of the map data directory.
+    /// A missing directory results in empty map data.
+    pub fn load(directory: &Path) -> Self {
+        let mut result = Self::default();
+        for path in map_data_files(directory) {
+            if let Err(error) = result.load_file(&path) {
+                result.errors.push(error);
+            }
+        }
+
+        result
+    }
+
+    fn load_file(&mut self, path: &Path) -> Result<(), MapDataError> {
+        let io_error = |error: io::Error| MapDataError {
+            path: path.to_path_buf(),
+            line: None,
+            message: error.to_string(),
+        };
+
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        if path.extension().map(|ext| ext == "bin").unwrap_or(false) {
+            let data = fs::read(path).map_err(io_error)?;
+            if data.is_empty() {
+                return Err(MapDataError {
+                    path: path.to_path_buf(),
+                    line: None,
+                    message: "file is empty".to_string(),
+                });
+            }
+
+            self.blobs.insert(stem, data);
+            return Ok(());
+        }
+
+        let data = fs::read_to_string(path).map_err(io_error)?;
+        match stem.as_str() {
+            "bomb_sites" => {
+                self.bomb_sites = Some(parse_sites(path, &data, parse_bomb_site_bounds)?);
+            }
+            "site_execute" => {
+                self.site_execute_requirements =
+                    Some(parse_sites(path, &data, parse_utility_counts)?);
+            }
+            _ => {
+                self.documents.insert(stem, parse_document(path, &data)?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// All `*.json` and `*.bin` files within the directory sorted by their path
+fn map_data_files(directory: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let mut files = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.extension()
+                .map(|ext| ext == "json" || ext == "bin")
+                .unwrap_or(false)
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+/// Modification time and size of every map data file
+type DirectoryStamp = Vec<(PathBuf, Option<SystemTime>, u64)>;
+
+fn directory_stamp(directory: &Path) -> DirectoryStamp {
+    map_data_files(directory)
+        .into_iter()
+        .map(|path| {
+            let metadata = fs::metadata(&path).ok();
+            let modified = metadata
+                .as_ref()
+                .and_then(|metadata| metadata.modified().ok());
+            let size = metadata.map(|metadata| metadata.len()).unwrap_or(0);
+            (path, modified, size)
+        })
+        .collect()
+}
+
+/// Root directory of the map data.
+/// Can be overridden using `StateRegistry::set`.
+#[derive(Debug, Clone)]
+pub struct MapDataDirectory {
+    pub root: PathBuf,
+}
+
+impl State for MapDataDirectory {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            root: PathBuf::from("maps"),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+}
+
+/// Map data of the current map.
+/// Once loaded, the data of each map will be cached and only be loaded again if its files changed.
+pub struct StateMapData {
+    current_map: Option<String>,
+    current: Arc<MapData>,
+
+    cache: BTreeMap<String, (DirectoryStamp, Arc<MapData>)>,
+    last_poll: Option<Instant>,
+
+    /// Incremented every time the data of the current map changed.
+    /// States deriving persistent values from the map data must rebuild them when it changes.
+    pub generation: u64,
+}
+
+impl StateMapData {
+    fn new() -> Self {
+        Self {
+            current_map: None,
+            current: Default::default(),
+
+            cache: Default::default(),
+            last_poll: None,
+
+            generation: 0,
+        }
+    }
+
+    /// Data of the current map (empty if no map is loaded or the map has no data)
+    pub fn data(&self) -> &Arc<MapData> {
+        &self.current
+    }
+
+    /// Check the data of the map for changes.
+    /// Returns true if the data of the current map changed.
+    fn refresh(&mut self, root: &Path, map_name: Option<&str>) -> bool {
+        let Some(map_name) = map_name else {
+            let changed = self.current_map.is_some();
+            self.current_map = None;
+            self.current = Default::default();
+            if changed {
+                self.generation += 1;
+            }
+            return changed;
+        };
+
+        let directory = root.join(map_name);
+        let stamp = directory_stamp(&directory);
+        let data = match self.cache.get(map_name) {
+            Some((cached_stamp, data)) if *cached_stamp == stamp => data.clone(),
+            _ => {
+                let data = Arc::new(MapData::load(&directory));
+                for error in data.errors.iter() {
+                    log::warn!("Failed to load map data: {}", error);
+                }
+
+                self.cache
+                    .insert(map_name.to_string(), (stamp, data.clone()));
+                data
+            }
+        };
+
+        let changed =
+            self.current_map.as_deref() != Some(map_name) || !Arc::ptr_eq(&self.current, &data);
+        self.current_map = Some(map_name.to_string());
+        self.current = data;
+        if changed {
+            self.generation += 1;
+        }
+        changed
+    }
+}
+
+impl State for StateMapData {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self::new())
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        let current_map = states.resolve::<StateCurrentMap>(())?;
+        let map_changed = current_map.current_map != self.current_map;
+        let poll_due = self
+            .last_poll
+            .map(|timestamp| timestamp.elapsed() >= MAP_DATA_POLL_INTERVAL)
+            .unwrap_or(true);
+        if !map_changed && !poll_due {
+            return Ok(());
+        }
+
+        self.last_poll = Some(Instant::now());
+        let root = states.resolve::<MapDataDirectory>(())?.root.clone();
+        self.refresh(&root, current_map.current_map.as_deref());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        fs,
+        path::PathBuf,
+        time::{
+            SystemTime,
+            UNIX_EPOCH,
+        },
+    };
+
+    use super::{
+        MapData,
+        StateMapData,
+    };
+
+    fn temp_directory(name: &str) -> PathBuf {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let directory = std::env::temp_dir().join(format!("cs2-map-data-{}-{}", name, nonce));
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    const BOMB_SITES: &str = r#"{
+    "A": { "mins": [1000, 2300, 64], "maxs": [1300, 2600, 256] },
+    "B": { "mins": [-2200, 1800, 0], "maxs": [-1800, 2200, 192] }
+}"#;
+
+    #[test]
+    fn malformed() {
+        let root = temp_directory("malformed");
+        let map = root.join("de_test");
+        fs::create_dir_all(&map).unwrap();
+
+        /* missing the z coordinate of the B maxs */
+        fs::write(
+            map.join("bomb_sites.json"),
+            BOMB_SITES.replace("[-1800, 2200, 192]", "[-1800, 2200]"),
+        )
+        .unwrap();
+        /* trailing comma */
+        fs::write(map.join("callouts.json"), "{\n    \"long\": \"Long A\",\n}").unwrap();
+        fs::write(map.join("site_execute.json"), r#"{ "A": { "smokes": 3 } }"#).unwrap();
+        fs::write(map.join("notes.txt"), "ignored").unwrap();
+
+        let data = MapData::load(&map);
+        assert!(data.bomb_sites.is_none());
+        assert!(data.documents.is_empty());
+        assert_eq!(data.site_execute_requirements.unwrap()["A"].smokes, 3);
+
+        assert_eq!(data.errors.len(), 2);
+        let error = &data.errors[0];
+        assert_eq!(error.path, map.join("bomb_sites.json"));
+        assert_eq!(error.line, Some(3));
+        assert!(
+            error.message.contains("invalid maxs for site B"),
+            "{}",
+            error
+        );
+
+        let error = &data.errors[1];
+        assert_eq!(error.path, map.join("callouts.json"));
+        assert_eq!(error.line, Some(3));
+        assert!(
+            error
+                .to_string()
+                .contains("callouts.json:3: trailing comma"),
+            "{}",
+            error
+        );
+
+        /* missing map directory */
+        let data = MapData::load(&root.join("de_missing"));
+        assert!(data.errors.is_empty() && data.bomb_sites.is_none());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn hot_reload() {
+        let root = temp_directory("reload");
+        let map = root.join("de_test");
+        fs::create_dir_all(&map).unwrap();
+        fs::write(map.join("bomb_sites.json"), "{ \"A\": ").unwrap();
+
+        let mut state = StateMapData::new();
+        assert!(state.refresh(&root, Some("de_test")));
+        assert!(state.data().bomb_sites.is_none());
+        assert_eq!(state.data().errors.len(), 1);
+
+        /* nothing changed */
+        assert!(!state.refresh(&root, Some("de_test")));
+        assert_eq!(state.generation, 1);
+
+        fs::write(map.join("bomb_sites.json"), BOMB_SITES).unwrap();
+        assert!(state.refresh(&root, Some("de_test")));
+        assert_eq!(state.data().bomb_sites.as_ref().unwrap().len(), 2);
+        assert!(state.data().errors.is_empty());
+
+        /* the data of the previous map will be reused */
+        assert!(state.refresh(&root, Some("de_other")));
+        assert!(state.data().bomb_sites.is_none());
+        assert!(state.refresh(&root, Some("de_test")));
+        assert_eq!(state.data().bomb_sites.as_ref().unwrap().len(), 2);
+        assert_eq!(state.generation, 4);
+
+        assert!(state.refresh(&root, None));
+        assert!(state.data().bomb_sites.is_none());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
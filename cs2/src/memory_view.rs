@@ -0,0 +1,155 @@
+use std::{
+    error::Error,
+    sync::Arc,
+};
+
+use arc_swap::ArcSwap;
+use raw_struct::MemoryView;
+
+struct MemoryBackend {
+    view: Arc<dyn MemoryView + Send + Sync>,
+
+    /// Epoch of the view. Stored alongside the view so both are replaced at once.
+    epoch: u64,
+}
+
+/// Backend of a [SwappableMemoryView] while not attached to any process
+pub struct DetachedMemoryView;
+
+impl MemoryView for DetachedMemoryView {
+    fn read_memory(
+        &self,
+        _offset: u64,
+        _buffer: &mut [u8],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        Err("not attached to any process".into())
+    }
+}
+
+/// Memory view which forwards all reads to a swappable backend.
+///
+/// All clones of the view share the same backend. Replacing the backend (e.g. after re-attaching to CS2)
+/// therefore redirects all existing clones to the new process.
+/// The epoch is incremented every time the backend has been replaced.
+pub struct SwappableMemoryView {
+    backend: ArcSwap<MemoryBackend>,
+}
+
+impl SwappableMemoryView {
+    pub fn new(view: Arc<dyn MemoryView + Send + Sync>) -> Self {
+        Self {
+            backend: ArcSwap::from_pointee(MemoryBackend { view, epoch: 0 }),
+        }
+    }
+
+    /// Create a view which is not attached to any process
+    pub fn detached() -> Self {
+        Self::new(Arc::new(DetachedMemoryView))
+    }
+
+    /// Replace the underlying view and return the new epoch.
+    /// Reads which are currently in progress will finish using the previous view.
+    pub fn swap_backend(&self, view: Arc<dyn MemoryView + Send + Sync>) -> u64 {
+        let previous = self.backend.rcu(|current| MemoryBackend {
+            view: view.clone(),
+            epoch: current.epoch + 1,
+        });
+        previous.epoch + 1
+    }
+
+    /// Amount of times the backend has been replaced.
+    /// States caching process specific values (e.g. addresses) should reset when the epoch changes.
+    pub fn epoch(&self) -> u64 {
+        self.backend.load().epoch
+    }
+}
+
+impl MemoryView for SwappableMemoryView {
+    fn read_memory(
+        &self,
+        offset: u64,
+        buffer: &mut [u8],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.backend.load().view.read_memory(offset, buffer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        error::Error,
+        sync::Arc,
+        thread,
+    };
+
+    use raw_struct::MemoryView;
+
+    use super::{
+        DetachedMemoryView,
+        SwappableMemoryView,
+    };
+
+    /// Backend which fills every read with a constant byte
+    struct FillBackend(u8);
+
+    impl MemoryView for FillBackend {
+        fn read_memory(
+            &self,
+            _offset: u64,
+            buffer: &mut [u8],
+        ) -> Result<(), Box<dyn Error + Send + Sync>> {
+            buffer.fill(self.0);
+            Ok(())
+        }
+    }
+
+    fn read_byte(view: &dyn MemoryView) -> Option<u8> {
+        let mut buffer = [0u8; 1];
+        view.read_memory(0x1000, &mut buffer)
+            .ok()
+            .map(|_| buffer[0])
+    }
+
+    #[test]
+    fn backend_swap() {
+        let view = Arc::new(SwappableMemoryView::new(Arc::new(FillBackend(1))));
+        let old_clone: Arc<dyn MemoryView + Send + Sync> = view.clone();
+        assert_eq!(view.epoch(), 0);
+        assert_eq!(read_byte(&*old_clone), Some(1));
+
+        /* the game has been closed */
+        assert_eq!(view.swap_backend(Arc::new(DetachedMemoryView)), 1);
+        assert_eq!(read_byte(&*old_clone), None);
+
+        /* re-attached to the new process */
+        assert_eq!(view.swap_backend(Arc::new(FillBackend(2))), 2);
+        assert_eq!(read_byte(&*old_clone), Some(2));
+        assert_eq!(read_byte(&*view), Some(2));
+        assert_eq!(view.epoch(), 2);
+    }
+
+    #[test]
+    fn concurrent_swaps() {
+        let view = Arc::new(SwappableMemoryView::detached());
+        let swappers = (0..4u8)
+            .map(|index| {
+                let view = view.clone();
+                thread::spawn(move || {
+                    (0..1000)
+                        .map(|_| view.swap_backend(Arc::new(FillBackend(index))))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut epochs = swappers
+            .into_iter()
+            .flat_map(|swapper| swapper.join().unwrap())
+            .collect::<Vec<_>>();
+        epochs.sort_unstable();
+
+        /* every swap resulted in its own epoch */
+        assert_eq!(epochs, (1..=4000).collect::<Vec<_>>());
+        assert_eq!(view.epoch(), 4000);
+    }
+}
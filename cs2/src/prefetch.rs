@@ -0,0 +1,545 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    ops::Range,
+    sync::{
+        atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        Arc,
+        OnceLock,
+    },
+};
+
+use arc_swap::ArcSwap;
+use cs2_schema_generated::cs2::layouts::SCHEMA_CLASS_LAYOUTS;
+use raw_struct::MemoryView;
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    client_class_inherits_from,
+    CEntityIdentityEx,
+    StateCS2Handle,
+    StateEntityClassIndex,
+    StateEntityList,
+    StateVariable,
+};
+
+/// Ranges which are separated by at most this amount of bytes will be read at once
+pub const PREFETCH_MAX_GAP: u64 = 0x200;
+
+/// Entity classes which will be prefetched
+const PREFETCH_CLASSES: &[&str] = &[
+    "C_CSPlayerPawn",
+    "CCSPlayerController",
+    "C_C4",
+    "C_PlantedC4",
+    "C_CSGameRulesProxy",
+];
+
+/// Base class of all weapon entities (firearms, knives, grenades, ...) which will be prefetched
+const PREFETCH_WEAPON_BASE_CLASS: &str = "C_CSWeaponBase";
+
+/// Instance size of a client class within the schema dump
+fn schema_class_size(class_name: &str) -> Option<u64> {
+    static CLASS_SIZES: OnceLock<HashMap<&'static str, u64>> = OnceLock::new();
+    CLASS_SIZES
+        .get_or_init(|| {
+            SCHEMA_CLASS_LAYOUTS
+                .iter()
+                .filter(|layout| layout.module == "client.dll")
+                .map(|layout| (layout.class_name, layout.class_size))
+                .collect()
+        })
+        .get(class_name)
+        .copied()
+}
+
+/// Size of the instance to prefetch for the given entity class.
+/// Returns None if entities of this class should not be prefetched.
+pub fn prefetch_class_size(class_name: &str) -> Option<u64> {
+    if PREFETCH_CLASSES.contains(&class_name) {
+        return schema_class_size(class_name);
+    }
+
+    if client_class_inherits_from(class_name, PREFETCH_WEAPON_BASE_CLASS) {
+        return schema_class_size(class_name);
+    }
+
+    None
+}
+
+/// Address ranges which should be read at the beginning of a frame
+#[derive(Debug, Clone, Default)]
+pub struct ReadPlan {
+    ranges: Vec<Range<u64>>,
+}
+
+impl ReadPlan {
+    pub fn push(&mut self, address: u64, size: u64) {
+        if size == 0 {
+            return;
+        }
+
+        self.ranges.push(address..address.saturating_add(size));
+    }
+
+    /// Merge overlapping ranges and ranges which are at most `max_gap` bytes apart
+    pub fn coalesce(&mut self, max_gap: u64) {
+        self.ranges.sort_by_key(|range| range.start);
+
+        let mut result: Vec<Range<u64>> = Vec::with_capacity(self.ranges.len());
+        for range in self.ranges.drain(..) {
+            match result.last_mut() {
+                Some(last) if range.start <= last.end.saturating_add(max_gap) => {
+                    last.end = last.end.max(range.end);
+                }
+                _ => result.push(range),
+            }
+        }
+
+        self.ranges = result;
+    }
+
+    pub fn ranges(&self) -> &[Range<u64>] {
+        &self.ranges
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.ranges
+            .iter()
+            .map(|range| range.end - range.start)
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrefetchStatistics {
+    /// Amount of (coalesced) ranges within the read plan
+    pub plan_ranges: usize,
+
+    /// Bytes read into the snapshot
+    pub bytes_read: u64,
+
+    /// Ranges which could not be read and will be read live instead
+    pub failed_ranges: usize,
+}
+
+#[derive(Default)]
+struct PrefetchSnapshot {
+    /// (start address, memory) sorted by the start address
+    regions: Vec<(u64, Vec<u8>)>,
+}
+
+impl PrefetchSnapshot {
+    fn read(&self, offset: u64, buffer: &mut [u8]) -> bool {
+        let index = self.regions.partition_point(|(start, _)| *start <= offset);
+        let Some((start, memory)) = index.checked_sub(1).map(|index| &self.regions[index]) else {
+            return false;
+        };
+
+        let begin = (offset - start) as usize;
+        let Some(source) = memory.get(begin..begin + buffer.len()) else {
+            /* read is not fully covered by the snapshot */
+            return false;
+        };
+
+        buffer.copy_from_slice(source);
+        true
+    }
+}
+
+/// Memory view serving reads from a snapshot captured at the beginning of the frame.
+/// Reads which are not covered by the snapshot are forwarded to the live view.
+pub struct PrefetchMemoryView {
+    live: Arc<dyn MemoryView + Send + Sync>,
+    snapshot: ArcSwap<PrefetchSnapshot>,
+
+    snapshot_reads: AtomicU64,
+    live_reads: AtomicU64,
+}
+
+impl PrefetchMemoryView {
+    pub fn new(live: Arc<dyn MemoryView + Send + Sync>) -> Self {
+        Self {
+            live,
+            snapshot: Default::default(),
+
+            snapshot_reads: AtomicU64::new(0),
+            live_reads: AtomicU64::new(0),
+        }
+    }
+
+    /// Drop the current snapshot and forward all reads to the live view
+    pub fn clear(&self) {
+        self.snapshot.store(Default::default());
+    }
+
+    /// Read all ranges of the plan and replace the current snapshot
+    pub fn execute(&self, plan: &ReadPlan) -> PrefetchStatistics {
+        let mut statistics = PrefetchStatistics {
+            plan_ranges: plan.ranges().len(),
+            ..Default::default()
+        };
+
+        let mut snapshot = PrefetchSnapshot {
+            regions: Vec::with_capacity(plan.ranges().len()),
+        };
+        for range in plan.ranges() {
+            let mut memory = vec![0u8; (range.end - range.start) as usize];
+            if self.live.read_memory(range.start, &mut memory).is_err() {
+                statistics.failed_ranges += 1;
+                continue;
+            }
+
+            statistics.bytes_read += memory.len() as u64;
+            snapshot.regions.push((range.start, memory));
+        }
+
+        snapshot.regions.sort_by_key(|(start, _)| *start);
+        self.snapshot.store(Arc::new(snapshot));
+        statistics
+    }
+
+    /// Total amount of reads served by the snapshot and the live view
+    pub fn read_counts(&self) -> (u64, u64) {
+        (
+            self.snapshot_reads.load(Ordering::Relaxed),
+            self.live_reads.load(Ordering::Relaxed),
+        )
+    }
+}
+
+impl MemoryView for PrefetchMemoryView {
+    fn read_memory(
+        &self,
+        offset: u64,
+        buffer: &mut [u8],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.snapshot.load().read(offset, buffer) {
+            self.snapshot_reads.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        }
+
+        self.live_reads.fetch_add(1, Ordering::Relaxed);
+        self.live.read_memory(offset, buffer)
+    }
+}
+
+pub type StatePrefetchView = StateVariable<Arc<PrefetchMemoryView>>;
+
+/// Build the read plan for all entities of the prefetched classes
+pub fn build_frame_read_plan(states: &StateRegistry) -> anyhow::Result<ReadPlan> {
+    let entities = states.resolve::<StateEntityList>(())?;
+    let class_index = states.resolve::<StateEntityClassIndex>(())?;
+
+    let mut plan = ReadPlan::default();
+    for (class_name, positions) in class_index.classes() {
+        let Some(size) = prefetch_class_size(class_name) else {
+            continue;
+        };
+
+        for identity in positions
+            .iter()
+            .filter_map(|position| entities.entities().get(*position))
+        {
+            plan.push(identity.entity_ptr::<()>()?.address, size);
+        }
+    }
+
+    plan.coalesce(PREFETCH_MAX_GAP);
+    Ok(plan)
+}
+
+/// Amount of frames after which the [StatePrefetchMetrics] will emit a metrics record
+const PREFETCH_METRICS_INTERVAL: u64 = 10_000;
+
+/// Prefetch statistics accumulated since the last metrics record
+#[derive(Debug, Default)]
+pub struct StatePrefetchMetrics {
+    frames: u64,
+    plan_ranges: u64,
+    bytes_read: u64,
+    failed_ranges: u64,
+}
+
+impl State for StatePrefetchMetrics {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Default::default())
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+}
+
+impl StatePrefetchMetrics {
+    /// Accumulate the statistics of a frame.
+    /// Returns the metrics record payload once [PREFETCH_METRICS_INTERVAL] frames have been accumulated.
+    pub fn push(&mut self, statistics: &PrefetchStatistics) -> Option<String> {
+        self.frames += 1;
+        self.plan_ranges += statistics.plan_ranges as u64;
+        self.bytes_read += statistics.bytes_read;
+        self.failed_ranges += statistics.failed_ranges as u64;
+
+        if self.frames < PREFETCH_METRICS_INTERVAL {
+            return None;
+        }
+
+        let payload = format!(
+            "frames: {}, avg plan ranges: {}, avg bytes read: {}, failed ranges: {}",
+            self.frames,
+            self.plan_ranges / self.frames,
+            self.bytes_read / self.frames,
+            self.failed_ranges
+        );
+        *self = Default::default();
+        Some(payload)
+    }
+}
+
+/// Captures the snapshot of the [StatePrefetchView] for the current frame.
+/// Must be resolved right after invalidating the states so all following states read from the snapshot.
+/// Note: Readers outside of the frame (e.g. the [crate::LocalPlayerSampler]) should use a live memory view.
+pub struct StateFramePrefetch {
+    pub statistics: PrefetchStatistics,
+}
+
+impl State for StateFramePrefetch {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let view = states.resolve::<StatePrefetchView>(())?;
+
+        /* the entity list and class names must be read live */
+        view.clear();
+        let plan = build_frame_read_plan(states)?;
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            ranges = plan.ranges().len(),
+            bytes = plan.total_bytes(),
+            "prefetch plan"
+        );
+
+        let statistics = view.execute(&plan);
+        let metrics_payload = states
+            .resolve_mut::<StatePrefetchMetrics>(())?
+            .push(&statistics);
+        if let Some(payload) = metrics_payload {
+            states
+                .resolve::<StateCS2Handle>(())?
+                .add_metrics_record("cs2-prefetch", &payload);
+        }
+
+        Ok(Self { statistics })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        error::Error,
+        sync::{
+            atomic::{
+                AtomicU64,
+                Ordering,
+            },
+            Arc,
+        },
+    };
+
+    use raw_struct::MemoryView;
+
+    use super::{
+        prefetch_class_size,
+        PrefetchMemoryView,
+        ReadPlan,
+        StateFramePrefetch,
+        StatePrefetchView,
+    };
+    use crate::{
+        test_fixture::{
+            match_fixture,
+            resolve_match_players,
+            setup_dump_schema,
+            MATCH_WEAPONS,
+        },
+        PlayerPawnState,
+    };
+
+    /// Deterministic fake process memory where every byte depends on its address.
+    /// Reads above `limit` fail.
+    struct FixtureMemory {
+        limit: u64,
+        reads: AtomicU64,
+    }
+
+    impl FixtureMemory {
+        fn byte(address: u64) -> u8 {
+            (address.wrapping_mul(31) ^ (address >> 8)) as u8
+        }
+    }
+
+    impl MemoryView for FixtureMemory {
+        fn read_memory(
+            &self,
+            offset: u64,
+            buffer: &mut [u8],
+        ) -> Result<(), Box<dyn Error + Send + Sync>> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            if offset + buffer.len() as u64 > self.limit {
+                return Err("invalid address".into());
+            }
+
+            for (index, value) in buffer.iter_mut().enumerate() {
+                *value = Self::byte(offset + index as u64);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn coalesce() {
+        let mut plan = ReadPlan::default();
+        plan.push(0x5000, 0x100);
+        plan.push(0x1000, 0x100);
+        plan.push(0x1080, 0x100);
+        plan.push(0x1200, 0x10);
+        plan.push(0x1400, 0x10);
+        plan.push(0x2000, 0);
+        plan.coalesce(0x80);
+
+        assert_eq!(
+            plan.ranges(),
+            &[0x1000..0x1210, 0x1400..0x1410, 0x5000..0x5100]
+        );
+        assert_eq!(plan.total_bytes(), 0x210 + 0x10 + 0x100);
+    }
+
+    #[test]
+    fn snapshot_equals_live() {
+        let live = Arc::new(FixtureMemory {
+            limit: 0x10_0000,
+            reads: AtomicU64::new(0),
+        });
+        let view = PrefetchMemoryView::new(live.clone());
+
+        let mut plan = ReadPlan::default();
+        /* entity instances, one of them can not be read */
+        for address in [0x1000, 0x1500, 0x4000, 0x20_0000] {
+            plan.push(address, 0x400);
+        }
+        plan.coalesce(0x200);
+
+        let statistics = view.execute(&plan);
+        assert_eq!(statistics.plan_ranges, 3);
+        assert_eq!(statistics.bytes_read, 0x900 + 0x400);
+        assert_eq!(statistics.failed_ranges, 1);
+        assert_eq!(live.reads.load(Ordering::Relaxed), 3);
+
+        /* covered, partially covered and uncovered reads */
+        let reads = [
+            (0x1000, 8),
+            (0x1450, 16),
+            (0x1A00, 0x200),
+            (0x18FC, 8),
+            (0x4100, 4),
+            (0x43FF, 1),
+            (0x8000, 16),
+            (0x20_0000, 4),
+        ];
+        for (address, length) in reads {
+            let mut expected = vec![0u8; length];
+            let expected_result = live.read_memory(address, &mut expected).is_ok();
+
+            let mut actual = vec![0u8; length];
+            assert_eq!(
+                view.read_memory(address, &mut actual).is_ok(),
+                expected_result,
+                "{:X}",
+                address
+            );
+            assert_eq!(actual, expected, "{:X}", address);
+        }
+
+        let (snapshot_reads, live_reads) = view.read_counts();
+        assert_eq!(snapshot_reads, 4);
+        assert_eq!(live_reads, 4);
+
+        view.clear();
+        let mut buffer = [0u8; 8];
+        view.read_memory(0x1000, &mut buffer).unwrap();
+        assert_eq!(view.read_counts(), (4, 5));
+    }
+
+    #[test]
+    fn class_sizes() {
+        assert_eq!(prefetch_class_size("C_CSPlayerPawn"), Some(0x3F20));
+        assert_eq!(prefetch_class_size("C_PlantedC4"), Some(0x16F0));
+        assert_eq!(prefetch_class_size("C_WeaponAWP"), Some(0x1FC0));
+
+        /* weapon classes without the `C_Weapon` prefix */
+        assert_eq!(prefetch_class_size("C_AK47"), Some(0x1FC0));
+        assert_eq!(prefetch_class_size("C_DEagle"), Some(0x1FC0));
+        assert_eq!(prefetch_class_size("C_Knife"), Some(0x1FA0));
+        assert_eq!(prefetch_class_size("C_HEGrenade"), Some(0x2050));
+        assert_eq!(prefetch_class_size("C_SmokeGrenade"), Some(0x2050));
+
+        /* classes unknown to the schema dump can not be identified as weapons */
+        assert_eq!(prefetch_class_size("C_WeaponUnreleased"), None);
+        assert_eq!(prefetch_class_size("C_Chicken"), None);
+        assert_eq!(prefetch_class_size("C_SmokeGrenadeProjectile"), None);
+    }
+
+    #[test]
+    fn states_equal_live() {
+        setup_dump_schema();
+
+        const PLAYERS: usize = 10;
+        let fixture = match_fixture(PLAYERS);
+
+        let live_states = fixture.clone().into_states();
+        let live_players = resolve_match_players(&live_states, PLAYERS).unwrap();
+        assert!(live_players
+            .iter()
+            .all(
+                |(pawn_state, equipment)| *pawn_state == PlayerPawnState::Alive
+                    && equipment.weapons.len() == MATCH_WEAPONS.len()
+            ));
+
+        let view = Arc::new(PrefetchMemoryView::new(Arc::new(fixture.memory.clone())));
+        let mut states = fixture.states(view.clone());
+        states
+            .set(StatePrefetchView::new(view.clone()), ())
+            .unwrap();
+
+        let statistics = states.resolve::<StateFramePrefetch>(()).unwrap().statistics;
+        /* pawn, controller and weapons of every player */
+        assert_eq!(statistics.plan_ranges, PLAYERS * (2 + MATCH_WEAPONS.len()));
+        assert_eq!(
+            statistics.bytes_read,
+            PLAYERS as u64 * (0x3F20 + 0x948 + MATCH_WEAPONS.len() as u64 * 0x1FC0)
+        );
+        assert_eq!(statistics.failed_ranges, 0);
+
+        let (snapshot_reads, _live_reads) = view.read_counts();
+        let prefetched_players = resolve_match_players(&states, PLAYERS).unwrap();
+        assert_eq!(prefetched_players, live_players);
+
+        /* the pawn and weapon fields have been served by the snapshot */
+        let (prefetched_snapshot_reads, _live_reads) = view.read_counts();
+        assert!(prefetched_snapshot_reads - snapshot_reads >= (PLAYERS * 4) as u64);
+    }
+}
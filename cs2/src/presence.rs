@@ -0,0 +1,354 @@
+//! Discord Rich Presence payload builder.
+//! Sending the payload to Discord is left to the consumer.
+
+use std::time::{
+    Duration,
+    Instant,
+};
+
+use utils_state::StateRegistry;
+
+use crate::{
+    MatchContext,
+    PlantedC4,
+    PlantedC4State,
+    StateCurrentMap,
+};
+
+/// Max length (in characters) Discord accepts for a presence text field
+pub const PRESENCE_FIELD_MAX_LENGTH: usize = 128;
+
+/// Min length (in characters) Discord accepts for a presence text field
+pub const PRESENCE_FIELD_MIN_LENGTH: usize = 2;
+
+/// Discord only allows one presence update every 15 seconds
+pub const PRESENCE_UPDATE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Values available to the presence templates
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PresenceSnapshot {
+    pub map: Option<String>,
+    pub round_number: Option<i32>,
+
+    pub score_terrorists: Option<i32>,
+    pub score_counter_terrorists: Option<i32>,
+
+    pub alive_terrorists: Option<u32>,
+    pub alive_counter_terrorists: Option<u32>,
+
+    /// Seconds until the planted bomb detonates
+    pub bomb_time_remaining: Option<f32>,
+}
+
+impl PresenceSnapshot {
+    pub fn read(states: &StateRegistry) -> anyhow::Result<Self> {
+        let context = states.resolve::<MatchContext>(())?;
+        let map = states
+            .resolve::<StateCurrentMap>(())
+            .ok()
+            .and_then(|map| map.current_map.clone());
+
+        let bomb_time_remaining =
+            states
+                .resolve::<PlantedC4>(())
+                .ok()
+                .and_then(|planted_c4| match planted_c4.state {
+                    PlantedC4State::Active { time_detonation } => Some(time_detonation),
+                    _ => None,
+                });
+
+        Ok(Self {
+            map,
+            round_number: context.round_number,
+
+            score_terrorists: context.score_terrorists,
+            score_counter_terrorists: context.score_counter_terrorists,
+
+            alive_terrorists: context.alive_terrorists,
+            alive_counter_terrorists: context.alive_counter_terrorists,
+
+            bomb_time_remaining,
+        })
+    }
+
+    /// Resolve a template placeholder.
+    /// Returns None for unknown placeholders.
+    pub fn placeholder(&self, name: &str) -> Option<String> {
+        fn or_unknown<T: ToString>(value: Option<T>) -> String {
+            value
+                .map(|value| value.to_string())
+                .unwrap_or_else(|| "?".to_string())
+        }
+
+        let value = match name {
+            "map" => self.map.clone().unwrap_or_else(|| "?".to_string()),
+            "round" => or_unknown(self.round_number),
+            "score_t" => or_unknown(self.score_terrorists),
+            "score_ct" => or_unknown(self.score_counter_terrorists),
+            "alive_t" => or_unknown(self.alive_terrorists),
+            "alive_ct" => or_unknown(self.alive_counter_terrorists),
+            "bomb_time" => self
+                .bomb_time_remaining
+                .map(format_countdown)
+                .unwrap_or_else(|| "?".to_string()),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+}
+
+/// Format seconds as `m:ss`
+pub fn format_countdown(seconds: f32) -> String {
+    let seconds = seconds.max(0.0).ceil() as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Substitute all `{name}` placeholders within the template.
+/// `{{` and `}}` can be used to insert literal braces.
+/// Unknown placeholders are kept as they are.
+pub fn render_template(template: &str, resolve: impl Fn(&str) -> Option<String>) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut remaining = template;
+
+    while let Some(index) = remaining.find(['{', '}']) {
+        result.push_str(&remaining[..index]);
+        remaining = &remaining[index..];
+
+        if remaining.starts_with("{{") || remaining.starts_with("}}") {
+            result.push_str(&remaining[..1]);
+            remaining = &remaining[2..];
+            continue;
+        }
+
+        if remaining.starts_with('{') {
+            if let Some(end) = remaining.find('}') {
+                let name = &remaining[1..end];
+                match resolve(name) {
+                    Some(value) => result.push_str(&value),
+                    None => result.push_str(&remaining[..=end]),
+                }
+
+                remaining = &remaining[end + 1..];
+                continue;
+            }
+        }
+
+        result.push_str(&remaining[..1]);
+        remaining = &remaining[1..];
+    }
+
+    result.push_str(remaining);
+    result
+}
+
+/// Enforce the Discord field length limits.
+/// Values which are too long will be truncated with an ellipsis, values which are too short will be padded.
+pub fn limit_field(value: &str) -> String {
+    let value = value.trim();
+    let length = value.chars().count();
+
+    if length > PRESENCE_FIELD_MAX_LENGTH {
+        let mut result = value
+            .chars()
+            .take(PRESENCE_FIELD_MAX_LENGTH - 1)
+            .collect::<String>()
+            .trim_end()
+            .to_string();
+        result.push('…');
+        result
+    } else if length < PRESENCE_FIELD_MIN_LENGTH {
+        format!("{:<width$}", value, width = PRESENCE_FIELD_MIN_LENGTH)
+    } else {
+        value.to_string()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PresenceTemplates {
+    pub details: String,
+    pub state: String,
+
+    /// Replaces the state template while the bomb is planted
+    pub state_bomb_planted: String,
+
+    pub large_image_text: String,
+}
+
+impl Default for PresenceTemplates {
+    fn default() -> Self {
+        Self {
+            details: "{map} | {score_t} - {score_ct}".to_string(),
+            state: "{alive_t} vs {alive_ct} alive".to_string(),
+            state_bomb_planted: "Bomb planted – {bomb_time}".to_string(),
+            large_image_text: "{map} – Round {round}".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresencePayload {
+    pub details: String,
+    pub state: String,
+    pub large_image_text: String,
+}
+
+/// Builds presence payloads from snapshots while respecting the Discord rate limit.
+pub struct PresenceBuilder {
+    pub templates: PresenceTemplates,
+    pub update_interval: Duration,
+
+    /// (time, last emitted payload)
+    last_update: Option<(Instant, PresencePayload)>,
+}
+
+impl PresenceBuilder {
+    pub fn new(templates: PresenceTemplates) -> Self {
+        Self {
+            templates,
+            update_interval: PRESENCE_UPDATE_INTERVAL,
+            last_update: None,
+        }
+    }
+
+    /// Render the payload for the snapshot without taking the rate limit into account
+    pub fn build(&self, snapshot: &PresenceSnapshot) -> PresencePayload {
+        let render = |template: &str| {
+            limit_field(&render_template(template, |name| {
+                snapshot.placeholder(name)
+            }))
+        };
+
+        let state = if snapshot.bomb_time_remaining.is_some() {
+            &self.templates.state_bomb_planted
+        } else {
+            &self.templates.state
+        };
+
+        PresencePayload {
+            details: render(&self.templates.details),
+            state: render(state),
+            large_image_text: render(&self.templates.large_image_text),
+        }
+    }
+
+    /// Returns the payload which should be sent to Discord.
+    /// None if the presence did not change or the last update is too recent.
+    /// Changes suppressed by the rate limit will be returned with the next call after the interval elapsed.
+    pub fn update(&mut self, snapshot: &PresenceSnapshot, now: Instant) -> Option<PresencePayload> {
+        let payload = self.build(snapshot);
+        if let Some((last_time, last_payload)) = &self.last_update {
+            if *last_payload == payload {
+                return None;
+            }
+
+            if now.saturating_duration_since(*last_time) < self.update_interval {
+                return None;
+            }
+        }
+
+        self.last_update = Some((now, payload.clone()));
+        Some(payload)
+    }
+
+    /// Forget the last emitted payload (e.g. after reconnecting to Discord)
+    pub fn reset(&mut self) {
+        self.last_update = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{
+        Duration,
+        Instant,
+    };
+
+    use super::{
+        format_countdown,
+        limit_field,
+        render_template,
+        PresenceBuilder,
+        PresenceSnapshot,
+        PRESENCE_FIELD_MAX_LENGTH,
+    };
+
+    fn snapshot() -> PresenceSnapshot {
+        PresenceSnapshot {
+            map: Some("de_mirage".to_string()),
+            round_number: Some(14),
+            score_terrorists: Some(7),
+            score_counter_terrorists: Some(6),
+            alive_terrorists: Some(3),
+            alive_counter_terrorists: Some(2),
+            bomb_time_remaining: None,
+        }
+    }
+
+    #[test]
+    fn templates() {
+        let snapshot = snapshot();
+        let render = |template: &str| render_template(template, |name| snapshot.placeholder(name));
+
+        assert_eq!(
+            render("{map} | {score_t} - {score_ct}"),
+            "de_mirage | 7 - 6"
+        );
+        assert_eq!(render("{{map}} {unknown}"), "{map} {unknown}");
+        assert_eq!(render("open {map"), "open {map");
+        assert_eq!(render("Bomb – {bomb_time}"), "Bomb – ?");
+
+        assert_eq!(format_countdown(23.2), "0:24");
+        assert_eq!(format_countdown(40.0), "0:40");
+        assert_eq!(format_countdown(-1.0), "0:00");
+    }
+
+    #[test]
+    fn field_limits() {
+        assert_eq!(limit_field("a"), "a ");
+        assert_eq!(limit_field("  de_dust2 "), "de_dust2");
+
+        let long = "ä".repeat(PRESENCE_FIELD_MAX_LENGTH + 10);
+        let limited = limit_field(&long);
+        assert_eq!(limited.chars().count(), PRESENCE_FIELD_MAX_LENGTH);
+        assert!(limited.ends_with('…'));
+    }
+
+    #[test]
+    fn rate_limit() {
+        let mut builder = PresenceBuilder::new(Default::default());
+        let start = Instant::now();
+
+        let mut snapshot = snapshot();
+        let payload = builder.update(&snapshot, start).unwrap();
+        assert_eq!(payload.details, "de_mirage | 7 - 6");
+        assert_eq!(payload.state, "3 vs 2 alive");
+
+        /* nothing changed */
+        assert_eq!(
+            builder.update(&snapshot, start + Duration::from_secs(5)),
+            None
+        );
+
+        /* changes within the interval are delayed */
+        snapshot.bomb_time_remaining = Some(23.0);
+        assert_eq!(
+            builder.update(&snapshot, start + Duration::from_secs(10)),
+            None
+        );
+
+        let payload = builder
+            .update(&snapshot, start + Duration::from_secs(15))
+            .unwrap();
+        assert_eq!(payload.state, "Bomb planted – 0:23");
+
+        snapshot.bomb_time_remaining = Some(22.0);
+        assert_eq!(
+            builder.update(&snapshot, start + Duration::from_secs(16)),
+            None
+        );
+        assert!(builder
+            .update(&snapshot, start + Duration::from_secs(30))
+            .is_some());
+    }
+}
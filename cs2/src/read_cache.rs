@@ -0,0 +1,370 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::{
+        atomic::{
+            AtomicBool,
+            Ordering,
+        },
+        Arc,
+        Mutex,
+        MutexGuard,
+        PoisonError,
+    },
+};
+
+use raw_struct::MemoryView;
+use utils_state::StateHeartbeat;
+
+use crate::StateVariable;
+
+/// Frame of a state registry (see [StateHeartbeat::frame])
+pub type FrameId = u64;
+
+/// Upper bound of the bytes cached within a single frame.
+/// Reads exceeding the bound will be forwarded to the backend without being cached.
+pub const READ_CACHE_MAX_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadCacheStatistics {
+    pub frame: FrameId,
+
+    /// Reads served by the cache
+    pub hits: u64,
+
+    /// Reads forwarded to the backend (including failed reads which will not be cached)
+    pub misses: u64,
+
+    /// Reads forwarded to the backend while the cache is disabled
+    pub bypassed: u64,
+
+    /// Distinct (address, length) entries and their total size
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+impl ReadCacheStatistics {
+    /// Amount of reads which have been forwarded to the backend
+    pub fn backend_reads(&self) -> u64 {
+        self.misses + self.bypassed
+    }
+
+    /// Fraction (0.0 to 1.0) of all reads served by the cache
+    pub fn hit_rate(&self) -> f32 {
+        let total = self.hits + self.backend_reads();
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f32 / total as f32
+        }
+    }
+}
+
+#[derive(Default)]
+struct FrameEntries {
+    entries: HashMap<(u64, usize), Box<[u8]>>,
+    statistics: ReadCacheStatistics,
+
+    /// Statistics of the previous frame
+    last_frame: ReadCacheStatistics,
+}
+
+impl FrameEntries {
+    fn enter_frame(&mut self, frame: FrameId) {
+        if self.statistics.frame == frame {
+            return;
+        }
+
+        self.entries.clear();
+        self.last_frame = self.statistics;
+        self.statistics = ReadCacheStatistics {
+            frame,
+            ..Default::default()
+        };
+    }
+}
+
+/// Memory view deduplicating identical reads within a single frame.
+///
+/// Reads are keyed by their address and length and will be served from the cache until the frame
+/// of the registry advances (see [StateHeartbeat::frame]). Entries are never carried over into the next frame.
+/// Failed reads are not cached.
+///
+/// Note: The cache should be placed in front of the [crate::PrefetchMemoryView] so cache hits
+/// do not even touch the snapshot. Readers outside of the frame (e.g. the [crate::LocalPlayerSampler])
+/// should use a live memory view.
+pub struct FrameReadCache {
+    backend: Arc<dyn MemoryView + Send + Sync>,
+    heartbeat: Arc<StateHeartbeat>,
+    enabled: AtomicBool,
+
+    frame: Mutex<FrameEntries>,
+}
+
+impl FrameReadCache {
+    pub fn new(backend: Arc<dyn MemoryView + Send + Sync>, heartbeat: Arc<StateHeartbeat>) -> Self {
+        Self {
+            backend,
+            heartbeat,
+            enabled: AtomicBool::new(true),
+
+            frame: Default::default(),
+        }
+    }
+
+    /// Disabling the cache forwards all reads to the backend and drops the cached entries
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.lock_frame().entries.clear();
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Statistics of the current frame
+    pub fn statistics(&self) -> ReadCacheStatistics {
+        let mut frame = self.lock_frame();
+        frame.enter_frame(self.heartbeat.frame());
+        frame.statistics
+    }
+
+    /// Statistics of the previously observed frame
+    pub fn last_frame_statistics(&self) -> ReadCacheStatistics {
+        let mut frame = self.lock_frame();
+        frame.enter_frame(self.heartbeat.frame());
+        frame.last_frame
+    }
+
+    fn lock_frame(&self) -> MutexGuard<'_, FrameEntries> {
+        self.frame.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl MemoryView for FrameReadCache {
+    fn read_memory(
+        &self,
+        offset: u64,
+        buffer: &mut [u8],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let current_frame = self.heartbeat.frame();
+        if !self.is_enabled() {
+            let mut frame = self.lock_frame();
+            frame.enter_frame(current_frame);
+            frame.statistics.bypassed += 1;
+            drop(frame);
+
+            return self.backend.read_memory(offset, buffer);
+        }
+
+        let key = (offset, buffer.len());
+        {
+            let mut frame = self.lock_frame();
+            frame.enter_frame(current_frame);
+            if let Some(memory) = frame.entries.get(&key) {
+                buffer.copy_from_slice(memory);
+                frame.statistics.hits += 1;
+                return Ok(());
+            }
+        }
+
+        /* the backend must not be read while holding the lock */
+        let result = self.backend.read_memory(offset, buffer);
+
+        let mut frame = self.lock_frame();
+        if frame.statistics.frame != current_frame {
+            /* the frame advanced while reading, the value belongs to the previous frame */
+            return result;
+        }
+
+        frame.statistics.misses += 1;
+        result?;
+
+        if frame.statistics.bytes + buffer.len() <= READ_CACHE_MAX_BYTES
+            && frame.entries.insert(key, buffer.into()).is_none()
+        {
+            frame.statistics.entries += 1;
+            frame.statistics.bytes += buffer.len();
+        }
+        Ok(())
+    }
+}
+
+pub type StateFrameReadCache = StateVariable<Arc<FrameReadCache>>;
+
+#[cfg(test)]
+mod test {
+    use std::{
+        error::Error,
+        sync::{
+            atomic::{
+                AtomicU64,
+                AtomicU8,
+                Ordering,
+            },
+            Arc,
+        },
+    };
+
+    use raw_struct::MemoryView;
+    use utils_state::StateRegistry;
+
+    use super::{
+        FrameReadCache,
+        ReadCacheStatistics,
+    };
+    use crate::{
+        diagnostics::MemoryFixture,
+        test_fixture::{
+            match_fixture,
+            resolve_match_players,
+            setup_dump_schema,
+        },
+        PlayerPawnState,
+        StatePlayerEquipment,
+    };
+
+    const GLOBALS: u64 = 0x1000;
+    const LOCAL_CONTROLLER: u64 = 0x2000;
+    const GAME_RULES: u64 = 0x3000;
+    const PAWNS: u64 = 0x10_0000;
+    const PAWN_SIZE: u64 = 0x4000;
+
+    /// Fixture backend counting the reads and offsetting every byte by the current generation
+    struct CountingBackend {
+        fixture: MemoryFixture,
+        generation: AtomicU8,
+        reads: AtomicU64,
+    }
+
+    impl CountingBackend {
+        fn new() -> Self {
+            let mut fixture = MemoryFixture::default();
+            for address in [GLOBALS, LOCAL_CONTROLLER, GAME_RULES] {
+                fixture.record(address, &vec![0x10; 0x1000]);
+            }
+            for pawn in 0..10 {
+                fixture.record(
+                    PAWNS + pawn * PAWN_SIZE,
+                    &vec![pawn as u8; PAWN_SIZE as usize],
+                );
+            }
+
+            Self::from_fixture(fixture)
+        }
+
+        fn from_fixture(fixture: MemoryFixture) -> Self {
+            Self {
+                fixture,
+                generation: AtomicU8::new(0),
+                reads: AtomicU64::new(0),
+            }
+        }
+    }
+
+    impl MemoryView for CountingBackend {
+        fn read_memory(
+            &self,
+            offset: u64,
+            buffer: &mut [u8],
+        ) -> Result<(), Box<dyn Error + Send + Sync>> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            self.fixture.read_memory(offset, buffer)?;
+
+            let generation = self.generation.load(Ordering::Relaxed);
+            for value in buffer.iter_mut() {
+                *value = value.wrapping_add(generation);
+            }
+            Ok(())
+        }
+    }
+
+    fn read(view: &dyn MemoryView, address: u64, length: usize) -> Option<Vec<u8>> {
+        let mut buffer = vec![0u8; length];
+        view.read_memory(address, &mut buffer).ok().map(|_| buffer)
+    }
+
+    const PLAYERS: usize = 10;
+
+    /// Resolve the player states of the [match_fixture] through the read cache.
+    /// Returns the resolved states, the backend reads and the cache statistics.
+    fn resolve_match(
+        cache_enabled: bool,
+    ) -> (
+        Vec<(PlayerPawnState, StatePlayerEquipment)>,
+        u64,
+        ReadCacheStatistics,
+    ) {
+        let fixture = match_fixture(PLAYERS);
+        let backend = Arc::new(CountingBackend::from_fixture(fixture.memory.clone()));
+
+        let mut states = StateRegistry::new(1024);
+        let cache = Arc::new(FrameReadCache::new(backend.clone(), states.heartbeat()));
+        cache.set_enabled(cache_enabled);
+        fixture.register(&mut states, cache.clone());
+
+        /* the entity identities have been read while registering the fixture */
+        backend.reads.store(0, Ordering::Relaxed);
+        let statistics_before = cache.statistics();
+
+        let players = resolve_match_players(&states, PLAYERS).unwrap();
+        let statistics = cache.statistics();
+        assert_eq!(
+            statistics.backend_reads() - statistics_before.backend_reads(),
+            backend.reads.load(Ordering::Relaxed)
+        );
+
+        (players, backend.reads.load(Ordering::Relaxed), statistics)
+    }
+
+    #[test]
+    fn backend_reads_reduced() {
+        setup_dump_schema();
+
+        let (uncached_players, uncached_reads, uncached_statistics) = resolve_match(false);
+        assert_eq!(uncached_statistics.hits, 0);
+
+        let (cached_players, cached_reads, cached_statistics) = resolve_match(true);
+        assert_eq!(cached_players, uncached_players);
+        assert_eq!(cached_statistics.bypassed, 0);
+
+        /* the pawn state and the equipment both read the health of every pawn */
+        assert!(
+            cached_statistics.hits >= PLAYERS as u64,
+            "{:?}",
+            cached_statistics
+        );
+        assert_eq!(cached_reads + cached_statistics.hits, uncached_reads);
+    }
+
+    #[test]
+    fn scoped_to_frame() {
+        let mut states = StateRegistry::new(8);
+        let backend = Arc::new(CountingBackend::new());
+        let cache = FrameReadCache::new(backend.clone(), states.heartbeat());
+
+        states.invalidate_states();
+        assert_eq!(read(&cache, GLOBALS, 4), Some(vec![0x10; 4]));
+
+        /* the game advanced within the frame, the frame keeps its consistent view */
+        backend.generation.store(1, Ordering::Relaxed);
+        assert_eq!(read(&cache, GLOBALS, 4), Some(vec![0x10; 4]));
+
+        /* other lengths of the same address are distinct entries */
+        assert_eq!(read(&cache, GLOBALS, 2), Some(vec![0x11; 2]));
+
+        /* disabling the cache bypasses it for all reads */
+        cache.set_enabled(false);
+        assert_eq!(read(&cache, GLOBALS, 4), Some(vec![0x11; 4]));
+        assert_eq!(cache.statistics().bypassed, 1);
+        cache.set_enabled(true);
+
+        /* nothing is carried over into the next frame */
+        states.invalidate_states();
+        assert_eq!(read(&cache, GLOBALS, 4), Some(vec![0x11; 4]));
+        assert_eq!(cache.statistics().misses, 1);
+        assert_eq!(cache.statistics().hits, 0);
+    }
+}
@@ -0,0 +1,384 @@
+use std::collections::VecDeque;
+
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    GameSnapshot,
+    SnapshotDetail,
+};
+
+/// Snapshots within this time (in seconds) of a queried server time are considered a match
+pub const REWIND_QUERY_TOLERANCE: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RewindConfig {
+    /// Server time (in seconds) covered by the buffer
+    pub retention: f32,
+
+    /// Snapshots older than this (in seconds) will be reduced (see [GameSnapshot::reduce])
+    pub full_detail: f32,
+
+    /// Upper bound of the estimated memory usage (see [GameSnapshot::estimated_size]).
+    /// The oldest snapshots will be dropped once exceeded.
+    pub max_bytes: usize,
+}
+
+impl Default for RewindConfig {
+    fn default() -> Self {
+        Self {
+            retention: 30.0,
+            full_detail: 3.0,
+            max_bytes: 16 * 1024 * 1024,
+        }
+    }
+}
+
+/// Snapshots of the last [RewindConfig::retention] seconds ordered by their server time.
+///
+/// The buffer only covers a single map: Snapshots of a different map
+/// or a server time going backwards (e.g. a new server) will clear the buffer.
+#[derive(Debug, Clone)]
+pub struct RewindBuffer {
+    config: RewindConfig,
+    snapshots: VecDeque<GameSnapshot>,
+
+    /// Estimated size of all snapshots
+    size: usize,
+
+    /// Index of the first snapshot with full detail
+    full_detail_start: usize,
+}
+
+impl RewindBuffer {
+    pub fn new(config: RewindConfig) -> Self {
+        Self {
+            config,
+            snapshots: Default::default(),
+            size: 0,
+            full_detail_start: 0,
+        }
+    }
+
+    pub fn config(&self) -> &RewindConfig {
+        &self.config
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.size = 0;
+        self.full_detail_start = 0;
+    }
+
+    pub fn push(&mut self, snapshot: GameSnapshot) {
+        if let Some(latest) = self.snapshots.back() {
+            if latest.map != snapshot.map || snapshot.server_time < latest.server_time {
+                self.clear();
+            } else if snapshot.server_time == latest.server_time {
+                /* the server did not advance */
+                return;
+            }
+        }
+
+        let server_time = snapshot.server_time;
+        self.size += snapshot.estimated_size();
+        self.snapshots.push_back(snapshot);
+
+        while self.full_detail_start < self.snapshots.len() {
+            let snapshot = &mut self.snapshots[self.full_detail_start];
+            if server_time - snapshot.server_time <= self.config.full_detail {
+                break;
+            }
+
+            self.size -= snapshot.estimated_size();
+            snapshot.reduce();
+            self.size += snapshot.estimated_size();
+            self.full_detail_start += 1;
+        }
+
+        while let Some(oldest) = self.snapshots.front() {
+            if server_time - oldest.server_time <= self.config.retention
+                && self.size <= self.config.max_bytes
+            {
+                break;
+            }
+
+            self.size -= oldest.estimated_size();
+            self.snapshots.pop_front();
+            self.full_detail_start = self.full_detail_start.saturating_sub(1);
+        }
+    }
+
+    /// Snapshot closest to the server time.
+    /// Returns None if the server time is not covered by the buffer.
+    pub fn query_at(&self, server_time: f32) -> Option<&GameSnapshot> {
+        let index = self
+            .snapshots
+            .partition_point(|snapshot| snapshot.server_time < server_time);
+
+        let before = index
+            .checked_sub(1)
+            .and_then(|index| self.snapshots.get(index));
+        let after = self.snapshots.get(index);
+        let closest = match (before, after) {
+            (Some(before), Some(after)) => {
+                if server_time - before.server_time <= after.server_time - server_time {
+                    before
+                } else {
+                    after
+                }
+            }
+            (Some(snapshot), None) | (None, Some(snapshot)) => snapshot,
+            (None, None) => return None,
+        };
+
+        if (closest.server_time - server_time).abs() > REWIND_QUERY_TOLERANCE {
+            return None;
+        }
+
+        Some(closest)
+    }
+
+    /// All snapshots within the server time range (inclusive), oldest first.
+    /// Empty if the range is not fully covered by the buffer.
+    pub fn range(&self, start: f32, end: f32) -> impl Iterator<Item = &GameSnapshot> {
+        let covered = match (self.snapshots.front(), self.snapshots.back()) {
+            (Some(oldest), Some(latest)) => {
+                oldest.server_time - REWIND_QUERY_TOLERANCE <= start
+                    && end <= latest.server_time + REWIND_QUERY_TOLERANCE
+            }
+            _ => false,
+        };
+
+        self.snapshots.iter().filter(move |snapshot| {
+            covered && start <= snapshot.server_time && snapshot.server_time <= end
+        })
+    }
+
+    /// Server time range of the buffered snapshots
+    pub fn time_range(&self) -> Option<(f32, f32)> {
+        Some((
+            self.snapshots.front()?.server_time,
+            self.snapshots.back()?.server_time,
+        ))
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Estimated memory usage of all snapshots (see [GameSnapshot::estimated_size])
+    pub fn estimated_size(&self) -> usize {
+        self.size
+    }
+
+    /// Amount of snapshots with [SnapshotDetail::Full]
+    pub fn full_detail_count(&self) -> usize {
+        self.snapshots
+            .iter()
+            .filter(|snapshot| snapshot.detail == SnapshotDetail::Full)
+            .count()
+    }
+}
+
+/// Captures a [GameSnapshot] every frame for rewinding (e.g. a caster radar).
+/// Snapshots will only be captured while this state is being resolved every frame,
+/// hence it should be subscribed on the poller.
+///
+/// Can be preset using `StateRegistry::set` to use a custom [RewindConfig].
+pub struct StateRewindBuffer {
+    pub buffer: RewindBuffer,
+}
+
+impl StateRewindBuffer {
+    pub fn new(config: RewindConfig) -> Self {
+        Self {
+            buffer: RewindBuffer::new(config),
+        }
+    }
+}
+
+impl State for StateRewindBuffer {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self::new(Default::default()))
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        match GameSnapshot::capture(states) {
+            Ok(snapshot) => self.buffer.push(snapshot),
+            Err(error) => log::trace!("Skipping rewind snapshot: {:#}", error),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::SystemTime;
+
+    use super::{
+        RewindBuffer,
+        RewindConfig,
+    };
+    use crate::{
+        BombState,
+        DataQuality,
+        EntityIndex,
+        GameSnapshot,
+        GrenadeKind,
+        PawnIndex,
+        SnapshotDetail,
+        SnapshotGrenade,
+        SnapshotPlayer,
+        SnapshotPlayerDetails,
+        WeaponId,
+    };
+
+    const TICK: f32 = 1.0 / 64.0;
+
+    fn snapshot(map: &str, server_time: f32) -> GameSnapshot {
+        GameSnapshot {
+            server_time,
+            captured_at: SystemTime::now(),
+            map: Some(map.to_string()),
+
+            players: (0..10)
+                .map(|index| SnapshotPlayer {
+                    pawn_entity_id: PawnIndex(index),
+                    controller_entity_id: None,
+                    team_id: 2 + (index % 2) as u8,
+                    alive: true,
+                    position: Default::default(),
+                    details: Some(SnapshotPlayerDetails {
+                        player_name: Some(format!("player {}", index)),
+                        player_health: 100,
+                        weapon: WeaponId::Ak47,
+                        view_angles: Default::default(),
+                    }),
+                })
+                .collect(),
+            bomb: BombState::NotInRound,
+            planted_c4: None,
+            grenades: (0..4)
+                .map(|index| SnapshotGrenade {
+                    entity_id: EntityIndex(200 + index),
+                    kind: GrenadeKind::HighExplosive,
+                    position: Default::default(),
+                    velocity: Default::default(),
+                    owner_team_id: Some(2),
+                    effect_time_remaining: None,
+                })
+                .collect(),
+
+            world_data_quality: DataQuality::Good,
+
+            detail: SnapshotDetail::Full,
+
+            #[cfg(feature = "serialize")]
+            custom: Default::default(),
+        }
+    }
+
+    /// Ten minute feed with one snapshot per tick
+    #[test]
+    fn bounded() {
+        let config = RewindConfig {
+            retention: 30.0,
+            full_detail: 3.0,
+            max_bytes: 4 * 1024 * 1024,
+        };
+        let mut buffer = RewindBuffer::new(config);
+
+        let ticks = 10 * 60 * 64;
+        for tick in 0..ticks {
+            buffer.push(snapshot("de_mirage", tick as f32 * TICK));
+            assert!(buffer.estimated_size() <= config.max_bytes);
+            assert!(buffer.len() <= (config.retention / TICK) as usize + 1);
+        }
+
+        let (oldest, latest) = buffer.time_range().unwrap();
+        assert!(latest - oldest <= config.retention);
+        assert!(latest - oldest >= config.retention - 1.0);
+        assert_eq!(
+            buffer.full_detail_count(),
+            (config.full_detail / TICK) as usize + 1
+        );
+        assert!(buffer.query_at(latest - 10.0).unwrap().grenades.is_empty());
+        assert_eq!(buffer.query_at(latest - 1.0).unwrap().grenades.len(), 4);
+
+        /* the memory bound takes precedence over the retention */
+        let mut buffer = RewindBuffer::new(RewindConfig {
+            max_bytes: 256 * 1024,
+            ..config
+        });
+        for tick in 0..ticks / 10 {
+            buffer.push(snapshot("de_mirage", tick as f32 * TICK));
+            assert!(buffer.estimated_size() <= 256 * 1024);
+        }
+        let (oldest, latest) = buffer.time_range().unwrap();
+        assert!(latest - oldest < config.retention);
+    }
+
+    #[test]
+    fn query() {
+        let mut buffer = RewindBuffer::new(Default::default());
+        for tick in 0..640 {
+            buffer.push(snapshot("de_mirage", 100.0 + tick as f32 * TICK));
+        }
+
+        /* nearest neighbor */
+        let snapshot = buffer.query_at(105.0 + TICK * 0.4).unwrap();
+        assert_eq!(snapshot.server_time, 105.0);
+        let snapshot = buffer.query_at(105.0 + TICK * 0.6).unwrap();
+        assert_eq!(snapshot.server_time, 105.0 + TICK);
+
+        assert!(buffer.query_at(99.9).is_some());
+        assert!(buffer.query_at(50.0).is_none());
+        assert!(buffer.query_at(200.0).is_none());
+
+        assert_eq!(buffer.range(101.0, 102.0).count(), 64 + 1);
+        assert!(buffer
+            .range(101.0, 102.0)
+            .all(|snapshot| (101.0..=102.0).contains(&snapshot.server_time)));
+        assert_eq!(buffer.range(90.0, 102.0).count(), 0);
+    }
+
+    #[test]
+    fn map_change() {
+        let mut buffer = RewindBuffer::new(Default::default());
+        for tick in 0..640 {
+            buffer.push(snapshot("de_mirage", 100.0 + tick as f32 * TICK));
+        }
+
+        /* the server time of the new map starts over */
+        for tick in 0..640 {
+            buffer.push(snapshot("de_inferno", 100.0 + tick as f32 * TICK));
+        }
+        assert!(buffer
+            .range(100.0, 109.0)
+            .all(|snapshot| snapshot.map.as_deref() == Some("de_inferno")));
+
+        for tick in 0..64 {
+            buffer.push(snapshot("de_nuke", 1.0 + tick as f32 * TICK));
+        }
+        assert!(buffer.query_at(105.0).is_none());
+        assert_eq!(buffer.range(100.0, 109.0).count(), 0);
+        assert_eq!(buffer.range(1.0, 105.0).count(), 0);
+        assert_eq!(buffer.len(), 64);
+        assert!(buffer.query_at(1.5).is_some());
+    }
+}
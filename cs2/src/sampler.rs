@@ -0,0 +1,476 @@
+use std::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    sync::{
+        atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use anyhow::Context;
+use cs2_schema_generated::cs2::client::{
+    CCSPlayerController,
+    C_CSPlayerPawn,
+    C_EconEntity,
+};
+use utils_state::{
+    ShutdownToken,
+    StateRegistry,
+};
+
+use crate::{
+    read_entity_identity,
+    CEntityIdentityEx,
+    CS2Offset,
+    StateCS2Handle,
+    StateCS2Memory,
+    StateGlobals,
+    StateLocalPlayerController,
+    StatePredefinedOffset,
+    StateResolvedOffset,
+    ViewAngles,
+    WeaponId,
+};
+
+struct RingBufferInner<T> {
+    /// Power of two amount of slots so the slot of an index does not change when the index wraps around
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+
+    /// Maximum amount of values within the buffer
+    capacity: usize,
+
+    /// Index of the next slot to read
+    head: AtomicUsize,
+
+    /// Index of the next slot to write
+    tail: AtomicUsize,
+
+    /// Amount of values which have been dropped because the buffer was full
+    dropped: AtomicUsize,
+}
+
+/* Safety: Slots are only accessed by the single producer or the single consumer. */
+unsafe impl<T: Send> Sync for RingBufferInner<T> {}
+
+/// Create a lock-free single producer single consumer ring buffer.
+/// Values pushed while the buffer is full will be dropped.
+pub fn sample_ring_buffer<T: Copy + Send>(
+    capacity: usize,
+) -> (SampleProducer<T>, SampleConsumer<T>) {
+    ring_buffer_starting_at(capacity, 0)
+}
+
+/// Create a ring buffer which head and tail start at the given index
+fn ring_buffer_starting_at<T: Copy + Send>(
+    capacity: usize,
+    start_index: usize,
+) -> (SampleProducer<T>, SampleConsumer<T>) {
+    let capacity = capacity.max(1);
+    let inner = Arc::new(RingBufferInner {
+        slots: (0..capacity.next_power_of_two())
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect(),
+        capacity,
+        head: AtomicUsize::new(start_index),
+        tail: AtomicUsize::new(start_index),
+        dropped: AtomicUsize::new(0),
+    });
+
+    (
+        SampleProducer {
+            inner: inner.clone(),
+        },
+        SampleConsumer { inner },
+    )
+}
+
+pub struct SampleProducer<T> {
+    inner: Arc<RingBufferInner<T>>,
+}
+
+impl<T: Copy + Send> SampleProducer<T> {
+    /// Push a value into the buffer.
+    /// Returns false if the buffer is full and the value has been dropped.
+    pub fn push(&mut self, value: T) -> bool {
+        let inner = &*self.inner;
+        let tail = inner.tail.load(Ordering::Relaxed);
+        let head = inner.head.load(Ordering::Acquire);
+        if tail.wrapping_sub(head) >= inner.capacity {
+            inner.dropped.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+
+        let slot = &inner.slots[tail & (inner.slots.len() - 1)];
+        unsafe { (*slot.get()).write(value) };
+        inner.tail.store(tail.wrapping_add(1), Ordering::Release);
+        true
+    }
+}
+
+pub struct SampleConsumer<T> {
+    inner: Arc<RingBufferInner<T>>,
+}
+
+impl<T: Copy + Send> SampleConsumer<T> {
+    pub fn pop(&mut self) -> Option<T> {
+        let inner = &*self.inner;
+        let head = inner.head.load(Ordering::Relaxed);
+        let tail = inner.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+
+        let slot = &inner.slots[head & (inner.slots.len() - 1)];
+        let value = unsafe { (*slot.get()).assume_init() };
+        inner.head.store(head.wrapping_add(1), Ordering::Release);
+        Some(value)
+    }
+
+    pub fn len(&self) -> usize {
+        let tail = self.inner.tail.load(Ordering::Acquire);
+        let head = self.inner.head.load(Ordering::Relaxed);
+        tail.wrapping_sub(head)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Total amount of values dropped because the buffer was full
+    pub fn dropped(&self) -> usize {
+        self.inner.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Normalize the pitch into [-89, 89] and the yaw into [-180, 180)
+pub fn normalize_view_angles(pitch: f32, yaw: f32) -> [f32; 2] {
+    [
+        pitch.clamp(-89.0, 89.0),
+        (yaw + 180.0).rem_euclid(360.0) - 180.0,
+    ]
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LocalPlayerSample {
+    pub timestamp: Instant,
+
+    /// Server time (`globals.time_2`) of the sample
+    pub server_time: f32,
+
+    /// Normalized view angles
+    pub view_angles: ViewAngles,
+
+    /// Aim punch angles (pitch, yaw)
+    pub punch_angles: [f32; 2],
+
+    pub shots_fired: i32,
+    pub weapon: WeaponId,
+}
+
+#[derive(Debug, Clone)]
+pub struct LocalPlayerSamplerConfig {
+    /// Samples per second
+    pub sample_rate: u32,
+
+    /// Amount of samples buffered until new samples will be dropped
+    pub buffer_capacity: usize,
+}
+
+impl Default for LocalPlayerSamplerConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 128,
+            buffer_capacity: 1024,
+        }
+    }
+}
+
+/// Samples the local players view at a fixed rate independently of the render loop.
+/// Only the local controller, its pawn and the globals will be resolved for each sample.
+pub struct LocalPlayerSampler {
+    consumer: SampleConsumer<LocalPlayerSample>,
+    shutdown: ShutdownToken,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl LocalPlayerSampler {
+    pub fn spawn(states: &StateRegistry, config: LocalPlayerSamplerConfig) -> anyhow::Result<Self> {
+        Self::spawn_with_shutdown(states, config, &ShutdownToken::new())
+    }
+
+    /// Spawn the sampler which also stops as soon as the shutdown of the token has been requested
+    pub fn spawn_with_shutdown(
+        states: &StateRegistry,
+        config: LocalPlayerSamplerConfig,
+        shutdown: &ShutdownToken,
+    ) -> anyhow::Result<Self> {
+        let cs2 = states.resolve::<StateCS2Handle>(())?.value().clone();
+
+        /*
+         * The memory view of the states may serve frame scoped data (prefetch snapshot, read cache).
+         * The sampler runs outside of the frame and must read from the process directly.
+         */
+        let mut sampler_states = StateRegistry::new(0x20);
+        sampler_states.set(StateCS2Memory::from_view(cs2.create_memory_view()), ())?;
+        for offset in [
+            CS2Offset::Globals,
+            CS2Offset::LocalController,
+            CS2Offset::GlobalEntityList,
+        ] {
+            /* reuse the already resolved offsets instead of scanning for them again */
+            let (module, _) = offset.signature();
+            let resolved = states.resolve::<StateResolvedOffset>(offset)?;
+            sampler_states.set(
+                StatePredefinedOffset {
+                    module,
+                    offset: resolved.offset,
+                    resolved: resolved.address,
+                },
+                offset,
+            )?;
+        }
+        sampler_states.set(StateCS2Handle::new(cs2), ())?;
+
+        let (mut producer, consumer) = sample_ring_buffer(config.buffer_capacity);
+        let shutdown = shutdown.child();
+        let interval = Duration::from_secs_f64(1.0 / config.sample_rate.max(1) as f64);
+
+        let thread = shutdown
+            .spawn_thread("local player sampler", {
+                let shutdown = shutdown.clone();
+                move || {
+                    let mut states = sampler_states;
+                    let mut next_sample = Instant::now();
+                    while !shutdown.is_requested() {
+                        states.invalidate_states();
+                        match Self::sample(&states) {
+                            Ok(Some(sample)) => {
+                                producer.push(sample);
+                            }
+                            Ok(None) => { /* no local player pawn */ }
+                            Err(error) => log::trace!("Failed to sample local player: {:#}", error),
+                        }
+
+                        next_sample += interval;
+                        let now = Instant::now();
+                        if next_sample > now {
+                            shutdown.sleep(next_sample - now);
+                        } else {
+                            /* we can't keep up, skip the missed samples */
+                            next_sample = now;
+                        }
+                    }
+                }
+            })
+            .context("spawn sampler thread")?;
+
+        Ok(Self {
+            consumer,
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    fn sample(states: &StateRegistry) -> anyhow::Result<Option<LocalPlayerSample>> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let globals = states.resolve::<StateGlobals>(())?;
+        let local_controller = states.resolve::<StateLocalPlayerController>(())?;
+        let entity_list = states.resolve::<StateResolvedOffset>(CS2Offset::GlobalEntityList)?;
+
+        let Some(local_controller) = local_controller.instance.value_reference(memory.view_arc())
+        else {
+            return Ok(None);
+        };
+
+        let pawn_handle = local_controller.m_hPlayerPawn()?;
+        if !pawn_handle.is_valid() {
+            return Ok(None);
+        }
+
+        let Some(pawn_identity) = read_entity_identity(
+            memory.view(),
+            entity_list.address,
+            pawn_handle.get_entity_index(),
+        )?
+        else {
+            return Ok(None);
+        };
+
+        let Some(pawn) = pawn_identity
+            .entity_ptr::<dyn C_CSPlayerPawn>()?
+            .value_reference(memory.view_arc())
+        else {
+            return Ok(None);
+        };
+
+        let eye_angles = pawn.m_angEyeAngles()?;
+        let punch_angles = pawn.m_aimPunchAngle()?;
+        let weapon = match pawn.m_pClippingWeapon()?.value_reference(memory.view_arc()) {
+            Some(weapon) => WeaponId::from_id(
+                weapon
+                    .cast::<dyn C_EconEntity>()
+                    .m_AttributeManager()?
+                    .m_Item()?
+                    .m_iItemDefinitionIndex()?,
+            )
+            .unwrap_or(WeaponId::Unknown),
+            None => WeaponId::Knife,
+        };
+
+        Ok(Some(LocalPlayerSample {
+            timestamp: Instant::now(),
+            server_time: globals.server_time()?,
+
+            view_angles: ViewAngles::from_engine(eye_angles[0], eye_angles[1]).normalized(),
+            punch_angles: [punch_angles[0], punch_angles[1]],
+
+            shots_fired: pawn.m_iShotsFired()?,
+            weapon,
+        }))
+    }
+
+    /// Take the oldest buffered sample
+    pub fn try_recv(&mut self) -> Option<LocalPlayerSample> {
+        self.consumer.pop()
+    }
+
+    /// Take all buffered samples
+    pub fn drain(&mut self) -> impl Iterator<Item = LocalPlayerSample> + '_ {
+        std::iter::from_fn(|| self.consumer.pop())
+    }
+
+    /// Total amount of samples dropped because nobody consumed them
+    pub fn dropped_samples(&self) -> usize {
+        self.consumer.dropped()
+    }
+}
+
+impl Drop for LocalPlayerSampler {
+    fn drop(&mut self) {
+        self.shutdown.request();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use super::{
+        ring_buffer_starting_at,
+        sample_ring_buffer,
+    };
+
+    #[test]
+    fn fifo_order() {
+        let (mut producer, mut consumer) = sample_ring_buffer::<u32>(4);
+        assert!(consumer.is_empty());
+        assert_eq!(consumer.pop(), None);
+
+        for value in 0..3 {
+            assert!(producer.push(value));
+        }
+        assert_eq!(consumer.len(), 3);
+        assert_eq!(consumer.pop(), Some(0));
+
+        assert!(producer.push(3));
+        assert!(producer.push(4));
+        assert_eq!(
+            (0..4).map(|_| consumer.pop()).collect::<Vec<_>>(),
+            [Some(1), Some(2), Some(3), Some(4)]
+        );
+        assert_eq!(consumer.pop(), None);
+        assert_eq!(consumer.dropped(), 0);
+    }
+
+    #[test]
+    fn drop_on_full() {
+        /* the capacity is not rounded up to the slot count */
+        let (mut producer, mut consumer) = sample_ring_buffer::<u32>(3);
+        for value in 0..3 {
+            assert!(producer.push(value));
+        }
+        assert!(!producer.push(3));
+        assert!(!producer.push(4));
+        assert_eq!(consumer.len(), 3);
+        assert_eq!(consumer.dropped(), 2);
+
+        /* dropped values do not replace buffered ones */
+        assert_eq!(consumer.pop(), Some(0));
+        assert!(producer.push(5));
+        assert_eq!(
+            (0..3).map(|_| consumer.pop()).collect::<Vec<_>>(),
+            [Some(1), Some(2), Some(5)]
+        );
+        assert_eq!(consumer.dropped(), 2);
+    }
+
+    #[test]
+    fn index_wraparound() {
+        for capacity in [1, 3, 4] {
+            let (mut producer, mut consumer) =
+                ring_buffer_starting_at::<usize>(capacity, usize::MAX - 4);
+
+            let mut next_value = 0;
+            let mut expected = 0;
+            for _ in 0..4 {
+                while producer.push(next_value) {
+                    next_value += 1;
+                }
+                assert_eq!(consumer.len(), capacity, "capacity {}", capacity);
+
+                while let Some(value) = consumer.pop() {
+                    assert_eq!(value, expected, "capacity {}", capacity);
+                    expected += 1;
+                }
+            }
+
+            assert_eq!(expected, 4 * capacity);
+            assert_eq!(consumer.dropped(), 4);
+        }
+    }
+
+    #[test]
+    fn concurrent_producer() {
+        const VALUES: u64 = 200_000;
+
+        let (mut producer, mut consumer) = sample_ring_buffer::<u64>(64);
+        let producer = thread::spawn(move || {
+            let mut pushed = 0;
+            for value in 0..VALUES {
+                if producer.push(value) {
+                    pushed += 1;
+                }
+            }
+            pushed
+        });
+
+        let mut received = Vec::new();
+        loop {
+            let finished = producer.is_finished();
+            while let Some(value) = consumer.pop() {
+                received.push(value);
+            }
+
+            if finished {
+                break;
+            }
+            thread::yield_now();
+        }
+
+        let pushed = producer.join().unwrap();
+        assert_eq!(received.len(), pushed);
+        assert_eq!(received.len() + consumer.dropped(), VALUES as usize);
+        assert!(received.windows(2).all(|values| values[0] < values[1]));
+    }
+}
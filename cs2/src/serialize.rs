@@ -0,0 +1,55 @@
+//! Serde helpers for fields which have no suitable serde representation
+
+/// `Vector3<f32>` as `[x, y, z]`, so consumers do not need nalgebra
+pub(crate) mod vector3 {
+    use nalgebra::Vector3;
+    use serde::{
+        Deserialize,
+        Deserializer,
+        Serialize,
+        Serializer,
+    };
+
+    pub fn serialize<S: Serializer>(
+        value: &Vector3<f32>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        [value.x, value.y, value.z].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vector3<f32>, D::Error> {
+        let [x, y, z] = <[f32; 3]>::deserialize(deserializer)?;
+        Ok(Vector3::new(x, y, z))
+    }
+}
+
+/// `Instant` as the amount of seconds elapsed until serialization.
+/// Instants are process local, hence the deserialized instant is relative to the time of deserialization.
+pub(crate) mod instant_age {
+    use std::time::{
+        Duration,
+        Instant,
+    };
+
+    use serde::{
+        de::Error,
+        Deserialize,
+        Deserializer,
+        Serialize,
+        Serializer,
+    };
+
+    pub fn serialize<S: Serializer>(value: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+        value.elapsed().as_secs_f32().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instant, D::Error> {
+        let age = Duration::try_from_secs_f32(f32::deserialize(deserializer)?)
+            .map_err(D::Error::custom)?;
+
+        let now = Instant::now();
+        Ok(now.checked_sub(age).unwrap_or(now))
+    }
+}
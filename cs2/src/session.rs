@@ -0,0 +1,383 @@
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context;
+use raw_struct::MemoryView;
+use utils_state::{
+    ShutdownToken,
+    StateRegistry,
+    StateWatchdog,
+    WatchdogReport,
+};
+
+use crate::{
+    derived::DerivedStates,
+    CS2Handle,
+    DetachedMemoryView,
+    LocalPlayerSampler,
+    LocalPlayerSamplerConfig,
+    StateCS2Handle,
+    StateCS2Memory,
+    SwappableMemoryView,
+};
+
+/// Time to wait for the background threads to stop when detaching
+pub const SESSION_DETACH_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+    Detached,
+    Attached,
+}
+
+type DetachHook = Box<dyn FnMut(&StateRegistry)>;
+
+/// Lifecycle (Detached -> Attached -> Detached -> ...) of a state registry attached to CS2.
+///
+/// Background components spawned while being attached respect the shutdown token of the session
+/// and will be stopped when detaching. Detaching drops all states, which releases the process handle,
+/// and leaves the registry ready to be attached again.
+///
+/// The memory view is kept across attachments and its backend is replaced with every attachment.
+/// Views handed out before (e.g. to background components) keep working and observe the epoch change.
+pub struct CS2Session {
+    states: StateRegistry,
+    state: SessionState,
+
+    /// Memory view of the current attachment, kept across attachments
+    memory: Arc<SwappableMemoryView>,
+
+    /// Shutdown token of the current attachment
+    shutdown: ShutdownToken,
+    watchdog: Option<StateWatchdog>,
+
+    /// Invoked before detaching (e.g. to flush recorders and match logs)
+    detach_hooks: Vec<DetachHook>,
+
+    /// Registered derived states, kept across attachments
+    derived_states: DerivedStates,
+}
+
+impl CS2Session {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            states: StateRegistry::new(capacity),
+            state: SessionState::Detached,
+
+            memory: Arc::new(SwappableMemoryView::detached()),
+
+            shutdown: ShutdownToken::new(),
+            watchdog: None,
+
+            detach_hooks: Vec::new(),
+            derived_states: DerivedStates::default(),
+        }
+    }
+
+    pub fn state(&self) -> SessionState {
+        self.state
+    }
+
+    pub fn states(&self) -> &StateRegistry {
+        &self.states
+    }
+
+    pub fn states_mut(&mut self) -> &mut StateRegistry {
+        &mut self.states
+    }
+
+    /// Shutdown token of the current attachment.
+    /// Background components of the consumer should stop when it has been requested.
+    pub fn shutdown_token(&self) -> &ShutdownToken {
+        &self.shutdown
+    }
+
+    /// Register a callback which will be invoked before every detach while the states are still available
+    pub fn on_detach(&mut self, hook: impl FnMut(&StateRegistry) + 'static) {
+        self.detach_hooks.push(Box::new(hook));
+    }
+
+    /// Register custom derived states (see [DerivedStates::register]).
+    /// The registrations remain across attachments.
+    pub fn derived_states(&self) -> &DerivedStates {
+        &self.derived_states
+    }
+
+    /// Memory view of the session.
+    /// The view stays valid across attachments and reads from the currently attached process.
+    pub fn memory(&self) -> &Arc<SwappableMemoryView> {
+        &self.memory
+    }
+
+    /// Attach to the CS2 process
+    pub fn attach(&mut self, metrics: bool) -> anyhow::Result<()> {
+        if self.state == SessionState::Attached {
+            anyhow::bail!("session is already attached");
+        }
+
+        let cs2 = CS2Handle::create(metrics)?;
+        self.attach_view(cs2.create_memory_view(), |states| {
+            states.set(StateCS2Handle::new(cs2), ())
+        })
+    }
+
+    /// Attach by swapping the given view into the memory view of the session
+    /// and registering it as [StateCS2Memory] before the custom setup
+    pub fn attach_view(
+        &mut self,
+        view: Arc<dyn MemoryView + Send + Sync>,
+        setup: impl FnOnce(&mut StateRegistry) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let memory = self.memory.clone();
+        self.attach_with(|states| {
+            memory.swap_backend(view);
+            states.set(StateCS2Memory::new(memory), ())?;
+            setup(states)
+        })
+    }
+
+    /// Attach using a custom setup of the initial states (e.g. a custom memory view)
+    pub fn attach_with(
+        &mut self,
+        setup: impl FnOnce(&mut StateRegistry) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        if self.state == SessionState::Attached {
+            anyhow::bail!("session is already attached");
+        }
+
+        self.shutdown = ShutdownToken::new();
+        let result =
+            setup(&mut self.states).and_then(|_| self.states.set(self.derived_states.clone(), ()));
+        if let Err(error) = result {
+            self.states.clear();
+            self.release_memory();
+            return Err(error);
+        }
+
+        self.state = SessionState::Attached;
+        Ok(())
+    }
+
+    /// Spawn a watchdog for the registry which will be stopped when detaching
+    pub fn spawn_watchdog(
+        &mut self,
+        deadline: Duration,
+        callback: impl Fn(&WatchdogReport) + Send + 'static,
+    ) -> anyhow::Result<()> {
+        if self.state != SessionState::Attached {
+            anyhow::bail!("session is not attached");
+        }
+
+        self.watchdog = Some(
+            StateWatchdog::spawn_with_shutdown(
+                self.states.heartbeat(),
+                deadline,
+                &self.shutdown,
+                callback,
+            )
+            .context("spawn watchdog")?,
+        );
+        Ok(())
+    }
+
+    /// Spawn a sampler which will be stopped when detaching
+    pub fn spawn_sampler(
+        &self,
+        config: LocalPlayerSamplerConfig,
+    ) -> anyhow::Result<LocalPlayerSampler> {
+        if self.state != SessionState::Attached {
+            anyhow::bail!("session is not attached");
+        }
+
+        LocalPlayerSampler::spawn_with_shutdown(&self.states, config, &self.shutdown)
+    }
+
+    /// Stop all background threads, flush the recorders and release the process handle.
+    /// Detaching a detached session is a no-op.
+    pub fn detach(&mut self) -> anyhow::Result<()> {
+        if self.state == SessionState::Detached {
+            return Ok(());
+        }
+
+        for hook in self.detach_hooks.iter_mut() {
+            hook(&self.states);
+        }
+
+        self.shutdown.request();
+        self.watchdog = None;
+        let stopped = self.shutdown.wait_stopped(SESSION_DETACH_TIMEOUT);
+
+        self.states.clear();
+        self.release_memory();
+        self.state = SessionState::Detached;
+
+        if !stopped {
+            anyhow::bail!(
+                "{} background threads did not stop within {:#?}",
+                self.shutdown.running_threads(),
+                SESSION_DETACH_TIMEOUT
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Redirect the memory view of the session away from the released process.
+    /// Reads of components which did not stop in time fail instead of reading the released process.
+    fn release_memory(&self) {
+        self.memory.swap_backend(Arc::new(DetachedMemoryView));
+    }
+}
+
+impl Drop for CS2Session {
+    fn drop(&mut self) {
+        if let Err(error) = self.detach() {
+            log::warn!("Failed to detach: {:#}", error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        cell::Cell,
+        error::Error,
+        rc::Rc,
+        sync::Arc,
+        time::Duration,
+    };
+
+    use raw_struct::MemoryView;
+
+    use super::{
+        CS2Session,
+        SessionState,
+    };
+    use crate::{
+        StateCS2Memory,
+        StateVariable,
+    };
+
+    /// Stand-in for the process handle
+    type StateProcessHandle = StateVariable<Arc<()>>;
+
+    /// Stand-in for the process memory, every read is filled with a constant byte
+    struct FillMemory(u8);
+
+    impl MemoryView for FillMemory {
+        fn read_memory(
+            &self,
+            _offset: u64,
+            buffer: &mut [u8],
+        ) -> Result<(), Box<dyn Error + Send + Sync>> {
+            buffer.fill(self.0);
+            Ok(())
+        }
+    }
+
+    fn read_byte(view: &dyn MemoryView) -> Option<u8> {
+        let mut buffer = [0u8; 1];
+        view.read_memory(0x1000, &mut buffer)
+            .ok()
+            .map(|_| buffer[0])
+    }
+
+    #[test]
+    fn attach_detach_cycles() {
+        let handle = Arc::new(());
+        let flushes = Rc::new(Cell::new(0));
+
+        let mut session = CS2Session::new(8);
+        session.on_detach({
+            let flushes = flushes.clone();
+            move |states| {
+                assert!(states.get::<StateProcessHandle>(()).is_some());
+                flushes.set(flushes.get() + 1);
+            }
+        });
+
+        for cycle in 1..=3 {
+            session
+                .attach_with(|states| states.set(StateProcessHandle::new(handle.clone()), ()))
+                .unwrap();
+            assert_eq!(session.state(), SessionState::Attached);
+            assert!(session.attach_with(|_| Ok(())).is_err());
+
+            session
+                .spawn_watchdog(Duration::from_secs(60), |_| {})
+                .unwrap();
+
+            /* a component of the consumer */
+            let component = {
+                let shutdown = session.shutdown_token().clone();
+                session
+                    .shutdown_token()
+                    .spawn_thread("component", move || {
+                        while !shutdown.sleep(Duration::from_secs(60)) {}
+                    })
+                    .unwrap()
+            };
+
+            let shutdown = session.shutdown_token().clone();
+            assert_eq!(shutdown.running_threads(), 2);
+            assert_eq!(Arc::strong_count(&handle), 2);
+
+            session.detach().unwrap();
+            assert_eq!(session.state(), SessionState::Detached);
+            assert_eq!(shutdown.running_threads(), 0);
+            component.join().unwrap();
+            assert_eq!(Arc::strong_count(&handle), 1);
+            assert!(session.states().get::<StateProcessHandle>(()).is_none());
+            assert_eq!(flushes.get(), cycle);
+
+            /* detaching again has no effect */
+            session.detach().unwrap();
+            assert_eq!(flushes.get(), cycle);
+        }
+    }
+
+    #[test]
+    fn memory_across_attachments() {
+        let mut session = CS2Session::new(8);
+        assert_eq!(read_byte(&**session.memory()), None);
+
+        session
+            .attach_view(Arc::new(FillMemory(1)), |_| Ok(()))
+            .unwrap();
+
+        /* view handed out to a component of the first attachment */
+        let component_view = session
+            .states()
+            .resolve::<StateCS2Memory>(())
+            .unwrap()
+            .value()
+            .clone();
+        assert_eq!(component_view.epoch(), 1);
+        assert_eq!(read_byte(&*component_view), Some(1));
+
+        session.detach().unwrap();
+        assert_eq!(component_view.epoch(), 2);
+        assert_eq!(read_byte(&*component_view), None);
+
+        session
+            .attach_view(Arc::new(FillMemory(2)), |_| Ok(()))
+            .unwrap();
+        assert_eq!(component_view.epoch(), 3);
+        assert_eq!(read_byte(&*component_view), Some(2));
+
+        let memory = session.states().resolve::<StateCS2Memory>(()).unwrap();
+        assert!(Arc::ptr_eq(memory.value(), &component_view));
+        drop(memory);
+        session.detach().unwrap();
+
+        /* a failed attachment does not leave the view attached */
+        assert!(session
+            .attach_view(Arc::new(FillMemory(3)), |_| anyhow::bail!("setup failed"))
+            .is_err());
+        assert_eq!(session.state(), SessionState::Detached);
+        assert_eq!(component_view.epoch(), 6);
+        assert_eq!(read_byte(&*component_view), None);
+    }
+}
@@ -0,0 +1,715 @@
+#[cfg(feature = "serialize")]
+use std::collections::BTreeMap;
+use std::{
+    collections::HashSet,
+    mem,
+    time::SystemTime,
+};
+
+use nalgebra::Vector3;
+use utils_state::StateRegistry;
+
+#[cfg(feature = "serialize")]
+use crate::derived::StateDerivedOutputs;
+use crate::{
+    diagnostics::NameRedactor,
+    BombState,
+    ControllerIndex,
+    DataQuality,
+    EntityIndex,
+    GrenadeKind,
+    PawnIndex,
+    PlantedC4Entry,
+    PlantedC4List,
+    PlantedC4State,
+    PlayerInterest,
+    StateCurrentMap,
+    StateGlobals,
+    StateGrenadeProjectiles,
+    StatePlayerList,
+    StateWorldDataQuality,
+    ViewAngles,
+    WeaponId,
+    BOMB_SITE_MAX,
+    BOMB_TIMER_MAX,
+    PLAYER_KNOWN_TEAM_IDS,
+    PLAYER_MAX_HEALTH_GUNGAME,
+    WORLD_BOUNDS,
+};
+
+/// Max amount of players within a snapshot
+pub const SNAPSHOT_MAX_PLAYERS: usize = 64;
+
+fn check_world_position(name: &str, position: &Vector3<f32>) -> Result<(), String> {
+    if position
+        .iter()
+        .all(|value| value.is_finite() && value.abs() <= WORLD_BOUNDS)
+    {
+        Ok(())
+    } else {
+        Err(format!("invalid {} position {:?}", name, position))
+    }
+}
+
+/// Details of a player which are only available within [SnapshotDetail::Full] snapshots
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotPlayerDetails {
+    pub player_name: Option<String>,
+    pub player_health: i32,
+    pub weapon: WeaponId,
+    pub view_angles: ViewAngles,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotPlayer {
+    pub pawn_entity_id: PawnIndex,
+    pub controller_entity_id: Option<ControllerIndex>,
+    pub team_id: u8,
+
+    pub alive: bool,
+    #[cfg_attr(feature = "serialize", serde(with = "crate::serialize::vector3"))]
+    pub position: Vector3<f32>,
+
+    pub details: Option<SnapshotPlayerDetails>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotGrenade {
+    pub entity_id: EntityIndex,
+    pub kind: GrenadeKind,
+
+    #[cfg_attr(feature = "serialize", serde(with = "crate::serialize::vector3"))]
+    pub position: Vector3<f32>,
+
+    /// Zero if the velocity could not be read
+    #[cfg_attr(feature = "serialize", serde(with = "crate::serialize::vector3"))]
+    pub velocity: Vector3<f32>,
+
+    /// Team of the player who threw the grenade.
+    /// None if the owner is unknown (e.g. disconnected).
+    pub owner_team_id: Option<u8>,
+
+    /// Time (in seconds) until the deployed smoke disappears (deployed smokes only)
+    pub effect_time_remaining: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum SnapshotDetail {
+    Full,
+
+    /// Player details and grenades have been dropped (see [GameSnapshot::reduce])
+    Reduced,
+}
+
+/// State of the game within a single frame.
+///
+/// With the `serialize` feature the snapshot can be serialized (e.g. for streaming it to a remote radar).
+/// The serialized form only contains plain values: vectors are serialized as `[x, y, z]`
+/// and enums are internally tagged by their `type`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameSnapshot {
+    pub server_time: f32,
+    pub captured_at: SystemTime,
+    pub map: Option<String>,
+
+    pub players: Vec<SnapshotPlayer>,
+    pub bomb: BombState,
+
+    /// Details of the primary planted bomb including its defuser (see [PlantedC4List::primary])
+    pub planted_c4: Option<PlantedC4Entry>,
+
+    pub grenades: Vec<SnapshotGrenade>,
+
+    /// Accuracy of the positions within this snapshot
+    pub world_data_quality: DataQuality,
+
+    pub detail: SnapshotDetail,
+
+    /// Outputs of the registered derived states by their name (see [crate::derived::DerivedStates]).
+    /// Derived states which failed are omitted.
+    #[cfg(feature = "serialize")]
+    #[serde(default)]
+    pub custom: BTreeMap<String, serde_json::Value>,
+}
+
+/// Outputs of the derived states for [GameSnapshot::custom]
+#[cfg(feature = "serialize")]
+fn capture_custom(states: &StateRegistry) -> anyhow::Result<BTreeMap<String, serde_json::Value>> {
+    let outputs = states.resolve::<StateDerivedOutputs>(())?;
+    for (name, error) in outputs.errors.iter() {
+        log::trace!("Derived state {} failed: {}", name, error);
+    }
+
+    Ok(outputs.custom.clone())
+}
+
+impl GameSnapshot {
+    /// Resolve the states of the current frame
+    pub fn capture(states: &StateRegistry) -> anyhow::Result<Self> {
+        let server_time = states.resolve::<StateGlobals>(())?.server_time()?;
+        let map = states.resolve::<StateCurrentMap>(())?.current_map.clone();
+
+        let player_list = states.resolve::<StatePlayerList>(PlayerInterest::All)?;
+        let players = player_list
+            .players
+            .iter()
+            .map(|entry| SnapshotPlayer {
+                pawn_entity_id: entry.pawn_entity_id,
+                controller_entity_id: entry.controller_entity_id,
+                team_id: entry.team_id,
+
+                alive: entry.alive,
+                position: entry.position,
+
+                details: entry.details.as_ref().map(|details| SnapshotPlayerDetails {
+                    player_name: details.player_name.clone(),
+                    player_health: details.player_health,
+                    weapon: if details.weapon_confidence.is_available() {
+                        details.weapon
+                    } else {
+                        WeaponId::Unknown
+                    },
+                    view_angles: details.view_angles,
+                }),
+            })
+            .collect();
+
+        let grenades = states
+            .resolve::<StateGrenadeProjectiles>(())?
+            .projectiles
+            .iter()
+            .map(|projectile| SnapshotGrenade {
+                entity_id: projectile.entity_id,
+                kind: projectile.kind,
+
+                position: projectile.position,
+                velocity: projectile.velocity.unwrap_or_default(),
+
+                owner_team_id: projectile.owner.as_ref().map(|owner| owner.team_id),
+                effect_time_remaining: projectile.effect_time_remaining,
+            })
+            .collect();
+
+        Ok(Self {
+            server_time,
+            captured_at: SystemTime::now(),
+            map,
+
+            players,
+            bomb: states.resolve::<BombState>(())?.clone(),
+            planted_c4: states.resolve::<PlantedC4List>(())?.primary().cloned(),
+            grenades,
+
+            world_data_quality: states.resolve::<StateWorldDataQuality>(())?.quality.clone(),
+
+            detail: SnapshotDetail::Full,
+
+            #[cfg(feature = "serialize")]
+            custom: capture_custom(states)?,
+        })
+    }
+
+    /// Drop the player details and grenades but keep the positions of the players and the bomb
+    pub fn reduce(&mut self) {
+        if self.detail == SnapshotDetail::Reduced {
+            return;
+        }
+
+        for player in self.players.iter_mut() {
+            player.details = None;
+        }
+        self.players.shrink_to_fit();
+        self.grenades = Vec::new();
+        self.detail = SnapshotDetail::Reduced;
+    }
+
+    /// Replace all player names by their pseudonyms (e.g. for sharing the snapshot publicly).
+    /// Use the same redactor for all snapshots of a recording to keep the pseudonyms stable.
+    pub fn redact(&mut self, redactor: &mut NameRedactor) {
+        for details in self
+            .players
+            .iter_mut()
+            .filter_map(|player| player.details.as_mut())
+        {
+            redactor.redact_option(&mut details.player_name);
+        }
+
+        if let BombState::Carried { carrier_name, .. } = &mut self.bomb {
+            redactor.redact_option(carrier_name);
+        }
+
+        if let Some(defuser) = self
+            .planted_c4
+            .as_mut()
+            .and_then(|bomb| bomb.defuser.as_mut())
+        {
+            /* the name is a placeholder if the details could not be read */
+            if defuser.confidence.is_available() {
+                defuser.player_name = redactor.redact(&defuser.player_name);
+            }
+        }
+    }
+
+    /// Check the snapshot for values and combinations which can not occur within a real game.
+    /// Shared by all producers of snapshots (e.g. replays and the [crate::DemoMatch])
+    /// so consumers can rely on the same invariants regardless of the data source.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.server_time.is_finite() || self.server_time < 0.0 {
+            return Err(format!("invalid server time {}", self.server_time));
+        }
+
+        if self.players.len() > SNAPSHOT_MAX_PLAYERS {
+            return Err(format!("too many players ({})", self.players.len()));
+        }
+
+        let mut pawns = HashSet::with_capacity(self.players.len());
+        for player in self.players.iter() {
+            if !pawns.insert(player.pawn_entity_id) {
+                return Err(format!("duplicated pawn {}", player.pawn_entity_id));
+            }
+
+            if !PLAYER_KNOWN_TEAM_IDS.contains(&player.team_id) {
+                return Err(format!(
+                    "invalid team {} of pawn {}",
+                    player.team_id, player.pawn_entity_id
+                ));
+            }
+
+            check_world_position("player", &player.position)?;
+            if let Some(details) = &player.details {
+                let health_range = if player.alive {
+                    1..=PLAYER_MAX_HEALTH_GUNGAME
+                } else {
+                    0..=0
+                };
+                if !health_range.contains(&details.player_health) {
+                    return Err(format!(
+                        "invalid health {} of pawn {} (alive = {})",
+                        details.player_health, player.pawn_entity_id, player.alive
+                    ));
+                }
+            }
+        }
+
+        let is_alive_player = |pawn: PawnIndex| {
+            self.players
+                .iter()
+                .any(|player| player.pawn_entity_id == pawn && player.alive)
+        };
+
+        match &self.bomb {
+            BombState::Carried {
+                carrier_entity_id,
+                position,
+                ..
+            } => {
+                check_world_position("bomb", position)?;
+                if !is_alive_player(*carrier_entity_id) {
+                    return Err(format!(
+                        "bomb carried by pawn {} which is not an alive player",
+                        carrier_entity_id
+                    ));
+                }
+            }
+            BombState::Dropped { position, .. } => check_world_position("bomb", position)?,
+            BombState::Planted {
+                bomb_site,
+                position,
+            } => {
+                check_world_position("bomb", position)?;
+                match &self.planted_c4 {
+                    Some(planted_c4) if planted_c4.bomb_site == *bomb_site => {}
+                    Some(planted_c4) => {
+                        return Err(format!(
+                            "bomb planted at site {} but the planted C4 is at site {}",
+                            bomb_site, planted_c4.bomb_site
+                        ));
+                    }
+                    None => return Err("bomb planted without a planted C4".to_string()),
+                }
+            }
+            BombState::NotInRound => {}
+        }
+
+        if let Some(planted_c4) = &self.planted_c4 {
+            if !matches!(self.bomb, BombState::Planted { .. }) {
+                return Err("planted C4 while the bomb has not been planted".to_string());
+            }
+
+            if planted_c4.bomb_site > BOMB_SITE_MAX {
+                return Err(format!("invalid bomb site {}", planted_c4.bomb_site));
+            }
+
+            check_world_position("planted C4", &planted_c4.position)?;
+            match planted_c4.state {
+                PlantedC4State::Active { time_detonation } => {
+                    if !(0.0..=BOMB_TIMER_MAX).contains(&time_detonation) {
+                        return Err(format!("invalid detonation time {}", time_detonation));
+                    }
+                }
+                PlantedC4State::NotPlanted => {
+                    return Err("planted C4 which has not been planted".to_string())
+                }
+                PlantedC4State::Detonated | PlantedC4State::Defused => {}
+            }
+
+            if let Some(defuser) = &planted_c4.defuser {
+                if !matches!(planted_c4.state, PlantedC4State::Active { .. }) {
+                    return Err("defuser of an inactive bomb".to_string());
+                }
+
+                if !(0.0..=defuser.defuse_duration_total).contains(&defuser.time_remaining) {
+                    return Err(format!(
+                        "invalid defuse time {} of {}",
+                        defuser.time_remaining, defuser.defuse_duration_total
+                    ));
+                }
+
+                if defuser.confidence.is_fresh() && !is_alive_player(defuser.pawn_entity_id) {
+                    return Err(format!(
+                        "bomb defused by pawn {} which is not an alive player",
+                        defuser.pawn_entity_id
+                    ));
+                }
+            }
+        }
+
+        let mut grenades = HashSet::with_capacity(self.grenades.len());
+        for grenade in self.grenades.iter() {
+            if !grenades.insert(grenade.entity_id) {
+                return Err(format!("duplicated grenade {}", grenade.entity_id));
+            }
+
+            check_world_position("grenade", &grenade.position)?;
+            if grenade.velocity.iter().any(|value| !value.is_finite()) {
+                return Err(format!("invalid grenade velocity {:?}", grenade.velocity));
+            }
+
+            if let Some(remaining) = grenade.effect_time_remaining {
+                if grenade.kind != GrenadeKind::Smoke || !remaining.is_finite() || remaining < 0.0 {
+                    return Err(format!(
+                        "invalid effect time {} of a {:?} grenade",
+                        remaining, grenade.kind
+                    ));
+                }
+            }
+        }
+
+        if self.detail == SnapshotDetail::Reduced
+            && (!self.grenades.is_empty()
+                || self.players.iter().any(|player| player.details.is_some()))
+        {
+            return Err("reduced snapshot with player details or grenades".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Estimated amount of heap and inline memory (in bytes) used by this snapshot
+    pub fn estimated_size(&self) -> usize {
+        let player_names = self
+            .players
+            .iter()
+            .filter_map(|player| player.details.as_ref())
+            .filter_map(|details| details.player_name.as_ref())
+            .map(String::capacity)
+            .sum::<usize>();
+
+        let bomb_carrier_name = match &self.bomb {
+            BombState::Carried {
+                carrier_name: Some(name),
+                ..
+            } => name.capacity(),
+            _ => 0,
+        };
+
+        let defuser_name = self
+            .planted_c4
+            .as_ref()
+            .and_then(|bomb| bomb.defuser.as_ref())
+            .map(|defuser| defuser.player_name.capacity())
+            .unwrap_or(0);
+
+        let quality_reason = match &self.world_data_quality {
+            DataQuality::Degraded { reason } => reason.capacity(),
+            _ => 0,
+        };
+
+        mem::size_of::<Self>()
+            + self.map.as_ref().map(String::capacity).unwrap_or(0)
+            + self.players.capacity() * mem::size_of::<SnapshotPlayer>()
+            + player_names
+            + bomb_carrier_name
+            + defuser_name
+            + quality_reason
+            + self.grenades.capacity() * mem::size_of::<SnapshotGrenade>()
+    }
+}
+
+#[cfg(all(test, feature = "serialize"))]
+mod test {
+    use std::{
+        collections::BTreeMap,
+        time::SystemTime,
+    };
+
+    use nalgebra::Vector3;
+    use serde_json::json;
+    use utils_state::StateRegistry;
+
+    use super::{
+        capture_custom,
+        GameSnapshot,
+        SnapshotDetail,
+        SnapshotPlayer,
+        SnapshotPlayerDetails,
+    };
+    use crate::{
+        derived::{
+            DerivedInputs,
+            DerivedState,
+            DerivedStates,
+        },
+        BombDefuser,
+        BombState,
+        DataQuality,
+        EntityIndex,
+        FieldConfidence,
+        PawnIndex,
+        PlantedC4Entry,
+        PlantedC4RawFields,
+        PlantedC4State,
+        ViewAngles,
+        WeaponId,
+    };
+
+    fn snapshot() -> GameSnapshot {
+        GameSnapshot {
+            server_time: 100.0,
+            captured_at: SystemTime::now(),
+            map: Some("de_mirage".to_string()),
+
+            players: vec![SnapshotPlayer {
+                pawn_entity_id: PawnIndex(1),
+                controller_entity_id: None,
+                team_id: 3,
+                alive: true,
+                position: Vector3::new(1.0, 2.0, 3.0),
+                details: Some(SnapshotPlayerDetails {
+                    player_name: Some("defuser".to_string()),
+                    player_health: 100,
+                    weapon: WeaponId::Ak47,
+                    view_angles: ViewAngles::from_engine(10.0, 370.0),
+                }),
+            }],
+            bomb: BombState::Planted {
+                bomb_site: 1,
+                position: Vector3::new(4.0, 5.0, 6.0),
+            },
+            planted_c4: Some(PlantedC4Entry {
+                entity_index: EntityIndex(120),
+                bomb_site: 1,
+                state: PlantedC4State::Active {
+                    time_detonation: 30.0,
+                },
+                position: Vector3::new(4.0, 5.0, 6.0),
+                defuser: Some(BombDefuser {
+                    pawn_entity_id: PawnIndex(1),
+                    time_remaining: 4.0,
+                    defuse_duration_total: 5.0,
+                    has_kit: true,
+                    can_defuse_in_time: true,
+                    player_name: "defuser".to_string(),
+                    health: 100,
+                    armor: 100,
+                    is_last_alive_ct: false,
+                    confidence: FieldConfidence::Fresh,
+                }),
+                time_blow: 130.0,
+                plant_time: 90.0,
+                pre_planted: false,
+                detonation_deadline: None,
+                raw_fields: PlantedC4RawFields {
+                    activated: true,
+                    time_blow: 130.0,
+                    being_defused: true,
+                    defused: false,
+                    defuse_countdown: 104.0,
+                },
+            }),
+            grenades: Vec::new(),
+
+            world_data_quality: DataQuality::Degraded {
+                reason: "recovering from a freeze".to_string(),
+            },
+
+            detail: SnapshotDetail::Full,
+
+            custom: BTreeMap::from([("spread".to_string(), json!({ "terrorists": 300.0 }))]),
+        }
+    }
+
+    #[test]
+    fn wire_format() {
+        let value = serde_json::to_value(snapshot()).unwrap();
+        assert_eq!(value["players"][0]["position"], json!([1.0, 2.0, 3.0]));
+        assert_eq!(value["players"][0]["pawn_entity_id"], json!(1));
+
+        /* raw engine view angles */
+        assert_eq!(
+            value["players"][0]["details"]["view_angles"],
+            json!({ "pitch": 10.0, "yaw": 370.0 })
+        );
+
+        assert_eq!(
+            value["bomb"],
+            json!({ "type": "Planted", "bomb_site": 1, "position": [4.0, 5.0, 6.0] })
+        );
+        assert_eq!(
+            value["planted_c4"]["state"],
+            json!({ "type": "Active", "time_detonation": 30.0 })
+        );
+        assert_eq!(
+            value["planted_c4"]["defuser"]["confidence"],
+            json!({ "type": "Fresh" })
+        );
+        assert_eq!(value["world_data_quality"]["type"], json!("Degraded"));
+        assert_eq!(
+            value["custom"],
+            json!({ "spread": { "terrorists": 300.0 } })
+        );
+    }
+
+    struct Constant(&'static str, Option<serde_json::Value>);
+    impl DerivedState for Constant {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn derive(&mut self, _inputs: &DerivedInputs) -> anyhow::Result<serde_json::Value> {
+            self.1
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("analysis failed"))
+        }
+    }
+
+    #[test]
+    fn custom_outputs() {
+        let states = StateRegistry::new(0x20);
+        assert!(capture_custom(&states).unwrap().is_empty());
+
+        let derived = states.resolve::<DerivedStates>(()).unwrap().clone();
+        derived
+            .register(Constant("average_team_distance", Some(json!(42.0))))
+            .unwrap();
+        derived.register(Constant("failing", None)).unwrap();
+
+        /* the failing derived state is omitted */
+        let custom = capture_custom(&states).unwrap();
+        assert_eq!(
+            custom,
+            BTreeMap::from([("average_team_distance".to_string(), json!(42.0))])
+        );
+
+        let mut snapshot = snapshot();
+        snapshot.custom = custom;
+        let value = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(value["custom"]["average_team_distance"], json!(42.0));
+
+        /* snapshots without custom outputs remain readable */
+        let mut value = value;
+        value.as_object_mut().unwrap().remove("custom");
+        let snapshot = serde_json::from_value::<GameSnapshot>(value).unwrap();
+        assert!(snapshot.custom.is_empty());
+    }
+
+    #[test]
+    fn validate() {
+        assert_eq!(snapshot().validate(), Ok(()));
+
+        let mut reduced = snapshot();
+        reduced.reduce();
+        assert_eq!(reduced.validate(), Ok(()));
+
+        let mut invalid = snapshot();
+        invalid.players[0].details.as_mut().unwrap().player_health = 0;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = snapshot();
+        invalid.players.push(invalid.players[0].clone());
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = snapshot();
+        invalid.players[0].position.x = f32::NAN;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = snapshot();
+        invalid.bomb = BombState::NotInRound;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = snapshot();
+        invalid.planted_c4.as_mut().unwrap().bomb_site = 0;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = snapshot();
+        invalid
+            .planted_c4
+            .as_mut()
+            .unwrap()
+            .defuser
+            .as_mut()
+            .unwrap()
+            .time_remaining = 6.0;
+        assert!(invalid.validate().is_err());
+
+        /* the defuser is only required to be an alive player if its details are fresh */
+        let mut stale = snapshot();
+        stale.players[0].alive = false;
+        stale.players[0].details = None;
+        assert!(stale.validate().is_err());
+        stale
+            .planted_c4
+            .as_mut()
+            .unwrap()
+            .defuser
+            .as_mut()
+            .unwrap()
+            .confidence = FieldConfidence::Stale { frames: 2 };
+        assert_eq!(stale.validate(), Ok(()));
+
+        let mut invalid = snapshot();
+        invalid.detail = SnapshotDetail::Reduced;
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let serialized = serde_json::to_string(&snapshot()).unwrap();
+        let snapshot = serde_json::from_str::<GameSnapshot>(&serialized).unwrap();
+        assert_eq!(serde_json::to_string(&snapshot).unwrap(), serialized);
+
+        assert_eq!(snapshot.players[0].position, Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(
+            snapshot.players[0].details.as_ref().unwrap().view_angles,
+            ViewAngles::from_engine(10.0, 370.0)
+        );
+        assert!(matches!(
+            snapshot.planted_c4.unwrap().state,
+            PlantedC4State::Active { time_detonation } if time_detonation == 30.0
+        ));
+
+        /* instants are relative to the deserialization */
+        let frozen =
+            serde_json::from_value::<DataQuality>(json!({ "type": "Frozen", "frozen_for": 2.0 }))
+                .unwrap();
+        match frozen {
+            DataQuality::Frozen { since } => assert!(since.elapsed().as_secs_f32() >= 2.0),
+            quality => panic!("unexpected quality {:?}", quality),
+        }
+    }
+}
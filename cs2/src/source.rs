@@ -0,0 +1,185 @@
+use std::path::PathBuf;
+#[cfg(feature = "serialize")]
+use std::{
+    fs::File,
+    io::{
+        self,
+        BufRead,
+        BufReader,
+    },
+};
+
+#[cfg(feature = "serialize")]
+use anyhow::Context;
+
+use crate::{
+    CS2Session,
+    DemoMatch,
+    GameSnapshot,
+    SessionState,
+};
+
+/// Source of the snapshots polled by a [SnapshotPoller]
+pub enum DataSource {
+    /// Capture the snapshots from an attached session.
+    /// The consumer is responsible for setting up the schema before attaching.
+    Live(CS2Session),
+
+    /// Replay snapshots from a file containing one serialized snapshot per line.
+    /// Requires the `serialize` feature.
+    Replay(PathBuf),
+
+    /// Generate synthetic snapshots from the given seed (see [DemoMatch])
+    Demo(u64),
+}
+
+enum PollerSource {
+    Live(CS2Session),
+    #[cfg(feature = "serialize")]
+    Replay(io::Lines<BufReader<File>>),
+    Demo(DemoMatch),
+}
+
+/// Polls one [GameSnapshot] per frame regardless of the data source,
+/// allowing consumers to develop and run their CI against a replay or the demo generator.
+pub struct SnapshotPoller {
+    source: PollerSource,
+}
+
+impl SnapshotPoller {
+    pub fn open(source: DataSource) -> anyhow::Result<Self> {
+        let source = match source {
+            DataSource::Live(session) => {
+                if session.state() != SessionState::Attached {
+                    anyhow::bail!("live session is not attached");
+                }
+
+                PollerSource::Live(session)
+            }
+            #[cfg(feature = "serialize")]
+            DataSource::Replay(file) => {
+                let reader =
+                    File::open(&file).with_context(|| format!("open replay {}", file.display()))?;
+                PollerSource::Replay(BufReader::new(reader).lines())
+            }
+            #[cfg(not(feature = "serialize"))]
+            DataSource::Replay(file) => {
+                anyhow::bail!(
+                    "replaying {} requires the serialize feature",
+                    file.display()
+                )
+            }
+            DataSource::Demo(seed) => PollerSource::Demo(DemoMatch::new(seed)),
+        };
+
+        Ok(Self { source })
+    }
+
+    /// Session of a live source
+    pub fn session(&self) -> Option<&CS2Session> {
+        match &self.source {
+            PollerSource::Live(session) => Some(session),
+            _ => None,
+        }
+    }
+
+    /// Poll the snapshot of the next frame.
+    /// Returns None if the source has been exhausted (end of a replay).
+    pub fn poll(&mut self) -> anyhow::Result<Option<GameSnapshot>> {
+        match &mut self.source {
+            PollerSource::Live(session) => {
+                let states = session.states_mut();
+                states.invalidate_states();
+                GameSnapshot::capture(states).map(Some)
+            }
+            #[cfg(feature = "serialize")]
+            PollerSource::Replay(lines) => {
+                for line in lines.by_ref() {
+                    let line = line.context("read replay")?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+
+                    let snapshot =
+                        serde_json::from_str::<GameSnapshot>(&line).context("parse snapshot")?;
+                    return Ok(Some(snapshot));
+                }
+
+                Ok(None)
+            }
+            PollerSource::Demo(demo) => {
+                demo.advance();
+                Ok(Some(demo.snapshot()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        DataSource,
+        SnapshotPoller,
+    };
+    use crate::CS2Session;
+
+    #[test]
+    fn demo_source() {
+        let mut poller = SnapshotPoller::open(DataSource::Demo(42)).unwrap();
+        let mut server_time = 0.0;
+        for _ in 0..1000 {
+            let snapshot = poller.poll().unwrap().unwrap();
+            snapshot.validate().unwrap();
+
+            assert!(snapshot.server_time > server_time);
+            server_time = snapshot.server_time;
+        }
+    }
+
+    #[test]
+    fn detached_session() {
+        let session = CS2Session::new(0x100);
+        assert!(SnapshotPoller::open(DataSource::Live(session)).is_err());
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn replay_source() {
+        use std::{
+            fs,
+            io::Write,
+            time::{
+                SystemTime,
+                UNIX_EPOCH,
+            },
+        };
+
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let file = std::env::temp_dir().join(format!("cs2-replay-{}.jsonl", nonce));
+
+        let mut demo = SnapshotPoller::open(DataSource::Demo(7)).unwrap();
+        let mut recorded = Vec::new();
+        {
+            let mut output = fs::File::create(&file).unwrap();
+            for _ in 0..100 {
+                let serialized = serde_json::to_string(&demo.poll().unwrap().unwrap()).unwrap();
+                writeln!(output, "{}", serialized).unwrap();
+                recorded.push(serialized);
+            }
+        }
+
+        let mut replay = SnapshotPoller::open(DataSource::Replay(file.clone())).unwrap();
+        for serialized in recorded.iter() {
+            let snapshot = replay.poll().unwrap().unwrap();
+            snapshot.validate().unwrap();
+            assert_eq!(&serde_json::to_string(&snapshot).unwrap(), serialized);
+        }
+        assert!(replay.poll().unwrap().is_none());
+
+        fs::remove_file(&file).unwrap();
+        assert!(SnapshotPoller::open(DataSource::Replay(file)).is_err());
+    }
+}
@@ -1,40 +1,121 @@
-use std::ffi::CStr;
+use std::{
+    ffi::CStr,
+    time::SystemTime,
+};
 
 use anyhow::Context;
+use cs2_schema_cutl::EntityHandle;
 use cs2_schema_generated::cs2::client::{
+    CCSPlayer_ItemServices,
+    CEntityIdentity,
     C_BaseEntity,
     C_BasePlayerPawn,
+    C_CSGameRules,
     C_CSPlayerPawn,
+    C_CSWeaponBase,
     C_EconEntity,
     C_PlantedC4,
     C_C4,
 };
 use nalgebra::Vector3;
 use obfstr::obfstr;
+use raw_struct::{
+    Copy,
+    Reference,
+};
 use utils_state::{
     State,
     StateCacheType,
     StateRegistry,
 };
 
-use super::StateGlobals;
+use super::{
+    check_planted_c4,
+    player_details_or_read,
+    resolve_pawn_owner,
+    FieldConfidence,
+    FieldShadow,
+    ResultSkipExt,
+    RoundClock,
+    RoundClockInput,
+    StateAlivePlayerCount,
+    StateDefuseShadow,
+    StateGameRules,
+    StateGlobals,
+    StatePlausibility,
+    StatePlayerList,
+    StateServerClock,
+};
 use crate::{
     CEntityIdentityEx,
+    CachedEntityLocator,
     ClassNameCache,
+    EntityClassFilter,
+    EntityIndex,
+    PawnIndex,
     StateCS2Memory,
+    StateEntityClassIndex,
     StateEntityList,
 };
 
-#[derive(Debug)]
+/// Defuse durations (in seconds) above this are considered to be without a defuse kit
+const DEFUSE_WITHOUT_KIT_MIN_LENGTH: f32 = 7.5;
+
+/// The defuse finishes before the bomb detonates
+pub fn can_defuse_in_time(defuse_time_remaining: f32, time_detonation: f32) -> bool {
+    defuse_time_remaining < time_detonation
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct BombDefuser {
+    /// Entity index of the defusers pawn
+    pub pawn_entity_id: PawnIndex,
+
     /// Totoal time remaining for a successful bomb defuse
     pub time_remaining: f32,
 
+    /// Total duration (in seconds) of the defuse (e.g. 5 seconds with a kit and 10 seconds without)
+    pub defuse_duration_total: f32,
+
+    /// The defuser has a defuse kit.
+    /// If the defusers pawn could not be read, this is derived from `defuse_duration_total`.
+    pub has_kit: bool,
+
+    /// The defuse will finish before the bomb detonates (see [can_defuse_in_time])
+    pub can_defuse_in_time: bool,
+
     /// The defusers player name
     pub player_name: String,
+
+    /// Current health of the defuser
+    pub health: i32,
+
+    /// Current armor of the defuser
+    pub armor: i32,
+
+    /// The defuser is the last alive counter-terrorist
+    pub is_last_alive_ct: bool,
+
+    /// Confidence of the defusers player details (`player_name`, `health`, `armor` and `has_kit`)
+    pub confidence: FieldConfidence,
+}
+
+impl BombDefuser {
+    /// The defusers player name or None if the name is a placeholder
+    /// because the player details could not be read
+    pub fn known_player_name(&self) -> Option<&str> {
+        if self.confidence.is_available() {
+            Some(&self.player_name)
+        } else {
+            None
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(tag = "type"))]
 pub enum PlantedC4State {
     /// Bomb is currently actively ticking
     Active {
@@ -52,7 +133,9 @@ pub enum PlantedC4State {
     NotPlanted,
 }
 
-/// Information about the currently active planted C4
+/// Information about the currently active planted C4.
+/// If multiple bombs have been planted, the primary bomb of the [PlantedC4List] will be reported.
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlantedC4 {
     /// Planted bomb site
     /// 0 = A
@@ -63,17 +146,67 @@ pub struct PlantedC4 {
     pub state: PlantedC4State,
 
     /// Position of the planted bomb.
+    #[cfg_attr(feature = "serialize", serde(with = "crate::serialize::vector3"))]
     pub position: Vector3<f32>,
 
     /// Current bomb defuser
     pub defuser: Option<BombDefuser>,
+
+    /// Amount of defuse attempts (including the current one) for this plant
+    pub defuse_attempts_this_plant: u32,
+
+    /// The defuser repeatedly started and stopped defusing (see [StateDefuseShadow])
+    pub likely_faking: bool,
+
+    /// The bomb has been planted before the round started (e.g. retake servers)
+    pub pre_planted: bool,
+
+    /// Time remaining on the round clock when the bomb has been planted.
+    /// None if the bomb has been pre-planted or the game rules could not be read.
+    pub planted_at_round_remaining: Option<f32>,
+
+    /// Server time of the plant
+    pub planted_at_server_time: Option<f32>,
+
+    /// Wall-clock time of the detonation.
+    /// Only available while the bomb is active and the server clock has been synchronized.
+    pub detonation_deadline: Option<SystemTime>,
+
+    /// The bomb state could not be read as the state registry is in degraded mode
+    pub unavailable: bool,
+
+    /// Raw values of the last inspected C4 entity which drove the state.
+    /// Intended for debugging state transitions only.
+    pub raw_fields: Option<PlantedC4RawFields>,
+}
+
+/// Values of the `C_PlantedC4` entity as read from memory (before any plausibility substitution)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlantedC4RawFields {
+    pub activated: bool,
+    pub time_blow: f32,
+    pub being_defused: bool,
+    pub defused: bool,
+    pub defuse_countdown: f32,
+}
+
+/// Bombs planted within this time (in seconds) after the round start are considered pre-planted
+pub const BOMB_PRE_PLANT_THRESHOLD: f32 = 1.0;
+
+/// Check if the bomb has been planted before the round started.
+/// This is the case on retake servers where the round starts with an already planted bomb.
+pub fn is_bomb_pre_planted(plant_time: f32, round_start_time: f32, freeze_period: bool) -> bool {
+    freeze_period || plant_time <= round_start_time + BOMB_PRE_PLANT_THRESHOLD
 }
 
-/// Information about the current bomb carrier
+/// Information about the current bomb carrier.
+/// See [BombState] for the location of the bomb if it is not being carried.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct BombCarrierInfo {
-    /// Entity ID of the player carrying the bomb
-    pub carrier_entity_id: Option<u32>,
+    /// Pawn of the player carrying the bomb
+    pub carrier_entity_id: Option<PawnIndex>,
 
     /// Name of the player carrying the bomb
     pub carrier_name: Option<String>,
@@ -82,124 +215,666 @@ pub struct BombCarrierInfo {
     pub carrier_team_id: Option<u8>,
 }
 
-impl State for PlantedC4 {
+#[derive(Debug, Clone, Copy)]
+struct PlantedC4Timers {
+    bomb_site: u8,
+    time_blow: f32,
+    timer_length: f32,
+}
+
+/// Last plausible timers of the planted C4 entities.
+/// Used to substitute implausible values (see [check_planted_c4]).
+struct StatePlantedC4Shadow {
+    frame: u64,
+    timers: FieldShadow<PlantedC4Timers>,
+}
+
+impl State for StatePlantedC4Shadow {
     type Parameter = ();
 
-    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            frame: 0,
+            timers: Default::default(),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, _states: &StateRegistry) -> anyhow::Result<()> {
+        self.frame += 1;
+        self.timers.prune(self.frame);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct PlantTiming {
+    round_remaining: Option<f32>,
+    server_time: Option<f32>,
+}
+
+/// Timing of the current plant captured at the first observation of the planted C4.
+/// As the round clock stops with the plant, the timing must be retained for the life of the plant.
+struct StatePlantTimingShadow {
+    /// Detonation time of the bomb this shadow is tracking.
+    /// Used to detect new plants.
+    plant_time_blow: Option<f32>,
+    timing: PlantTiming,
+}
+
+impl State for StatePlantTimingShadow {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            plant_time_blow: None,
+            timing: Default::default(),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+}
+
+impl StatePlantTimingShadow {
+    /// Timing of the bomb identified by its detonation time.
+    /// The timing will only be captured for new plants.
+    fn push_plant(
+        &mut self,
+        plant_time_blow: f32,
+        capture: impl FnOnce() -> PlantTiming,
+    ) -> PlantTiming {
+        if self.plant_time_blow != Some(plant_time_blow) {
+            self.plant_time_blow = Some(plant_time_blow);
+            self.timing = capture();
+        }
+
+        self.timing
+    }
+
+    fn reset(&mut self) {
+        self.plant_time_blow = None;
+        self.timing = Default::default();
+    }
+}
+
+impl PlantedC4List {
+    fn capture_plant_timing(
+        states: &StateRegistry,
+        plant_time: f32,
+        pre_planted: bool,
+    ) -> PlantTiming {
+        let round_remaining = (|| -> anyhow::Result<Option<f32>> {
+            if pre_planted {
+                return Ok(None);
+            }
+
+            let game_rules = states.resolve::<StateGameRules>(())?;
+            let Some(rules) = &game_rules.rules else {
+                return Ok(None);
+            };
+
+            Ok(RoundClock::remaining_at_plant(
+                &RoundClockInput::read(rules)?,
+                plant_time,
+            ))
+        })();
+
+        PlantTiming {
+            round_remaining: round_remaining.ok().flatten(),
+            server_time: Some(plant_time),
+        }
+    }
+
+    fn read_defuser_details(
+        states: &StateRegistry,
+        handle_defuser: &EntityHandle<dyn C_CSPlayerPawn>,
+    ) -> anyhow::Result<(String, i32, i32, bool)> {
         let memory = states.resolve::<StateCS2Memory>(())?;
-        let globals = states.resolve::<StateGlobals>(())?;
         let entities = states.resolve::<StateEntityList>(())?;
         let class_name_cache = states.resolve::<ClassNameCache>(())?;
 
-        for entity_identity in entities.entities().iter() {
-            let class_name = class_name_cache
-                .lookup(&entity_identity.entity_class_info()?)
-                .context("class name")?;
+        let defuser = entities
+            .resolve_typed(&class_name_cache, handle_defuser)?
+            .context("missing bomb defuser pawn")?
+            .value_reference(memory.view_arc())
+            .context("defuser pawn nullptr")?;
 
-            if !class_name
-                .map(|name| name == "C_PlantedC4")
-                .unwrap_or(false)
-            {
-                /* Entity isn't the planted bomb. */
-                continue;
-            }
+        let defuser_health = defuser.m_iHealth()?;
+        let defuser_armor = defuser.m_ArmorValue()?;
+        let defuser_has_kit = defuser
+            .m_pItemServices()?
+            .value_reference(memory.view_arc())
+            .context("m_pItemServices nullptr")?
+            .cast::<dyn CCSPlayer_ItemServices>()
+            .m_bHasDefuser()?;
 
-            let bomb = entity_identity
-                .entity_ptr::<dyn C_PlantedC4>()?
-                .value_copy(memory.view())?
-                .context("bomb entity nullptr")?;
+        let defuser_controller = defuser.m_hController()?;
+        let defuser_controller = entities
+            .resolve_typed(&class_name_cache, &defuser_controller)?
+            .with_context(|| obfstr!("missing bomb defuser controller").to_string())?
+            .value_reference(memory.view_arc())
+            .context("defuser constroller nullptr")?;
 
-            let game_scene_node = entity_identity
-                .entity_ptr::<dyn C_BaseEntity>()?
-                .value_reference(memory.view_arc())
-                .context("C_BaseEntity pointer was null")?
-                .m_pGameSceneNode()?
-                .value_reference(memory.view_arc())
-                .context("m_pGameSceneNode pointer was null")?
-                .copy()?;
+        let defuser_name = CStr::from_bytes_until_nul(&defuser_controller.m_iszPlayerName()?)
+            .ok()
+            .map(CStr::to_string_lossy)
+            .unwrap_or("Name Error".into())
+            .to_string();
 
-            let position = game_scene_node.m_vecAbsOrigin()?;
+        Ok((defuser_name, defuser_health, defuser_armor, defuser_has_kit))
+    }
 
-            if !bomb.m_bC4Activated()? {
-                /* This bomb hasn't been activated (yet) */
-                continue;
+    /// Name, health, armor, kit and the confidence of these details of the bomb defuser.
+    /// Uses the details of an already resolved player list if available (see [player_details_or_read])
+    /// which may be stale. The kit will be derived from the defuse duration if the details are not readable.
+    fn defuser_details(
+        states: &StateRegistry,
+        handle_defuser: &EntityHandle<dyn C_CSPlayerPawn>,
+        defuse_duration_total: f32,
+    ) -> (String, i32, i32, bool, FieldConfidence) {
+        let defuser_details = player_details_or_read(
+            StatePlayerList::resolved_details(states, PawnIndex::from_handle(handle_defuser)),
+            |details| {
+                /* read the kit and armor directly if they are not known within the details */
+                if !details.economy_confidence.is_available() {
+                    return None;
+                }
+
+                Some((
+                    details.player_name.clone()?,
+                    details.player_health,
+                    details.player_armor,
+                    details.player_has_defuser,
+                ))
+            },
+            || Self::read_defuser_details(states, handle_defuser),
+        );
+
+        match defuser_details {
+            Ok(((name, health, armor, has_kit), confidence)) => {
+                (name, health, armor, has_kit, confidence)
             }
+            Err(err) => (
+                "Unknown".to_string(),
+                0,
+                0,
+                defuse_duration_total < DEFUSE_WITHOUT_KIT_MIN_LENGTH,
+                FieldConfidence::Unavailable {
+                    reason: format!("{:#}", err),
+                },
+            ),
+        }
+    }
+}
 
-            let bomb_site = bomb.m_nBombSite()? as u8;
-            if bomb.m_bBombDefused()? {
-                return Ok(Self {
-                    bomb_site,
-                    position: position.into(),
-                    defuser: None,
-                    state: PlantedC4State::Defused,
-                });
+/// World position of an entity
+fn read_scene_origin(
+    memory: &StateCS2Memory,
+    entity_identity: &Copy<dyn CEntityIdentity>,
+) -> anyhow::Result<Vector3<f32>> {
+    let position = entity_identity
+        .entity_ptr::<dyn C_BaseEntity>()?
+        .value_reference(memory.view_arc())
+        .context("C_BaseEntity pointer was null")?
+        .m_pGameSceneNode()?
+        .value_reference(memory.view_arc())
+        .context("m_pGameSceneNode pointer was null")?
+        .copy()?
+        .m_vecAbsOrigin()?;
+
+    Ok(position.into())
+}
+
+/// Raw values of a planted C4 found by the class scan.
+/// Fails if the entity has been removed since the scan (e.g. during a round restart).
+fn read_planted_c4(
+    memory: &StateCS2Memory,
+    entity_identity: &Copy<dyn CEntityIdentity>,
+) -> anyhow::Result<(Copy<dyn C_PlantedC4>, PlantedC4RawFields)> {
+    let bomb = entity_identity
+        .entity_ptr::<dyn C_PlantedC4>()?
+        .value_copy(memory.view())?
+        .context("bomb entity nullptr")?;
+
+    let raw_fields = PlantedC4RawFields {
+        activated: bomb.m_bC4Activated()?,
+        time_blow: bomb.m_flC4Blow()?.m_Value()?,
+        being_defused: bomb.m_bBeingDefused()?,
+        defused: bomb.m_bBombDefused()?,
+        defuse_countdown: bomb.m_flDefuseCountDown()?.m_Value()?,
+    };
+
+    Ok((bomb, raw_fields))
+}
+
+/// C4 entity found by the class scan and the handle of its owner.
+/// Fails if the entity has been removed since the scan (e.g. the carrier disconnected).
+fn read_c4_entity(
+    memory: &StateCS2Memory,
+    entity_identity: &Copy<dyn CEntityIdentity>,
+) -> anyhow::Result<(Reference<dyn C_C4>, EntityHandle<dyn C_BaseEntity>)> {
+    let c4_entity = entity_identity
+        .entity_ptr::<dyn C_EconEntity>()?
+        .value_reference(memory.view_arc())
+        .context("C4 entity nullptr")?
+        .cast::<dyn C_C4>();
+
+    let owner_handle = c4_entity.m_hOwnerEntity()?;
+    Ok((c4_entity, owner_handle))
+}
+
+struct PlantedC4Class;
+
+impl EntityClassFilter for PlantedC4Class {
+    const CLASS_NAME: &'static str = "C_PlantedC4";
+}
+
+/// A single activated `C_PlantedC4` entity
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlantedC4Entry {
+    pub entity_index: EntityIndex,
+
+    /// Planted bomb site
+    /// 0 = A
+    /// 1 = B
+    pub bomb_site: u8,
+
+    /// Current state of the planted C4.
+    /// Never [PlantedC4State::NotPlanted].
+    pub state: PlantedC4State,
+
+    /// Position of the planted bomb.
+    #[cfg_attr(feature = "serialize", serde(with = "crate::serialize::vector3"))]
+    pub position: Vector3<f32>,
+
+    /// Current bomb defuser
+    pub defuser: Option<BombDefuser>,
+
+    /// Server time of the detonation
+    pub time_blow: f32,
+
+    /// Server time of the plant
+    pub plant_time: f32,
+
+    /// The bomb has been planted before the round started (e.g. retake servers)
+    pub pre_planted: bool,
+
+    /// Wall-clock time of the detonation.
+    /// Only available while the bomb is active and the server clock has been synchronized.
+    pub detonation_deadline: Option<SystemTime>,
+
+    /// Raw values of the C4 entity as read this frame
+    pub raw_fields: PlantedC4RawFields,
+}
+
+/// Select the primary bomb: the active bomb which detonates first.
+/// If no bomb is active, the bomb of the most recent plant will be selected.
+fn select_primary_bomb(bombs: &[PlantedC4Entry]) -> Option<usize> {
+    let active = bombs
+        .iter()
+        .enumerate()
+        .filter_map(|(index, bomb)| match bomb.state {
+            PlantedC4State::Active { time_detonation } => Some((index, time_detonation)),
+            _ => None,
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index);
+    if active.is_some() {
+        return active;
+    }
+
+    bombs
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.time_blow.total_cmp(&b.time_blow))
+        .map(|(index, _)| index)
+}
+
+/// All activated planted C4 entities.
+/// Multiple bombs may be planted at once on community servers or when a game mode re-plants
+/// before the previous bomb entity has been removed.
+pub struct PlantedC4List {
+    pub bombs: Vec<PlantedC4Entry>,
+
+    /// Index of the primary bomb within `bombs` (see [PlantedC4List::primary])
+    primary: Option<usize>,
+
+    /// Amount of defuse attempts (including the current one) for the plant of the primary bomb
+    pub defuse_attempts_this_plant: u32,
+
+    /// The defuser of the primary bomb repeatedly started and stopped defusing (see [StateDefuseShadow])
+    pub likely_faking: bool,
+
+    /// Time remaining on the round clock when the primary bomb has been planted.
+    /// None if the bomb has been pre-planted or the game rules could not be read.
+    pub planted_at_round_remaining: Option<f32>,
+
+    /// Server time of the plant of the primary bomb
+    pub planted_at_server_time: Option<f32>,
+
+    /// The bomb states could not be read as the state registry is in degraded mode
+    pub unavailable: bool,
+}
+
+impl PlantedC4List {
+    /// The active bomb which detonates first.
+    /// If no bomb is active, the bomb of the most recent plant.
+    pub fn primary(&self) -> Option<&PlantedC4Entry> {
+        self.primary.and_then(|index| self.bombs.get(index))
+    }
+
+    fn read_entry(
+        states: &StateRegistry,
+        memory: &StateCS2Memory,
+        entities: &StateEntityList,
+        class_name_cache: &ClassNameCache,
+        globals: &StateGlobals,
+        entity_identity: &Copy<dyn CEntityIdentity>,
+    ) -> anyhow::Result<Option<PlantedC4Entry>> {
+        let (bomb, raw_fields) = read_planted_c4(memory, entity_identity)?;
+        if !raw_fields.activated {
+            /* This bomb hasn't been activated (yet) */
+            return Ok(None);
+        }
+
+        let entity_handle = entity_identity.handle::<()>()?;
+        let entity_index = EntityIndex::from_handle(&entity_handle);
+        let position = read_scene_origin(memory, entity_identity)
+            .ok_or_skip("planted C4 position")
+            .unwrap_or_default();
+
+        let PlantedC4Timers {
+            bomb_site,
+            time_blow,
+            timer_length,
+        } = {
+            let timers = PlantedC4Timers {
+                bomb_site: bomb.m_nBombSite()? as u8,
+                time_blow: raw_fields.time_blow,
+                timer_length: bomb.m_flTimerLength()?,
+            };
+            let timers = StatePlausibility::validate(
+                states,
+                timers,
+                check_planted_c4(
+                    timers.bomb_site,
+                    timers.timer_length,
+                    globals.time_remaining(timers.time_blow)?,
+                ),
+            );
+
+            let mut shadow = states.resolve_mut::<StatePlantedC4Shadow>(())?;
+            let frame = shadow.frame;
+            let (timers, _confidence) = shadow.timers.observe(entity_handle.value, frame, timers);
+            timers.context("implausible planted C4 timers")?
+        };
+        let pre_planted = (|| -> anyhow::Result<bool> {
+            let game_rules = states.resolve::<StateGameRules>(())?;
+            let Some(rules) = &game_rules.rules else {
+                return Ok(false);
+            };
+
+            Ok(is_bomb_pre_planted(
+                time_blow - timer_length,
+                rules.m_fRoundStartTime()?.m_Value()?,
+                rules.m_bFreezePeriod()?,
+            ))
+        })()
+        .unwrap_or(false);
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            bomb_site,
+            time_blow,
+            is_defusing = raw_fields.being_defused,
+            "planted c4 found"
+        );
+
+        let mut entry = PlantedC4Entry {
+            entity_index,
+            bomb_site,
+            state: PlantedC4State::Active {
+                time_detonation: globals.time_remaining(time_blow)?,
+            },
+            position,
+            defuser: None,
+            time_blow,
+            plant_time: time_blow - timer_length,
+            pre_planted,
+            detonation_deadline: None,
+            raw_fields,
+        };
+
+        if raw_fields.defused {
+            entry.state = PlantedC4State::Defused;
+            return Ok(Some(entry));
+        }
+
+        if globals.time_remaining(time_blow)? <= 0.0 {
+            entry.state = PlantedC4State::Detonated;
+            return Ok(Some(entry));
+        }
+
+        let handle_defuser = bomb.m_hBombDefuser()?;
+        let defuser_present = handle_defuser.is_valid()
+            && entities
+                .resolve_typed(class_name_cache, &handle_defuser)
+                .ok_or_skip("bomb defuser")
+                .flatten()
+                .is_some();
+
+        /* the defuser handle becomes stale if the defuser dies or disconnects mid-defuse */
+        if raw_fields.being_defused && defuser_present {
+            let pawn_entity_id = PawnIndex::from_handle(&handle_defuser);
+            let defuse_duration_total = bomb.m_flDefuseLength()?;
+            let (defuser_name, defuser_health, defuser_armor, has_kit, confidence) =
+                Self::defuser_details(states, &handle_defuser, defuse_duration_total);
+
+            let is_last_alive_ct = states
+                .resolve::<StateAlivePlayerCount>(())
+                .map(|count| count.counter_terrorists <= 1)
+                .unwrap_or(false);
+
+            let time_remaining = globals.time_remaining(raw_fields.defuse_countdown)?;
+            entry.defuser = Some(BombDefuser {
+                pawn_entity_id,
+                time_remaining,
+                defuse_duration_total,
+                has_kit,
+                can_defuse_in_time: can_defuse_in_time(
+                    time_remaining,
+                    globals.time_remaining(time_blow)?,
+                ),
+                player_name: defuser_name,
+
+                health: defuser_health,
+                armor: defuser_armor,
+                is_last_alive_ct,
+                confidence,
+            });
+        }
+
+        entry.detonation_deadline = states
+            .resolve::<StateServerClock>(())
+            .ok()
+            .and_then(|clock| clock.server_time_to_system_time(time_blow));
+
+        Ok(Some(entry))
+    }
+}
+
+impl State for PlantedC4List {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let globals = states.resolve::<StateGlobals>(())?;
+        let entities = states.resolve::<StateEntityList>(())?;
+        let class_name_cache = states.resolve::<ClassNameCache>(())?;
+        let class_index = states.resolve::<StateEntityClassIndex>(())?;
+
+        let planted_bombs = states
+            .resolve_mut::<CachedEntityLocator<PlantedC4Class>>(())?
+            .locate(&entities, &class_name_cache, &class_index)
+            .context("locate planted c4")?;
+
+        let mut bombs = Vec::with_capacity(planted_bombs.len());
+        for entity_identity in planted_bombs {
+            /* the bomb entity may be removed while being read */
+            let entry = Self::read_entry(
+                states,
+                &memory,
+                &entities,
+                &class_name_cache,
+                &globals,
+                entity_identity,
+            )
+            .ok_or_skip("planted C4");
+            if let Some(entry) = entry.flatten() {
+                bombs.push(entry);
             }
+        }
 
-            let time_blow = bomb.m_flC4Blow()?.m_Value()?;
+        if bombs
+            .iter()
+            .any(|bomb| !matches!(bomb.state, PlantedC4State::Active { .. }))
+        {
+            /* these bombs will not change any more, look for new plants instead */
+            states
+                .resolve_mut::<CachedEntityLocator<PlantedC4Class>>(())?
+                .invalidate();
+        }
 
-            if time_blow <= globals.time_2()? {
-                return Ok(Self {
-                    bomb_site,
-                    position: position.into(),
-                    defuser: None,
-                    state: PlantedC4State::Detonated,
-                });
+        let mut result = Self {
+            primary: select_primary_bomb(&bombs),
+            bombs,
+            defuse_attempts_this_plant: 0,
+            likely_faking: false,
+            planted_at_round_remaining: None,
+            planted_at_server_time: None,
+            unavailable: false,
+        };
+
+        let Some(primary) = result.primary() else {
+            /* the bomb is no longer planted (e.g. round restart) */
+            if let Ok(mut shadow) = states.resolve_mut::<StateDefuseShadow>(()) {
+                shadow.reset();
             }
+            if let Ok(mut shadow) = states.resolve_mut::<StatePlantTimingShadow>(()) {
+                shadow.reset();
+            }
+
+            return Ok(result);
+        };
 
-            let is_defusing = bomb.m_bBeingDefused()?;
-            let defusing = if is_defusing {
-                let time_defuse = bomb.m_flDefuseCountDown()?.m_Value()?;
-
-                let handle_defuser = bomb.m_hBombDefuser()?;
-                let defuser = entities
-                    .entity_from_handle(&handle_defuser)
-                    .context("missing bomb defuser pawn")?
-                    .value_reference(memory.view_arc())
-                    .context("defuser pawn nullptr")?;
-
-                let defuser_controller = defuser.m_hController()?;
-                let defuser_controller = entities
-                    .entity_from_handle(&defuser_controller)
-                    .with_context(|| obfstr!("missing bomb defuser controller").to_string())?
-                    .value_reference(memory.view_arc())
-                    .context("defuser constroller nullptr")?;
-
-                let defuser_name =
-                    CStr::from_bytes_until_nul(&defuser_controller.m_iszPlayerName()?)
-                        .ok()
-                        .map(CStr::to_string_lossy)
-                        .unwrap_or("Name Error".into())
-                        .to_string();
-
-                Some(BombDefuser {
-                    time_remaining: time_defuse - globals.time_2()?,
-                    player_name: defuser_name,
-                })
+        let time_blow = primary.time_blow;
+        let plant_timing = states
+            .resolve_mut::<StatePlantTimingShadow>(())?
+            .push_plant(time_blow, || {
+                Self::capture_plant_timing(states, primary.plant_time, primary.pre_planted)
+            });
+
+        let defuse_attempts = {
+            let mut shadow = states.resolve_mut::<StateDefuseShadow>(())?;
+            if matches!(primary.state, PlantedC4State::Active { .. }) {
+                shadow.push_sample(
+                    time_blow,
+                    primary.raw_fields.being_defused,
+                    globals.server_time()?,
+                )
             } else {
-                None
-            };
+                shadow.attempts()
+            }
+        };
+
+        result.defuse_attempts_this_plant = defuse_attempts.attempts;
+        result.likely_faking = defuse_attempts.likely_faking;
+        result.planted_at_round_remaining = plant_timing.round_remaining;
+        result.planted_at_server_time = plant_timing.server_time;
+        Ok(result)
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+
+    fn unavailable(_reason: &str) -> Option<Self> {
+        Some(Self {
+            bombs: Vec::new(),
+            primary: None,
+            defuse_attempts_this_plant: 0,
+            likely_faking: false,
+            planted_at_round_remaining: None,
+            planted_at_server_time: None,
+            unavailable: true,
+        })
+    }
+}
 
+impl State for PlantedC4 {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let bombs = states.resolve::<PlantedC4List>(())?;
+        let Some(primary) = bombs.primary() else {
             return Ok(Self {
-                bomb_site,
-                defuser: defusing,
-                position: position.into(),
-                state: PlantedC4State::Active {
-                    time_detonation: time_blow - globals.time_2()?,
-                },
+                bomb_site: 0,
+                defuser: None,
+                defuse_attempts_this_plant: 0,
+                likely_faking: false,
+                pre_planted: false,
+                planted_at_round_remaining: None,
+                planted_at_server_time: None,
+                detonation_deadline: None,
+                unavailable: bombs.unavailable,
+                raw_fields: None,
+                position: Default::default(),
+                state: PlantedC4State::NotPlanted,
             });
-        }
+        };
 
-        return Ok(Self {
-            bomb_site: 0,
-            defuser: None,
-            position: Default::default(),
-            state: PlantedC4State::NotPlanted,
-        });
+        Ok(Self {
+            bomb_site: primary.bomb_site,
+            defuser: primary.defuser.clone(),
+            defuse_attempts_this_plant: bombs.defuse_attempts_this_plant,
+            likely_faking: bombs.likely_faking,
+            pre_planted: primary.pre_planted,
+            planted_at_round_remaining: bombs.planted_at_round_remaining,
+            planted_at_server_time: bombs.planted_at_server_time,
+            detonation_deadline: primary.detonation_deadline,
+            unavailable: false,
+            raw_fields: Some(primary.raw_fields),
+            position: primary.position,
+            state: primary.state,
+        })
     }
 
     fn cache_type() -> StateCacheType {
         StateCacheType::Volatile
     }
+
+    fn unavailable(_reason: &str) -> Option<Self> {
+        Some(Self {
+            bomb_site: 0,
+            defuser: None,
+            defuse_attempts_this_plant: 0,
+            likely_faking: false,
+            pre_planted: false,
+            planted_at_round_remaining: None,
+            planted_at_server_time: None,
+            detonation_deadline: None,
+            unavailable: true,
+            raw_fields: None,
+            position: Default::default(),
+            state: PlantedC4State::NotPlanted,
+        })
+    }
 }
 
 impl State for BombCarrierInfo {
@@ -208,62 +883,30 @@ impl State for BombCarrierInfo {
     fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
         let memory = states.resolve::<StateCS2Memory>(())?;
         let entities = states.resolve::<StateEntityList>(())?;
-        let class_name_cache = states.resolve::<ClassNameCache>(())?;
+        let class_index = states.resolve::<StateEntityClassIndex>(())?;
 
         // Find the C4 entity and its owner
-        for entity_identity in entities.entities().iter() {
-            let class_info = entity_identity.entity_class_info()?;
-            let class_name = class_name_cache.lookup(&class_info)?;
-
-            if !class_name.map(|name| name == "C_C4").unwrap_or(false) {
+        for entity_identity in class_index.entities_of_class(&entities, "C_C4") {
+            let Some((_c4_entity, owner_handle)) =
+                read_c4_entity(&memory, entity_identity).ok_or_skip("C4 entity")
+            else {
                 continue;
-            }
-
-            let c4_entity = entity_identity
-                .entity_ptr::<dyn C_EconEntity>()?
-                .value_reference(memory.view_arc())
-                .context("C4 entity nullptr")?
-                .cast::<dyn C_C4>();
-
-            let owner_handle = c4_entity.m_hOwnerEntity()?;
+            };
             if !owner_handle.is_valid() {
                 continue;
             }
 
-            let owner_entity = entities.entity_from_handle(&owner_handle);
-            if let Some(owner_identity) = owner_entity {
-                let owner_pawn = owner_identity
-                    .value_reference(memory.view_arc())
-                    .context("owner pawn nullptr")?
-                    .cast::<dyn C_CSPlayerPawn>();
-
-                let controller_handle = owner_pawn.m_hController()?;
-                let team_id = owner_pawn.m_iTeamNum()?;
-
-                let carrier_name = if controller_handle.is_valid() {
-                    entities
-                        .entity_from_handle(&controller_handle)
-                        .and_then(|controller| controller.value_reference(memory.view_arc()))
-                        .and_then(|controller_ref| {
-                            controller_ref
-                                .m_iszPlayerName()
-                                .ok()
-                                .and_then(|name_bytes| {
-                                    CStr::from_bytes_until_nul(&name_bytes)
-                                        .ok()
-                                        .map(|name| name.to_string_lossy().to_string())
-                                })
-                        })
-                } else {
-                    None
-                };
-
-                return Ok(Self {
-                    carrier_entity_id: Some(owner_handle.get_entity_index()),
-                    carrier_name,
-                    carrier_team_id: Some(team_id),
-                });
-            }
+            let carrier_entity_id = PawnIndex::from_handle(&owner_handle);
+            let carrier = resolve_pawn_owner(states, &owner_handle).ok_or_skip("C4 carrier");
+            let Some((carrier_name, team_id)) = carrier.flatten() else {
+                continue;
+            };
+
+            return Ok(Self {
+                carrier_entity_id: Some(carrier_entity_id),
+                carrier_name,
+                carrier_team_id: Some(team_id),
+            });
         }
 
         // No bomb carrier found
@@ -278,3 +921,425 @@ impl State for BombCarrierInfo {
         StateCacheType::Volatile
     }
 }
+
+/// Location of the bomb within the current round
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(tag = "type"))]
+pub enum BombState {
+    /// A player is carrying the bomb
+    Carried {
+        carrier_entity_id: PawnIndex,
+        carrier_name: Option<String>,
+        carrier_team_id: u8,
+
+        /// Position of the carried C4 entity
+        #[cfg_attr(feature = "serialize", serde(with = "crate::serialize::vector3"))]
+        position: Vector3<f32>,
+    },
+
+    /// The bomb has been dropped (e.g. the carrier died)
+    Dropped {
+        #[cfg_attr(feature = "serialize", serde(with = "crate::serialize::vector3"))]
+        position: Vector3<f32>,
+
+        /// The bomb has been dropped near a buy zone
+        near_buy_zone: bool,
+    },
+
+    /// The bomb has been planted (see [PlantedC4List] for the details)
+    Planted {
+        bomb_site: u8,
+        #[cfg_attr(feature = "serialize", serde(with = "crate::serialize::vector3"))]
+        position: Vector3<f32>,
+    },
+
+    /// There is no bomb (e.g. warmup, after the round end or a game mode without a bomb)
+    NotInRound,
+}
+
+struct C4Class;
+
+impl EntityClassFilter for C4Class {
+    const CLASS_NAME: &'static str = "C_C4";
+}
+
+impl State for BombState {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        if let Some(primary) = states.resolve::<PlantedC4List>(())?.primary() {
+            return Ok(Self::Planted {
+                bomb_site: primary.bomb_site,
+                position: primary.position,
+            });
+        }
+
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let entities = states.resolve::<StateEntityList>(())?;
+        let class_name_cache = states.resolve::<ClassNameCache>(())?;
+        let class_index = states.resolve::<StateEntityClassIndex>(())?;
+
+        let bombs = states
+            .resolve_mut::<CachedEntityLocator<C4Class>>(())?
+            .locate(&entities, &class_name_cache, &class_index)
+            .context("locate c4")?;
+
+        for entity_identity in bombs {
+            let Some((c4_entity, owner_handle)) =
+                read_c4_entity(&memory, entity_identity).ok_or_skip("C4 entity")
+            else {
+                continue;
+            };
+
+            let position = read_scene_origin(&memory, entity_identity)
+                .ok_or_skip("C4 position")
+                .unwrap_or_default();
+            if !owner_handle.is_valid() {
+                return Ok(Self::Dropped {
+                    position,
+                    near_buy_zone: c4_entity.m_bDroppedNearBuyZone().unwrap_or(false),
+                });
+            }
+
+            let carrier_entity_id = PawnIndex::from_handle(&owner_handle);
+            let carrier = resolve_pawn_owner(states, &owner_handle).ok_or_skip("C4 carrier");
+            let Some((carrier_name, carrier_team_id)) = carrier.flatten() else {
+                /* the owner entity does not exist (yet) */
+                continue;
+            };
+
+            return Ok(Self::Carried {
+                carrier_entity_id,
+                carrier_name,
+                carrier_team_id,
+                position,
+            });
+        }
+
+        Ok(Self::NotInRound)
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use cs2_schema_cutl::EntityHandle;
+    use cs2_schema_generated::cs2::client::{
+        CEntityIdentity,
+        C_BaseEntity,
+        C_CSPlayerPawn,
+    };
+    use nalgebra::Vector3;
+    use raw_struct::{
+        Copy,
+        FromMemoryView,
+    };
+
+    use super::{
+        can_defuse_in_time,
+        read_c4_entity,
+        read_planted_c4,
+        read_scene_origin,
+        select_primary_bomb,
+        BombDefuser,
+        PlantTiming,
+        PlantedC4Entry,
+        PlantedC4List,
+        PlantedC4RawFields,
+        PlantedC4State,
+        ResultSkipExt,
+        StatePlantTimingShadow,
+    };
+    use crate::{
+        read_pawn_owner,
+        test_fixture::{
+            match_fixture,
+            match_pawn_handle,
+            setup_dump_schema,
+            EntityFixture,
+            MATCH_PLAYER_NAMES,
+        },
+        EntityClassMismatch,
+        EntityIndex,
+        FieldConfidence,
+        PawnIndex,
+        PlayerDetailBudget,
+        PlayerInterest,
+        StateCS2Memory,
+        StatePlayerList,
+    };
+
+    fn bomb(entity_index: u32, state: PlantedC4State, time_blow: f32) -> PlantedC4Entry {
+        PlantedC4Entry {
+            entity_index: EntityIndex(entity_index),
+            bomb_site: 0,
+            state,
+            position: Default::default(),
+            defuser: None,
+            time_blow,
+            plant_time: time_blow - 40.0,
+            pre_planted: false,
+            detonation_deadline: None,
+            raw_fields: PlantedC4RawFields {
+                activated: true,
+                time_blow,
+                being_defused: false,
+                defused: matches!(state, PlantedC4State::Defused),
+                defuse_countdown: 0.0,
+            },
+        }
+    }
+
+    fn primary(bombs: &[PlantedC4Entry]) -> Option<u32> {
+        select_primary_bomb(bombs).map(|index| bombs[index].entity_index.0)
+    }
+
+    #[test]
+    fn primary_bomb() {
+        assert_eq!(primary(&[]), None);
+
+        /* the detonated bomb of the previous plant has not been removed yet */
+        let bombs = [
+            bomb(100, PlantedC4State::Detonated, 180.0),
+            bomb(
+                120,
+                PlantedC4State::Active {
+                    time_detonation: 38.0,
+                },
+                258.0,
+            ),
+        ];
+        assert_eq!(primary(&bombs), Some(120));
+
+        let bombs = [
+            bomb(
+                100,
+                PlantedC4State::Active {
+                    time_detonation: 30.0,
+                },
+                250.0,
+            ),
+            bomb(
+                120,
+                PlantedC4State::Active {
+                    time_detonation: 12.0,
+                },
+                232.0,
+            ),
+            bomb(140, PlantedC4State::Defused, 300.0),
+        ];
+        assert_eq!(primary(&bombs), Some(120));
+
+        /* no active bomb, report the most recent plant */
+        let bombs = [
+            bomb(100, PlantedC4State::Defused, 320.0),
+            bomb(120, PlantedC4State::Detonated, 280.0),
+        ];
+        assert_eq!(primary(&bombs), Some(100));
+    }
+
+    #[test]
+    fn plant_timing() {
+        let mut shadow = StatePlantTimingShadow {
+            plant_time_blow: None,
+            timing: Default::default(),
+        };
+
+        let timing = |server_time: f32| PlantTiming {
+            round_remaining: Some(7.0),
+            server_time: Some(server_time),
+        };
+        assert_eq!(shadow.push_plant(240.0, || timing(200.0)), timing(200.0));
+
+        /* the round clock stopped, the timing of the first observation will be retained */
+        assert_eq!(shadow.push_plant(240.0, || unreachable!()), timing(200.0));
+
+        /* new plant (e.g. the previous round has been restarted) */
+        assert_eq!(shadow.push_plant(310.0, || timing(270.0)), timing(270.0));
+
+        shadow.reset();
+        assert_eq!(
+            shadow.push_plant(310.0, PlantTiming::default),
+            PlantTiming::default()
+        );
+    }
+
+    fn defuser(confidence: FieldConfidence) -> BombDefuser {
+        BombDefuser {
+            pawn_entity_id: PawnIndex(3),
+            time_remaining: 4.0,
+            defuse_duration_total: 5.0,
+            has_kit: true,
+            can_defuse_in_time: true,
+            player_name: "defuser".to_string(),
+            health: 100,
+            armor: 100,
+            is_last_alive_ct: false,
+            confidence,
+        }
+    }
+
+    #[test]
+    fn defuser_name() {
+        assert_eq!(
+            defuser(FieldConfidence::Fresh).known_player_name(),
+            Some("defuser")
+        );
+        assert_eq!(
+            defuser(FieldConfidence::Stale { frames: 2 }).known_player_name(),
+            Some("defuser")
+        );
+
+        /* placeholder name of unreadable details */
+        let unavailable = defuser(FieldConfidence::Unavailable {
+            reason: "defuser pawn nullptr".to_string(),
+        });
+        assert_eq!(unavailable.known_player_name(), None);
+    }
+
+    #[test]
+    fn stale_defuser_details() {
+        setup_dump_schema();
+
+        /* two players with a budget of a single detail read per frame */
+        let mut states = match_fixture(2).into_states();
+        states
+            .set(
+                PlayerDetailBudget {
+                    max_detail_reads: Some(1),
+                },
+                (),
+            )
+            .unwrap();
+        for _ in 0..2 {
+            states.invalidate_states();
+            states
+                .resolve::<StatePlayerList>(PlayerInterest::All)
+                .unwrap();
+        }
+
+        /* the first player has been read within the previous frame */
+        let (name, _health, _armor, has_kit, confidence) =
+            PlantedC4List::defuser_details(&states, &match_pawn_handle(0), 10.0);
+        assert_eq!(name, MATCH_PLAYER_NAMES[0]);
+        assert!(!has_kit);
+        assert_eq!(confidence, FieldConfidence::Stale { frames: 1 });
+
+        let (name, _health, _armor, has_kit, confidence) =
+            PlantedC4List::defuser_details(&states, &match_pawn_handle(1), 10.0);
+        assert_eq!(name, MATCH_PLAYER_NAMES[1]);
+        assert!(has_kit);
+        assert_eq!(confidence, FieldConfidence::Fresh);
+    }
+
+    #[test]
+    fn defuse_in_time() {
+        /* kit defuse with three seconds to spare */
+        assert!(can_defuse_in_time(5.0, 8.0));
+
+        /* no kit and the bomb detonates first */
+        assert!(!can_defuse_in_time(10.0, 8.0));
+
+        /* the bomb detonates in the tick the defuse finishes */
+        assert!(!can_defuse_in_time(8.0, 8.0));
+    }
+
+    const ENTITY_ADDRESS: u64 = 0x10_0000;
+
+    /// Handle of the entity within [entity_fixture]
+    const ENTITY_HANDLE: u32 = 0x8040;
+
+    /// Entity list containing a single entity of the given class and (if present) the zeroed entity itself
+    fn entity_fixture(class_name: &str, entity_present: bool) -> EntityFixture {
+        let mut fixture = EntityFixture::default();
+        fixture.push_entity(ENTITY_HANDLE, class_name, ENTITY_ADDRESS);
+        if entity_present {
+            fixture.record(ENTITY_ADDRESS, &vec![0u8; 0x2000]);
+        }
+        fixture
+    }
+
+    #[test]
+    fn entity_removed_during_read() {
+        setup_dump_schema();
+
+        let memory =
+            StateCS2Memory::from_view(Arc::new(entity_fixture("C_PlantedC4", true).memory));
+        let entity_identity = Copy::<dyn CEntityIdentity>::read_object(
+            memory.view(),
+            EntityFixture::identity_address(ENTITY_HANDLE),
+        )
+        .unwrap();
+
+        /* the entity exists but has no scene node */
+        let (_bomb, raw_fields) = read_planted_c4(&memory, &entity_identity).unwrap();
+        assert!(!raw_fields.activated);
+        assert!(read_c4_entity(&memory, &entity_identity).is_ok());
+        assert_eq!(
+            read_scene_origin(&memory, &entity_identity)
+                .ok_or_skip("position")
+                .unwrap_or_default(),
+            Vector3::zeros()
+        );
+
+        /* the entity has been torn down after the identity scan */
+        memory
+            .value()
+            .swap_backend(Arc::new(entity_fixture("C_PlantedC4", false).memory));
+        assert!(read_planted_c4(&memory, &entity_identity)
+            .ok_or_skip("planted C4")
+            .is_none());
+        assert!(read_c4_entity(&memory, &entity_identity)
+            .ok_or_skip("C4 entity")
+            .is_none());
+        assert!(read_scene_origin(&memory, &entity_identity).is_err());
+    }
+
+    #[test]
+    fn handle_class_mismatch() {
+        setup_dump_schema();
+
+        /* the defuser handle points to the C4 instead of a player pawn */
+        let states = entity_fixture("C_C4", true).into_states();
+        let expected_mismatch = EntityClassMismatch {
+            entity_index: EntityIndex(0x40),
+            expected: "C_CSPlayerPawn",
+            actual: Some("C_C4".to_string()),
+        };
+
+        let error = PlantedC4List::read_defuser_details(
+            &states,
+            &EntityHandle::<dyn C_CSPlayerPawn>::from_index(ENTITY_HANDLE),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<EntityClassMismatch>(),
+            Some(&expected_mismatch)
+        );
+
+        let error = read_pawn_owner(
+            &states,
+            &EntityHandle::<dyn C_BaseEntity>::from_index(ENTITY_HANDLE),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<EntityClassMismatch>(),
+            Some(&expected_mismatch)
+        );
+
+        /* the owner is a player pawn without a controller */
+        let states = entity_fixture("C_CSPlayerPawn", true).into_states();
+        let owner = read_pawn_owner(
+            &states,
+            &EntityHandle::<dyn C_BaseEntity>::from_index(ENTITY_HANDLE),
+        )
+        .unwrap();
+        assert_eq!(owner, Some((None, 0)));
+    }
+}
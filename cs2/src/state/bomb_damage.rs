@@ -0,0 +1,145 @@
+use anyhow::Context;
+use cs2_schema_generated::cs2::client::{
+    CCSPlayerController,
+    CGameSceneNode,
+    CMapInfo,
+    C_BaseEntity,
+    C_CSPlayerPawn,
+};
+use nalgebra::Vector3;
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use super::{
+    PlantedC4,
+    PlantedC4State,
+    PlayerPawnState,
+};
+use crate::{
+    damage::{
+        self,
+        DamageResult,
+    },
+    CEntityIdentityEx,
+    CachedEntityLocator,
+    ClassNameCache,
+    EntityClassFilter,
+    StateCS2Memory,
+    StateEntityClassIndex,
+    StateEntityList,
+    StateLocalPlayerController,
+};
+
+struct MapInfoClass;
+
+impl EntityClassFilter for MapInfoClass {
+    const CLASS_NAME: &'static str = "CMapInfo";
+}
+
+/// Damage of the C4 explosion configured by the map.
+/// Falls back to [damage::C4_DAMAGE] if the map does not have any map info.
+fn read_map_bomb_damage(states: &StateRegistry) -> anyhow::Result<f32> {
+    let memory = states.resolve::<StateCS2Memory>(())?;
+    let entities = states.resolve::<StateEntityList>(())?;
+    let class_name_cache = states.resolve::<ClassNameCache>(())?;
+    let class_index = states.resolve::<StateEntityClassIndex>(())?;
+
+    let map_infos = states
+        .resolve_mut::<CachedEntityLocator<MapInfoClass>>(())?
+        .locate(&entities, &class_name_cache, &class_index)
+        .context("locate map info")?;
+
+    let Some(entity_identity) = map_infos.first() else {
+        return Ok(damage::C4_DAMAGE);
+    };
+
+    let bomb_damage = entity_identity
+        .entity_ptr::<dyn CMapInfo>()?
+        .value_reference(memory.view_arc())
+        .context("map info nullptr")?
+        .m_flBombRadius()?;
+
+    Ok(match bomb_damage {
+        damage if damage > 0.0 => damage,
+        _ => damage::C4_DAMAGE,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BombDamage {
+    /// Distance (in units) between the bomb and the origin of the local player
+    pub distance: f32,
+
+    /// Estimated damage of the detonation
+    pub damage: DamageResult,
+
+    /// The local player will not survive the detonation
+    pub is_lethal: bool,
+}
+
+/// Estimated damage of the primary planted C4 (see [PlantedC4]) to the local player.
+/// Occlusion by the world geometry is not taken into account.
+pub struct StateBombDamage {
+    /// None if the bomb is not ticking or the local player is not alive (e.g. when spectating)
+    pub local_player: Option<BombDamage>,
+}
+
+impl State for StateBombDamage {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let bomb = states.resolve::<PlantedC4>(())?;
+        if !matches!(bomb.state, PlantedC4State::Active { .. }) {
+            return Ok(Self { local_player: None });
+        }
+
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let entities = states.resolve::<StateEntityList>(())?;
+        let class_name_cache = states.resolve::<ClassNameCache>(())?;
+        let local_controller = states.resolve::<StateLocalPlayerController>(())?;
+        let Some(local_controller) = local_controller.instance.value_reference(memory.view_arc())
+        else {
+            return Ok(Self { local_player: None });
+        };
+
+        let pawn_handle = local_controller.m_hPlayerPawn()?;
+        if *states.resolve::<PlayerPawnState>(pawn_handle)? != PlayerPawnState::Alive {
+            return Ok(Self { local_player: None });
+        }
+
+        let Some(local_pawn) = entities.resolve_typed(&class_name_cache, &pawn_handle)? else {
+            return Ok(Self { local_player: None });
+        };
+        let local_pawn = local_pawn
+            .value_reference(memory.view_arc())
+            .context("local pawn nullptr")?;
+
+        let position = Vector3::from_column_slice(
+            &local_pawn
+                .m_pGameSceneNode()?
+                .value_reference(memory.view_arc())
+                .context("m_pGameSceneNode nullptr")?
+                .m_vecAbsOrigin()?,
+        );
+        let health = local_pawn.m_iHealth()?;
+        let armor = local_pawn.m_ArmorValue()?;
+
+        let distance = (position - bomb.position).norm();
+        let damage = damage::c4_explosion_damage(read_map_bomb_damage(states)?, distance, armor);
+
+        Ok(Self {
+            local_player: Some(BombDamage {
+                distance,
+                damage,
+                is_lethal: damage.hp_damage >= health,
+            }),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use nalgebra::Vector3;
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    map_data::StateMapData,
+    units::WorldUnits,
+    PlantedC4,
+    PlantedC4State,
+    StateCurrentMap,
+};
+
+/// Approximated bomb site bounds per map and bomb site
+const BOMB_SITE_BOUNDS: &str = include_str!("../../resources/bomb_site_bounds.json");
+
+/// Axis aligned bounding box of a bomb site.
+/// The min z coordinate is the floor of the bomb site.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BombSiteBounds {
+    pub mins: Vector3<f32>,
+    pub maxs: Vector3<f32>,
+}
+
+impl BombSiteBounds {
+    /// Horizontal distance to the bounds (zero if the position is above or within the bounds)
+    pub fn horizontal_distance(&self, position: &Vector3<f32>) -> f32 {
+        let dx = (self.mins.x - position.x)
+            .max(position.x - self.maxs.x)
+            .max(0.0);
+        let dy = (self.mins.y - position.y)
+            .max(position.y - self.maxs.y)
+            .max(0.0);
+        (dx * dx + dy * dy).sqrt()
+    }
+}
+
+/// Bomb site geometry of all known maps
+pub struct StateBombSiteGeometry {
+    /// Bounds by map name and bomb site name
+    pub maps: BTreeMap<String, BTreeMap<String, BombSiteBounds>>,
+}
+
+/// Parse the bounds of a single bomb site (`{ "mins": [x, y, z], "maxs": [x, y, z] }`).
+/// The context (e.g. map and site name) will be included in errors.
+pub fn parse_bomb_site_bounds(
+    context: &str,
+    bounds: &serde_json::Value,
+) -> anyhow::Result<BombSiteBounds> {
+    let vector = |name: &str| -> anyhow::Result<Vector3<f32>> {
+        let values = bounds
+            .get(name)
+            .and_then(|value| value.as_array())
+            .filter(|values| values.len() == 3)
+            .with_context(|| format!("invalid {} for {}", name, context))?
+            .iter()
+            .map(|value| {
+                value
+                    .as_f64()
+                    .map(|value| value as f32)
+                    .with_context(|| format!("invalid {} for {}", name, context))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Vector3::from_column_slice(&values))
+    };
+
+    Ok(BombSiteBounds {
+        mins: vector("mins")?,
+        maxs: vector("maxs")?,
+    })
+}
+
+impl StateBombSiteGeometry {
+    pub fn parse(data: &str) -> anyhow::Result<Self> {
+        let value = serde_json::from_str::<serde_json::Value>(data)?;
+        let mut maps = BTreeMap::new();
+        for (map_name, sites) in value.as_object().context("expected an object")? {
+            let mut map_sites = BTreeMap::new();
+            for (site_name, bounds) in sites
+                .as_object()
+                .with_context(|| format!("expected an object for {}", map_name))?
+            {
+                map_sites.insert(
+                    site_name.clone(),
+                    parse_bomb_site_bounds(&format!("{} {}", map_name, site_name), bounds)?,
+                );
+            }
+
+            maps.insert(map_name.clone(), map_sites);
+        }
+
+        Ok(Self { maps })
+    }
+
+    pub fn bomb_sites(&self, map_name: &str) -> Option<&BTreeMap<String, BombSiteBounds>> {
+        self.maps.get(map_name)
+    }
+}
+
+impl State for StateBombSiteGeometry {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Self::parse(BOMB_SITE_BOUNDS).context("bomb site bounds")
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+}
+
+/// Thresholds for detecting unusual plants.
+/// Can be overridden using `StateRegistry::set`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnusualPlantThresholds {
+    /// Max height (in units) above the bomb site floor
+    pub max_height_above_floor: f32,
+
+    /// Max horizontal distance (in units) outside of the bomb site bounds
+    pub max_distance_outside: f32,
+}
+
+impl Default for UnusualPlantThresholds {
+    fn default() -> Self {
+        Self {
+            max_height_above_floor: 96.0,
+            max_distance_outside: 32.0,
+        }
+    }
+}
+
+impl State for UnusualPlantThresholds {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self::default())
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlantPlacement {
+    /// Name of the nearest bomb site
+    pub bomb_site: String,
+
+    /// Height of the bomb above the bomb sites floor
+    pub vertical_offset: WorldUnits,
+
+    /// Horizontal distance outside of the bomb site bounds
+    pub distance_outside: WorldUnits,
+
+    /// The bomb has been planted in an unusual spot (e.g. boost plant)
+    pub unusual_plant: bool,
+}
+
+/// Classify the bomb position relative to the nearest bomb site.
+/// Returns None if no bomb sites are given.
+pub fn classify_plant_placement(
+    position: &Vector3<f32>,
+    bomb_sites: &BTreeMap<String, BombSiteBounds>,
+    thresholds: &UnusualPlantThresholds,
+) -> Option<PlantPlacement> {
+    let (bomb_site, bounds) = bomb_sites.iter().min_by(|(_, a), (_, b)| {
+        a.horizontal_distance(position)
+            .total_cmp(&b.horizontal_distance(position))
+    })?;
+
+    let vertical_offset = position.z - bounds.mins.z;
+    let distance_outside = bounds.horizontal_distance(position);
+    Some(PlantPlacement {
+        bomb_site: bomb_site.clone(),
+        vertical_offset: WorldUnits(vertical_offset),
+        distance_outside: WorldUnits(distance_outside),
+        unusual_plant: vertical_offset > thresholds.max_height_above_floor
+            || distance_outside > thresholds.max_distance_outside,
+    })
+}
+
+/// Placement of the currently planted bomb.
+pub struct StatePlantPlacement {
+    /// None if the bomb is not planted or the bomb sites of the current map are unknown
+    pub placement: Option<PlantPlacement>,
+}
+
+impl State for StatePlantPlacement {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let planted_c4 = states.resolve::<PlantedC4>(())?;
+        if matches!(planted_c4.state, PlantedC4State::NotPlanted) {
+            return Ok(Self { placement: None });
+        }
+
+        let current_map = states.resolve::<StateCurrentMap>(())?;
+        let geometry = states.resolve::<StateBombSiteGeometry>(())?;
+        let map_data = states.resolve::<StateMapData>(())?;
+        let Some(bomb_sites) = map_data.data().bomb_sites.as_ref().or_else(|| {
+            current_map
+                .current_map
+                .as_ref()
+                .and_then(|map| geometry.bomb_sites(map))
+        }) else {
+            return Ok(Self { placement: None });
+        };
+
+        let thresholds = states.resolve::<UnusualPlantThresholds>(())?;
+        Ok(Self {
+            placement: classify_plant_placement(&planted_c4.position, bomb_sites, &thresholds),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use nalgebra::Vector3;
+
+    use super::{
+        classify_plant_placement,
+        BombSiteBounds,
+        UnusualPlantThresholds,
+    };
+
+    fn bomb_sites() -> BTreeMap<String, BombSiteBounds> {
+        let mut result = BTreeMap::new();
+        result.insert(
+            "A".to_string(),
+            BombSiteBounds {
+                mins: Vector3::new(0.0, 0.0, 100.0),
+                maxs: Vector3::new(500.0, 500.0, 250.0),
+            },
+        );
+        result.insert(
+            "B".to_string(),
+            BombSiteBounds {
+                mins: Vector3::new(2000.0, 0.0, -50.0),
+                maxs: Vector3::new(2400.0, 400.0, 100.0),
+            },
+        );
+        result
+    }
+
+    #[test]
+    fn placement() {
+        let thresholds = UnusualPlantThresholds::default();
+        let cases = [
+            /* (position, site, vertical offset, distance outside, unusual) */
+            (Vector3::new(250.0, 250.0, 100.0), "A", 0.0, 0.0, false),
+            (Vector3::new(0.0, 500.0, 140.0), "A", 40.0, 0.0, false),
+            (Vector3::new(250.0, 250.0, 260.0), "A", 160.0, 0.0, true),
+            (Vector3::new(520.0, 250.0, 100.0), "A", 0.0, 20.0, false),
+            (Vector3::new(600.0, 250.0, 100.0), "A", 0.0, 100.0, true),
+            (Vector3::new(2200.0, 200.0, -50.0), "B", 0.0, 0.0, false),
+            (Vector3::new(1800.0, 200.0, -50.0), "B", 0.0, 200.0, true),
+        ];
+
+        for (position, site, vertical_offset, distance_outside, unusual) in cases {
+            let placement = classify_plant_placement(&position, &bomb_sites(), &thresholds)
+                .expect("a bomb site");
+
+            assert_eq!(placement.bomb_site, site, "{:?}", position);
+            assert!(
+                (placement.vertical_offset.0 - vertical_offset).abs() < 0.01,
+                "{:?}",
+                position
+            );
+            assert!(
+                (placement.distance_outside.0 - distance_outside).abs() < 0.01,
+                "{:?}",
+                position
+            );
+            assert_eq!(placement.unusual_plant, unusual, "{:?}", position);
+        }
+    }
+
+    #[test]
+    fn no_sites() {
+        assert!(classify_plant_placement(
+            &Vector3::new(0.0, 0.0, 0.0),
+            &BTreeMap::new(),
+            &UnusualPlantThresholds::default()
+        )
+        .is_none());
+    }
+}
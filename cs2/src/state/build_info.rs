@@ -3,6 +3,7 @@ use cs2_schema_cutl::CStringUtil;
 use raw_struct::{
     Copy,
     FromMemoryView,
+    MemoryView,
 };
 use utils_state::{
     State,
@@ -11,6 +12,7 @@ use utils_state::{
 };
 
 use crate::{
+    diagnostics::StateSchemaDiff,
     schema::EngineBuildInfo,
     CS2Offset,
     StateCS2Memory,
@@ -57,3 +59,353 @@ impl State for StateBuildInfo {
         StateCacheType::Persistent
     }
 }
+
+/// Amount of broken fields named within the reason of a failed offset validation
+const OFFSET_VALIDATION_REPORTED_FIELDS: usize = 8;
+
+/// Offsets pointing to a pointer which will be dereferenced by the core states every frame
+const OFFSET_VALIDATION_PROBES: [CS2Offset; 3] = [
+    CS2Offset::Globals,
+    CS2Offset::GlobalEntityList,
+    CS2Offset::LocalController,
+];
+
+/// Validate that the offset points to a readable pointer and the pointer (if not null) to readable memory.
+/// Stale offsets (e.g. predefined offsets of an outdated revision) usually point to some other value.
+fn probe_offset(states: &StateRegistry, offset: CS2Offset) -> Result<(), String> {
+    let memory = states
+        .resolve::<StateCS2Memory>(())
+        .map_err(|err| format!("failed to access the process memory: {:#}", err))?;
+    let resolved = states
+        .resolve::<StateResolvedOffset>(offset)
+        .map_err(|err| format!("failed to resolve offset {:?}: {:#}", offset, err))?;
+
+    let mut pointer = [0u8; 8];
+    memory
+        .view()
+        .read_memory(resolved.address, &mut pointer)
+        .map_err(|err| {
+            format!(
+                "offset {:?} points to unreadable memory at 0x{:X}: {}",
+                offset, resolved.address, err
+            )
+        })?;
+
+    let pointer = u64::from_le_bytes(pointer);
+    if pointer == 0 {
+        /* not yet initialized (e.g. no local controller while not connected) */
+        return Ok(());
+    }
+
+    let mut target = [0u8; 8];
+    memory
+        .view()
+        .read_memory(pointer, &mut target)
+        .map_err(|err| {
+            format!(
+                "offset {:?} points to an invalid pointer (0x{:X}): {}",
+                offset, pointer, err
+            )
+        })
+}
+
+/// Validates, that the loaded offsets match the running game.
+/// The state registry will be put into degraded mode if the validation fails
+/// and resumes normal operation as soon the validation passes again.
+///
+/// The offsets of the core states will be validated against the process memory (see [OFFSET_VALIDATION_PROBES]).
+/// A revision mismatch only fails the validation if a used field moved since the schema has been generated
+/// or the runtime schema could not be compared (see [StateSchemaDiff]).
+/// The broken fields will be named within the reason.
+pub struct StateOffsetValidation {
+    /// CS2 revision the offsets have been created for.
+    /// If None, the revision will not be validated.
+    pub expected_revision: Option<String>,
+
+    /// The revision mismatch without any moved field has already been reported
+    mismatch_reported: bool,
+}
+
+impl StateOffsetValidation {
+    pub fn new(expected_revision: Option<String>) -> Self {
+        Self {
+            expected_revision,
+            mismatch_reported: false,
+        }
+    }
+
+    fn validate(&mut self, states: &StateRegistry) -> Result<(), String> {
+        let build_info = states
+            .resolve::<StateBuildInfo>(())
+            .map_err(|err| format!("failed to read build info: {:#}", err))?;
+
+        for offset in OFFSET_VALIDATION_PROBES {
+            probe_offset(states, offset)?;
+        }
+
+        if let Some(expected_revision) = &self.expected_revision {
+            if *expected_revision != build_info.revision {
+                let reason = format!(
+                    "offsets have been created for revision {} but the game revision is {}",
+                    expected_revision, build_info.revision
+                );
+
+                let schema_diff = states
+                    .resolve::<StateSchemaDiff>(())
+                    .map_err(|err| format!("{} ({:#})", reason, err))?;
+
+                match &schema_diff.report {
+                    Ok(diff) => match diff.summary(OFFSET_VALIDATION_REPORTED_FIELDS) {
+                        Some(summary) => {
+                            return Err(format!("{} (broken fields: {})", reason, summary))
+                        }
+                        None if !self.mismatch_reported => {
+                            log::warn!("{} but no used field has moved", reason);
+                            self.mismatch_reported = true;
+                        }
+                        None => {}
+                    },
+                    Err(error) => return Err(format!("{} ({})", reason, error)),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl State for StateOffsetValidation {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _params: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self::new(None))
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        match self.validate(states) {
+            Ok(_) => states.exit_degraded_mode(),
+            Err(reason) => states.enter_degraded_mode(reason),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use utils_state::{
+        State,
+        StateRegistry,
+    };
+
+    use super::StateOffsetValidation;
+    use crate::{
+        diagnostics::{
+            ClassDiff,
+            FieldChange,
+            FieldDiff,
+            MemoryFixture,
+            SchemaDiffReport,
+            StateSchemaDiff,
+        },
+        CS2Offset,
+        StateCS2Memory,
+        StateResolvedOffset,
+    };
+
+    const BUILD_INFO_ADDRESS: u64 = 0x1000;
+    const REVISION_ADDRESS: u64 = 0x2000;
+
+    /// Address of a value which is not a pointer (two floats)
+    const STALE_ADDRESS: u64 = 0x4000;
+
+    /// Address of the pointer referenced by the offset and the address of the pointee
+    fn probe_addresses(offset: CS2Offset) -> (u64, u64) {
+        match offset {
+            CS2Offset::Globals => (0x3000, 0x3_0000),
+            CS2Offset::GlobalEntityList => (0x3100, 0x3_1000),
+            _ => (0x3200, 0x3_2000),
+        }
+    }
+
+    /// States reading from a process with the given revision
+    fn process_states(revision: &str) -> StateRegistry {
+        let mut memory = MemoryFixture::default();
+
+        let mut build_info = vec![0u8; 0x28];
+        build_info[0x00..0x08].copy_from_slice(&REVISION_ADDRESS.to_le_bytes());
+        memory.record(BUILD_INFO_ADDRESS, &build_info);
+        let mut revision_buffer = vec![0u8; 0x40];
+        revision_buffer[..revision.len()].copy_from_slice(revision.as_bytes());
+        memory.record(REVISION_ADDRESS, &revision_buffer);
+        memory.record(STALE_ADDRESS, &0x4270_0000_4270_0000u64.to_le_bytes());
+
+        for offset in super::OFFSET_VALIDATION_PROBES {
+            let (address, pointee) = probe_addresses(offset);
+            memory.record(address, &pointee.to_le_bytes());
+            memory.record(pointee, &[0u8; 0x100]);
+        }
+
+        let mut states = StateRegistry::new(0x20);
+        states
+            .set(StateCS2Memory::from_view(Arc::new(memory)), ())
+            .unwrap();
+        for (offset, address) in [
+            (CS2Offset::BuildInfo, BUILD_INFO_ADDRESS),
+            (CS2Offset::Globals, probe_addresses(CS2Offset::Globals).0),
+            (
+                CS2Offset::GlobalEntityList,
+                probe_addresses(CS2Offset::GlobalEntityList).0,
+            ),
+            (
+                CS2Offset::LocalController,
+                probe_addresses(CS2Offset::LocalController).0,
+            ),
+        ] {
+            states
+                .set(StateResolvedOffset { offset: 0, address }, offset)
+                .unwrap();
+        }
+
+        states
+    }
+
+    fn health_diff(change: FieldChange) -> SchemaDiffReport {
+        SchemaDiffReport {
+            classes: vec![ClassDiff {
+                module: "client.dll".to_string(),
+                class_name: "C_BaseEntity".to_string(),
+                generated_size: Some(0x500),
+                runtime_size: Some(0x500),
+                fields: vec![FieldDiff {
+                    field_name: "m_iHealth".to_string(),
+                    change,
+                }],
+            }],
+        }
+    }
+
+    fn validate(states: &StateRegistry) {
+        StateOffsetValidation::new(Some("10000".to_string()))
+            .update(states)
+            .unwrap();
+    }
+
+    #[test]
+    fn offsets_valid() {
+        let states = process_states("10000");
+        validate(&states);
+        assert!(!states.is_degraded(), "{:?}", states.degraded_reason());
+    }
+
+    #[test]
+    fn offsets_stale() {
+        let mut states = process_states("10000");
+
+        /* the entity list offset points to a value which is not a pointer */
+        states
+            .set(
+                StateResolvedOffset {
+                    offset: 0,
+                    address: STALE_ADDRESS,
+                },
+                CS2Offset::GlobalEntityList,
+            )
+            .unwrap();
+        validate(&states);
+        assert!(states.degraded_reason().unwrap().starts_with(
+            "offset GlobalEntityList points to an invalid pointer (0x4270000042700000)"
+        ));
+
+        /* the offset points outside of the module */
+        states
+            .set(
+                StateResolvedOffset {
+                    offset: 0,
+                    address: 0xDEAD_0000,
+                },
+                CS2Offset::GlobalEntityList,
+            )
+            .unwrap();
+        validate(&states);
+        assert!(states
+            .degraded_reason()
+            .unwrap()
+            .starts_with("offset GlobalEntityList points to unreadable memory at 0xDEAD0000"));
+
+        /* recovers once the offsets are valid again */
+        states
+            .set(
+                StateResolvedOffset {
+                    offset: 0,
+                    address: probe_addresses(CS2Offset::GlobalEntityList).0,
+                },
+                CS2Offset::GlobalEntityList,
+            )
+            .unwrap();
+        validate(&states);
+        assert!(!states.is_degraded(), "{:?}", states.degraded_reason());
+    }
+
+    #[test]
+    fn revision_mismatch_unchanged_fields() {
+        let mut states = process_states("10001");
+        states
+            .set(
+                StateSchemaDiff {
+                    report: Ok(health_diff(FieldChange::Unchanged { offset: 0x34C })),
+                },
+                (),
+            )
+            .unwrap();
+
+        validate(&states);
+        assert!(!states.is_degraded(), "{:?}", states.degraded_reason());
+    }
+
+    #[test]
+    fn revision_mismatch_moved_field() {
+        let mut states = process_states("10001");
+        states
+            .set(
+                StateSchemaDiff {
+                    report: Ok(health_diff(FieldChange::Moved {
+                        old: 0x344,
+                        new: 0x34C,
+                    })),
+                },
+                (),
+            )
+            .unwrap();
+
+        validate(&states);
+        assert_eq!(
+            states.degraded_reason().as_deref(),
+            Some(
+                "offsets have been created for revision 10000 but the game revision is 10001 \
+                 (broken fields: C_BaseEntity.m_iHealth 0x344 -> 0x34C)"
+            )
+        );
+
+        /* the moved fields can not be determined */
+        states
+            .set(
+                StateSchemaDiff {
+                    report: Err("failed to dump the runtime schema".to_string()),
+                },
+                (),
+            )
+            .unwrap();
+        validate(&states);
+        assert!(states
+            .degraded_reason()
+            .unwrap()
+            .ends_with("(failed to dump the runtime schema)"));
+    }
+}
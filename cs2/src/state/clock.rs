@@ -0,0 +1,225 @@
+use std::{
+    collections::VecDeque,
+    time::{
+        Duration,
+        Instant,
+        SystemTime,
+    },
+};
+
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use super::StateGlobals;
+
+/// Amount of recent samples used to calculate the clock offset
+const CLOCK_SAMPLE_COUNT: usize = 32;
+
+/// If a sample deviates more then this from the current offset,
+/// we assume the server time jumped (e.g. map change) and reset the mapping.
+const CLOCK_JUMP_THRESHOLD: f64 = 1.0;
+
+/// Mapping between the server time (`globals.server_time()`) and the local monotonic clock.
+pub struct StateServerClock {
+    reference: Instant,
+    last_server_time: Option<f32>,
+
+    /// Recent offsets between the local clock (seconds since `reference`) and the server time
+    offsets: VecDeque<f64>,
+    offset: Option<f64>,
+}
+
+impl StateServerClock {
+    fn reset(&mut self) {
+        self.offsets.clear();
+        self.offset = None;
+        self.last_server_time = None;
+    }
+
+    /// Push a sample of the server time captured at `timestamp`
+    pub fn push_sample(&mut self, timestamp: Instant, server_time: f32) {
+        if self
+            .last_server_time
+            .map(|last| server_time < last)
+            .unwrap_or(false)
+        {
+            /* server time went backwards */
+            self.reset();
+        }
+
+        let sample = timestamp
+            .saturating_duration_since(self.reference)
+            .as_secs_f64()
+            - server_time as f64;
+        if self
+            .offset
+            .map(|offset| (offset - sample).abs() > CLOCK_JUMP_THRESHOLD)
+            .unwrap_or(false)
+        {
+            self.reset();
+        }
+
+        if self.offsets.len() >= CLOCK_SAMPLE_COUNT {
+            self.offsets.pop_front();
+        }
+        self.offsets.push_back(sample);
+        self.last_server_time = Some(server_time);
+
+        let mut sorted = self.offsets.iter().cloned().collect::<Vec<_>>();
+        sorted.sort_by(f64::total_cmp);
+        self.offset = sorted.get(sorted.len() / 2).cloned();
+    }
+
+    /// Convert a server time into the local monotonic clock.
+    /// Returns `None` if no mapping has been established yet.
+    pub fn server_time_to_instant(&self, server_time: f32) -> Option<Instant> {
+        let local_time = server_time as f64 + self.offset?;
+        if local_time >= 0.0 {
+            Some(self.reference + Duration::from_secs_f64(local_time))
+        } else {
+            self.reference
+                .checked_sub(Duration::from_secs_f64(-local_time))
+        }
+    }
+
+    /// Convert a server time into the wall-clock time.
+    /// Returns `None` if no mapping has been established yet.
+    pub fn server_time_to_system_time(&self, server_time: f32) -> Option<SystemTime> {
+        let target = self.server_time_to_instant(server_time)?;
+
+        let now = Instant::now();
+        let system_now = SystemTime::now();
+        if target >= now {
+            Some(system_now + target.duration_since(now))
+        } else {
+            system_now.checked_sub(now.duration_since(target))
+        }
+    }
+}
+
+impl State for StateServerClock {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            reference: Instant::now(),
+            last_server_time: None,
+
+            offsets: VecDeque::with_capacity(CLOCK_SAMPLE_COUNT),
+            offset: None,
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        let globals = states.resolve::<StateGlobals>(())?;
+        let timestamp = Instant::now();
+        self.push_sample(timestamp, globals.server_time()?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use utils_state::{
+        State,
+        StateRegistry,
+    };
+
+    use super::StateServerClock;
+
+    fn server_clock() -> StateServerClock {
+        StateServerClock::create(&StateRegistry::new(1), ()).unwrap()
+    }
+
+    /// Push a sample captured at the given local time (seconds since the clock reference)
+    fn push_sample(clock: &mut StateServerClock, local_time: f64, server_time: f32) {
+        let timestamp = clock.reference + Duration::from_secs_f64(local_time);
+        clock.push_sample(timestamp, server_time);
+    }
+
+    /// Local time (seconds since the clock reference) of the server time
+    fn local_time(clock: &StateServerClock, server_time: f32) -> f64 {
+        clock
+            .server_time_to_instant(server_time)
+            .unwrap()
+            .duration_since(clock.reference)
+            .as_secs_f64()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn offset() {
+        let mut clock = server_clock();
+        assert!(clock.server_time_to_instant(100.0).is_none());
+        assert!(clock.server_time_to_system_time(100.0).is_none());
+
+        /* the server started 90 seconds before the local clock */
+        for tick in 0..8 {
+            let server_time = 100.0 + tick as f32 * 0.5;
+            push_sample(&mut clock, server_time as f64 - 90.0, server_time);
+        }
+
+        assert_close(local_time(&clock, 100.0), 10.0);
+        assert_close(local_time(&clock, 120.0), 30.0);
+
+        /* server times before the clock reference */
+        assert!(clock.server_time_to_instant(80.0).unwrap() < clock.reference);
+    }
+
+    #[test]
+    fn drift() {
+        let mut clock = server_clock();
+
+        /* the local clock runs 1% faster then the server clock */
+        for tick in 0..64 {
+            let server_time = 100.0 + tick as f32 * 0.5;
+            push_sample(&mut clock, (server_time as f64 - 90.0) * 1.01, server_time);
+        }
+
+        /* the mapping follows the recent samples */
+        let server_time = 100.0 + 63.0 * 0.5;
+        let local = (server_time as f64 - 90.0) * 1.01;
+        assert!((local_time(&clock, server_time) - local).abs() < 0.2);
+
+        /* a single delayed sample does not shift the mapping */
+        let mapped = local_time(&clock, server_time);
+        push_sample(&mut clock, local + 0.7, server_time + 0.5);
+        assert!((local_time(&clock, server_time) - mapped).abs() < 0.01);
+    }
+
+    #[test]
+    fn server_time_reset() {
+        let mut clock = server_clock();
+        for tick in 0..8 {
+            let server_time = 500.0 + tick as f32;
+            push_sample(&mut clock, server_time as f64 - 490.0, server_time);
+        }
+        assert_close(local_time(&clock, 500.0), 10.0);
+
+        /* map change: the server time restarts */
+        push_sample(&mut clock, 20.0, 1.0);
+        assert_close(local_time(&clock, 1.0), 20.0);
+        assert_close(local_time(&clock, 2.0), 21.0);
+
+        /* the server time jumps forward (e.g. reconnecting to a running server) */
+        push_sample(&mut clock, 21.0, 10.0);
+        assert_close(local_time(&clock, 10.0), 21.0);
+    }
+}
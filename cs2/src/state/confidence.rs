@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+/// Values which are older then this amount of frames will not be substituted
+pub const FIELD_MAX_STALE_FRAMES: u64 = 32;
+
+/// Describes how trustworthy the values of a field group are
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(tag = "type"))]
+pub enum FieldConfidence {
+    /// Values have been read this frame
+    Fresh,
+
+    /// Values could not be read this frame.
+    /// The last known values from `frames` frames ago have been substituted.
+    Stale { frames: u64 },
+
+    /// Values could not be read and no previous values are available.
+    /// The values of the group contain their defaults.
+    Unavailable { reason: String },
+}
+
+impl FieldConfidence {
+    pub fn is_fresh(&self) -> bool {
+        matches!(self, Self::Fresh)
+    }
+
+    /// The values are either fresh or have been substituted by a previous value
+    pub fn is_available(&self) -> bool {
+        !matches!(self, Self::Unavailable { .. })
+    }
+}
+
+/// Last successfully read values of a field group, keyed by the full entity handle.
+///
+/// The key must include the serial number of the handle (see `EntityHandle::value`),
+/// otherwise an entity reusing the slot of a removed entity would inherit its values.
+pub struct FieldShadow<T> {
+    /// (value, frame of the read)
+    entries: HashMap<u32, (T, u64)>,
+}
+
+impl<T> Default for FieldShadow<T> {
+    fn default() -> Self {
+        Self {
+            entries: Default::default(),
+        }
+    }
+}
+
+impl<T: Clone> FieldShadow<T> {
+    /// Record the result of reading the field group in `frame`.
+    /// If the read failed the last known value will be substituted if it is not older then [FIELD_MAX_STALE_FRAMES].
+    pub fn observe(
+        &mut self,
+        key: u32,
+        frame: u64,
+        result: anyhow::Result<T>,
+    ) -> (Option<T>, FieldConfidence) {
+        let error = match result {
+            Ok(value) => {
+                self.entries.insert(key, (value.clone(), frame));
+                return (Some(value), FieldConfidence::Fresh);
+            }
+            Err(error) => error,
+        };
+
+        match self.entries.get(&key) {
+            Some((value, value_frame))
+                if frame.saturating_sub(*value_frame) <= FIELD_MAX_STALE_FRAMES =>
+            {
+                (
+                    Some(value.clone()),
+                    FieldConfidence::Stale {
+                        frames: frame.saturating_sub(*value_frame),
+                    },
+                )
+            }
+            _ => (
+                None,
+                FieldConfidence::Unavailable {
+                    reason: format!("{:#}", error),
+                },
+            ),
+        }
+    }
+
+    /// Remove all values which can no longer be substituted
+    pub fn prune(&mut self, frame: u64) {
+        self.entries.retain(|_, (_, value_frame)| {
+            frame.saturating_sub(*value_frame) <= FIELD_MAX_STALE_FRAMES
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::anyhow;
+    use cs2_schema_cutl::EntityHandle;
+
+    use super::{
+        FieldConfidence,
+        FieldShadow,
+        FIELD_MAX_STALE_FRAMES,
+    };
+
+    #[test]
+    fn substitution() {
+        let mut shadow = FieldShadow::<u32>::default();
+        assert_eq!(
+            shadow.observe(1, 0, Err(anyhow!("read failed"))),
+            (
+                None,
+                FieldConfidence::Unavailable {
+                    reason: "read failed".to_string()
+                }
+            )
+        );
+
+        assert_eq!(
+            shadow.observe(1, 1, Ok(42)),
+            (Some(42), FieldConfidence::Fresh)
+        );
+        assert_eq!(
+            shadow.observe(1, 4, Err(anyhow!("read failed"))),
+            (Some(42), FieldConfidence::Stale { frames: 3 })
+        );
+
+        /* other keys are not affected */
+        assert!(!shadow
+            .observe(2, 4, Err(anyhow!("read failed")))
+            .1
+            .is_available());
+
+        let frame = 1 + FIELD_MAX_STALE_FRAMES + 1;
+        assert!(!shadow
+            .observe(1, frame, Err(anyhow!("read failed")))
+            .1
+            .is_available());
+    }
+
+    #[test]
+    fn reused_entity_slot() {
+        let removed = EntityHandle::<()>::from_index((1 << 15) | 5);
+        let reused = EntityHandle::<()>::from_index((2 << 15) | 5);
+        assert_eq!(removed.get_entity_index(), reused.get_entity_index());
+
+        let mut shadow = FieldShadow::<u32>::default();
+        shadow.observe(removed.value, 0, Ok(42));
+
+        /* the new entity must not inherit the values of the removed entity */
+        assert_eq!(
+            shadow
+                .observe(reused.value, 1, Err(anyhow!("read failed")))
+                .0,
+            None
+        );
+        assert_eq!(
+            shadow
+                .observe(removed.value, 1, Err(anyhow!("read failed")))
+                .0,
+            Some(42)
+        );
+    }
+
+    #[test]
+    fn prune() {
+        let mut shadow = FieldShadow::<u32>::default();
+        shadow.observe(1, 0, Ok(1));
+        shadow.observe(2, 10, Ok(2));
+
+        shadow.prune(FIELD_MAX_STALE_FRAMES + 5);
+        assert_eq!(
+            shadow
+                .observe(1, FIELD_MAX_STALE_FRAMES + 5, Err(anyhow!("read failed")))
+                .0,
+            None
+        );
+        assert_eq!(
+            shadow
+                .observe(2, FIELD_MAX_STALE_FRAMES + 5, Err(anyhow!("read failed")))
+                .0,
+            Some(2)
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use std::{
+    collections::VecDeque,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use cs2_schema_generated::cs2::client::C_GameRules;
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use super::{
+    StateCurrentMap,
+    StateGameRules,
+    StateGlobals,
+};
+use crate::{
+    ConVars,
+    StateLocalPlayerController,
+};
+
+/// Amount of lag samples used for the median filter
+const BROADCAST_DELAY_SAMPLE_COUNT: usize = 64;
+
+/// Server time jumps larger then this (in seconds) reset the estimator (e.g. map change or reconnect)
+const BROADCAST_TIME_JUMP_THRESHOLD: f64 = 5.0;
+
+/// Interval in which the `tv_delay` is read again
+const BROADCAST_CONVAR_UPDATE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Estimates the delay of a broadcast (GOTV) by comparing the progression of the server time against the local clock.
+///
+/// The initial delay is given by the configured broadcast delay (`tv_delay`).
+/// Every time the playback stalls (e.g. buffering) the stream falls further behind.
+/// This additional lag is measured relative to the point where the playback was the most ahead
+/// and refined using a median filter.
+/// While the game is paused the server time does not advance and the samples will be ignored.
+#[derive(Debug, Clone)]
+pub struct BroadcastDelayEstimator {
+    /// (local time, server time) of the first sample
+    reference: Option<(Instant, f32)>,
+
+    /// (local time, server time) of the last sample
+    last_sample: Option<(Instant, f32)>,
+
+    /// Recent differences between the local clock and the server time progression (in seconds)
+    lags: VecDeque<f64>,
+
+    /// Smallest lag observed since the last reset
+    min_lag: f64,
+}
+
+impl Default for BroadcastDelayEstimator {
+    fn default() -> Self {
+        Self {
+            reference: None,
+            last_sample: None,
+            lags: VecDeque::with_capacity(BROADCAST_DELAY_SAMPLE_COUNT),
+            min_lag: 0.0,
+        }
+    }
+}
+
+impl BroadcastDelayEstimator {
+    pub fn reset(&mut self) {
+        *self = Default::default();
+    }
+
+    /// Push the server time observed at `timestamp`
+    pub fn push_sample(&mut self, timestamp: Instant, server_time: f32, paused: bool) {
+        let Some((reference_time, reference_server_time)) = self.reference else {
+            self.reference = Some((timestamp, server_time));
+            self.last_sample = Some((timestamp, server_time));
+            return;
+        };
+
+        let (last_time, last_server_time) = self.last_sample.unwrap_or((timestamp, server_time));
+        let local_progress = timestamp.saturating_duration_since(last_time).as_secs_f64();
+        let server_progress = server_time as f64 - last_server_time as f64;
+        self.last_sample = Some((timestamp, server_time));
+
+        if paused {
+            /* the server time does not advance, but the delay stays the same */
+            self.reference = Some((
+                reference_time + Duration::from_secs_f64(local_progress),
+                reference_server_time + server_progress as f32,
+            ));
+            return;
+        }
+
+        if server_progress < 0.0 || server_progress - local_progress > BROADCAST_TIME_JUMP_THRESHOLD
+        {
+            /* server time jumped (e.g. map change) */
+            self.reset();
+            self.reference = Some((timestamp, server_time));
+            self.last_sample = Some((timestamp, server_time));
+            return;
+        }
+
+        let lag = timestamp
+            .saturating_duration_since(reference_time)
+            .as_secs_f64()
+            - (server_time as f64 - reference_server_time as f64);
+
+        if self.lags.len() >= BROADCAST_DELAY_SAMPLE_COUNT {
+            self.lags.pop_front();
+        }
+        self.lags.push_back(lag);
+        self.min_lag = self.min_lag.min(lag);
+    }
+
+    /// Additional delay caused by playback stalls since the first sample.
+    /// Returns None if not enough samples are available.
+    pub fn additional_delay(&self) -> Option<Duration> {
+        if self.lags.is_empty() {
+            return None;
+        }
+
+        let mut lags = self.lags.iter().copied().collect::<Vec<_>>();
+        lags.sort_by(f64::total_cmp);
+        let median = lags[lags.len() / 2];
+
+        Some(Duration::from_secs_f64((median - self.min_lag).max(0.0)))
+    }
+
+    /// Estimated broadcast delay given the configured broadcast delay
+    pub fn estimate(&self, configured_delay: Duration) -> Option<Duration> {
+        Some(configured_delay + self.additional_delay()?)
+    }
+}
+
+/// Status of the connection to the game server
+pub struct StateConnectionStatus {
+    /// Connected to a server and a map is loaded
+    pub connected: bool,
+
+    /// Connected to a broadcast (GOTV).
+    /// Detected by a loaded map without a local player controller.
+    pub broadcast: bool,
+
+    /// Estimated delay of the broadcast relative to the live game.
+    /// Only available when connected to a broadcast.
+    pub estimated_broadcast_delay: Option<Duration>,
+
+    estimator: BroadcastDelayEstimator,
+
+    /// (last update, configured `tv_delay`)
+    configured_delay: Option<(Instant, Duration)>,
+}
+
+impl StateConnectionStatus {
+    fn read_configured_delay(states: &StateRegistry) -> anyhow::Result<Duration> {
+        let cvars = ConVars::new(states)?;
+        let delay = match cvars.find_cvar("tv_delay")? {
+            Some(cvar) => cvar.fl_value()?,
+            None => 0.0,
+        };
+
+        Ok(Duration::from_secs_f32(delay.max(0.0)))
+    }
+
+    pub(crate) fn read_paused(states: &StateRegistry) -> anyhow::Result<bool> {
+        let game_rules = states.resolve::<StateGameRules>(())?;
+        Ok(match &game_rules.rules {
+            Some(rules) => rules.m_bGamePaused()?,
+            None => false,
+        })
+    }
+}
+
+impl State for StateConnectionStatus {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            connected: false,
+            broadcast: false,
+            estimated_broadcast_delay: None,
+
+            estimator: Default::default(),
+            configured_delay: None,
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        let current_map = states.resolve::<StateCurrentMap>(())?;
+        self.connected = current_map.current_map.is_some();
+        self.broadcast = self.connected
+            && states
+                .resolve::<StateLocalPlayerController>(())?
+                .instance
+                .is_null();
+
+        if !self.broadcast {
+            self.estimator.reset();
+            self.configured_delay = None;
+            self.estimated_broadcast_delay = None;
+            return Ok(());
+        }
+
+        let configured_delay = match self.configured_delay {
+            Some((timestamp, delay)) if timestamp.elapsed() < BROADCAST_CONVAR_UPDATE_INTERVAL => {
+                delay
+            }
+            _ => {
+                let delay = Self::read_configured_delay(states)?;
+                self.configured_delay = Some((Instant::now(), delay));
+                delay
+            }
+        };
+
+        let server_time = states.resolve::<StateGlobals>(())?.server_time()?;
+        self.estimator
+            .push_sample(Instant::now(), server_time, Self::read_paused(states)?);
+        self.estimated_broadcast_delay = self.estimator.estimate(configured_delay);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{
+        Duration,
+        Instant,
+    };
+
+    use super::BroadcastDelayEstimator;
+
+    const FRAME: f32 = 1.0 / 64.0;
+
+    fn assert_delay(estimator: &BroadcastDelayEstimator, expected: f32) {
+        let delay = estimator
+            .estimate(Duration::from_secs(10))
+            .expect("an estimate")
+            .as_secs_f32();
+
+        assert!(
+            (delay - expected).abs() < 0.05,
+            "delay {} (expected {})",
+            delay,
+            expected
+        );
+    }
+
+    #[test]
+    fn convergence() {
+        let start = Instant::now();
+        let mut estimator = BroadcastDelayEstimator::default();
+        assert_eq!(estimator.estimate(Duration::from_secs(10)), None);
+
+        let mut local_time = 0.0;
+        let mut server_time = 100.0;
+        for _ in 0..32 {
+            estimator.push_sample(
+                start + Duration::from_secs_f32(local_time),
+                server_time,
+                false,
+            );
+            local_time += FRAME;
+            server_time += FRAME;
+        }
+        assert_delay(&estimator, 10.0);
+
+        /* playback stalls for two seconds */
+        local_time += 2.0;
+
+        /* the median needs some samples to follow */
+        for index in 0..64 {
+            estimator.push_sample(
+                start + Duration::from_secs_f32(local_time),
+                server_time,
+                false,
+            );
+            local_time += FRAME;
+            server_time += FRAME;
+
+            if index == 0 {
+                assert_delay(&estimator, 10.0);
+            }
+        }
+        assert_delay(&estimator, 12.0);
+
+        /* a single outlier (e.g. frame hitch) does not affect the estimate */
+        local_time += 0.5;
+        estimator.push_sample(
+            start + Duration::from_secs_f32(local_time),
+            server_time,
+            false,
+        );
+        assert_delay(&estimator, 12.0);
+    }
+
+    #[test]
+    fn pauses() {
+        let start = Instant::now();
+        let mut estimator = BroadcastDelayEstimator::default();
+
+        let mut local_time = 0.0;
+        let mut server_time = 100.0;
+        for frame in 0..256 {
+            /* the game is paused for 30 seconds and the server time stands still */
+            let paused = (64..128).contains(&frame);
+            if paused {
+                local_time += 30.0 / 64.0;
+            } else {
+                local_time += FRAME;
+                server_time += FRAME;
+            }
+
+            estimator.push_sample(
+                start + Duration::from_secs_f32(local_time),
+                server_time,
+                paused,
+            );
+        }
+        assert_delay(&estimator, 10.0);
+
+        /* map change */
+        local_time += 5.0;
+        estimator.push_sample(start + Duration::from_secs_f32(local_time), 1.0, false);
+        estimator.push_sample(
+            start + Duration::from_secs_f32(local_time + FRAME),
+            1.0 + FRAME,
+            false,
+        );
+        assert_delay(&estimator, 10.0);
+    }
+}
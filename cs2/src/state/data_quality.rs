@@ -0,0 +1,429 @@
+use std::time::{
+    Duration,
+    Instant,
+};
+
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use super::{
+    is_server_time_precise,
+    RoundPhase,
+    StateConnectionStatus,
+    StateGlobals,
+    StateRound,
+};
+
+/// Expected interval between two server ticks (in seconds)
+pub const WORLD_TICK_INTERVAL: f32 = 1.0 / 64.0;
+
+/// Amount of expected ticks without the server time advancing until the world is considered frozen
+pub const WORLD_FREEZE_TICKS: u32 = 8;
+
+/// Duration after a freeze or a server time jump in which positions may still snap
+pub const WORLD_RECOVERY_PERIOD: Duration = Duration::from_secs(1);
+
+/// Server time progressing faster then the local clock by this amount (in seconds) indicates
+/// that the client is catching up on missed ticks
+pub const WORLD_TIME_JUMP_THRESHOLD: f32 = 0.25;
+
+/// Accuracy of the entity positions within memory.
+///
+/// Note: The packet loss and choke of the net channel are not taken into account.
+/// The net channel is not part of the schema system and none of the known signatures locate it,
+/// therefore the quality is derived from the progression of the server time only.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(tag = "type"))]
+pub enum DataQuality {
+    Good,
+
+    /// Positions are updated, but may snap (e.g. after recovering from a lag spike)
+    /// or timers are less precise (see [crate::SERVER_TIME_MAX])
+    Degraded {
+        reason: String,
+    },
+
+    /// The server time has not advanced since `since` while being connected.
+    /// The client is most likely not receiving any updates.
+    Frozen {
+        /// Serialized as the amount of seconds the world has been frozen
+        #[cfg_attr(
+            feature = "serialize",
+            serde(rename = "frozen_for", with = "crate::serialize::instant_age")
+        )]
+        since: Instant,
+    },
+}
+
+impl DataQuality {
+    pub fn is_good(&self) -> bool {
+        matches!(self, Self::Good)
+    }
+}
+
+/// Detects the world freezing by tracking the server time across frames.
+///
+/// The world is considered frozen if the server time does not advance for [WORLD_FREEZE_TICKS]
+/// while the local clock does. Entity positions are not taken into account as a world where
+/// every entity stands still can not be distinguished from a lag freeze.
+///
+/// Frames while being disconnected (e.g. idling in the main menu) or while the world is
+/// suspended (paused game, freeze time or warmup) are never considered frozen.
+#[derive(Debug, Clone)]
+pub struct DataQualityTracker {
+    /// Timestamp of the last frame the server time advanced
+    last_advance: Option<Instant>,
+
+    /// (local time, server time) of the last frame
+    last_sample: Option<(Instant, f32)>,
+
+    /// (end of the degradation, reason)
+    degraded: Option<(Instant, String)>,
+
+    quality: DataQuality,
+}
+
+impl Default for DataQualityTracker {
+    fn default() -> Self {
+        Self {
+            last_advance: None,
+            last_sample: None,
+            degraded: None,
+            quality: DataQuality::Good,
+        }
+    }
+}
+
+impl DataQualityTracker {
+    pub fn reset(&mut self) {
+        *self = Default::default();
+    }
+
+    /// Quality of the last frame
+    pub fn quality(&self) -> &DataQuality {
+        &self.quality
+    }
+
+    fn freeze_threshold() -> Duration {
+        Duration::from_secs_f32(WORLD_TICK_INTERVAL * WORLD_FREEZE_TICKS as f32)
+    }
+
+    /// Observe the server time of a frame captured at `timestamp`
+    pub fn push(
+        &mut self,
+        timestamp: Instant,
+        connected: bool,
+        suspended: bool,
+        server_time: Option<f32>,
+    ) -> &DataQuality {
+        if !connected {
+            /* nothing to be updated */
+            self.reset();
+            return &self.quality;
+        }
+
+        if suspended {
+            /* the server time may stand still */
+            self.last_advance = Some(timestamp);
+            self.last_sample = None;
+            self.degraded = None;
+            self.quality = DataQuality::Good;
+            return &self.quality;
+        }
+
+        let Some(server_time) = server_time else {
+            self.last_sample = None;
+            self.quality = DataQuality::Degraded {
+                reason: "server time unavailable".to_string(),
+            };
+            return &self.quality;
+        };
+
+        match self.last_sample {
+            Some((last_timestamp, last_server_time)) if server_time > last_server_time => {
+                if matches!(self.quality, DataQuality::Frozen { .. }) {
+                    self.degraded = Some((
+                        timestamp + WORLD_RECOVERY_PERIOD,
+                        "recovering from a freeze".to_string(),
+                    ));
+                }
+
+                let local_progress = timestamp
+                    .saturating_duration_since(last_timestamp)
+                    .as_secs_f32();
+                let server_progress = server_time - last_server_time;
+                if server_progress - local_progress > WORLD_TIME_JUMP_THRESHOLD {
+                    self.degraded = Some((
+                        timestamp + WORLD_RECOVERY_PERIOD,
+                        format!("server time jumped by {:.2}s", server_progress),
+                    ));
+                }
+
+                self.last_advance = Some(timestamp);
+            }
+            Some((_, last_server_time)) if server_time == last_server_time => {
+                /* no tick has been received since the last frame */
+            }
+            _ => {
+                /* first sample or the server time has been reset (e.g. map change) */
+                self.last_advance = Some(timestamp);
+            }
+        }
+        self.last_sample = Some((timestamp, server_time));
+
+        let last_advance = *self.last_advance.get_or_insert(timestamp);
+        self.quality =
+            if timestamp.saturating_duration_since(last_advance) > Self::freeze_threshold() {
+                DataQuality::Frozen {
+                    since: last_advance,
+                }
+            } else {
+                match &self.degraded {
+                    Some((until, reason)) if timestamp < *until => DataQuality::Degraded {
+                        reason: reason.clone(),
+                    },
+                    _ if !is_server_time_precise(server_time) => DataQuality::Degraded {
+                        reason: "server time precision reduced by a long server uptime".to_string(),
+                    },
+                    _ => DataQuality::Good,
+                }
+            };
+
+        &self.quality
+    }
+}
+
+/// Accuracy of the world data (see [DataQuality]).
+/// Freezes will only be detected while this state is being resolved every frame.
+pub struct StateWorldDataQuality {
+    pub quality: DataQuality,
+    tracker: DataQualityTracker,
+}
+
+impl State for StateWorldDataQuality {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            quality: DataQuality::Good,
+            tracker: Default::default(),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        let connection = states.resolve::<StateConnectionStatus>(())?;
+        if !connection.connected {
+            self.tracker.reset();
+            self.quality = DataQuality::Good;
+            return Ok(());
+        }
+
+        let suspended = StateConnectionStatus::read_paused(states)?
+            || matches!(
+                states.resolve::<StateRound>(())?.phase,
+                RoundPhase::FreezeTime | RoundPhase::Warmup
+            );
+        let server_time = states
+            .resolve::<StateGlobals>(())
+            .ok()
+            .and_then(|globals| globals.server_time().ok());
+
+        self.quality = self
+            .tracker
+            .push(Instant::now(), true, suspended, server_time)
+            .clone();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{
+        Duration,
+        Instant,
+    };
+
+    use super::{
+        DataQuality,
+        DataQualityTracker,
+        WORLD_FREEZE_TICKS,
+        WORLD_TICK_INTERVAL,
+    };
+    use crate::SERVER_TIME_MAX;
+
+    /// Simulated game feed with two overlay frames per tick
+    struct Feed {
+        start: Instant,
+        frame: u32,
+        server_time: f32,
+        tracker: DataQualityTracker,
+    }
+
+    impl Feed {
+        fn new() -> Self {
+            Self {
+                start: Instant::now(),
+                frame: 0,
+                server_time: 100.0,
+                tracker: Default::default(),
+            }
+        }
+
+        fn timestamp(&self) -> Instant {
+            self.start + Duration::from_secs_f32(self.frame as f32 * WORLD_TICK_INTERVAL / 2.0)
+        }
+
+        /// Push a frame, the server time advances by a tick every second frame if `ticking`
+        fn push(&mut self, connected: bool, suspended: bool, ticking: bool) -> DataQuality {
+            self.frame += 1;
+            if ticking && self.frame % 2 == 0 {
+                self.server_time += WORLD_TICK_INTERVAL;
+            }
+
+            self.tracker
+                .push(
+                    self.timestamp(),
+                    connected,
+                    suspended,
+                    connected.then_some(self.server_time),
+                )
+                .clone()
+        }
+    }
+
+    #[test]
+    fn lag_freeze() {
+        let mut feed = Feed::new();
+        for _ in 0..128 {
+            assert_eq!(feed.push(true, false, true), DataQuality::Good);
+        }
+
+        /* the server time stops advancing, a couple of ticks are tolerated */
+        let frozen_at = feed.timestamp();
+        for _ in 0..WORLD_FREEZE_TICKS * 2 {
+            assert_eq!(feed.push(true, false, false), DataQuality::Good);
+        }
+
+        /* skip the frame at the threshold */
+        feed.push(true, false, false);
+        for _ in 0..64 {
+            assert_eq!(
+                feed.push(true, false, false),
+                DataQuality::Frozen { since: frozen_at }
+            );
+        }
+
+        /* the ticks are received again and the world settles after the recovery period */
+        feed.push(true, false, true);
+        assert!(matches!(
+            feed.push(true, false, true),
+            DataQuality::Degraded { .. }
+        ));
+        for frame in 0..256 {
+            let quality = feed.push(true, false, true);
+            if frame < 120 {
+                assert!(matches!(quality, DataQuality::Degraded { .. }));
+            } else if frame > 132 {
+                assert_eq!(quality, DataQuality::Good);
+            }
+        }
+    }
+
+    #[test]
+    fn standing_still() {
+        /* nobody moves, but the server keeps ticking */
+        let mut feed = Feed::new();
+        for _ in 0..1024 {
+            assert_eq!(feed.push(true, false, true), DataQuality::Good);
+        }
+    }
+
+    #[test]
+    fn idle_and_suspended() {
+        let mut feed = Feed::new();
+
+        /* idling in the main menu */
+        for _ in 0..256 {
+            assert_eq!(feed.push(false, false, false), DataQuality::Good);
+        }
+
+        /* the server time does not advance while paused (e.g. a tactical timeout in the warmup) */
+        for _ in 0..32 {
+            assert_eq!(feed.push(true, false, true), DataQuality::Good);
+        }
+        for _ in 0..256 {
+            assert_eq!(feed.push(true, true, false), DataQuality::Good);
+        }
+        for _ in 0..32 {
+            assert_eq!(feed.push(true, false, true), DataQuality::Good);
+        }
+
+        /* a disconnect while frozen resets the tracker */
+        for _ in 0..32 {
+            feed.push(true, false, false);
+        }
+        assert!(matches!(feed.tracker.quality(), DataQuality::Frozen { .. }));
+        assert_eq!(feed.push(false, false, false), DataQuality::Good);
+        assert_eq!(feed.push(true, false, true), DataQuality::Good);
+    }
+
+    #[test]
+    fn server_time_jump() {
+        let mut feed = Feed::new();
+        for _ in 0..32 {
+            assert_eq!(feed.push(true, false, true), DataQuality::Good);
+        }
+
+        /* the client catches up on half a second of ticks */
+        feed.server_time += 0.5;
+        match feed.push(true, false, true) {
+            DataQuality::Degraded { reason } => assert!(reason.contains("jumped"), "{}", reason),
+            quality => panic!("unexpected quality {:?}", quality),
+        }
+
+        for _ in 0..256 {
+            feed.push(true, false, true);
+        }
+        assert_eq!(feed.tracker.quality(), &DataQuality::Good);
+
+        /* the server time has been reset (e.g. map change) */
+        feed.server_time = 1.0;
+        assert_eq!(feed.push(true, false, true), DataQuality::Good);
+
+        feed.tracker.push(feed.timestamp(), true, false, None);
+        assert!(matches!(
+            feed.tracker.quality(),
+            DataQuality::Degraded { .. }
+        ));
+    }
+
+    #[test]
+    fn long_uptime() {
+        /* half a second before the server time looses precision */
+        let mut feed = Feed::new();
+        feed.server_time = SERVER_TIME_MAX - 0.5;
+        for _ in 0..64 {
+            assert_eq!(feed.push(true, false, true), DataQuality::Good);
+        }
+        assert_eq!(feed.server_time, SERVER_TIME_MAX);
+
+        /* the world keeps updating, only the timers are less precise */
+        assert_eq!(feed.push(true, false, true), DataQuality::Good);
+        for _ in 0..256 {
+            match feed.push(true, false, true) {
+                DataQuality::Degraded { reason } => {
+                    assert!(reason.contains("precision"), "{}", reason)
+                }
+                quality => panic!("unexpected quality {:?}", quality),
+            }
+        }
+    }
+}
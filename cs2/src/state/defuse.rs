@@ -0,0 +1,206 @@
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+/// Aborted defuse attempts shorter then this (in seconds) are considered short attempts
+pub const DEFUSE_SHORT_ATTEMPT_DURATION: f32 = 2.0;
+
+/// The last aborted attempt must be shorter then this (in seconds) to be considered a fake
+pub const DEFUSE_FAKE_ATTEMPT_DURATION: f32 = 1.0;
+
+/// Min amount of short attempts for the defuser to be considered faking
+pub const DEFUSE_FAKE_MIN_SHORT_ATTEMPTS: u32 = 2;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DefuseAttempts {
+    /// Amount of defuse attempts (including the current one) for the current plant
+    pub attempts: u32,
+
+    /// The defuser(s) repeatedly started and stopped defusing
+    pub likely_faking: bool,
+}
+
+/// Defuse start and stop edges of the current plant
+pub struct StateDefuseShadow {
+    /// Detonation time of the bomb this shadow is tracking.
+    /// Used to detect new plants.
+    plant_time_blow: Option<f32>,
+
+    /// Server time when the current defuse attempt started
+    defuse_started: Option<f32>,
+
+    attempts: u32,
+    short_attempts: u32,
+    last_aborted_duration: Option<f32>,
+}
+
+impl State for StateDefuseShadow {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self::new())
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+}
+
+impl StateDefuseShadow {
+    fn new() -> Self {
+        Self {
+            plant_time_blow: None,
+            defuse_started: None,
+            attempts: 0,
+            short_attempts: 0,
+            last_aborted_duration: None,
+        }
+    }
+
+    /// Forget all attempts (e.g. the bomb is no longer planted)
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn attempts(&self) -> DefuseAttempts {
+        DefuseAttempts {
+            attempts: self.attempts,
+            likely_faking: self.short_attempts >= DEFUSE_FAKE_MIN_SHORT_ATTEMPTS
+                && self
+                    .last_aborted_duration
+                    .map(|duration| duration < DEFUSE_FAKE_ATTEMPT_DURATION)
+                    .unwrap_or(false),
+        }
+    }
+
+    /// Record the defuse state of the active bomb identified by its detonation time.
+    pub fn push_sample(
+        &mut self,
+        plant_time_blow: f32,
+        defusing: bool,
+        server_time: f32,
+    ) -> DefuseAttempts {
+        if self.plant_time_blow != Some(plant_time_blow) {
+            self.reset();
+            self.plant_time_blow = Some(plant_time_blow);
+        }
+
+        match (self.defuse_started, defusing) {
+            (None, true) => {
+                self.defuse_started = Some(server_time);
+                self.attempts += 1;
+            }
+            (Some(started), false) => {
+                let duration = (server_time as f64 - started as f64) as f32;
+                if duration < DEFUSE_SHORT_ATTEMPT_DURATION {
+                    self.short_attempts += 1;
+                }
+
+                self.last_aborted_duration = Some(duration);
+                self.defuse_started = None;
+            }
+            _ => {}
+        }
+
+        self.attempts()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        DefuseAttempts,
+        StateDefuseShadow,
+    };
+
+    /// Push a sample every 100ms between `from` and `to`
+    fn push_range(
+        shadow: &mut StateDefuseShadow,
+        plant: f32,
+        defusing: bool,
+        from: f32,
+        to: f32,
+    ) -> DefuseAttempts {
+        let mut result = shadow.attempts();
+        let mut time = from;
+        while time < to {
+            result = shadow.push_sample(plant, defusing, time);
+            time += 0.1;
+        }
+        result
+    }
+
+    #[test]
+    fn stick_fake_stick() {
+        let mut shadow = StateDefuseShadow::new();
+        let plant = 140.0;
+
+        /* tap the bomb */
+        push_range(&mut shadow, plant, true, 100.0, 100.5);
+        let result = push_range(&mut shadow, plant, false, 100.5, 102.0);
+        assert_eq!(
+            result,
+            DefuseAttempts {
+                attempts: 1,
+                likely_faking: false
+            }
+        );
+
+        /* fake */
+        push_range(&mut shadow, plant, true, 102.0, 102.4);
+        let result = push_range(&mut shadow, plant, false, 102.4, 104.0);
+        assert_eq!(
+            result,
+            DefuseAttempts {
+                attempts: 2,
+                likely_faking: true
+            }
+        );
+
+        /* stick */
+        let result = push_range(&mut shadow, plant, true, 104.0, 110.0);
+        assert_eq!(
+            result,
+            DefuseAttempts {
+                attempts: 3,
+                likely_faking: true
+            }
+        );
+    }
+
+    #[test]
+    fn long_attempts() {
+        let mut shadow = StateDefuseShadow::new();
+        let plant = 140.0;
+
+        push_range(&mut shadow, plant, true, 100.0, 100.5);
+        push_range(&mut shadow, plant, false, 100.5, 101.0);
+
+        /* the last attempt was aborted after a longer time */
+        push_range(&mut shadow, plant, true, 101.0, 104.0);
+        let result = push_range(&mut shadow, plant, false, 104.0, 105.0);
+        assert_eq!(
+            result,
+            DefuseAttempts {
+                attempts: 2,
+                likely_faking: false
+            }
+        );
+    }
+
+    #[test]
+    fn new_plant() {
+        let mut shadow = StateDefuseShadow::new();
+
+        push_range(&mut shadow, 140.0, true, 100.0, 100.5);
+        push_range(&mut shadow, 140.0, false, 100.5, 101.0);
+        push_range(&mut shadow, 140.0, true, 101.0, 101.5);
+        let result = push_range(&mut shadow, 140.0, false, 101.5, 102.0);
+        assert!(result.likely_faking);
+
+        let result = shadow.push_sample(260.0, false, 220.0);
+        assert_eq!(result, DefuseAttempts::default());
+    }
+}
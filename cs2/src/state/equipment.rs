@@ -0,0 +1,355 @@
+use anyhow::Context;
+use cs2_schema_cutl::EntityHandle;
+use cs2_schema_generated::cs2::client::{
+    CCSPlayer_ItemServices,
+    CPlayer_WeaponServices,
+    C_BaseEntity,
+    C_BasePlayerPawn,
+    C_CSPlayerPawn,
+    C_EconEntity,
+};
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use super::ResultSkipExt;
+use crate::{
+    ClassNameCache,
+    EntityIndex,
+    StateCS2Memory,
+    StateEntityList,
+    WeaponId,
+    WeaponKind,
+};
+
+/// Upper bound of the weapon slots read from `m_hMyWeapons` (guards against torn reads of the vector size)
+const PLAYER_EQUIPMENT_MAX_WEAPONS: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EquippedWeapon {
+    pub weapon: WeaponId,
+    pub kind: WeaponKind,
+
+    /// Entity index of the weapon entity
+    pub entity_index: EntityIndex,
+}
+
+/// Weapons, armor and defuse kit of a player pawn.
+/// Dead pawns do not have any equipment.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatePlayerEquipment {
+    pub alive: bool,
+
+    pub weapons: Vec<EquippedWeapon>,
+
+    /// Index of the weapon currently held by the player within `weapons`
+    pub active_weapon: Option<usize>,
+
+    pub has_defuser: bool,
+    pub armor: i32,
+}
+
+impl StatePlayerEquipment {
+    pub fn active(&self) -> Option<&EquippedWeapon> {
+        self.weapons.get(self.active_weapon?)
+    }
+
+    pub fn has_weapon(&self, weapon: WeaponId) -> bool {
+        self.weapons.iter().any(|entry| entry.weapon == weapon)
+    }
+
+    pub fn has_kind(&self, kind: WeaponKind) -> bool {
+        self.weapons.iter().any(|entry| entry.kind == kind)
+    }
+}
+
+impl State for StatePlayerEquipment {
+    type Parameter = EntityHandle<dyn C_CSPlayerPawn>;
+
+    fn create(states: &StateRegistry, handle: Self::Parameter) -> anyhow::Result<Self> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let entities = states.resolve::<StateEntityList>(())?;
+        let class_name_cache = states.resolve::<ClassNameCache>(())?;
+
+        let Some(pawn) = entities.resolve_typed(&class_name_cache, &handle)? else {
+            anyhow::bail!("entity does not exists")
+        };
+        let pawn = pawn
+            .value_reference(memory.view_arc())
+            .context("player pawn nullptr")?;
+
+        if pawn.m_lifeState()? != 0 || pawn.m_iHealth()? <= 0 {
+            return Ok(Self::default());
+        }
+
+        let has_defuser = pawn
+            .m_pItemServices()?
+            .value_reference(memory.view_arc())
+            .context("m_pItemServices nullptr")?
+            .cast::<dyn CCSPlayer_ItemServices>()
+            .m_bHasDefuser()?;
+
+        let weapon_services = pawn
+            .m_pWeaponServices()?
+            .value_reference(memory.view_arc())
+            .context("m_pWeaponServices nullptr")?;
+
+        let weapon_handles = weapon_services.m_hMyWeapons()?;
+        let weapon_count = (weapon_handles.size()? as usize).min(PLAYER_EQUIPMENT_MAX_WEAPONS);
+
+        let mut weapons = Vec::with_capacity(weapon_count);
+        for weapon_handle in weapon_handles
+            .data()?
+            .elements(memory.view(), 0..weapon_count)?
+        {
+            if !weapon_handle.is_valid() {
+                /* empty weapon slot */
+                continue;
+            }
+
+            let Some(weapon) = entities
+                .resolve_typed(&class_name_cache, &weapon_handle)
+                .ok_or_skip("equipped weapon")
+                .flatten()
+                .and_then(|weapon| weapon.value_reference(memory.view_arc()))
+            else {
+                continue;
+            };
+
+            /* the weapon entity may be removed while being read (e.g. dropped and picked up) */
+            let weapon_id = (|| -> anyhow::Result<u16> {
+                Ok(weapon
+                    .cast::<dyn C_EconEntity>()
+                    .m_AttributeManager()?
+                    .m_Item()?
+                    .m_iItemDefinitionIndex()?)
+            })();
+            let Some(weapon_id) = weapon_id.ok_or_skip("equipped weapon definition index") else {
+                continue;
+            };
+
+            let weapon = WeaponId::from_id(weapon_id).unwrap_or(WeaponId::Unknown);
+            weapons.push(EquippedWeapon {
+                weapon,
+                kind: weapon.kind(),
+                entity_index: EntityIndex::from_handle(&weapon_handle),
+            });
+        }
+
+        let active_weapon = EntityIndex::from_valid_handle(&weapon_services.m_hActiveWeapon()?)
+            .and_then(|active| {
+                weapons
+                    .iter()
+                    .position(|weapon| weapon.entity_index == active)
+            });
+
+        Ok(Self {
+            alive: true,
+
+            weapons,
+            active_weapon,
+
+            has_defuser,
+            armor: pawn.m_ArmorValue()?,
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cs2_schema_cutl::EntityHandle;
+    use cs2_schema_generated::cs2::client::C_CSPlayerPawn;
+    use utils_state::{
+        State,
+        StateRegistry,
+    };
+
+    use super::{
+        EquippedWeapon,
+        StatePlayerEquipment,
+    };
+    use crate::{
+        test_fixture::{
+            field_offset,
+            setup_dump_schema,
+            write,
+            EntityFixture,
+        },
+        EntityIndex,
+        WeaponId,
+        WeaponKind,
+    };
+
+    const PAWN_HANDLE: u32 = 0x8001;
+    const PAWN_ADDRESS: u64 = 0x10_0000;
+    const WEAPON_SERVICES_ADDRESS: u64 = 0x20_0000;
+    const ITEM_SERVICES_ADDRESS: u64 = 0x21_0000;
+    const WEAPON_HANDLES_ADDRESS: u64 = 0x22_0000;
+
+    /// (handle, class name, item definition index)
+    const WEAPONS: [(u32, &str, u16); 3] = [
+        (0x8002, "C_AK47", 7),
+        (0x8003, "C_C4", 49),
+        /* the weapon slot is reused by another entity */
+        (0x8004, "C_PlantedC4", 49),
+    ];
+
+    /// States containing a player pawn with the [WEAPONS], an empty weapon slot and a removed weapon
+    fn equipment_states(health: i32) -> StateRegistry {
+        let mut fixture = EntityFixture::default();
+        let entity_address = |handle: u32| PAWN_ADDRESS + ((handle & 0x7FFF) as u64 - 1) * 0x1_0000;
+
+        fixture.push_entity(PAWN_HANDLE, "C_CSPlayerPawn", PAWN_ADDRESS);
+
+        let mut pawn = vec![0u8; 0x4000];
+        write(
+            &mut pawn,
+            field_offset("C_BaseEntity", "m_iHealth"),
+            &health.to_le_bytes(),
+        );
+        write(
+            &mut pawn,
+            field_offset("C_CSPlayerPawn", "m_ArmorValue"),
+            &100i32.to_le_bytes(),
+        );
+        write(
+            &mut pawn,
+            field_offset("C_BasePlayerPawn", "m_pWeaponServices"),
+            &WEAPON_SERVICES_ADDRESS.to_le_bytes(),
+        );
+        write(
+            &mut pawn,
+            field_offset("C_BasePlayerPawn", "m_pItemServices"),
+            &ITEM_SERVICES_ADDRESS.to_le_bytes(),
+        );
+        fixture.record(PAWN_ADDRESS, &pawn);
+
+        let mut item_services = vec![0u8; 0x100];
+        write(
+            &mut item_services,
+            field_offset("CCSPlayer_ItemServices", "m_bHasDefuser"),
+            &[1],
+        );
+        fixture.record(ITEM_SERVICES_ADDRESS, &item_services);
+
+        /* one empty slot and a weapon which has already been removed */
+        let weapon_handles = [WEAPONS[0].0, u32::MAX, WEAPONS[1].0, WEAPONS[2].0, 0x8005];
+        let weapon_handles_offset = field_offset("CPlayer_WeaponServices", "m_hMyWeapons");
+        let mut weapon_services = vec![0u8; 0x100];
+        write(
+            &mut weapon_services,
+            weapon_handles_offset,
+            &(weapon_handles.len() as u32).to_le_bytes(),
+        );
+        write(
+            &mut weapon_services,
+            weapon_handles_offset + 0x08,
+            &WEAPON_HANDLES_ADDRESS.to_le_bytes(),
+        );
+        write(
+            &mut weapon_services,
+            field_offset("CPlayer_WeaponServices", "m_hActiveWeapon"),
+            &WEAPONS[1].0.to_le_bytes(),
+        );
+        fixture.record(WEAPON_SERVICES_ADDRESS, &weapon_services);
+        fixture.record(
+            WEAPON_HANDLES_ADDRESS,
+            &weapon_handles
+                .iter()
+                .flat_map(|handle| handle.to_le_bytes())
+                .collect::<Vec<_>>(),
+        );
+
+        let item_definition_offset = field_offset("C_EconEntity", "m_AttributeManager")
+            + field_offset("C_AttributeContainer", "m_Item")
+            + field_offset("C_EconItemView", "m_iItemDefinitionIndex");
+        for (handle, class_name, item_definition) in WEAPONS {
+            let weapon_address = entity_address(handle);
+            fixture.push_entity(handle, class_name, weapon_address);
+
+            let mut weapon = vec![0u8; 0x2000];
+            write(
+                &mut weapon,
+                item_definition_offset,
+                &item_definition.to_le_bytes(),
+            );
+            fixture.record(weapon_address, &weapon);
+        }
+
+        fixture.into_states()
+    }
+
+    #[test]
+    fn weapon_slots() {
+        setup_dump_schema();
+
+        let states = equipment_states(100);
+        let equipment = StatePlayerEquipment::create(
+            &states,
+            EntityHandle::<dyn C_CSPlayerPawn>::from_index(PAWN_HANDLE),
+        )
+        .unwrap();
+
+        assert!(equipment.alive);
+        assert_eq!(
+            equipment.weapons,
+            vec![
+                EquippedWeapon {
+                    weapon: WeaponId::Ak47,
+                    kind: WeaponKind::Rifle,
+                    entity_index: EntityIndex(2),
+                },
+                EquippedWeapon {
+                    weapon: WeaponId::C4,
+                    kind: WeaponKind::C4,
+                    entity_index: EntityIndex(3),
+                },
+            ]
+        );
+        assert_eq!(
+            equipment.active().map(|weapon| weapon.weapon),
+            Some(WeaponId::C4)
+        );
+        assert!(equipment.has_kind(WeaponKind::C4));
+        assert!(!equipment.has_weapon(WeaponId::Taser));
+        assert!(equipment.has_defuser);
+        assert_eq!(equipment.armor, 100);
+    }
+
+    #[test]
+    fn dead_pawn() {
+        setup_dump_schema();
+
+        let states = equipment_states(0);
+        let equipment = StatePlayerEquipment::create(
+            &states,
+            EntityHandle::<dyn C_CSPlayerPawn>::from_index(PAWN_HANDLE),
+        )
+        .unwrap();
+        assert_eq!(equipment, StatePlayerEquipment::default());
+
+        /* the handle does not point to a player pawn */
+        assert!(StatePlayerEquipment::create(
+            &states,
+            EntityHandle::<dyn C_CSPlayerPawn>::from_index(WEAPONS[0].0),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn weapon_kinds() {
+        assert_eq!(WeaponId::Ak47.kind(), WeaponKind::Rifle);
+        assert_eq!(WeaponId::USPS.kind(), WeaponKind::Pistol);
+        assert_eq!(WeaponId::Elite.kind(), WeaponKind::Pistol);
+        assert_eq!(WeaponId::KnifeKarambit.kind(), WeaponKind::Knife);
+        assert_eq!(WeaponId::Molotov.kind(), WeaponKind::Grenade);
+        assert_eq!(WeaponId::Taser.kind(), WeaponKind::Taser);
+        assert_eq!(WeaponId::Unknown.kind(), WeaponKind::Unknown);
+    }
+}
@@ -0,0 +1,843 @@
+use std::ffi::CStr;
+
+use anyhow::Context;
+use cs2_schema_generated::cs2::client::{
+    CBasePlayerController,
+    CCSPlayerController,
+    CCSPlayerController_ActionTrackingServices,
+    CEntityInstance,
+    C_CSGameRules,
+};
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    BombCarrierInfo,
+    BombDefuser,
+    CEntityIdentityEx,
+    ControllerIndex,
+    EconomyRules,
+    PlantedC4,
+    PlantedC4State,
+    StateCS2Memory,
+    StateEconomyRules,
+    StateGameRules,
+    StatePlayerControllers,
+    StateTeamScores,
+    TEAM_ID_COUNTER_TERRORIST,
+    TEAM_ID_TERRORIST,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchEvent {
+    RoundStart {
+        /// Round number starting with 1
+        round_number: i32,
+        score_terrorists: Option<i32>,
+        score_counter_terrorists: Option<i32>,
+    },
+    RoundEnd {
+        /// Round number starting with 1
+        round_number: i32,
+        score_terrorists: Option<i32>,
+        score_counter_terrorists: Option<i32>,
+    },
+    /// The second half of the regulation time or an overtime starts with this round
+    HalfTimeStarted {
+        /// Round number starting with 1
+        round_number: i32,
+    },
+    /// An overtime starts with this round
+    OvertimeStarted {
+        /// Round number starting with 1
+        round_number: i32,
+    },
+    /// The round is the last round of the current half
+    LastRoundOfHalf {
+        /// Round number starting with 1
+        round_number: i32,
+    },
+    /// The team wins the match by winning this round
+    MatchPoint {
+        /// Round number starting with 1
+        round_number: i32,
+        team: u8,
+    },
+    BombPlanted {
+        bomb_site: u8,
+
+        /// Time (in seconds) until detonation
+        time_detonation: f32,
+
+        /// Time remaining on the round clock at the plant (see [PlantedC4::planted_at_round_remaining])
+        planted_at_round_remaining: Option<f32>,
+        planted_at_server_time: Option<f32>,
+
+        /// The last known bomb carrier
+        planter_name: Option<String>,
+    },
+    BombDefused {
+        bomb_site: u8,
+
+        /// Time (in seconds) which was remaining until detonation
+        time_detonation: Option<f32>,
+        defuser_name: Option<String>,
+    },
+    BombDetonated {
+        bomb_site: u8,
+    },
+    Kill {
+        victim_name: String,
+
+        /// Attacker which received the kill.
+        /// None if the attacker is ambiguous (multiple kills within the same frame) or there was no attacker.
+        attacker_name: Option<String>,
+        headshot: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerSnapshot {
+    pub player_name: String,
+
+    /// Steam id of the player (zero for bots)
+    pub steam_id: u64,
+
+    pub alive: bool,
+    pub round_kills: i32,
+    pub round_kills_headshot: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BombSnapshot {
+    NotPlanted,
+    Active {
+        bomb_site: u8,
+        time_detonation: f32,
+
+        /// The bomb has been planted before the round started (see [PlantedC4::pre_planted])
+        pre_planted: bool,
+
+        planted_at_round_remaining: Option<f32>,
+        planted_at_server_time: Option<f32>,
+    },
+    Defused {
+        bomb_site: u8,
+    },
+    Detonated {
+        bomb_site: u8,
+    },
+}
+
+/// Values of a single frame which are relevant for detecting match events
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchSnapshot {
+    pub rounds_played: Option<i32>,
+    pub freeze_period: Option<bool>,
+    pub score_terrorists: Option<i32>,
+    pub score_counter_terrorists: Option<i32>,
+
+    pub bomb: BombSnapshot,
+    pub bomb_carrier_name: Option<String>,
+    pub bomb_defuser_name: Option<String>,
+
+    /// Players by their controller entity id
+    pub players: Vec<(ControllerIndex, PlayerSnapshot)>,
+}
+
+impl MatchSnapshot {
+    pub fn read(states: &StateRegistry) -> anyhow::Result<Self> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+
+        let (rounds_played, freeze_period) = {
+            let game_rules = states.resolve::<StateGameRules>(())?;
+            match &game_rules.rules {
+                Some(rules) => (
+                    Some(rules.m_totalRoundsPlayed()?),
+                    Some(rules.m_bFreezePeriod()?),
+                ),
+                None => (None, None),
+            }
+        };
+
+        let scores = states.resolve::<StateTeamScores>(())?;
+
+        let planted_c4 = states.resolve::<PlantedC4>(())?;
+        let bomb_site = planted_c4.bomb_site;
+        let bomb = match planted_c4.state {
+            PlantedC4State::NotPlanted => BombSnapshot::NotPlanted,
+            PlantedC4State::Active { time_detonation } => BombSnapshot::Active {
+                bomb_site,
+                time_detonation,
+                pre_planted: planted_c4.pre_planted,
+                planted_at_round_remaining: planted_c4.planted_at_round_remaining,
+                planted_at_server_time: planted_c4.planted_at_server_time,
+            },
+            PlantedC4State::Defused => BombSnapshot::Defused { bomb_site },
+            PlantedC4State::Detonated => BombSnapshot::Detonated { bomb_site },
+        };
+
+        let bomb_carrier_name = states.resolve::<BombCarrierInfo>(())?.carrier_name.clone();
+        let bomb_defuser_name = planted_c4
+            .defuser
+            .as_ref()
+            .and_then(BombDefuser::known_player_name)
+            .map(str::to_string);
+
+        let controllers = states.resolve::<StatePlayerControllers>(())?;
+        let mut players = Vec::with_capacity(controllers.instances.len());
+        for entry in controllers.instances.iter() {
+            let Some(controller) = entry.instance.value_reference(memory.view_arc()) else {
+                continue;
+            };
+
+            let entity_id = ControllerIndex::from_handle(
+                &controller
+                    .m_pEntity()?
+                    .value_reference(memory.view_arc())
+                    .context("m_pEntity nullptr")?
+                    .handle::<()>()?,
+            );
+
+            let action_tracking = controller
+                .m_pActionTrackingServices()?
+                .value_reference(memory.view_arc())
+                .context("m_pActionTrackingServices nullptr")?;
+
+            let player_name = CStr::from_bytes_until_nul(&controller.m_iszPlayerName()?)
+                .context("player name missing nul terminator")?
+                .to_string_lossy()
+                .to_string();
+
+            players.push((
+                entity_id,
+                PlayerSnapshot {
+                    player_name,
+                    steam_id: controller.m_steamID()?,
+                    alive: controller.m_bPawnIsAlive()?,
+                    round_kills: action_tracking.m_iNumRoundKills()?,
+                    round_kills_headshot: action_tracking.m_iNumRoundKillsHeadshots()?,
+                },
+            ));
+        }
+
+        Ok(Self {
+            rounds_played,
+            freeze_period,
+            score_terrorists: scores.terrorists,
+            score_counter_terrorists: scores.counter_terrorists,
+
+            bomb,
+            bomb_carrier_name,
+            bomb_defuser_name,
+
+            players,
+        })
+    }
+}
+
+/// Detect all match events which occurred between two snapshots
+pub fn detect_match_events(previous: &MatchSnapshot, current: &MatchSnapshot) -> Vec<MatchEvent> {
+    let mut events = Vec::new();
+
+    if let (Some(previous_rounds), Some(current_rounds)) =
+        (previous.rounds_played, current.rounds_played)
+    {
+        if current_rounds > previous_rounds {
+            events.push(MatchEvent::RoundEnd {
+                round_number: current_rounds,
+                score_terrorists: current.score_terrorists,
+                score_counter_terrorists: current.score_counter_terrorists,
+            });
+        }
+    }
+
+    if previous.freeze_period == Some(true) && current.freeze_period == Some(false) {
+        if let Some(rounds_played) = current.rounds_played {
+            events.push(MatchEvent::RoundStart {
+                round_number: rounds_played + 1,
+                score_terrorists: current.score_terrorists,
+                score_counter_terrorists: current.score_counter_terrorists,
+            });
+        }
+    }
+
+    match (&previous.bomb, &current.bomb) {
+        (
+            BombSnapshot::NotPlanted,
+            BombSnapshot::Active {
+                bomb_site,
+                time_detonation,
+                pre_planted: false,
+                planted_at_round_remaining,
+                planted_at_server_time,
+            },
+        ) => events.push(MatchEvent::BombPlanted {
+            bomb_site: *bomb_site,
+            time_detonation: *time_detonation,
+            planted_at_round_remaining: *planted_at_round_remaining,
+            planted_at_server_time: *planted_at_server_time,
+            planter_name: previous.bomb_carrier_name.clone(),
+        }),
+        (previous_bomb, BombSnapshot::Defused { bomb_site })
+            if !matches!(previous_bomb, BombSnapshot::Defused { .. }) =>
+        {
+            events.push(MatchEvent::BombDefused {
+                bomb_site: *bomb_site,
+                time_detonation: match previous_bomb {
+                    BombSnapshot::Active {
+                        time_detonation, ..
+                    } => Some(*time_detonation),
+                    _ => None,
+                },
+                defuser_name: previous.bomb_defuser_name.clone(),
+            })
+        }
+        (previous_bomb, BombSnapshot::Detonated { bomb_site })
+            if !matches!(previous_bomb, BombSnapshot::Detonated { .. }) =>
+        {
+            events.push(MatchEvent::BombDetonated {
+                bomb_site: *bomb_site,
+            })
+        }
+        _ => {}
+    }
+
+    /* (player, new kills, new headshot kills) */
+    let mut attackers = Vec::new();
+    let mut victims = Vec::new();
+    for (entity_id, player) in current.players.iter() {
+        let Some((_, previous_player)) = previous
+            .players
+            .iter()
+            .find(|(previous_id, _)| previous_id == entity_id)
+        else {
+            continue;
+        };
+
+        if previous_player.alive && !player.alive {
+            victims.push(player);
+        }
+
+        if player.round_kills > previous_player.round_kills {
+            attackers.push((
+                player,
+                player.round_kills - previous_player.round_kills,
+                player.round_kills_headshot - previous_player.round_kills_headshot,
+            ));
+        }
+    }
+
+    /* a single attacker can be attributed to all victims of this frame */
+    let attacker = match attackers.as_slice() {
+        [(attacker, kills, headshots)] if *kills as usize == victims.len() => {
+            Some((attacker, *headshots > 0 && victims.len() == 1))
+        }
+        _ => None,
+    };
+
+    for victim in victims {
+        events.push(MatchEvent::Kill {
+            victim_name: victim.player_name.clone(),
+            attacker_name: attacker.map(|(attacker, _)| attacker.player_name.clone()),
+            headshot: attacker.map(|(_, headshot)| headshot).unwrap_or(false),
+        });
+    }
+
+    events
+}
+
+/// Announcements (half time, overtime, last round of half and match points) for the round (zero based)
+/// with the given scores (terrorists, counter terrorists) at the start of the round
+pub fn round_announcements(
+    rules: &EconomyRules,
+    round_index: i32,
+    scores: (i32, i32),
+) -> Vec<MatchEvent> {
+    let round_number = round_index + 1;
+    let mut events = Vec::new();
+
+    if rules.is_overtime_start(round_index) {
+        events.push(MatchEvent::OvertimeStarted { round_number });
+    } else if round_index > 0 && rules.is_half_start(round_index) {
+        events.push(MatchEvent::HalfTimeStarted { round_number });
+    }
+
+    if rules.is_half_start(round_index + 1) || round_number == rules.max_rounds {
+        events.push(MatchEvent::LastRoundOfHalf { round_number });
+    }
+
+    let rounds_to_win = rules.rounds_to_win(round_index);
+    for (team, score) in [
+        (TEAM_ID_TERRORIST, scores.0),
+        (TEAM_ID_COUNTER_TERRORIST, scores.1),
+    ] {
+        if score == rounds_to_win - 1 {
+            events.push(MatchEvent::MatchPoint { round_number, team });
+        }
+    }
+
+    events
+}
+
+/// Emits the [round_announcements] once for every round
+#[derive(Debug, Clone, Default)]
+pub struct RoundAnnouncer {
+    /// Round number of the last announcements.
+    /// Retained while no snapshots are available (e.g. while reconnecting)
+    /// so the announcements of the current round will not be repeated.
+    last_announced_round: Option<i32>,
+}
+
+impl RoundAnnouncer {
+    pub fn push_snapshot(
+        &mut self,
+        snapshot: &MatchSnapshot,
+        rules: &EconomyRules,
+    ) -> Vec<MatchEvent> {
+        let (Some(rounds_played), Some(score_terrorists), Some(score_counter_terrorists)) = (
+            snapshot.rounds_played,
+            snapshot.score_terrorists,
+            snapshot.score_counter_terrorists,
+        ) else {
+            return Vec::new();
+        };
+
+        let round_number = rounds_played + 1;
+        if self.last_announced_round == Some(round_number) {
+            return Vec::new();
+        }
+
+        self.last_announced_round = Some(round_number);
+        round_announcements(
+            rules,
+            rounds_played,
+            (score_terrorists, score_counter_terrorists),
+        )
+    }
+}
+
+/// Match events which occurred since the last frame.
+/// The events will only be detected while this state is being resolved every frame.
+pub struct StateMatchEvents {
+    pub events: Vec<MatchEvent>,
+    snapshot: Option<MatchSnapshot>,
+    announcer: RoundAnnouncer,
+}
+
+impl StateMatchEvents {
+    /// Snapshot of the current frame
+    pub fn snapshot(&self) -> Option<&MatchSnapshot> {
+        self.snapshot.as_ref()
+    }
+}
+
+impl State for StateMatchEvents {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            events: Vec::new(),
+            snapshot: MatchSnapshot::read(states).ok(),
+            announcer: Default::default(),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        self.events.clear();
+
+        let snapshot = match MatchSnapshot::read(states) {
+            Ok(snapshot) => snapshot,
+            Err(err) => {
+                /* Do not detect events across frames which could not be read */
+                self.snapshot = None;
+                return Err(err);
+            }
+        };
+
+        if let Some(previous) = &self.snapshot {
+            self.events = detect_match_events(previous, &snapshot);
+        }
+
+        let rules = states
+            .resolve::<StateEconomyRules>(())
+            .map(|state| state.rules)
+            .unwrap_or_default();
+        self.events
+            .extend(self.announcer.push_snapshot(&snapshot, &rules));
+
+        self.snapshot = Some(snapshot);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        detect_match_events,
+        BombSnapshot,
+        MatchEvent,
+        MatchSnapshot,
+        PlayerSnapshot,
+        RoundAnnouncer,
+    };
+    use crate::{
+        ControllerIndex,
+        EconomyRules,
+        TEAM_ID_COUNTER_TERRORIST,
+        TEAM_ID_TERRORIST,
+    };
+
+    fn player(
+        name: &str,
+        alive: bool,
+        round_kills: i32,
+        round_kills_headshot: i32,
+    ) -> PlayerSnapshot {
+        PlayerSnapshot {
+            player_name: name.to_string(),
+            steam_id: 0,
+            alive,
+            round_kills,
+            round_kills_headshot,
+        }
+    }
+
+    fn snapshot() -> MatchSnapshot {
+        MatchSnapshot {
+            rounds_played: Some(3),
+            freeze_period: Some(false),
+            score_terrorists: Some(2),
+            score_counter_terrorists: Some(1),
+
+            bomb: BombSnapshot::NotPlanted,
+            bomb_carrier_name: Some("carrier".to_string()),
+            bomb_defuser_name: None,
+
+            players: vec![
+                (ControllerIndex(1), player("alice", true, 0, 0)),
+                (ControllerIndex(2), player("bob", true, 0, 0)),
+                (ControllerIndex(3), player("carol", true, 0, 0)),
+            ],
+        }
+    }
+
+    #[test]
+    fn no_change() {
+        assert!(detect_match_events(&snapshot(), &snapshot()).is_empty());
+    }
+
+    #[test]
+    fn rounds() {
+        let previous = MatchSnapshot {
+            freeze_period: Some(true),
+            ..snapshot()
+        };
+        assert_eq!(
+            detect_match_events(&previous, &snapshot()),
+            vec![MatchEvent::RoundStart {
+                round_number: 4,
+                score_terrorists: Some(2),
+                score_counter_terrorists: Some(1)
+            }]
+        );
+
+        let current = MatchSnapshot {
+            rounds_played: Some(4),
+            score_terrorists: Some(3),
+            ..snapshot()
+        };
+        assert_eq!(
+            detect_match_events(&snapshot(), &current),
+            vec![MatchEvent::RoundEnd {
+                round_number: 4,
+                score_terrorists: Some(3),
+                score_counter_terrorists: Some(1)
+            }]
+        );
+    }
+
+    #[test]
+    fn bomb() {
+        let planted = MatchSnapshot {
+            bomb: BombSnapshot::Active {
+                bomb_site: 1,
+                time_detonation: 40.0,
+                pre_planted: false,
+                planted_at_round_remaining: Some(62.5),
+                planted_at_server_time: Some(1200.0),
+            },
+            bomb_defuser_name: Some("defuser".to_string()),
+            ..snapshot()
+        };
+        assert_eq!(
+            detect_match_events(&snapshot(), &planted),
+            vec![MatchEvent::BombPlanted {
+                bomb_site: 1,
+                time_detonation: 40.0,
+                planted_at_round_remaining: Some(62.5),
+                planted_at_server_time: Some(1200.0),
+                planter_name: Some("carrier".to_string())
+            }]
+        );
+
+        let defused = MatchSnapshot {
+            bomb: BombSnapshot::Defused { bomb_site: 1 },
+            ..snapshot()
+        };
+        assert_eq!(
+            detect_match_events(&planted, &defused),
+            vec![MatchEvent::BombDefused {
+                bomb_site: 1,
+                time_detonation: Some(40.0),
+                defuser_name: Some("defuser".to_string())
+            }]
+        );
+        assert!(detect_match_events(&defused, &defused).is_empty());
+
+        let detonated = MatchSnapshot {
+            bomb: BombSnapshot::Detonated { bomb_site: 1 },
+            ..snapshot()
+        };
+        assert_eq!(
+            detect_match_events(&planted, &detonated),
+            vec![MatchEvent::BombDetonated { bomb_site: 1 }]
+        );
+    }
+
+    #[test]
+    fn kills() {
+        let current = MatchSnapshot {
+            players: vec![
+                (ControllerIndex(1), player("alice", true, 1, 1)),
+                (ControllerIndex(2), player("bob", false, 0, 0)),
+                (ControllerIndex(3), player("carol", true, 0, 0)),
+            ],
+            ..snapshot()
+        };
+        assert_eq!(
+            detect_match_events(&snapshot(), &current),
+            vec![MatchEvent::Kill {
+                victim_name: "bob".to_string(),
+                attacker_name: Some("alice".to_string()),
+                headshot: true
+            }]
+        );
+
+        /* two attackers within the same frame can not be attributed */
+        let current = MatchSnapshot {
+            players: vec![
+                (ControllerIndex(1), player("alice", false, 1, 0)),
+                (ControllerIndex(2), player("bob", false, 1, 0)),
+                (ControllerIndex(3), player("carol", true, 0, 0)),
+            ],
+            ..snapshot()
+        };
+        assert_eq!(
+            detect_match_events(&snapshot(), &current),
+            vec![
+                MatchEvent::Kill {
+                    victim_name: "alice".to_string(),
+                    attacker_name: None,
+                    headshot: false
+                },
+                MatchEvent::Kill {
+                    victim_name: "bob".to_string(),
+                    attacker_name: None,
+                    headshot: false
+                }
+            ]
+        );
+    }
+
+    /// Recorded retake round: The bomb gets planted while the round is being restarted
+    #[test]
+    fn retake_round() {
+        let active = |time_detonation| BombSnapshot::Active {
+            bomb_site: 0,
+            time_detonation,
+            pre_planted: true,
+            planted_at_round_remaining: None,
+            planted_at_server_time: Some(95.0),
+        };
+
+        let frames = [
+            /* previous round ended with a detonation */
+            MatchSnapshot {
+                bomb: BombSnapshot::Detonated { bomb_site: 1 },
+                ..snapshot()
+            },
+            MatchSnapshot {
+                rounds_played: Some(4),
+                freeze_period: Some(true),
+                bomb: BombSnapshot::NotPlanted,
+                bomb_carrier_name: None,
+                ..snapshot()
+            },
+            MatchSnapshot {
+                rounds_played: Some(4),
+                freeze_period: Some(true),
+                bomb: active(40.0),
+                bomb_carrier_name: None,
+                ..snapshot()
+            },
+            MatchSnapshot {
+                rounds_played: Some(4),
+                freeze_period: Some(false),
+                bomb: active(37.5),
+                bomb_carrier_name: None,
+                ..snapshot()
+            },
+            MatchSnapshot {
+                rounds_played: Some(4),
+                bomb: active(12.0),
+                bomb_carrier_name: None,
+                bomb_defuser_name: Some("defuser".to_string()),
+                ..snapshot()
+            },
+            MatchSnapshot {
+                rounds_played: Some(4),
+                bomb: BombSnapshot::Defused { bomb_site: 0 },
+                bomb_carrier_name: None,
+                ..snapshot()
+            },
+        ];
+
+        let events = frames
+            .windows(2)
+            .flat_map(|frames| detect_match_events(&frames[0], &frames[1]))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            events,
+            vec![
+                MatchEvent::RoundEnd {
+                    round_number: 4,
+                    score_terrorists: Some(2),
+                    score_counter_terrorists: Some(1)
+                },
+                MatchEvent::RoundStart {
+                    round_number: 5,
+                    score_terrorists: Some(2),
+                    score_counter_terrorists: Some(1)
+                },
+                MatchEvent::BombDefused {
+                    bomb_site: 0,
+                    time_detonation: Some(12.0),
+                    defuser_name: Some("defuser".to_string())
+                }
+            ]
+        );
+    }
+
+    /// Announcements of a MR12 match ending in the first overtime
+    #[test]
+    fn announcements() {
+        let rules = EconomyRules::default();
+        let mut announcer = RoundAnnouncer::default();
+
+        /* scores (terrorists, counter terrorists) after each round */
+        let mut scores = vec![(0, 0)];
+        let winners = [TEAM_ID_TERRORIST, TEAM_ID_COUNTER_TERRORIST]
+            .repeat(6)
+            .into_iter()
+            .chain([TEAM_ID_COUNTER_TERRORIST; 6])
+            .chain([TEAM_ID_TERRORIST; 6])
+            .chain([TEAM_ID_TERRORIST; 4]);
+        for winner in winners {
+            let (terrorists, counter_terrorists) = scores[scores.len() - 1];
+            scores.push(if winner == TEAM_ID_TERRORIST {
+                (terrorists + 1, counter_terrorists)
+            } else {
+                (terrorists, counter_terrorists + 1)
+            });
+        }
+        assert_eq!(scores[scores.len() - 1], (16, 12));
+
+        let mut announcements = Vec::new();
+        for (rounds_played, (terrorists, counter_terrorists)) in scores.into_iter().enumerate() {
+            let snapshot = MatchSnapshot {
+                rounds_played: Some(rounds_played as i32),
+                score_terrorists: Some(terrorists),
+                score_counter_terrorists: Some(counter_terrorists),
+                ..snapshot()
+            };
+
+            /* multiple frames of the same round */
+            for _ in 0..3 {
+                announcements.extend(announcer.push_snapshot(&snapshot, &rules));
+            }
+        }
+
+        let match_point = |round_number, team| MatchEvent::MatchPoint { round_number, team };
+        assert_eq!(
+            announcements,
+            vec![
+                MatchEvent::LastRoundOfHalf { round_number: 12 },
+                MatchEvent::HalfTimeStarted { round_number: 13 },
+                /* 6:12 */
+                match_point(19, TEAM_ID_COUNTER_TERRORIST),
+                match_point(20, TEAM_ID_COUNTER_TERRORIST),
+                match_point(21, TEAM_ID_COUNTER_TERRORIST),
+                match_point(22, TEAM_ID_COUNTER_TERRORIST),
+                match_point(23, TEAM_ID_COUNTER_TERRORIST),
+                MatchEvent::LastRoundOfHalf { round_number: 24 },
+                match_point(24, TEAM_ID_COUNTER_TERRORIST),
+                /* 12:12 */
+                MatchEvent::OvertimeStarted { round_number: 25 },
+                MatchEvent::LastRoundOfHalf { round_number: 27 },
+                /* 15:12 */
+                MatchEvent::HalfTimeStarted { round_number: 28 },
+                match_point(28, TEAM_ID_TERRORIST),
+            ]
+        );
+    }
+
+    #[test]
+    fn announcements_reconnect() {
+        let rules = EconomyRules::default();
+        let mut announcer = RoundAnnouncer::default();
+
+        let last_round = MatchSnapshot {
+            rounds_played: Some(11),
+            score_terrorists: Some(5),
+            score_counter_terrorists: Some(6),
+            ..snapshot()
+        };
+        assert_eq!(
+            announcer.push_snapshot(&last_round, &rules),
+            vec![MatchEvent::LastRoundOfHalf { round_number: 12 }]
+        );
+
+        /* no game rules while reconnecting */
+        let reconnecting = MatchSnapshot {
+            rounds_played: None,
+            score_terrorists: None,
+            score_counter_terrorists: None,
+            ..snapshot()
+        };
+        assert!(announcer.push_snapshot(&reconnecting, &rules).is_empty());
+        assert!(announcer.push_snapshot(&last_round, &rules).is_empty());
+
+        /* MR15 server */
+        let mut rules = EconomyRules::default();
+        rules.apply_convar("mp_maxrounds", 30);
+        assert_eq!(
+            announcer.push_snapshot(
+                &MatchSnapshot {
+                    rounds_played: Some(14),
+                    ..last_round
+                },
+                &rules
+            ),
+            vec![MatchEvent::LastRoundOfHalf { round_number: 15 }]
+        );
+    }
+}
@@ -22,6 +22,41 @@ use crate::{
     StateResolvedOffset,
 };
 
+/// Max precise server time (2^17 seconds, ~36 hours).
+/// Above this value the resolution of the games f32 time values exceeds 10ms.
+/// Later server times remain valid but timers derived from them are less precise.
+pub const SERVER_TIME_MAX: f32 = 131_072.0;
+
+/// Validate a server time read from the game.
+/// Server times must be finite and not negative.
+pub fn validate_server_time(time: f32) -> anyhow::Result<f32> {
+    if !time.is_finite() || time < 0.0 {
+        anyhow::bail!("server time {} out of the valid range", time);
+    }
+
+    Ok(time)
+}
+
+/// The resolution of the server time is sufficient for timers (see [SERVER_TIME_MAX])
+pub fn is_server_time_precise(time: f32) -> bool {
+    time <= SERVER_TIME_MAX
+}
+
+/// Time in seconds until the server time `target` is reached.
+///
+/// Both times are f32 values of the game. Their difference is exact if they do not differ by more
+/// than a factor of two, hence the precision is only limited by the resolution of the times themself.
+/// Use [server_tick_remaining] for targets which the game stores as ticks.
+pub fn server_time_remaining(target: f32, now: f32) -> f32 {
+    target - now
+}
+
+/// Time in seconds until the server tick `target_tick` is reached.
+/// The tick is converted in f64 as f32 can not represent every tick above 2^24 ticks (~72 hours at 64 tick).
+pub fn server_tick_remaining(target_tick: i32, tick_rate: f32, now: f32) -> f32 {
+    (target_tick as f64 / tick_rate as f64 - now as f64) as f32
+}
+
 pub struct StateGlobals(Copy<dyn Globals>);
 impl State for StateGlobals {
     type Parameter = ();
@@ -44,6 +79,25 @@ impl State for StateGlobals {
     }
 }
 
+impl StateGlobals {
+    /// Current server time (`time_2`) in seconds.
+    /// Fails if the server time is invalid (see [validate_server_time]).
+    pub fn server_time(&self) -> anyhow::Result<f32> {
+        validate_server_time(self.time_2()?)
+    }
+
+    /// The server time is still precise enough for timers (see [SERVER_TIME_MAX])
+    pub fn server_time_precise(&self) -> anyhow::Result<bool> {
+        Ok(is_server_time_precise(self.server_time()?))
+    }
+
+    /// Time in seconds until the server time `target` is reached.
+    /// Negative if `target` lies in the past.
+    pub fn time_remaining(&self, target: f32) -> anyhow::Result<f32> {
+        Ok(server_time_remaining(target, self.server_time()?))
+    }
+}
+
 impl Deref for StateGlobals {
     type Target = dyn Globals;
 
@@ -51,3 +105,80 @@ impl Deref for StateGlobals {
         self.0.deref()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        is_server_time_precise,
+        server_tick_remaining,
+        server_time_remaining,
+        validate_server_time,
+        SERVER_TIME_MAX,
+    };
+    use crate::units::{
+        self,
+        DEFAULT_TICK_RATE,
+    };
+
+    #[test]
+    fn long_session_countdowns() {
+        for now in [86_400.0f32, 86_400.37, 100_000.13, SERVER_TIME_MAX - 41.0] {
+            let now = validate_server_time(now).unwrap();
+
+            /* bomb planted at `now` with a 40s timer, defuse with a kit started 1.5s later */
+            let time_blow = now + 40.0;
+            let time_defuse = now + 1.5 + 5.0;
+
+            for elapsed in [0.0f32, 1.5, 10.25, 39.9] {
+                let current = now + elapsed;
+                let expected_blow = 40.0 - elapsed as f64;
+                assert!(
+                    (server_time_remaining(time_blow, current) as f64 - expected_blow).abs() < 0.01,
+                    "now {} elapsed {}",
+                    now,
+                    elapsed
+                );
+
+                let expected_defuse = 6.5 - elapsed as f64;
+                assert!(
+                    (server_time_remaining(time_defuse, current) as f64 - expected_defuse).abs()
+                        < 0.01,
+                    "now {} elapsed {}",
+                    now,
+                    elapsed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tick_countdowns() {
+        /* 2^24 + 1 ticks (~72 hours) can not be represented by f32 */
+        let target_tick = (1 << 24) + 1;
+        let now = 262_140.0f32;
+        let expected = 4.015625;
+
+        let f32_remaining = units::ticks_to_seconds(target_tick, DEFAULT_TICK_RATE) - now;
+        assert!((f32_remaining - expected).abs() > 0.01);
+
+        assert_eq!(
+            server_tick_remaining(target_tick, DEFAULT_TICK_RATE, now),
+            expected
+        );
+        assert_eq!(server_tick_remaining(6400, DEFAULT_TICK_RATE, 95.5), 4.5);
+    }
+
+    #[test]
+    fn valid_range() {
+        assert!(validate_server_time(0.0).is_ok());
+        assert!(validate_server_time(86_400.0).is_ok());
+        assert!(validate_server_time(-1.0).is_err());
+        assert!(validate_server_time(f32::NAN).is_err());
+        assert!(validate_server_time(f32::INFINITY).is_err());
+
+        /* long sessions only lose precision */
+        assert!(validate_server_time(SERVER_TIME_MAX * 2.0).is_ok());
+        assert!(is_server_time_precise(86_400.0));
+        assert!(!is_server_time_precise(SERVER_TIME_MAX * 2.0));
+    }
+}
@@ -0,0 +1,704 @@
+use anyhow::Context;
+use cs2_schema_cutl::EntityHandle;
+use cs2_schema_generated::cs2::client::{
+    CEntityIdentity,
+    CGameSceneNode,
+    C_BaseCSGrenadeProjectile,
+    C_BaseEntity,
+    C_BaseGrenade,
+    C_MolotovProjectile,
+    C_SmokeGrenadeProjectile,
+};
+use nalgebra::Vector3;
+use raw_struct::{
+    Copy,
+    Reference,
+};
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use super::{
+    resolve_pawn_owner,
+    ResultSkipExt,
+};
+use crate::{
+    damage::{
+        self,
+        DamageResult,
+    },
+    server_tick_remaining,
+    server_time_remaining,
+    units::DEFAULT_TICK_RATE,
+    CEntityIdentityEx,
+    EntityClassMismatch,
+    EntityIndex,
+    PawnIndex,
+    PlayerPawnState,
+    StateCS2Memory,
+    StateEntityClassIndex,
+    StateEntityList,
+    StateGlobals,
+    StateLocalPlayerController,
+    StatePawnInfo,
+};
+
+/// Gravity applied to grenade projectiles (sv_gravity * 0.4)
+pub const GRENADE_GRAVITY: f32 = 800.0 * 0.4;
+
+/// Height of the players center above the players origin
+const PLAYER_CENTER_HEIGHT: f32 = 36.0;
+
+/// Duration (in seconds) of a deployed smoke
+pub const SMOKE_EFFECT_DURATION: f32 = 20.0;
+
+/// Predict the position of a grenade after `time` seconds.
+///
+/// Without the world geometry bounces can not be predicted.
+/// We assume the grenade does not fall below `floor_height`.
+pub fn predict_grenade_position(
+    position: Vector3<f32>,
+    velocity: Vector3<f32>,
+    time: f32,
+    floor_height: f32,
+) -> Vector3<f32> {
+    let time = time.max(0.0);
+    let mut result = position + velocity * time;
+    result.z -= 0.5 * GRENADE_GRAVITY * time * time;
+    result.z = result.z.max(floor_height.min(position.z));
+    result
+}
+
+#[derive(Debug, Clone)]
+pub struct HeGrenadeProjectile {
+    pub entity_id: EntityIndex,
+    pub thrower_entity_id: Option<PawnIndex>,
+
+    pub position: Vector3<f32>,
+    pub velocity: Vector3<f32>,
+
+    /// Time (in seconds) until the grenade detonates
+    pub time_detonation: f32,
+
+    /// Predicted position of the detonation
+    pub predicted_detonation: Vector3<f32>,
+
+    /// Expected damage to the local player if the grenade detonates at the predicted position
+    pub local_player_damage: Option<DamageResult>,
+
+    /// Expected damage to each teammate of the local player (pawn, damage).
+    /// Players which will not be damaged are omitted.
+    pub teammate_damage: Vec<(PawnIndex, DamageResult)>,
+}
+
+/// All currently flying HE grenades (derived from [StateGrenadeProjectiles]).
+/// Note: Occlusion by world geometry is not taken into account for the damage estimates.
+pub struct StateHeGrenadeProjectiles {
+    pub projectiles: Vec<HeGrenadeProjectile>,
+}
+
+impl State for StateHeGrenadeProjectiles {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let entities = states.resolve::<StateEntityList>(())?;
+        let class_index = states.resolve::<StateEntityClassIndex>(())?;
+
+        /* the projectiles have already been read by the generic grenade scan */
+        let grenade_projectiles = states.resolve::<StateGrenadeProjectiles>(())?;
+        let grenades = grenade_projectiles
+            .projectiles
+            .iter()
+            .filter_map(|projectile| {
+                Some((
+                    projectile,
+                    projectile.velocity?,
+                    projectile.he_detonation.as_ref()?,
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        if grenades.is_empty() {
+            return Ok(Self {
+                projectiles: Vec::new(),
+            });
+        }
+
+        let player_pawns = class_index
+            .entities_of_class(&entities, "C_CSPlayerPawn")
+            .collect::<Vec<_>>();
+
+        let local_pawn_entity_id = states
+            .resolve::<StateLocalPlayerController>(())?
+            .instance
+            .value_reference(memory.view_arc())
+            .map(|controller| controller.m_hPlayerPawn())
+            .transpose()?
+            .and_then(|handle| PawnIndex::from_valid_handle(&handle));
+
+        /* (pawn entity id, team id, player center, armor) */
+        let mut players = Vec::with_capacity(player_pawns.len());
+        let mut local_team_id = None;
+        for entity_identity in player_pawns {
+            if *states.resolve::<PlayerPawnState>(entity_identity.handle()?)?
+                != PlayerPawnState::Alive
+            {
+                continue;
+            }
+
+            let Some(pawn_info) = states
+                .resolve::<StatePawnInfo>(entity_identity.handle()?)
+                .ok_or_skip("player pawn info")
+            else {
+                continue;
+            };
+            if Some(pawn_info.pawn_entity_id) == local_pawn_entity_id {
+                local_team_id = Some(pawn_info.team_id);
+            }
+
+            /* assume no armor if unknown (worst case) */
+            let armor = if pawn_info.economy_confidence.is_available() {
+                pawn_info.player_armor
+            } else {
+                0
+            };
+            players.push((
+                pawn_info.pawn_entity_id,
+                pawn_info.team_id,
+                pawn_info.position + Vector3::new(0.0, 0.0, PLAYER_CENTER_HEIGHT),
+                armor,
+            ));
+        }
+
+        let mut projectiles = Vec::with_capacity(grenades.len());
+        for (projectile, velocity, detonation) in grenades {
+            let predicted_detonation = predict_grenade_position(
+                projectile.position,
+                velocity,
+                detonation.time_detonation,
+                detonation.initial_height,
+            );
+
+            let mut local_player_damage = None;
+            let mut teammate_damage = Vec::new();
+            for (pawn_entity_id, team_id, center, armor) in players.iter() {
+                if Some(*team_id) != local_team_id {
+                    continue;
+                }
+
+                let distance = (center - predicted_detonation).norm();
+                let estimate = damage::explosion_damage(
+                    detonation.damage,
+                    detonation.damage_radius,
+                    distance,
+                    *armor,
+                );
+                if Some(*pawn_entity_id) == local_pawn_entity_id {
+                    local_player_damage = Some(estimate);
+                } else if estimate.hp_damage > 0 {
+                    teammate_damage.push((*pawn_entity_id, estimate));
+                }
+            }
+
+            projectiles.push(HeGrenadeProjectile {
+                entity_id: projectile.entity_id,
+                thrower_entity_id: detonation.thrower_entity_id,
+
+                position: projectile.position,
+                velocity,
+
+                time_detonation: detonation.time_detonation,
+                predicted_detonation,
+
+                local_player_damage,
+                teammate_damage,
+            });
+        }
+
+        Ok(Self { projectiles })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum GrenadeKind {
+    Smoke,
+    Molotov,
+    Incendiary,
+    HighExplosive,
+    Flashbang,
+    Decoy,
+}
+
+impl GrenadeKind {
+    /// Entity classes of the grenade projectiles.
+    /// Incendiary grenades share the class with molotovs.
+    pub const PROJECTILE_CLASSES: [(&'static str, Self); 5] = [
+        ("C_SmokeGrenadeProjectile", Self::Smoke),
+        ("C_MolotovProjectile", Self::Molotov),
+        ("C_HEGrenadeProjectile", Self::HighExplosive),
+        ("C_FlashbangProjectile", Self::Flashbang),
+        ("C_DecoyProjectile", Self::Decoy),
+    ];
+}
+
+/// Time (in seconds) until a smoke deployed at `effect_tick_begin` disappears
+pub fn smoke_effect_remaining(effect_tick_begin: i32, server_time: f32) -> f32 {
+    (server_tick_remaining(effect_tick_begin, DEFAULT_TICK_RATE, server_time)
+        + SMOKE_EFFECT_DURATION)
+        .max(0.0)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrenadeOwner {
+    pub pawn_entity_id: PawnIndex,
+    pub player_name: Option<String>,
+    pub team_id: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct GrenadeProjectile {
+    pub entity_id: EntityIndex,
+    pub kind: GrenadeKind,
+
+    pub position: Vector3<f32>,
+
+    /// None if the velocity could not be read
+    pub velocity: Option<Vector3<f32>>,
+
+    /// None if the owner handle is invalid or the owner does not exist (e.g. disconnected)
+    pub owner: Option<GrenadeOwner>,
+
+    /// The smoke has been deployed (smokes only)
+    pub smoke_detonated: bool,
+
+    /// Time (in seconds) until the deployed smoke disappears (see [SMOKE_EFFECT_DURATION]).
+    /// Only available for deployed smokes.
+    pub effect_time_remaining: Option<f32>,
+
+    /// Only available for HE grenades.
+    /// None if the detonation parameters could not be read.
+    pub he_detonation: Option<HeGrenadeDetonation>,
+}
+
+/// Detonation parameters of a flying HE grenade
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeGrenadeDetonation {
+    pub thrower_entity_id: Option<PawnIndex>,
+
+    /// Time (in seconds) until the grenade detonates
+    pub time_detonation: f32,
+
+    /// Height the grenade has been thrown from.
+    /// The grenade is assumed not to fall below this height.
+    pub initial_height: f32,
+
+    pub damage: f32,
+    pub damage_radius: f32,
+}
+
+/// All grenade projectiles of the kinds listed in [GrenadeKind::PROJECTILE_CLASSES]
+pub struct StateGrenadeProjectiles {
+    pub projectiles: Vec<GrenadeProjectile>,
+}
+
+impl StateGrenadeProjectiles {
+    fn read_owner(
+        states: &StateRegistry,
+        owner_handle: &EntityHandle<dyn C_BaseEntity>,
+    ) -> anyhow::Result<Option<GrenadeOwner>> {
+        if !owner_handle.is_valid() {
+            return Ok(None);
+        }
+
+        let owner = match resolve_pawn_owner(states, owner_handle) {
+            Ok(owner) => owner,
+            Err(error) if error.is::<EntityClassMismatch>() => {
+                /* the grenade has not been thrown by a player */
+                log::trace!("Ignoring grenade owner: {:#}", error);
+                None
+            }
+            Err(error) => return Err(error),
+        };
+
+        Ok(owner.map(|(player_name, team_id)| GrenadeOwner {
+            pawn_entity_id: PawnIndex::from_handle(owner_handle),
+            player_name,
+            team_id,
+        }))
+    }
+
+    fn read_he_detonation(
+        globals: &StateGlobals,
+        projectile: &Reference<dyn C_BaseCSGrenadeProjectile>,
+    ) -> anyhow::Result<HeGrenadeDetonation> {
+        let damage_radius = match projectile.m_DmgRadius()? {
+            radius if radius > 0.0 => radius,
+            _ => damage::HE_GRENADE_RADIUS,
+        };
+        let damage = match projectile.m_flDamage()? {
+            damage if damage > 0.0 => damage,
+            _ => damage::HE_GRENADE_DAMAGE,
+        };
+
+        Ok(HeGrenadeDetonation {
+            thrower_entity_id: PawnIndex::from_valid_handle(&projectile.m_hThrower()?),
+            time_detonation: server_time_remaining(
+                projectile.m_flDetonateTime()?.m_Value()?,
+                globals.server_time()?,
+            ),
+            initial_height: projectile.m_vInitialPosition()?[2],
+
+            damage,
+            damage_radius,
+        })
+    }
+
+    fn read_projectile(
+        states: &StateRegistry,
+        memory: &StateCS2Memory,
+        globals: &StateGlobals,
+        entity_identity: &Copy<dyn CEntityIdentity>,
+        kind: GrenadeKind,
+    ) -> anyhow::Result<GrenadeProjectile> {
+        let projectile = entity_identity
+            .entity_ptr::<dyn C_BaseCSGrenadeProjectile>()?
+            .value_reference(memory.view_arc())
+            .context("grenade projectile nullptr")?;
+
+        let position = Vector3::from_column_slice(
+            &projectile
+                .m_pGameSceneNode()?
+                .value_reference(memory.view_arc())
+                .context("m_pGameSceneNode nullptr")?
+                .m_vecAbsOrigin()?,
+        );
+
+        let kind = match kind {
+            GrenadeKind::Molotov
+                if projectile
+                    .cast::<dyn C_MolotovProjectile>()
+                    .m_bIsIncGrenade()? =>
+            {
+                GrenadeKind::Incendiary
+            }
+            kind => kind,
+        };
+
+        let (smoke_detonated, effect_time_remaining) = if kind == GrenadeKind::Smoke {
+            let smoke = projectile.cast::<dyn C_SmokeGrenadeProjectile>();
+            if smoke.m_bDidSmokeEffect()? {
+                (
+                    true,
+                    Some(smoke_effect_remaining(
+                        smoke.m_nSmokeEffectTickBegin()?,
+                        globals.server_time()?,
+                    )),
+                )
+            } else {
+                (false, None)
+            }
+        } else {
+            (false, None)
+        };
+
+        let he_detonation = if kind == GrenadeKind::HighExplosive {
+            Self::read_he_detonation(globals, &projectile).ok_or_skip("HE grenade detonation")
+        } else {
+            None
+        };
+
+        Ok(GrenadeProjectile {
+            entity_id: EntityIndex::from_handle(&entity_identity.handle::<()>()?),
+            kind,
+
+            position,
+            velocity: projectile
+                .m_vecAbsVelocity()
+                .ok()
+                .map(|velocity| Vector3::from_column_slice(&velocity)),
+
+            owner: Self::read_owner(states, &projectile.m_hOwnerEntity()?)
+                .ok_or_skip("grenade owner")
+                .flatten(),
+
+            smoke_detonated,
+            effect_time_remaining,
+            he_detonation,
+        })
+    }
+}
+
+impl State for StateGrenadeProjectiles {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let globals = states.resolve::<StateGlobals>(())?;
+        let entities = states.resolve::<StateEntityList>(())?;
+        let class_index = states.resolve::<StateEntityClassIndex>(())?;
+
+        let mut projectiles = Vec::new();
+        for (class_name, kind) in GrenadeKind::PROJECTILE_CLASSES {
+            for entity_identity in class_index.entities_of_class(&entities, class_name) {
+                /* the projectile may be removed while being read (e.g. the grenade detonated) */
+                let Some(projectile) =
+                    Self::read_projectile(states, &memory, &globals, entity_identity, kind)
+                        .ok_or_skip("grenade projectile")
+                else {
+                    continue;
+                };
+
+                projectiles.push(projectile);
+            }
+        }
+
+        Ok(Self { projectiles })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector3;
+    use utils_state::State;
+
+    use super::{
+        smoke_effect_remaining,
+        GrenadeKind,
+        GrenadeOwner,
+        HeGrenadeDetonation,
+        StateGrenadeProjectiles,
+        SMOKE_EFFECT_DURATION,
+    };
+    use crate::{
+        damage,
+        test_fixture::{
+            field_offset,
+            setup_dump_schema,
+            write,
+            EntityFixture,
+        },
+        EntityIndex,
+        PawnIndex,
+    };
+
+    const OWNER_PAWN_HANDLE: u32 = 0x8001;
+    const OWNER_CONTROLLER_HANDLE: u32 = 0x8002;
+    const CHICKEN_HANDLE: u32 = 0x8003;
+
+    /// (class name, incendiary, smoke effect tick begin, owner handle)
+    const PROJECTILES: [(&str, bool, Option<i32>, u32); 7] = [
+        (
+            "C_SmokeGrenadeProjectile",
+            false,
+            Some(6400),
+            OWNER_PAWN_HANDLE,
+        ),
+        ("C_SmokeGrenadeProjectile", false, None, OWNER_PAWN_HANDLE),
+        ("C_MolotovProjectile", false, None, OWNER_PAWN_HANDLE),
+        ("C_MolotovProjectile", true, None, OWNER_PAWN_HANDLE),
+        ("C_HEGrenadeProjectile", false, None, OWNER_PAWN_HANDLE),
+        ("C_FlashbangProjectile", false, None, OWNER_PAWN_HANDLE),
+        /* thrown by a bot which has been replaced by a chicken */
+        ("C_DecoyProjectile", false, None, CHICKEN_HANDLE),
+    ];
+
+    fn projectile_handle(index: usize) -> u32 {
+        0x8010 + index as u32
+    }
+
+    /// Entities of all [PROJECTILES] thrown by a single player and one grenade being removed
+    fn grenade_fixture() -> EntityFixture {
+        let mut fixture = EntityFixture::default();
+        fixture.record_globals(105.0);
+
+        fixture.push_entity(OWNER_PAWN_HANDLE, "C_CSPlayerPawn", 0x10_0000);
+        let mut pawn = vec![0u8; 0x4000];
+        write(&mut pawn, field_offset("C_BaseEntity", "m_iTeamNum"), &[3]);
+        write(
+            &mut pawn,
+            field_offset("C_BasePlayerPawn", "m_hController"),
+            &OWNER_CONTROLLER_HANDLE.to_le_bytes(),
+        );
+        fixture.record(0x10_0000, &pawn);
+
+        fixture.push_entity(OWNER_CONTROLLER_HANDLE, "CCSPlayerController", 0x11_0000);
+        let mut controller = vec![0u8; 0x1000];
+        write(
+            &mut controller,
+            field_offset("CBasePlayerController", "m_iszPlayerName"),
+            b"Alice\0",
+        );
+        fixture.record(0x11_0000, &controller);
+
+        fixture.push_entity(CHICKEN_HANDLE, "C_Chicken", 0x12_0000);
+        fixture.record(0x12_0000, &vec![0u8; 0x1000]);
+
+        for (index, (class_name, incendiary, smoke_effect, owner)) in
+            PROJECTILES.into_iter().enumerate()
+        {
+            let address = 0x20_0000 + index as u64 * 0x1_0000;
+            let scene_node_address = address + 0x8000;
+            fixture.push_entity(projectile_handle(index), class_name, address);
+
+            let mut projectile = vec![0u8; 0x2000];
+            write(
+                &mut projectile,
+                field_offset("C_BaseEntity", "m_pGameSceneNode"),
+                &scene_node_address.to_le_bytes(),
+            );
+            write(
+                &mut projectile,
+                field_offset("C_BaseEntity", "m_hOwnerEntity"),
+                &owner.to_le_bytes(),
+            );
+            write(
+                &mut projectile,
+                field_offset("C_BaseEntity", "m_vecAbsVelocity"),
+                &250.0f32.to_le_bytes(),
+            );
+            write(
+                &mut projectile,
+                field_offset("C_MolotovProjectile", "m_bIsIncGrenade"),
+                &[incendiary as u8],
+            );
+            write(
+                &mut projectile,
+                field_offset("C_BaseGrenade", "m_hThrower"),
+                &owner.to_le_bytes(),
+            );
+            write(
+                &mut projectile,
+                field_offset("C_BaseGrenade", "m_flDetonateTime"),
+                &106.5f32.to_le_bytes(),
+            );
+            write(
+                &mut projectile,
+                field_offset("C_BaseCSGrenadeProjectile", "m_vInitialPosition") + 0x08,
+                &64.0f32.to_le_bytes(),
+            );
+            if let Some(tick_begin) = smoke_effect {
+                write(
+                    &mut projectile,
+                    field_offset("C_SmokeGrenadeProjectile", "m_bDidSmokeEffect"),
+                    &[1],
+                );
+                write(
+                    &mut projectile,
+                    field_offset("C_SmokeGrenadeProjectile", "m_nSmokeEffectTickBegin"),
+                    &tick_begin.to_le_bytes(),
+                );
+            }
+            fixture.record(address, &projectile);
+
+            let mut scene_node = vec![0u8; 0x200];
+            write(
+                &mut scene_node,
+                field_offset("CGameSceneNode", "m_vecAbsOrigin"),
+                &(index as f32).to_le_bytes(),
+            );
+            fixture.record(scene_node_address, &scene_node);
+        }
+
+        /* the grenade detonated and its entity has already been freed */
+        fixture.push_entity(
+            projectile_handle(PROJECTILES.len()),
+            "C_HEGrenadeProjectile",
+            0x30_0000,
+        );
+
+        fixture
+    }
+
+    #[test]
+    fn projectiles() {
+        setup_dump_schema();
+
+        let states = grenade_fixture().into_states();
+        let state = StateGrenadeProjectiles::create(&states, ()).unwrap();
+
+        let owner = GrenadeOwner {
+            pawn_entity_id: PawnIndex(OWNER_PAWN_HANDLE & 0x7FFF),
+            player_name: Some("Alice".to_string()),
+            team_id: 3,
+        };
+        let expected = [
+            (GrenadeKind::Smoke, true, Some(SMOKE_EFFECT_DURATION - 5.0)),
+            (GrenadeKind::Smoke, false, None),
+            (GrenadeKind::Molotov, false, None),
+            (GrenadeKind::Incendiary, false, None),
+            (GrenadeKind::HighExplosive, false, None),
+            (GrenadeKind::Flashbang, false, None),
+            (GrenadeKind::Decoy, false, None),
+        ];
+
+        /* the removed grenade has been skipped */
+        assert_eq!(state.projectiles.len(), expected.len());
+        for (index, (kind, smoke_detonated, effect_time_remaining)) in
+            expected.into_iter().enumerate()
+        {
+            let projectile = state
+                .projectiles
+                .iter()
+                .find(|projectile| {
+                    projectile.entity_id == EntityIndex(projectile_handle(index) & 0x7FFF)
+                })
+                .unwrap_or_else(|| panic!("missing projectile {}", index));
+
+            assert_eq!(projectile.kind, kind, "{}", index);
+            assert_eq!(projectile.position, Vector3::new(index as f32, 0.0, 0.0));
+            assert_eq!(projectile.velocity, Some(Vector3::new(250.0, 0.0, 0.0)));
+            assert_eq!(projectile.smoke_detonated, smoke_detonated, "{}", index);
+            assert_eq!(
+                projectile.effect_time_remaining, effect_time_remaining,
+                "{}",
+                index
+            );
+
+            if kind == GrenadeKind::HighExplosive {
+                /* the damage and radius are not set, the defaults will be used */
+                assert_eq!(
+                    projectile.he_detonation,
+                    Some(HeGrenadeDetonation {
+                        thrower_entity_id: Some(owner.pawn_entity_id),
+                        time_detonation: 1.5,
+                        initial_height: 64.0,
+                        damage: damage::HE_GRENADE_DAMAGE,
+                        damage_radius: damage::HE_GRENADE_RADIUS,
+                    })
+                );
+            } else {
+                assert_eq!(projectile.he_detonation, None, "{}", index);
+            }
+
+            if kind == GrenadeKind::Decoy {
+                /* the owner is not a player */
+                assert_eq!(projectile.owner, None);
+            } else {
+                assert_eq!(projectile.owner.as_ref(), Some(&owner), "{}", index);
+            }
+        }
+    }
+
+    #[test]
+    fn smoke_timer() {
+        /* deployed at tick 6400 (server time 100.0) */
+        assert_eq!(smoke_effect_remaining(6400, 100.0), SMOKE_EFFECT_DURATION);
+        assert_eq!(
+            smoke_effect_remaining(6400, 105.0),
+            SMOKE_EFFECT_DURATION - 5.0
+        );
+        assert_eq!(smoke_effect_remaining(6400, 200.0), 0.0);
+    }
+}
@@ -0,0 +1,225 @@
+use anyhow::Context;
+use cs2_schema_generated::cs2::client::{
+    CCSPlayerController,
+    CCSPlayerController_ActionTrackingServices,
+    C_CSGameRules,
+    C_CSPlayerPawn,
+};
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    CEntityIdentityEx,
+    ClassNameCache,
+    PawnIndex,
+    StateCS2Memory,
+    StateEntityList,
+    StateGameRules,
+    StateLocalPlayerController,
+};
+
+/// Derives the damage dealt within a frame by diffing the total damage of the current round.
+#[derive(Debug, Clone, Default)]
+pub struct RoundDamageTracker {
+    /// (round, total damage dealt within the round)
+    last_sample: Option<(Option<i32>, u32)>,
+}
+
+impl RoundDamageTracker {
+    /// Push the total damage dealt within `round`.
+    /// Returns the damage dealt since the last sample or None if no damage has been dealt.
+    pub fn push_sample(&mut self, round: Option<i32>, total_damage: u32) -> Option<u32> {
+        let previous = match self.last_sample.replace((round, total_damage)) {
+            /* the stats got reset */
+            Some((_, previous_damage)) if total_damage < previous_damage => 0,
+
+            Some((previous_round, previous_damage)) if previous_round == round => previous_damage,
+
+            /*
+             * The round changed but the total has not been reset yet.
+             * The total still belongs to the previous round.
+             */
+            Some(_) => total_damage,
+
+            /* damage dealt before the first sample is not attributed to this frame */
+            None => total_damage,
+        };
+
+        Some(total_damage - previous).filter(|damage| *damage > 0)
+    }
+
+    pub fn reset(&mut self) {
+        self.last_sample = None;
+    }
+}
+
+/// Damage dealt by the local player within the last frame (e.g. for a hit marker).
+/// Note: Must be resolved every frame as the damage is accumulated between two updates.
+pub struct StateLocalHitFeedback {
+    tracker: RoundDamageTracker,
+
+    /// Damage dealt since the last frame.
+    /// None if no damage has been dealt.
+    pub dealt_damage_last_frame: Option<u32>,
+
+    /// Player pawn under the crosshair while the damage has been dealt
+    pub victim_pawn_entity_id: Option<PawnIndex>,
+}
+
+impl StateLocalHitFeedback {
+    /// (round, total damage dealt this round)
+    fn read_round_damage(states: &StateRegistry) -> anyhow::Result<Option<(Option<i32>, u32)>> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let local_controller = states.resolve::<StateLocalPlayerController>(())?;
+        let Some(local_controller) = local_controller.instance.value_reference(memory.view_arc())
+        else {
+            return Ok(None);
+        };
+
+        let total_damage = local_controller
+            .m_pActionTrackingServices()?
+            .value_reference(memory.view_arc())
+            .context("m_pActionTrackingServices nullptr")?
+            .m_flTotalRoundDamageDealt()?;
+
+        let round = states
+            .resolve::<StateGameRules>(())?
+            .rules
+            .as_ref()
+            .map(|rules| rules.m_totalRoundsPlayed())
+            .transpose()?;
+
+        Ok(Some((round, total_damage.max(0.0).round() as u32)))
+    }
+
+    fn read_crosshair_pawn(states: &StateRegistry) -> anyhow::Result<Option<PawnIndex>> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let entities = states.resolve::<StateEntityList>(())?;
+        let class_name_cache = states.resolve::<ClassNameCache>(())?;
+        let local_controller = states.resolve::<StateLocalPlayerController>(())?;
+        let Some(local_controller) = local_controller.instance.value_reference(memory.view_arc())
+        else {
+            return Ok(None);
+        };
+
+        let Some(local_pawn) = entities.entity_from_handle(&local_controller.m_hPlayerPawn()?)
+        else {
+            return Ok(None);
+        };
+
+        let target_entity_id = local_pawn
+            .value_reference(memory.view_arc())
+            .context("local pawn nullptr")?
+            .m_iIDEntIndex()?;
+
+        let Some(target) = entities.identity_from_index(target_entity_id) else {
+            return Ok(None);
+        };
+
+        let class_name = class_name_cache.lookup(&target.entity_class_info()?)?;
+        if class_name
+            .map(|name| name == "C_CSPlayerPawn")
+            .unwrap_or(false)
+        {
+            Ok(Some(PawnIndex(target_entity_id)))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl State for StateLocalHitFeedback {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            tracker: Default::default(),
+            dealt_damage_last_frame: None,
+            victim_pawn_entity_id: None,
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        self.dealt_damage_last_frame = None;
+        self.victim_pawn_entity_id = None;
+
+        let Some((round, total_damage)) = Self::read_round_damage(states)? else {
+            /* not connected */
+            self.tracker.reset();
+            return Ok(());
+        };
+
+        self.dealt_damage_last_frame = self.tracker.push_sample(round, total_damage);
+        if self.dealt_damage_last_frame.is_some() {
+            self.victim_pawn_entity_id = Self::read_crosshair_pawn(states).ok().flatten();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RoundDamageTracker;
+
+    #[test]
+    fn multi_hit_frames() {
+        let mut tracker = RoundDamageTracker::default();
+        let round = Some(3);
+
+        /* (total round damage, expected damage of the frame) */
+        let samples = [
+            (0, None),
+            (0, None),
+            /* three shotgun pellets hit within the same frame */
+            (26 * 3, Some(78)),
+            (78, None),
+            /* two more pellets */
+            (78 + 19 * 2, Some(38)),
+            (116, None),
+            (120, Some(4)),
+        ];
+
+        for (index, (total_damage, expected)) in samples.into_iter().enumerate() {
+            assert_eq!(
+                tracker.push_sample(round, total_damage),
+                expected,
+                "sample {}",
+                index
+            );
+        }
+    }
+
+    #[test]
+    fn round_start() {
+        let mut tracker = RoundDamageTracker::default();
+
+        /* damage before the first sample is not a hit of this frame */
+        assert_eq!(tracker.push_sample(Some(1), 55), None);
+        assert_eq!(tracker.push_sample(Some(1), 100), Some(45));
+
+        /* new round, stats have been reset */
+        assert_eq!(tracker.push_sample(Some(2), 0), None);
+        assert_eq!(tracker.push_sample(Some(2), 27), Some(27));
+
+        /* round number not updated yet but the stats already got reset */
+        assert_eq!(tracker.push_sample(Some(2), 0), None);
+        assert_eq!(tracker.push_sample(Some(3), 0), None);
+        assert_eq!(tracker.push_sample(Some(3), 12), Some(12));
+
+        /* round number updated one frame before the stats get reset */
+        assert_eq!(tracker.push_sample(Some(4), 12), None);
+        assert_eq!(tracker.push_sample(Some(4), 0), None);
+        assert_eq!(tracker.push_sample(Some(4), 31), Some(31));
+
+        /* round number and stats updated within the same frame */
+        assert_eq!(tracker.push_sample(Some(5), 9), Some(9));
+    }
+}
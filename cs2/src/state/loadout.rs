@@ -0,0 +1,552 @@
+use std::collections::{
+    BTreeMap,
+    HashSet,
+};
+
+use anyhow::Context;
+use cs2_schema_generated::cs2::client::{
+    CBasePlayerController,
+    CCSPlayerController,
+    CCSPlayerController_InGameMoneyServices,
+    CPlayer_WeaponServices,
+    C_BasePlayerPawn,
+    C_EconEntity,
+};
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use super::{
+    MatchEvent,
+    StateMatchEvents,
+};
+use crate::{
+    ControllerIndex,
+    EntityIndex,
+    StateCS2Memory,
+    StateEntityList,
+    StatePlayerControllers,
+    WeaponId,
+};
+
+/// Identity of a player which remains stable across rounds.
+/// Bots do not have a steam id and are identified by their controller entity id instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PlayerKey {
+    Player { steam_id: u64 },
+    Bot { entity_index: ControllerIndex },
+}
+
+impl PlayerKey {
+    pub fn new(steam_id: u64, entity_index: ControllerIndex) -> Self {
+        if steam_id == 0 {
+            Self::Bot { entity_index }
+        } else {
+            Self::Player { steam_id }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadoutItem {
+    pub weapon: WeaponId,
+
+    /// Entity index and serial number of the weapon entity.
+    /// Identifies the individual weapon while it is being dropped and picked up.
+    pub entity: (EntityIndex, u32),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerLoadout {
+    /// Money of the player.
+    /// None if the money services could not be read.
+    pub money: Option<i32>,
+    pub items: Vec<LoadoutItem>,
+}
+
+/// Loadouts of all players at a specific point of time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoadoutSnapshot {
+    pub players: BTreeMap<PlayerKey, PlayerLoadout>,
+}
+
+impl LoadoutSnapshot {
+    pub fn read(states: &StateRegistry) -> anyhow::Result<Self> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let entities = states.resolve::<StateEntityList>(())?;
+        let controllers = states.resolve::<StatePlayerControllers>(())?;
+
+        let mut players = BTreeMap::new();
+        for entry in controllers.instances.iter() {
+            let Some(controller) = entry.instance.value_reference(memory.view_arc()) else {
+                continue;
+            };
+
+            let money = controller
+                .m_pInGameMoneyServices()?
+                .value_reference(memory.view_arc())
+                .map(|money_services| money_services.m_iAccount())
+                .transpose()?;
+
+            let mut items = Vec::new();
+            if let Some(pawn) = entities
+                .entity_from_handle(&controller.m_hPlayerPawn()?)
+                .and_then(|pawn| pawn.value_reference(memory.view_arc()))
+            {
+                let weapon_services = pawn
+                    .m_pWeaponServices()?
+                    .value_reference(memory.view_arc())
+                    .context("m_pWeaponServices nullptr")?;
+
+                let weapons = weapon_services.m_hMyWeapons()?;
+                for handle in weapons
+                    .data()?
+                    .elements(memory.view(), 0..weapons.size()? as usize)?
+                {
+                    let Some(weapon) = entities
+                        .entity_from_handle(&handle)
+                        .and_then(|weapon| weapon.value_reference(memory.view_arc()))
+                    else {
+                        continue;
+                    };
+
+                    let weapon_id = weapon
+                        .cast::<dyn C_EconEntity>()
+                        .m_AttributeManager()?
+                        .m_Item()?
+                        .m_iItemDefinitionIndex()?;
+
+                    items.push(LoadoutItem {
+                        weapon: WeaponId::from_id(weapon_id).unwrap_or(WeaponId::Unknown),
+                        entity: (
+                            EntityIndex::from_handle(&handle),
+                            handle.get_serial_number(),
+                        ),
+                    });
+                }
+            }
+
+            players.insert(
+                PlayerKey::new(controller.m_steamID()?, entry.entity_index),
+                PlayerLoadout { money, items },
+            );
+        }
+
+        Ok(Self { players })
+    }
+
+    /// Add the items of a later snapshot and take over its money.
+    /// Players which are not contained within this snapshot will be added.
+    pub fn merge(&mut self, other: LoadoutSnapshot) {
+        for (player, loadout) in other.players {
+            let target = self.players.entry(player).or_default();
+            if loadout.money.is_some() {
+                target.money = loadout.money;
+            }
+
+            for item in loadout.items {
+                if !target.items.contains(&item) {
+                    target.items.push(item);
+                }
+            }
+        }
+    }
+
+    fn weapon_entities(&self) -> HashSet<(EntityIndex, u32)> {
+        self.players
+            .values()
+            .flat_map(|loadout| loadout.items.iter().map(|item| item.entity))
+            .collect()
+    }
+}
+
+/// Items a player bought within the freeze time
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoughtThisRound {
+    pub player: PlayerKey,
+    pub items: Vec<WeaponId>,
+
+    /// Money spent on the items according to the weapon price table (see [WeaponId::price]).
+    /// Armor and defuse kits are not included.
+    pub spent_estimate: i32,
+}
+
+/// Diff the loadouts of the previous round against the loadouts at the end of the freeze time.
+///
+/// Weapons which have been owned by any player within the previous round
+/// (including weapons dropped and picked up) do not count as bought.
+/// If the money delta of a player is known, only items which are covered by it count as bought,
+/// as new weapons which exceed the money delta have been dropped by a teammate.
+pub fn diff_loadouts(
+    previous: &LoadoutSnapshot,
+    current: &LoadoutSnapshot,
+) -> Vec<BoughtThisRound> {
+    let known_entities = previous.weapon_entities();
+
+    let mut result = Vec::new();
+    for (player, loadout) in current.players.iter() {
+        let mut candidates = loadout
+            .items
+            .iter()
+            .filter(|item| !known_entities.contains(&item.entity))
+            .filter_map(|item| Some((item.weapon, item.weapon.price()?)))
+            .collect::<Vec<_>>();
+
+        let money_delta = previous
+            .players
+            .get(player)
+            .and_then(|previous| previous.money)
+            .zip(loadout.money)
+            .map(|(previous, current)| (previous - current).max(0));
+
+        if let Some(money_delta) = money_delta {
+            /* cover the most expensive items first */
+            candidates.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+            let mut remaining = money_delta;
+            candidates.retain(|(_, price)| {
+                if *price > remaining {
+                    return false;
+                }
+
+                remaining -= price;
+                true
+            });
+        }
+
+        if candidates.is_empty() {
+            continue;
+        }
+
+        result.push(BoughtThisRound {
+            player: *player,
+            spent_estimate: candidates.iter().map(|(_, price)| price).sum(),
+            items: candidates.into_iter().map(|(weapon, _)| weapon).collect(),
+        });
+    }
+
+    result
+}
+
+/// Takes the loadout snapshots at the round transitions and diffs them at the end of the freeze time.
+///
+/// The snapshot of the previous round will be taken when the round ends
+/// and extended by the items and money of all players when the freeze time starts
+/// (weapons handed out at the round start must not count as bought).
+#[derive(Debug, Clone, Default)]
+pub struct RoundLoadouts {
+    /// Round number (starting with 1) of the latest purchases
+    pub round_number: Option<i32>,
+    pub purchases: Vec<BoughtThisRound>,
+
+    previous: Option<LoadoutSnapshot>,
+    freeze_period: Option<bool>,
+}
+
+impl RoundLoadouts {
+    /// Apply the events and the freeze period of a frame.
+    /// The loadouts will only be read if a snapshot is required.
+    pub fn push_events(
+        &mut self,
+        events: &[MatchEvent],
+        freeze_period: Option<bool>,
+        mut read: impl FnMut() -> anyhow::Result<LoadoutSnapshot>,
+    ) -> anyhow::Result<()> {
+        for event in events {
+            match event {
+                MatchEvent::RoundEnd { .. } => {
+                    self.previous = Some(read()?);
+                }
+                MatchEvent::RoundStart { round_number, .. } => {
+                    let Some(previous) = &self.previous else {
+                        continue;
+                    };
+
+                    self.purchases = diff_loadouts(previous, &read()?);
+                    self.round_number = Some(*round_number);
+                }
+                _ => {}
+            }
+        }
+
+        if self.freeze_period == Some(false) && freeze_period == Some(true) {
+            let snapshot = read()?;
+            match &mut self.previous {
+                Some(previous) => previous.merge(snapshot),
+                None => self.previous = Some(snapshot),
+            }
+        }
+
+        self.freeze_period = freeze_period;
+        Ok(())
+    }
+}
+
+/// Items bought by each player within the last freeze time.
+/// The loadouts will only be tracked while this state is being resolved every frame.
+pub struct StateRoundLoadouts {
+    pub loadouts: RoundLoadouts,
+}
+
+impl State for StateRoundLoadouts {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            loadouts: Default::default(),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        let events = states.resolve::<StateMatchEvents>(())?;
+        let freeze_period = events
+            .snapshot()
+            .and_then(|snapshot| snapshot.freeze_period);
+
+        self.loadouts
+            .push_events(&events.events, freeze_period, || {
+                LoadoutSnapshot::read(states)
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::{
+        diff_loadouts,
+        BoughtThisRound,
+        LoadoutItem,
+        LoadoutSnapshot,
+        PlayerKey,
+        PlayerLoadout,
+        RoundLoadouts,
+    };
+    use crate::{
+        ControllerIndex,
+        EntityIndex,
+        MatchEvent,
+        WeaponId,
+    };
+
+    const ALICE: PlayerKey = PlayerKey::Player {
+        steam_id: 76561198000000001,
+    };
+    const BOB: PlayerKey = PlayerKey::Bot {
+        entity_index: ControllerIndex(2),
+    };
+
+    fn item(weapon: WeaponId, entity_index: u32) -> LoadoutItem {
+        LoadoutItem {
+            weapon,
+            entity: (EntityIndex(entity_index), 1),
+        }
+    }
+
+    fn snapshot(players: &[(PlayerKey, i32, &[LoadoutItem])]) -> LoadoutSnapshot {
+        LoadoutSnapshot {
+            players: players
+                .iter()
+                .map(|(player, money, items)| {
+                    (
+                        *player,
+                        PlayerLoadout {
+                            money: Some(*money),
+                            items: items.to_vec(),
+                        },
+                    )
+                })
+                .collect::<BTreeMap<_, _>>(),
+        }
+    }
+
+    fn round_event(round_number: i32, end: bool) -> MatchEvent {
+        if end {
+            MatchEvent::RoundEnd {
+                round_number,
+                score_terrorists: None,
+                score_counter_terrorists: None,
+            }
+        } else {
+            MatchEvent::RoundStart {
+                round_number,
+                score_terrorists: None,
+                score_counter_terrorists: None,
+            }
+        }
+    }
+
+    #[test]
+    fn key() {
+        assert_eq!(PlayerKey::new(76561198000000001, ControllerIndex(1)), ALICE);
+        assert_eq!(PlayerKey::new(0, ControllerIndex(2)), BOB);
+    }
+
+    #[test]
+    fn picked_up_weapons() {
+        /* bob picked up the ak of alice which died last round */
+        let previous = snapshot(&[
+            (ALICE, 1400, &[item(WeaponId::Glock, 100)]),
+            (BOB, 4000, &[item(WeaponId::Glock, 102)]),
+        ]);
+        let mut previous_round = snapshot(&[(ALICE, 0, &[item(WeaponId::Ak47, 101)])]);
+        previous_round.merge(previous);
+
+        let current = snapshot(&[
+            (ALICE, 1200, &[item(WeaponId::Glock, 100)]),
+            (
+                BOB,
+                3700,
+                &[
+                    item(WeaponId::Glock, 102),
+                    item(WeaponId::Ak47, 101),
+                    item(WeaponId::HZgrenade, 103),
+                ],
+            ),
+        ]);
+
+        assert_eq!(
+            diff_loadouts(&previous_round, &current),
+            vec![BoughtThisRound {
+                player: BOB,
+                items: vec![WeaponId::HZgrenade],
+                spent_estimate: 300,
+            }]
+        );
+    }
+
+    #[test]
+    fn dropped_by_teammate() {
+        let previous = snapshot(&[
+            (ALICE, 6000, &[item(WeaponId::USPS, 100)]),
+            (BOB, 1000, &[item(WeaponId::HKP200, 101)]),
+        ]);
+
+        /* alice bought an awp for bob */
+        let current = snapshot(&[
+            (
+                ALICE,
+                0,
+                &[item(WeaponId::USPS, 100), item(WeaponId::M4A1Silencer, 102)],
+            ),
+            (
+                BOB,
+                800,
+                &[
+                    item(WeaponId::HKP200, 101),
+                    item(WeaponId::Flashbang, 104),
+                    item(WeaponId::AWP, 103),
+                ],
+            ),
+        ]);
+
+        let purchases = diff_loadouts(&previous, &current);
+        assert_eq!(purchases[0].player, ALICE);
+        assert_eq!(purchases[0].items, vec![WeaponId::M4A1Silencer]);
+        assert_eq!(purchases[0].spent_estimate, 2900);
+
+        assert_eq!(purchases[1].player, BOB);
+        assert_eq!(purchases[1].items, vec![WeaponId::Flashbang]);
+        assert_eq!(purchases[1].spent_estimate, 200);
+    }
+
+    #[test]
+    fn two_rounds() {
+        let mut loadouts = RoundLoadouts::default();
+        let mut push = |events: &[MatchEvent], freeze_period: bool, snapshot: &LoadoutSnapshot| {
+            loadouts
+                .push_events(events, Some(freeze_period), || Ok(snapshot.clone()))
+                .unwrap();
+            (loadouts.round_number, loadouts.purchases.clone())
+        };
+
+        /* pistol round */
+        let pistols = snapshot(&[
+            (ALICE, 800, &[item(WeaponId::Glock, 100)]),
+            (BOB, 800, &[item(WeaponId::HKP200, 101)]),
+        ]);
+        let freeze_end = snapshot(&[
+            (
+                ALICE,
+                0,
+                &[item(WeaponId::Glock, 100), item(WeaponId::P250, 102)],
+            ),
+            (BOB, 800, &[item(WeaponId::HKP200, 101)]),
+        ]);
+        push(&[], false, &pistols);
+        push(&[], true, &pistols);
+        assert_eq!(
+            push(&[round_event(1, false)], false, &freeze_end),
+            (
+                Some(1),
+                vec![BoughtThisRound {
+                    player: ALICE,
+                    items: vec![WeaponId::P250],
+                    spent_estimate: 300,
+                }]
+            )
+        );
+
+        /* alice survived, bob died and receives a new pistol */
+        let round_end = snapshot(&[
+            (
+                ALICE,
+                3300,
+                &[item(WeaponId::Glock, 100), item(WeaponId::P250, 102)],
+            ),
+            (BOB, 200, &[]),
+        ]);
+        let freeze_start = snapshot(&[
+            (
+                ALICE,
+                6550,
+                &[item(WeaponId::Glock, 100), item(WeaponId::P250, 102)],
+            ),
+            (BOB, 2100, &[item(WeaponId::HKP200, 103)]),
+        ]);
+        let freeze_end = snapshot(&[
+            (
+                ALICE,
+                1050,
+                &[
+                    item(WeaponId::Glock, 100),
+                    item(WeaponId::P250, 102),
+                    item(WeaponId::Ak47, 104),
+                    item(WeaponId::Smokegrenade, 105),
+                ],
+            ),
+            (
+                BOB,
+                1800,
+                &[item(WeaponId::HKP200, 103), item(WeaponId::Decoy, 106)],
+            ),
+        ]);
+
+        assert_eq!(push(&[round_event(1, true)], false, &round_end).0, Some(1));
+        push(&[], true, &freeze_start);
+        let (round_number, purchases) = push(&[round_event(2, false)], false, &freeze_end);
+        assert_eq!(round_number, Some(2));
+        assert_eq!(
+            purchases,
+            vec![
+                BoughtThisRound {
+                    player: ALICE,
+                    items: vec![WeaponId::Ak47, WeaponId::Smokegrenade],
+                    spent_estimate: 3000,
+                },
+                BoughtThisRound {
+                    player: BOB,
+                    items: vec![WeaponId::Decoy],
+                    spent_estimate: 50,
+                },
+            ]
+        );
+    }
+}
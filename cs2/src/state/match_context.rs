@@ -0,0 +1,327 @@
+use anyhow::Context;
+use cs2_schema_generated::cs2::client::{
+    CCSPlayerController,
+    CCSPlayerController_InGameMoneyServices,
+    C_BaseEntity,
+    C_CSGameRules,
+    C_GameRules,
+};
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    BombCarrierInfo,
+    PlantedC4,
+    PlantedC4State,
+    StateAlivePlayerCount,
+    StateCS2Memory,
+    StateEntityClassIndex,
+    StateEntityList,
+    StateGameRules,
+    StateLocalPlayerController,
+    StatePlayerControllers,
+    StateTeamScores,
+    TEAM_ID_COUNTER_TERRORIST,
+    TEAM_ID_TERRORIST,
+};
+
+/// Average team money (account + spent this round) below which a team is on an eco round
+pub const ECONOMY_ECO_THRESHOLD: i32 = 2000;
+
+/// Average team money (account + spent this round) below which a team is force buying
+pub const ECONOMY_FULL_BUY_THRESHOLD: i32 = 3900;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TeamEconomy {
+    Eco,
+    ForceBuy,
+    FullBuy,
+}
+
+/// Classify the economy of a team by the average money available to each player this round
+pub fn classify_team_economy(average_money: i32) -> TeamEconomy {
+    if average_money < ECONOMY_ECO_THRESHOLD {
+        TeamEconomy::Eco
+    } else if average_money < ECONOMY_FULL_BUY_THRESHOLD {
+        TeamEconomy::ForceBuy
+    } else {
+        TeamEconomy::FullBuy
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BombSummary {
+    /// There is no bomb within the match (e.g. on hostage maps or in deathmatch)
+    Absent,
+
+    /// Bomb is carried by a player
+    Carried,
+
+    /// Bomb is lying on the ground
+    Loose,
+
+    /// Bomb is planted and ticking
+    Planted,
+
+    Defused,
+    Detonated,
+}
+
+/// Denormalized summary of the current match.
+/// Every field is resolved independently and will be None if it could not be read this frame.
+#[derive(Debug, Clone, Default)]
+pub struct MatchContext {
+    pub score_terrorists: Option<i32>,
+    pub score_counter_terrorists: Option<i32>,
+
+    /// Current round number starting with 1
+    pub round_number: Option<i32>,
+
+    /// Team id of the local player
+    pub local_team_id: Option<u8>,
+
+    pub alive_terrorists: Option<u32>,
+    pub alive_counter_terrorists: Option<u32>,
+
+    pub economy_terrorists: Option<TeamEconomy>,
+    pub economy_counter_terrorists: Option<TeamEconomy>,
+
+    pub bomb: Option<BombSummary>,
+
+    /// Match is paused (including tactical and technical timeouts)
+    pub paused: Option<bool>,
+
+    /// Reason why the match context is not available (degraded mode)
+    pub degraded_reason: Option<String>,
+}
+
+impl MatchContext {
+    fn read_local_team_id(states: &StateRegistry) -> anyhow::Result<Option<u8>> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let local_controller = states.resolve::<StateLocalPlayerController>(())?;
+        let Some(local_controller) = local_controller.instance.value_reference(memory.view_arc())
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(local_controller.m_iTeamNum()?))
+    }
+
+    fn read_economy(
+        states: &StateRegistry,
+    ) -> anyhow::Result<(Option<TeamEconomy>, Option<TeamEconomy>)> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let controllers = states.resolve::<StatePlayerControllers>(())?;
+
+        /* (total money, player count) */
+        let mut terrorists = (0i32, 0i32);
+        let mut counter_terrorists = (0i32, 0i32);
+        for entry in controllers.instances.iter() {
+            let Some(controller) = entry.instance.value_reference(memory.view_arc()) else {
+                continue;
+            };
+
+            let team = match controller.m_iTeamNum()? {
+                TEAM_ID_TERRORIST => &mut terrorists,
+                TEAM_ID_COUNTER_TERRORIST => &mut counter_terrorists,
+                _ => continue,
+            };
+
+            let money_services = controller
+                .m_pInGameMoneyServices()?
+                .value_reference(memory.view_arc())
+                .context("money services nullptr")?;
+
+            team.0 += money_services.m_iAccount()? + money_services.m_iCashSpentThisRound()?;
+            team.1 += 1;
+        }
+
+        let classify = |(money, players): (i32, i32)| {
+            (players > 0).then(|| classify_team_economy(money / players))
+        };
+        Ok((classify(terrorists), classify(counter_terrorists)))
+    }
+
+    fn read_bomb(states: &StateRegistry) -> anyhow::Result<Option<BombSummary>> {
+        let planted_c4 = states.resolve::<PlantedC4>(())?;
+        let summary = match planted_c4.state {
+            PlantedC4State::Active { .. } => BombSummary::Planted,
+            PlantedC4State::Defused => BombSummary::Defused,
+            PlantedC4State::Detonated => BombSummary::Detonated,
+            PlantedC4State::NotPlanted => {
+                let carrier = states.resolve::<BombCarrierInfo>(())?;
+                if carrier.carrier_entity_id.is_some() {
+                    BombSummary::Carried
+                } else {
+                    let entities = states.resolve::<StateEntityList>(())?;
+                    let class_index = states.resolve::<StateEntityClassIndex>(())?;
+                    if class_index
+                        .entities_of_class(&entities, "C_C4")
+                        .next()
+                        .is_some()
+                    {
+                        BombSummary::Loose
+                    } else {
+                        BombSummary::Absent
+                    }
+                }
+            }
+        };
+
+        Ok(Some(summary))
+    }
+
+    fn read_paused(states: &StateRegistry) -> anyhow::Result<Option<bool>> {
+        let game_rules = states.resolve::<StateGameRules>(())?;
+        let Some(rules) = &game_rules.rules else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            rules.m_bGamePaused()?
+                || rules.m_bTechnicalTimeOut()?
+                || rules.m_bTerroristTimeOutActive()?
+                || rules.m_bCTTimeOutActive()?,
+        ))
+    }
+}
+
+impl State for MatchContext {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let mut result = Self::default();
+
+        if let Ok(scores) = states.resolve::<StateTeamScores>(()) {
+            result.score_terrorists = scores.terrorists;
+            result.score_counter_terrorists = scores.counter_terrorists;
+        }
+
+        result.round_number = states
+            .resolve::<StateGameRules>(())
+            .ok()
+            .and_then(|game_rules| game_rules.rules.as_ref()?.m_totalRoundsPlayed().ok())
+            .map(|rounds_played| rounds_played + 1);
+
+        result.local_team_id = Self::read_local_team_id(states).ok().flatten();
+
+        if let Ok(alive) = states.resolve::<StateAlivePlayerCount>(()) {
+            result.alive_terrorists = Some(alive.terrorists);
+            result.alive_counter_terrorists = Some(alive.counter_terrorists);
+        }
+
+        if let Ok((terrorists, counter_terrorists)) = Self::read_economy(states) {
+            result.economy_terrorists = terrorists;
+            result.economy_counter_terrorists = counter_terrorists;
+        }
+
+        result.bomb = Self::read_bomb(states).ok().flatten();
+        result.paused = Self::read_paused(states).ok().flatten();
+        Ok(result)
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+
+    fn unavailable(reason: &str) -> Option<Self> {
+        Some(Self {
+            degraded_reason: Some(reason.to_string()),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        classify_team_economy,
+        BombSummary,
+        MatchContext,
+        TeamEconomy,
+        ECONOMY_ECO_THRESHOLD,
+        ECONOMY_FULL_BUY_THRESHOLD,
+    };
+    use crate::{
+        test_fixture::{
+            field_offset,
+            setup_dump_schema,
+            write,
+            EntityFixture,
+        },
+        TEAM_ID_COUNTER_TERRORIST,
+        TEAM_ID_TERRORIST,
+    };
+
+    #[test]
+    fn team_economy() {
+        let cases = [
+            (-800, TeamEconomy::Eco),
+            (0, TeamEconomy::Eco),
+            (ECONOMY_ECO_THRESHOLD - 1, TeamEconomy::Eco),
+            (ECONOMY_ECO_THRESHOLD, TeamEconomy::ForceBuy),
+            (ECONOMY_FULL_BUY_THRESHOLD - 1, TeamEconomy::ForceBuy),
+            (ECONOMY_FULL_BUY_THRESHOLD, TeamEconomy::FullBuy),
+            (16000, TeamEconomy::FullBuy),
+        ];
+
+        for (average_money, economy) in cases {
+            assert_eq!(
+                classify_team_economy(average_money),
+                economy,
+                "{}",
+                average_money
+            );
+        }
+    }
+
+    #[test]
+    fn partially_unavailable() {
+        setup_dump_schema();
+
+        /* team entities and globals only: no game rules, no bomb and no local controller */
+        let mut fixture = EntityFixture::default();
+        fixture.record_globals(100.0);
+        for (index, (team_id, score)) in [(TEAM_ID_TERRORIST, 7i32), (TEAM_ID_COUNTER_TERRORIST, 9)]
+            .into_iter()
+            .enumerate()
+        {
+            let address = 0x10_0000 + index as u64 * 0x1_0000;
+            fixture.push_entity(0x8001 + index as u32, "C_CSTeam", address);
+
+            let mut team = vec![0u8; 0x1000];
+            write(
+                &mut team,
+                field_offset("C_BaseEntity", "m_iTeamNum"),
+                &[team_id],
+            );
+            write(
+                &mut team,
+                field_offset("C_Team", "m_iScore"),
+                &score.to_le_bytes(),
+            );
+            fixture.record(address, &team);
+        }
+
+        let states = fixture.into_states();
+        let context = states.resolve::<MatchContext>(()).unwrap();
+        assert_eq!(context.score_terrorists, Some(7));
+        assert_eq!(context.score_counter_terrorists, Some(9));
+        assert_eq!(context.bomb, Some(BombSummary::Absent));
+
+        /* the game rules and the player controllers could not be read */
+        assert_eq!(context.round_number, None);
+        assert_eq!(context.paused, None);
+        assert_eq!(context.local_team_id, None);
+        assert_eq!(context.alive_terrorists, None);
+        assert_eq!(context.alive_counter_terrorists, None);
+        assert_eq!(context.economy_terrorists, None);
+        assert_eq!(context.economy_counter_terrorists, None);
+
+        /* the context itself is available */
+        assert_eq!(context.degraded_reason, None);
+    }
+}
@@ -1,17 +1,95 @@
+mod skip;
+pub use skip::*;
+
+mod confidence;
+pub use confidence::*;
+
+mod plausibility;
+pub use plausibility::*;
+
 mod player;
 pub use player::*;
 
+mod player_list;
+pub use player_list::*;
+
+mod pinning;
+pub use pinning::*;
+
 mod observer;
 pub use observer::*;
 
+mod owner;
+pub use owner::*;
+
 mod bomb;
 pub use bomb::*;
 
+mod bomb_damage;
+pub use bomb_damage::*;
+
+mod equipment;
+pub use equipment::*;
+
+mod defuse;
+pub use defuse::*;
+
+mod bomb_site;
+pub use bomb_site::*;
+
 mod map;
 pub use map::*;
 
 mod globals;
 pub use globals::*;
 
+mod clock;
+pub use clock::*;
+
+mod connection;
+pub use connection::*;
+
+mod data_quality;
+pub use data_quality::*;
+
 mod build_info;
 pub use build_info::*;
+
+mod team;
+pub use team::*;
+
+mod player_order;
+pub use player_order::*;
+
+mod movement;
+pub use movement::*;
+
+mod peek;
+pub use peek::*;
+
+mod rules;
+pub use rules::*;
+
+mod round_info;
+pub use round_info::*;
+
+mod match_context;
+pub use match_context::*;
+
+mod utility;
+pub use utility::*;
+
+mod grenade;
+pub use grenade::*;
+
+mod events;
+pub use events::*;
+
+mod round_history;
+pub use round_history::*;
+
+mod loadout;
+pub use loadout::*;
+
+mod hit_feedback;
+pub use hit_feedback::*;
@@ -0,0 +1,197 @@
+use std::{
+    collections::HashMap,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    units::UnitsPerSecond,
+    PawnIndex,
+};
+
+/// Entity flag indicating the entity is standing on the ground
+pub const FL_ONGROUND: u32 = 1 << 0;
+
+/// Fall speeds up to this value will not cause any damage
+pub const PLAYER_MAX_SAFE_FALL_SPEED: f32 = 580.0;
+
+/// Fall speeds equal or above this value are lethal (without the CS damage multiplier)
+pub const PLAYER_FATAL_FALL_SPEED: f32 = 1024.0;
+
+/// Min downward speed for a landing to be considered a (loud) landing
+pub const PLAYER_LANDING_SPEED_THRESHOLD: f32 = 350.0;
+
+const DAMAGE_FOR_FALL_SPEED: f32 = 100.0 / (PLAYER_FATAL_FALL_SPEED - PLAYER_MAX_SAFE_FALL_SPEED);
+
+/// Samples older then this will not be used for the landing detection
+const MOVEMENT_SAMPLE_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Estimate the fall damage for the given downward impact speed
+/// using the engines fall damage formula.
+pub fn estimate_fall_damage(fall_speed: f32) -> f32 {
+    if fall_speed <= PLAYER_MAX_SAFE_FALL_SPEED {
+        return 0.0;
+    }
+
+    (fall_speed - PLAYER_MAX_SAFE_FALL_SPEED) * DAMAGE_FOR_FALL_SPEED * 1.25
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PawnMovementSample {
+    pub flags: u32,
+    pub velocity: [f32; 3],
+    pub timestamp: Instant,
+}
+
+/// Result of a detected landing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PawnLanding {
+    /// Downward speed before the landing
+    pub impact_speed: UnitsPerSecond,
+
+    /// Estimated fall damage
+    pub fall_damage: f32,
+}
+
+/// Detect a landing by the rising edge of the on-ground flag
+/// while moving downwards faster then [PLAYER_LANDING_SPEED_THRESHOLD] before.
+pub fn detect_landing(previous: &PawnMovementSample, current_flags: u32) -> Option<PawnLanding> {
+    if previous.flags & FL_ONGROUND != 0 || current_flags & FL_ONGROUND == 0 {
+        /* no rising edge */
+        return None;
+    }
+
+    let impact_speed = -previous.velocity[2];
+    if impact_speed < PLAYER_LANDING_SPEED_THRESHOLD {
+        return None;
+    }
+
+    Some(PawnLanding {
+        impact_speed: UnitsPerSecond(impact_speed),
+        fall_damage: estimate_fall_damage(impact_speed),
+    })
+}
+
+/// Movement of all player pawns observed within the previous frame.
+pub struct StatePawnMovementShadow {
+    samples: HashMap<PawnIndex, PawnMovementSample>,
+}
+
+impl State for StatePawnMovementShadow {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            samples: Default::default(),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, _states: &StateRegistry) -> anyhow::Result<()> {
+        self.samples
+            .retain(|_, sample| sample.timestamp.elapsed() < MOVEMENT_SAMPLE_TIMEOUT);
+        Ok(())
+    }
+}
+
+impl StatePawnMovementShadow {
+    /// Record the current movement of the pawn and
+    /// return the landing if the pawn landed since the last sample.
+    pub fn push_sample(
+        &mut self,
+        pawn_entity_index: PawnIndex,
+        sample: PawnMovementSample,
+    ) -> Option<PawnLanding> {
+        let previous = self.samples.insert(pawn_entity_index, sample)?;
+        if sample
+            .timestamp
+            .saturating_duration_since(previous.timestamp)
+            > MOVEMENT_SAMPLE_TIMEOUT
+        {
+            return None;
+        }
+
+        detect_landing(&previous, sample.flags)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Instant;
+
+    use super::{
+        detect_landing,
+        estimate_fall_damage,
+        PawnMovementSample,
+        UnitsPerSecond,
+        FL_ONGROUND,
+    };
+
+    #[test]
+    fn fall_damage() {
+        let cases: &[(f32, f32)] = &[
+            (0.0, 0.0),
+            (350.0, 0.0),
+            (580.0, 0.0),
+            (624.4, 12.5),
+            (802.0, 62.5),
+            (1024.0, 125.0),
+        ];
+
+        for (speed, expected) in cases {
+            let damage = estimate_fall_damage(*speed);
+            assert!(
+                (damage - expected).abs() < 0.01,
+                "speed {} -> {} (expected {})",
+                speed,
+                damage,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn landing() {
+        let sample = |flags: u32, velocity_z: f32| PawnMovementSample {
+            flags,
+            velocity: [0.0, 0.0, velocity_z],
+            timestamp: Instant::now(),
+        };
+
+        let cases = &[
+            /* (previous flags, previous z velocity, current flags, landed) */
+            (0, -600.0, FL_ONGROUND, true),
+            (0, -400.0, FL_ONGROUND, true),
+            (0, -200.0, FL_ONGROUND, false),
+            (0, 400.0, FL_ONGROUND, false),
+            (0, -600.0, 0, false),
+            (FL_ONGROUND, -600.0, FL_ONGROUND, false),
+        ];
+
+        for (previous_flags, velocity_z, current_flags, landed) in cases {
+            let result = detect_landing(&sample(*previous_flags, *velocity_z), *current_flags);
+            assert_eq!(
+                result.is_some(),
+                *landed,
+                "{} {}",
+                previous_flags,
+                velocity_z
+            );
+        }
+
+        let landing = detect_landing(&sample(0, -802.0), FL_ONGROUND).unwrap();
+        assert_eq!(landing.impact_speed, UnitsPerSecond(802.0));
+        assert!((landing.fall_damage - 62.5).abs() < 0.01);
+    }
+}
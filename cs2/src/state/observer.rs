@@ -14,8 +14,9 @@ use utils_state::{
 
 use crate::{
     CEntityIdentityEx,
-    ClassNameCache,
+    PawnIndex,
     StateCS2Memory,
+    StateEntityClassIndex,
     StateEntityList,
     StateLocalPlayerController,
 };
@@ -25,28 +26,20 @@ pub struct SpectatorInfo {
 }
 
 pub struct SpectatorList {
-    pub target_entity_id: u32,
+    pub target_entity_id: PawnIndex,
     pub spectators: Vec<SpectatorInfo>,
 }
 
 impl State for SpectatorList {
-    type Parameter = u32;
+    type Parameter = PawnIndex;
 
     fn create(states: &StateRegistry, target_entity_id: Self::Parameter) -> anyhow::Result<Self> {
         let memory = states.resolve::<StateCS2Memory>(())?;
         let entities = states.resolve::<StateEntityList>(())?;
-        let class_name_cache = states.resolve::<ClassNameCache>(())?;
+        let class_index = states.resolve::<StateEntityClassIndex>(())?;
 
         let mut spectators = Vec::new();
-        for entity_identity in entities.entities() {
-            let entity_class = class_name_cache.lookup(&entity_identity.entity_class_info()?)?;
-            if entity_class
-                .map(|name| *name != "C_CSObserverPawn")
-                .unwrap_or(true)
-            {
-                continue;
-            }
-
+        for entity_identity in class_index.entities_of_class(&entities, "C_CSObserverPawn") {
             let observer_pawn = entity_identity
                 .entity_ptr::<dyn C_CSObserverPawn>()?
                 .value_copy(memory.view())?
@@ -63,7 +56,7 @@ impl State for SpectatorList {
                 }
             };
 
-            if observer_target_handle.get_entity_index() != target_entity_id {
+            if PawnIndex::from_handle(&observer_target_handle) != target_entity_id {
                 continue;
             }
 
@@ -102,7 +95,7 @@ impl State for SpectatorList {
 /// Get the entity id which we're currently following
 pub struct LocalCameraControllerTarget {
     pub is_local_entity: bool,
-    pub target_entity_id: Option<u32>,
+    pub target_entity_id: Option<PawnIndex>,
 }
 
 impl State for LocalCameraControllerTarget {
@@ -135,7 +128,7 @@ impl State for LocalCameraControllerTarget {
              */
 
             Ok(Self {
-                target_entity_id: Some(player_controller.m_hPawn()?.get_entity_index()),
+                target_entity_id: Some(PawnIndex::from_handle(&player_controller.m_hPawn()?)),
                 is_local_entity: true,
             })
         } else {
@@ -165,11 +158,9 @@ impl State for LocalCameraControllerTarget {
                     is_local_entity: false,
                 });
             }
-            let target_entity_id = observer_target_handle.get_entity_index();
-
             Ok(Self {
                 is_local_entity: false,
-                target_entity_id: Some(target_entity_id),
+                target_entity_id: Some(PawnIndex::from_handle(&observer_target_handle)),
             })
         }
     }
@@ -0,0 +1,87 @@
+use std::ffi::CStr;
+
+use anyhow::Context;
+use cs2_schema_cutl::EntityHandle;
+use cs2_schema_generated::cs2::client::{
+    C_BaseEntity,
+    C_CSPlayerPawn,
+};
+use utils_state::StateRegistry;
+
+use super::{
+    player_details_or_read,
+    ResultSkipExt,
+    StatePlayerList,
+};
+use crate::{
+    ClassNameCache,
+    PawnIndex,
+    StateCS2Memory,
+    StateEntityList,
+};
+
+/// Read the name and team of the player pawn owning an entity (e.g. the C4 or a grenade).
+/// Returns None if the owner entity does not exist.
+pub fn read_pawn_owner(
+    states: &StateRegistry,
+    owner_handle: &EntityHandle<dyn C_BaseEntity>,
+) -> anyhow::Result<Option<(Option<String>, u8)>> {
+    let memory = states.resolve::<StateCS2Memory>(())?;
+    let entities = states.resolve::<StateEntityList>(())?;
+    let class_name_cache = states.resolve::<ClassNameCache>(())?;
+
+    let Some(owner_pawn) = entities.resolve_typed(
+        &class_name_cache,
+        &owner_handle.cast::<dyn C_CSPlayerPawn>(),
+    )?
+    else {
+        return Ok(None);
+    };
+
+    let owner_pawn = owner_pawn
+        .value_reference(memory.view_arc())
+        .context("owner pawn nullptr")?;
+
+    let controller_handle = owner_pawn.m_hController()?;
+    let team_id = owner_pawn.m_iTeamNum()?;
+
+    let player_name = if controller_handle.is_valid() {
+        entities
+            .resolve_typed(&class_name_cache, &controller_handle)
+            .ok_or_skip("owner controller")
+            .flatten()
+            .and_then(|controller| controller.value_reference(memory.view_arc()))
+            .and_then(|controller_ref| {
+                controller_ref
+                    .m_iszPlayerName()
+                    .ok()
+                    .and_then(|name_bytes| {
+                        CStr::from_bytes_until_nul(&name_bytes)
+                            .ok()
+                            .map(|name| name.to_string_lossy().to_string())
+                    })
+            })
+    } else {
+        None
+    };
+
+    Ok(Some((player_name, team_id)))
+}
+
+/// Name and team of the player pawn owning an entity.
+/// Uses the fresh details of an already resolved player list if available (see [player_details_or_read]).
+/// Stale details will not be used as the owner may have changed teams in the meantime.
+pub fn resolve_pawn_owner(
+    states: &StateRegistry,
+    owner_handle: &EntityHandle<dyn C_BaseEntity>,
+) -> anyhow::Result<Option<(Option<String>, u8)>> {
+    let details = StatePlayerList::resolved_details(states, PawnIndex::from_handle(owner_handle))
+        .filter(|(_, confidence)| confidence.is_fresh());
+
+    let (owner, _confidence) = player_details_or_read(
+        details,
+        |details| Some(Some((Some(details.player_name.clone()?), details.team_id))),
+        || read_pawn_owner(states, owner_handle),
+    )?;
+    Ok(owner)
+}
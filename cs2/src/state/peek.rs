@@ -0,0 +1,278 @@
+use std::{
+    collections::HashMap,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use cs2_schema_generated::cs2::client::CCSPlayerController;
+use nalgebra::Vector3;
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use super::{
+    PlayerInterest,
+    StatePawnInfo,
+    StatePlayerList,
+};
+use crate::{
+    PawnIndex,
+    StateCS2Memory,
+    StateEntityList,
+    StateLocalPlayerController,
+};
+
+/// Min closing angular velocity (in degrees per second) for a player to be considered peeking
+pub const PEEK_ANGULAR_VELOCITY_THRESHOLD: f32 = 15.0;
+
+/// Max ratio between the radial and the lateral speed for the distance to be considered constant
+pub const PEEK_MAX_RADIAL_RATIO: f32 = 0.5;
+
+/// Positions older then this will not be used for the peek detection
+const PEEK_SAMPLE_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Movement of a player relative to the view of the local player between two frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PeekObservation {
+    /// Angular velocity (in degrees per second) around the local player.
+    /// Positive if the player is moving towards the view direction of the local player.
+    pub angular_velocity: f32,
+
+    /// Change of the distance to the local player (in units per second)
+    pub distance_rate: f32,
+
+    /// The player swings towards the view of the local player while keeping the distance
+    pub peeking_toward_you: bool,
+}
+
+fn normalize_degrees(angle: f32) -> f32 {
+    (angle + 180.0).rem_euclid(360.0) - 180.0
+}
+
+/// Detect a peek from two positions of a player (`delta_time` seconds apart)
+/// as seen by the local player at `observer` looking at `view_yaw` (in degrees).
+/// The height of the positions is ignored.
+///
+/// Returns None if the positions can not be compared (e.g. the player is at the observers position).
+pub fn detect_peek(
+    observer: &Vector3<f32>,
+    view_yaw: f32,
+    previous: &Vector3<f32>,
+    current: &Vector3<f32>,
+    delta_time: f32,
+) -> Option<PeekObservation> {
+    if delta_time <= 0.0 {
+        return None;
+    }
+
+    let relative = |position: &Vector3<f32>| (position - observer).xy();
+    let (previous, current) = (relative(previous), relative(current));
+    let (previous_distance, current_distance) = (previous.norm(), current.norm());
+    if previous_distance < f32::EPSILON || current_distance < f32::EPSILON {
+        return None;
+    }
+
+    let bearing = |position: &nalgebra::Vector2<f32>| position.y.atan2(position.x).to_degrees();
+    let angular_speed =
+        normalize_degrees(bearing(&current) - bearing(&previous)).abs() / delta_time;
+
+    let view_offset =
+        |position: &nalgebra::Vector2<f32>| normalize_degrees(bearing(position) - view_yaw).abs();
+    let angular_velocity = if view_offset(&current) < view_offset(&previous) {
+        angular_speed
+    } else {
+        -angular_speed
+    };
+
+    let distance_rate = (current_distance - previous_distance) / delta_time;
+    let lateral_speed = angular_speed.to_radians() * current_distance;
+
+    Some(PeekObservation {
+        angular_velocity,
+        distance_rate,
+        peeking_toward_you: angular_velocity >= PEEK_ANGULAR_VELOCITY_THRESHOLD
+            && distance_rate.abs() <= PEEK_MAX_RADIAL_RATIO * lateral_speed,
+    })
+}
+
+/// Positions of all player pawns observed within the previous frame
+struct StatePeekShadow {
+    positions: HashMap<PawnIndex, (Vector3<f32>, Instant)>,
+}
+
+impl State for StatePeekShadow {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            positions: Default::default(),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, _states: &StateRegistry) -> anyhow::Result<()> {
+        self.positions
+            .retain(|_, (_, timestamp)| timestamp.elapsed() < PEEK_SAMPLE_TIMEOUT);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PeekingPlayer {
+    pub pawn_entity_id: PawnIndex,
+    pub observation: PeekObservation,
+}
+
+/// Movement of all alive enemies relative to the view of the local player (e.g. for a pre-aim hint).
+/// Enemies will only be contained if they have been observed within the previous frame,
+/// hence this state must be resolved every frame.
+pub struct StatePeekingPlayers {
+    pub players: Vec<PeekingPlayer>,
+}
+
+impl State for StatePeekingPlayers {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let entities = states.resolve::<StateEntityList>(())?;
+        let local_controller = states.resolve::<StateLocalPlayerController>(())?;
+        let Some(local_pawn) = local_controller
+            .instance
+            .value_reference(memory.view_arc())
+            .map(|controller| controller.m_hPlayerPawn())
+            .transpose()?
+            .filter(|handle| entities.entity_from_handle(handle).is_some())
+        else {
+            return Ok(Self {
+                players: Vec::new(),
+            });
+        };
+
+        let local_pawn = states.resolve::<StatePawnInfo>(local_pawn)?;
+
+        /* positions only, no need for the pawn details */
+        let player_list = states.resolve::<StatePlayerList>(PlayerInterest::Players(Vec::new()))?;
+        let mut shadow = states.resolve_mut::<StatePeekShadow>(())?;
+
+        let now = Instant::now();
+        let mut players = Vec::new();
+        for entry in player_list.players.iter() {
+            if !entry.alive
+                || entry.team_id == local_pawn.team_id
+                || entry.pawn_entity_id == local_pawn.pawn_entity_id
+            {
+                continue;
+            }
+
+            let Some((previous, timestamp)) = shadow
+                .positions
+                .insert(entry.pawn_entity_id, (entry.position, now))
+            else {
+                continue;
+            };
+
+            let delta_time = now.saturating_duration_since(timestamp);
+            if delta_time > PEEK_SAMPLE_TIMEOUT {
+                continue;
+            }
+
+            if let Some(observation) = detect_peek(
+                &local_pawn.position,
+                local_pawn.view_angles.yaw_ccw_from_x(),
+                &previous,
+                &entry.position,
+                delta_time.as_secs_f32(),
+            ) {
+                players.push(PeekingPlayer {
+                    pawn_entity_id: entry.pawn_entity_id,
+                    observation,
+                });
+            }
+        }
+
+        Ok(Self { players })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector3;
+
+    use super::{
+        detect_peek,
+        PeekObservation,
+    };
+
+    const FRAME_TIME: f32 = 1.0 / 64.0;
+
+    /// Observe a trajectory (position per frame) from the origin looking along the x axis
+    fn observe(trajectory: impl Fn(f32) -> Vector3<f32>) -> Vec<PeekObservation> {
+        let observer = Vector3::zeros();
+        (0..32)
+            .filter_map(|frame| {
+                detect_peek(
+                    &observer,
+                    0.0,
+                    &trajectory(frame as f32 * FRAME_TIME),
+                    &trajectory((frame + 1) as f32 * FRAME_TIME),
+                    FRAME_TIME,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wide_swing() {
+        /* strafing out of cover at 250 u/s, 500 units in front and to the left */
+        let observations = observe(|time| Vector3::new(500.0, 150.0 - 250.0 * time, 0.0));
+        assert_eq!(observations.len(), 32);
+        for observation in observations {
+            assert!(observation.peeking_toward_you, "{:?}", observation);
+            assert!(observation.angular_velocity > 20.0);
+        }
+
+        /* swinging along the view direction of the local player */
+        let observations = observe(|time| Vector3::new(600.0, -20.0 - 250.0 * time, 0.0));
+        assert!(observations.iter().all(
+            |observation| !observation.peeking_toward_you && observation.angular_velocity < 0.0
+        ));
+    }
+
+    #[test]
+    fn straight_approach() {
+        let observations = observe(|time| Vector3::new(800.0 - 250.0 * time, 200.0, 0.0));
+        for observation in observations {
+            assert!(!observation.peeking_toward_you, "{:?}", observation);
+            assert!(observation.distance_rate < -200.0);
+        }
+    }
+
+    #[test]
+    fn retreating() {
+        let observations =
+            observe(|time| Vector3::new(600.0 + 200.0 * time, 100.0 + 100.0 * time, 0.0));
+        for observation in observations {
+            assert!(!observation.peeking_toward_you, "{:?}", observation);
+            assert!(observation.distance_rate > 0.0);
+        }
+    }
+
+    #[test]
+    fn degenerated() {
+        let position = Vector3::new(100.0, 0.0, 0.0);
+        assert!(detect_peek(&Vector3::zeros(), 0.0, &position, &position, 0.0).is_none());
+        assert!(detect_peek(&position, 0.0, &position, &Vector3::zeros(), FRAME_TIME).is_none());
+    }
+}
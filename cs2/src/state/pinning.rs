@@ -0,0 +1,98 @@
+use std::collections::{
+    BTreeSet,
+    HashSet,
+};
+
+use utils_state::{
+    State,
+    StateCacheType,
+    StatePoller,
+    StateRegistry,
+};
+
+use crate::{
+    ControllerIndex,
+    PlayerKey,
+    StatePlayerControllers,
+};
+
+/// Players of interest (e.g. the player a caster is focusing on).
+/// The details of pinned players will be read every frame regardless of the [PlayerDetailBudget](crate::PlayerDetailBudget)
+/// and the interest set of the player list.
+///
+/// Published by the [StatePoller] every frame (see [PlayerPinning]).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PinnedPlayers {
+    pub players: BTreeSet<PlayerKey>,
+}
+
+impl State for PinnedPlayers {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self::default())
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+impl PinnedPlayers {
+    /// Controller entity ids of all pinned players which are currently connected
+    pub fn controllers(states: &StateRegistry) -> anyhow::Result<HashSet<ControllerIndex>> {
+        let pinned = states.resolve::<Self>(())?;
+        if pinned.players.is_empty() {
+            return Ok(Default::default());
+        }
+
+        let controllers = states.resolve::<StatePlayerControllers>(())?;
+        Ok(controllers
+            .instances
+            .iter()
+            .filter(|entry| pinned.players.contains(&entry.order_key.player_key()))
+            .map(|entry| entry.entity_index)
+            .collect())
+    }
+}
+
+/// Pin players of interest on the poller
+pub trait PlayerPinning {
+    fn pin_player(&mut self, player: PlayerKey);
+
+    /// Returns false if the player has not been pinned
+    fn unpin_player(&mut self, player: &PlayerKey) -> bool;
+
+    fn pinned_players(&self) -> BTreeSet<PlayerKey>;
+}
+
+impl PlayerPinning for StatePoller {
+    fn pin_player(&mut self, player: PlayerKey) {
+        match self.input_mut::<PinnedPlayers>() {
+            Some(pinned) => {
+                pinned.players.insert(player);
+            }
+            None => self.set_input(PinnedPlayers {
+                players: [player].into(),
+            }),
+        }
+    }
+
+    fn unpin_player(&mut self, player: &PlayerKey) -> bool {
+        let Some(pinned) = self.input_mut::<PinnedPlayers>() else {
+            return false;
+        };
+
+        let removed = pinned.players.remove(player);
+        if pinned.players.is_empty() {
+            self.remove_input::<PinnedPlayers>();
+        }
+        removed
+    }
+
+    fn pinned_players(&self) -> BTreeSet<PlayerKey> {
+        self.input::<PinnedPlayers>()
+            .map(|pinned| pinned.players.clone())
+            .unwrap_or_default()
+    }
+}
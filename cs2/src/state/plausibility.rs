@@ -0,0 +1,372 @@
+use std::{
+    error::Error,
+    fmt,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use nalgebra::Vector3;
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    ConVars,
+    StateCS2Handle,
+};
+
+/// Max absolute coordinate of any position within the world
+pub const WORLD_BOUNDS: f32 = 32768.0;
+
+/// Max player health in all regular game modes
+pub const PLAYER_MAX_HEALTH: i32 = 100;
+
+/// Max player health in gun game modes (`game_type` 1) which may grant additional health
+pub const PLAYER_MAX_HEALTH_GUNGAME: i32 = 1000;
+
+/// Team ids which are known to the game (unassigned, spectator, terrorist and counter terrorist)
+pub const PLAYER_KNOWN_TEAM_IDS: [u8; 4] = [0, 1, 2, 3];
+
+/// Max bomb site index
+pub const BOMB_SITE_MAX: u8 = 8;
+
+/// Max bomb timer (in seconds)
+pub const BOMB_TIMER_MAX: f32 = 120.0;
+
+/// `game_type` of the gun game modes (arms race, demolition, deathmatch)
+const GAME_TYPE_GUNGAME: i32 = 1;
+
+/// Interval in which the `game_type` will be read again
+const PLAUSIBILITY_CONVAR_UPDATE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A value has been read successfully but lies outside of its plausible range.
+/// This usually happens when the game updated the struct while it has been read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImplausibleValue {
+    /// Name of the struct and field (e.g. `C_CSPlayerPawn::m_iHealth`)
+    pub field: &'static str,
+    pub value: f64,
+
+    /// Plausible range (inclusive)
+    pub min: f64,
+    pub max: f64,
+}
+
+impl fmt::Display for ImplausibleValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "implausible value {} for {} (expected {} to {})",
+            self.value, self.field, self.min, self.max
+        )
+    }
+}
+
+impl Error for ImplausibleValue {}
+
+pub fn check_plausible_range(
+    field: &'static str,
+    value: impl Into<f64>,
+    min: impl Into<f64>,
+    max: impl Into<f64>,
+) -> Result<(), ImplausibleValue> {
+    let (value, min, max) = (value.into(), min.into(), max.into());
+    if value.is_nan() || value < min || value > max {
+        return Err(ImplausibleValue {
+            field,
+            value,
+            min,
+            max,
+        });
+    }
+
+    Ok(())
+}
+
+pub fn check_plausible_position(
+    field: &'static str,
+    position: &Vector3<f32>,
+) -> Result<(), ImplausibleValue> {
+    for value in position.iter() {
+        check_plausible_range(field, *value, -WORLD_BOUNDS, WORLD_BOUNDS)?;
+    }
+
+    Ok(())
+}
+
+/// Plausible values of the player pawn depending on the current game mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PawnPlausibility {
+    pub max_health: i32,
+}
+
+impl Default for PawnPlausibility {
+    fn default() -> Self {
+        Self {
+            max_health: PLAYER_MAX_HEALTH,
+        }
+    }
+}
+
+impl PawnPlausibility {
+    pub fn from_game_type(game_type: i32) -> Self {
+        Self {
+            max_health: if game_type == GAME_TYPE_GUNGAME {
+                PLAYER_MAX_HEALTH_GUNGAME
+            } else {
+                PLAYER_MAX_HEALTH
+            },
+        }
+    }
+
+    pub fn check_vitals(&self, health: i32, team_id: u8) -> Result<(), ImplausibleValue> {
+        check_plausible_range("C_CSPlayerPawn::m_iHealth", health, 0, self.max_health)?;
+        if !PLAYER_KNOWN_TEAM_IDS.contains(&team_id) {
+            return Err(ImplausibleValue {
+                field: "C_CSPlayerPawn::m_iTeamNum",
+                value: team_id as f64,
+                min: 0.0,
+                max: PLAYER_KNOWN_TEAM_IDS.len() as f64 - 1.0,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn check_position(&self, position: &Vector3<f32>) -> Result<(), ImplausibleValue> {
+        check_plausible_position("C_CSPlayerPawn::m_vecAbsOrigin", position)
+    }
+}
+
+/// Check the bomb site and timers of the planted C4.
+/// `time_detonation` is the time (in seconds) remaining until detonation and may be negative after the detonation.
+pub fn check_planted_c4(
+    bomb_site: u8,
+    timer_length: f32,
+    time_detonation: f32,
+) -> Result<(), ImplausibleValue> {
+    check_plausible_range("C_PlantedC4::m_nBombSite", bomb_site, 0, BOMB_SITE_MAX)?;
+    check_plausible_range(
+        "C_PlantedC4::m_flTimerLength",
+        timer_length,
+        0.0,
+        BOMB_TIMER_MAX,
+    )?;
+    check_plausible_range(
+        "C_PlantedC4::m_flC4Blow",
+        time_detonation,
+        f32::MIN,
+        BOMB_TIMER_MAX,
+    )?;
+    Ok(())
+}
+
+/// Implausible values observed since the process has been attached
+pub struct StatePlausibility {
+    /// Bounds of the player pawn for the current game mode
+    pub pawn: PawnPlausibility,
+
+    /// Total amount of implausible values
+    pub violations: u64,
+    pub last_violation: Option<ImplausibleValue>,
+
+    game_type_updated: Option<Instant>,
+}
+
+impl StatePlausibility {
+    /// Returns the value if the check passed.
+    /// Otherwise the violation will be recorded and returned as error
+    /// so the caller can substitute the previous value (see [crate::FieldShadow]).
+    pub fn validate<T>(
+        states: &StateRegistry,
+        value: T,
+        check: Result<(), ImplausibleValue>,
+    ) -> anyhow::Result<T> {
+        match check {
+            Ok(()) => Ok(value),
+            Err(violation) => {
+                Self::record_violation(states, &violation);
+                Err(violation.into())
+            }
+        }
+    }
+
+    /// Count the violation.
+    /// A metrics record will be emitted for the first violation and every time the total amount doubles.
+    pub fn record_violation(states: &StateRegistry, violation: &ImplausibleValue) {
+        log::debug!("{}", violation);
+
+        let violations = {
+            let Ok(mut plausibility) = states.resolve_mut::<Self>(()) else {
+                return;
+            };
+
+            plausibility.violations += 1;
+            plausibility.last_violation = Some(violation.clone());
+            plausibility.violations
+        };
+
+        if violations.is_power_of_two() {
+            if let Ok(cs2) = states.resolve::<StateCS2Handle>(()) {
+                cs2.add_metrics_record(
+                    "cs2-implausible-value",
+                    &format!(
+                        "field: {}, value: {}, total: {}",
+                        violation.field, violation.value, violations
+                    ),
+                );
+            }
+        }
+    }
+}
+
+impl State for StatePlausibility {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            pawn: Default::default(),
+
+            violations: 0,
+            last_violation: None,
+
+            game_type_updated: None,
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        if self
+            .game_type_updated
+            .map(|timestamp| timestamp.elapsed() < PLAUSIBILITY_CONVAR_UPDATE_INTERVAL)
+            .unwrap_or(false)
+        {
+            return Ok(());
+        }
+
+        self.game_type_updated = Some(Instant::now());
+        let game_type = match ConVars::new(states)?.find_cvar("game_type")? {
+            Some(cvar) => cvar.n_value()? as i32,
+            None => 0,
+        };
+        self.pawn = PawnPlausibility::from_game_type(game_type);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector3;
+
+    use super::{
+        check_planted_c4,
+        PawnPlausibility,
+    };
+    use crate::FieldShadow;
+
+    /// Health, team and position of a pawn as raw bytes
+    fn decode_pawn(bytes: &[u8; 17]) -> (i32, u8, Vector3<f32>) {
+        let float =
+            |offset: usize| f32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        (
+            i32::from_le_bytes(bytes[0..4].try_into().unwrap()),
+            bytes[4],
+            Vector3::new(float(5), float(9), float(13)),
+        )
+    }
+
+    /// health 87, team 2, position (1024, -512, 64)
+    const PAWN_VALID: [u8; 17] = [
+        0x57, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x80, 0x44, 0x00, 0x00, 0x00, 0xC4, 0x00, 0x00,
+        0x80, 0x42,
+    ];
+
+    #[test]
+    fn pawn_fixtures() {
+        let plausibility = PawnPlausibility::default();
+
+        let (health, team, position) = decode_pawn(&PAWN_VALID);
+        assert_eq!((health, team), (87, 2));
+        assert!(plausibility.check_vitals(health, team).is_ok());
+        assert!(plausibility.check_position(&position).is_ok());
+
+        /* torn read of the health */
+        let mut corrupted = PAWN_VALID;
+        corrupted[0..2].copy_from_slice(&[0xFF, 0x7F]);
+        let (health, team, _) = decode_pawn(&corrupted);
+        let error = plausibility.check_vitals(health, team).unwrap_err();
+        assert_eq!(error.field, "C_CSPlayerPawn::m_iHealth");
+        assert_eq!(error.value, 32767.0);
+
+        /* gun game may grant additional health */
+        let mut boosted = PAWN_VALID;
+        boosted[0] = 0xC8;
+        let (health, team, _) = decode_pawn(&boosted);
+        assert!(plausibility.check_vitals(health, team).is_err());
+        assert!(PawnPlausibility::from_game_type(1)
+            .check_vitals(health, team)
+            .is_ok());
+
+        let mut corrupted = PAWN_VALID;
+        corrupted[4] = 17;
+        let (health, team, _) = decode_pawn(&corrupted);
+        assert_eq!(
+            plausibility.check_vitals(health, team).unwrap_err().field,
+            "C_CSPlayerPawn::m_iTeamNum"
+        );
+
+        /* x = 2.0e9, y = NaN */
+        for patch in [(5, [0x28, 0x6B, 0xEE, 0x4E]), (9, [0x00, 0x00, 0xC0, 0x7F])] {
+            let mut corrupted = PAWN_VALID;
+            corrupted[patch.0..patch.0 + 4].copy_from_slice(&patch.1);
+            let (_, _, position) = decode_pawn(&corrupted);
+            assert!(plausibility.check_position(&position).is_err());
+        }
+    }
+
+    #[test]
+    fn planted_c4() {
+        assert!(check_planted_c4(1, 40.0, 23.5).is_ok());
+        assert!(check_planted_c4(1, 40.0, -3.0).is_ok());
+
+        assert!(check_planted_c4(0xCD, 40.0, 23.5).is_err());
+        assert!(check_planted_c4(1, f32::from_le_bytes([0xCD; 4]), 23.5).is_err());
+        assert!(check_planted_c4(1, 40.0, 1.0e7).is_err());
+    }
+
+    #[test]
+    fn substitution() {
+        let plausibility = PawnPlausibility::default();
+        let mut shadow = FieldShadow::<(i32, u8)>::default();
+
+        let mut read = |frame: u64, bytes: &[u8; 17]| {
+            let (health, team, _) = decode_pawn(bytes);
+            let result = plausibility
+                .check_vitals(health, team)
+                .map(|_| (health, team))
+                .map_err(anyhow::Error::from);
+            shadow.observe(1, frame, result)
+        };
+
+        let mut corrupted = PAWN_VALID;
+        corrupted[0..4].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+
+        /* no previous value available */
+        let (value, confidence) = read(0, &corrupted);
+        assert_eq!(value, None);
+        assert!(!confidence.is_available());
+
+        assert_eq!(read(1, &PAWN_VALID).0, Some((87, 2)));
+
+        let (value, confidence) = read(2, &corrupted);
+        assert_eq!(value, Some((87, 2)));
+        assert!(confidence.is_available() && !confidence.is_fresh());
+    }
+}
@@ -1,6 +1,7 @@
 use std::{
     ffi::CStr,
     ops::Deref,
+    time::Instant,
 };
 
 use anyhow::{
@@ -27,24 +28,36 @@ use utils_state::{
     StateRegistry,
 };
 
+use super::{
+    FieldConfidence,
+    FieldShadow,
+    PawnMovementSample,
+    StatePawnMovementShadow,
+    StatePlausibility,
+};
 use crate::{
     schema::{
         CBoneStateData,
         CModelStateEx,
     },
     CS2Model,
+    ControllerIndex,
+    PawnIndex,
     StateCS2Memory,
     StateEntityList,
+    ViewAngles,
     WeaponId,
 };
 
 #[derive(Debug, Clone)]
 pub struct StatePawnInfo {
-    pub controller_entity_id: Option<u32>,
-    pub pawn_entity_id: u32,
+    pub controller_entity_id: Option<ControllerIndex>,
+    pub pawn_entity_id: PawnIndex,
     pub team_id: u8,
 
     pub player_health: i32,
+    pub player_armor: i32,
+    pub player_has_helmet: bool,
     pub player_has_defuser: bool,
     pub player_has_bomb: bool,
     pub player_name: Option<String>,
@@ -61,7 +74,108 @@ pub struct StatePawnInfo {
     pub player_has_decoy: bool,
 
     pub position: nalgebra::Vector3<f32>,
-    pub rotation: f32,
+    pub view_angles: ViewAngles,
+
+    /// Confidence of `player_health` and `team_id`.
+    /// Implausible values (see [crate::PawnPlausibility]) will be substituted by their previous values.
+    pub vitals_confidence: FieldConfidence,
+
+    /// Confidence of `position` and `view_angles`.
+    /// Never [FieldConfidence::Unavailable]: the pawn info fails to resolve if the position is not known.
+    pub position_confidence: FieldConfidence,
+
+    /// Confidence of `weapon`, `weapon_current_ammo` and `weapon_reserve_ammo`.
+    /// The values are defaults if [FieldConfidence::Unavailable] and must not be used.
+    pub weapon_confidence: FieldConfidence,
+
+    /// Confidence of `player_armor`, `player_has_helmet` and `player_has_defuser`.
+    /// The values are defaults if [FieldConfidence::Unavailable] and must not be used.
+    pub economy_confidence: FieldConfidence,
+
+    /// The player landed since the last frame after falling
+    pub just_landed: bool,
+
+    /// Estimated fall damage of the landing (zero if not landed)
+    pub fall_damage: f32,
+
+    /// The player has been muted by the local player.
+    /// Always None: the client schema does not contain the mutes of the local player.
+    /// `m_bHasCommunicationAbuseMute` and `m_uiCommunicationMuteFlags` of the controller
+    /// describe server side communication penalties and must not be used instead.
+    pub locally_muted: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PawnVitals {
+    health: i32,
+    team_id: u8,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct PawnPosition {
+    position: nalgebra::Vector3<f32>,
+    view_angles: ViewAngles,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PawnWeapon {
+    weapon: WeaponId,
+    current_ammo: i32,
+    reserve_ammo: i32,
+}
+
+impl Default for PawnWeapon {
+    fn default() -> Self {
+        Self {
+            weapon: WeaponId::Unknown,
+            current_ammo: -1,
+            reserve_ammo: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct PawnEconomy {
+    armor: i32,
+    has_helmet: bool,
+    has_defuser: bool,
+}
+
+/// Last known field groups of all player pawns.
+/// Used to substitute values which could not be read within the current frame.
+struct StatePawnInfoShadow {
+    frame: u64,
+    vitals: FieldShadow<PawnVitals>,
+    position: FieldShadow<PawnPosition>,
+    weapon: FieldShadow<PawnWeapon>,
+    economy: FieldShadow<PawnEconomy>,
+}
+
+impl State for StatePawnInfoShadow {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            frame: 0,
+            vitals: Default::default(),
+            position: Default::default(),
+            weapon: Default::default(),
+            economy: Default::default(),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, _states: &StateRegistry) -> anyhow::Result<()> {
+        self.frame += 1;
+        self.vitals.prune(self.frame);
+        self.position.prune(self.frame);
+        self.weapon.prune(self.frame);
+        self.economy.prune(self.frame);
+        Ok(())
+    }
 }
 
 impl State for StatePawnInfo {
@@ -77,12 +191,37 @@ impl State for StatePawnInfo {
             .value_copy(memory.view())?
             .context("player pawn nullptr")?;
 
-        let player_health = player_pawn.m_iHealth()?;
+        let plausibility = states
+            .resolve::<StatePlausibility>(())
+            .map(|state| state.pawn)
+            .unwrap_or_default();
+
+        let (vitals, vitals_confidence) = {
+            let vitals = PawnVitals {
+                health: player_pawn.m_iHealth()?,
+                team_id: player_pawn.m_iTeamNum()?,
+            };
+            let vitals = StatePlausibility::validate(
+                states,
+                vitals,
+                plausibility.check_vitals(vitals.health, vitals.team_id),
+            );
+
+            let mut shadow = states.resolve_mut::<StatePawnInfoShadow>(())?;
+            let frame = shadow.frame;
+            shadow.vitals.observe(handle.value, frame, vitals)
+        };
+        let Some(PawnVitals {
+            health: player_health,
+            team_id: player_team,
+        }) = vitals
+        else {
+            anyhow::bail!("implausible player vitals")
+        };
 
         let controller_handle = player_pawn.m_hController()?;
         let current_controller = entities.entity_from_handle(&controller_handle);
 
-        let player_team = player_pawn.m_iTeamNum()?;
         let player_name = if let Some(identity) = &current_controller {
             let player_controller = identity
                 .value_reference(memory.view_arc())
@@ -97,12 +236,19 @@ impl State for StatePawnInfo {
             None
         };
 
-        let player_has_defuser = player_pawn
-            .m_pItemServices()?
-            .value_reference(memory.view_arc())
-            .context("m_pItemServices nullptr")?
-            .cast::<dyn CCSPlayer_ItemServices>()
-            .m_bHasDefuser()?;
+        let economy = (|| -> anyhow::Result<PawnEconomy> {
+            let item_services = player_pawn
+                .m_pItemServices()?
+                .value_reference(memory.view_arc())
+                .context("m_pItemServices nullptr")?
+                .cast::<dyn CCSPlayer_ItemServices>();
+
+            Ok(PawnEconomy {
+                armor: player_pawn.m_ArmorValue()?,
+                has_helmet: item_services.m_bHasHelmet()?,
+                has_defuser: item_services.m_bHasDefuser()?,
+            })
+        })();
 
         let weapon_services = player_pawn
             .m_pWeaponServices()?
@@ -145,64 +291,121 @@ impl State for StatePawnInfo {
             }
         }
 
-        /* Will be an instance of CSkeletonInstance */
-        let game_screen_node = player_pawn
-            .m_pGameSceneNode()?
-            .value_reference(memory.view_arc())
-            .context("game screen node nullptr")?
-            .cast::<dyn CSkeletonInstance>()
-            .copy()?;
-
-        let position =
-            nalgebra::Vector3::<f32>::from_column_slice(&game_screen_node.m_vecAbsOrigin()?);
-
-        let weapon_ref = player_pawn
-            .m_pClippingWeapon()?
-            .value_reference(memory.view_arc());
-        let weapon_type = if let Some(weapon) = &weapon_ref {
-            weapon
-                .cast::<dyn C_EconEntity>()
-                .m_AttributeManager()?
-                .m_Item()?
-                .m_iItemDefinitionIndex()?
-        } else {
-            WeaponId::Knife.id()
+        let position = (|| -> anyhow::Result<PawnPosition> {
+            /* Will be an instance of CSkeletonInstance */
+            let game_screen_node = player_pawn
+                .m_pGameSceneNode()?
+                .value_reference(memory.view_arc())
+                .context("game screen node nullptr")?
+                .cast::<dyn CSkeletonInstance>()
+                .copy()?;
+
+            let position = PawnPosition {
+                position: nalgebra::Vector3::<f32>::from_column_slice(
+                    &game_screen_node.m_vecAbsOrigin()?,
+                ),
+                view_angles: {
+                    let eye_angles = player_pawn.m_angEyeAngles()?;
+                    ViewAngles::from_engine(eye_angles[0], eye_angles[1])
+                },
+            };
+            StatePlausibility::validate(
+                states,
+                position,
+                plausibility.check_position(&position.position),
+            )
+        })();
+
+        let landing = {
+            let movement_sample = PawnMovementSample {
+                flags: player_pawn.m_fFlags()?,
+                velocity: player_pawn.m_vecAbsVelocity()?,
+                timestamp: Instant::now(),
+            };
+
+            states
+                .resolve_mut::<StatePawnMovementShadow>(())
+                .ok()
+                .and_then(|mut shadow| {
+                    shadow.push_sample(PawnIndex::from_handle(&handle), movement_sample)
+                })
         };
 
-        let (weapon_current_ammo, weapon_reserve_ammo) = if let Some(weapon) = weapon_ref.as_ref() {
-            let weapon = weapon.cast::<dyn C_BasePlayerWeapon>();
-            (weapon.m_iClip1()?, weapon.m_pReserveAmmo()?[0])
-        } else {
-            (-1, 0)
+        let weapon = (|| -> anyhow::Result<PawnWeapon> {
+            let weapon_ref = player_pawn
+                .m_pClippingWeapon()?
+                .value_reference(memory.view_arc());
+            let weapon_type = if let Some(weapon) = &weapon_ref {
+                weapon
+                    .cast::<dyn C_EconEntity>()
+                    .m_AttributeManager()?
+                    .m_Item()?
+                    .m_iItemDefinitionIndex()?
+            } else {
+                WeaponId::Knife.id()
+            };
+
+            let (current_ammo, reserve_ammo) = if let Some(weapon) = weapon_ref.as_ref() {
+                let weapon = weapon.cast::<dyn C_BasePlayerWeapon>();
+                (weapon.m_iClip1()?, weapon.m_pReserveAmmo()?[0])
+            } else {
+                (-1, 0)
+            };
+
+            Ok(PawnWeapon {
+                weapon: WeaponId::from_id(weapon_type).unwrap_or(WeaponId::Unknown),
+                current_ammo,
+                reserve_ammo,
+            })
+        })();
+
+        let (
+            (position, position_confidence),
+            (weapon, weapon_confidence),
+            (economy, economy_confidence),
+        ) = {
+            let mut shadow = states.resolve_mut::<StatePawnInfoShadow>(())?;
+            let shadow = &mut *shadow;
+            (
+                shadow
+                    .position
+                    .observe(handle.value, shadow.frame, position),
+                shadow.weapon.observe(handle.value, shadow.frame, weapon),
+                shadow.economy.observe(handle.value, shadow.frame, economy),
+            )
+        };
+        let Some(position) = position else {
+            /* a player without any known position would be reported at the world origin */
+            anyhow::bail!("player position unavailable ({:?})", position_confidence)
         };
+        let weapon = weapon.unwrap_or_default();
+        let economy = economy.unwrap_or_default();
 
         let player_flashtime = player_pawn.m_flFlashBangTime()?;
 
         // Use cached bomb carrier state instead of iterating through all entities
         let player_has_bomb = if let Ok(bomb_carrier) = states.resolve::<super::BombCarrierInfo>(())
         {
-            bomb_carrier.carrier_entity_id == Some(handle.get_entity_index())
+            bomb_carrier.carrier_entity_id == Some(PawnIndex::from_handle(&handle))
         } else {
             false
         };
 
         Ok(Self {
-            controller_entity_id: if controller_handle.is_valid() {
-                Some(controller_handle.get_entity_index())
-            } else {
-                None
-            },
-            pawn_entity_id: handle.get_entity_index(),
+            controller_entity_id: ControllerIndex::from_valid_handle(&controller_handle),
+            pawn_entity_id: PawnIndex::from_handle(&handle),
 
             team_id: player_team,
 
             player_name,
-            player_has_defuser,
+            player_has_defuser: economy.has_defuser,
             player_has_bomb,
             player_health,
-            weapon: WeaponId::from_id(weapon_type).unwrap_or(WeaponId::Unknown),
-            weapon_current_ammo,
-            weapon_reserve_ammo,
+            player_armor: economy.armor,
+            player_has_helmet: economy.has_helmet,
+            weapon: weapon.weapon,
+            weapon_current_ammo: weapon.current_ammo,
+            weapon_reserve_ammo: weapon.reserve_ammo,
             player_flashtime,
 
             player_has_flash,
@@ -212,8 +415,20 @@ impl State for StatePawnInfo {
             player_has_incendiary,
             player_has_decoy,
 
-            position,
-            rotation: player_pawn.m_angEyeAngles()?[1],
+            position: position.position,
+            view_angles: position.view_angles,
+
+            vitals_confidence,
+            position_confidence,
+            weapon_confidence,
+            economy_confidence,
+
+            just_landed: landing.is_some(),
+            fall_damage: landing
+                .map(|landing| landing.fall_damage)
+                .unwrap_or_default(),
+
+            locally_muted: None,
         })
     }
 
@@ -331,3 +546,89 @@ impl State for PlayerPawnState {
         StateCacheType::Volatile
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use utils_state::StateRegistry;
+
+    use super::StatePawnInfo;
+    use crate::{
+        test_fixture::{
+            field_offset,
+            match_fixture,
+            match_pawn_handle,
+            setup_dump_schema,
+            EntityFixture,
+        },
+        FL_ONGROUND,
+    };
+
+    /// Address of the pawn of the first player within the [match_fixture]
+    const PAWN_ADDRESS: u64 = 0x100_0000;
+
+    /// Single player [match_fixture] with the given entity flags and vertical velocity
+    fn movement_fixture(flags: u32, velocity_z: f32) -> EntityFixture {
+        let mut fixture = match_fixture(1);
+        fixture.record(
+            PAWN_ADDRESS + field_offset("C_BaseEntity", "m_fFlags") as u64,
+            &flags.to_le_bytes(),
+        );
+        fixture.record(
+            PAWN_ADDRESS + field_offset("C_BaseEntity", "m_vecAbsVelocity") as u64,
+            &[0.0, 0.0, velocity_z]
+                .into_iter()
+                .flat_map(f32::to_le_bytes)
+                .collect::<Vec<_>>(),
+        );
+        fixture
+    }
+
+    /// Resolve the pawn info of the next frame read from the fixture
+    fn next_frame(states: &mut StateRegistry, fixture: EntityFixture) -> StatePawnInfo {
+        states.invalidate_states();
+        let memory = Arc::new(fixture.memory.clone());
+        fixture.register(states, memory);
+
+        states
+            .resolve::<StatePawnInfo>(match_pawn_handle(0))
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn landing() {
+        setup_dump_schema();
+
+        let mut states = StateRegistry::new(1024);
+        let falling = next_frame(&mut states, movement_fixture(0, -700.0));
+        assert!(!falling.just_landed);
+        assert_eq!(falling.fall_damage, 0.0);
+
+        /* rising edge of the on-ground flag */
+        let landed = next_frame(&mut states, movement_fixture(FL_ONGROUND, 0.0));
+        assert!(landed.just_landed);
+        assert!(
+            (landed.fall_damage - 33.78).abs() < 0.01,
+            "{}",
+            landed.fall_damage
+        );
+
+        /* still standing on the ground */
+        let standing = next_frame(&mut states, movement_fixture(FL_ONGROUND, 0.0));
+        assert!(!standing.just_landed);
+        assert_eq!(standing.fall_damage, 0.0);
+    }
+
+    #[test]
+    fn soft_landing() {
+        setup_dump_schema();
+
+        /* jumping in place does not exceed the landing speed threshold */
+        let mut states = StateRegistry::new(1024);
+        next_frame(&mut states, movement_fixture(0, -250.0));
+        let landed = next_frame(&mut states, movement_fixture(FL_ONGROUND, 0.0));
+        assert!(!landed.just_landed);
+    }
+}
@@ -0,0 +1,734 @@
+use std::collections::{
+    HashMap,
+    HashSet,
+};
+
+use anyhow::Context;
+use cs2_schema_cutl::EntityHandle;
+use cs2_schema_generated::cs2::client::{
+    CGameSceneNode,
+    C_BaseEntity,
+    C_BasePlayerPawn,
+    C_CSPlayerPawn,
+};
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use super::{
+    FieldConfidence,
+    PinnedPlayers,
+    StatePawnInfo,
+    FIELD_MAX_STALE_FRAMES,
+    TEAM_ID_COUNTER_TERRORIST,
+    TEAM_ID_TERRORIST,
+};
+use crate::{
+    CEntityIdentityEx,
+    ControllerIndex,
+    PawnIndex,
+    StateCS2Memory,
+    StateEntityClassIndex,
+    StateEntityList,
+};
+
+/// Players for which the full pawn details should be read
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PlayerInterest {
+    All,
+
+    /// All players of the given team id
+    Team(u8),
+
+    /// Players by their controller entity id
+    Players(Vec<ControllerIndex>),
+}
+
+impl PlayerInterest {
+    pub fn is_interested(
+        &self,
+        controller_entity_id: Option<ControllerIndex>,
+        team_id: u8,
+    ) -> bool {
+        match self {
+            Self::All => true,
+            Self::Team(team) => *team == team_id,
+            Self::Players(players) => controller_entity_id
+                .map(|entity_id| players.contains(&entity_id))
+                .unwrap_or(false),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlayerListEntry {
+    pub pawn_entity_id: PawnIndex,
+    pub controller_entity_id: Option<ControllerIndex>,
+    pub team_id: u8,
+
+    pub alive: bool,
+    pub position: nalgebra::Vector3<f32>,
+
+    /// Full pawn details.
+    /// Only available for players within the interest set and pinned players (see [PinnedPlayers]).
+    pub details: Option<StatePawnInfo>,
+
+    /// None if the details have not been requested.
+    /// The details may be stale if the detail reads are limited (see [PlayerDetailBudget]).
+    pub details_confidence: Option<FieldConfidence>,
+}
+
+/// Limits the amount of players for which the full details will be read per frame and player list.
+/// Players which exceed the budget will report the details of their last read as stale.
+/// Pinned players (see [PinnedPlayers]) will always be read and do not count towards the budget.
+///
+/// Can be overridden using `StateRegistry::set`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlayerDetailBudget {
+    /// None for no limit
+    pub max_detail_reads: Option<usize>,
+}
+
+impl State for PlayerDetailBudget {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self::default())
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+}
+
+/// Decides which players get their details read within a frame.
+/// Pinned players will always be read while the remaining budget goes to the players with the oldest details.
+#[derive(Debug, Clone, Default)]
+pub struct DetailRefreshSchedule {
+    /// Frame of the last read by the pawn entity id
+    last_refresh: HashMap<PawnIndex, u64>,
+}
+
+impl DetailRefreshSchedule {
+    /// Schedule the reads of `frame` for the candidates (pawn entity id, pinned).
+    /// The budget does not include the pinned players.
+    pub fn schedule(
+        &mut self,
+        frame: u64,
+        candidates: &[(PawnIndex, bool)],
+        budget: Option<usize>,
+    ) -> HashSet<PawnIndex> {
+        let mut others = candidates
+            .iter()
+            .filter(|(_, pinned)| !*pinned)
+            .map(|(pawn, _)| *pawn)
+            .collect::<Vec<_>>();
+        others.sort_by_key(|pawn| (self.last_refresh.get(pawn).copied(), *pawn));
+
+        let scheduled = candidates
+            .iter()
+            .filter(|(_, pinned)| *pinned)
+            .map(|(pawn, _)| *pawn)
+            .chain(others.into_iter().take(budget.unwrap_or(usize::MAX)))
+            .collect::<HashSet<_>>();
+
+        for pawn in scheduled.iter() {
+            self.last_refresh.insert(*pawn, frame);
+        }
+        self.last_refresh
+            .retain(|pawn, _| candidates.iter().any(|(candidate, _)| candidate == pawn));
+        scheduled
+    }
+
+    /// Confidence of the details in `frame` after the reads of the frame have been scheduled
+    pub fn confidence(&self, frame: u64, pawn: PawnIndex) -> FieldConfidence {
+        match self.last_refresh.get(&pawn) {
+            Some(last_refresh) if *last_refresh == frame => FieldConfidence::Fresh,
+            Some(last_refresh) if frame - last_refresh <= FIELD_MAX_STALE_FRAMES => {
+                FieldConfidence::Stale {
+                    frames: frame - last_refresh,
+                }
+            }
+            _ => FieldConfidence::Unavailable {
+                reason: "detail read budget exceeded".to_string(),
+            },
+        }
+    }
+}
+
+/// Last read details of every player list
+struct StatePlayerDetailShadow {
+    schedule: DetailRefreshSchedule,
+    details: HashMap<PawnIndex, StatePawnInfo>,
+}
+
+impl State for StatePlayerDetailShadow {
+    type Parameter = PlayerInterest;
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            schedule: Default::default(),
+            details: Default::default(),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+}
+
+/// All player pawns.
+/// Full details (weapons, economy, effects) will only be read for the players within the interest set.
+/// Every interest set has its own cache entry.
+#[derive(Debug, Clone)]
+pub struct StatePlayerList {
+    pub players: Vec<PlayerListEntry>,
+
+    /// Amount of players for which the full details have been read
+    pub detail_reads: usize,
+
+    /// Index within `players` by the pawn entity id
+    pawn_index: HashMap<PawnIndex, usize>,
+}
+
+/// Use the details of a player list if available (see [StatePlayerList::resolved_details]).
+/// Reads the value directly if there are no details or the value is not contained within the details.
+/// Returns the value with the confidence of the details it has been taken from (fresh if read directly).
+pub fn player_details_or_read<T>(
+    details: Option<(StatePawnInfo, FieldConfidence)>,
+    from_details: impl FnOnce(&StatePawnInfo) -> Option<T>,
+    read: impl FnOnce() -> anyhow::Result<T>,
+) -> anyhow::Result<(T, FieldConfidence)> {
+    match details.and_then(|(details, confidence)| Some((from_details(&details)?, confidence))) {
+        Some(value) => Ok(value),
+        None => Ok((read()?, FieldConfidence::Fresh)),
+    }
+}
+
+impl StatePlayerList {
+    /// Player list of already read entries (e.g. for replays or tests)
+    pub fn from_entries(players: Vec<PlayerListEntry>) -> Self {
+        let pawn_index = players
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| (entry.pawn_entity_id, index))
+            .collect();
+
+        Self {
+            players,
+            detail_reads: 0,
+            pawn_index,
+        }
+    }
+
+    pub fn entry(&self, pawn_entity_id: PawnIndex) -> Option<&PlayerListEntry> {
+        self.pawn_index
+            .get(&pawn_entity_id)
+            .and_then(|index| self.players.get(*index))
+    }
+
+    /// Details of the player pawn from a player list which has already been resolved this frame.
+    /// The player list will not be resolved if it hasn't been already, hence other states can use this
+    /// to opportunistically reuse the player list without extra memory reads.
+    /// The details are returned with their confidence as they may be stale (see [PlayerDetailBudget]).
+    /// Note: Player lists for [PlayerInterest::Players] will not be considered.
+    pub fn resolved_details(
+        states: &StateRegistry,
+        pawn_entity_id: PawnIndex,
+    ) -> Option<(StatePawnInfo, FieldConfidence)> {
+        let interests = [
+            PlayerInterest::All,
+            PlayerInterest::Team(TEAM_ID_TERRORIST),
+            PlayerInterest::Team(TEAM_ID_COUNTER_TERRORIST),
+        ];
+
+        for interest in interests {
+            let Some(player_list) = states.get::<Self>(interest) else {
+                continue;
+            };
+
+            let Some(entry) = player_list.entry(pawn_entity_id) else {
+                continue;
+            };
+
+            if let (Some(details), Some(confidence)) = (&entry.details, &entry.details_confidence) {
+                return Some((details.clone(), confidence.clone()));
+            }
+        }
+
+        None
+    }
+
+    fn read_entry(
+        states: &StateRegistry,
+        handle: EntityHandle<dyn C_CSPlayerPawn>,
+    ) -> anyhow::Result<PlayerListEntry> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let entities = states.resolve::<StateEntityList>(())?;
+        let player_pawn = entities
+            .entity_from_handle(&handle)
+            .context("entity does not exists")?
+            .value_reference(memory.view_arc())
+            .context("player pawn nullptr")?;
+
+        let controller_handle = player_pawn.m_hController()?;
+        let position = player_pawn
+            .m_pGameSceneNode()?
+            .value_reference(memory.view_arc())
+            .context("game scene node nullptr")?
+            .m_vecAbsOrigin()?;
+
+        Ok(PlayerListEntry {
+            pawn_entity_id: PawnIndex::from_handle(&handle),
+            controller_entity_id: ControllerIndex::from_valid_handle(&controller_handle),
+            team_id: player_pawn.m_iTeamNum()?,
+
+            alive: player_pawn.m_iHealth()? > 0,
+            position: nalgebra::Vector3::from_column_slice(&position),
+
+            details: None,
+            details_confidence: None,
+        })
+    }
+}
+
+impl State for StatePlayerList {
+    type Parameter = PlayerInterest;
+
+    fn create(states: &StateRegistry, interest: Self::Parameter) -> anyhow::Result<Self> {
+        let entities = states.resolve::<StateEntityList>(())?;
+        let class_index = states.resolve::<StateEntityClassIndex>(())?;
+
+        let pinned_controllers = PinnedPlayers::controllers(states)?;
+        let budget = *states.resolve::<PlayerDetailBudget>(())?;
+
+        let mut result = Self {
+            players: Vec::with_capacity(16),
+            detail_reads: 0,
+            pawn_index: HashMap::with_capacity(16),
+        };
+
+        /* (pawn handle, pinned) */
+        let mut candidates = Vec::new();
+
+        for entity_identity in class_index.entities_of_class(&entities, "C_CSPlayerPawn") {
+            let handle = entity_identity.handle::<dyn C_CSPlayerPawn>()?;
+            let entry = match Self::read_entry(states, handle) {
+                Ok(entry) => entry,
+                Err(error) => {
+                    log::debug!(
+                        "Failed to read player list entry for {}: {:#}",
+                        handle.get_entity_index(),
+                        error
+                    );
+                    continue;
+                }
+            };
+
+            let pinned = entry
+                .controller_entity_id
+                .map(|controller| pinned_controllers.contains(&controller))
+                .unwrap_or(false);
+            if pinned || interest.is_interested(entry.controller_entity_id, entry.team_id) {
+                candidates.push((handle, pinned));
+            }
+
+            result
+                .pawn_index
+                .insert(entry.pawn_entity_id, result.players.len());
+            result.players.push(entry);
+        }
+
+        let frame = states.heartbeat().frame();
+        let mut shadow = states.resolve_mut::<StatePlayerDetailShadow>(interest)?;
+        let scheduled = shadow.schedule.schedule(
+            frame,
+            &candidates
+                .iter()
+                .map(|(handle, pinned)| (PawnIndex::from_handle(handle), *pinned))
+                .collect::<Vec<_>>(),
+            budget.max_detail_reads,
+        );
+
+        for (handle, _) in candidates {
+            let pawn_entity_id = PawnIndex::from_handle(&handle);
+            let mut read_error = None;
+            if scheduled.contains(&pawn_entity_id) {
+                match states.resolve::<StatePawnInfo>(handle) {
+                    Ok(info) => {
+                        shadow.details.insert(pawn_entity_id, info.clone());
+                    }
+                    Err(error) => {
+                        shadow.details.remove(&pawn_entity_id);
+                        read_error = Some(format!("{:#}", error));
+                    }
+                }
+                result.detail_reads += 1;
+            }
+
+            let Some(index) = result.pawn_index.get(&pawn_entity_id) else {
+                continue;
+            };
+
+            let mut confidence = shadow.schedule.confidence(frame, pawn_entity_id);
+            let details = shadow
+                .details
+                .get(&pawn_entity_id)
+                .filter(|_| confidence.is_available())
+                .cloned();
+            if details.is_none() && confidence.is_available() {
+                confidence = FieldConfidence::Unavailable {
+                    reason: read_error.unwrap_or_else(|| "details not available".to_string()),
+                };
+            }
+
+            let entry = &mut result.players[*index];
+            entry.details = details;
+            entry.details_confidence = Some(confidence);
+        }
+
+        let player_pawns = result.pawn_index.keys().copied().collect::<HashSet<_>>();
+        shadow
+            .details
+            .retain(|pawn_entity_id, _| player_pawns.contains(pawn_entity_id));
+
+        Ok(result)
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        cell::Cell,
+        collections::HashMap,
+        sync::Arc,
+    };
+
+    use super::{
+        player_details_or_read,
+        DetailRefreshSchedule,
+        PlayerInterest,
+        PlayerListEntry,
+        StatePlayerList,
+    };
+    use crate::{
+        test_fixture::{
+            match_controller_handle,
+            match_fixture,
+            setup_dump_schema,
+            CountingMemoryView,
+        },
+        ControllerIndex,
+        FieldConfidence,
+        PawnIndex,
+        StatePawnInfo,
+        WeaponId,
+        TEAM_ID_COUNTER_TERRORIST,
+        TEAM_ID_TERRORIST,
+    };
+
+    /// (controller entity id, team id)
+    const PLAYERS: [(Option<ControllerIndex>, u8); 6] = [
+        (Some(ControllerIndex(1)), TEAM_ID_TERRORIST),
+        (Some(ControllerIndex(2)), TEAM_ID_TERRORIST),
+        (Some(ControllerIndex(3)), TEAM_ID_COUNTER_TERRORIST),
+        (Some(ControllerIndex(4)), TEAM_ID_COUNTER_TERRORIST),
+        (Some(ControllerIndex(5)), TEAM_ID_COUNTER_TERRORIST),
+        (None, TEAM_ID_TERRORIST),
+    ];
+
+    fn detail_reads(interest: &PlayerInterest) -> usize {
+        PLAYERS
+            .iter()
+            .filter(|(controller, team)| interest.is_interested(*controller, *team))
+            .count()
+    }
+
+    #[test]
+    fn interested_players() {
+        assert_eq!(detail_reads(&PlayerInterest::All), PLAYERS.len());
+        assert_eq!(
+            detail_reads(&PlayerInterest::Team(TEAM_ID_COUNTER_TERRORIST)),
+            3
+        );
+        assert_eq!(detail_reads(&PlayerInterest::Team(TEAM_ID_TERRORIST)), 3);
+        assert_eq!(
+            detail_reads(&PlayerInterest::Players(vec![ControllerIndex(4)])),
+            1
+        );
+        assert_eq!(detail_reads(&PlayerInterest::Players(vec![])), 0);
+    }
+
+    const MATCH_PLAYERS: usize = 6;
+
+    /// Resolve the player list of the [match_fixture] for the interest set.
+    /// Returns the player list, the amount of created pawn infos and the memory reads of the player list.
+    fn resolve_interest(interest: PlayerInterest) -> (StatePlayerList, u64, u64) {
+        let fixture = match_fixture(MATCH_PLAYERS);
+        let memory = Arc::new(CountingMemoryView::new(Arc::new(fixture.memory.clone())));
+        let states = fixture.states(memory.clone());
+
+        let reads_before = memory.reads();
+        let player_list = states.resolve::<StatePlayerList>(interest).unwrap().clone();
+        let reads = memory.reads() - reads_before;
+
+        let pawn_infos = states.statistics().counters::<StatePawnInfo>().creations;
+        (player_list, pawn_infos, reads)
+    }
+
+    #[test]
+    fn reduced_reads() {
+        setup_dump_schema();
+
+        let (all, all_pawn_infos, all_reads) = resolve_interest(PlayerInterest::All);
+        assert_eq!(all.players.len(), MATCH_PLAYERS);
+        assert_eq!(all.detail_reads, MATCH_PLAYERS);
+        assert_eq!(all_pawn_infos, MATCH_PLAYERS as u64);
+        assert!(all.players.iter().all(|entry| entry.details.is_some()));
+
+        /* positions and alive state for everyone, details only for the counter terrorists */
+        let (team, team_pawn_infos, team_reads) =
+            resolve_interest(PlayerInterest::Team(TEAM_ID_COUNTER_TERRORIST));
+        assert_eq!(team.players.len(), MATCH_PLAYERS);
+        assert!(team.players.iter().all(|entry| entry.alive));
+        assert_eq!(team.detail_reads, MATCH_PLAYERS / 2);
+        assert_eq!(team_pawn_infos, (MATCH_PLAYERS / 2) as u64);
+        assert!(team
+            .players
+            .iter()
+            .all(|entry| entry.details.is_some() == (entry.team_id == TEAM_ID_COUNTER_TERRORIST)));
+        assert!(team_reads < all_reads, "{} < {}", team_reads, all_reads);
+
+        let tracked = ControllerIndex::from_handle(&match_controller_handle(3));
+        let (single, single_pawn_infos, single_reads) =
+            resolve_interest(PlayerInterest::Players(vec![tracked]));
+        assert_eq!(single.players.len(), MATCH_PLAYERS);
+        assert_eq!(single.detail_reads, 1);
+        assert_eq!(single_pawn_infos, 1);
+        assert_eq!(
+            single
+                .players
+                .iter()
+                .filter(|entry| entry.details.is_some())
+                .map(|entry| entry.controller_entity_id)
+                .collect::<Vec<_>>(),
+            vec![Some(tracked)]
+        );
+        assert!(
+            single_reads < team_reads,
+            "{} < {}",
+            single_reads,
+            team_reads
+        );
+
+        let (none, none_pawn_infos, none_reads) = resolve_interest(PlayerInterest::Players(vec![]));
+        assert_eq!(none.players.len(), MATCH_PLAYERS);
+        assert_eq!((none.detail_reads, none_pawn_infos), (0, 0));
+        assert!(
+            none_reads < single_reads,
+            "{} < {}",
+            none_reads,
+            single_reads
+        );
+    }
+
+    fn pawn_info(pawn: PawnIndex, name: Option<&str>) -> StatePawnInfo {
+        StatePawnInfo {
+            controller_entity_id: Some(ControllerIndex(pawn.0 + 100)),
+            pawn_entity_id: pawn,
+            team_id: TEAM_ID_COUNTER_TERRORIST,
+
+            player_health: 64,
+            player_armor: 100,
+            player_has_helmet: true,
+            player_has_defuser: true,
+            player_has_bomb: false,
+            player_name: name.map(str::to_string),
+            weapon: WeaponId::Knife,
+            weapon_current_ammo: -1,
+            weapon_reserve_ammo: 0,
+            player_flashtime: 0.0,
+
+            player_has_flash: 0,
+            player_has_smoke: false,
+            player_has_hegrenade: false,
+            player_has_molotov: false,
+            player_has_incendiary: false,
+            player_has_decoy: false,
+
+            position: Default::default(),
+            view_angles: Default::default(),
+
+            vitals_confidence: FieldConfidence::Fresh,
+            position_confidence: FieldConfidence::Fresh,
+            weapon_confidence: FieldConfidence::Fresh,
+            economy_confidence: FieldConfidence::Fresh,
+
+            just_landed: false,
+            fall_damage: 0.0,
+
+            locally_muted: None,
+        }
+    }
+
+    /// Pawn 10 with details, pawn 11 without and pawn 12 with details but without a name
+    fn player_list() -> StatePlayerList {
+        let mut list = StatePlayerList {
+            players: Vec::new(),
+            detail_reads: 0,
+            pawn_index: HashMap::new(),
+        };
+
+        for (pawn, details) in [
+            (
+                PawnIndex(10),
+                Some(pawn_info(PawnIndex(10), Some("defuser"))),
+            ),
+            (PawnIndex(11), None),
+            (PawnIndex(12), Some(pawn_info(PawnIndex(12), None))),
+        ] {
+            list.pawn_index.insert(pawn, list.players.len());
+            list.players.push(PlayerListEntry {
+                pawn_entity_id: pawn,
+                controller_entity_id: None,
+                team_id: TEAM_ID_COUNTER_TERRORIST,
+                alive: true,
+                position: Default::default(),
+                details_confidence: details.as_ref().map(|_| FieldConfidence::Fresh),
+                details,
+            });
+        }
+
+        list
+    }
+
+    /// Budget of two detail reads per frame with one of five players being pinned
+    #[test]
+    fn pinned_budget() {
+        let candidates = (1..=5)
+            .map(|pawn| (PawnIndex(pawn), pawn == 3))
+            .collect::<Vec<_>>();
+
+        let mut schedule = DetailRefreshSchedule::default();
+        for frame in 1..=12 {
+            let scheduled = schedule.schedule(frame, &candidates, Some(1));
+            assert_eq!(scheduled.len(), 2);
+            assert!(scheduled.contains(&PawnIndex(3)));
+            assert_eq!(
+                schedule.confidence(frame, PawnIndex(3)),
+                FieldConfidence::Fresh
+            );
+
+            if frame < 4 {
+                /* not every player has been read yet */
+                continue;
+            }
+
+            let others = candidates
+                .iter()
+                .filter(|(_, pinned)| !*pinned)
+                .map(|(pawn, _)| schedule.confidence(frame, *pawn))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                others
+                    .iter()
+                    .filter(|confidence| confidence.is_fresh())
+                    .count(),
+                1
+            );
+            for confidence in others.iter().filter(|confidence| !confidence.is_fresh()) {
+                assert!(
+                    matches!(confidence, FieldConfidence::Stale { frames } if *frames < 4),
+                    "{:?}",
+                    confidence
+                );
+            }
+        }
+
+        /* pinned players will be read even without any budget */
+        let scheduled = schedule.schedule(13, &candidates, Some(0));
+        assert_eq!(
+            scheduled.into_iter().collect::<Vec<_>>(),
+            vec![PawnIndex(3)]
+        );
+
+        /* no budget limit */
+        let mut schedule = DetailRefreshSchedule::default();
+        assert_eq!(schedule.schedule(1, &candidates, None).len(), 5);
+        assert!(candidates
+            .iter()
+            .all(|(pawn, _)| schedule.confidence(1, *pawn).is_fresh()));
+    }
+
+    #[test]
+    fn details_or_read() {
+        let list = player_list();
+        assert_eq!(
+            list.entry(PawnIndex(11)).map(|entry| entry.pawn_entity_id),
+            Some(PawnIndex(11))
+        );
+        assert!(list.entry(PawnIndex(13)).is_none());
+
+        let direct_reads = Cell::new(0);
+        let lookup = |pawn: PawnIndex| {
+            player_details_or_read(
+                list.entry(pawn).and_then(|entry| {
+                    Some((entry.details.clone()?, entry.details_confidence.clone()?))
+                }),
+                |details| {
+                    details
+                        .player_name
+                        .clone()
+                        .map(|name| (name, details.player_health))
+                },
+                || {
+                    direct_reads.set(direct_reads.get() + 1);
+                    Ok(("direct".to_string(), 100))
+                },
+            )
+            .unwrap()
+        };
+
+        /* served by the player list */
+        assert_eq!(
+            lookup(PawnIndex(10)),
+            (("defuser".to_string(), 64), FieldConfidence::Fresh)
+        );
+        assert_eq!(direct_reads.get(), 0);
+
+        /* no details, missing name and unknown pawn */
+        for pawn in [PawnIndex(11), PawnIndex(12), PawnIndex(13)] {
+            assert_eq!(
+                lookup(pawn),
+                (("direct".to_string(), 100), FieldConfidence::Fresh)
+            );
+        }
+        assert_eq!(direct_reads.get(), 3);
+
+        /* the confidence of stale details will be retained */
+        let stale = player_details_or_read(
+            Some((
+                pawn_info(PawnIndex(10), Some("defuser")),
+                FieldConfidence::Stale { frames: 3 },
+            )),
+            |details| details.player_name.clone(),
+            || unreachable!(),
+        )
+        .unwrap();
+        assert_eq!(
+            stale,
+            ("defuser".to_string(), FieldConfidence::Stale { frames: 3 })
+        );
+
+        /* player list not resolved this frame */
+        assert!(
+            player_details_or_read(None, |_| Some(()), || anyhow::bail!("read failed")).is_err()
+        );
+    }
+}
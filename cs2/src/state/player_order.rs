@@ -0,0 +1,93 @@
+use crate::{
+    ControllerIndex,
+    PlayerKey,
+    TEAM_ID_COUNTER_TERRORIST,
+    TEAM_ID_TERRORIST,
+};
+
+/// Stable sort key for players.
+///
+/// Players are ordered by team (terrorists, counter terrorists, others) and then by their steam id.
+/// Bots (steam id zero) are ordered after all players by their entity index.
+/// In contrast to the entity index the order does not change when a player reconnects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PlayerOrderKey {
+    team_rank: u8,
+    is_bot: bool,
+    steam_id: u64,
+
+    pub entity_index: ControllerIndex,
+}
+
+impl PlayerOrderKey {
+    pub fn new(team_id: u8, steam_id: u64, entity_index: ControllerIndex) -> Self {
+        Self {
+            team_rank: match team_id {
+                TEAM_ID_TERRORIST => 0,
+                TEAM_ID_COUNTER_TERRORIST => 1,
+                _ => 2,
+            },
+            is_bot: steam_id == 0,
+            steam_id,
+            entity_index,
+        }
+    }
+
+    pub fn player_key(&self) -> PlayerKey {
+        PlayerKey::new(self.steam_id, self.entity_index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PlayerOrderKey;
+    use crate::{
+        ControllerIndex,
+        TEAM_ID_COUNTER_TERRORIST,
+        TEAM_ID_TERRORIST,
+    };
+
+    /// (name, team, steam id, entity index)
+    type Player = (&'static str, u8, u64, u32);
+
+    fn ordered_names(players: &[Player]) -> Vec<&'static str> {
+        let mut players = players.to_vec();
+        players.sort_by_key(|(_, team_id, steam_id, entity_index)| {
+            PlayerOrderKey::new(*team_id, *steam_id, ControllerIndex(*entity_index))
+        });
+        players.into_iter().map(|(name, ..)| name).collect()
+    }
+
+    #[test]
+    fn reconnect() {
+        let players = [
+            ("ct_a", TEAM_ID_COUNTER_TERRORIST, 76561198000000003, 1),
+            ("t_a", TEAM_ID_TERRORIST, 76561198000000002, 2),
+            ("bot", TEAM_ID_TERRORIST, 0, 3),
+            ("t_b", TEAM_ID_TERRORIST, 76561198000000001, 4),
+            ("spectator", 1, 76561198000000004, 5),
+            ("ct_b", TEAM_ID_COUNTER_TERRORIST, 76561198000000005, 6),
+        ];
+
+        let before = ordered_names(&players);
+        assert_eq!(before, ["t_b", "t_a", "bot", "ct_a", "ct_b", "spectator"]);
+
+        /* t_a and ct_a reconnect and receive new entity slots */
+        let mut reconnected = players;
+        reconnected[0].3 = 8;
+        reconnected[1].3 = 7;
+        reconnected.swap(0, 5);
+        assert_eq!(ordered_names(&reconnected), before);
+    }
+
+    #[test]
+    fn bots_by_entity_index() {
+        let players = [
+            ("bot_b", TEAM_ID_COUNTER_TERRORIST, 0, 9),
+            ("bot_a", TEAM_ID_COUNTER_TERRORIST, 0, 2),
+            ("player", TEAM_ID_COUNTER_TERRORIST, 76561198000000001, 5),
+        ];
+
+        assert_eq!(ordered_names(&players), ["player", "bot_a", "bot_b"]);
+    }
+}
@@ -0,0 +1,309 @@
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use super::{
+    MatchEvent,
+    MatchSnapshot,
+    StateMatchEvents,
+};
+
+/// Max amount of rounds kept within the [StateRoundHistory]
+const ROUND_HISTORY_CAPACITY: usize = 64;
+
+/// Identity of a player captured at the time of an event.
+/// Remains valid after the player disconnected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundPlayer {
+    pub player_name: String,
+
+    /// None for bots or if the player could not be found within the snapshot
+    pub steam_id: Option<u64>,
+}
+
+impl RoundPlayer {
+    fn capture(player_name: &str, snapshot: Option<&MatchSnapshot>) -> Self {
+        let steam_id = snapshot
+            .and_then(|snapshot| {
+                snapshot
+                    .players
+                    .iter()
+                    .find(|(_, player)| player.player_name == player_name)
+            })
+            .map(|(_, player)| player.steam_id)
+            .filter(|steam_id| *steam_id != 0);
+
+        Self {
+            player_name: player_name.to_string(),
+            steam_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundBombOutcome {
+    Planted,
+    Defused,
+    Detonated,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundResult {
+    /// Round number starting with 1
+    pub round_number: i32,
+    pub score_terrorists: Option<i32>,
+    pub score_counter_terrorists: Option<i32>,
+
+    /// Bomb site and final bomb state.
+    /// None if the bomb has not been planted this round.
+    pub bomb: Option<(u8, RoundBombOutcome)>,
+
+    pub planter: Option<RoundPlayer>,
+    pub defuser: Option<RoundPlayer>,
+}
+
+/// Reconstructs the round results from the match events
+#[derive(Debug, Clone, Default)]
+pub struct RoundHistory {
+    pub rounds: Vec<RoundResult>,
+
+    bomb: Option<(u8, RoundBombOutcome)>,
+    planter: Option<RoundPlayer>,
+    defuser: Option<RoundPlayer>,
+}
+
+impl RoundHistory {
+    /// Apply the events of a frame.
+    /// The snapshot is used to capture the identities of the involved players.
+    pub fn push_events(&mut self, events: &[MatchEvent], snapshot: Option<&MatchSnapshot>) {
+        for event in events {
+            match event {
+                MatchEvent::RoundStart { .. } => self.reset_round(),
+                MatchEvent::BombPlanted {
+                    bomb_site,
+                    planter_name,
+                    ..
+                } => {
+                    self.bomb = Some((*bomb_site, RoundBombOutcome::Planted));
+                    self.planter = planter_name
+                        .as_deref()
+                        .map(|name| RoundPlayer::capture(name, snapshot));
+                }
+                MatchEvent::BombDefused {
+                    bomb_site,
+                    defuser_name,
+                    ..
+                } => {
+                    self.bomb = Some((*bomb_site, RoundBombOutcome::Defused));
+                    self.defuser = defuser_name
+                        .as_deref()
+                        .map(|name| RoundPlayer::capture(name, snapshot));
+                }
+                MatchEvent::BombDetonated { bomb_site } => {
+                    self.bomb = Some((*bomb_site, RoundBombOutcome::Detonated));
+                }
+                MatchEvent::RoundEnd {
+                    round_number,
+                    score_terrorists,
+                    score_counter_terrorists,
+                } => {
+                    if self.rounds.len() >= ROUND_HISTORY_CAPACITY {
+                        self.rounds.remove(0);
+                    }
+
+                    self.rounds.push(RoundResult {
+                        round_number: *round_number,
+                        score_terrorists: *score_terrorists,
+                        score_counter_terrorists: *score_counter_terrorists,
+
+                        bomb: self.bomb,
+                        planter: self.planter.clone(),
+                        defuser: self.defuser.clone(),
+                    });
+                    self.reset_round();
+                }
+                MatchEvent::HalfTimeStarted { .. }
+                | MatchEvent::OvertimeStarted { .. }
+                | MatchEvent::LastRoundOfHalf { .. }
+                | MatchEvent::MatchPoint { .. }
+                | MatchEvent::Kill { .. } => {}
+            }
+        }
+    }
+
+    fn reset_round(&mut self) {
+        self.bomb = None;
+        self.planter = None;
+        self.defuser = None;
+    }
+}
+
+/// Results of the previous rounds.
+/// The history will only be recorded while this state is being resolved every frame.
+pub struct StateRoundHistory {
+    pub history: RoundHistory,
+}
+
+impl State for StateRoundHistory {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self {
+            history: Default::default(),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+
+    fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+        let events = states.resolve::<StateMatchEvents>(())?;
+        self.history.push_events(&events.events, events.snapshot());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        RoundBombOutcome,
+        RoundHistory,
+        RoundPlayer,
+    };
+    use crate::{
+        BombSnapshot,
+        ControllerIndex,
+        MatchEvent,
+        MatchSnapshot,
+        PlayerSnapshot,
+    };
+
+    fn snapshot(players: &[(&str, u64)]) -> MatchSnapshot {
+        MatchSnapshot {
+            rounds_played: Some(3),
+            freeze_period: Some(false),
+            score_terrorists: Some(2),
+            score_counter_terrorists: Some(1),
+
+            bomb: BombSnapshot::NotPlanted,
+            bomb_carrier_name: None,
+            bomb_defuser_name: None,
+
+            players: players
+                .iter()
+                .enumerate()
+                .map(|(index, (name, steam_id))| {
+                    (
+                        ControllerIndex(index as u32 + 1),
+                        PlayerSnapshot {
+                            player_name: name.to_string(),
+                            steam_id: *steam_id,
+                            alive: true,
+                            round_kills: 0,
+                            round_kills_headshot: 0,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn round_end(round_number: i32) -> MatchEvent {
+        MatchEvent::RoundEnd {
+            round_number,
+            score_terrorists: Some(3),
+            score_counter_terrorists: Some(1),
+        }
+    }
+
+    #[test]
+    fn planter_and_defuser() {
+        let mut history = RoundHistory::default();
+        let players = snapshot(&[("planter", 76561198000000001), ("defuser", 0)]);
+
+        history.push_events(
+            &[MatchEvent::BombPlanted {
+                bomb_site: 1,
+                time_detonation: 40.0,
+                planted_at_round_remaining: None,
+                planted_at_server_time: None,
+                planter_name: Some("planter".to_string()),
+            }],
+            Some(&players),
+        );
+        history.push_events(
+            &[
+                MatchEvent::BombDefused {
+                    bomb_site: 1,
+                    time_detonation: Some(3.0),
+                    defuser_name: Some("defuser".to_string()),
+                },
+                round_end(4),
+            ],
+            Some(&players),
+        );
+
+        let round = &history.rounds[0];
+        assert_eq!(round.round_number, 4);
+        assert_eq!(round.bomb, Some((1, RoundBombOutcome::Defused)));
+        assert_eq!(
+            round.planter,
+            Some(RoundPlayer {
+                player_name: "planter".to_string(),
+                steam_id: Some(76561198000000001),
+            })
+        );
+        assert_eq!(
+            round.defuser,
+            Some(RoundPlayer {
+                player_name: "defuser".to_string(),
+                steam_id: None,
+            })
+        );
+
+        /* the next round does not inherit the bomb state */
+        history.push_events(&[round_end(5)], Some(&players));
+        assert_eq!(history.rounds[1].bomb, None);
+        assert_eq!(history.rounds[1].planter, None);
+    }
+
+    #[test]
+    fn planter_disconnected() {
+        let mut history = RoundHistory::default();
+        history.push_events(
+            &[MatchEvent::BombPlanted {
+                bomb_site: 0,
+                time_detonation: 40.0,
+                planted_at_round_remaining: None,
+                planted_at_server_time: None,
+                planter_name: Some("planter".to_string()),
+            }],
+            Some(&snapshot(&[("planter", 76561198000000001)])),
+        );
+
+        /* the planter left before the bomb detonated */
+        let players = snapshot(&[]);
+        history.push_events(
+            &[MatchEvent::BombDetonated { bomb_site: 0 }],
+            Some(&players),
+        );
+        history.push_events(&[round_end(4)], Some(&players));
+
+        let round = &history.rounds[0];
+        assert_eq!(round.bomb, Some((0, RoundBombOutcome::Detonated)));
+        assert_eq!(
+            round
+                .planter
+                .as_ref()
+                .map(|planter| planter.player_name.as_str()),
+            Some("planter")
+        );
+        assert_eq!(
+            round.planter.as_ref().and_then(|planter| planter.steam_id),
+            Some(76561198000000001)
+        );
+    }
+}
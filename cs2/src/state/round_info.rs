@@ -0,0 +1,364 @@
+use cs2_schema_generated::cs2::client::C_CSGameRules;
+use raw_struct::Copy;
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use super::{
+    server_time_remaining,
+    PlantedC4,
+    PlantedC4State,
+    StateGameRules,
+    StateGlobals,
+};
+
+/// State of the round timer as shown by the in-game HUD
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RoundClock {
+    /// The round is live
+    Running {
+        /// Time (in seconds) until the round time runs out
+        remaining: f32,
+    },
+
+    /// The bomb has been planted and the round timer has been replaced by the bomb countdown
+    StoppedBombPlanted,
+
+    FreezeTime {
+        /// Time (in seconds) until the freeze time ends
+        remaining: f32,
+    },
+
+    /// The round has been decided
+    Over,
+}
+
+/// Values of the game rules required to derive the [RoundClock]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoundClockInput {
+    pub freeze_period: bool,
+    pub bomb_planted: bool,
+
+    /// Winner of the current round (zero while the round has not been decided)
+    pub round_win_status: i32,
+
+    /// Server time when the round went or will go live (end of the freeze time)
+    pub round_start_time: f32,
+
+    /// Length of the round (in seconds)
+    pub round_time: i32,
+}
+
+impl RoundClockInput {
+    pub fn read(rules: &Copy<dyn C_CSGameRules>) -> anyhow::Result<Self> {
+        Ok(Self {
+            freeze_period: rules.m_bFreezePeriod()?,
+            bomb_planted: rules.m_bBombPlanted()?,
+            round_win_status: rules.m_iRoundWinStatus()?,
+            round_start_time: rules.m_fRoundStartTime()?.m_Value()?,
+            round_time: rules.m_iRoundTime()?,
+        })
+    }
+}
+
+impl RoundClock {
+    pub fn from_rules(input: &RoundClockInput, server_time: f32) -> Self {
+        if input.round_win_status != 0 {
+            Self::Over
+        } else if input.bomb_planted {
+            Self::StoppedBombPlanted
+        } else if input.freeze_period {
+            Self::FreezeTime {
+                remaining: server_time_remaining(input.round_start_time, server_time).max(0.0),
+            }
+        } else {
+            Self::Running {
+                remaining: server_time_remaining(
+                    input.round_start_time + input.round_time as f32,
+                    server_time,
+                )
+                .max(0.0),
+            }
+        }
+    }
+
+    /// Time remaining on the round clock.
+    /// None if the clock is not visible (bomb planted or round over).
+    pub fn remaining(&self) -> Option<f32> {
+        match self {
+            Self::Running { remaining } | Self::FreezeTime { remaining } => Some(*remaining),
+            Self::StoppedBombPlanted | Self::Over => None,
+        }
+    }
+    /// Time which was remaining on the round clock when the bomb has been planted at `plant_time` (server time).
+    /// The round clock stops with the plant, hence this can not be derived from the current clock.
+    /// None if the bomb has been planted within the freeze time (e.g. retake servers).
+    pub fn remaining_at_plant(input: &RoundClockInput, plant_time: f32) -> Option<f32> {
+        if plant_time < input.round_start_time {
+            return None;
+        }
+
+        let input = RoundClockInput {
+            freeze_period: false,
+            bomb_planted: false,
+            round_win_status: 0,
+            ..*input
+        };
+        Self::from_rules(&input, plant_time).remaining()
+    }
+}
+
+/// Information about the current round
+pub struct StateRoundInfo {
+    /// Current round number starting with 1.
+    /// None if there are no game rules (e.g. when not connected to any server).
+    pub round_number: Option<i32>,
+
+    /// The bomb case is derived from the game rules and therefore
+    /// independent of the planted C4 entity being found this frame.
+    /// Reports [RoundClock::Over] if there are no game rules.
+    pub round_clock: RoundClock,
+}
+
+impl State for StateRoundInfo {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let game_rules = states.resolve::<StateGameRules>(())?;
+        let Some(rules) = &game_rules.rules else {
+            return Ok(Self {
+                round_number: None,
+                round_clock: RoundClock::Over,
+            });
+        };
+
+        let globals = states.resolve::<StateGlobals>(())?;
+        let input = RoundClockInput::read(rules)?;
+
+        Ok(Self {
+            round_number: Some(rules.m_totalRoundsPlayed()? + 1),
+            round_clock: RoundClock::from_rules(&input, globals.server_time()?),
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundPhase {
+    Warmup,
+    FreezeTime,
+    Live,
+    BombPlanted,
+    RoundOver,
+}
+
+impl RoundPhase {
+    /// The warmup takes precedence over the round clock
+    pub fn from_clock(warmup_period: bool, clock: &RoundClock) -> Self {
+        if warmup_period {
+            return Self::Warmup;
+        }
+
+        match clock {
+            RoundClock::Running { .. } => Self::Live,
+            RoundClock::StoppedBombPlanted => Self::BombPlanted,
+            RoundClock::FreezeTime { .. } => Self::FreezeTime,
+            RoundClock::Over => Self::RoundOver,
+        }
+    }
+}
+
+/// Remaining time of the round countdown.
+/// While the bomb is planted the countdown is the time until the detonation.
+pub fn round_countdown(clock: &RoundClock, bomb_time_detonation: Option<f32>) -> Option<f32> {
+    match clock {
+        RoundClock::StoppedBombPlanted => bomb_time_detonation.map(|time| time.max(0.0)),
+        clock => clock.remaining(),
+    }
+}
+
+/// Phase of the current round with a single countdown
+pub struct StateRound {
+    /// Reports [RoundPhase::Warmup] if there are no game rules (e.g. on the main menu)
+    pub phase: RoundPhase,
+
+    /// Time (in seconds) remaining of the freeze time, the round or the bomb timer (see [round_countdown]).
+    /// None if unknown or the round is over.
+    pub round_time_remaining: Option<f32>,
+
+    /// Current round number starting with 1.
+    /// Zero if there are no game rules.
+    pub round_number: u16,
+
+    pub bomb_planted: bool,
+}
+
+impl State for StateRound {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let game_rules = states.resolve::<StateGameRules>(())?;
+        let Some(rules) = &game_rules.rules else {
+            return Ok(Self {
+                phase: RoundPhase::Warmup,
+                round_time_remaining: None,
+                round_number: 0,
+                bomb_planted: false,
+            });
+        };
+
+        let round_info = states.resolve::<StateRoundInfo>(())?;
+        let bomb_time_detonation = if round_info.round_clock == RoundClock::StoppedBombPlanted {
+            states
+                .resolve::<PlantedC4>(())
+                .ok()
+                .and_then(|planted_c4| match planted_c4.state {
+                    PlantedC4State::Active { time_detonation } => Some(time_detonation),
+                    _ => None,
+                })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            phase: RoundPhase::from_clock(rules.m_bWarmupPeriod()?, &round_info.round_clock),
+            round_time_remaining: round_countdown(&round_info.round_clock, bomb_time_detonation),
+            round_number: round_info
+                .round_number
+                .map(|round_number| round_number.clamp(0, u16::MAX as i32) as u16)
+                .unwrap_or(0),
+            bomb_planted: rules.m_bBombPlanted()?,
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        round_countdown,
+        RoundClock,
+        RoundClockInput,
+        RoundPhase,
+    };
+
+    const INPUT: RoundClockInput = RoundClockInput {
+        freeze_period: false,
+        bomb_planted: false,
+        round_win_status: 0,
+        round_start_time: 100.0,
+        round_time: 115,
+    };
+
+    #[test]
+    fn clock() {
+        assert_eq!(
+            RoundClock::from_rules(
+                &RoundClockInput {
+                    freeze_period: true,
+                    ..INPUT
+                },
+                90.0
+            ),
+            RoundClock::FreezeTime { remaining: 10.0 }
+        );
+        assert_eq!(
+            RoundClock::from_rules(&INPUT, 200.0),
+            RoundClock::Running { remaining: 15.0 }
+        );
+        assert_eq!(
+            RoundClock::from_rules(&INPUT, 300.0),
+            RoundClock::Running { remaining: 0.0 }
+        );
+    }
+
+    #[test]
+    fn bomb_planted() {
+        let planted = RoundClockInput {
+            bomb_planted: true,
+            ..INPUT
+        };
+        assert_eq!(
+            RoundClock::from_rules(&planted, 200.0),
+            RoundClock::StoppedBombPlanted
+        );
+        assert_eq!(RoundClock::from_rules(&planted, 200.0).remaining(), None);
+
+        /* bomb detonated or defused */
+        assert_eq!(
+            RoundClock::from_rules(
+                &RoundClockInput {
+                    round_win_status: 2,
+                    ..planted
+                },
+                200.0
+            ),
+            RoundClock::Over
+        );
+    }
+
+    #[test]
+    fn remaining_at_plant() {
+        /* planted with 0:07 on the clock */
+        let planted = RoundClockInput {
+            bomb_planted: true,
+            ..INPUT
+        };
+        assert_eq!(RoundClock::remaining_at_plant(&planted, 208.0), Some(7.0));
+        assert_eq!(RoundClock::remaining_at_plant(&planted, 100.0), Some(115.0));
+
+        /* planted within the freeze time */
+        assert_eq!(
+            RoundClock::remaining_at_plant(
+                &RoundClockInput {
+                    freeze_period: true,
+                    ..planted
+                },
+                95.0
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn phase_and_countdown() {
+        let freeze_time = RoundClock::FreezeTime { remaining: 10.0 };
+        assert_eq!(
+            RoundPhase::from_clock(false, &freeze_time),
+            RoundPhase::FreezeTime
+        );
+        assert_eq!(
+            RoundPhase::from_clock(true, &freeze_time),
+            RoundPhase::Warmup
+        );
+        assert_eq!(round_countdown(&freeze_time, None), Some(10.0));
+
+        let running = RoundClock::Running { remaining: 15.0 };
+        assert_eq!(RoundPhase::from_clock(false, &running), RoundPhase::Live);
+        assert_eq!(round_countdown(&running, Some(30.0)), Some(15.0));
+
+        /* the countdown switches to the bomb timer */
+        let planted = RoundClock::StoppedBombPlanted;
+        assert_eq!(
+            RoundPhase::from_clock(false, &planted),
+            RoundPhase::BombPlanted
+        );
+        assert_eq!(round_countdown(&planted, Some(30.0)), Some(30.0));
+        assert_eq!(round_countdown(&planted, Some(-0.5)), Some(0.0));
+        assert_eq!(round_countdown(&planted, None), None);
+
+        assert_eq!(
+            RoundPhase::from_clock(false, &RoundClock::Over),
+            RoundPhase::RoundOver
+        );
+        assert_eq!(round_countdown(&RoundClock::Over, Some(30.0)), None);
+    }
+}
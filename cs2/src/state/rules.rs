@@ -0,0 +1,66 @@
+use anyhow::Context;
+use cs2_schema_generated::cs2::client::{
+    C_CSGameRules,
+    C_CSGameRulesProxy,
+};
+use raw_struct::Copy;
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    CEntityIdentityEx,
+    CachedEntityLocator,
+    ClassNameCache,
+    EntityClassFilter,
+    StateCS2Memory,
+    StateEntityClassIndex,
+    StateEntityList,
+};
+
+struct GameRulesProxyClass;
+
+impl EntityClassFilter for GameRulesProxyClass {
+    const CLASS_NAME: &'static str = "C_CSGameRulesProxy";
+}
+
+/// The current game rules
+pub struct StateGameRules {
+    /// Will be None if there are no game rules (e.g. when not connected to any server)
+    pub rules: Option<Copy<dyn C_CSGameRules>>,
+}
+
+impl State for StateGameRules {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let entities = states.resolve::<StateEntityList>(())?;
+        let class_name_cache = states.resolve::<ClassNameCache>(())?;
+        let class_index = states.resolve::<StateEntityClassIndex>(())?;
+
+        let proxies = states
+            .resolve_mut::<CachedEntityLocator<GameRulesProxyClass>>(())?
+            .locate(&entities, &class_name_cache, &class_index)
+            .context("locate game rules proxy")?;
+
+        if let Some(entity_identity) = proxies.first() {
+            let rules = entity_identity
+                .entity_ptr::<dyn C_CSGameRulesProxy>()?
+                .value_reference(memory.view_arc())
+                .context("game rules proxy nullptr")?
+                .m_pGameRules()?
+                .value_copy(memory.view())?;
+
+            return Ok(Self { rules });
+        }
+
+        Ok(Self { rules: None })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
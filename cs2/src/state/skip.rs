@@ -0,0 +1,22 @@
+/// Downgrade the failure of reading a single entity instead of failing the whole state.
+///
+/// Entities may be torn down while being read (e.g. during round transitions), hence reads of
+/// individual entities should not fail states which cover multiple entities.
+/// Failures of the core dependencies (e.g. [crate::StateCS2Memory] or [crate::StateEntityList])
+/// should still be propagated.
+pub trait ResultSkipExt<T> {
+    /// Log the error and return None
+    fn ok_or_skip(self, what: &str) -> Option<T>;
+}
+
+impl<T> ResultSkipExt<T> for anyhow::Result<T> {
+    fn ok_or_skip(self, what: &str) -> Option<T> {
+        match self {
+            Ok(value) => Some(value),
+            Err(error) => {
+                log::trace!("Skipping {}: {:#}", what, error);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::Context;
+use cs2_schema_generated::cs2::client::{
+    CCSPlayerController,
+    C_BaseEntity,
+    C_Team,
+};
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    CEntityIdentityEx,
+    CachedEntityLocator,
+    ClassNameCache,
+    EntityClassFilter,
+    StateCS2Memory,
+    StateEntityClassIndex,
+    StateEntityList,
+    StatePlayerControllers,
+};
+
+pub const TEAM_ID_TERRORIST: u8 = 2;
+pub const TEAM_ID_COUNTER_TERRORIST: u8 = 3;
+
+/// Amount of alive players per team
+#[derive(Debug, Clone, Default)]
+pub struct StateAlivePlayerCount {
+    pub terrorists: u32,
+    pub counter_terrorists: u32,
+}
+
+impl State for StateAlivePlayerCount {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let controllers = states.resolve::<StatePlayerControllers>(())?;
+
+        let mut result = Self::default();
+        for entry in controllers.instances.iter() {
+            let Some(controller) = entry.instance.value_reference(memory.view_arc()) else {
+                continue;
+            };
+
+            if !controller.m_bPawnIsAlive()? {
+                continue;
+            }
+
+            match controller.m_iTeamNum()? {
+                TEAM_ID_TERRORIST => result.terrorists += 1,
+                TEAM_ID_COUNTER_TERRORIST => result.counter_terrorists += 1,
+                _ => {}
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+struct TeamClass;
+
+impl EntityClassFilter for TeamClass {
+    const CLASS_NAME: &'static str = "C_CSTeam";
+}
+
+/// Current score of each team.
+/// Scores are None if the team entity could not be found.
+#[derive(Debug, Clone, Default)]
+pub struct StateTeamScores {
+    pub terrorists: Option<i32>,
+    pub counter_terrorists: Option<i32>,
+}
+
+impl State for StateTeamScores {
+    type Parameter = ();
+
+    fn create(states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        let memory = states.resolve::<StateCS2Memory>(())?;
+        let entities = states.resolve::<StateEntityList>(())?;
+        let class_name_cache = states.resolve::<ClassNameCache>(())?;
+        let class_index = states.resolve::<StateEntityClassIndex>(())?;
+
+        let teams = states
+            .resolve_mut::<CachedEntityLocator<TeamClass>>(())?
+            .locate(&entities, &class_name_cache, &class_index)?;
+
+        let mut result = Self::default();
+        for entity_identity in teams {
+            let team = entity_identity
+                .entity_ptr::<dyn C_Team>()?
+                .value_reference(memory.view_arc())
+                .context("team entity nullptr")?;
+
+            match team.m_iTeamNum()? {
+                TEAM_ID_TERRORIST => result.terrorists = Some(team.m_iScore()?),
+                TEAM_ID_COUNTER_TERRORIST => result.counter_terrorists = Some(team.m_iScore()?),
+                _ => {}
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    map_data::StateMapData,
+    CEntityIdentityEx,
+    PawnIndex,
+    PlayerPawnState,
+    ResultSkipExt,
+    StateCurrentMap,
+    StateEntityClassIndex,
+    StateEntityList,
+    StatePawnInfo,
+};
+
+/// Amount of utility required for a standard site execute per map and bomb site
+const SITE_EXECUTE_REQUIREMENTS: &str =
+    include_str!("../../resources/site_execute_requirements.json");
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UtilityCounts {
+    pub smokes: u32,
+    pub flashes: u32,
+    pub he_grenades: u32,
+
+    /// Molotovs and incendiary grenades
+    pub molotovs: u32,
+    pub decoys: u32,
+}
+
+impl UtilityCounts {
+    fn add(&mut self, other: &Self) {
+        self.smokes += other.smokes;
+        self.flashes += other.flashes;
+        self.he_grenades += other.he_grenades;
+        self.molotovs += other.molotovs;
+        self.decoys += other.decoys;
+    }
+
+    /// Utility which is required but not available
+    pub fn missing(&self, required: &Self) -> Self {
+        Self {
+            smokes: required.smokes.saturating_sub(self.smokes),
+            flashes: required.flashes.saturating_sub(self.flashes),
+            he_grenades: required.he_grenades.saturating_sub(self.he_grenades),
+            molotovs: required.molotovs.saturating_sub(self.molotovs),
+            decoys: required.decoys.saturating_sub(self.decoys),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn from_pawn_info(pawn_info: &StatePawnInfo) -> Self {
+        Self {
+            smokes: pawn_info.player_has_smoke as u32,
+            flashes: pawn_info.player_has_flash,
+            he_grenades: pawn_info.player_has_hegrenade as u32,
+            molotovs: pawn_info.player_has_molotov as u32 + pawn_info.player_has_incendiary as u32,
+            decoys: pawn_info.player_has_decoy as u32,
+        }
+    }
+}
+
+/// Utility requirements for site executes
+pub struct StateSiteExecuteRequirements {
+    /// Requirements by map name and bomb site name
+    pub maps: BTreeMap<String, BTreeMap<String, UtilityCounts>>,
+}
+
+/// Parse the utility requirement of a single bomb site (e.g. `{ "smokes": 2, "flashes": 2 }`).
+/// Missing utility counts default to zero. The context (e.g. map and site name) will be included in errors.
+pub fn parse_utility_counts(
+    context: &str,
+    requirement: &serde_json::Value,
+) -> anyhow::Result<UtilityCounts> {
+    let count = |name: &str| -> anyhow::Result<u32> {
+        match requirement.get(name) {
+            Some(value) => value
+                .as_u64()
+                .map(|value| value as u32)
+                .with_context(|| format!("invalid {} for {}", name, context)),
+            None => Ok(0),
+        }
+    };
+
+    Ok(UtilityCounts {
+        smokes: count("smokes")?,
+        flashes: count("flashes")?,
+        he_grenades: count("he_grenades")?,
+        molotovs: count("molotovs")?,
+        decoys: count("decoys")?,
+    })
+}
+
+impl StateSiteExecuteRequirements {
+    pub fn parse(data: &str) -> anyhow::Result<Self> {
+        let value = serde_json::from_str::<serde_json::Value>(data)?;
+        let mut maps = BTreeMap::new();
+        for (map_name, sites) in value.as_object().context("expected an object")? {
+            let mut map_sites = BTreeMap::new();
+            for (site_name, requirement) in sites
+                .as_object()
+                .with_context(|| format!("expected an object for {}", map_name))?
+            {
+                map_sites.insert(
+                    site_name.clone(),
+                    parse_utility_counts(&format!("{} {}", map_name, site_name), requirement)?,
+                );
+            }
+
+            maps.insert(map_name.clone(), map_sites);
+        }
+
+        Ok(Self { maps })
+    }
+
+    pub fn site_requirements(&self, map_name: &str) -> Option<&BTreeMap<String, UtilityCounts>> {
+        self.maps.get(map_name)
+    }
+}
+
+impl State for StateSiteExecuteRequirements {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Self::parse(SITE_EXECUTE_REQUIREMENTS).context("site execute requirements")
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlayerUtility {
+    pub pawn_entity_id: PawnIndex,
+    pub player_name: Option<String>,
+    pub utility: UtilityCounts,
+}
+
+#[derive(Debug, Clone)]
+pub struct SiteExecuteReadiness {
+    pub site: String,
+    pub required: UtilityCounts,
+    pub missing: UtilityCounts,
+}
+
+impl SiteExecuteReadiness {
+    pub fn is_ready(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Utility of all alive players of a team
+pub struct StateTeamUtility {
+    pub team_id: u8,
+    pub totals: UtilityCounts,
+    pub players: Vec<PlayerUtility>,
+
+    /// Execute readiness for every bomb site.
+    /// Empty if the current map is unknown.
+    pub site_readiness: Vec<SiteExecuteReadiness>,
+}
+
+impl StateTeamUtility {
+    pub fn site(&self, site: &str) -> Option<&SiteExecuteReadiness> {
+        self.site_readiness.iter().find(|entry| entry.site == site)
+    }
+
+    /// Players holding at least one smoke grenade
+    pub fn smoke_holders(&self) -> impl Iterator<Item = &PlayerUtility> {
+        self.players
+            .iter()
+            .filter(|player| player.utility.smokes > 0)
+    }
+}
+
+/// Calculate the execute readiness for each site of the map
+pub fn site_execute_readiness(
+    totals: &UtilityCounts,
+    sites: &BTreeMap<String, UtilityCounts>,
+) -> Vec<SiteExecuteReadiness> {
+    sites
+        .iter()
+        .map(|(site, required)| SiteExecuteReadiness {
+            site: site.clone(),
+            required: *required,
+            missing: totals.missing(required),
+        })
+        .collect()
+}
+
+impl State for StateTeamUtility {
+    type Parameter = u8;
+
+    fn create(states: &StateRegistry, team_id: Self::Parameter) -> anyhow::Result<Self> {
+        let entities = states.resolve::<StateEntityList>(())?;
+        let class_index = states.resolve::<StateEntityClassIndex>(())?;
+
+        let mut totals = UtilityCounts::default();
+        let mut players = Vec::with_capacity(8);
+        for entity_identity in class_index.entities_of_class(&entities, "C_CSPlayerPawn") {
+            let pawn_state = states.resolve::<PlayerPawnState>(entity_identity.handle()?)?;
+            if *pawn_state != PlayerPawnState::Alive {
+                continue;
+            }
+
+            /* players which could not be read (e.g. without a known position) are skipped */
+            let Some(pawn_info) = states
+                .resolve::<StatePawnInfo>(entity_identity.handle()?)
+                .ok_or_skip("player pawn info")
+            else {
+                continue;
+            };
+            if pawn_info.team_id != team_id {
+                continue;
+            }
+
+            let utility = UtilityCounts::from_pawn_info(&pawn_info);
+            totals.add(&utility);
+            players.push(PlayerUtility {
+                pawn_entity_id: pawn_info.pawn_entity_id,
+                player_name: pawn_info.player_name.clone(),
+                utility,
+            });
+        }
+
+        let site_readiness = {
+            let current_map = states.resolve::<StateCurrentMap>(())?;
+            let requirements = states.resolve::<StateSiteExecuteRequirements>(())?;
+            let map_data = states.resolve::<StateMapData>(())?;
+            match map_data
+                .data()
+                .site_execute_requirements
+                .as_ref()
+                .or_else(|| {
+                    current_map
+                        .current_map
+                        .as_ref()
+                        .and_then(|map| requirements.site_requirements(map))
+                }) {
+                Some(sites) => site_execute_readiness(&totals, sites),
+                None => Vec::new(),
+            }
+        };
+
+        Ok(Self {
+            team_id,
+            totals,
+            players,
+            site_readiness,
+        })
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Volatile
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        site_execute_readiness,
+        StateSiteExecuteRequirements,
+        UtilityCounts,
+        SITE_EXECUTE_REQUIREMENTS,
+    };
+
+    #[test]
+    fn requirements_parse() {
+        let requirements = StateSiteExecuteRequirements::parse(SITE_EXECUTE_REQUIREMENTS).unwrap();
+        let mirage = requirements.site_requirements("de_mirage").unwrap();
+        assert_eq!(
+            mirage["A"],
+            UtilityCounts {
+                smokes: 3,
+                flashes: 2,
+                molotovs: 1,
+                ..Default::default()
+            }
+        );
+        assert!(requirements.site_requirements("de_unknown").is_none());
+    }
+
+    #[test]
+    fn readiness() {
+        let requirements = StateSiteExecuteRequirements::parse(SITE_EXECUTE_REQUIREMENTS).unwrap();
+        let sites = requirements.site_requirements("de_mirage").unwrap();
+
+        let totals = UtilityCounts {
+            smokes: 2,
+            flashes: 4,
+            molotovs: 1,
+            ..Default::default()
+        };
+        let readiness = site_execute_readiness(&totals, sites);
+        let site_a = readiness.iter().find(|site| site.site == "A").unwrap();
+        assert!(!site_a.is_ready());
+        assert_eq!(
+            site_a.missing,
+            UtilityCounts {
+                smokes: 1,
+                ..Default::default()
+            }
+        );
+
+        let site_b = readiness.iter().find(|site| site.site == "B").unwrap();
+        assert!(site_b.is_ready());
+    }
+}
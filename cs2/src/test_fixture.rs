@@ -0,0 +1,410 @@
+//! Process memory fixtures shared by the state tests.
+
+use std::{
+    error::Error,
+    sync::{
+        atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        Arc,
+    },
+};
+
+use cs2_schema_cutl::EntityHandle;
+use cs2_schema_generated::cs2::{
+    client::{
+        CCSPlayerController,
+        CEntityIdentity,
+        C_CSPlayerPawn,
+    },
+    layouts::SCHEMA_CLASS_LAYOUTS,
+};
+use cs2_schema_provider::{
+    OffsetInfo,
+    SchemaProvider,
+};
+use raw_struct::{
+    Copy,
+    FromMemoryView,
+    MemoryView,
+};
+use utils_state::{
+    State,
+    StateRegistry,
+};
+
+use crate::{
+    diagnostics::MemoryFixture,
+    CS2Offset,
+    ClassNameCache,
+    PlayerPawnState,
+    StateCS2Memory,
+    StateEntityList,
+    StatePlayerEquipment,
+    StateResolvedOffset,
+};
+
+/// Offsets of the bundled schema dump
+pub struct DumpSchemaProvider;
+
+impl SchemaProvider for DumpSchemaProvider {
+    fn resolve_offset(&self, offset: &OffsetInfo) -> Option<u64> {
+        Some(offset.default_value)
+    }
+}
+
+/// Resolve all schema offsets from the bundled schema dump
+pub fn setup_dump_schema() {
+    cs2_schema_provider::setup_provider(Box::new(DumpSchemaProvider));
+}
+
+/// Offset of a field within a client.dll class of the bundled schema dump
+pub fn field_offset(class_name: &str, field_name: &str) -> usize {
+    let class = SCHEMA_CLASS_LAYOUTS
+        .iter()
+        .find(|class| class.module == "client.dll" && class.class_name == class_name)
+        .unwrap();
+
+    class
+        .fields
+        .iter()
+        .find(|(name, _)| *name == field_name)
+        .map(|(_, offset)| *offset as usize)
+        .unwrap()
+}
+
+pub fn write(memory: &mut [u8], offset: usize, value: &[u8]) {
+    memory[offset..offset + value.len()].copy_from_slice(value);
+}
+
+/// Memory view counting the reads of the underlying memory view
+pub struct CountingMemoryView {
+    inner: Arc<dyn MemoryView + Send + Sync>,
+    reads: AtomicU64,
+}
+
+impl CountingMemoryView {
+    pub fn new(inner: Arc<dyn MemoryView + Send + Sync>) -> Self {
+        Self {
+            inner,
+            reads: AtomicU64::new(0),
+        }
+    }
+
+    /// Amount of reads (including failed ones) so far
+    pub fn reads(&self) -> u64 {
+        self.reads.load(Ordering::Relaxed)
+    }
+}
+
+impl MemoryView for CountingMemoryView {
+    fn read_memory(
+        &self,
+        offset: u64,
+        buffer: &mut [u8],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        self.inner.read_memory(offset, buffer)
+    }
+}
+
+/// Globals, identities and class infos are placed far above the entities of the tests.
+const GLOBALS_POINTER_ADDRESS: u64 = 0x6000_0000;
+const GLOBALS_ADDRESS: u64 = 0x6000_1000;
+const IDENTITY_BASE_ADDRESS: u64 = 0x7000_0000;
+const CLASS_INFO_BASE_ADDRESS: u64 = 0x7800_0000;
+
+/// Process memory containing an entity list.
+/// The entities themself need to be recorded by the test.
+#[derive(Default, Clone)]
+pub struct EntityFixture {
+    pub memory: MemoryFixture,
+    identities: Vec<u64>,
+    class_names: Vec<(u64, String)>,
+    has_globals: bool,
+}
+
+impl EntityFixture {
+    /// Address of the entity identity for the given handle
+    pub fn identity_address(handle: u32) -> u64 {
+        IDENTITY_BASE_ADDRESS + (handle & 0x7FFF) as u64 * 0x100
+    }
+
+    /// Add an entity identity pointing to the given entity address
+    pub fn push_entity(&mut self, handle: u32, class_name: &str, entity_address: u64) {
+        let identity_address = Self::identity_address(handle);
+        let class_info_address = CLASS_INFO_BASE_ADDRESS + (handle & 0x7FFF) as u64 * 0x10;
+
+        let mut identity = vec![0u8; 0x100];
+        write(&mut identity, 0x00, &entity_address.to_le_bytes());
+        write(&mut identity, 0x08, &class_info_address.to_le_bytes());
+        write(&mut identity, 0x10, &handle.to_le_bytes());
+        self.memory.record(identity_address, &identity);
+
+        self.identities.push(identity_address);
+        self.class_names
+            .push((class_info_address, class_name.to_string()));
+    }
+
+    pub fn record(&mut self, address: u64, value: &[u8]) {
+        self.memory.record(address, value);
+    }
+
+    /// Add the globals with the given server time (see [crate::StateGlobals])
+    pub fn record_globals(&mut self, server_time: f32) {
+        let mut globals = vec![0u8; 0x100];
+        write(&mut globals, 0x30, &server_time.to_le_bytes());
+        self.memory.record(GLOBALS_ADDRESS, &globals);
+        self.memory
+            .record(GLOBALS_POINTER_ADDRESS, &GLOBALS_ADDRESS.to_le_bytes());
+        self.has_globals = true;
+    }
+
+    /// States containing the entity list which read from the fixture memory
+    pub fn into_states(self) -> StateRegistry {
+        let memory = Arc::new(self.memory.clone());
+        self.states(memory)
+    }
+
+    /// States containing the entity list which read from the given memory view.
+    /// The view should serve the fixture memory (e.g. by wrapping it).
+    pub fn states(self, memory: Arc<dyn MemoryView + Send + Sync>) -> StateRegistry {
+        let mut states = StateRegistry::new(1024);
+        self.register(&mut states, memory);
+        states
+    }
+
+    /// Register the memory, the entity list and the class names of the fixture
+    pub fn register(self, states: &mut StateRegistry, memory: Arc<dyn MemoryView + Send + Sync>) {
+        let memory = StateCS2Memory::from_view(memory);
+        let identities = self
+            .identities
+            .into_iter()
+            .map(|address| Copy::<dyn CEntityIdentity>::read_object(memory.view(), address))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let mut class_name_cache = ClassNameCache::create(states, ()).unwrap();
+        for (address, class_name) in self.class_names {
+            class_name_cache.insert(address, class_name);
+        }
+
+        states.set(memory, ()).unwrap();
+        states
+            .set(StateEntityList::from_identities(identities).unwrap(), ())
+            .unwrap();
+        states.set(class_name_cache, ()).unwrap();
+
+        if self.has_globals {
+            states
+                .set(
+                    StateResolvedOffset {
+                        offset: 0,
+                        address: GLOBALS_POINTER_ADDRESS,
+                    },
+                    CS2Offset::Globals,
+                )
+                .unwrap();
+        }
+    }
+}
+
+/// (class name, item definition index) of the weapons every player of the [match_fixture] carries
+pub const MATCH_WEAPONS: [(&str, u16); 2] = [("C_WeaponAWP", 9), ("C_WeaponGlock", 4)];
+
+/// Names of the players of the [match_fixture].
+/// Contains names shorter than their pseudonyms and names contained within other names.
+pub const MATCH_PLAYER_NAMES: [&str; 10] = [
+    "electronic",
+    "zywoo_fan",
+    "JimBob",
+    "Bob",
+    "s1mple_enjoyer",
+    "device",
+    "m0NESY",
+    "NiKo",
+    "ropz_main",
+    "broky",
+];
+
+/// Handle of the pawn of a player within the [match_fixture]
+pub fn match_pawn_handle(player: usize) -> EntityHandle<dyn C_CSPlayerPawn> {
+    EntityHandle::from_index(0x8000 | (player as u32 + 1))
+}
+
+/// Handle of the controller of a player within the [match_fixture]
+pub fn match_controller_handle(player: usize) -> EntityHandle<dyn CCSPlayerController> {
+    EntityHandle::from_index(0x8000 | (player as u32 + 0x41))
+}
+
+/// Steam id of a player within the [match_fixture]
+pub fn match_steam_id(player: usize) -> u64 {
+    76561198000000001 + player as u64
+}
+
+/// Entity list of a running match where every player is alive, carries the [MATCH_WEAPONS]
+/// and is controlled by a player controller named after the [MATCH_PLAYER_NAMES]
+pub fn match_fixture(players: usize) -> EntityFixture {
+    let mut fixture = EntityFixture::default();
+
+    let item_definition_offset = field_offset("C_EconEntity", "m_AttributeManager")
+        + field_offset("C_AttributeContainer", "m_Item")
+        + field_offset("C_EconItemView", "m_iItemDefinitionIndex");
+    let weapon_handles_offset = field_offset("CPlayer_WeaponServices", "m_hMyWeapons");
+
+    for player in 0..players {
+        let pawn_handle = match_pawn_handle(player).value;
+        let pawn_address = 0x100_0000 + player as u64 * 0x10_0000;
+        let scene_node_address = pawn_address + 0x8_0000;
+        let item_services_address = pawn_address + 0x9_0000;
+        let weapon_services_address = pawn_address + 0xA_0000;
+        let weapon_handles_address = pawn_address + 0xB_0000;
+        let controller_handle = match_controller_handle(player).value;
+        let controller_address = pawn_address + 0xC_0000;
+        fixture.push_entity(pawn_handle, "C_CSPlayerPawn", pawn_address);
+        fixture.push_entity(controller_handle, "CCSPlayerController", controller_address);
+
+        let mut pawn = vec![0u8; 0x4000];
+        write(
+            &mut pawn,
+            field_offset("C_BaseEntity", "m_iHealth"),
+            &(100 - player as i32).to_le_bytes(),
+        );
+        write(
+            &mut pawn,
+            field_offset("C_BaseEntity", "m_iTeamNum"),
+            &[2 + (player % 2) as u8],
+        );
+        write(
+            &mut pawn,
+            field_offset("C_BaseEntity", "m_pGameSceneNode"),
+            &scene_node_address.to_le_bytes(),
+        );
+        write(
+            &mut pawn,
+            field_offset("C_CSPlayerPawn", "m_ArmorValue"),
+            &(player as i32 * 10).to_le_bytes(),
+        );
+        write(
+            &mut pawn,
+            field_offset("C_BasePlayerPawn", "m_pWeaponServices"),
+            &weapon_services_address.to_le_bytes(),
+        );
+        write(
+            &mut pawn,
+            field_offset("C_BasePlayerPawn", "m_pItemServices"),
+            &item_services_address.to_le_bytes(),
+        );
+        write(
+            &mut pawn,
+            field_offset("C_BasePlayerPawn", "m_hController"),
+            &controller_handle.to_le_bytes(),
+        );
+        fixture.record(pawn_address, &pawn);
+
+        let mut controller = vec![0u8; 0x1000];
+        write(
+            &mut controller,
+            field_offset("CBasePlayerController", "m_iszPlayerName"),
+            MATCH_PLAYER_NAMES[player].as_bytes(),
+        );
+        write(
+            &mut controller,
+            field_offset("CBasePlayerController", "m_steamID"),
+            &match_steam_id(player).to_le_bytes(),
+        );
+        write(
+            &mut controller,
+            field_offset("CCSPlayerController", "m_hPlayerPawn"),
+            &pawn_handle.to_le_bytes(),
+        );
+        write(
+            &mut controller,
+            field_offset("CCSPlayerController", "m_bPawnIsAlive"),
+            &[1],
+        );
+        fixture.record(controller_address, &controller);
+
+        /* the player pawn info copies the whole CSkeletonInstance */
+        let mut scene_node = vec![0u8; 0x600];
+        for (axis, value) in [player as f32 * 100.0, -250.0, 64.0]
+            .into_iter()
+            .enumerate()
+        {
+            write(
+                &mut scene_node,
+                field_offset("CGameSceneNode", "m_vecAbsOrigin") + axis * 4,
+                &value.to_le_bytes(),
+            );
+        }
+        fixture.record(scene_node_address, &scene_node);
+
+        let mut item_services = vec![0u8; 0x100];
+        write(
+            &mut item_services,
+            field_offset("CCSPlayer_ItemServices", "m_bHasDefuser"),
+            &[(player % 2) as u8],
+        );
+        fixture.record(item_services_address, &item_services);
+
+        let mut weapon_handles = Vec::with_capacity(MATCH_WEAPONS.len());
+        for (weapon, (class_name, item_definition)) in MATCH_WEAPONS.into_iter().enumerate() {
+            let weapon_index = 0x100 + (player * MATCH_WEAPONS.len() + weapon) as u32;
+            let weapon_address = 0x4000_0000 + weapon_index as u64 * 0x1_0000;
+            fixture.push_entity(0x8000 | weapon_index, class_name, weapon_address);
+
+            let mut weapon = vec![0u8; 0x2000];
+            write(
+                &mut weapon,
+                item_definition_offset,
+                &item_definition.to_le_bytes(),
+            );
+            fixture.record(weapon_address, &weapon);
+            weapon_handles.push(0x8000 | weapon_index);
+        }
+
+        let mut weapon_services = vec![0u8; 0x100];
+        write(
+            &mut weapon_services,
+            weapon_handles_offset,
+            &(weapon_handles.len() as u32).to_le_bytes(),
+        );
+        write(
+            &mut weapon_services,
+            weapon_handles_offset + 0x08,
+            &weapon_handles_address.to_le_bytes(),
+        );
+        write(
+            &mut weapon_services,
+            field_offset("CPlayer_WeaponServices", "m_hActiveWeapon"),
+            &weapon_handles[player % weapon_handles.len()].to_le_bytes(),
+        );
+        fixture.record(weapon_services_address, &weapon_services);
+        fixture.record(
+            weapon_handles_address,
+            &weapon_handles
+                .iter()
+                .flat_map(|handle| handle.to_le_bytes())
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    fixture
+}
+
+/// Pawn state and equipment of all players of the [match_fixture]
+pub fn resolve_match_players(
+    states: &StateRegistry,
+    players: usize,
+) -> anyhow::Result<Vec<(PlayerPawnState, StatePlayerEquipment)>> {
+    (0..players)
+        .map(|player| {
+            let handle = match_pawn_handle(player);
+            Ok((
+                states.resolve::<PlayerPawnState>(handle)?.clone(),
+                states.resolve::<StatePlayerEquipment>(handle)?.clone(),
+            ))
+        })
+        .collect()
+}
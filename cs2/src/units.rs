@@ -0,0 +1,148 @@
+//! Conversions between engine (hammer) units and metric units.
+
+use std::fmt;
+
+/// One engine unit equals one inch
+pub const METERS_PER_UNIT: f32 = 0.0254;
+
+/// Default tick rate of CS2 servers
+pub const DEFAULT_TICK_RATE: f32 = 64.0;
+
+/// Distance in engine units
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct WorldUnits(pub f32);
+
+/// Distance in meters
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Meters(pub f32);
+
+impl WorldUnits {
+    pub fn to_meters(self) -> Meters {
+        Meters(self.0 * METERS_PER_UNIT)
+    }
+}
+
+impl Meters {
+    pub fn to_world_units(self) -> WorldUnits {
+        WorldUnits(self.0 / METERS_PER_UNIT)
+    }
+}
+
+impl From<WorldUnits> for Meters {
+    fn from(value: WorldUnits) -> Self {
+        value.to_meters()
+    }
+}
+
+impl From<Meters> for WorldUnits {
+    fn from(value: Meters) -> Self {
+        value.to_world_units()
+    }
+}
+
+/// Velocity in engine units per second
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct UnitsPerSecond(pub f32);
+
+impl UnitsPerSecond {
+    pub fn from_kilometers_per_hour(value: f32) -> Self {
+        Self(value / 3.6 / METERS_PER_UNIT)
+    }
+
+    pub fn to_meters_per_second(self) -> f32 {
+        self.0 * METERS_PER_UNIT
+    }
+
+    pub fn to_kilometers_per_hour(self) -> f32 {
+        self.to_meters_per_second() * 3.6
+    }
+}
+
+/// Convert an amount of server ticks into seconds
+pub fn ticks_to_seconds(ticks: i32, tick_rate: f32) -> f32 {
+    ticks as f32 / tick_rate
+}
+
+/// Convert seconds into the amount of (started) server ticks
+pub fn seconds_to_ticks(seconds: f32, tick_rate: f32) -> i32 {
+    (seconds * tick_rate).ceil() as i32
+}
+
+/// Format a distance choosing the unit automatically (cm, m or km)
+pub fn format_distance(distance: WorldUnits) -> String {
+    let meters = distance.to_meters().0.abs();
+    if meters < 1.0 {
+        format!("{:.0} cm", meters * 100.0)
+    } else if meters < 10.0 {
+        format!("{:.1} m", meters)
+    } else if meters < 1000.0 {
+        format!("{:.0} m", meters)
+    } else {
+        format!("{:.2} km", meters / 1000.0)
+    }
+}
+
+/// Format a velocity in km/h
+pub fn format_speed(speed: UnitsPerSecond) -> String {
+    format!("{:.0} km/h", speed.to_kilometers_per_hour().abs())
+}
+
+impl fmt::Display for WorldUnits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_distance(*self))
+    }
+}
+
+impl fmt::Display for Meters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_distance(self.to_world_units()))
+    }
+}
+
+impl fmt::Display for UnitsPerSecond {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_speed(*self))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        format_distance,
+        format_speed,
+        seconds_to_ticks,
+        ticks_to_seconds,
+        Meters,
+        UnitsPerSecond,
+        WorldUnits,
+        DEFAULT_TICK_RATE,
+    };
+
+    #[test]
+    fn conversions() {
+        assert!((WorldUnits(100.0).to_meters().0 - 2.54).abs() < 0.0001);
+        assert!((Meters(2.54).to_world_units().0 - 100.0).abs() < 0.001);
+
+        /* default player run speed (knife) */
+        let speed = UnitsPerSecond(250.0);
+        assert!((speed.to_meters_per_second() - 6.35).abs() < 0.0001);
+        assert!((speed.to_kilometers_per_hour() - 22.86).abs() < 0.001);
+        assert!((UnitsPerSecond::from_kilometers_per_hour(22.86).0 - 250.0).abs() < 0.01);
+
+        assert_eq!(ticks_to_seconds(128, DEFAULT_TICK_RATE), 2.0);
+        assert_eq!(seconds_to_ticks(0.5, DEFAULT_TICK_RATE), 32);
+        assert_eq!(seconds_to_ticks(0.51, DEFAULT_TICK_RATE), 33);
+    }
+
+    #[test]
+    fn formatting() {
+        assert_eq!(format_distance(WorldUnits(10.0)), "25 cm");
+        assert_eq!(format_distance(WorldUnits(100.0)), "2.5 m");
+        assert_eq!(format_distance(WorldUnits(1000.0)), "25 m");
+        assert_eq!(format_distance(WorldUnits(50_000.0)), "1.27 km");
+        assert_eq!(Meters(3.0).to_string(), "3.0 m");
+
+        assert_eq!(format_speed(UnitsPerSecond(250.0)), "23 km/h");
+        assert_eq!(UnitsPerSecond(-250.0).to_string(), "23 km/h");
+    }
+}
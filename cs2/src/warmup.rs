@@ -0,0 +1,214 @@
+use std::{
+    any,
+    thread::{
+        self,
+        JoinHandle,
+    },
+};
+
+use utils_state::{
+    State,
+    StateCacheType,
+    StateRegistry,
+};
+
+use crate::{
+    CS2Offset,
+    ClassNameCache,
+    StateBombSiteGeometry,
+    StateBuildInfo,
+    StateEconomyRules,
+    StateEntityList,
+    StateResolvedOffset,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WarmupStage {
+    /// Reading the build info and resolving all signatures
+    ResolvingOffsets,
+
+    /// Reading the entity list and the class names of all entity classes
+    BuildingClassCache,
+
+    /// Loading the map data (e.g. bomb site geometry) and the server rules
+    LoadingMapData,
+
+    Finished,
+}
+
+impl WarmupStage {
+    const STAGES: [Self; 3] = [
+        Self::ResolvingOffsets,
+        Self::BuildingClassCache,
+        Self::LoadingMapData,
+    ];
+
+    /// Progress (0.0 to 1.0) when entering this stage
+    pub fn progress(&self) -> f32 {
+        match Self::STAGES.iter().position(|stage| stage == self) {
+            Some(index) => index as f32 / Self::STAGES.len() as f32,
+            None => 1.0,
+        }
+    }
+}
+
+/// A state which could not be resolved during the warmup.
+/// The state will be created lazily in the first frame instead.
+#[derive(Debug)]
+pub struct WarmupError {
+    pub stage: WarmupStage,
+
+    /// Type name of the state
+    pub state: &'static str,
+    pub error: anyhow::Error,
+}
+
+/// Marks the warmup as completed
+struct StateWarmup;
+
+impl State for StateWarmup {
+    type Parameter = ();
+
+    fn create(_states: &StateRegistry, _param: Self::Parameter) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    fn cache_type() -> StateCacheType {
+        StateCacheType::Persistent
+    }
+}
+
+fn warmup_state<T: State>(
+    states: &StateRegistry,
+    stage: WarmupStage,
+    params: T::Parameter,
+    errors: &mut Vec<WarmupError>,
+) {
+    if let Err(error) = states.resolve::<T>(params) {
+        errors.push(WarmupError {
+            stage,
+            state: any::type_name::<T>(),
+            error,
+        });
+    }
+}
+
+/// Create all persistent states which would otherwise be created lazily within the first frame.
+/// The progress callback will be invoked when entering a stage and once the warmup has been finished.
+///
+/// The warmup is idempotent: States which already exist will not be created again and calling it
+/// after a successful warmup only reports [WarmupStage::Finished].
+/// If any state fails, the warmup will not be marked as completed and can be retried.
+pub fn warmup(states: &StateRegistry, progress: impl Fn(WarmupStage)) -> Vec<WarmupError> {
+    let mut errors = Vec::new();
+    if states.get::<StateWarmup>(()).is_some() {
+        progress(WarmupStage::Finished);
+        return errors;
+    }
+
+    for stage in WarmupStage::STAGES {
+        progress(stage);
+        match stage {
+            WarmupStage::ResolvingOffsets => {
+                warmup_state::<StateBuildInfo>(states, stage, (), &mut errors);
+                for offset in CS2Offset::available_offsets() {
+                    warmup_state::<StateResolvedOffset>(states, stage, *offset, &mut errors);
+                }
+            }
+            WarmupStage::BuildingClassCache => {
+                warmup_state::<StateEntityList>(states, stage, (), &mut errors);
+                warmup_state::<ClassNameCache>(states, stage, (), &mut errors);
+            }
+            WarmupStage::LoadingMapData => {
+                warmup_state::<StateBombSiteGeometry>(states, stage, (), &mut errors);
+                warmup_state::<StateEconomyRules>(states, stage, (), &mut errors);
+            }
+            WarmupStage::Finished => unreachable!(),
+        }
+    }
+
+    if errors.is_empty() {
+        warmup_state::<StateWarmup>(states, WarmupStage::Finished, (), &mut errors);
+    }
+
+    progress(WarmupStage::Finished);
+    errors
+}
+
+/// Warmup running on a background thread (see [spawn_warmup])
+pub struct WarmupHandle {
+    thread: JoinHandle<(StateRegistry, Vec<WarmupError>)>,
+}
+
+impl WarmupHandle {
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Wait for the warmup to finish and return the registry
+    pub fn join(self) -> anyhow::Result<(StateRegistry, Vec<WarmupError>)> {
+        self.thread
+            .join()
+            .map_err(|_| anyhow::anyhow!("warmup thread panicked"))
+    }
+
+    /// Return the registry if the warmup has been finished.
+    /// Consumers polling for the registry will receive the handle back otherwise.
+    pub fn try_join(self) -> Result<anyhow::Result<(StateRegistry, Vec<WarmupError>)>, Self> {
+        if self.is_finished() {
+            Ok(self.join())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+/// Run the [warmup] on a background thread.
+/// The registry will be moved to the background thread and handed back once the warmup has been finished,
+/// therefore consumers can not access it while the warmup is running.
+pub fn spawn_warmup(
+    states: StateRegistry,
+    progress: impl Fn(WarmupStage) + Send + 'static,
+) -> anyhow::Result<WarmupHandle> {
+    let thread = thread::Builder::new()
+        .name("state-warmup".to_string())
+        .spawn(move || {
+            let errors = warmup(&states, progress);
+            (states, errors)
+        })?;
+
+    Ok(WarmupHandle { thread })
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+
+    use utils_state::StateRegistry;
+
+    use super::{
+        warmup,
+        StateWarmup,
+        WarmupStage,
+    };
+
+    #[test]
+    fn progress() {
+        assert_eq!(WarmupStage::ResolvingOffsets.progress(), 0.0);
+        assert!(
+            WarmupStage::BuildingClassCache.progress() < WarmupStage::LoadingMapData.progress()
+        );
+        assert_eq!(WarmupStage::Finished.progress(), 1.0);
+    }
+
+    #[test]
+    fn idempotent() {
+        let mut states = StateRegistry::new(8);
+        states.set(StateWarmup, ()).unwrap();
+
+        let stages = RefCell::new(Vec::new());
+        let errors = warmup(&states, |stage| stages.borrow_mut().push(stage));
+        assert!(errors.is_empty());
+        assert_eq!(*stages.borrow(), vec![WarmupStage::Finished]);
+    }
+}
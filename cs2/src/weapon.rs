@@ -7,7 +7,31 @@ pub const WEAPON_FLAG_TYPE_SNIPER_RIFLE: u32 = 0x20;
 pub const WEAPON_FLAG_TYPE_MACHINE_GUN: u32 = 0x40;
 pub const WEAPON_FLAG_TYPE_GRENADE: u32 = 0x80;
 
+/// Damage characteristics of a firearm
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeaponDamageProfile {
+    /// Base damage of a single bullet
+    pub damage: f32,
+
+    /// Armor ratio of the weapon.
+    /// Half of this value is the fraction of damage dealt to armored players.
+    pub armor_ratio: f32,
+
+    /// Damage multiplier for every 500 units of distance
+    pub range_modifier: f32,
+}
+
 macro_rules! define_weapons {
+    (@damage_profile) => {
+        None
+    };
+    (@damage_profile $damage:literal, $armor_ratio:literal, $range_modifier:literal) => {
+        Some(WeaponDamageProfile {
+            damage: $damage as f32,
+            armor_ratio: $armor_ratio,
+            range_modifier: $range_modifier,
+        })
+    };
     (
         $(#[$struct_meta:meta])*
         pub enum $struct_name:ident {
@@ -16,6 +40,7 @@ macro_rules! define_weapons {
                     id: $id:literal,
                     name: $name:literal,
                     flags: $flags:tt
+                    $(, damage: ($damage:literal, $armor_ratio:literal, $range_modifier:literal))?
                 },
             )*
         }
@@ -62,46 +87,55 @@ macro_rules! define_weapons {
                     $(Self::$member_name => $name,)*
                 }
             }
+
+            /// Damage characteristics of the weapon.
+            /// None for weapons which do not fire bullets.
+            pub fn damage_profile(&self) -> Option<WeaponDamageProfile> {
+                match self {
+                    $(Self::$member_name => define_weapons!(@damage_profile $($damage, $armor_ratio, $range_modifier)?),)*
+                }
+            }
         }
     };
 }
 
 define_weapons! {
     #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
     pub enum WeaponId {
         Unknown { id: 0, name: "Unknown", flags: WEAPON_FLAG_TYPE_KNIFE },
-        Deagle { id: 1, name: "Desert Eagle", flags: WEAPON_FLAG_TYPE_PISTOL },
-        Elite { id: 2, name: "Elite", flags: 0 },
+        Deagle { id: 1, name: "Desert Eagle", flags: WEAPON_FLAG_TYPE_PISTOL, damage: (53, 1.864, 0.85) },
+        Elite { id: 2, name: "Elite", flags: 0, damage: (38, 1.05, 0.75) },
         // # spellchecker:ignore-next-line
-        FiveSeven { id: 3, name: "Five-Five-SeveN", flags: WEAPON_FLAG_TYPE_PISTOL },
-        Glock { id: 4, name: "Glock-18", flags: WEAPON_FLAG_TYPE_PISTOL },
-        Ak47 { id: 7, name: "AK-47", flags: WEAPON_FLAG_TYPE_RIFLE },
-        Aug { id: 8, name: "AUG", flags: WEAPON_FLAG_TYPE_RIFLE },
-        AWP { id: 9, name: "AWP", flags: WEAPON_FLAG_TYPE_SNIPER_RIFLE },
-        Famas { id: 10, name: "FAMAS", flags: WEAPON_FLAG_TYPE_RIFLE },
-        G3SG1 { id: 11, name: "G3SG1", flags: WEAPON_FLAG_TYPE_SNIPER_RIFLE },
-        Galilar { id: 13, name: "Galil AR", flags: WEAPON_FLAG_TYPE_RIFLE },
-        M249 { id: 14, name: "M249", flags: WEAPON_FLAG_TYPE_MACHINE_GUN },
-        M4A4 { id: 16, name: "M4A4", flags: WEAPON_FLAG_TYPE_RIFLE },
-        Mac10 { id: 17, name: "MAC-10", flags: WEAPON_FLAG_TYPE_SMG },
-        P90 { id: 19, name: "P90", flags: WEAPON_FLAG_TYPE_SMG },
-        MP5SD { id: 23, name: "MP5-SD", flags: WEAPON_FLAG_TYPE_SMG },
-        Ump45 { id: 24, name: "UMP-45", flags: WEAPON_FLAG_TYPE_SMG },
-        XM1014 { id: 25, name: "XM1014", flags: WEAPON_FLAG_TYPE_SHOTGUN },
-        Bizon { id: 26, name: "PP-Bizon", flags: WEAPON_FLAG_TYPE_SMG },
-        Mag7 { id: 27, name: "MAG-7", flags: WEAPON_FLAG_TYPE_SHOTGUN },
-        Negev { id: 28, name: "Negev", flags: WEAPON_FLAG_TYPE_MACHINE_GUN },
-        SawedOff { id: 29, name: "Sawed-Off", flags: WEAPON_FLAG_TYPE_SHOTGUN },
-        Tec9 { id: 30, name: "Tec-9", flags: WEAPON_FLAG_TYPE_PISTOL },
+        FiveSeven { id: 3, name: "Five-Five-SeveN", flags: WEAPON_FLAG_TYPE_PISTOL, damage: (32, 1.823, 0.81) },
+        Glock { id: 4, name: "Glock-18", flags: WEAPON_FLAG_TYPE_PISTOL, damage: (30, 0.94, 0.85) },
+        Ak47 { id: 7, name: "AK-47", flags: WEAPON_FLAG_TYPE_RIFLE, damage: (36, 1.55, 0.98) },
+        Aug { id: 8, name: "AUG", flags: WEAPON_FLAG_TYPE_RIFLE, damage: (28, 1.8, 0.98) },
+        AWP { id: 9, name: "AWP", flags: WEAPON_FLAG_TYPE_SNIPER_RIFLE, damage: (115, 1.95, 0.99) },
+        Famas { id: 10, name: "FAMAS", flags: WEAPON_FLAG_TYPE_RIFLE, damage: (30, 1.4, 0.96) },
+        G3SG1 { id: 11, name: "G3SG1", flags: WEAPON_FLAG_TYPE_SNIPER_RIFLE, damage: (80, 1.65, 0.98) },
+        Galilar { id: 13, name: "Galil AR", flags: WEAPON_FLAG_TYPE_RIFLE, damage: (30, 1.55, 0.98) },
+        M249 { id: 14, name: "M249", flags: WEAPON_FLAG_TYPE_MACHINE_GUN, damage: (32, 1.6, 0.97) },
+        M4A4 { id: 16, name: "M4A4", flags: WEAPON_FLAG_TYPE_RIFLE, damage: (33, 1.4, 0.97) },
+        Mac10 { id: 17, name: "MAC-10", flags: WEAPON_FLAG_TYPE_SMG, damage: (29, 1.15, 0.8) },
+        P90 { id: 19, name: "P90", flags: WEAPON_FLAG_TYPE_SMG, damage: (26, 1.38, 0.86) },
+        MP5SD { id: 23, name: "MP5-SD", flags: WEAPON_FLAG_TYPE_SMG, damage: (27, 1.25, 0.81) },
+        Ump45 { id: 24, name: "UMP-45", flags: WEAPON_FLAG_TYPE_SMG, damage: (35, 1.3, 0.85) },
+        XM1014 { id: 25, name: "XM1014", flags: WEAPON_FLAG_TYPE_SHOTGUN, damage: (20, 1.6, 0.7) },
+        Bizon { id: 26, name: "PP-Bizon", flags: WEAPON_FLAG_TYPE_SMG, damage: (27, 1.15, 0.8) },
+        Mag7 { id: 27, name: "MAG-7", flags: WEAPON_FLAG_TYPE_SHOTGUN, damage: (30, 1.5, 0.45) },
+        Negev { id: 28, name: "Negev", flags: WEAPON_FLAG_TYPE_MACHINE_GUN, damage: (35, 1.42, 0.97) },
+        SawedOff { id: 29, name: "Sawed-Off", flags: WEAPON_FLAG_TYPE_SHOTGUN, damage: (32, 1.5, 0.45) },
+        Tec9 { id: 30, name: "Tec-9", flags: WEAPON_FLAG_TYPE_PISTOL, damage: (33, 1.812, 0.831) },
         Taser { id: 31, name: "Zeus x27", flags: 0 },
-        HKP200 { id: 32, name: "P2000", flags: WEAPON_FLAG_TYPE_PISTOL },
-        MP7 { id: 33, name: "MP7", flags: WEAPON_FLAG_TYPE_SMG },
-        MP9 { id: 34, name: "MP9", flags: WEAPON_FLAG_TYPE_SMG },
-        Nova { id: 35, name: "Nova", flags: WEAPON_FLAG_TYPE_SHOTGUN },
-        P250 { id: 36, name: "P250", flags: WEAPON_FLAG_TYPE_PISTOL },
-        Scar20 { id: 38, name: "SCAR-20", flags: WEAPON_FLAG_TYPE_SNIPER_RIFLE },
-        Sg553 { id: 39, name: "SG 553", flags: WEAPON_FLAG_TYPE_RIFLE },
-        Ssg08 { id: 40, name: "SSG 08", flags: WEAPON_FLAG_TYPE_SNIPER_RIFLE },
+        HKP200 { id: 32, name: "P2000", flags: WEAPON_FLAG_TYPE_PISTOL, damage: (35, 1.01, 0.91) },
+        MP7 { id: 33, name: "MP7", flags: WEAPON_FLAG_TYPE_SMG, damage: (29, 1.25, 0.85) },
+        MP9 { id: 34, name: "MP9", flags: WEAPON_FLAG_TYPE_SMG, damage: (26, 1.2, 0.87) },
+        Nova { id: 35, name: "Nova", flags: WEAPON_FLAG_TYPE_SHOTGUN, damage: (26, 1.0, 0.7) },
+        P250 { id: 36, name: "P250", flags: WEAPON_FLAG_TYPE_PISTOL, damage: (38, 1.28, 0.9) },
+        Scar20 { id: 38, name: "SCAR-20", flags: WEAPON_FLAG_TYPE_SNIPER_RIFLE, damage: (80, 1.65, 0.98) },
+        Sg553 { id: 39, name: "SG 553", flags: WEAPON_FLAG_TYPE_RIFLE, damage: (30, 2.0, 1.0) },
+        Ssg08 { id: 40, name: "SSG 08", flags: WEAPON_FLAG_TYPE_SNIPER_RIFLE, damage: (88, 1.7, 0.98) },
         Knife { id: 42, name: "Knife", flags: WEAPON_FLAG_TYPE_KNIFE },
         Flashbang { id: 43, name: "Flashbang", flags: WEAPON_FLAG_TYPE_GRENADE },
         HZgrenade { id: 44, name: "HE grenade", flags: WEAPON_FLAG_TYPE_GRENADE },
@@ -112,10 +146,10 @@ define_weapons! {
         C4 { id: 49, name: "C4", flags: 0 },
         Healthshot { id: 57, name: "Healthshot", flags: 0 },
         KnifeT { id: 59, name: "Knife (T)", flags: WEAPON_FLAG_TYPE_KNIFE },
-        M4A1Silencer { id: 60, name: "M4A1-S", flags: WEAPON_FLAG_TYPE_RIFLE },
-        USPS { id: 61, name: "USP-S", flags: WEAPON_FLAG_TYPE_RIFLE },
-        CZ75a { id: 63, name: "CZ75-Auto", flags: WEAPON_FLAG_TYPE_RIFLE },
-        Revolver { id: 64, name: "Revolver", flags: WEAPON_FLAG_TYPE_RIFLE },
+        M4A1Silencer { id: 60, name: "M4A1-S", flags: WEAPON_FLAG_TYPE_RIFLE, damage: (38, 1.4, 0.94) },
+        USPS { id: 61, name: "USP-S", flags: WEAPON_FLAG_TYPE_RIFLE, damage: (35, 1.01, 0.91) },
+        CZ75a { id: 63, name: "CZ75-Auto", flags: WEAPON_FLAG_TYPE_RIFLE, damage: (31, 1.552, 0.85) },
+        Revolver { id: 64, name: "Revolver", flags: WEAPON_FLAG_TYPE_RIFLE, damage: (86, 1.864, 0.94) },
 
         KnifeBayonet { id: 500, name: "Knife (Bayonet)", flags: WEAPON_FLAG_TYPE_KNIFE },
         KnifesClassic { id: 503, name: "Knife (Classic)", flags: WEAPON_FLAG_TYPE_KNIFE },
@@ -138,3 +172,104 @@ define_weapons! {
         KnifesSkeleton { id: 525, name: "Knife (Skeleton)", flags: WEAPON_FLAG_TYPE_KNIFE },
     }
 }
+
+/// Category of a weapon (see [WeaponId::kind])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum WeaponKind {
+    Knife,
+    Pistol,
+    Shotgun,
+    Smg,
+    Rifle,
+    SniperRifle,
+    MachineGun,
+    Grenade,
+    Taser,
+    C4,
+    Healthshot,
+    Unknown,
+}
+
+impl WeaponId {
+    pub fn kind(&self) -> WeaponKind {
+        match self {
+            Self::Unknown => return WeaponKind::Unknown,
+            Self::Taser => return WeaponKind::Taser,
+            Self::C4 => return WeaponKind::C4,
+            Self::Healthshot => return WeaponKind::Healthshot,
+
+            /* the type flags of these pistols are not accurate */
+            Self::Elite | Self::USPS | Self::CZ75a | Self::Revolver => return WeaponKind::Pistol,
+            _ => {}
+        }
+
+        let flags = self.flags();
+        if flags & WEAPON_FLAG_TYPE_KNIFE != 0 {
+            WeaponKind::Knife
+        } else if flags & WEAPON_FLAG_TYPE_PISTOL != 0 {
+            WeaponKind::Pistol
+        } else if flags & WEAPON_FLAG_TYPE_SHOTGUN != 0 {
+            WeaponKind::Shotgun
+        } else if flags & WEAPON_FLAG_TYPE_SMG != 0 {
+            WeaponKind::Smg
+        } else if flags & WEAPON_FLAG_TYPE_RIFLE != 0 {
+            WeaponKind::Rifle
+        } else if flags & WEAPON_FLAG_TYPE_SNIPER_RIFLE != 0 {
+            WeaponKind::SniperRifle
+        } else if flags & WEAPON_FLAG_TYPE_MACHINE_GUN != 0 {
+            WeaponKind::MachineGun
+        } else if flags & WEAPON_FLAG_TYPE_GRENADE != 0 {
+            WeaponKind::Grenade
+        } else {
+            WeaponKind::Unknown
+        }
+    }
+
+    /// Price of the weapon in the buy menu (competitive matchmaking).
+    /// None for weapons which can not be bought (e.g. knives or the C4).
+    pub fn price(&self) -> Option<i32> {
+        let price = match self {
+            Self::Glock | Self::HKP200 | Self::USPS | Self::Taser => 200,
+            Self::Elite | Self::P250 => 300,
+            Self::FiveSeven | Self::Tec9 | Self::CZ75a => 500,
+            Self::Deagle => 700,
+            Self::Revolver => 600,
+
+            Self::Nova => 1050,
+            Self::XM1014 => 2000,
+            Self::Mag7 => 1300,
+            Self::SawedOff => 1100,
+            Self::M249 => 5200,
+            Self::Negev => 1700,
+
+            Self::Mac10 => 1050,
+            Self::MP9 => 1250,
+            Self::MP7 | Self::MP5SD => 1500,
+            Self::Ump45 => 1200,
+            Self::P90 => 2350,
+            Self::Bizon => 1400,
+
+            Self::Galilar => 1800,
+            Self::Famas => 2050,
+            Self::Ak47 => 2700,
+            Self::M4A4 => 3100,
+            Self::M4A1Silencer => 2900,
+            Self::Sg553 => 3000,
+            Self::Aug => 3300,
+            Self::Ssg08 => 1700,
+            Self::AWP => 4750,
+            Self::G3SG1 | Self::Scar20 => 5000,
+
+            Self::Decoy => 50,
+            Self::Flashbang => 200,
+            Self::HZgrenade | Self::Smokegrenade => 300,
+            Self::Molotov => 400,
+            Self::Incendiary => 500,
+
+            _ => return None,
+        };
+
+        Some(price)
+    }
+}
@@ -4,7 +4,10 @@ use std::{
 };
 
 use anyhow::Context;
-use clap::Parser;
+use clap::{
+    Parser,
+    ValueEnum,
+};
 use cs2::{
     CS2Handle,
     InterfaceError,
@@ -14,6 +17,8 @@ use cs2::{
 use obfstr::obfstr;
 use radar_client::{
     CS2RadarGenerator,
+    CoordinateTransform,
+    DemoRadarGenerator,
     DummyRadarGenerator,
     RadarGenerator,
     WebRadarPublisher,
@@ -24,6 +29,25 @@ use utils_state::StateRegistry;
 
 mod arch;
 
+/// Coordinate system of the published positions
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Coordinates {
+    /// Engine units, Z-up
+    Engine,
+
+    /// Meters, Z-up
+    Meters,
+}
+
+impl Coordinates {
+    fn transform(self) -> CoordinateTransform {
+        match self {
+            Self::Engine => CoordinateTransform::identity(),
+            Self::Meters => CoordinateTransform::meters_z_up(),
+        }
+    }
+}
+
 /// Standalone Valthrun CS2 radar
 #[derive(Parser, Debug)]
 #[command(long_about = None)]
@@ -38,10 +62,19 @@ struct Args {
     #[arg(short, long)]
     schema_file: Option<PathBuf>,
 
+    /// Coordinate system of the published player and bomb positions
+    #[arg(long, value_enum, default_value_t = Coordinates::Engine)]
+    coordinates: Coordinates,
+
     /// Use a dummy generator instead of generating the radar data from CS2.
     /// This is useful when testing the radar client without CS2.
     #[arg(long, hide = true)]
     dummy_generator: bool,
+
+    /// Use a deterministic demo generator, producing synthetic rounds from the given seed.
+    /// This is useful when developing the web radar without CS2.
+    #[arg(long, hide = true)]
+    demo_seed: Option<u64>,
 }
 
 #[tokio::main]
@@ -73,6 +106,8 @@ async fn real_main(args: &Args) -> anyhow::Result<()> {
 
     let radar_generator: Box<dyn RadarGenerator> = if args.dummy_generator {
         Box::new(DummyRadarGenerator)
+    } else if let Some(seed) = args.demo_seed {
+        Box::new(DemoRadarGenerator::new(seed))
     } else {
         let cs2 = match CS2Handle::create(true) {
             Ok(cs2) => cs2,
@@ -88,7 +123,7 @@ async fn real_main(args: &Args) -> anyhow::Result<()> {
             }
         };
         let mut states = StateRegistry::new(1024 * 8);
-        states.set(StateCS2Memory::new(cs2.create_memory_view()), ())?;
+        states.set(StateCS2Memory::from_view(cs2.create_memory_view()), ())?;
         states.set(StateCS2Handle::new(cs2), ())?;
 
         if let Some(file) = &args.schema_file {
@@ -112,7 +147,9 @@ async fn real_main(args: &Args) -> anyhow::Result<()> {
         }
         log::info!("CS2 schema (offsets) loaded.");
 
-        Box::new(CS2RadarGenerator::new(states)?)
+        Box::new(
+            CS2RadarGenerator::new(states)?.with_coordinate_transform(args.coordinates.transform()),
+        )
     };
 
     self::radar_publish_loop(radar_generator, &url).await
@@ -0,0 +1,404 @@
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use crate::MapCalibration;
+
+/// Radar image of a map level
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RadarImage {
+    /// Image path relative to the radar image directory (`<map name>/<file name>`)
+    pub path: &'static str,
+
+    /// Image width in pixels
+    pub width: u32,
+
+    /// Image height in pixels
+    pub height: u32,
+
+    #[cfg(feature = "embedded-radar-images")]
+    data: &'static [u8],
+}
+
+/// Where the contents of a radar image can be found
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RadarImageSource {
+    /// PNG image embedded into the binary
+    Embedded(&'static [u8]),
+
+    /// PNG image provided by the user.
+    /// The path is relative to the radar image directory (see [RadarImage::resolve_path]).
+    File(&'static str),
+}
+
+impl RadarImage {
+    #[cfg(feature = "embedded-radar-images")]
+    pub fn source(&self) -> RadarImageSource {
+        RadarImageSource::Embedded(self.data)
+    }
+
+    #[cfg(not(feature = "embedded-radar-images"))]
+    pub fn source(&self) -> RadarImageSource {
+        RadarImageSource::File(self.path)
+    }
+
+    /// Resolve the image path within an user provided radar image directory.
+    /// The directory layout equals the one of the web radar (`<map name>/<file name>`).
+    pub fn resolve_path(&self, image_directory: &Path) -> PathBuf {
+        image_directory.join(self.path)
+    }
+}
+
+/// A vertical section of a map with its own radar image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RadarAssetLevel {
+    pub name: &'static str,
+
+    /// Lower bound of the section (world z coordinate)
+    pub altitude_min: f32,
+
+    /// Upper bound of the section (world z coordinate)
+    pub altitude_max: f32,
+
+    pub image: RadarImage,
+}
+
+/// Radar image metadata of a map.
+/// The calibration references the native 1024x1024 overview image and
+/// must be scaled accordingly for images with a different resolution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RadarAsset {
+    pub map_name: &'static str,
+    pub display_name: &'static str,
+    pub calibration: MapCalibration,
+    pub levels: &'static [RadarAssetLevel],
+}
+
+/// Resolution of the overview image the map calibrations reference
+pub const RADAR_OVERVIEW_RESOLUTION: u32 = 1024;
+
+impl RadarAsset {
+    /// Find the level which contains the given world z coordinate
+    pub fn level(&self, altitude: f32) -> Option<&'static RadarAssetLevel> {
+        self.levels
+            .iter()
+            .find(|level| level.altitude_min <= altitude && altitude < level.altitude_max)
+    }
+}
+
+macro_rules! radar_image {
+    ($path:literal, $width:literal, $height:literal) => {
+        RadarImage {
+            path: $path,
+            width: $width,
+            height: $height,
+
+            #[cfg(feature = "embedded-radar-images")]
+            data: include_bytes!(concat!("../../web/src/map-info/", $path)),
+        }
+    };
+}
+
+/// Radar images of the official map overviews (same images as used by the web radar)
+const RADAR_ASSETS: &[RadarAsset] = &[
+    RadarAsset {
+        map_name: "cs_italy",
+        display_name: "Italy",
+        calibration: MapCalibration {
+            pos_x: -2647.0,
+            pos_y: 2592.0,
+            scale: 4.6,
+        },
+        levels: &[RadarAssetLevel {
+            name: "default",
+            altitude_min: -10000.0,
+            altitude_max: 10000.0,
+            image: radar_image!("cs_italy/map_style_cs2.png", 1024, 1024),
+        }],
+    },
+    RadarAsset {
+        map_name: "cs_office",
+        display_name: "Office",
+        calibration: MapCalibration {
+            pos_x: -1838.0,
+            pos_y: 1858.0,
+            scale: 4.1,
+        },
+        levels: &[RadarAssetLevel {
+            name: "default",
+            altitude_min: -10000.0,
+            altitude_max: 10000.0,
+            image: radar_image!("cs_office/map_style_cs2.png", 1024, 1024),
+        }],
+    },
+    RadarAsset {
+        map_name: "de_ancient",
+        display_name: "Ancient",
+        calibration: MapCalibration {
+            pos_x: -2953.0,
+            pos_y: 2164.0,
+            scale: 5.0,
+        },
+        levels: &[RadarAssetLevel {
+            name: "default",
+            altitude_min: -10000.0,
+            altitude_max: 10000.0,
+            image: radar_image!("de_ancient/map_style_cs2.png", 1024, 1024),
+        }],
+    },
+    RadarAsset {
+        map_name: "de_anubis",
+        display_name: "Anubis",
+        calibration: MapCalibration {
+            pos_x: -2796.0,
+            pos_y: 3328.0,
+            scale: 5.22,
+        },
+        levels: &[RadarAssetLevel {
+            name: "default",
+            altitude_min: -10000.0,
+            altitude_max: 10000.0,
+            image: radar_image!("de_anubis/map_style_cs2.png", 1024, 1024),
+        }],
+    },
+    RadarAsset {
+        map_name: "de_cache",
+        display_name: "Cache",
+        calibration: MapCalibration {
+            pos_x: -2020.0,
+            pos_y: 2390.0,
+            scale: 5.54,
+        },
+        levels: &[RadarAssetLevel {
+            name: "default",
+            altitude_min: -10000.0,
+            altitude_max: 10000.0,
+            image: radar_image!("de_cache/map_style_cs2.png", 1024, 1024),
+        }],
+    },
+    RadarAsset {
+        map_name: "de_dust2",
+        display_name: "Dust II",
+        calibration: MapCalibration {
+            pos_x: -2476.0,
+            pos_y: 3239.0,
+            scale: 4.4,
+        },
+        levels: &[RadarAssetLevel {
+            name: "default",
+            altitude_min: -10000.0,
+            altitude_max: 10000.0,
+            image: radar_image!("de_dust2/map_style_cs2.png", 1024, 1024),
+        }],
+    },
+    RadarAsset {
+        map_name: "de_inferno",
+        display_name: "Inferno",
+        calibration: MapCalibration {
+            pos_x: -2087.0,
+            pos_y: 3870.0,
+            scale: 4.9,
+        },
+        levels: &[RadarAssetLevel {
+            name: "default",
+            altitude_min: -10000.0,
+            altitude_max: 10000.0,
+            image: radar_image!("de_inferno/map_style_cs2.png", 1024, 1024),
+        }],
+    },
+    RadarAsset {
+        map_name: "de_mills",
+        display_name: "Mills",
+        calibration: MapCalibration {
+            pos_x: -4810.0,
+            pos_y: -320.0,
+            scale: 5.148,
+        },
+        levels: &[RadarAssetLevel {
+            name: "default",
+            altitude_min: -10000.0,
+            altitude_max: 10000.0,
+            image: radar_image!("de_mills/map_style_cs2.png", 1024, 1024),
+        }],
+    },
+    RadarAsset {
+        map_name: "de_mirage",
+        display_name: "Mirage",
+        calibration: MapCalibration {
+            pos_x: -3230.0,
+            pos_y: 1713.0,
+            scale: 5.0,
+        },
+        levels: &[RadarAssetLevel {
+            name: "default",
+            altitude_min: -10000.0,
+            altitude_max: 10000.0,
+            image: radar_image!("de_mirage/map_style_cs2.png", 1024, 1024),
+        }],
+    },
+    RadarAsset {
+        map_name: "de_nuke",
+        display_name: "Nuke",
+        calibration: MapCalibration {
+            pos_x: -3453.0,
+            pos_y: 2887.0,
+            scale: 7.0,
+        },
+        levels: &[
+            RadarAssetLevel {
+                name: "default",
+                altitude_min: -495.0,
+                altitude_max: 10000.0,
+                image: radar_image!("de_nuke/map_style_cs2.png", 1024, 1024),
+            },
+            RadarAssetLevel {
+                name: "lower",
+                altitude_min: -10000.0,
+                altitude_max: -495.0,
+                image: radar_image!("de_nuke/radar_1_lower.png", 1024, 1024),
+            },
+        ],
+    },
+    RadarAsset {
+        map_name: "de_overpass",
+        display_name: "Overpass",
+        calibration: MapCalibration {
+            pos_x: -4831.0,
+            pos_y: 1781.0,
+            scale: 5.2,
+        },
+        levels: &[RadarAssetLevel {
+            name: "default",
+            altitude_min: -10000.0,
+            altitude_max: 10000.0,
+            image: radar_image!("de_overpass/map_style_cs2.png", 1024, 1024),
+        }],
+    },
+    RadarAsset {
+        map_name: "de_thera",
+        display_name: "Thera",
+        calibration: MapCalibration {
+            pos_x: -85.609764,
+            pos_y: 2261.8025,
+            scale: 4.85,
+        },
+        levels: &[RadarAssetLevel {
+            name: "default",
+            altitude_min: -10000.0,
+            altitude_max: 10000.0,
+            image: radar_image!("de_thera/map_style_cs2.png", 1024, 1024),
+        }],
+    },
+    RadarAsset {
+        map_name: "de_train",
+        display_name: "Train",
+        calibration: MapCalibration {
+            pos_x: -2510.0,
+            pos_y: 2440.0,
+            scale: 4.74,
+        },
+        levels: &[RadarAssetLevel {
+            name: "default",
+            altitude_min: -10000.0,
+            altitude_max: 10000.0,
+            image: radar_image!("de_train/map_style_simple_radar.png", 2048, 2048),
+        }],
+    },
+    RadarAsset {
+        map_name: "de_vertigo",
+        display_name: "Vertigo",
+        calibration: MapCalibration {
+            pos_x: -3168.0,
+            pos_y: 1762.0,
+            scale: 4.0,
+        },
+        levels: &[
+            RadarAssetLevel {
+                name: "default",
+                altitude_min: 11700.0,
+                altitude_max: 20000.0,
+                image: radar_image!("de_vertigo/map_style_cs2.png", 1024, 1024),
+            },
+            RadarAssetLevel {
+                name: "lower",
+                altitude_min: -10000.0,
+                altitude_max: 11700.0,
+                image: radar_image!("de_vertigo/radar_1_lower.png", 1024, 1024),
+            },
+        ],
+    },
+];
+
+/// Radar image metadata (and the image contents if embedded) of a map
+pub fn radar_assets(map_name: &str) -> Option<RadarAsset> {
+    RADAR_ASSETS
+        .iter()
+        .find(|asset| asset.map_name == map_name)
+        .copied()
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{
+        radar_assets,
+        RADAR_ASSETS,
+        RADAR_OVERVIEW_RESOLUTION,
+    };
+    use crate::CoordinateTransform;
+
+    /// Read the image dimensions from the PNG header
+    fn png_dimensions(path: &Path) -> (u32, u32) {
+        let data = std::fs::read(path).unwrap();
+        assert_eq!(&data[1..4], b"PNG", "{}", path.display());
+
+        let width = u32::from_be_bytes(data[16..20].try_into().unwrap());
+        let height = u32::from_be_bytes(data[20..24].try_into().unwrap());
+        (width, height)
+    }
+
+    #[test]
+    fn image_dimensions() {
+        let image_directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("../web/src/map-info");
+
+        for asset in RADAR_ASSETS {
+            assert!(!asset.levels.is_empty(), "{}", asset.map_name);
+            for level in asset.levels {
+                let path = level.image.resolve_path(&image_directory);
+                assert_eq!(
+                    png_dimensions(&path),
+                    (level.image.width, level.image.height),
+                    "{}",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn calibration_covers_image() {
+        let asset = radar_assets("de_dust2").unwrap();
+        let transform = CoordinateTransform::radar_image_pixels(&asset.calibration);
+
+        let size = RADAR_OVERVIEW_RESOLUTION as f32 * asset.calibration.scale;
+        let lower_right = transform.apply([
+            asset.calibration.pos_x + size,
+            asset.calibration.pos_y - size,
+            0.0,
+        ]);
+
+        let image = &asset.levels[0].image;
+        assert!((lower_right[0] - image.width as f32).abs() < 0.01);
+        assert!((lower_right[1] - image.height as f32).abs() < 0.01);
+    }
+
+    #[test]
+    fn levels() {
+        let asset = radar_assets("de_nuke").unwrap();
+        assert_eq!(asset.level(0.0).map(|level| level.name), Some("default"));
+        assert_eq!(asset.level(-600.0).map(|level| level.name), Some("lower"));
+        assert!(radar_assets("de_unknown").is_none());
+    }
+}
@@ -5,14 +5,22 @@ use std::{
 
 use anyhow::Context;
 use cs2::{
+    BombSummary,
     CEntityIdentityEx,
     ClassNameCache,
+    ControllerIndex,
+    MatchContext,
+    StateAlivePlayerCount,
     StateCS2Memory,
     StateCurrentMap,
     StateEntityList,
     StateGlobals,
     StateLocalPlayerController,
+    StateOffsetValidation,
     StatePawnInfo,
+    StatePlayerControllers,
+    TeamEconomy,
+    WeaponId,
 };
 use cs2_schema_cutl::EntityHandle;
 use cs2_schema_generated::cs2::client::{
@@ -27,14 +35,18 @@ use obfstr::obfstr;
 use radar_shared::{
     BombDefuser,
     PlantedC4State,
+    RadarBombSummary,
     RadarC4,
+    RadarMatchContext,
     RadarPlantedC4,
     RadarPlayerPawn,
     RadarState,
+    RadarTeamEconomy,
 };
 use utils_state::StateRegistry;
 
 use super::RadarGenerator;
+use crate::CoordinateTransform;
 
 fn planted_c4_to_radar_state(
     generator: &CS2RadarGenerator,
@@ -46,7 +58,7 @@ fn planted_c4_to_radar_state(
 
     let globals = generator.states.resolve::<StateGlobals>(())?;
     let time_fuse = planted_c4.m_flC4Blow()?.m_Value()?;
-    if time_fuse <= globals.time_2()? {
+    if globals.time_remaining(time_fuse)? <= 0.0 {
         return Ok(PlantedC4State::Detonated {});
     }
 
@@ -65,6 +77,9 @@ fn planted_c4_to_radar_state(
             .value_reference(memory.view_arc())
             .context("entity nullptr")?;
 
+        let defuser_health = defuser.m_iHealth()?;
+        let defuser_armor = defuser.m_ArmorValue()?;
+
         let defuser_controller = defuser.m_hController()?;
         let defuser_controller = entities
             .entity_from_handle(&defuser_controller)
@@ -78,30 +93,85 @@ fn planted_c4_to_radar_state(
             .unwrap_or("Name Error".into())
             .to_string();
 
+        let is_last_alive_ct = generator
+            .states
+            .resolve::<StateAlivePlayerCount>(())
+            .map(|count| count.counter_terrorists <= 1)
+            .unwrap_or(false);
+
         Some(BombDefuser {
-            time_remaining: time_defuse - globals.time_2()?,
+            time_remaining: globals.time_remaining(time_defuse)?,
             time_total: time_total,
 
             player_name: defuser_name,
+
+            health: defuser_health,
+            armor: defuser_armor,
+            is_last_alive_ct,
         })
     } else {
         None
     };
 
     Ok(PlantedC4State::Active {
-        time_detonation: time_fuse - globals.time_2()?,
+        time_detonation: globals.time_remaining(time_fuse)?,
         time_total,
         defuser,
     })
 }
 
+fn team_economy_to_radar(economy: TeamEconomy) -> RadarTeamEconomy {
+    match economy {
+        TeamEconomy::Eco => RadarTeamEconomy::Eco,
+        TeamEconomy::ForceBuy => RadarTeamEconomy::ForceBuy,
+        TeamEconomy::FullBuy => RadarTeamEconomy::FullBuy,
+    }
+}
+
+fn bomb_summary_to_radar(bomb: BombSummary) -> RadarBombSummary {
+    match bomb {
+        BombSummary::Absent => RadarBombSummary::Absent,
+        BombSummary::Carried => RadarBombSummary::Carried,
+        BombSummary::Loose => RadarBombSummary::Loose,
+        BombSummary::Planted => RadarBombSummary::Planted,
+        BombSummary::Defused => RadarBombSummary::Defused,
+        BombSummary::Detonated => RadarBombSummary::Detonated,
+    }
+}
+
 pub struct CS2RadarGenerator {
     states: StateRegistry,
+    coordinate_transform: CoordinateTransform,
 }
 
 impl CS2RadarGenerator {
     pub fn new(states: StateRegistry) -> anyhow::Result<Self> {
-        Ok(Self { states })
+        Ok(Self {
+            states,
+            coordinate_transform: Default::default(),
+        })
+    }
+
+    /// Set the transform applied to all positions (and view yaws) of the generated radar state.
+    pub fn with_coordinate_transform(mut self, transform: CoordinateTransform) -> Self {
+        self.coordinate_transform = transform;
+        self
+    }
+
+    fn apply_coordinate_transform(&self, radar_state: &mut RadarState) {
+        let transform = &self.coordinate_transform;
+        for pawn in radar_state.player_pawns.iter_mut() {
+            pawn.position = transform.apply(pawn.position);
+            pawn.rotation = transform.apply_yaw(pawn.rotation);
+        }
+
+        if let Some(planted_c4) = &mut radar_state.planted_c4 {
+            planted_c4.position = transform.apply(planted_c4.position);
+        }
+
+        for c4 in radar_state.c4_entities.iter_mut() {
+            c4.position = transform.apply(c4.position);
+        }
     }
 
     fn generate_pawn_info(
@@ -109,14 +179,24 @@ impl CS2RadarGenerator {
         player_pawn_handle: EntityHandle<dyn C_CSPlayerPawn>,
     ) -> anyhow::Result<RadarPlayerPawn> {
         let pawn_info = self.states.resolve::<StatePawnInfo>(player_pawn_handle)?;
+        if !pawn_info.position_confidence.is_available() {
+            anyhow::bail!("player position unavailable");
+        }
+
+        let weapon = if pawn_info.weapon_confidence.is_available() {
+            pawn_info.weapon
+        } else {
+            WeaponId::Unknown
+        };
 
         Ok(RadarPlayerPawn {
-            controller_entity_id: pawn_info.controller_entity_id,
-            pawn_entity_id: pawn_info.pawn_entity_id,
+            controller_entity_id: pawn_info.controller_entity_id.map(ControllerIndex::value),
+            pawn_entity_id: pawn_info.pawn_entity_id.value(),
 
             player_name: pawn_info.player_name.clone().unwrap_or_default(),
             player_flashtime: pawn_info.player_flashtime,
-            player_has_defuser: pawn_info.player_has_defuser,
+            player_has_defuser: pawn_info.economy_confidence.is_available()
+                && pawn_info.player_has_defuser,
             player_health: pawn_info.player_health,
 
             position: [
@@ -124,10 +204,10 @@ impl CS2RadarGenerator {
                 pawn_info.position.y,
                 pawn_info.position.z,
             ],
-            rotation: pawn_info.rotation,
+            rotation: pawn_info.view_angles.yaw_ccw_from_x(),
 
             team_id: pawn_info.team_id,
-            weapon: pawn_info.weapon.id(),
+            weapon: weapon.id(),
         })
     }
 }
@@ -135,6 +215,7 @@ impl CS2RadarGenerator {
 impl RadarGenerator for CS2RadarGenerator {
     fn generate_state(&mut self) -> anyhow::Result<RadarState> {
         self.states.invalidate_states();
+        let _ = self.states.resolve::<StateOffsetValidation>(());
 
         let memory = self.states.resolve::<StateCS2Memory>(())?;
         let current_map = self.states.resolve::<StateCurrentMap>(())?;
@@ -151,6 +232,7 @@ impl RadarGenerator for CS2RadarGenerator {
             c4_entities: Default::default(),
 
             local_controller_entity_id: None,
+            degraded_reason: self.states.degraded_reason(),
         };
 
         let local_controller = self.states.resolve::<StateLocalPlayerController>(())?;
@@ -255,6 +337,44 @@ impl RadarGenerator for CS2RadarGenerator {
             }
         }
 
+        /* order the players like the controllers so the order does not change on reconnects */
+        if let Ok(controllers) = self.states.resolve::<StatePlayerControllers>(()) {
+            radar_state.player_pawns.sort_by_key(|pawn| {
+                let order = pawn.controller_entity_id.and_then(|entity_index| {
+                    controllers
+                        .instances
+                        .iter()
+                        .position(|entry| entry.entity_index.value() == entity_index)
+                });
+
+                (order.is_none(), order, pawn.pawn_entity_id)
+            });
+        }
+
+        self.apply_coordinate_transform(&mut radar_state);
         Ok(radar_state)
     }
+
+    fn generate_match_context(&mut self) -> anyhow::Result<Option<RadarMatchContext>> {
+        /* reuses the states of the radar state generated this frame */
+        let context = self.states.resolve::<MatchContext>(())?;
+        Ok(Some(RadarMatchContext {
+            score_terrorists: context.score_terrorists,
+            score_counter_terrorists: context.score_counter_terrorists,
+            round_number: context.round_number,
+            local_team_id: context.local_team_id,
+
+            alive_terrorists: context.alive_terrorists,
+            alive_counter_terrorists: context.alive_counter_terrorists,
+
+            economy_terrorists: context.economy_terrorists.map(team_economy_to_radar),
+            economy_counter_terrorists: context
+                .economy_counter_terrorists
+                .map(team_economy_to_radar),
+
+            bomb: context.bomb.map(bomb_summary_to_radar),
+            paused: context.paused,
+            degraded_reason: context.degraded_reason.clone(),
+        }))
+    }
 }
@@ -0,0 +1,222 @@
+use cs2::{
+    BombState,
+    DemoMatch,
+    GameSnapshot,
+    PlantedC4Entry,
+    DEMO_MAP,
+};
+use radar_shared::{
+    BombDefuser,
+    PlantedC4State,
+    RadarBombSummary,
+    RadarC4,
+    RadarMatchContext,
+    RadarPlantedC4,
+    RadarPlayerPawn,
+    RadarState,
+    RadarTeamEconomy,
+};
+
+use super::RadarGenerator;
+
+/// Entity index of the carried C4
+const DEMO_C4_ENTITY_ID: u32 = 400;
+
+const TEAM_ID_TERRORIST: u8 = 2;
+const TEAM_ID_COUNTER_TERRORIST: u8 = 3;
+
+fn planted_c4_to_radar(planted_c4: &PlantedC4Entry) -> RadarPlantedC4 {
+    let state = match planted_c4.state {
+        cs2::PlantedC4State::Active { time_detonation } => PlantedC4State::Active {
+            time_detonation,
+            time_total: planted_c4.time_blow - planted_c4.plant_time,
+            defuser: planted_c4.defuser.as_ref().map(|defuser| BombDefuser {
+                time_remaining: defuser.time_remaining,
+                time_total: defuser.defuse_duration_total,
+                player_name: defuser.player_name.clone(),
+                health: defuser.health,
+                armor: defuser.armor,
+                is_last_alive_ct: defuser.is_last_alive_ct,
+            }),
+        },
+        cs2::PlantedC4State::Defused => PlantedC4State::Defused {},
+        /* the demo never reports a bomb which has not been planted */
+        cs2::PlantedC4State::Detonated | cs2::PlantedC4State::NotPlanted => {
+            PlantedC4State::Detonated {}
+        }
+    };
+
+    RadarPlantedC4 {
+        position: planted_c4.position.into(),
+        bomb_site: planted_c4.bomb_site,
+        state,
+    }
+}
+
+/// Generates a deterministic stream of plausible radar states without CS2 running
+/// from the snapshots of a [DemoMatch].
+/// The same seed will always produce the same sequence of states.
+pub struct DemoRadarGenerator {
+    demo: DemoMatch,
+    snapshot: GameSnapshot,
+}
+
+impl DemoRadarGenerator {
+    pub fn new(seed: u64) -> Self {
+        let demo = DemoMatch::new(seed);
+        let snapshot = demo.snapshot();
+        Self { demo, snapshot }
+    }
+}
+
+impl RadarGenerator for DemoRadarGenerator {
+    fn generate_state(&mut self) -> anyhow::Result<RadarState> {
+        self.demo.advance();
+        self.snapshot = self.demo.snapshot();
+
+        let player_pawns = self
+            .snapshot
+            .players
+            .iter()
+            .filter(|player| player.alive)
+            .filter_map(|player| {
+                let details = player.details.as_ref()?;
+                Some(RadarPlayerPawn {
+                    controller_entity_id: player.controller_entity_id.map(|index| index.value()),
+                    pawn_entity_id: player.pawn_entity_id.value(),
+                    team_id: player.team_id,
+
+                    player_name: details.player_name.clone().unwrap_or_default(),
+                    player_health: details.player_health,
+                    player_has_defuser: self.demo.has_defuser(player.pawn_entity_id),
+                    player_flashtime: self.demo.flash_remaining(player.pawn_entity_id),
+
+                    weapon: details.weapon.id(),
+
+                    position: player.position.into(),
+                    rotation: details.view_angles.yaw_ccw_from_x(),
+                })
+            })
+            .collect();
+
+        let c4_entities = match &self.snapshot.bomb {
+            BombState::Carried {
+                carrier_entity_id,
+                position,
+                ..
+            } => vec![RadarC4 {
+                entity_id: DEMO_C4_ENTITY_ID,
+                position: (*position).into(),
+                owner_entity_id: Some(carrier_entity_id.value()),
+            }],
+            _ => Vec::new(),
+        };
+
+        Ok(RadarState {
+            world_name: DEMO_MAP.to_string(),
+            player_pawns,
+            planted_c4: self.snapshot.planted_c4.as_ref().map(planted_c4_to_radar),
+            c4_entities,
+            local_controller_entity_id: Some(self.demo.local_controller().value()),
+            degraded_reason: None,
+        })
+    }
+
+    fn generate_match_context(&mut self) -> anyhow::Result<Option<RadarMatchContext>> {
+        let alive = |team_id: u8| {
+            self.snapshot
+                .players
+                .iter()
+                .filter(|player| player.team_id == team_id && player.alive)
+                .count() as u32
+        };
+
+        let bomb = match self.snapshot.planted_c4.as_ref().map(|bomb| bomb.state) {
+            None => RadarBombSummary::Carried,
+            Some(cs2::PlantedC4State::Active { .. }) => RadarBombSummary::Planted,
+            Some(cs2::PlantedC4State::Defused) => RadarBombSummary::Defused,
+            Some(cs2::PlantedC4State::Detonated | cs2::PlantedC4State::NotPlanted) => {
+                RadarBombSummary::Detonated
+            }
+        };
+
+        Ok(Some(RadarMatchContext {
+            score_terrorists: Some(self.demo.score_terrorists()),
+            score_counter_terrorists: Some(self.demo.score_counter_terrorists()),
+            round_number: Some(self.demo.round_number()),
+            local_team_id: Some(TEAM_ID_TERRORIST),
+
+            alive_terrorists: Some(alive(TEAM_ID_TERRORIST)),
+            alive_counter_terrorists: Some(alive(TEAM_ID_COUNTER_TERRORIST)),
+
+            economy_terrorists: Some(RadarTeamEconomy::FullBuy),
+            economy_counter_terrorists: Some(RadarTeamEconomy::FullBuy),
+
+            bomb: Some(bomb),
+            paused: Some(false),
+            degraded_reason: None,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cs2::DEMO_TICK_INTERVAL;
+
+    use super::DemoRadarGenerator;
+    use crate::RadarGenerator;
+
+    #[test]
+    fn deterministic() {
+        let mut generator_a = DemoRadarGenerator::new(1337);
+        let mut generator_b = DemoRadarGenerator::new(1337);
+        for _ in 0..2000 {
+            let state_a = serde_json::to_string(&generator_a.generate_state().unwrap()).unwrap();
+            let state_b = serde_json::to_string(&generator_b.generate_state().unwrap()).unwrap();
+            assert_eq!(state_a, state_b);
+        }
+    }
+
+    #[test]
+    fn plausible_states() {
+        for seed in 0..8 {
+            let mut generator = DemoRadarGenerator::new(seed);
+            let mut plants = 0;
+            let mut was_planted = false;
+
+            /* simulate 10 minutes */
+            for _ in 0..(600.0 / DEMO_TICK_INTERVAL) as usize {
+                let state = generator.generate_state().unwrap();
+                if let Err(err) = generator.snapshot.validate() {
+                    panic!(
+                        "seed {} round {}: {}",
+                        seed,
+                        generator.demo.round_number(),
+                        err
+                    );
+                }
+
+                /* the bomb is either carried or planted */
+                assert_eq!(
+                    state.c4_entities.len() + state.planted_c4.iter().count(),
+                    1,
+                    "seed {}",
+                    seed
+                );
+
+                let planted = state.planted_c4.is_some();
+                if planted && !was_planted {
+                    plants += 1;
+                }
+                was_planted = planted;
+
+                let context = generator.generate_match_context().unwrap().unwrap();
+                assert_eq!(context.round_number, Some(generator.demo.round_number()));
+            }
+
+            let round_number = generator.demo.round_number();
+            assert!(round_number > 3, "seed {}", seed);
+            assert!(plants >= round_number - 1, "seed {}", seed);
+        }
+    }
+}
@@ -12,6 +12,7 @@ impl RadarGenerator for DummyRadarGenerator {
             planted_c4: None,
             player_pawns: Vec::new(),
             local_controller_entity_id: None,
+            degraded_reason: None,
         };
         Ok(state)
     }
@@ -1,4 +1,7 @@
-use radar_shared::RadarState;
+use radar_shared::{
+    RadarMatchContext,
+    RadarState,
+};
 
 mod cs2;
 pub use cs2::CS2RadarGenerator;
@@ -6,6 +9,15 @@ pub use cs2::CS2RadarGenerator;
 mod dummy;
 pub use dummy::DummyRadarGenerator;
 
+mod demo;
+pub use demo::DemoRadarGenerator;
+
 pub trait RadarGenerator: Send {
     fn generate_state(&mut self) -> anyhow::Result<RadarState>;
+
+    /// Generate the match context header from the states of the last generated radar state.
+    /// Returns None if the generator does not support match context headers.
+    fn generate_match_context(&mut self) -> anyhow::Result<Option<RadarMatchContext>> {
+        Ok(None)
+    }
 }
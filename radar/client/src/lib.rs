@@ -6,3 +6,9 @@ pub use publish::*;
 
 mod transport;
 pub use transport::*;
+
+mod transform;
+pub use transform::*;
+
+mod assets;
+pub use assets::*;
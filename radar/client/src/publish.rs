@@ -4,10 +4,13 @@ use std::{
 };
 
 use anyhow::Context;
-use radar_shared::protocol::{
-    C2SMessage,
-    ClientEvent,
-    S2CMessage,
+use radar_shared::{
+    protocol::{
+        C2SMessage,
+        ClientEvent,
+        S2CMessage,
+    },
+    RadarMatchContext,
 };
 use tokio::{
     self,
@@ -34,6 +37,10 @@ pub struct WebRadarPublisher {
     generator: Option<Box<dyn RadarGenerator>>,
     generate_interval: Pin<Box<Interval>>,
 
+    /// Last published match context header.
+    /// The header will only be published when it changes.
+    match_context: Option<RadarMatchContext>,
+
     transport_tx: Sender<C2SMessage>,
     transport_rx: Receiver<ClientEvent<S2CMessage>>,
 }
@@ -85,6 +92,7 @@ impl WebRadarPublisher {
 
             generator: None,
             generate_interval: Box::pin(time::interval(Duration::from_millis(50))),
+            match_context: None,
 
             transport_rx: rx,
             transport_tx: tx,
@@ -124,6 +132,7 @@ impl WebRadarPublisher {
                 },
                 _ = self.generate_interval.tick() => {
                     self.send_radar_state();
+                    self.send_match_context();
                 }
             }
         }
@@ -142,6 +151,28 @@ impl WebRadarPublisher {
         }
     }
 
+    fn send_match_context(&mut self) {
+        let Some(generator) = &mut self.generator else {
+            return;
+        };
+
+        match generator.generate_match_context() {
+            Ok(Some(context)) => {
+                if self.match_context.as_ref() == Some(&context) {
+                    /* match context did not change */
+                    return;
+                }
+
+                self.match_context = Some(context.clone());
+                self.send_message(C2SMessage::NotifyMatchContext { context });
+            }
+            Ok(None) => { /* generator does not support match context headers */ }
+            Err(err) => {
+                log::warn!("Failed to generate match context: {:#}", err);
+            }
+        }
+    }
+
     fn handle_event(&mut self, event: ClientEvent<S2CMessage>) -> anyhow::Result<()> {
         match event {
             ClientEvent::RecvError(err) => {
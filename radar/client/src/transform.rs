@@ -0,0 +1,283 @@
+use std::array;
+
+use cs2::units::METERS_PER_UNIT;
+
+/// Radar image calibration of a map.
+/// The values equal to the ones found within the map overview text files.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapCalibration {
+    /// World x coordinate of the upper left image corner
+    pub pos_x: f32,
+
+    /// World y coordinate of the upper left image corner
+    pub pos_y: f32,
+
+    /// World units per image pixel
+    pub scale: f32,
+}
+
+/// Conversion applied to every position leaving the radar generator.
+/// Internally all positions are kept in engine units.
+///
+/// A position gets transformed by `scale * (axes * position) + offset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoordinateTransform {
+    /// Row-major axis remapping matrix
+    pub axes: [[f32; 3]; 3],
+
+    /// Uniform scale applied after the axis remapping
+    pub scale: f32,
+
+    /// Offset applied after scaling
+    pub offset: [f32; 3],
+}
+
+impl Default for CoordinateTransform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl CoordinateTransform {
+    /// Engine units, Z-up
+    pub const fn identity() -> Self {
+        Self {
+            axes: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            scale: 1.0,
+            offset: [0.0, 0.0, 0.0],
+        }
+    }
+
+    /// Meters, Z-up (see [METERS_PER_UNIT])
+    pub const fn meters_z_up() -> Self {
+        Self {
+            scale: METERS_PER_UNIT,
+            ..Self::identity()
+        }
+    }
+
+    /// Pixel coordinates on the radar image of the map (Y-down).
+    /// The Z axis remains in engine units.
+    pub fn radar_image_pixels(calibration: &MapCalibration) -> Self {
+        Self {
+            axes: [
+                [1.0 / calibration.scale, 0.0, 0.0],
+                [0.0, -1.0 / calibration.scale, 0.0],
+                [0.0, 0.0, 1.0],
+            ],
+            scale: 1.0,
+            offset: [
+                -calibration.pos_x / calibration.scale,
+                calibration.pos_y / calibration.scale,
+                0.0,
+            ],
+        }
+    }
+
+    pub fn apply(&self, position: [f32; 3]) -> [f32; 3] {
+        let mut result = [0.0; 3];
+        for (index, row) in self.axes.iter().enumerate() {
+            let value = row[0] * position[0] + row[1] * position[1] + row[2] * position[2];
+            result[index] = value * self.scale + self.offset[index];
+        }
+        result
+    }
+
+    /// Transform a yaw (in degrees, counter-clockwise from the positive X axis) by the axis remapping.
+    /// Mirrored axes (e.g. the Y-down radar image) reverse the rotation direction.
+    /// The result is within [-180, 180).
+    pub fn apply_yaw(&self, yaw: f32) -> f32 {
+        let (sin, cos) = yaw.to_radians().sin_cos();
+        let x = (self.axes[0][0] * cos + self.axes[0][1] * sin) * self.scale;
+        let y = (self.axes[1][0] * cos + self.axes[1][1] * sin) * self.scale;
+
+        let yaw = y.atan2(x).to_degrees();
+        if yaw >= 180.0 {
+            yaw - 360.0
+        } else {
+            yaw
+        }
+    }
+
+    /// Create a transform which first applies `self` and then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        let axes = array::from_fn(|row| {
+            array::from_fn(|column| {
+                (0..3)
+                    .map(|k| next.axes[row][k] * self.axes[k][column])
+                    .sum::<f32>()
+                    * self.scale
+                    * next.scale
+            })
+        });
+
+        Self {
+            axes,
+            scale: 1.0,
+            offset: next.apply(self.offset),
+        }
+    }
+
+    /// Returns the inverse transform or `None` if the transform is not invertible.
+    pub fn inverse(&self) -> Option<Self> {
+        let [[a, b, c], [d, e, f], [g, h, i]] = self.axes;
+        let determinant = a * (e * i - f * h) - b * (d * i - f * g) + c * (d * h - e * g);
+        if determinant.abs() <= f32::EPSILON || self.scale.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let factor = 1.0 / (determinant * self.scale);
+        let axes = [
+            [
+                (e * i - f * h) * factor,
+                (c * h - b * i) * factor,
+                (b * f - c * e) * factor,
+            ],
+            [
+                (f * g - d * i) * factor,
+                (a * i - c * g) * factor,
+                (c * d - a * f) * factor,
+            ],
+            [
+                (d * h - e * g) * factor,
+                (b * g - a * h) * factor,
+                (a * e - b * d) * factor,
+            ],
+        ];
+
+        let mut inverse = Self {
+            axes,
+            scale: 1.0,
+            offset: [0.0; 3],
+        };
+        let offset = inverse.apply(self.offset);
+        inverse.offset = [-offset[0], -offset[1], -offset[2]];
+        Some(inverse)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        CoordinateTransform,
+        MapCalibration,
+    };
+
+    const EPSILON: f32 = 1e-3;
+    const POSITIONS: &[[f32; 3]] = &[
+        [0.0, 0.0, 0.0],
+        [-2476.0, 3239.0, 0.0],
+        [1234.5, -987.25, 128.0],
+        [-16384.0, 16384.0, -4096.0],
+    ];
+
+    fn assert_identity(transform: &CoordinateTransform) {
+        let inverse = transform.inverse().expect("transform to be invertible");
+        for position in POSITIONS {
+            let result = inverse.apply(transform.apply(*position));
+            for axis in 0..3 {
+                let error = (result[axis] - position[axis]).abs();
+                assert!(
+                    error <= EPSILON * position[axis].abs().max(1.0),
+                    "{:?} -> {:?}",
+                    position,
+                    result
+                );
+            }
+
+            let result = transform.then(&inverse).apply(*position);
+            for axis in 0..3 {
+                let error = (result[axis] - position[axis]).abs();
+                assert!(error <= EPSILON * position[axis].abs().max(1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn identity() {
+        assert_identity(&CoordinateTransform::identity());
+        assert_eq!(
+            CoordinateTransform::identity().apply([1.0, 2.0, 3.0]),
+            [1.0, 2.0, 3.0]
+        );
+    }
+
+    #[test]
+    fn meters_z_up() {
+        assert_identity(&CoordinateTransform::meters_z_up());
+
+        let result = CoordinateTransform::meters_z_up().apply([100.0, 0.0, 0.0]);
+        assert!((result[0] - 2.54).abs() < EPSILON);
+    }
+
+    #[test]
+    fn radar_image_pixels() {
+        let transform = CoordinateTransform::radar_image_pixels(&MapCalibration {
+            pos_x: -2476.0,
+            pos_y: 3239.0,
+            scale: 4.4,
+        });
+        assert_identity(&transform);
+
+        let upper_left = transform.apply([-2476.0, 3239.0, 0.0]);
+        assert!(upper_left[0].abs() < EPSILON);
+        assert!(upper_left[1].abs() < EPSILON);
+
+        let lower_right = transform.apply([-2476.0 + 4.4 * 1024.0, 3239.0 - 4.4 * 1024.0, 0.0]);
+        assert!((lower_right[0] - 1024.0).abs() < EPSILON);
+        assert!((lower_right[1] - 1024.0).abs() < EPSILON);
+    }
+
+    /// Difference between two angles in degrees
+    fn yaw_difference(a: f32, b: f32) -> f32 {
+        (a - b + 540.0).rem_euclid(360.0) - 180.0
+    }
+
+    #[test]
+    fn yaw() {
+        let radar_image = CoordinateTransform::radar_image_pixels(&MapCalibration {
+            pos_x: -2476.0,
+            pos_y: 3239.0,
+            scale: 4.4,
+        });
+
+        for yaw in [-180.0, -135.0, -90.0, -30.0, 0.0, 45.0, 90.0, 179.0] {
+            for transform in [
+                CoordinateTransform::identity(),
+                CoordinateTransform::meters_z_up(),
+            ] {
+                assert!(yaw_difference(transform.apply_yaw(yaw), yaw).abs() < EPSILON);
+            }
+
+            /* the Y-down radar image mirrors the rotation */
+            let mirrored = radar_image.apply_yaw(yaw);
+            assert!(
+                yaw_difference(mirrored, -yaw).abs() < EPSILON,
+                "{} -> {}",
+                yaw,
+                mirrored
+            );
+            assert!((-180.0..180.0).contains(&mirrored));
+
+            /* the view direction follows the transformed positions */
+            let (sin, cos) = yaw.to_radians().sin_cos();
+            let origin = radar_image.apply([100.0, 200.0, 0.0]);
+            let target = radar_image.apply([100.0 + cos * 100.0, 200.0 + sin * 100.0, 0.0]);
+            let direction = (target[1] - origin[1])
+                .atan2(target[0] - origin[0])
+                .to_degrees();
+            assert!(yaw_difference(mirrored, direction).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn composed() {
+        let transform = CoordinateTransform::radar_image_pixels(&MapCalibration {
+            pos_x: -2476.0,
+            pos_y: 3239.0,
+            scale: 4.4,
+        })
+        .then(&CoordinateTransform::meters_z_up());
+        assert_identity(&transform);
+    }
+}
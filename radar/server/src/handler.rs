@@ -48,10 +48,13 @@ impl ServerCommandHandler {
                     session_auth_token: session.session_auth_token.clone(),
                 }
             }
-            C2SMessage::InitializeSubscribe { session_id, .. } => {
+            C2SMessage::InitializeSubscribe {
+                session_id,
+                header_only,
+            } => {
                 let mut server = self.server.write().await;
                 match server
-                    .pub_session_subscribe(&session_id, self.client_id)
+                    .pub_session_subscribe(&session_id, self.client_id, header_only)
                     .await
                 {
                     PubSessionSubscribeResult::Success => S2CMessage::ResponseSubscribeSuccess {},
@@ -67,34 +70,12 @@ impl ServerCommandHandler {
                 }
             }
             C2SMessage::NotifyRadarState { state } => {
-                let server = self.server.read().await;
-                let client = self.client.read().await;
-
-                let session_id = match &client.state {
-                    ClientState::Publisher { session_id } => session_id,
-                    _ => return S2CMessage::ResponseInvalidClientState {},
-                };
-
-                let session = match server.pub_session_find(session_id) {
-                    Some(session) => session,
-                    None => return S2CMessage::ResponseSessionInvalidId {},
-                };
-
-                let PubSessionOwner::Owned {
-                    client_id: owner_client_id,
-                } = &session.owner
-                else {
-                    return S2CMessage::ResponseSessionInvalidId {};
-                };
-
-                if *owner_client_id != client.client_id {
-                    return S2CMessage::ResponseError {
-                        error: "you're not allowed to send updates".to_string(),
-                    };
-                }
-
-                session.broadcast(&S2CMessage::NotifyRadarState { state });
-                S2CMessage::ResponseSuccess {}
+                self.broadcast_publisher_update(S2CMessage::NotifyRadarState { state })
+                    .await
+            }
+            C2SMessage::NotifyMatchContext { context } => {
+                self.broadcast_publisher_update(S2CMessage::NotifyMatchContext { context })
+                    .await
             }
             C2SMessage::Disconnect { .. } => {
                 /* command is already handled within the connection code */
@@ -102,4 +83,37 @@ impl ServerCommandHandler {
             }
         }
     }
+
+    /// Broadcast an update to all session subscribers.
+    /// Only the owner of the session is allowed to publish updates.
+    async fn broadcast_publisher_update(&self, message: S2CMessage) -> S2CMessage {
+        let mut server = self.server.write().await;
+        let client = self.client.read().await;
+
+        let session_id = match &client.state {
+            ClientState::Publisher { session_id } => session_id,
+            _ => return S2CMessage::ResponseInvalidClientState {},
+        };
+
+        let session = match server.pub_session_find(session_id) {
+            Some(session) => session,
+            None => return S2CMessage::ResponseSessionInvalidId {},
+        };
+
+        let PubSessionOwner::Owned {
+            client_id: owner_client_id,
+        } = &session.owner
+        else {
+            return S2CMessage::ResponseSessionInvalidId {};
+        };
+
+        if *owner_client_id != client.client_id {
+            return S2CMessage::ResponseError {
+                error: "you're not allowed to send updates".to_string(),
+            };
+        }
+
+        session.broadcast(&message);
+        S2CMessage::ResponseSuccess {}
+    }
 }
@@ -13,10 +13,13 @@ use std::{
 };
 
 use futures_util::Future;
-use radar_shared::protocol::{
-    C2SMessage,
-    ClientEvent,
-    S2CMessage,
+use radar_shared::{
+    protocol::{
+        C2SMessage,
+        ClientEvent,
+        S2CMessage,
+    },
+    RadarMatchContext,
 };
 use rand::{
     distributions::Alphanumeric,
@@ -52,19 +55,39 @@ pub enum PubSessionOwner {
     Unbound { timestamp: Instant },
 }
 
+struct PubSubscriber {
+    tx: mpsc::Sender<S2CMessage>,
+
+    /// Only the match context header and session notifications will be sent
+    header_only: bool,
+}
+
 pub struct PubSession {
     pub owner: PubSessionOwner,
 
     pub session_id: String,
     pub session_auth_token: String,
 
-    subscriber: BTreeMap<u32, mpsc::Sender<S2CMessage>>,
+    subscriber: BTreeMap<u32, PubSubscriber>,
+
+    /// Latest match context of the publisher.
+    /// The match context is only published on change, hence new subscribers receive it when subscribing.
+    match_context: Option<RadarMatchContext>,
 }
 
 impl PubSession {
-    pub fn broadcast(&self, message: &S2CMessage) {
+    pub fn broadcast(&mut self, message: &S2CMessage) {
+        if let S2CMessage::NotifyMatchContext { context } = message {
+            self.match_context = Some(context.clone());
+        }
+
+        let is_radar_state = matches!(message, S2CMessage::NotifyRadarState { .. });
         for subscriber in self.subscriber.values() {
-            let _ = subscriber.try_send(message.clone());
+            if is_radar_state && subscriber.header_only {
+                continue;
+            }
+
+            let _ = subscriber.tx.try_send(message.clone());
         }
     }
 
@@ -316,6 +339,7 @@ impl RadarServer {
                 session_auth_token: session_auth_token.clone(),
 
                 subscriber: Default::default(),
+                match_context: None,
             },
         );
 
@@ -377,7 +401,7 @@ impl RadarServer {
     }
 
     pub async fn pub_session_close(&mut self, session_id: &str) {
-        let session = match self.pub_sessions.remove(session_id) {
+        let mut session = match self.pub_sessions.remove(session_id) {
             Some(session) => session,
             None => return,
         };
@@ -396,8 +420,8 @@ impl RadarServer {
         }
     }
 
-    pub fn pub_session_find(&self, session_id: &str) -> Option<&PubSession> {
-        self.pub_sessions.get(session_id)
+    pub fn pub_session_find(&mut self, session_id: &str) -> Option<&mut PubSession> {
+        self.pub_sessions.get_mut(session_id)
     }
 
     pub async fn pub_session_unsubscribe(&mut self, session_id: &String, client_id: u32) {
@@ -429,6 +453,7 @@ impl RadarServer {
         &mut self,
         session_id: &String,
         client_id: u32,
+        header_only: bool,
     ) -> PubSessionSubscribeResult {
         let client = match self.clients.get(&client_id) {
             Some(client) => client,
@@ -445,14 +470,24 @@ impl RadarServer {
             None => return PubSessionSubscribeResult::InvalidSessionId,
         };
 
-        session
-            .subscriber
-            .insert(client.client_id, client.tx.clone());
+        session.subscriber.insert(
+            client.client_id,
+            PubSubscriber {
+                tx: client.tx.clone(),
+                header_only,
+            },
+        );
 
         session.broadcast(&S2CMessage::NotifyViewCount {
             viewers: session.subscriber.len(),
         });
 
+        if let Some(context) = &session.match_context {
+            let _ = client.tx.try_send(S2CMessage::NotifyMatchContext {
+                context: context.clone(),
+            });
+        }
+
         client.state = ClientState::Subscriber {
             session_id: session.session_id.clone(),
         };
@@ -0,0 +1,15 @@
+use std::fs;
+
+use anyhow::Context;
+use radar_shared::export_schema;
+
+fn main() -> anyhow::Result<()> {
+    let Some(target) = std::env::args().nth(1) else {
+        anyhow::bail!("please provide a target path")
+    };
+
+    fs::write(&target, export_schema()).context("write target")?;
+
+    println!("JSON schema written to {}", target);
+    Ok(())
+}
@@ -2,3 +2,6 @@ pub mod protocol;
 
 mod types;
 pub use types::*;
+
+mod schema;
+pub use schema::*;
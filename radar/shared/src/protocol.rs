@@ -1,21 +1,25 @@
+use schemars::JsonSchema;
 use serde::{
     Deserialize,
     Serialize,
 };
 use typescript_type_def::TypeDef;
 
-use crate::RadarState;
+use crate::{
+    RadarMatchContext,
+    RadarState,
+};
 
-pub const RADAR_PROTOCOL_VERSION: u32 = 2;
+pub const RADAR_PROTOCOL_VERSION: u32 = 3;
 
-#[derive(Serialize, Deserialize, Clone, Debug, TypeDef)]
+#[derive(Serialize, Deserialize, Clone, Debug, TypeDef, JsonSchema)]
 pub enum SubscribeResult {
     Success,
     SessionDoesNotExists,
     // SessionRequiresPassword,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, TypeDef)]
+#[derive(Serialize, Deserialize, Clone, Debug, TypeDef, JsonSchema)]
 #[serde(rename_all = "kebab-case", tag = "type", content = "payload")]
 pub enum S2CMessage {
     // Generic responses
@@ -35,13 +39,16 @@ pub enum S2CMessage {
     NotifyRadarState {
         state: RadarState,
     },
+    NotifyMatchContext {
+        context: RadarMatchContext,
+    },
     NotifyViewCount {
         viewers: usize,
     },
     NotifySessionClosed {},
 }
 
-#[derive(Serialize, Deserialize, TypeDef)]
+#[derive(Serialize, Deserialize, TypeDef, JsonSchema)]
 #[serde(rename_all = "kebab-case", tag = "type", content = "payload")]
 pub enum C2SMessage {
     InitializePublish {
@@ -50,12 +57,22 @@ pub enum C2SMessage {
     },
     InitializeSubscribe {
         session_id: String,
+
+        /// Only receive the match context header instead of the full radar state
+        #[serde(default)]
+        header_only: bool,
     },
 
     NotifyRadarState {
         state: RadarState,
     },
 
+    /// Match context header only.
+    /// Published whenever the match context changes.
+    NotifyMatchContext {
+        context: RadarMatchContext,
+    },
+
     Disconnect {
         reason: String,
     },
@@ -67,7 +84,7 @@ pub enum ClientEvent<T> {
     SendError(anyhow::Error),
 }
 
-#[derive(Serialize, Deserialize, TypeDef)]
+#[derive(Serialize, Deserialize, TypeDef, JsonSchema)]
 pub enum HandshakeProtocolV1 {
     /*
      * Protocol version 1
@@ -83,7 +100,7 @@ pub enum HandshakeProtocolV1 {
     ResponseError { error: String },
 }
 
-#[derive(Serialize, Deserialize, TypeDef)]
+#[derive(Serialize, Deserialize, TypeDef, JsonSchema)]
 #[serde(
     rename_all = "kebab-case",
     rename_all_fields = "camelCase",
@@ -98,7 +115,7 @@ pub enum HandshakeProtocolV2 {
     ResponseGenericFailure { message: String },
 }
 
-#[derive(Serialize, Deserialize, TypeDef)]
+#[derive(Serialize, Deserialize, TypeDef, JsonSchema)]
 #[serde(untagged)]
 pub enum HandshakeMessage {
     V1(HandshakeProtocolV1),
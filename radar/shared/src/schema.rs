@@ -0,0 +1,72 @@
+use schemars::{
+    schema_for,
+    JsonSchema,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::protocol::{
+    C2SMessage,
+    HandshakeMessage,
+    S2CMessage,
+};
+
+/// Any message exchanged between the radar server and its clients
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
+pub enum RadarMessage {
+    ServerToClient(S2CMessage),
+    ClientToServer(C2SMessage),
+    Handshake(HandshakeMessage),
+}
+
+/// Export the JSON schema of all radar messages (including the radar state).
+/// Definitions of all referenced types can be found within `definitions`.
+pub fn export_schema() -> String {
+    let schema = schema_for!(RadarMessage);
+    serde_json::to_string_pretty(&schema).expect("the schema to be serializable")
+}
+
+#[cfg(test)]
+mod test {
+    use super::export_schema;
+    use crate::protocol::S2CMessage;
+
+    const RADAR_STATE_FIXTURE: &str = include_str!("../resources/radar_state_fixture.json");
+
+    fn validator() -> jsonschema::JSONSchema {
+        let schema = serde_json::from_str::<serde_json::Value>(&export_schema()).unwrap();
+        jsonschema::JSONSchema::compile(&schema).unwrap()
+    }
+
+    #[test]
+    fn radar_state_fixture() {
+        let fixture = serde_json::from_str::<serde_json::Value>(RADAR_STATE_FIXTURE).unwrap();
+        let validator = validator();
+        if let Err(errors) = validator.validate(&fixture) {
+            let errors = errors.map(|error| error.to_string()).collect::<Vec<_>>();
+            panic!("fixture does not match the schema: {:?}", errors);
+        }
+
+        /* the fixture must be a valid message as well */
+        let message = serde_json::from_value::<S2CMessage>(fixture.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&message).unwrap(), fixture);
+    }
+
+    #[test]
+    fn rejects_invalid_state() {
+        let mut fixture = serde_json::from_str::<serde_json::Value>(RADAR_STATE_FIXTURE).unwrap();
+        fixture["payload"]["state"]["plantedC4"]["state"]["state"] = "exploding".into();
+        assert!(!validator().is_valid(&fixture));
+
+        let mut fixture = serde_json::from_str::<serde_json::Value>(RADAR_STATE_FIXTURE).unwrap();
+        fixture["payload"]["state"]["playerPawns"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("teamId");
+        assert!(!validator().is_valid(&fixture));
+    }
+}
@@ -1,10 +1,11 @@
+use schemars::JsonSchema;
 use serde::{
     Deserialize,
     Serialize,
 };
 use typescript_type_def::TypeDef;
 
-#[derive(Serialize, Deserialize, Clone, Debug, TypeDef)]
+#[derive(Serialize, Deserialize, Clone, Debug, TypeDef, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct BombDefuser {
     /// Total time remaining for a successful bomb defuse
@@ -15,9 +16,18 @@ pub struct BombDefuser {
 
     /// The defusers player name
     pub player_name: String,
+
+    /// Current health of the defuser
+    pub health: i32,
+
+    /// Current armor of the defuser
+    pub armor: i32,
+
+    /// The defuser is the last alive counter-terrorist
+    pub is_last_alive_ct: bool,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, TypeDef)]
+#[derive(Serialize, Deserialize, Clone, Debug, TypeDef, JsonSchema)]
 #[serde(rename_all = "kebab-case", tag = "state")]
 pub enum PlantedC4State {
     /// Bomb is currently actively ticking
@@ -41,7 +51,7 @@ pub enum PlantedC4State {
     Defused {},
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, TypeDef)]
+#[derive(Serialize, Deserialize, Clone, Debug, TypeDef, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RadarState {
     pub world_name: String,
@@ -51,9 +61,12 @@ pub struct RadarState {
     pub c4_entities: Vec<RadarC4>,
 
     pub local_controller_entity_id: Option<u32>,
+
+    /// Reason why the radar state is incomplete (e.g. outdated offsets)
+    pub degraded_reason: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, TypeDef)]
+#[derive(Serialize, Deserialize, Clone, Debug, TypeDef, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RadarPlayerPawn {
     pub controller_entity_id: Option<u32>,
@@ -68,10 +81,12 @@ pub struct RadarPlayerPawn {
     pub weapon: u16,
 
     pub position: [f32; 3],
+
+    /// View yaw in degrees [-180, 180), counter-clockwise from the positive X axis (engine convention)
     pub rotation: f32,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, TypeDef)]
+#[derive(Serialize, Deserialize, Clone, Debug, TypeDef, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RadarPlantedC4 {
     pub position: [f32; 3],
@@ -84,10 +99,53 @@ pub struct RadarPlantedC4 {
     pub state: PlantedC4State,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, TypeDef)]
+#[derive(Serialize, Deserialize, Clone, Debug, TypeDef, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct RadarC4 {
     pub entity_id: u32,
     pub position: [f32; 3],
     pub owner_entity_id: Option<u32>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, TypeDef, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RadarTeamEconomy {
+    Eco,
+    ForceBuy,
+    FullBuy,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, TypeDef, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RadarBombSummary {
+    /// There is no bomb within the match (e.g. on hostage maps)
+    Absent,
+    Carried,
+    Loose,
+    Planted,
+    Defused,
+    Detonated,
+}
+
+/// Lightweight summary of the current match.
+/// Fields which could not be determined will be null.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, TypeDef, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RadarMatchContext {
+    pub score_terrorists: Option<i32>,
+    pub score_counter_terrorists: Option<i32>,
+    pub round_number: Option<i32>,
+    pub local_team_id: Option<u8>,
+
+    pub alive_terrorists: Option<u32>,
+    pub alive_counter_terrorists: Option<u32>,
+
+    pub economy_terrorists: Option<RadarTeamEconomy>,
+    pub economy_counter_terrorists: Option<RadarTeamEconomy>,
+
+    pub bomb: Option<RadarBombSummary>,
+    pub paused: Option<bool>,
+
+    /// Reason why the match context is incomplete (e.g. outdated offsets)
+    pub degraded_reason: Option<String>,
+}
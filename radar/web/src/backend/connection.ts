@@ -1,5 +1,5 @@
 import { EventEmitter } from "../utils/ee";
-import { C2SMessage, HandshakeProtocolV2, RadarState, S2CMessage } from "./definitions";
+import { C2SMessage, HandshakeProtocolV2, RadarMatchContext, RadarState, S2CMessage } from "./definitions";
 
 export type SubscriberClientState =
     | {
@@ -13,6 +13,7 @@ export type SubscriberClientState =
 export interface SubscriberClientEvents {
     state_changed: SubscriberClientState;
     "radar.state": RadarState;
+    "radar.match-context": RadarMatchContext;
 }
 
 export class SubscriberClient {
@@ -48,6 +49,10 @@ export class SubscriberClient {
             this.events.emit("radar.state", payload.state);
         };
 
+        this.commandHandler["notify-match-context"] = (payload) => {
+            this.events.emit("radar.match-context", payload.context);
+        };
+
         this.commandHandler["notify-session-closed"] = () => {
             this.updateState({ state: "disconnected" });
         };
@@ -94,7 +99,7 @@ export class SubscriberClient {
                 JSON.stringify({
                     type: "request-initialize",
                     payload: {
-                        clientVersion: 2,
+                        clientVersion: 3,
                     },
                 } satisfies HandshakeProtocolV2),
             );
@@ -33,6 +33,21 @@ export type BombDefuser = {
      * The defusers player name
      */
     playerName: string;
+
+    /**
+     * Current health of the defuser
+     */
+    health: I32;
+
+    /**
+     * Current armor of the defuser
+     */
+    armor: I32;
+
+    /**
+     * The defuser is the last alive counter-terrorist
+     */
+    isLastAliveCt: boolean;
 };
 export type PlantedC4State =
     | ({
@@ -90,6 +105,35 @@ export type RadarState = {
     plantedC4: RadarPlantedC4 | null;
     c4Entities: RadarC4[];
     localControllerEntityId: U32 | null;
+
+    /**
+     * Reason why the radar state is incomplete (e.g. outdated offsets)
+     */
+    degradedReason: string | null;
+};
+export type RadarTeamEconomy = "eco" | "force-buy" | "full-buy";
+export type RadarBombSummary = "absent" | "carried" | "loose" | "planted" | "defused" | "detonated";
+
+/**
+ * Lightweight summary of the current match.
+ * Fields which could not be determined will be null.
+ */
+export type RadarMatchContext = {
+    scoreTerrorists: I32 | null;
+    scoreCounterTerrorists: I32 | null;
+    roundNumber: I32 | null;
+    localTeamId: U8 | null;
+    aliveTerrorists: U32 | null;
+    aliveCounterTerrorists: U32 | null;
+    economyTerrorists: RadarTeamEconomy | null;
+    economyCounterTerrorists: RadarTeamEconomy | null;
+    bomb: RadarBombSummary | null;
+    paused: boolean | null;
+
+    /**
+     * Reason why the match context is incomplete (e.g. outdated offsets)
+     */
+    degradedReason: string | null;
 };
 export type Usize = number;
 export type S2CMessage =
@@ -127,6 +171,12 @@ export type S2CMessage =
               state: RadarState;
           };
       }
+    | {
+          type: "notify-match-context";
+          payload: {
+              context: RadarMatchContext;
+          };
+      }
     | {
           type: "notify-view-count";
           payload: {
@@ -146,6 +196,10 @@ export type C2SMessage =
           type: "initialize-subscribe";
           payload: {
               session_id: string;
+              /**
+               * Only receive the match context header instead of the full radar state
+               */
+              header_only?: boolean;
           };
       }
     | {
@@ -154,6 +208,15 @@ export type C2SMessage =
               state: RadarState;
           };
       }
+    | {
+          type: "notify-match-context";
+          payload: {
+              /**
+               * Published whenever the match context changes.
+               */
+              context: RadarMatchContext;
+          };
+      }
     | {
           type: "disconnect";
           payload: {
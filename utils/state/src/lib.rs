@@ -6,6 +6,7 @@ use std::{
         TypeId,
     },
     cell::{
+        Cell,
         Ref,
         RefCell,
         RefMut,
@@ -13,13 +14,20 @@ use std::{
     collections::{
         hash_map::Entry,
         HashMap,
+        HashSet,
     },
+    fmt,
     hash::{
         DefaultHasher,
         Hash,
         Hasher,
     },
     ops::DerefMut,
+    panic::{
+        self,
+        AssertUnwindSafe,
+    },
+    sync::Arc,
     time::{
         Duration,
         Instant,
@@ -31,6 +39,15 @@ use anyhow::{
     Context,
 };
 
+mod watchdog;
+pub use watchdog::*;
+
+mod poller;
+pub use poller::*;
+
+mod shutdown;
+pub use shutdown::*;
+
 pub enum StateCacheType {
     /// The state will be cached and never removed
     Persistent,
@@ -62,6 +79,77 @@ pub trait State: Any + Sized + Send {
     fn update(&mut self, _states: &StateRegistry) -> anyhow::Result<()> {
         Ok(())
     }
+
+    /// Explicit output of this state while the registry is in degraded mode
+    /// or after the state panicked without a previous value to fall back on.
+    /// Only used for volatile states. Returning None will create the state regularly
+    /// while degraded and report the panic otherwise.
+    fn unavailable(_reason: &str) -> Option<Self> {
+        None
+    }
+}
+
+/// A state panicked while being created or updated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatePanicked {
+    /// Type name of the state
+    pub state: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for StatePanicked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "state {} panicked: {}", self.state, self.message)
+    }
+}
+
+impl std::error::Error for StatePanicked {}
+
+impl StatePanicked {
+    fn from_payload<T: State>(payload: Box<dyn Any + Send>) -> Self {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "unknown panic payload".to_string()
+        };
+
+        Self {
+            state: any::type_name::<T>(),
+            message,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StateCounters {
+    /// Amount of times the state has been created
+    pub creations: u64,
+
+    /// Amount of times the state has been updated
+    pub updates: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StateStatistics {
+    /// Total amount of panics caught while creating or updating states
+    pub panics: u64,
+
+    /// The most recent panic
+    pub last_panic: Option<StatePanicked>,
+
+    /// Creations and updates by the state type
+    pub states: HashMap<TypeId, StateCounters>,
+}
+
+impl StateStatistics {
+    pub fn counters<T: State>(&self) -> StateCounters {
+        self.states
+            .get(&TypeId::of::<T>())
+            .copied()
+            .unwrap_or_default()
+    }
 }
 
 fn value_update_proxy<T: State>(
@@ -155,6 +243,18 @@ fn transpose_ref_mut_opt<T>(x: RefMut<'_, Option<T>>) -> Option<RefMut<'_, T>> {
 pub struct StateRegistry {
     allocator: RefCell<StateAllocator>,
     states: Vec<RefCell<Option<InternalState>>>,
+
+    /// Reason why the registry is in degraded mode
+    degraded_reason: RefCell<Option<String>>,
+
+    statistics: RefCell<StateStatistics>,
+    heartbeat: Arc<StateHeartbeat>,
+
+    /// States resolved while creating or updating a state (state -> dependencies)
+    dependencies: RefCell<HashMap<TypeId, HashSet<TypeId>>>,
+
+    /// Incremented every time a new dependency has been recorded
+    dependency_generation: Cell<u64>,
 }
 
 impl StateRegistry {
@@ -164,10 +264,125 @@ impl StateRegistry {
         Self {
             allocator: RefCell::new(StateAllocator::new(capacity)),
             states,
+
+            degraded_reason: Default::default(),
+            statistics: Default::default(),
+            heartbeat: Default::default(),
+
+            dependencies: Default::default(),
+            dependency_generation: Cell::new(0),
+        }
+    }
+
+    pub fn statistics(&self) -> StateStatistics {
+        self.statistics.borrow().clone()
+    }
+
+    /// Heartbeat of this registry (e.g. for a [StateWatchdog])
+    pub fn heartbeat(&self) -> Arc<StateHeartbeat> {
+        self.heartbeat.clone()
+    }
+
+    /// Dependencies of the given state type recorded so far.
+    /// Dependencies are recorded when they are resolved while creating or updating the state.
+    pub fn dependencies(&self, state: TypeId) -> Vec<TypeId> {
+        self.dependencies
+            .borrow()
+            .get(&state)
+            .map(|dependencies| dependencies.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// All states (including the roots) which are transitively required by the given states
+    pub fn dependency_closure(&self, roots: impl IntoIterator<Item = TypeId>) -> HashSet<TypeId> {
+        let dependencies = self.dependencies.borrow();
+
+        let mut result = HashSet::new();
+        let mut pending = roots.into_iter().collect::<Vec<_>>();
+        while let Some(state) = pending.pop() {
+            if !result.insert(state) {
+                continue;
+            }
+
+            if let Some(state_dependencies) = dependencies.get(&state) {
+                pending.extend(state_dependencies.iter().copied());
+            }
+        }
+
+        result
+    }
+
+    /// Changes every time a new dependency has been recorded
+    pub fn dependency_generation(&self) -> u64 {
+        self.dependency_generation.get()
+    }
+
+    fn record_dependency<T: State>(&self) {
+        let Some(parent) = self.heartbeat.current_state() else {
+            return;
+        };
+
+        if self
+            .dependencies
+            .borrow_mut()
+            .entry(parent)
+            .or_default()
+            .insert(TypeId::of::<T>())
+        {
+            self.dependency_generation
+                .set(self.dependency_generation.get() + 1);
+        }
+    }
+
+    fn record_operation<T: State>(&self, operation: StateOperation) {
+        let mut statistics = self.statistics.borrow_mut();
+        let counters = statistics.states.entry(TypeId::of::<T>()).or_default();
+        match operation {
+            StateOperation::Create => counters.creations += 1,
+            StateOperation::Update => counters.updates += 1,
         }
     }
 
+    fn record_panic<T: State>(&self, payload: Box<dyn Any + Send>) -> StatePanicked {
+        let panic = StatePanicked::from_payload::<T>(payload);
+
+        let mut statistics = self.statistics.borrow_mut();
+        statistics.panics += 1;
+        statistics.last_panic = Some(panic.clone());
+        panic
+    }
+
+    /// Output of a state which panicked without a previous value to fall back on.
+    /// Only volatile states will be substituted by their unavailable output (if any).
+    fn panic_fallback<T: State>(panic: &StatePanicked) -> Option<T> {
+        match T::cache_type() {
+            StateCacheType::Volatile => T::unavailable(&panic.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Enter the degraded mode.
+    /// While degraded, volatile states will be replaced by their unavailable output (if any)
+    /// instead of reading potentially invalid data.
+    pub fn enter_degraded_mode(&self, reason: impl Into<String>) {
+        *self.degraded_reason.borrow_mut() = Some(reason.into());
+    }
+
+    pub fn exit_degraded_mode(&self) {
+        *self.degraded_reason.borrow_mut() = None;
+    }
+
+    pub fn degraded_reason(&self) -> Option<String> {
+        self.degraded_reason.borrow().clone()
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.degraded_reason.borrow().is_some()
+    }
+
     pub fn invalidate_states(&mut self) {
+        self.heartbeat.begin_frame();
+
         /* As we're mutable there should be no more references to the underlying state */
         let mut allocator = self.allocator.borrow_mut();
 
@@ -198,6 +413,19 @@ impl StateRegistry {
         }
     }
 
+    /// Drop all states including persistent ones and exit the degraded mode.
+    /// Statistics and the heartbeat remain untouched.
+    pub fn clear(&mut self) {
+        let mut allocator = self.allocator.borrow_mut();
+        for state in self.states.iter_mut() {
+            if let Some(state) = state.get_mut().take() {
+                allocator.free_entry(&state.cache_key);
+            }
+        }
+
+        self.exit_degraded_mode();
+    }
+
     /// Preset a specific state
     pub fn set<T: State>(&mut self, value: T, params: T::Parameter) -> anyhow::Result<()> {
         let (cache_key, index) = self
@@ -264,39 +492,94 @@ impl StateRegistry {
         value: &mut RefMut<'_, Option<InternalState>>,
         params: T::Parameter,
     ) -> anyhow::Result<()> {
-        let value = match value.as_mut() {
-            Some(value) => value,
-            None => {
-                /* create a new value */
-                let state = Box::new(
-                    T::create(self, params)
-                        .with_context(|| format!("create {}", any::type_name::<T>()))?,
-                );
-                **value = Some(InternalState {
-                    value: state,
-                    value_update: value_update_proxy::<T>,
-
-                    cache_key,
-                    cache_type: T::cache_type(),
-
-                    dirty: true,
-                    last_access: Instant::now(),
-                });
-
-                value.as_mut().unwrap()
-            }
-        };
+        let created = value.is_none();
+        if created {
+            let unavailable = match T::cache_type() {
+                StateCacheType::Volatile => self
+                    .degraded_reason
+                    .borrow()
+                    .as_deref()
+                    .and_then(T::unavailable),
+                _ => None,
+            };
+
+            /* create a new value */
+            #[cfg(feature = "tracing")]
+            let _span =
+                tracing::trace_span!("state_create", state = any::type_name::<T>()).entered();
+
+            let state = match unavailable {
+                Some(state) => Box::new(state),
+                None => {
+                    /*
+                     * A panic must not take down the whole process.
+                     * Note: Panics can only be caught if the binary has not been built with panic = "abort".
+                     */
+                    let _active = self.heartbeat.enter::<T>(StateOperation::Create);
+                    let state = panic::catch_unwind(AssertUnwindSafe(|| T::create(self, params)))
+                        .or_else(|payload| {
+                        let panic = self.record_panic::<T>(payload);
+                        Self::panic_fallback::<T>(&panic).map(Ok).ok_or(panic)
+                    })?;
+                    self.record_operation::<T>(StateOperation::Create);
+
+                    Box::new(state.with_context(|| format!("create {}", any::type_name::<T>()))?)
+                }
+            };
+            **value = Some(InternalState {
+                value: state,
+                value_update: value_update_proxy::<T>,
 
-        if value.dirty {
-            (value.value_update)(&mut value.value, self)
-                .with_context(|| format!("update {}", any::type_name::<T>()))?;
-            value.dirty = false;
+                cache_key,
+                cache_type: T::cache_type(),
+
+                dirty: true,
+                last_access: Instant::now(),
+            });
+        }
+
+        let state = value.as_mut().expect("to be initialized");
+        if state.dirty {
+            #[cfg(feature = "tracing")]
+            let _span =
+                tracing::trace_span!("state_update", state = any::type_name::<T>()).entered();
+
+            let result = {
+                let _active = self.heartbeat.enter::<T>(StateOperation::Update);
+                panic::catch_unwind(AssertUnwindSafe(|| {
+                    (state.value_update)(&mut state.value, self)
+                }))
+            };
+            self.record_operation::<T>(StateOperation::Update);
+
+            match result {
+                Ok(result) => {
+                    result.with_context(|| format!("update {}", any::type_name::<T>()))?;
+                    state.dirty = false;
+                }
+                Err(payload) => {
+                    let panic = self.record_panic::<T>(payload);
+                    if created {
+                        /* there is no previous value which could be served */
+                        let Some(fallback) = Self::panic_fallback::<T>(&panic) else {
+                            **value = None;
+                            return Err(panic.into());
+                        };
+
+                        state.value = Box::new(fallback);
+                    }
+
+                    /* keep serving the previously cached value until the next update */
+                    state.dirty = false;
+                }
+            }
         }
 
         Ok(())
     }
 
     pub fn resolve_mut<T: State>(&self, params: T::Parameter) -> anyhow::Result<RefMut<'_, T>> {
+        self.record_dependency::<T>();
         let (cache_key, index) = self
             .allocator
             .borrow_mut()
@@ -316,6 +599,7 @@ impl StateRegistry {
     }
 
     pub fn resolve<T: State>(&self, params: T::Parameter) -> anyhow::Result<Ref<'_, T>> {
+        self.record_dependency::<T>();
         let (cache_key, index) = self
             .allocator
             .borrow_mut()
@@ -348,6 +632,7 @@ mod test {
     use super::{
         State,
         StateCacheType,
+        StatePanicked,
         StateRegistry,
     };
 
@@ -425,4 +710,238 @@ mod test {
         assert!(states.get::<StateA>(()).is_some());
         assert!(states.get::<StateB>(()).is_some());
     }
+
+    #[test]
+    fn test_clear() {
+        let mut states = StateRegistry::new(2);
+        assert!(states.resolve::<StateA>(()).is_ok());
+        assert!(states.resolve::<StateB>(()).is_ok());
+        states.enter_degraded_mode("detached");
+
+        states.clear();
+        assert!(states.get::<StateA>(()).is_none());
+        assert!(states.get::<StateB>(()).is_none());
+        assert!(!states.is_degraded());
+
+        /* all entries must have been released */
+        assert!(states.resolve::<StateA>(()).is_ok());
+        assert!(states.resolve::<StateB>(()).is_ok());
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum StateReading {
+        Value(u32),
+        Unavailable(String),
+    }
+
+    impl State for StateReading {
+        type Parameter = ();
+
+        fn create(_states: &StateRegistry, _params: Self::Parameter) -> anyhow::Result<Self> {
+            Ok(Self::Value(42))
+        }
+
+        fn cache_type() -> StateCacheType {
+            StateCacheType::Volatile
+        }
+
+        fn unavailable(reason: &str) -> Option<Self> {
+            Some(Self::Unavailable(reason.to_string()))
+        }
+    }
+
+    /// Trips the degraded mode when the validated value is not the expected one
+    struct StateValidation {
+        expected: u32,
+        current: u32,
+    }
+
+    impl State for StateValidation {
+        type Parameter = ();
+
+        fn cache_type() -> StateCacheType {
+            StateCacheType::Persistent
+        }
+
+        fn update(&mut self, states: &StateRegistry) -> anyhow::Result<()> {
+            if self.current == self.expected {
+                states.exit_degraded_mode();
+            } else {
+                states.enter_degraded_mode("validation failed");
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_degraded_mode() {
+        let mut states = StateRegistry::new(4);
+        states
+            .set(
+                StateValidation {
+                    expected: 1,
+                    current: 1,
+                },
+                (),
+            )
+            .unwrap();
+
+        let _ = states.resolve::<StateValidation>(()).unwrap();
+        assert!(!states.is_degraded());
+        assert_eq!(
+            *states.resolve::<StateReading>(()).unwrap(),
+            StateReading::Value(42)
+        );
+
+        /* trip the validation */
+        states.invalidate_states();
+        states.resolve_mut::<StateValidation>(()).unwrap().current = 2;
+        states.invalidate_states();
+        let _ = states.resolve::<StateValidation>(()).unwrap();
+        assert_eq!(
+            states.degraded_reason().as_deref(),
+            Some("validation failed")
+        );
+        assert_eq!(
+            *states.resolve::<StateReading>(()).unwrap(),
+            StateReading::Unavailable("validation failed".to_string())
+        );
+
+        /* persistent states and states without an unavailable output are not affected */
+        assert!(states.resolve::<StateA>(()).is_ok());
+
+        /* recovery */
+        states.invalidate_states();
+        states.resolve_mut::<StateValidation>(()).unwrap().current = 1;
+        states.invalidate_states();
+        let _ = states.resolve::<StateValidation>(()).unwrap();
+        assert!(!states.is_degraded());
+        assert_eq!(
+            *states.resolve::<StateReading>(()).unwrap(),
+            StateReading::Value(42)
+        );
+    }
+
+    struct StatePanicking;
+    impl State for StatePanicking {
+        type Parameter = ();
+
+        fn create(_states: &StateRegistry, _params: Self::Parameter) -> anyhow::Result<Self> {
+            let bones: Vec<u32> = Vec::new();
+            let _ = bones[3];
+            Ok(Self)
+        }
+
+        fn cache_type() -> StateCacheType {
+            StateCacheType::Volatile
+        }
+    }
+
+    /// Panics on every update after the first one
+    struct StatePanickingUpdate {
+        value: u32,
+    }
+
+    impl State for StatePanickingUpdate {
+        type Parameter = ();
+
+        fn create(_states: &StateRegistry, _params: Self::Parameter) -> anyhow::Result<Self> {
+            Ok(Self { value: 0 })
+        }
+
+        fn cache_type() -> StateCacheType {
+            StateCacheType::Persistent
+        }
+
+        fn update(&mut self, _states: &StateRegistry) -> anyhow::Result<()> {
+            if self.value > 0 {
+                panic!("update failed");
+            }
+
+            self.value += 1;
+            Ok(())
+        }
+    }
+
+    /// Panics while being created but provides an unavailable output
+    #[derive(Debug, PartialEq)]
+    enum StatePanickingReading {
+        Value(u32),
+        Unavailable(String),
+    }
+
+    impl State for StatePanickingReading {
+        type Parameter = ();
+
+        fn create(_states: &StateRegistry, _params: Self::Parameter) -> anyhow::Result<Self> {
+            let bones: Vec<u32> = Vec::new();
+            Ok(Self::Value(bones[3]))
+        }
+
+        fn cache_type() -> StateCacheType {
+            StateCacheType::Volatile
+        }
+
+        fn unavailable(reason: &str) -> Option<Self> {
+            Some(Self::Unavailable(reason.to_string()))
+        }
+    }
+
+    #[test]
+    fn test_panic_unavailable() {
+        let mut states = StateRegistry::new(4);
+
+        let reading = states.resolve::<StatePanickingReading>(()).unwrap();
+        let StatePanickingReading::Unavailable(reason) = &*reading else {
+            panic!("expected the unavailable output, got {:?}", reading);
+        };
+        assert!(reason.contains("index out of bounds"), "{}", reason);
+        drop(reading);
+        assert_eq!(states.statistics().panics, 1);
+
+        /* the unavailable output will be served until the next frame */
+        assert!(matches!(
+            *states.resolve::<StatePanickingReading>(()).unwrap(),
+            StatePanickingReading::Unavailable(_)
+        ));
+        assert_eq!(states.statistics().panics, 1);
+
+        /* the state will be recreated */
+        states.invalidate_states();
+        assert!(states.resolve::<StatePanickingReading>(()).is_ok());
+        assert_eq!(states.statistics().panics, 2);
+        assert!(states.resolve::<StateA>(()).is_ok());
+    }
+
+    #[test]
+    fn test_panic_containment() {
+        let mut states = StateRegistry::new(4);
+
+        let error = states.resolve::<StatePanicking>(()).err().unwrap();
+        let panic = error.downcast_ref::<StatePanicked>().unwrap();
+        assert!(panic.state.ends_with("StatePanicking"));
+        assert!(panic.message.contains("index out of bounds"));
+        assert_eq!(states.statistics().panics, 1);
+
+        /* other states keep functioning */
+        assert!(states.resolve::<StateA>(()).is_ok());
+        assert!(states.resolve::<StateB>(()).is_ok());
+
+        assert_eq!(states.resolve::<StatePanickingUpdate>(()).unwrap().value, 1);
+        states.invalidate_states();
+
+        /* the previous value will be served */
+        assert_eq!(states.resolve::<StatePanickingUpdate>(()).unwrap().value, 1);
+        assert_eq!(states.statistics().panics, 2);
+        assert_eq!(
+            states.statistics().last_panic.map(|panic| panic.message),
+            Some("update failed".to_string())
+        );
+
+        /* the panicking state will be retried */
+        states.invalidate_states();
+        assert!(states.resolve::<StatePanicking>(()).is_err());
+        assert!(states.resolve::<StateA>(()).is_ok());
+        assert_eq!(states.statistics().panics, 3);
+    }
 }
@@ -0,0 +1,303 @@
+use std::{
+    any::{
+        self,
+        Any,
+        TypeId,
+    },
+    collections::{
+        HashMap,
+        HashSet,
+    },
+};
+
+use crate::{
+    State,
+    StateRegistry,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+/// Resolves the subscribed state with its subscription parameters
+type SubscriptionResolver = Box<dyn Fn(&StateRegistry) -> anyhow::Result<()>>;
+
+struct Subscription {
+    id: SubscriptionId,
+    type_id: TypeId,
+    name: &'static str,
+    resolve: SubscriptionResolver,
+}
+
+struct PollerInput {
+    value: Box<dyn Any>,
+    publish: fn(&dyn Any, &mut StateRegistry) -> anyhow::Result<()>,
+}
+
+fn publish_input<T: State<Parameter = ()> + Clone>(
+    value: &dyn Any,
+    states: &mut StateRegistry,
+) -> anyhow::Result<()> {
+    let value = value.downcast_ref::<T>().expect("to be type T");
+    states.set(value.clone(), ())
+}
+
+/// A subscribed state which could not be resolved
+#[derive(Debug)]
+pub struct PollError {
+    /// Type name of the state
+    pub state: &'static str,
+    pub error: anyhow::Error,
+}
+
+/// Resolves the subscribed states every frame.
+///
+/// Only the subscribed states and their dependencies will be resolved.
+/// The dependencies are recorded by the [StateRegistry] when they are resolved
+/// while creating or updating a state.
+pub struct StatePoller {
+    subscriptions: Vec<Subscription>,
+    next_subscription_id: u64,
+
+    /// All states required by the subscriptions
+    closure: HashSet<TypeId>,
+
+    /// (subscriptions changed, dependency generation of the closure)
+    closure_version: (bool, u64),
+
+    /// States published into the registry at the start of every poll
+    inputs: HashMap<TypeId, PollerInput>,
+}
+
+impl Default for StatePoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatePoller {
+    pub fn new() -> Self {
+        Self {
+            subscriptions: Vec::new(),
+            next_subscription_id: 1,
+
+            closure: Default::default(),
+            closure_version: (true, 0),
+
+            inputs: Default::default(),
+        }
+    }
+
+    /// Subscribe to a state which will be resolved on every poll
+    pub fn subscribe<T: State>(&mut self, params: T::Parameter) -> SubscriptionId
+    where
+        T::Parameter: Clone + 'static,
+    {
+        let id = SubscriptionId(self.next_subscription_id);
+        self.next_subscription_id += 1;
+
+        self.subscriptions.push(Subscription {
+            id,
+            type_id: TypeId::of::<T>(),
+            name: any::type_name::<T>(),
+            resolve: Box::new(move |states| states.resolve::<T>(params.clone()).map(|_| ())),
+        });
+        self.closure_version.0 = true;
+        id
+    }
+
+    /// Returns false if the subscription does not exist
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let count = self.subscriptions.len();
+        self.subscriptions
+            .retain(|subscription| subscription.id != id);
+        if self.subscriptions.len() == count {
+            return false;
+        }
+
+        self.closure_version.0 = true;
+        true
+    }
+
+    /// Set an input of the subscribed states (e.g. preferences of the consumer).
+    /// The input will be published into the registry at the start of every poll
+    /// and should therefore be a volatile state so it falls back to its default once removed.
+    pub fn set_input<T: State<Parameter = ()> + Clone>(&mut self, value: T) {
+        self.inputs.insert(
+            TypeId::of::<T>(),
+            PollerInput {
+                value: Box::new(value),
+                publish: publish_input::<T>,
+            },
+        );
+    }
+
+    pub fn input<T: State>(&self) -> Option<&T> {
+        self.inputs
+            .get(&TypeId::of::<T>())
+            .and_then(|input| input.value.downcast_ref::<T>())
+    }
+
+    pub fn input_mut<T: State>(&mut self) -> Option<&mut T> {
+        self.inputs
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|input| input.value.downcast_mut::<T>())
+    }
+
+    pub fn remove_input<T: State>(&mut self) -> Option<T> {
+        self.inputs
+            .remove(&TypeId::of::<T>())
+            .and_then(|input| input.value.downcast::<T>().ok())
+            .map(|value| *value)
+    }
+
+    /// Start a new frame and resolve all subscribed states
+    pub fn poll(&mut self, states: &mut StateRegistry) -> Vec<PollError> {
+        states.invalidate_states();
+
+        let mut errors = Vec::new();
+        for input in self.inputs.values() {
+            if let Err(error) = (input.publish)(input.value.as_ref(), states) {
+                errors.push(PollError {
+                    state: "poller input",
+                    error,
+                });
+            }
+        }
+
+        for subscription in self.subscriptions.iter() {
+            if let Err(error) = (subscription.resolve)(states) {
+                errors.push(PollError {
+                    state: subscription.name,
+                    error,
+                });
+            }
+        }
+
+        let (subscriptions_changed, generation) = self.closure_version;
+        if subscriptions_changed || generation != states.dependency_generation() {
+            self.closure = states.dependency_closure(
+                self.subscriptions
+                    .iter()
+                    .map(|subscription| subscription.type_id),
+            );
+            self.closure_version = (false, states.dependency_generation());
+        }
+
+        errors
+    }
+
+    /// All states required by the current subscriptions.
+    /// Updated on every poll.
+    pub fn closure(&self) -> &HashSet<TypeId> {
+        &self.closure
+    }
+
+    pub fn is_required<T: State>(&self) -> bool {
+        self.closure.contains(&TypeId::of::<T>())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StatePoller;
+    use crate::{
+        State,
+        StateCacheType,
+        StateRegistry,
+    };
+
+    struct EntityList;
+    impl State for EntityList {
+        type Parameter = ();
+
+        fn create(_states: &StateRegistry, _params: Self::Parameter) -> anyhow::Result<Self> {
+            Ok(Self)
+        }
+
+        fn cache_type() -> StateCacheType {
+            StateCacheType::Persistent
+        }
+    }
+
+    struct Grenades;
+    impl State for Grenades {
+        type Parameter = ();
+
+        fn create(states: &StateRegistry, _params: Self::Parameter) -> anyhow::Result<Self> {
+            states.resolve::<EntityList>(())?;
+            Ok(Self)
+        }
+    }
+
+    struct Bomb;
+    impl State for Bomb {
+        type Parameter = ();
+
+        fn create(states: &StateRegistry, _params: Self::Parameter) -> anyhow::Result<Self> {
+            states.resolve::<EntityList>(())?;
+            Ok(Self)
+        }
+    }
+
+    #[derive(Debug, Clone, Default, PartialEq)]
+    struct Focus(Vec<u64>);
+    impl State for Focus {
+        type Parameter = ();
+
+        fn create(_states: &StateRegistry, _params: Self::Parameter) -> anyhow::Result<Self> {
+            Ok(Self::default())
+        }
+    }
+
+    #[test]
+    fn inputs() {
+        let mut states = StateRegistry::new(8);
+        let mut poller = StatePoller::new();
+        poller.set_input(Focus(vec![7]));
+        poller.input_mut::<Focus>().unwrap().0.push(9);
+        assert_eq!(poller.input::<Focus>(), Some(&Focus(vec![7, 9])));
+
+        for _ in 0..2 {
+            assert!(poller.poll(&mut states).is_empty());
+            assert_eq!(*states.resolve::<Focus>(()).unwrap(), Focus(vec![7, 9]));
+        }
+
+        assert_eq!(poller.remove_input::<Focus>(), Some(Focus(vec![7, 9])));
+        assert!(poller.poll(&mut states).is_empty());
+        assert_eq!(*states.resolve::<Focus>(()).unwrap(), Focus::default());
+    }
+
+    #[test]
+    fn unsubscribe() {
+        let mut states = StateRegistry::new(8);
+        let mut poller = StatePoller::new();
+        let grenades = poller.subscribe::<Grenades>(());
+        poller.subscribe::<Bomb>(());
+
+        for _ in 0..2 {
+            assert!(poller.poll(&mut states).is_empty());
+        }
+        assert!(poller.is_required::<Grenades>());
+        assert!(poller.is_required::<EntityList>());
+
+        let statistics = states.statistics();
+        assert_eq!(statistics.counters::<Grenades>().creations, 2);
+        assert_eq!(statistics.counters::<Bomb>().creations, 2);
+        assert_eq!(statistics.counters::<EntityList>().creations, 1);
+        assert_eq!(statistics.counters::<EntityList>().updates, 2);
+
+        assert!(poller.unsubscribe(grenades));
+        assert!(!poller.unsubscribe(grenades));
+        for _ in 0..3 {
+            assert!(poller.poll(&mut states).is_empty());
+        }
+        assert!(!poller.is_required::<Grenades>());
+        assert!(poller.is_required::<Bomb>());
+        assert!(poller.is_required::<EntityList>());
+
+        let statistics = states.statistics();
+        assert_eq!(statistics.counters::<Grenades>().creations, 2);
+        assert_eq!(statistics.counters::<Bomb>().creations, 5);
+        assert_eq!(statistics.counters::<EntityList>().updates, 5);
+    }
+}
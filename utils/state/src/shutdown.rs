@@ -0,0 +1,261 @@
+use std::{
+    fmt,
+    io,
+    sync::{
+        atomic::{
+            AtomicBool,
+            AtomicUsize,
+            Ordering,
+        },
+        Arc,
+        Mutex,
+        MutexGuard,
+        Weak,
+    },
+    thread::{
+        self,
+        JoinHandle,
+        Thread,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+#[derive(Default)]
+struct ShutdownInner {
+    requested: AtomicBool,
+
+    /// Threads spawned by the token which have not yet exited
+    threads: Mutex<Vec<Thread>>,
+    running: AtomicUsize,
+
+    children: Mutex<Vec<Weak<ShutdownInner>>>,
+}
+
+/// Shared shutdown request for background threads.
+/// Threads spawned by the token (see [ShutdownToken::spawn_thread]) will be woken up
+/// while sleeping (see [ShutdownToken::sleep]) as soon as the shutdown has been requested.
+///
+/// Requesting the shutdown also requests the shutdown of all child tokens (see [ShutdownToken::child]).
+#[derive(Clone, Default)]
+pub struct ShutdownToken {
+    inner: Arc<ShutdownInner>,
+}
+
+impl fmt::Debug for ShutdownToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShutdownToken")
+            .field("requested", &self.is_requested())
+            .field("running_threads", &self.running_threads())
+            .finish()
+    }
+}
+
+impl ShutdownToken {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn lock_threads(&self) -> MutexGuard<'_, Vec<Thread>> {
+        self.inner
+            .threads
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+
+    fn children(&self) -> Vec<ShutdownToken> {
+        let mut children = self
+            .inner
+            .children
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+
+        children.retain(|child| child.strong_count() > 0);
+        children
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|inner| ShutdownToken { inner })
+            .collect()
+    }
+
+    /// Create a token which will be requested together with this token
+    /// but can also be requested on its own (e.g. to stop a single component).
+    pub fn child(&self) -> ShutdownToken {
+        let child = ShutdownToken::new();
+        self.inner
+            .children
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .push(Arc::downgrade(&child.inner));
+
+        if self.is_requested() {
+            child.request();
+        }
+        child
+    }
+
+    /// Request all threads of this token and its children to shut down
+    pub fn request(&self) {
+        self.inner.requested.store(true, Ordering::Relaxed);
+        for thread in self.lock_threads().iter() {
+            thread.unpark();
+        }
+
+        for child in self.children() {
+            child.request();
+        }
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.inner.requested.load(Ordering::Relaxed)
+    }
+
+    /// Spawn a thread which counts as running until it exits.
+    /// The thread will be woken up while sleeping (see [ShutdownToken::sleep]) once the shutdown has been requested.
+    pub fn spawn_thread<T: Send + 'static>(
+        &self,
+        name: &str,
+        callback: impl FnOnce() -> T + Send + 'static,
+    ) -> io::Result<JoinHandle<T>> {
+        /* count the thread before spawning it so it can't be missed by wait_stopped */
+        self.inner.running.fetch_add(1, Ordering::Relaxed);
+        let guard = ShutdownThreadGuard {
+            token: self.clone(),
+        };
+
+        thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                guard.token.lock_threads().push(thread::current());
+                let _guard = guard;
+                callback()
+            })
+    }
+
+    /// Amount of threads spawned by this token or any of its children which did not yet exit
+    pub fn running_threads(&self) -> usize {
+        self.inner.running.load(Ordering::Relaxed)
+            + self
+                .children()
+                .iter()
+                .map(ShutdownToken::running_threads)
+                .sum::<usize>()
+    }
+
+    /// Sleep for the given duration or until the shutdown has been requested.
+    /// The current thread must have been spawned by the token to be woken up early.
+    /// Returns true if the shutdown has been requested.
+    pub fn sleep(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        loop {
+            if self.is_requested() {
+                return true;
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+
+            thread::park_timeout(deadline - now);
+        }
+    }
+
+    /// Wait until all threads have exited.
+    /// Returns false if there are still threads running after the timeout.
+    pub fn wait_stopped(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while self.running_threads() > 0 {
+            if Instant::now() >= deadline {
+                return false;
+            }
+
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        true
+    }
+}
+
+/// Marks the thread as exited when dropped (including unwinding)
+struct ShutdownThreadGuard {
+    token: ShutdownToken,
+}
+
+impl Drop for ShutdownThreadGuard {
+    fn drop(&mut self) {
+        let current = thread::current().id();
+        self.token
+            .lock_threads()
+            .retain(|thread| thread.id() != current);
+        self.token.inner.running.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{
+        Duration,
+        Instant,
+    };
+
+    use super::ShutdownToken;
+
+    #[test]
+    fn wakes_sleeping_threads() {
+        let token = ShutdownToken::new();
+        let threads = (0..4)
+            .map(|_| {
+                let sleeper = token.clone();
+                token
+                    .spawn_thread(
+                        "sleeper",
+                        move || {
+                            while !sleeper.sleep(Duration::from_secs(60)) {}
+                        },
+                    )
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(token.running_threads(), 4);
+
+        let requested = Instant::now();
+        token.request();
+        assert!(token.wait_stopped(Duration::from_secs(5)));
+        assert!(requested.elapsed() < Duration::from_secs(5));
+        assert_eq!(token.running_threads(), 0);
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn child_tokens() {
+        let parent = ShutdownToken::new();
+        let first = parent.child();
+        let second = parent.child();
+
+        first.request();
+        assert!(first.is_requested());
+        assert!(!second.is_requested() && !parent.is_requested());
+
+        let sleeper = second.clone();
+        let thread = second
+            .spawn_thread(
+                "sleeper",
+                move || while !sleeper.sleep(Duration::from_secs(60)) {},
+            )
+            .unwrap();
+        assert_eq!(parent.running_threads(), 1);
+
+        parent.request();
+        assert!(second.is_requested());
+        assert!(parent.child().is_requested());
+
+        thread.join().unwrap();
+        assert_eq!(parent.running_threads(), 0);
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    any::{
+        self,
+        TypeId,
+    },
+    sync::{
+        atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        Arc,
+        Mutex,
+        MutexGuard,
+    },
+    thread::JoinHandle,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use crate::{
+    ShutdownToken,
+    State,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateOperation {
+    Create,
+    Update,
+}
+
+/// A state which is currently being created or updated
+#[derive(Debug, Clone)]
+pub struct ActiveState {
+    pub type_id: TypeId,
+
+    /// Type name of the state
+    pub name: &'static str,
+
+    pub operation: StateOperation,
+    pub since: Instant,
+}
+
+/// Progress of a [crate::StateRegistry] which can be observed from other threads.
+#[derive(Debug, Default)]
+pub struct StateHeartbeat {
+    frame: AtomicU64,
+
+    /// States currently being created or updated.
+    /// The last entry is the innermost state.
+    active_states: Mutex<Vec<ActiveState>>,
+}
+
+impl StateHeartbeat {
+    /// Amount of frames (calls to `invalidate_states`) started
+    pub fn frame(&self) -> u64 {
+        self.frame.load(Ordering::Relaxed)
+    }
+
+    pub fn active_states(&self) -> Vec<ActiveState> {
+        self.lock_active_states().clone()
+    }
+
+    /// The innermost state currently being created or updated
+    pub(crate) fn current_state(&self) -> Option<TypeId> {
+        self.lock_active_states().last().map(|state| state.type_id)
+    }
+
+    pub(crate) fn begin_frame(&self) {
+        self.frame.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn enter<T: State>(&self, operation: StateOperation) -> ActiveStateGuard<'_> {
+        self.lock_active_states().push(ActiveState {
+            type_id: TypeId::of::<T>(),
+            name: any::type_name::<T>(),
+            operation,
+            since: Instant::now(),
+        });
+
+        ActiveStateGuard { heartbeat: self }
+    }
+
+    fn lock_active_states(&self) -> MutexGuard<'_, Vec<ActiveState>> {
+        /* a panicking state must not disable the heartbeat */
+        self.active_states
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+/// Removes the state from the active states when dropped (including unwinding)
+pub(crate) struct ActiveStateGuard<'a> {
+    heartbeat: &'a StateHeartbeat,
+}
+
+impl Drop for ActiveStateGuard<'_> {
+    fn drop(&mut self) {
+        self.heartbeat.lock_active_states().pop();
+    }
+}
+
+/// Diagnostic emitted by the [StateWatchdog] if no frame completed within the deadline
+#[derive(Debug, Clone)]
+pub struct WatchdogReport {
+    /// The frame which did not complete
+    pub frame: u64,
+    pub stalled_for: Duration,
+
+    /// States which were being created or updated at the time of the report.
+    /// The last entry is most likely the offending state.
+    pub active_states: Vec<ActiveState>,
+}
+
+/// Observes the heartbeat of a state registry and reports frames which do not complete in time.
+/// The watchdog only reports the stall and does not interrupt the registry.
+pub struct StateWatchdog {
+    shutdown: ShutdownToken,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl StateWatchdog {
+    /// Spawn the watchdog thread.
+    /// The callback will be invoked once per stalled frame.
+    pub fn spawn(
+        heartbeat: Arc<StateHeartbeat>,
+        deadline: Duration,
+        callback: impl Fn(&WatchdogReport) + Send + 'static,
+    ) -> std::io::Result<Self> {
+        Self::spawn_with_shutdown(heartbeat, deadline, &ShutdownToken::new(), callback)
+    }
+
+    /// Spawn the watchdog thread which also stops as soon as the shutdown of the token has been requested
+    pub fn spawn_with_shutdown(
+        heartbeat: Arc<StateHeartbeat>,
+        deadline: Duration,
+        shutdown: &ShutdownToken,
+        callback: impl Fn(&WatchdogReport) + Send + 'static,
+    ) -> std::io::Result<Self> {
+        let shutdown = shutdown.child();
+        let poll_interval =
+            (deadline / 4).clamp(Duration::from_millis(1), Duration::from_millis(250));
+
+        let thread = shutdown.spawn_thread("state-watchdog", {
+            let shutdown = shutdown.clone();
+            move || {
+                let mut frame = heartbeat.frame();
+                let mut frame_start = Instant::now();
+                let mut reported = false;
+
+                while !shutdown.sleep(poll_interval) {
+                    let current_frame = heartbeat.frame();
+                    if current_frame != frame {
+                        frame = current_frame;
+                        frame_start = Instant::now();
+                        reported = false;
+                        continue;
+                    }
+
+                    let stalled_for = frame_start.elapsed();
+                    if reported || stalled_for < deadline {
+                        continue;
+                    }
+
+                    callback(&WatchdogReport {
+                        frame,
+                        stalled_for,
+                        active_states: heartbeat.active_states(),
+                    });
+                    reported = true;
+                }
+            }
+        })?;
+
+        Ok(Self {
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for StateWatchdog {
+    fn drop(&mut self) {
+        self.shutdown.request();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::mpsc,
+        thread,
+        time::Duration,
+    };
+
+    use super::{
+        StateOperation,
+        StateWatchdog,
+    };
+    use crate::{
+        State,
+        StateCacheType,
+        StateRegistry,
+    };
+
+    struct SlowState;
+    impl State for SlowState {
+        type Parameter = ();
+
+        fn create(_states: &StateRegistry, _params: Self::Parameter) -> anyhow::Result<Self> {
+            thread::sleep(Duration::from_millis(300));
+            Ok(Self)
+        }
+
+        fn cache_type() -> StateCacheType {
+            StateCacheType::Volatile
+        }
+    }
+
+    struct OuterState;
+    impl State for OuterState {
+        type Parameter = ();
+
+        fn create(states: &StateRegistry, _params: Self::Parameter) -> anyhow::Result<Self> {
+            states.resolve::<SlowState>(())?;
+            Ok(Self)
+        }
+
+        fn cache_type() -> StateCacheType {
+            StateCacheType::Volatile
+        }
+    }
+
+    #[test]
+    fn slow_state() {
+        let mut states = StateRegistry::new(4);
+        let (sender, receiver) = mpsc::channel();
+        let _watchdog = StateWatchdog::spawn(
+            states.heartbeat(),
+            Duration::from_millis(50),
+            move |report| {
+                let _ = sender.send(report.clone());
+            },
+        )
+        .unwrap();
+
+        states.invalidate_states();
+        assert!(states.resolve::<OuterState>(()).is_ok());
+
+        let report = receiver
+            .recv_timeout(Duration::from_secs(1))
+            .expect("a watchdog report");
+        assert!(report.stalled_for >= Duration::from_millis(50));
+
+        let offending = report.active_states.last().expect("an active state");
+        assert!(offending.name.ends_with("SlowState"));
+        assert_eq!(offending.operation, StateOperation::Create);
+        assert_eq!(report.active_states.len(), 2);
+
+        /* only one report per stalled frame */
+        assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+        assert!(states.heartbeat().active_states().is_empty());
+    }
+}